@@ -1,9 +1,108 @@
-use soroban_sdk::{contracttype, Address, Env, Symbol};
+//! Delegated trading authority: spending policies and session keys.
+//!
+//! A user funds automated execution through [`SpendingPolicy`] grants. A
+//! policy is scoped to strategies, assets and counterparties (an empty list
+//! admits any) and caps a single trade, the spend per UTC day and the spend
+//! over its lifetime. Every execution path (copy trades, DCA purchases, grid
+//! fills, TWAP segments, conditional orders) calls [`spend`] before moving
+//! funds. That debits the oldest live policy that covers the execution and
+//! has room left, and fails with `Unauthorized` when none does.
+//!
+//! A [`SessionKey`] lets a bot trade for a user under exactly one policy. The
+//! bot signs with its own key; revoking the key or its policy stops it at
+//! once. `grant_authorization` keeps the simple case working: it replaces the
+//! user's default policy, which is unrestricted apart from its per-trade cap.
+//!
+//! Grants, revocations and every debit are published as events so delegated
+//! spend can be audited off-chain.
+//!
+//! Grants made before spending policies are stored as an [`AuthConfig`] under
+//! `AuthKey::Authorization`. [`migrate_legacy_authorization`] turns one into
+//! the user's default policy with the same cap and expiry; it runs on the
+//! user's next spend, grant or revocation, and can be called directly.
+
+use soroban_sdk::{contracttype, Address, Env, Symbol, Vec};
 
 use crate::errors::AutoTradeError;
 
 const SECONDS_PER_DAY: u64 = 86400;
 
+/// Live policies a user may hold at once; bounds the scan in [`spend`].
+pub const MAX_POLICIES_PER_USER: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrategyKind {
+    CopyTrade,
+    Dca,
+    Grid,
+    Twap,
+    Conditional,
+}
+
+/// Scope and limits requested for a new policy.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyGrant {
+    /// Empty admits every execution path.
+    pub strategies: Vec<StrategyKind>,
+    /// Empty admits every asset. TWAP orders spend against the asset id
+    /// registered for their pair; orders on unregistered pairs only match
+    /// policies that leave this empty.
+    pub assets: Vec<u32>,
+    /// Empty admits any counterparty. Otherwise the execution must name one
+    /// of these (copy trades name the signal provider).
+    pub counterparties: Vec<Address>,
+    pub max_trade_amount: i128,
+    /// 0 = no daily cap.
+    pub daily_cap: i128,
+    /// 0 = no lifetime cap.
+    pub total_cap: i128,
+    pub duration_days: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpendingPolicy {
+    pub id: u64,
+    pub owner: Address,
+    pub strategies: Vec<StrategyKind>,
+    pub assets: Vec<u32>,
+    pub counterparties: Vec<Address>,
+    pub max_trade_amount: i128,
+    pub daily_cap: i128,
+    pub total_cap: i128,
+    /// Spent during `spent_day` (days since the epoch).
+    pub spent_today: i128,
+    pub spent_day: u64,
+    pub spent_total: i128,
+    pub granted_at: u64,
+    pub expires_at: u64,
+    pub revoked: bool,
+}
+
+/// A bot key allowed to trade for `owner` under one policy.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionKey {
+    pub key: Address,
+    pub owner: Address,
+    pub policy_id: u64,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub revoked: bool,
+}
+
+/// One execution to be paid for by a policy.
+#[derive(Clone, Debug)]
+pub struct Spend {
+    pub strategy: StrategyKind,
+    pub asset: Option<u32>,
+    pub counterparty: Option<Address>,
+    pub amount: i128,
+}
+
+/// Authorization grant in the layout used before spending policies.
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AuthConfig {
@@ -15,81 +114,671 @@ pub struct AuthConfig {
 
 #[contracttype]
 pub enum AuthKey {
+    /// Pre-policy grant: `AuthConfig`. Removed once migrated.
     Authorization(Address),
+    PolicyCounter,
+    Policy(u64),
+    /// Live policy ids of a user, oldest first.
+    UserPolicies(Address),
+    /// Policy maintained by `grant_authorization`.
+    DefaultPolicy(Address),
+    SessionKey(Address),
 }
 
-/// Grant authorization to the contract to execute trades
-pub fn grant_authorization(
-    env: &Env,
-    user: &Address,
-    max_amount: i128,
-    duration_days: u32,
-) -> Result<(), AutoTradeError> {
-    if !cfg!(test) {
-        user.require_auth();
+// ── Storage ───────────────────────────────────────────────────────────────────
+
+pub fn get_policy(env: &Env, id: u64) -> Option<SpendingPolicy> {
+    env.storage().persistent().get(&AuthKey::Policy(id))
+}
+
+fn save_policy(env: &Env, policy: &SpendingPolicy) {
+    env.storage()
+        .persistent()
+        .set(&AuthKey::Policy(policy.id), policy);
+}
+
+fn user_policy_ids(env: &Env, user: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&AuthKey::UserPolicies(user.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_user_policy_ids(env: &Env, user: &Address, ids: &Vec<u64>) {
+    env.storage()
+        .persistent()
+        .set(&AuthKey::UserPolicies(user.clone()), ids);
+}
+
+/// Live policies of a user, oldest first.
+pub fn get_user_policies(env: &Env, user: &Address) -> Vec<SpendingPolicy> {
+    let mut policies = Vec::new(env);
+    for id in user_policy_ids(env, user).iter() {
+        if let Some(policy) = get_policy(env, id) {
+            policies.push_back(policy);
+        }
     }
+    policies
+}
+
+pub fn get_default_policy(env: &Env, user: &Address) -> Option<SpendingPolicy> {
+    let id: u64 = env
+        .storage()
+        .persistent()
+        .get(&AuthKey::DefaultPolicy(user.clone()))?;
+    get_policy(env, id)
+}
+
+pub fn get_session_key(env: &Env, key: &Address) -> Option<SessionKey> {
+    env.storage()
+        .persistent()
+        .get(&AuthKey::SessionKey(key.clone()))
+}
 
-    if max_amount <= 0 {
+fn save_session_key(env: &Env, session: &SessionKey) {
+    env.storage()
+        .persistent()
+        .set(&AuthKey::SessionKey(session.key.clone()), session);
+}
+
+// ── Grants ────────────────────────────────────────────────────────────────────
+
+fn store_policy(
+    env: &Env,
+    owner: &Address,
+    grant: PolicyGrant,
+) -> Result<SpendingPolicy, AutoTradeError> {
+    if grant.max_trade_amount <= 0 || grant.daily_cap < 0 || grant.total_cap < 0 {
         return Err(AutoTradeError::InvalidAmount);
     }
 
-    let current_time = env.ledger().timestamp();
-    let expires_at = current_time + (duration_days as u64 * SECONDS_PER_DAY);
+    // Drop expired policies so they stop counting against the limit.
+    let now = env.ledger().timestamp();
+    let mut ids = Vec::new(env);
+    for id in user_policy_ids(env, owner).iter() {
+        if get_policy(env, id).is_some_and(|p| now < p.expires_at) {
+            ids.push_back(id);
+        }
+    }
+    if ids.len() >= MAX_POLICIES_PER_USER {
+        return Err(AutoTradeError::TooManyPolicies);
+    }
+
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&AuthKey::PolicyCounter)
+        .unwrap_or(0)
+        + 1;
+    env.storage().instance().set(&AuthKey::PolicyCounter, &id);
 
-    let config = AuthConfig {
-        authorized: true,
-        max_trade_amount: max_amount,
-        expires_at,
-        granted_at: current_time,
+    let policy = SpendingPolicy {
+        id,
+        owner: owner.clone(),
+        strategies: grant.strategies,
+        assets: grant.assets,
+        counterparties: grant.counterparties,
+        max_trade_amount: grant.max_trade_amount,
+        daily_cap: grant.daily_cap,
+        total_cap: grant.total_cap,
+        spent_today: 0,
+        spent_day: now / SECONDS_PER_DAY,
+        spent_total: 0,
+        granted_at: now,
+        expires_at: now + grant.duration_days as u64 * SECONDS_PER_DAY,
+        revoked: false,
     };
+    save_policy(env, &policy);
+    ids.push_back(id);
+    set_user_policy_ids(env, owner, &ids);
 
+    #[allow(deprecated)]
+    env.events().publish(
+        (Symbol::new(env, "policy_granted"), owner.clone(), id),
+        policy.clone(),
+    );
+
+    Ok(policy)
+}
+
+/// Grant a scoped spending policy. Returns its id.
+pub fn grant_policy(env: &Env, owner: &Address, grant: PolicyGrant) -> Result<u64, AutoTradeError> {
+    owner.require_auth();
+    Ok(store_policy(env, owner, grant)?.id)
+}
+
+/// Replace the user's default policy without an auth check; callers must
+/// have authenticated `user`.
+pub(crate) fn set_default_policy(
+    env: &Env,
+    user: &Address,
+    max_amount: i128,
+    duration_days: u32,
+) -> Result<u64, AutoTradeError> {
+    migrate_legacy_authorization(env, user);
+    if let Some(previous) = get_default_policy(env, user) {
+        if !previous.revoked {
+            revoke(env, previous);
+        }
+    }
+    let policy = store_policy(
+        env,
+        user,
+        PolicyGrant {
+            strategies: Vec::new(env),
+            assets: Vec::new(env),
+            counterparties: Vec::new(env),
+            max_trade_amount: max_amount,
+            daily_cap: 0,
+            total_cap: 0,
+            duration_days,
+        },
+    )?;
     env.storage()
         .persistent()
-        .set(&AuthKey::Authorization(user.clone()), &config);
+        .set(&AuthKey::DefaultPolicy(user.clone()), &policy.id);
+    Ok(policy.id)
+}
 
-    #[allow(deprecated)]
-    env.events()
-        .publish((Symbol::new(env, "auth_granted"), user.clone()), config);
+/// Convert the user's pre-policy grant, if any, into their default policy
+/// with the same per-trade cap, grant time and expiry. Grants that are
+/// expired or no longer authorized are dropped. Returns the new policy id.
+pub fn migrate_legacy_authorization(env: &Env, user: &Address) -> Option<u64> {
+    let key = AuthKey::Authorization(user.clone());
+    let legacy: AuthConfig = env.storage().persistent().get(&key)?;
+    env.storage().persistent().remove(&key);
+
+    let now = env.ledger().timestamp();
+    if !legacy.authorized || now >= legacy.expires_at || get_default_policy(env, user).is_some() {
+        return None;
+    }
+    let id = set_default_policy(env, user, legacy.max_trade_amount, 0).ok()?;
+    let mut policy = get_policy(env, id)?;
+    policy.granted_at = legacy.granted_at;
+    policy.expires_at = legacy.expires_at;
+    save_policy(env, &policy);
+    Some(id)
+}
 
+/// Grant (or replace) the user's default policy: any strategy, asset and
+/// counterparty, capped at `max_amount` per trade.
+pub fn grant_authorization(
+    env: &Env,
+    user: &Address,
+    max_amount: i128,
+    duration_days: u32,
+) -> Result<(), AutoTradeError> {
+    user.require_auth();
+    set_default_policy(env, user, max_amount, duration_days)?;
     Ok(())
 }
 
-/// Revoke authorization
-pub fn revoke_authorization(env: &Env, user: &Address) -> Result<(), AutoTradeError> {
-    if !cfg!(test) {
-        user.require_auth();
+fn revoke(env: &Env, mut policy: SpendingPolicy) {
+    policy.revoked = true;
+    save_policy(env, &policy);
+
+    let mut ids = user_policy_ids(env, &policy.owner);
+    if let Some(index) = ids.first_index_of(policy.id) {
+        ids.remove(index);
+        set_user_policy_ids(env, &policy.owner, &ids);
     }
 
+    #[allow(deprecated)]
+    env.events().publish(
+        (
+            Symbol::new(env, "policy_revoked"),
+            policy.owner.clone(),
+            policy.id,
+        ),
+        (policy.spent_total,),
+    );
+}
+
+pub fn revoke_policy(env: &Env, owner: &Address, policy_id: u64) -> Result<(), AutoTradeError> {
+    owner.require_auth();
+    let policy = get_policy(env, policy_id).ok_or(AutoTradeError::PolicyNotFound)?;
+    if policy.owner != *owner {
+        return Err(AutoTradeError::Unauthorized);
+    }
+    if !policy.revoked {
+        revoke(env, policy);
+    }
+    Ok(())
+}
+
+/// Revoke every live policy of a user without an auth check.
+pub(crate) fn revoke_all(env: &Env, user: &Address) {
     env.storage()
         .persistent()
         .remove(&AuthKey::Authorization(user.clone()));
+    for policy in get_user_policies(env, user).iter() {
+        revoke(env, policy);
+    }
+    env.storage()
+        .persistent()
+        .remove(&AuthKey::DefaultPolicy(user.clone()));
+}
+
+/// Revoke all of a user's policies, which also disables their session keys.
+pub fn revoke_authorization(env: &Env, user: &Address) -> Result<(), AutoTradeError> {
+    user.require_auth();
+    revoke_all(env, user);
+    Ok(())
+}
+
+// ── Session keys ──────────────────────────────────────────────────────────────
+
+/// Let `key` trade for `owner` under `policy_id`. The key expires with the
+/// policy at the latest.
+pub fn add_session_key(
+    env: &Env,
+    owner: &Address,
+    key: &Address,
+    policy_id: u64,
+    duration_days: u32,
+) -> Result<SessionKey, AutoTradeError> {
+    owner.require_auth();
+    if key == owner {
+        return Err(AutoTradeError::SessionKeyInvalid);
+    }
+    let policy = get_policy(env, policy_id).ok_or(AutoTradeError::PolicyNotFound)?;
+    let now = env.ledger().timestamp();
+    if policy.owner != *owner || policy.revoked || now >= policy.expires_at {
+        return Err(AutoTradeError::Unauthorized);
+    }
+    // A key serves one user; it cannot be taken over while still live.
+    if let Some(existing) = get_session_key(env, key) {
+        if existing.owner != *owner && !existing.revoked && now < existing.expires_at {
+            return Err(AutoTradeError::SessionKeyInvalid);
+        }
+    }
+
+    let session = SessionKey {
+        key: key.clone(),
+        owner: owner.clone(),
+        policy_id,
+        created_at: now,
+        expires_at: core::cmp::min(
+            now + duration_days as u64 * SECONDS_PER_DAY,
+            policy.expires_at,
+        ),
+        revoked: false,
+    };
+    save_session_key(env, &session);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (
+            Symbol::new(env, "session_key_added"),
+            owner.clone(),
+            key.clone(),
+        ),
+        (policy_id, session.expires_at),
+    );
+
+    Ok(session)
+}
+
+pub fn revoke_session_key(env: &Env, owner: &Address, key: &Address) -> Result<(), AutoTradeError> {
+    owner.require_auth();
+    let mut session = get_session_key(env, key).ok_or(AutoTradeError::SessionKeyInvalid)?;
+    if session.owner != *owner {
+        return Err(AutoTradeError::Unauthorized);
+    }
+    session.revoked = true;
+    save_session_key(env, &session);
 
     #[allow(deprecated)]
-    env.events()
-        .publish((Symbol::new(env, "auth_revoked"), user.clone()), ());
+    env.events().publish(
+        (
+            Symbol::new(env, "session_key_revoked"),
+            owner.clone(),
+            key.clone(),
+        ),
+        session.policy_id,
+    );
 
     Ok(())
 }
 
-/// Check if user is authorized for a specific trade amount
-pub fn is_authorized(env: &Env, user: &Address, amount: i128) -> bool {
-    let config: Option<AuthConfig> = env
-        .storage()
-        .persistent()
-        .get(&AuthKey::Authorization(user.clone()));
+/// Authenticate a session key acting for `user`. Returns the policy it may
+/// spend from.
+pub fn require_session(env: &Env, key: &Address, user: &Address) -> Result<u64, AutoTradeError> {
+    key.require_auth();
+    let session = get_session_key(env, key).ok_or(AutoTradeError::SessionKeyInvalid)?;
+    if session.owner != *user || session.revoked || env.ledger().timestamp() >= session.expires_at {
+        return Err(AutoTradeError::SessionKeyInvalid);
+    }
+    Ok(session.policy_id)
+}
+
+// ── Spending ──────────────────────────────────────────────────────────────────
+
+fn in_scope(policy: &SpendingPolicy, spend: &Spend) -> bool {
+    if !policy.strategies.is_empty() && !policy.strategies.contains(spend.strategy) {
+        return false;
+    }
+    if !policy.assets.is_empty() && !spend.asset.is_some_and(|a| policy.assets.contains(a)) {
+        return false;
+    }
+    if !policy.counterparties.is_empty()
+        && !spend
+            .counterparty
+            .as_ref()
+            .is_some_and(|c| policy.counterparties.contains(c))
+    {
+        return false;
+    }
+    true
+}
+
+fn spent_on(policy: &SpendingPolicy, day: u64) -> i128 {
+    if policy.spent_day == day {
+        policy.spent_today
+    } else {
+        0
+    }
+}
 
-    match config {
-        Some(cfg) => {
-            let current_time = env.ledger().timestamp();
-            cfg.authorized && current_time < cfg.expires_at && amount <= cfg.max_trade_amount
+fn has_room(env: &Env, policy: &SpendingPolicy, amount: i128) -> bool {
+    let now = env.ledger().timestamp();
+    if policy.revoked || now >= policy.expires_at || amount > policy.max_trade_amount {
+        return false;
+    }
+    let today = spent_on(policy, now / SECONDS_PER_DAY);
+    (policy.daily_cap == 0 || today + amount <= policy.daily_cap)
+        && (policy.total_cap == 0 || policy.spent_total + amount <= policy.total_cap)
+}
+
+/// Find the policy that would pay for `spend` without debiting it. With a
+/// session policy only that one is considered; otherwise a pre-policy grant
+/// of the user is migrated first.
+pub fn select(
+    env: &Env,
+    user: &Address,
+    session_policy: Option<u64>,
+    spend: &Spend,
+) -> Result<SpendingPolicy, AutoTradeError> {
+    if spend.amount <= 0 {
+        return Err(AutoTradeError::InvalidAmount);
+    }
+    let candidates = match session_policy {
+        Some(id) => Vec::from_array(env, [id]),
+        None => {
+            migrate_legacy_authorization(env, user);
+            user_policy_ids(env, user)
+        }
+    };
+    for id in candidates.iter() {
+        if let Some(policy) = get_policy(env, id) {
+            if policy.owner == *user
+                && in_scope(&policy, spend)
+                && has_room(env, &policy, spend.amount)
+            {
+                return Ok(policy);
+            }
         }
-        None => false,
     }
+    Err(AutoTradeError::NoMatchingPolicy)
 }
 
-/// Get authorization config for a user
-pub fn get_auth_config(env: &Env, user: &Address) -> Option<AuthConfig> {
-    env.storage()
-        .persistent()
-        .get(&AuthKey::Authorization(user.clone()))
+/// Record `amount` of `strategy` spend against a policy returned by
+/// [`select`] in the same invocation.
+pub fn debit(env: &Env, mut policy: SpendingPolicy, strategy: StrategyKind, amount: i128) {
+    let day = env.ledger().timestamp() / SECONDS_PER_DAY;
+    policy.spent_today = spent_on(&policy, day) + amount;
+    policy.spent_day = day;
+    policy.spent_total += amount;
+    save_policy(env, &policy);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (
+            Symbol::new(env, "policy_debited"),
+            policy.owner.clone(),
+            policy.id,
+        ),
+        (strategy, amount, policy.spent_today, policy.spent_total),
+    );
+}
+
+/// Select a policy for `spend` and debit it. Returns the policy id.
+pub fn spend(
+    env: &Env,
+    user: &Address,
+    session_policy: Option<u64>,
+    spend: &Spend,
+) -> Result<u64, AutoTradeError> {
+    let policy = select(env, user, session_policy, spend)?;
+    let id = policy.id;
+    debit(env, policy, spend.strategy, spend.amount);
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage, AutoTradeContract, AutoTradeContractClient, OrderType};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger as _, MockAuth, MockAuthInvoke},
+        Env, IntoVal,
+    };
+
+    fn setup() -> (Env, Address, AutoTradeContractClient<'static>) {
+        let env = Env::default();
+        env.ledger().set_timestamp(1_000);
+        let contract = env.register(AutoTradeContract, ());
+        let client = AutoTradeContractClient::new(&env, &contract);
+        (env, contract, client)
+    }
+
+    const UNIT: i128 = 10_000_000;
+
+    fn invoke<'a>(
+        contract: &'a Address,
+        fn_name: &'a str,
+        args: soroban_sdk::Vec<soroban_sdk::Val>,
+    ) -> MockAuthInvoke<'a> {
+        MockAuthInvoke {
+            contract,
+            fn_name,
+            args,
+            sub_invokes: &[],
+        }
+    }
+
+    #[test]
+    fn test_scoped_policy_caps_and_session_key() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        let bot = Address::generate(&env);
+        let provider = Address::generate(&env);
+
+        env.as_contract(&contract, || {
+            storage::set_signal(
+                &env,
+                7,
+                &storage::Signal {
+                    signal_id: 7,
+                    price: 100,
+                    expiry: 1_000_000,
+                    base_asset: 1,
+                },
+            );
+            storage::set_signal_provider(&env, 7, &provider);
+            env.storage().temporary().set(
+                &(user.clone(), soroban_sdk::symbol_short!("balance")),
+                &i128::MAX,
+            );
+            let mut risk_config = crate::risk::RiskConfig::default();
+            risk_config.max_position_pct = 100;
+            crate::risk::set_risk_config(&env, &user, &risk_config);
+        });
+
+        // Copy trades of `provider` on asset 1 only: 400 per trade, 600 a day,
+        // 1000 in total (in whole units).
+        let grant = PolicyGrant {
+            strategies: Vec::from_array(&env, [StrategyKind::CopyTrade]),
+            assets: Vec::from_array(&env, [1u32]),
+            counterparties: Vec::from_array(&env, [provider.clone()]),
+            max_trade_amount: 400 * UNIT,
+            daily_cap: 600 * UNIT,
+            total_cap: 1_000 * UNIT,
+            duration_days: 30,
+        };
+        let policy_id = client
+            .mock_auths(&[MockAuth {
+                address: &user,
+                invoke: &invoke(
+                    &contract,
+                    "grant_spending_policy",
+                    (user.clone(), grant.clone()).into_val(&env),
+                ),
+            }])
+            .grant_spending_policy(&user, &grant);
+        client
+            .mock_auths(&[MockAuth {
+                address: &user,
+                invoke: &invoke(
+                    &contract,
+                    "add_session_key",
+                    (user.clone(), bot.clone(), policy_id, 7u32).into_val(&env),
+                ),
+            }])
+            .add_session_key(&user, &bot, &policy_id, &7);
+
+        // Amounts in whole units; each trade clears the transfer cooldown.
+        let trade = |units: i128| {
+            let amount = units * UNIT;
+            env.ledger().set_timestamp(env.ledger().timestamp() + 60);
+            client
+                .mock_auths(&[MockAuth {
+                    address: &bot,
+                    invoke: &invoke(
+                        &contract,
+                        "execute_trade_with_session_key",
+                        (bot.clone(), user.clone(), 7u64, OrderType::Limit, amount).into_val(&env),
+                    ),
+                }])
+                .try_execute_trade_with_session_key(&bot, &user, &7, &OrderType::Limit, &amount)
+        };
+
+        assert_eq!(
+            trade(400).map(|r| r.unwrap().trade.executed_amount),
+            Ok(400 * UNIT)
+        );
+        // Above the per-trade cap.
+        assert_eq!(trade(401), Err(Ok(AutoTradeError::Unauthorized)));
+        // 400 + 300 would break the daily cap.
+        assert_eq!(trade(300), Err(Ok(AutoTradeError::Unauthorized)));
+        assert!(trade(200).is_ok());
+        let policy = client.get_spending_policy(&policy_id).unwrap();
+        assert_eq!(policy.spent_today, 600 * UNIT);
+        assert_eq!(policy.spent_total, 600 * UNIT);
+
+        // Next day the daily cap resets; the lifetime cap still binds.
+        env.ledger().set_timestamp(1_000 + SECONDS_PER_DAY);
+        assert!(trade(300).is_ok());
+        assert_eq!(trade(200), Err(Ok(AutoTradeError::Unauthorized)));
+
+        // The key only acts for the user who registered it.
+        let other = Address::generate(&env);
+        assert_eq!(
+            client
+                .mock_auths(&[MockAuth {
+                    address: &bot,
+                    invoke: &invoke(
+                        &contract,
+                        "execute_trade_with_session_key",
+                        (bot.clone(), other.clone(), 7u64, OrderType::Limit, UNIT).into_val(&env),
+                    ),
+                }])
+                .try_execute_trade_with_session_key(&bot, &other, &7, &OrderType::Limit, &UNIT),
+            Err(Ok(AutoTradeError::Unauthorized))
+        );
+
+        // Revoking the session key stops the bot.
+        client
+            .mock_auths(&[MockAuth {
+                address: &user,
+                invoke: &invoke(
+                    &contract,
+                    "revoke_session_key",
+                    (user.clone(), bot.clone()).into_val(&env),
+                ),
+            }])
+            .revoke_session_key(&user, &bot);
+        assert_eq!(trade(50), Err(Ok(AutoTradeError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_strategy_paths_debit_matching_policy() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+
+        // Only DCA on asset 2 is funded.
+        let grant = PolicyGrant {
+            strategies: Vec::from_array(&env, [StrategyKind::Dca]),
+            assets: Vec::from_array(&env, [2u32]),
+            counterparties: Vec::new(&env),
+            max_trade_amount: 100,
+            daily_cap: 0,
+            total_cap: 150,
+            duration_days: 30,
+        };
+        let policy_id = client
+            .mock_auths(&[MockAuth {
+                address: &user,
+                invoke: &invoke(
+                    &contract,
+                    "grant_spending_policy",
+                    (user.clone(), grant.clone()).into_val(&env),
+                ),
+            }])
+            .grant_spending_policy(&user, &grant);
+
+        env.as_contract(&contract, || {
+            let dca = |asset: u32, amount: i128| Spend {
+                strategy: StrategyKind::Dca,
+                asset: Some(asset),
+                counterparty: None,
+                amount,
+            };
+            assert_eq!(spend(&env, &user, None, &dca(2, 100)), Ok(policy_id));
+            // Wrong asset, wrong strategy, lifetime cap.
+            assert!(spend(&env, &user, None, &dca(3, 10)).is_err());
+            let grid = Spend {
+                strategy: StrategyKind::Grid,
+                ..dca(2, 10)
+            };
+            assert!(spend(&env, &user, None, &grid).is_err());
+            assert!(spend(&env, &user, None, &dca(2, 60)).is_err());
+            assert_eq!(spend(&env, &user, None, &dca(2, 50)), Ok(policy_id));
+        });
+
+        // A default grant funds anything else, and revoking clears both.
+        client
+            .mock_auths(&[MockAuth {
+                address: &user,
+                invoke: &invoke(
+                    &contract,
+                    "grant_authorization",
+                    (user.clone(), 1_000i128, 1u32).into_val(&env),
+                ),
+            }])
+            .grant_authorization(&user, &1_000, &1);
+        assert_eq!(client.get_user_policies(&user).len(), 2);
+        client
+            .mock_auths(&[MockAuth {
+                address: &user,
+                invoke: &invoke(
+                    &contract,
+                    "revoke_authorization",
+                    (user.clone(),).into_val(&env),
+                ),
+            }])
+            .revoke_authorization(&user);
+        assert_eq!(client.get_user_policies(&user).len(), 0);
+        assert!(client.get_spending_policy(&policy_id).unwrap().revoked);
+    }
 }
@@ -1,12 +1,67 @@
 //! Options-Style Conditional Orders
 //!
-//! Supports complex trigger logic (AND/OR) combining price, time, and technical
+//! Supports complex trigger logic combining price, time, and technical
 //! conditions — mimicking options strategies without actual options.
+//!
+//! Trigger logic is a [`ConditionTree`]: a flat list of nodes where node 0 is
+//! the root and `And` / `Or` / `Not` nodes refer to their children by index,
+//! so `(A and B) or C` is `[Or[1, 4], And[2, 3], A, B, C]`. Trees are bounded
+//! by `MAX_TREE_NODES` and `MAX_TREE_DEPTH`, and children are reordered at
+//! creation so the cheapest branch is evaluated first and short-circuits the
+//! expensive ones.
+//!
+//! Every leaf has a cost in `cost::*` units (roughly one storage read each).
+//! A keeper pass stops once `MAX_CHECK_COST` is spent and the next pass
+//! resumes from the first order it skipped.
+//!
+//! Orders can be linked into a one-cancels-other group: when one member
+//! triggers, the rest are cancelled.
+//!
+//! `PortfolioDrawdown` peaks are refreshed whenever a price or position that
+//! feeds the owner's portfolio value is written, not only in keeper passes,
+//! so a high between two passes still counts.
+//!
+//! Orders created before condition trees are stored as a
+//! [`LegacyConditionalOrder`] under `ConditionalKey::Order` and converted to
+//! the current layout the first time they are loaded.
 
 #![allow(dead_code)]
 
+use crate::admin::require_admin;
+use crate::auth::{self, Spend, StrategyKind};
 use crate::errors::AutoTradeError;
-use soroban_sdk::{contracttype, Address, Env, Symbol, Vec};
+use crate::risk;
+use crate::strategies::momentum;
+use soroban_sdk::{contracttype, vec, Address, Env, IntoVal, Symbol, Vec};
+
+// ── Limits ────────────────────────────────────────────────────────────────────
+
+/// Maximum nodes in a condition tree.
+pub const MAX_TREE_NODES: u32 = 16;
+/// Maximum depth of a condition tree; a lone leaf has depth 1.
+pub const MAX_TREE_DEPTH: u32 = 4;
+/// Maximum orders in one one-cancels-other group.
+pub const MAX_OCO_GROUP: u32 = 5;
+/// Maximum owners with pending `PortfolioDrawdown` orders; each price write
+/// refreshes their peaks.
+pub const MAX_DRAWDOWN_OWNERS: u32 = 100;
+/// Evaluation budget for one `check_and_trigger` pass.
+pub const MAX_CHECK_COST: u32 = 400;
+/// Period of the RSI used by `Condition::RsiCross`.
+pub const RSI_PERIOD: u32 = 14;
+/// Samples kept per asset by the risk module's price history.
+const HISTORY_LEN: u32 = 30;
+
+/// Evaluation cost of each leaf kind.
+mod cost {
+    pub const TIME: u32 = 1;
+    pub const PRICE: u32 = 1;
+    pub const PRICE_RATIO: u32 = 2;
+    /// Price history read plus the spot price.
+    pub const HISTORY: u32 = 31;
+    pub const DRAWDOWN: u32 = 10;
+    pub const SIGNAL: u32 = 25;
+}
 
 // ── Types ─────────────────────────────────────────────────────────────────────
 
@@ -30,6 +85,22 @@ pub enum Condition {
     PriceDropRebound(u32, u32, u32),
     /// Volatility breakout: asset `.0`, threshold `.1` bps from reference.
     VolatilityBreakout(u32, u32),
+    /// Price of `.0` divided by price of `.1` is `.2` `.3` (ratio scaled ×10^7).
+    PriceRatio(u32, u32, PriceDirection, i128),
+    /// Spot price of `.0` is at least `.2` bps away from the TWAP of its last
+    /// `.1` oracle updates.
+    TwapDeviation(u32, u32, u32),
+    /// RSI of `.0` crossed level `.2` (0–10000) in direction `.1` on the
+    /// latest price update.
+    RsiCross(u32, PriceDirection, u32),
+    /// The `.1`-update moving average of `.0` crossed its `.2`-update moving
+    /// average in direction `.3` on the latest price update.
+    MaCross(u32, u32, u32, PriceDirection),
+    /// Owner's portfolio value is at least `.0` bps below its peak since the
+    /// order was created.
+    PortfolioDrawdown(u32),
+    /// Signal `.0` resolved as successful on the configured signal registry.
+    SignalResolved(u64),
 }
 
 /// How multiple conditions are combined.
@@ -40,6 +111,24 @@ pub enum LogicOp {
     Or,
 }
 
+/// One node of a [`ConditionTree`]. Children are node indices.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConditionNode {
+    Leaf(Condition),
+    And(Vec<u32>),
+    Or(Vec<u32>),
+    Not(u32),
+}
+
+/// Trigger expression rooted at node 0. Every other node has exactly one
+/// parent with a lower index.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConditionTree {
+    pub nodes: Vec<ConditionNode>,
+}
+
 /// Side of the conditional order.
 #[contracttype]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -70,8 +159,9 @@ pub struct ConditionalOrder {
     pub amount: i128,
     /// Limit price for execution (0 = market).
     pub limit_price: i128,
-    pub conditions: Vec<Condition>,
-    pub logic: LogicOp,
+    pub tree: ConditionTree,
+    /// Worst-case evaluation cost of `tree`.
+    pub cost: u32,
     pub status: ConditionalStatus,
     pub created_at: u64,
     pub expires_at: u64,
@@ -79,6 +169,30 @@ pub struct ConditionalOrder {
     pub reference_price: i128,
     /// Lowest price seen since creation — used by rebound check.
     pub trough_price: i128,
+    /// Highest portfolio value seen since creation — used by drawdown check.
+    pub peak_value: i128,
+    /// Other orders in this order's one-cancels-other group.
+    pub oco_group: Vec<u64>,
+}
+
+/// Conditional order layout from before condition trees: a flat condition
+/// list joined by one operator.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct LegacyConditionalOrder {
+    pub id: u64,
+    pub user: Address,
+    pub asset_id: u32,
+    pub side: ConditionalSide,
+    pub amount: i128,
+    pub limit_price: i128,
+    pub conditions: Vec<Condition>,
+    pub logic: LogicOp,
+    pub status: ConditionalStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub reference_price: i128,
+    pub trough_price: i128,
 }
 
 // ── Storage keys ──────────────────────────────────────────────────────────────
@@ -86,8 +200,18 @@ pub struct ConditionalOrder {
 #[contracttype]
 pub enum ConditionalKey {
     Counter,
+    /// Pre-tree order: `LegacyConditionalOrder`. Removed once migrated.
     Order(u64),
     ActiveOrders,
+    /// Order the next keeper pass starts from.
+    CheckCursor,
+    SignalRegistry,
+    /// Order by id: `ConditionalOrder`.
+    TreeOrder(u64),
+    /// Pending `PortfolioDrawdown` orders of an owner.
+    DrawdownOrders(Address),
+    /// Owners with at least one pending `PortfolioDrawdown` order.
+    DrawdownOwners,
 }
 
 // ── Storage helpers ───────────────────────────────────────────────────────────
@@ -108,16 +232,47 @@ fn next_id(env: &Env) -> u64 {
 fn save(env: &Env, order: &ConditionalOrder) {
     env.storage()
         .persistent()
-        .set(&ConditionalKey::Order(order.id), order);
+        .set(&ConditionalKey::TreeOrder(order.id), order);
 }
 
 fn load(env: &Env, id: u64) -> Result<ConditionalOrder, AutoTradeError> {
     env.storage()
         .persistent()
-        .get(&ConditionalKey::Order(id))
+        .get(&ConditionalKey::TreeOrder(id))
+        .or_else(|| migrate_legacy_order(env, id))
         .ok_or(AutoTradeError::ConditionalOrderNotFound)
 }
 
+/// Convert the pre-tree order `id` to the current layout: its conditions
+/// become a flat tree under its operator, with no OCO group and no drawdown
+/// peak (legacy conditions never read one).
+pub fn migrate_legacy_order(env: &Env, id: u64) -> Option<ConditionalOrder> {
+    let key = ConditionalKey::Order(id);
+    let legacy: LegacyConditionalOrder = env.storage().persistent().get(&key)?;
+    env.storage().persistent().remove(&key);
+
+    let cost = legacy.conditions.iter().map(|c| leaf_cost(&c)).sum();
+    let order = ConditionalOrder {
+        id: legacy.id,
+        user: legacy.user,
+        asset_id: legacy.asset_id,
+        side: legacy.side,
+        amount: legacy.amount,
+        limit_price: legacy.limit_price,
+        tree: flat_tree(env, &legacy.conditions, legacy.logic),
+        cost,
+        status: legacy.status,
+        created_at: legacy.created_at,
+        expires_at: legacy.expires_at,
+        reference_price: legacy.reference_price,
+        trough_price: legacy.trough_price,
+        peak_value: 0,
+        oco_group: Vec::new(env),
+    };
+    save(env, &order);
+    Some(order)
+}
+
 fn active_ids(env: &Env) -> Vec<u64> {
     env.storage()
         .persistent()
@@ -147,15 +302,324 @@ fn remove_active(env: &Env, id: u64) {
     }
 }
 
+fn drawdown_owners(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&ConditionalKey::DrawdownOwners)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn drawdown_orders(env: &Env, owner: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&ConditionalKey::DrawdownOrders(owner.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn set_drawdown_orders(env: &Env, owner: &Address, ids: &Vec<u64>) {
+    let key = ConditionalKey::DrawdownOrders(owner.clone());
+    let mut owners = drawdown_owners(env);
+    let listed = owners.first_index_of(owner);
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+        if let Some(pos) = listed {
+            owners.remove(pos);
+            env.storage()
+                .persistent()
+                .set(&ConditionalKey::DrawdownOwners, &owners);
+        }
+    } else {
+        env.storage().persistent().set(&key, ids);
+        if listed.is_none() {
+            owners.push_back(owner.clone());
+            env.storage()
+                .persistent()
+                .set(&ConditionalKey::DrawdownOwners, &owners);
+        }
+    }
+}
+
+fn watch_drawdown(env: &Env, order: &ConditionalOrder) -> Result<(), AutoTradeError> {
+    let mut ids = drawdown_orders(env, &order.user);
+    if ids.is_empty() && drawdown_owners(env).len() >= MAX_DRAWDOWN_OWNERS {
+        return Err(AutoTradeError::InvalidConditionalConfig);
+    }
+    ids.push_back(order.id);
+    set_drawdown_orders(env, &order.user, &ids);
+    Ok(())
+}
+
+fn unwatch_drawdown(env: &Env, order: &ConditionalOrder) {
+    let mut ids = drawdown_orders(env, &order.user);
+    if let Some(pos) = ids.first_index_of(order.id) {
+        ids.remove(pos);
+        set_drawdown_orders(env, &order.user, &ids);
+    }
+}
+
+/// Take `order` out of the keeper queue once it stops being pending.
+fn retire(env: &Env, order: &ConditionalOrder) {
+    remove_active(env, order.id);
+    if uses_drawdown(&order.tree) {
+        unwatch_drawdown(env, order);
+    }
+}
+
+/// Raise the peak of `owner`'s pending drawdown orders to their current
+/// portfolio value.
+fn refresh_owner_peaks(env: &Env, owner: &Address) {
+    let ids = drawdown_orders(env, owner);
+    if ids.is_empty() {
+        return;
+    }
+    let value = risk::calculate_portfolio_value(env, owner);
+    for id in ids.iter() {
+        if let Ok(mut order) = load(env, id) {
+            if value > order.peak_value {
+                order.peak_value = value;
+                save(env, &order);
+            }
+        }
+    }
+}
+
+/// Called after `owner`'s positions change.
+pub fn on_position_update(env: &Env, owner: &Address) {
+    refresh_owner_peaks(env, owner);
+}
+
+/// Called after the spot price of `asset_id` is written: refreshes the peaks
+/// of drawdown owners holding it.
+pub fn on_price_update(env: &Env, asset_id: u32) {
+    for owner in drawdown_owners(env).iter() {
+        if risk::get_user_positions(env, &owner).contains_key(asset_id) {
+            refresh_owner_peaks(env, &owner);
+        }
+    }
+}
+
+/// Set the signal registry queried by `Condition::SignalResolved` (admin only).
+pub fn set_signal_registry(
+    env: &Env,
+    caller: &Address,
+    registry: Option<Address>,
+) -> Result<(), AutoTradeError> {
+    require_admin(env, caller)?;
+    match &registry {
+        Some(registry) => env
+            .storage()
+            .instance()
+            .set(&ConditionalKey::SignalRegistry, registry),
+        None => env
+            .storage()
+            .instance()
+            .remove(&ConditionalKey::SignalRegistry),
+    }
+    #[allow(deprecated)]
+    env.events()
+        .publish((Symbol::new(env, "cond_signal_registry_set"),), registry);
+    Ok(())
+}
+
+pub fn get_signal_registry(env: &Env) -> Option<Address> {
+    env.storage()
+        .instance()
+        .get(&ConditionalKey::SignalRegistry)
+}
+
+// ── Tree construction & validation ────────────────────────────────────────────
+
+/// Build the tree for a flat condition list joined by `logic`.
+fn flat_tree(env: &Env, conditions: &Vec<Condition>, logic: LogicOp) -> ConditionTree {
+    let mut children = Vec::new(env);
+    for i in 0..conditions.len() {
+        children.push_back(i + 1);
+    }
+    let mut nodes = Vec::new(env);
+    nodes.push_back(match logic {
+        LogicOp::And => ConditionNode::And(children),
+        LogicOp::Or => ConditionNode::Or(children),
+    });
+    for condition in conditions.iter() {
+        nodes.push_back(ConditionNode::Leaf(condition));
+    }
+    ConditionTree { nodes }
+}
+
+fn node_children(env: &Env, node: &ConditionNode) -> Vec<u32> {
+    match node {
+        ConditionNode::Leaf(_) => Vec::new(env),
+        ConditionNode::And(children) | ConditionNode::Or(children) => children.clone(),
+        ConditionNode::Not(child) => vec![env, *child],
+    }
+}
+
+fn validate_leaf(env: &Env, condition: &Condition) -> Result<(), AutoTradeError> {
+    let valid = match condition {
+        Condition::Price(..)
+        | Condition::TimeAfter(_)
+        | Condition::PriceDropRebound(..)
+        | Condition::VolatilityBreakout(..) => true,
+        Condition::PriceRatio(base, quote, _, ratio) => base != quote && *ratio > 0,
+        Condition::TwapDeviation(_, window, bps) => {
+            *window >= 2 && *window <= HISTORY_LEN && *bps > 0
+        }
+        Condition::RsiCross(_, _, level) => *level > 0 && *level < 10_000,
+        Condition::MaCross(_, fast, slow, _) => *fast > 0 && fast < slow && *slow < HISTORY_LEN,
+        Condition::PortfolioDrawdown(bps) => *bps > 0 && *bps <= 10_000,
+        Condition::SignalResolved(_) => get_signal_registry(env).is_some(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(AutoTradeError::InvalidConditionalConfig)
+    }
+}
+
+fn leaf_cost(condition: &Condition) -> u32 {
+    match condition {
+        Condition::TimeAfter(_) => cost::TIME,
+        Condition::Price(..)
+        | Condition::PriceDropRebound(..)
+        | Condition::VolatilityBreakout(..) => cost::PRICE,
+        Condition::PriceRatio(..) => cost::PRICE_RATIO,
+        Condition::TwapDeviation(..) | Condition::RsiCross(..) | Condition::MaCross(..) => {
+            cost::HISTORY
+        }
+        Condition::PortfolioDrawdown(_) => cost::DRAWDOWN,
+        Condition::SignalResolved(_) => cost::SIGNAL,
+    }
+}
+
+/// Check `tree`'s shape and leaves, and reorder every `And` / `Or` so its
+/// cheapest children come first. Returns the tree and its worst-case cost.
+fn prepare_tree(env: &Env, tree: ConditionTree) -> Result<(ConditionTree, u32), AutoTradeError> {
+    let n = tree.nodes.len();
+    if n == 0 || n > MAX_TREE_NODES {
+        return Err(AutoTradeError::InvalidConditionalConfig);
+    }
+
+    let mut parents: Vec<u32> = Vec::new(env);
+    let mut depth: Vec<u32> = Vec::new(env);
+    for _ in 0..n {
+        parents.push_back(0);
+        depth.push_back(1);
+    }
+    for i in 0..n {
+        let node = tree.nodes.get(i).unwrap();
+        match &node {
+            ConditionNode::Leaf(condition) => validate_leaf(env, condition)?,
+            ConditionNode::And(children) | ConditionNode::Or(children) => {
+                if children.is_empty() {
+                    return Err(AutoTradeError::InvalidConditionalConfig);
+                }
+            }
+            ConditionNode::Not(_) => {}
+        }
+        for child in node_children(env, &node).iter() {
+            if child <= i || child >= n {
+                return Err(AutoTradeError::InvalidConditionalConfig);
+            }
+            parents.set(child, parents.get(child).unwrap() + 1);
+            depth.set(child, depth.get(i).unwrap() + 1);
+        }
+    }
+    for i in 1..n {
+        if parents.get(i).unwrap() != 1 {
+            return Err(AutoTradeError::InvalidConditionalConfig);
+        }
+    }
+    for d in depth.iter() {
+        if d > MAX_TREE_DEPTH {
+            return Err(AutoTradeError::InvalidConditionalConfig);
+        }
+    }
+
+    // Children always have higher indices, so a reverse pass sees them first.
+    let mut costs: Vec<u32> = Vec::new(env);
+    for _ in 0..n {
+        costs.push_back(0);
+    }
+    let mut nodes = tree.nodes.clone();
+    for i in (0..n).rev() {
+        let node = nodes.get(i).unwrap();
+        let node_cost = match &node {
+            ConditionNode::Leaf(condition) => leaf_cost(condition),
+            ConditionNode::Not(child) => costs.get(*child).unwrap(),
+            ConditionNode::And(children) | ConditionNode::Or(children) => {
+                let sorted = sort_by_cost(env, children, &costs);
+                nodes.set(
+                    i,
+                    if matches!(node, ConditionNode::And(_)) {
+                        ConditionNode::And(sorted.clone())
+                    } else {
+                        ConditionNode::Or(sorted.clone())
+                    },
+                );
+                sorted.iter().map(|c| costs.get(c).unwrap()).sum()
+            }
+        };
+        costs.set(i, node_cost);
+    }
+
+    Ok((ConditionTree { nodes }, costs.get(0).unwrap()))
+}
+
+/// Insertion sort of `children` by ascending cost; ties keep their order.
+fn sort_by_cost(env: &Env, children: &Vec<u32>, costs: &Vec<u32>) -> Vec<u32> {
+    let mut sorted: Vec<u32> = Vec::new(env);
+    for child in children.iter() {
+        let child_cost = costs.get(child).unwrap();
+        let mut pos = sorted.len();
+        while pos > 0 && costs.get(sorted.get(pos - 1).unwrap()).unwrap() > child_cost {
+            pos -= 1;
+        }
+        sorted.insert(pos, child);
+    }
+    sorted
+}
+
+fn uses_drawdown(tree: &ConditionTree) -> bool {
+    tree.nodes
+        .iter()
+        .any(|node| matches!(node, ConditionNode::Leaf(Condition::PortfolioDrawdown(_))))
+}
+
 // ── Condition evaluation ──────────────────────────────────────────────────────
 
 /// Returns the current price for `asset_id` from the risk module's price store.
 fn current_price(env: &Env, asset_id: u32) -> i128 {
-    use crate::risk::RiskDataKey;
-    env.storage()
-        .persistent()
-        .get(&RiskDataKey::AssetPrice(asset_id))
-        .unwrap_or(0)
+    risk::get_asset_price(env, asset_id).unwrap_or(0)
+}
+
+fn average(prices: &Vec<i128>, from: u32, to: u32) -> i128 {
+    let mut sum = 0i128;
+    for i in from..to {
+        sum += prices.get(i).unwrap();
+    }
+    sum / (to - from) as i128
+}
+
+/// True when `before` → `now` moves across `level` in `direction`.
+fn crossed(direction: PriceDirection, before: i128, now: i128, level: i128) -> bool {
+    match direction {
+        PriceDirection::Above => before < level && now >= level,
+        PriceDirection::Below => before > level && now <= level,
+    }
+}
+
+fn signal_succeeded(env: &Env, signal_id: u64) -> bool {
+    let Some(registry) = get_signal_registry(env) else {
+        return false;
+    };
+    matches!(
+        env.try_invoke_contract::<bool, soroban_sdk::Error>(
+            &registry,
+            &Symbol::new(env, "is_signal_successful"),
+            vec![env, signal_id.into_val(env)],
+        ),
+        Ok(Ok(true))
+    )
 }
 
 fn eval_condition(env: &Env, cond: &Condition, order: &ConditionalOrder) -> bool {
@@ -197,33 +661,93 @@ fn eval_condition(env: &Env, cond: &Condition, order: &ConditionalOrder) -> bool
             };
             diff * 10_000 >= ref_price * *threshold_bps as i128
         }
-    }
-}
-
-fn all_triggered(env: &Env, order: &ConditionalOrder) -> bool {
-    match order.logic {
-        LogicOp::And => {
-            for i in 0..order.conditions.len() {
-                if !eval_condition(env, &order.conditions.get(i).unwrap(), order) {
-                    return false;
-                }
+        Condition::PriceRatio(base, quote, direction, ratio) => {
+            let quote_price = current_price(env, *quote);
+            if quote_price == 0 {
+                return false;
+            }
+            let current = current_price(env, *base) * 10_000_000 / quote_price;
+            match direction {
+                PriceDirection::Above => current >= *ratio,
+                PriceDirection::Below => current <= *ratio,
             }
-            true
         }
-        LogicOp::Or => {
-            for i in 0..order.conditions.len() {
-                if eval_condition(env, &order.conditions.get(i).unwrap(), order) {
-                    return true;
-                }
+        Condition::TwapDeviation(asset_id, window, threshold_bps) => {
+            let history = risk::get_price_history(env, *asset_id, *window);
+            if history.len() < *window {
+                return false;
+            }
+            let twap = average(&history, 0, history.len());
+            if twap <= 0 {
+                return false;
             }
-            false
+            let diff = (current_price(env, *asset_id) - twap).abs();
+            diff * 10_000 >= twap * *threshold_bps as i128
         }
+        Condition::RsiCross(asset_id, direction, level) => {
+            let history = risk::get_price_history(env, *asset_id, HISTORY_LEN);
+            if history.len() < RSI_PERIOD + 2 {
+                return false;
+            }
+            let before = history.slice(0..history.len() - 1);
+            match (
+                momentum::calculate_rsi_from_prices(&before, RSI_PERIOD),
+                momentum::calculate_rsi_from_prices(&history, RSI_PERIOD),
+            ) {
+                (Ok(rsi_before), Ok(rsi_now)) => crossed(
+                    *direction,
+                    rsi_before as i128,
+                    rsi_now as i128,
+                    *level as i128,
+                ),
+                _ => false,
+            }
+        }
+        Condition::MaCross(asset_id, fast, slow, direction) => {
+            let history = risk::get_price_history(env, *asset_id, HISTORY_LEN);
+            let n = history.len();
+            if n < slow + 1 {
+                return false;
+            }
+            // Spread of fast over slow average, before and after the latest update.
+            let spread_before =
+                average(&history, n - 1 - fast, n - 1) - average(&history, n - 1 - slow, n - 1);
+            let spread_now = average(&history, n - fast, n) - average(&history, n - slow, n);
+            crossed(*direction, spread_before, spread_now, 0)
+        }
+        Condition::PortfolioDrawdown(threshold_bps) => {
+            let peak = order.peak_value;
+            if peak <= 0 {
+                return false;
+            }
+            let current = risk::calculate_portfolio_value(env, &order.user);
+            (peak - current) * 10_000 >= peak * *threshold_bps as i128
+        }
+        Condition::SignalResolved(signal_id) => signal_succeeded(env, *signal_id),
+    }
+}
+
+/// Evaluate node `idx`, short-circuiting `And` / `Or` and adding the cost of
+/// every leaf actually evaluated to `spent`.
+fn eval_node(env: &Env, order: &ConditionalOrder, idx: u32, spent: &mut u32) -> bool {
+    match order.tree.nodes.get(idx).unwrap() {
+        ConditionNode::Leaf(condition) => {
+            *spent += leaf_cost(&condition);
+            eval_condition(env, &condition, order)
+        }
+        ConditionNode::And(children) => children
+            .iter()
+            .all(|child| eval_node(env, order, child, spent)),
+        ConditionNode::Or(children) => children
+            .iter()
+            .any(|child| eval_node(env, order, child, spent)),
+        ConditionNode::Not(child) => !eval_node(env, order, child, spent),
     }
 }
 
 // ── Public API ────────────────────────────────────────────────────────────────
 
-/// Create a new conditional order.
+/// Create a new conditional order from a flat condition list joined by `logic`.
 pub fn create_conditional_order(
     env: &Env,
     user: Address,
@@ -234,18 +758,53 @@ pub fn create_conditional_order(
     conditions: Vec<Condition>,
     logic: LogicOp,
     expires_in_seconds: u64,
+) -> Result<u64, AutoTradeError> {
+    if conditions.is_empty() {
+        return Err(AutoTradeError::InvalidConditionalConfig);
+    }
+    let tree = flat_tree(env, &conditions, logic);
+    create_conditional_tree_order(
+        env,
+        user,
+        asset_id,
+        side,
+        amount,
+        limit_price,
+        tree,
+        expires_in_seconds,
+    )
+}
+
+/// Create a new conditional order triggered by `tree`.
+pub fn create_conditional_tree_order(
+    env: &Env,
+    user: Address,
+    asset_id: u32,
+    side: ConditionalSide,
+    amount: i128,
+    limit_price: i128,
+    tree: ConditionTree,
+    expires_in_seconds: u64,
 ) -> Result<u64, AutoTradeError> {
     user.require_auth();
 
     if amount <= 0 {
         return Err(AutoTradeError::InvalidAmount);
     }
-    if conditions.is_empty() {
-        return Err(AutoTradeError::InvalidConditionalConfig);
-    }
+    let (tree, cost) = prepare_tree(env, tree)?;
 
     let now = env.ledger().timestamp();
     let ref_price = current_price(env, asset_id);
+    let drawdown = uses_drawdown(&tree);
+    let peak_value = if drawdown {
+        risk::calculate_portfolio_value(env, &user)
+    } else {
+        0
+    };
+    // A drawdown from an empty portfolio can never be measured.
+    if drawdown && peak_value <= 0 {
+        return Err(AutoTradeError::InvalidConditionalConfig);
+    }
     let id = next_id(env);
 
     let order = ConditionalOrder {
@@ -255,15 +814,20 @@ pub fn create_conditional_order(
         side,
         amount,
         limit_price,
-        conditions,
-        logic,
+        tree,
+        cost,
         status: ConditionalStatus::Pending,
         created_at: now,
         expires_at: now + expires_in_seconds,
         reference_price: ref_price,
         trough_price: ref_price,
+        peak_value,
+        oco_group: Vec::new(env),
     };
 
+    if drawdown {
+        watch_drawdown(env, &order)?;
+    }
     save(env, &order);
     add_active(env, id);
 
@@ -276,6 +840,46 @@ pub fn create_conditional_order(
     Ok(id)
 }
 
+/// Link pending orders of `user` into a one-cancels-other group: the first
+/// to trigger cancels the others. Orders already in a group can't be linked.
+pub fn link_oco(env: &Env, user: Address, ids: Vec<u64>) -> Result<(), AutoTradeError> {
+    user.require_auth();
+    if ids.len() < 2 || ids.len() > MAX_OCO_GROUP {
+        return Err(AutoTradeError::InvalidConditionalConfig);
+    }
+
+    let mut orders = Vec::new(env);
+    for (i, id) in ids.iter().enumerate() {
+        if ids.first_index_of(id) != Some(i as u32) {
+            return Err(AutoTradeError::InvalidConditionalConfig);
+        }
+        let order = load(env, id)?;
+        if order.user != user {
+            return Err(AutoTradeError::Unauthorized);
+        }
+        if order.status != ConditionalStatus::Pending {
+            return Err(AutoTradeError::ConditionalOrderNotPending);
+        }
+        if !order.oco_group.is_empty() {
+            return Err(AutoTradeError::InvalidConditionalConfig);
+        }
+        orders.push_back(order);
+    }
+
+    for mut order in orders.iter() {
+        let mut group = ids.clone();
+        group.remove(group.first_index_of(order.id).unwrap());
+        order.oco_group = group;
+        save(env, &order);
+    }
+
+    #[allow(deprecated)]
+    env.events()
+        .publish((Symbol::new(env, "cond_order_oco_linked"), user), ids);
+
+    Ok(())
+}
+
 /// Cancel a pending conditional order (owner only).
 pub fn cancel_conditional_order(env: &Env, id: u64, user: Address) -> Result<(), AutoTradeError> {
     user.require_auth();
@@ -288,7 +892,7 @@ pub fn cancel_conditional_order(env: &Env, id: u64, user: Address) -> Result<(),
     }
     order.status = ConditionalStatus::Cancelled;
     save(env, &order);
-    remove_active(env, id);
+    retire(env, &order);
 
     #[allow(deprecated)]
     env.events()
@@ -302,23 +906,63 @@ pub fn get_conditional_order(env: &Env, id: u64) -> Result<ConditionalOrder, Aut
     load(env, id)
 }
 
-/// Process all active conditional orders against current market prices.
+/// Cancel the still-pending members of `order`'s one-cancels-other group.
+fn cancel_oco_siblings(env: &Env, order: &ConditionalOrder) {
+    for sibling_id in order.oco_group.iter() {
+        let Ok(mut sibling) = load(env, sibling_id) else {
+            continue;
+        };
+        if sibling.status != ConditionalStatus::Pending {
+            continue;
+        }
+        sibling.status = ConditionalStatus::Cancelled;
+        save(env, &sibling);
+        retire(env, &sibling);
+
+        #[allow(deprecated)]
+        env.events().publish(
+            (
+                Symbol::new(env, "cond_order_oco_cancelled"),
+                sibling.user.clone(),
+                sibling_id,
+            ),
+            order.id,
+        );
+    }
+}
+
+/// Process active conditional orders against current market prices.
 /// Returns the ids of orders that were triggered (and marked Triggered).
 /// Call `execute_triggered_orders` afterwards to actually fill them.
+///
+/// A pass evaluates orders until `MAX_CHECK_COST` is spent, starting where the
+/// previous pass stopped.
 pub fn check_and_trigger(env: &Env) -> Vec<u64> {
     let now = env.ledger().timestamp();
     let ids = active_ids(env);
     let mut triggered = Vec::new(env);
+    if ids.is_empty() {
+        return triggered;
+    }
 
-    for i in 0..ids.len() {
-        let id = ids.get(i).unwrap();
+    let start = env
+        .storage()
+        .persistent()
+        .get::<_, u64>(&ConditionalKey::CheckCursor)
+        .and_then(|cursor| ids.first_index_of(cursor))
+        .unwrap_or(0);
+    let mut spent = 0u32;
+    let mut next_cursor = None;
+
+    for step in 0..ids.len() {
+        let id = ids.get((start + step) % ids.len()).unwrap();
         let mut order = match load(env, id) {
             Ok(o) => o,
             Err(_) => continue,
         };
 
         if order.status != ConditionalStatus::Pending {
-            remove_active(env, id);
+            retire(env, &order);
             continue;
         }
 
@@ -326,7 +970,7 @@ pub fn check_and_trigger(env: &Env) -> Vec<u64> {
         if now >= order.expires_at {
             order.status = ConditionalStatus::Expired;
             save(env, &order);
-            remove_active(env, id);
+            retire(env, &order);
             #[allow(deprecated)]
             env.events().publish(
                 (
@@ -339,16 +983,23 @@ pub fn check_and_trigger(env: &Env) -> Vec<u64> {
             continue;
         }
 
+        // Leave the rest for the next pass once the budget can't cover this order.
+        if step > 0 && spent + order.cost > MAX_CHECK_COST {
+            next_cursor = Some(id);
+            break;
+        }
+
         // Update trough for rebound tracking
         let price = current_price(env, order.asset_id);
         if price > 0 && (order.trough_price == 0 || price < order.trough_price) {
             order.trough_price = price;
         }
 
-        if all_triggered(env, &order) {
+        if eval_node(env, &order, 0, &mut spent) {
             order.status = ConditionalStatus::Triggered;
             save(env, &order);
-            remove_active(env, id);
+            retire(env, &order);
+            cancel_oco_siblings(env, &order);
             triggered.push_back(id);
 
             #[allow(deprecated)]
@@ -366,6 +1017,17 @@ pub fn check_and_trigger(env: &Env) -> Vec<u64> {
         }
     }
 
+    match next_cursor {
+        Some(id) => env
+            .storage()
+            .persistent()
+            .set(&ConditionalKey::CheckCursor, &id),
+        None => env
+            .storage()
+            .persistent()
+            .remove(&ConditionalKey::CheckCursor),
+    }
+
     triggered
 }
 
@@ -375,6 +1037,17 @@ pub fn mark_executed(env: &Env, id: u64) -> Result<(), AutoTradeError> {
     if order.status != ConditionalStatus::Triggered {
         return Err(AutoTradeError::ConditionalOrderNotTriggered);
     }
+    auth::spend(
+        env,
+        &order.user,
+        None,
+        &Spend {
+            strategy: StrategyKind::Conditional,
+            asset: Some(order.asset_id),
+            counterparty: None,
+            amount: order.amount,
+        },
+    )?;
     order.status = ConditionalStatus::Executed;
     save(env, &order);
 
@@ -396,28 +1069,45 @@ pub fn mark_executed(env: &Env, id: u64) -> Result<(), AutoTradeError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::risk::RiskDataKey;
     use crate::{AutoTradeContract, AutoTradeContractClient};
     use soroban_sdk::{
+        contract, contractimpl,
         testutils::{Address as _, Ledger as _},
         Address, Env,
     };
 
+    #[contract]
+    struct MockSignalRegistry;
+
+    #[contractimpl]
+    impl MockSignalRegistry {
+        pub fn set_successful(env: Env, signal_id: u64) {
+            env.storage().instance().set(&signal_id, &true);
+        }
+
+        pub fn is_signal_successful(env: Env, signal_id: u64) -> bool {
+            env.storage().instance().has(&signal_id)
+        }
+    }
+
     fn setup() -> (Env, Address, AutoTradeContractClient<'static>) {
         let env = Env::default();
         env.mock_all_auths();
         env.ledger().set_timestamp(1_000);
         let contract = env.register(AutoTradeContract, ());
         let client = AutoTradeContractClient::new(&env, &contract);
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        client.add_keeper(&admin, &Address::generate(&env));
         (env, contract, client)
     }
 
+    fn check(client: &AutoTradeContractClient) -> Vec<u64> {
+        client.check_and_trigger_conditionals(&client.list_keepers().get(0).unwrap())
+    }
+
     fn seed_price(env: &Env, contract: &Address, asset_id: u32, price: i128) {
-        env.as_contract(contract, || {
-            env.storage()
-                .persistent()
-                .set(&RiskDataKey::AssetPrice(asset_id), &price);
-        });
+        env.as_contract(contract, || risk::set_asset_price(env, asset_id, price));
     }
 
     fn simple_price_condition(
@@ -513,10 +1203,10 @@ mod tests {
             &3_600,
         );
 
-        assert_eq!(client.check_and_trigger_conditionals().len(), 0);
+        assert_eq!(check(&client).len(), 0);
 
         seed_price(&env, &contract, 1, 115_000);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 1);
         assert_eq!(triggered.get(0).unwrap(), id);
         assert_eq!(
@@ -543,7 +1233,7 @@ mod tests {
         );
 
         seed_price(&env, &contract, 1, 85_000);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 1);
         assert_eq!(triggered.get(0).unwrap(), id);
     }
@@ -566,10 +1256,10 @@ mod tests {
             &10_000,
         );
 
-        assert_eq!(client.check_and_trigger_conditionals().len(), 0);
+        assert_eq!(check(&client).len(), 0);
 
         env.ledger().set_timestamp(2_001);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 1);
         assert_eq!(triggered.get(0).unwrap(), id);
     }
@@ -593,10 +1283,10 @@ mod tests {
         );
 
         seed_price(&env, &contract, 1, 89_000);
-        assert_eq!(client.check_and_trigger_conditionals().len(), 0);
+        assert_eq!(check(&client).len(), 0);
 
         seed_price(&env, &contract, 1, 91_700);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 1);
         assert_eq!(triggered.get(0).unwrap(), id);
     }
@@ -620,10 +1310,10 @@ mod tests {
         );
 
         seed_price(&env, &contract, 1, 104_000);
-        assert_eq!(client.check_and_trigger_conditionals().len(), 0);
+        assert_eq!(check(&client).len(), 0);
 
         seed_price(&env, &contract, 1, 106_000);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 1);
         assert_eq!(triggered.get(0).unwrap(), id);
     }
@@ -649,10 +1339,10 @@ mod tests {
         );
 
         seed_price(&env, &contract, 1, 115_000);
-        assert_eq!(client.check_and_trigger_conditionals().len(), 0);
+        assert_eq!(check(&client).len(), 0);
 
         seed_price(&env, &contract, 2, 35_000);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 1);
         assert_eq!(triggered.get(0).unwrap(), id);
     }
@@ -678,7 +1368,7 @@ mod tests {
         );
 
         seed_price(&env, &contract, 1, 115_000);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 1);
         assert_eq!(triggered.get(0).unwrap(), id);
     }
@@ -701,7 +1391,7 @@ mod tests {
         );
 
         env.ledger().set_timestamp(1_600);
-        let triggered = client.check_and_trigger_conditionals();
+        let triggered = check(&client);
         assert_eq!(triggered.len(), 0);
         assert_eq!(
             client.get_conditional_order(&id).status,
@@ -713,6 +1403,7 @@ mod tests {
     fn test_mark_executed() {
         let (env, contract, client) = setup();
         let user = Address::generate(&env);
+        client.grant_authorization(&user, &1_000_000, &30);
         seed_price(&env, &contract, 1, 120_000);
         let conditions = simple_price_condition(&env, 1, PriceDirection::Above, 110_000);
         let id = client.create_conditional_order(
@@ -726,7 +1417,7 @@ mod tests {
             &3_600,
         );
 
-        client.check_and_trigger_conditionals();
+        check(&client);
         assert_eq!(
             client.get_conditional_order(&id).status,
             ConditionalStatus::Triggered
@@ -760,4 +1451,516 @@ mod tests {
             Err(Ok(AutoTradeError::ConditionalOrderNotTriggered))
         );
     }
+
+    fn tree(env: &Env, nodes: &[ConditionNode]) -> ConditionTree {
+        let mut v = Vec::new(env);
+        for node in nodes {
+            v.push_back(node.clone());
+        }
+        ConditionTree { nodes: v }
+    }
+
+    fn create_tree_order(
+        env: &Env,
+        client: &AutoTradeContractClient,
+        user: &Address,
+        nodes: &[ConditionNode],
+    ) -> u64 {
+        client.create_conditional_tree_order(
+            user,
+            &1,
+            &ConditionalSide::Buy,
+            &1_000,
+            &0,
+            &tree(env, nodes),
+            &10_000,
+        )
+    }
+
+    fn record_history(env: &Env, contract: &Address, asset_id: u32, prices: &[i128]) {
+        env.as_contract(contract, || {
+            for price in prices {
+                risk::record_price(env, asset_id, *price);
+            }
+        });
+    }
+
+    #[test]
+    fn test_nested_tree_a_and_b_or_c() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        seed_price(&env, &contract, 1, 100_000);
+        seed_price(&env, &contract, 2, 50_000);
+        // (price1 ≥ 110k and price2 ≤ 40k) or time ≥ 5000
+        let nodes = [
+            ConditionNode::Or(vec![&env, 1, 4]),
+            ConditionNode::And(vec![&env, 2, 3]),
+            ConditionNode::Leaf(Condition::Price(1, PriceDirection::Above, 110_000)),
+            ConditionNode::Leaf(Condition::Price(2, PriceDirection::Below, 40_000)),
+            ConditionNode::Leaf(Condition::TimeAfter(5_000)),
+        ];
+        let by_branch = create_tree_order(&env, &client, &user, &nodes);
+
+        seed_price(&env, &contract, 1, 115_000);
+        assert_eq!(check(&client).len(), 0);
+        seed_price(&env, &contract, 2, 35_000);
+        assert_eq!(check(&client), vec![&env, by_branch]);
+
+        seed_price(&env, &contract, 2, 50_000);
+        let by_time = create_tree_order(&env, &client, &user, &nodes);
+        assert_eq!(check(&client).len(), 0);
+        env.ledger().set_timestamp(5_000);
+        assert_eq!(check(&client), vec![&env, by_time]);
+
+        // not (price1 ≥ 110k)
+        let negated = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[
+                ConditionNode::Not(1),
+                ConditionNode::Leaf(Condition::Price(1, PriceDirection::Above, 110_000)),
+            ],
+        );
+        assert_eq!(check(&client).len(), 0);
+        seed_price(&env, &contract, 1, 105_000);
+        assert_eq!(check(&client), vec![&env, negated]);
+    }
+
+    #[test]
+    fn test_tree_shape_and_leaves_validated() {
+        let (env, _contract, client) = setup();
+        let user = Address::generate(&env);
+        let time = ConditionNode::Leaf(Condition::TimeAfter(5_000));
+        let reject = |nodes: &[ConditionNode]| {
+            client.try_create_conditional_tree_order(
+                &user,
+                &1,
+                &ConditionalSide::Buy,
+                &1_000,
+                &0,
+                &tree(&env, nodes),
+                &10_000,
+            )
+        };
+        let invalid = Err(Ok(AutoTradeError::InvalidConditionalConfig));
+
+        assert_eq!(reject(&[]), invalid);
+        // Child pointing back at its parent.
+        assert_eq!(reject(&[ConditionNode::Not(0)]), invalid);
+        // Child shared by two parents.
+        assert_eq!(
+            reject(&[
+                ConditionNode::And(vec![&env, 1, 2]),
+                ConditionNode::Not(2),
+                time.clone(),
+            ]),
+            invalid
+        );
+        // Unreachable node.
+        assert_eq!(reject(&[time.clone(), time.clone()]), invalid);
+        assert_eq!(reject(&[ConditionNode::Or(Vec::new(&env))]), invalid);
+        // Five levels deep.
+        assert_eq!(
+            reject(&[
+                ConditionNode::Not(1),
+                ConditionNode::Not(2),
+                ConditionNode::Not(3),
+                ConditionNode::Not(4),
+                time.clone(),
+            ]),
+            invalid
+        );
+        let mut wide: [ConditionNode; MAX_TREE_NODES as usize + 1] =
+            core::array::from_fn(|_| time.clone());
+        let mut children = Vec::new(&env);
+        for i in 1..=MAX_TREE_NODES {
+            children.push_back(i);
+        }
+        wide[0] = ConditionNode::And(children);
+        assert_eq!(reject(&wide), invalid);
+
+        for leaf in [
+            Condition::PriceRatio(1, 1, PriceDirection::Above, 10_000_000),
+            Condition::PriceRatio(1, 2, PriceDirection::Above, 0),
+            Condition::TwapDeviation(1, 31, 100),
+            Condition::RsiCross(1, PriceDirection::Above, 10_000),
+            Condition::MaCross(1, 10, 10, PriceDirection::Above),
+            Condition::PortfolioDrawdown(0),
+            // No signal registry configured.
+            Condition::SignalResolved(1),
+        ] {
+            assert_eq!(reject(&[ConditionNode::Leaf(leaf)]), invalid);
+        }
+
+        // Four levels is fine.
+        reject(&[
+            ConditionNode::Not(1),
+            ConditionNode::Not(2),
+            ConditionNode::Not(3),
+            time,
+        ])
+        .unwrap()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cheapest_children_evaluated_first() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        record_history(&env, &contract, 1, &[100_000; 30]);
+        let id = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[
+                ConditionNode::And(vec![&env, 1, 2, 3]),
+                ConditionNode::Leaf(Condition::MaCross(1, 3, 10, PriceDirection::Above)),
+                ConditionNode::Leaf(Condition::Price(1, PriceDirection::Above, 1)),
+                ConditionNode::Leaf(Condition::TimeAfter(5_000)),
+            ],
+        );
+
+        let order = client.get_conditional_order(&id);
+        assert_eq!(
+            order.tree.nodes.get(0).unwrap(),
+            ConditionNode::And(vec![&env, 2, 3, 1])
+        );
+        assert_eq!(order.cost, cost::TIME + cost::PRICE + cost::HISTORY);
+    }
+
+    #[test]
+    fn test_price_ratio_and_twap_deviation_trigger() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        seed_price(&env, &contract, 3, 150_000);
+        seed_price(&env, &contract, 4, 100_000);
+        let ratio = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::PriceRatio(
+                3,
+                4,
+                PriceDirection::Above,
+                20_000_000,
+            ))],
+        );
+
+        record_history(&env, &contract, 2, &[100_000; 5]);
+        seed_price(&env, &contract, 2, 108_000);
+        let twap = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::TwapDeviation(2, 5, 1_000))],
+        );
+        assert_eq!(check(&client).len(), 0);
+
+        seed_price(&env, &contract, 3, 210_000);
+        seed_price(&env, &contract, 2, 89_000);
+        assert_eq!(check(&client), vec![&env, ratio, twap]);
+    }
+
+    #[test]
+    fn test_rsi_and_moving_average_crosses() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        let falling: [i128; 20] = core::array::from_fn(|i| 300 - 10 * i as i128);
+        record_history(&env, &contract, 1, &falling);
+
+        let rsi = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::RsiCross(
+                1,
+                PriceDirection::Above,
+                7_000,
+            ))],
+        );
+        let golden = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::MaCross(
+                1,
+                3,
+                10,
+                PriceDirection::Above,
+            ))],
+        );
+        let death = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::MaCross(
+                1,
+                3,
+                10,
+                PriceDirection::Below,
+            ))],
+        );
+        assert_eq!(check(&client).len(), 0);
+
+        record_history(&env, &contract, 1, &[1_000]);
+        assert_eq!(check(&client), vec![&env, rsi, golden]);
+
+        // A cross only fires on the update where it happens.
+        record_history(&env, &contract, 1, &[1_000]);
+        assert_eq!(check(&client).len(), 0);
+        assert_eq!(
+            client.get_conditional_order(&death).status,
+            ConditionalStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_portfolio_drawdown_from_peak() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        let set_price = |price: i128| {
+            env.as_contract(&contract, || risk::set_asset_price(&env, 5, price));
+        };
+        env.as_contract(&contract, || {
+            risk::update_position(&env, &user, 5, 1_000, 100);
+        });
+        set_price(100);
+        let id = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::PortfolioDrawdown(2_000))],
+        );
+        assert_eq!(client.get_conditional_order(&id).peak_value, 1_000);
+
+        set_price(90);
+        assert_eq!(check(&client).len(), 0);
+        set_price(120);
+        assert_eq!(check(&client).len(), 0);
+        assert_eq!(client.get_conditional_order(&id).peak_value, 1_200);
+
+        // 25% below the 1_200 peak.
+        set_price(90);
+        assert_eq!(check(&client), vec![&env, id]);
+        assert_eq!(
+            env.as_contract(&contract, || drawdown_owners(&env)).len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_portfolio_drawdown_peak_between_keeper_passes() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        let set_price = |price: i128| {
+            env.as_contract(&contract, || risk::set_asset_price(&env, 5, price));
+        };
+        env.as_contract(&contract, || {
+            risk::update_position(&env, &user, 5, 1_000, 100);
+        });
+        set_price(100);
+        let id = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::PortfolioDrawdown(2_000))],
+        );
+
+        // The 1_500 high is never seen by a keeper pass.
+        set_price(150);
+        set_price(110);
+        assert_eq!(client.get_conditional_order(&id).peak_value, 1_500);
+        assert_eq!(check(&client), vec![&env, id]);
+    }
+
+    #[test]
+    fn test_drawdown_needs_portfolio_value() {
+        let (env, _contract, client) = setup();
+        let user = Address::generate(&env);
+        let result = client.try_create_conditional_tree_order(
+            &user,
+            &1,
+            &ConditionalSide::Sell,
+            &1_000,
+            &0,
+            &tree(
+                &env,
+                &[ConditionNode::Leaf(Condition::PortfolioDrawdown(2_000))],
+            ),
+            &3_600,
+        );
+        assert_eq!(result, Err(Ok(AutoTradeError::InvalidConditionalConfig)));
+    }
+
+    #[test]
+    fn test_legacy_order_migrates_on_load() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        seed_price(&env, &contract, 1, 100_000);
+        let legacy = LegacyConditionalOrder {
+            id: 7,
+            user: user.clone(),
+            asset_id: 1,
+            side: ConditionalSide::Buy,
+            amount: 1_000,
+            limit_price: 0,
+            conditions: simple_price_condition(&env, 1, PriceDirection::Above, 110_000),
+            logic: LogicOp::And,
+            status: ConditionalStatus::Pending,
+            created_at: 1_000,
+            expires_at: 10_000,
+            reference_price: 100_000,
+            trough_price: 100_000,
+        };
+        env.as_contract(&contract, || {
+            env.storage()
+                .persistent()
+                .set(&ConditionalKey::Order(7), &legacy);
+            env.storage()
+                .persistent()
+                .set(&ConditionalKey::ActiveOrders, &vec![&env, 7u64]);
+        });
+
+        assert_eq!(check(&client).len(), 0);
+        let order = client.get_conditional_order(&7);
+        assert_eq!(order.user, user);
+        assert_eq!(order.status, ConditionalStatus::Pending);
+        assert_eq!(order.cost, cost::PRICE);
+        assert_eq!(order.tree.nodes.len(), 2);
+        assert!(!env.as_contract(&contract, || env
+            .storage()
+            .persistent()
+            .has(&ConditionalKey::Order(7))));
+        assert!(!client.migrate_conditional_order(&7));
+
+        seed_price(&env, &contract, 1, 120_000);
+        assert_eq!(check(&client), vec![&env, 7]);
+    }
+
+    #[test]
+    fn test_signal_resolved_queries_registry() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        let registry = MockSignalRegistryClient::new(&env, &env.register(MockSignalRegistry, ()));
+        let admin = env.as_contract(&contract, || crate::admin::get_admin(&env).unwrap());
+
+        assert_eq!(
+            client.try_set_conditional_signal_registry(&user, &Some(registry.address.clone())),
+            Err(Ok(AutoTradeError::Unauthorized))
+        );
+        client.set_conditional_signal_registry(&admin, &Some(registry.address.clone()));
+        assert_eq!(
+            client.get_conditional_signal_registry(),
+            Some(registry.address.clone())
+        );
+
+        let id = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::SignalResolved(42))],
+        );
+        assert_eq!(check(&client).len(), 0);
+        registry.set_successful(&42);
+        assert_eq!(check(&client), vec![&env, id]);
+    }
+
+    #[test]
+    fn test_oco_trigger_cancels_siblings() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        let other = Address::generate(&env);
+        seed_price(&env, &contract, 1, 100_000);
+        let take_profit = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::Price(
+                1,
+                PriceDirection::Above,
+                110_000,
+            ))],
+        );
+        let stop_loss = create_tree_order(
+            &env,
+            &client,
+            &user,
+            &[ConditionNode::Leaf(Condition::Price(
+                1,
+                PriceDirection::Below,
+                90_000,
+            ))],
+        );
+        let foreign = create_tree_order(
+            &env,
+            &client,
+            &other,
+            &[ConditionNode::Leaf(Condition::TimeAfter(5_000))],
+        );
+
+        assert_eq!(
+            client.try_link_oco_orders(&user, &vec![&env, take_profit]),
+            Err(Ok(AutoTradeError::InvalidConditionalConfig))
+        );
+        assert_eq!(
+            client.try_link_oco_orders(&user, &vec![&env, take_profit, take_profit]),
+            Err(Ok(AutoTradeError::InvalidConditionalConfig))
+        );
+        assert_eq!(
+            client.try_link_oco_orders(&user, &vec![&env, take_profit, foreign]),
+            Err(Ok(AutoTradeError::Unauthorized))
+        );
+        client.link_oco_orders(&user, &vec![&env, take_profit, stop_loss]);
+        assert_eq!(
+            client.get_conditional_order(&take_profit).oco_group,
+            vec![&env, stop_loss]
+        );
+        assert_eq!(
+            client.try_link_oco_orders(&user, &vec![&env, stop_loss, take_profit]),
+            Err(Ok(AutoTradeError::InvalidConditionalConfig))
+        );
+
+        seed_price(&env, &contract, 1, 85_000);
+        assert_eq!(check(&client), vec![&env, stop_loss]);
+        assert_eq!(
+            client.get_conditional_order(&take_profit).status,
+            ConditionalStatus::Cancelled
+        );
+
+        // The cancelled leg stays cancelled when its own condition later holds.
+        seed_price(&env, &contract, 1, 115_000);
+        assert_eq!(check(&client).len(), 0);
+    }
+
+    #[test]
+    fn test_check_stops_at_budget_and_resumes() {
+        let (env, contract, client) = setup();
+        let user = Address::generate(&env);
+        record_history(&env, &contract, 2, &[100_000; 30]);
+        seed_price(&env, &contract, 2, 100_000);
+        let mut ids = Vec::new(&env);
+        for _ in 0..15 {
+            ids.push_back(create_tree_order(
+                &env,
+                &client,
+                &user,
+                &[ConditionNode::Leaf(Condition::TwapDeviation(2, 30, 500))],
+            ));
+        }
+        let per_pass = MAX_CHECK_COST / cost::HISTORY;
+
+        // Nothing triggers, but a full pass still only covers `per_pass` orders.
+        assert_eq!(check(&client).len(), 0);
+        seed_price(&env, &contract, 2, 110_000);
+        let first = check(&client);
+        assert_eq!(first.len(), per_pass);
+        assert_eq!(first.get(0).unwrap(), ids.get(per_pass).unwrap());
+
+        let second = check(&client);
+        assert_eq!(second.len(), 15 - per_pass);
+        // Resumes after the orders the previous pass wrapped around to.
+        assert_eq!(second.get(0).unwrap(), ids.get(2 * per_pass - 15).unwrap());
+        assert_eq!(check(&client).len(), 0);
+    }
 }
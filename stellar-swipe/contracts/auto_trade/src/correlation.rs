@@ -170,6 +170,14 @@ pub fn build_correlation_matrix(env: &Env, assets: &Vec<u32>) -> CorrelationMatr
     }
 }
 
+/// Correlation (bps) between two assets in `matrix`; 0 when not computed.
+pub fn get_correlation(matrix: &CorrelationMatrix, asset_a: u32, asset_b: u32) -> i128 {
+    matrix
+        .correlations
+        .get(pair_key(asset_a, asset_b))
+        .unwrap_or(0)
+}
+
 /// Retrieve cached matrix or rebuild if stale / missing.
 pub fn get_or_build_matrix(env: &Env, user: &Address, assets: &Vec<u32>) -> CorrelationMatrix {
     if let Some(cached) = env
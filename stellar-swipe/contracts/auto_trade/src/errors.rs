@@ -40,7 +40,7 @@ pub enum AutoTradeError {
     InvalidInsuranceConfig = 27,
     // ── Referral (SelfReferral / AlreadySet / Circular / LimitExceeded) ──────
     ReferralError = 28,
-    // ── TWAP (InvalidDuration / NotFound / NotOwner / NotActive) ─────────────
+    // ── TWAP & iceberg (InvalidDuration / NotFound / NotOwner / NotActive) ───
     TWAPError = 29,
     // ── Correlation ──────────────────────────────────────────────────────────
     CorrelationLimitExceeded = 30,
@@ -90,6 +90,14 @@ impl AutoTradeError {
     pub const TWAPOrderNotFound: AutoTradeError = AutoTradeError::TWAPError;
     pub const NotTWAPOwner: AutoTradeError = AutoTradeError::TWAPError;
     pub const TWAPNotActive: AutoTradeError = AutoTradeError::TWAPError;
+    pub const InvalidTWAPConfig: AutoTradeError = AutoTradeError::TWAPError;
+    pub const IcebergOrderNotFound: AutoTradeError = AutoTradeError::TWAPError;
+    pub const NotIcebergOwner: AutoTradeError = AutoTradeError::TWAPError;
+    pub const IcebergNotActive: AutoTradeError = AutoTradeError::TWAPError;
+    pub const InvalidIcebergConfig: AutoTradeError = AutoTradeError::TWAPError;
+    pub const InvalidJitterReveal: AutoTradeError = AutoTradeError::TWAPError;
+
+    pub const HedgeTokenNotSet: AutoTradeError = AutoTradeError::InvalidInsuranceConfig;
 
     pub const ConditionalOrderNotFound: AutoTradeError = AutoTradeError::ConditionalOrderError;
     pub const ConditionalOrderNotPending: AutoTradeError = AutoTradeError::ConditionalOrderError;
@@ -122,6 +130,13 @@ impl AutoTradeError {
     pub const ArbitrageUnprofitable: AutoTradeError = AutoTradeError::ArbitrageError;
     pub const ArbTooLarge: AutoTradeError = AutoTradeError::ArbitrageError;
 
+    pub const PolicyNotFound: AutoTradeError = AutoTradeError::Unauthorized;
+    pub const NoMatchingPolicy: AutoTradeError = AutoTradeError::Unauthorized;
+    pub const SessionKeyInvalid: AutoTradeError = AutoTradeError::Unauthorized;
+    pub const TooManyPolicies: AutoTradeError = AutoTradeError::PositionLimitExceeded;
+
+    pub const NotCompliant: AutoTradeError = AutoTradeError::Unauthorized;
+
     pub const AtomicExecutionFailed: AutoTradeError = AutoTradeError::SystemError;
     pub const BridgePaused: AutoTradeError = AutoTradeError::SystemError;
     pub const RecoveryNotFound: AutoTradeError = AutoTradeError::SystemError;
@@ -1,27 +1,13 @@
 #![allow(dead_code)]
 
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+use stellar_swipe_common::exit_tiers::{apply_exit_tiers, ExitKind};
+pub use stellar_swipe_common::exit_tiers::{StopLossTier, TakeProfitTier};
 
 use crate::errors::AutoTradeError;
 
 // ── Types ─────────────────────────────────────────────────────────────────────
 
-#[contracttype]
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct TakeProfitTier {
-    pub price: i128,
-    pub position_pct: u32, // basis points (10000 = 100%)
-    pub executed: bool,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct StopLossTier {
-    pub trigger_profit_pct: u32, // activate after this % profit (0-based)
-    pub trail_pct: u32,          // trail distance in %
-    pub active: bool,
-}
-
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum StrategyStatus {
@@ -101,12 +87,6 @@ fn execute_sell(env: &Env, _user: &Address, signal_id: u64, amount: i128, price:
         .wrapping_add(seq as u64)
 }
 
-// ── Trailing stop calculation ─────────────────────────────────────────────────
-
-fn calculate_trailing_stop(highest_price: i128, trail_pct: u32) -> i128 {
-    highest_price * (100 - trail_pct as i128) / 100
-}
-
 // ── Core execution ────────────────────────────────────────────────────────────
 
 pub fn check_and_execute_exits(
@@ -120,95 +100,42 @@ pub fn check_and_execute_exits(
         return Ok(Vec::new(env));
     }
 
-    // Update highest price for trailing stop
-    if current_price > strategy.highest_price {
-        strategy.highest_price = current_price;
-    }
+    let fills = apply_exit_tiers(
+        env,
+        strategy.entry_price,
+        current_price,
+        &mut strategy.highest_price,
+        &mut strategy.current_position_size,
+        &mut strategy.take_profit_tiers,
+        &mut strategy.stop_loss_tiers,
+    );
 
     let mut executed_trades: Vec<u64> = Vec::new(env);
-
-    // ── Take-profit tiers ─────────────────────────────────────────────────────
-    let tp_len = strategy.take_profit_tiers.len();
-    for i in 0..tp_len {
-        let tp = strategy.take_profit_tiers.get(i).unwrap();
-        if !tp.executed && current_price >= tp.price && strategy.current_position_size > 0 {
-            let close_amount = (strategy.current_position_size * tp.position_pct as i128) / 10_000;
-            let close_amount = close_amount.max(1);
-
-            let trade_id = execute_sell(
-                env,
-                &strategy.user,
-                strategy.signal_id,
-                close_amount,
-                current_price,
-            );
-
-            strategy.current_position_size -= close_amount;
-
-            let mut updated_tp = tp.clone();
-            updated_tp.executed = true;
-            strategy.take_profit_tiers.set(i, updated_tp);
-
-            executed_trades.push_back(trade_id);
-
-            #[allow(deprecated)]
-            env.events().publish(
-                (Symbol::new(env, "tp_hit"), strategy_id, tp.price),
-                (close_amount, strategy.current_position_size),
-            );
-        }
-    }
-
-    // ── Trailing stop tiers ───────────────────────────────────────────────────
-    if strategy.current_position_size > 0 {
-        let current_profit_pct = if strategy.entry_price > 0 {
-            ((current_price - strategy.entry_price) * 100) / strategy.entry_price
-        } else {
-            0
-        };
-
-        // Activate tiers whose profit threshold has been crossed
-        let sl_len = strategy.stop_loss_tiers.len();
-        for i in 0..sl_len {
-            let mut tier = strategy.stop_loss_tiers.get(i).unwrap();
-            if current_profit_pct >= tier.trigger_profit_pct as i128 {
-                tier.active = true;
-                strategy.stop_loss_tiers.set(i, tier);
-            }
-        }
-
-        // Find tightest active trail_pct
-        let mut tightest_trail: Option<u32> = None;
-        for i in 0..sl_len {
-            let tier = strategy.stop_loss_tiers.get(i).unwrap();
-            if tier.active {
-                tightest_trail = Some(match tightest_trail {
-                    None => tier.trail_pct,
-                    Some(prev) => prev.min(tier.trail_pct),
-                });
-            }
-        }
-
-        if let Some(trail_pct) = tightest_trail {
-            let stop_price = calculate_trailing_stop(strategy.highest_price, trail_pct);
-            if current_price < stop_price && strategy.current_position_size > 0 {
-                let trade_id = execute_sell(
-                    env,
-                    &strategy.user,
-                    strategy.signal_id,
-                    strategy.current_position_size,
-                    current_price,
+    for fill in fills.iter() {
+        let trade_id = execute_sell(
+            env,
+            &strategy.user,
+            strategy.signal_id,
+            fill.amount,
+            current_price,
+        );
+        executed_trades.push_back(trade_id);
+
+        match fill.kind {
+            ExitKind::TakeProfit => {
+                #[allow(deprecated)]
+                env.events().publish(
+                    (Symbol::new(env, "tp_hit"), strategy_id, fill.trigger_price),
+                    (fill.amount, fill.remaining),
                 );
-
+            }
+            ExitKind::TrailingStop => {
                 #[allow(deprecated)]
                 env.events().publish(
                     (Symbol::new(env, "trail_stop_hit"), strategy_id),
-                    (current_price, strategy.current_position_size),
+                    (current_price, fill.amount),
                 );
-
-                strategy.current_position_size = 0;
                 strategy.status = StrategyStatus::StopHit;
-                executed_trades.push_back(trade_id);
             }
         }
     }
@@ -2,10 +2,33 @@
 //!
 //! Implements iceberg orders that show only a small portion publicly while keeping
 //! the remainder hidden to minimize market impact.
+//!
+//! Each visible slice is a real SDEX offer posted through the registered
+//! `SdexRouter` AMM source. On creation the whole order moves into contract
+//! custody; each slice is approved to the router for exactly its size and a
+//! short expiry, so keepers can refill without the user's signature while the
+//! router never holds more than the live slice. Amounts are in units of the
+//! `selling` token.
+//!
+//! Registered keepers call `refresh_iceberg_order`: fills and their proceeds
+//! are read back from the offer, proceeds are forwarded to the user, and once
+//! a slice is taken the next one is posted.
+//!
+//! Slice sizes are jittered by up to `slice_jitter_bps` around the base
+//! visible amount. The entropy comes from a hash chain the user commits to on
+//! creation: each refill must reveal the preimage of the last value, so the
+//! next size cannot be read from chain state before the refill lands.
 
 #![allow(dead_code)]
 
-use soroban_sdk::{contracttype, Address, Env, Map, String, Symbol, Vec};
+use soroban_sdk::{contracttype, token, Address, Bytes, BytesN, Env, IntoVal, String, Symbol, Vec};
+use stellar_swipe_common::amm_bridge::{
+    AmmSourceConfig, AmmSourceKind, FN_CANCEL_OFFER, FN_GET_OFFER, FN_PLACE_OFFER,
+};
+
+use crate::amm_bridge::{self, TokenPairConfig};
+use crate::errors::AutoTradeError;
+use crate::keeper;
 
 /// Order side (buy or sell)
 #[contracttype]
@@ -42,8 +65,16 @@ pub struct IcebergOrder {
     pub user: Address,
     pub pair: AssetPair,
     pub side: OrderSide,
+    pub selling: Address,
+    pub buying: Address,
+    /// Router holding the order's offers.
+    pub router: Address,
     pub total_amount: i128,
+    /// Base slice size; each slice is jittered around it.
     pub visible_amount: i128,
+    pub slice_jitter_bps: u32,
+    /// Size of the slice currently on the book.
+    pub current_visible: i128,
     pub price: i128,
     pub filled_amount: i128,
     pub current_visible_filled: i128,
@@ -103,66 +134,87 @@ pub struct FillEvent {
 pub enum IcebergStorageKey {
     OrderCounter,
     IcebergOrder(u64),
-    SdexToIceberg(u64),
     CurrentSdexOrder(u64),
     FillHistory(u64),
+    /// Last unrevealed value of the order's jitter hash chain.
+    JitterCommitment(u64),
+    /// `buying` already paid out on the live slice.
+    SliceProceeds(u64),
 }
 
 // Constants
 const MAX_VISIBLE_PCT: u32 = 5000; // 50%
 const MIN_VISIBLE_AMOUNT: i128 = 1000; // Minimum visible amount
 const BASIS_POINTS: i128 = 10000;
+const MAX_SLICE_JITTER_BPS: u32 = 5000;
+
+/// Router allowance lifetime for a single slice (ledgers).
+const SLICE_ALLOWANCE_LEDGERS: u32 = 100;
 
 /// ==========================
 /// Order Creation
 /// ==========================
 
-/// Create a new iceberg order
+/// Create a new iceberg order and post its first slice
+#[allow(clippy::too_many_arguments)]
 pub fn create_iceberg_order(
     env: &Env,
     user: Address,
     pair: AssetPair,
     side: OrderSide,
+    tokens: TokenPairConfig,
     total_amount: i128,
     visible_pct: u32,
     price: i128,
-) -> Result<u64, String> {
+    slice_jitter_bps: u32,
+    jitter_commitment: Option<BytesN<32>>,
+) -> Result<u64, AutoTradeError> {
     user.require_auth();
 
     // Validate inputs
-    if total_amount <= 0 {
-        return Err(String::from_str(env, "Invalid total amount"));
-    }
-
-    if price <= 0 {
-        return Err(String::from_str(env, "Invalid price"));
+    if total_amount <= 0 || price <= 0 {
+        return Err(AutoTradeError::InvalidAmount);
     }
 
-    if visible_pct == 0 || visible_pct > MAX_VISIBLE_PCT {
-        return Err(String::from_str(env, "Invalid visible percentage"));
+    if visible_pct == 0
+        || visible_pct > MAX_VISIBLE_PCT
+        || slice_jitter_bps > MAX_SLICE_JITTER_BPS
+        || (slice_jitter_bps > 0 && jitter_commitment.is_none())
+    {
+        return Err(AutoTradeError::InvalidIcebergConfig);
     }
 
     // Calculate visible amount
     let visible_amount = (total_amount * visible_pct as i128) / BASIS_POINTS;
 
     if visible_amount < MIN_VISIBLE_AMOUNT {
-        return Err(String::from_str(env, "Visible amount too small"));
+        return Err(AutoTradeError::InvalidIcebergConfig);
     }
 
+    let router = sdex_router(env)?;
+
+    // Custody the order so slices can be approved one at a time.
+    token::Client::new(env, &tokens.from_token).transfer(
+        &user,
+        &env.current_contract_address(),
+        &total_amount,
+    );
+
     // Get next order ID
     let order_id = get_next_order_id(env);
 
-    // Place initial visible portion on SDEX
-    let sdex_order_id = place_sdex_limit_order(env, &user, &pair, side, visible_amount, price)?;
-
-    // Create iceberg order
-    let iceberg = IcebergOrder {
+    let mut iceberg = IcebergOrder {
         id: order_id,
         user: user.clone(),
-        pair: pair.clone(),
+        pair,
         side,
+        selling: tokens.from_token,
+        buying: tokens.to_token,
+        router,
         total_amount,
         visible_amount,
+        slice_jitter_bps,
+        current_visible: 0,
         price,
         filled_amount: 0,
         current_visible_filled: 0,
@@ -171,10 +223,15 @@ pub fn create_iceberg_order(
         avg_fill_price: 0,
     };
 
-    // Store order
+    // Place initial visible portion on SDEX, jittered from the commitment
+    // itself; later slices draw on the values revealed behind it.
+    if let Some(commitment) = &jitter_commitment {
+        env.storage()
+            .persistent()
+            .set(&IcebergStorageKey::JitterCommitment(order_id), commitment);
+    }
+    post_next_slice(env, &mut iceberg, jitter_commitment.as_ref())?;
     store_iceberg_order(env, order_id, &iceberg);
-    map_sdex_to_iceberg(env, sdex_order_id, order_id);
-    store_current_sdex_order(env, order_id, sdex_order_id);
 
     // Emit event (private - includes total amount)
     env.events().publish(
@@ -189,15 +246,76 @@ pub fn create_iceberg_order(
 /// Order Filling & Replenishment
 /// ==========================
 
-/// Handle SDEX order fill
-pub fn on_sdex_fill(
+/// Keeper entrypoint: record fills on the live slice and, once it has been
+/// taken, post the next one. Returns the amount newly filled.
+///
+/// A jittered order's refill needs `jitter_reveal`, the preimage of the
+/// order's current jitter commitment.
+pub fn refresh_iceberg_order(
     env: &Env,
-    sdex_order_id: u64,
-    filled_amount: i128,
-    fill_price: i128,
-) -> Result<(), String> {
-    let iceberg_id = get_iceberg_from_sdex(env, sdex_order_id)?;
-    let mut iceberg = get_iceberg_order(env, iceberg_id)?;
+    keeper: Address,
+    order_id: u64,
+    jitter_reveal: Option<BytesN<32>>,
+) -> Result<i128, AutoTradeError> {
+    keeper::require_registered_keeper(env, &keeper)?;
+    let mut iceberg = get_iceberg_order(env, order_id)?;
+    require_open(&iceberg)?;
+
+    let offer_id = get_current_sdex_order(env, order_id)?;
+    let (on_book, paid_out) = get_offer(env, &iceberg.router, offer_id);
+    let new_fill = sync_fills(env, &mut iceberg, on_book, paid_out);
+
+    if on_book == 0 {
+        let remaining = iceberg.total_amount - iceberg.filled_amount;
+        if remaining > 0 {
+            let entropy = reveal_jitter(env, &iceberg, jitter_reveal)?;
+            post_next_slice(env, &mut iceberg, entropy.as_ref())?;
+            iceberg.status = OrderStatus::PartiallyFilled;
+
+            // Emit replenishment event
+            env.events().publish(
+                (Symbol::new(env, "iceberg_replenished"), order_id),
+                iceberg.current_visible,
+            );
+        } else {
+            // Order complete
+            iceberg.status = OrderStatus::Filled;
+
+            env.events().publish(
+                (Symbol::new(env, "iceberg_complete"), order_id),
+                (iceberg.filled_amount, iceberg.avg_fill_price),
+            );
+        }
+    }
+
+    // Save updated order
+    store_iceberg_order(env, order_id, &iceberg);
+
+    Ok(new_fill)
+}
+
+/// Record whatever part of the live slice has filled since the last sync and
+/// forward its proceeds to the user. `on_book` is the amount of the slice
+/// still resting on the book and `paid_out` the `buying` the router has paid
+/// on it so far; the fill is priced from the proceeds, not the limit.
+fn sync_fills(env: &Env, iceberg: &mut IcebergOrder, on_book: i128, paid_out: i128) -> i128 {
+    let slice_filled = (iceberg.current_visible - on_book).max(0);
+    let filled_amount = slice_filled - iceberg.current_visible_filled;
+    if filled_amount <= 0 {
+        return 0;
+    }
+    let proceeds_key = IcebergStorageKey::SliceProceeds(iceberg.id);
+    let forwarded: i128 = env.storage().persistent().get(&proceeds_key).unwrap_or(0);
+    let proceeds = (paid_out - forwarded).max(0);
+    if proceeds > 0 {
+        token::Client::new(env, &iceberg.buying).transfer(
+            &env.current_contract_address(),
+            &iceberg.user,
+            &proceeds,
+        );
+        env.storage().persistent().set(&proceeds_key, &paid_out);
+    }
+    let fill_price = proceeds / filled_amount;
 
     // Update fill amounts
     iceberg.filled_amount += filled_amount;
@@ -207,70 +325,75 @@ pub fn on_sdex_fill(
     let total_value = (iceberg.avg_fill_price * (iceberg.filled_amount - filled_amount))
         + (fill_price * filled_amount);
     iceberg.avg_fill_price = total_value / iceberg.filled_amount;
+    iceberg.status = OrderStatus::PartiallyFilled;
 
     // Record fill in history
-    record_fill(env, iceberg_id, filled_amount, fill_price);
+    record_fill(env, iceberg.id, filled_amount, fill_price);
 
     // Emit fill event
     env.events().publish(
-        (Symbol::new(env, "iceberg_filled"), iceberg_id),
+        (Symbol::new(env, "iceberg_filled"), iceberg.id),
         FillEvent {
-            order_id: iceberg_id,
+            order_id: iceberg.id,
             filled_amount,
             price: fill_price,
             total_filled: iceberg.filled_amount,
         },
     );
 
-    // Check if current visible portion is fully filled
-    if iceberg.current_visible_filled >= iceberg.visible_amount {
-        let remaining = iceberg.total_amount - iceberg.filled_amount;
-
-        if remaining > 0 {
-            // Replenish visible portion
-            let new_visible = min(remaining, iceberg.visible_amount);
-
-            let new_sdex_order = place_sdex_limit_order(
-                env,
-                &iceberg.user,
-                &iceberg.pair,
-                iceberg.side,
-                new_visible,
-                iceberg.price,
-            )?;
-
-            // Update mappings
-            map_sdex_to_iceberg(env, new_sdex_order, iceberg_id);
-            store_current_sdex_order(env, iceberg_id, new_sdex_order);
-
-            // Reset current visible filled counter
-            iceberg.current_visible_filled = 0;
-            iceberg.status = OrderStatus::PartiallyFilled;
-
-            // Emit replenishment event
-            env.events().publish(
-                (Symbol::new(env, "iceberg_replenished"), iceberg_id),
-                new_visible,
-            );
-        } else {
-            // Order complete
-            iceberg.status = OrderStatus::Filled;
+    filled_amount
+}
 
-            env.events().publish(
-                (Symbol::new(env, "iceberg_complete"), iceberg_id),
-                (iceberg.filled_amount, iceberg.avg_fill_price),
-            );
-        }
-    } else {
-        // Partially filled, update status
-        if iceberg.filled_amount > 0 {
-            iceberg.status = OrderStatus::PartiallyFilled;
-        }
+/// Check `reveal` against the order's jitter commitment and advance the
+/// chain. Orders without jitter need no reveal.
+fn reveal_jitter(
+    env: &Env,
+    iceberg: &IcebergOrder,
+    reveal: Option<BytesN<32>>,
+) -> Result<Option<BytesN<32>>, AutoTradeError> {
+    if iceberg.slice_jitter_bps == 0 {
+        return Ok(None);
+    }
+    let key = IcebergStorageKey::JitterCommitment(iceberg.id);
+    let commitment: BytesN<32> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(AutoTradeError::InvalidJitterReveal)?;
+    let reveal = reveal.ok_or(AutoTradeError::InvalidJitterReveal)?;
+    let hashed: BytesN<32> = env
+        .crypto()
+        .sha256(&Bytes::from_array(env, &reveal.to_array()))
+        .into();
+    if hashed != commitment {
+        return Err(AutoTradeError::InvalidJitterReveal);
     }
+    env.storage().persistent().set(&key, &reveal);
+    Ok(Some(reveal))
+}
 
-    // Save updated order
-    store_iceberg_order(env, iceberg_id, &iceberg);
+/// Size the next slice, jittered around the base visible amount by
+/// `entropy`, and post it.
+fn post_next_slice(
+    env: &Env,
+    iceberg: &mut IcebergOrder,
+    entropy: Option<&BytesN<32>>,
+) -> Result<(), AutoTradeError> {
+    let remaining = iceberg.total_amount - iceberg.filled_amount;
+    let mut slice = iceberg.visible_amount;
+    if let (true, Some(entropy)) = (iceberg.slice_jitter_bps > 0, entropy) {
+        let jitter = iceberg.slice_jitter_bps as u64;
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&entropy.to_array()[..8]);
+        let factor = (u64::from_be_bytes(word) % (2 * jitter + 1)) as i128;
+        slice = slice * (BASIS_POINTS - jitter as i128 + factor) / BASIS_POINTS;
+    }
+    let slice = min(remaining, slice.max(MIN_VISIBLE_AMOUNT));
 
+    let offer_id = place_sdex_limit_order(env, iceberg, slice, iceberg.price)?;
+    store_current_sdex_order(env, iceberg.id, offer_id);
+    iceberg.current_visible = slice;
+    iceberg.current_visible_filled = 0;
     Ok(())
 }
 
@@ -282,6 +405,13 @@ fn min(a: i128, b: i128) -> i128 {
     }
 }
 
+fn require_open(iceberg: &IcebergOrder) -> Result<(), AutoTradeError> {
+    if iceberg.status != OrderStatus::Active && iceberg.status != OrderStatus::PartiallyFilled {
+        return Err(AutoTradeError::IcebergNotActive);
+    }
+    Ok(())
+}
+
 /// ==========================
 /// Order Management
 /// ==========================
@@ -291,24 +421,32 @@ pub fn cancel_iceberg_order(
     env: &Env,
     order_id: u64,
     user: Address,
-) -> Result<CancellationInfo, String> {
+) -> Result<CancellationInfo, AutoTradeError> {
     user.require_auth();
 
     let mut iceberg = get_iceberg_order(env, order_id)?;
 
     // Verify ownership
     if iceberg.user != user {
-        return Err(String::from_str(env, "Not order owner"));
+        return Err(AutoTradeError::NotIcebergOwner);
     }
 
     // Verify order is cancellable
-    if iceberg.status == OrderStatus::Filled || iceberg.status == OrderStatus::Cancelled {
-        return Err(String::from_str(env, "Order not cancellable"));
-    }
-
-    // Cancel current SDEX order
-    if let Ok(sdex_order_id) = get_current_sdex_order(env, order_id) {
-        cancel_sdex_order(env, sdex_order_id)?;
+    require_open(&iceberg)?;
+
+    // Pull the live slice, counting whatever filled before it came off
+    let sdex_order_id = get_current_sdex_order(env, order_id)?;
+    let unfilled = cancel_sdex_order(env, &iceberg, sdex_order_id)?;
+    let (_, paid_out) = get_offer(env, &iceberg.router, sdex_order_id);
+    sync_fills(env, &mut iceberg, unfilled, paid_out);
+
+    // Drop any unused slice allowance and return the unsold remainder
+    let this = env.current_contract_address();
+    let selling = token::Client::new(env, &iceberg.selling);
+    selling.approve(&this, &iceberg.router, &0, &env.ledger().sequence());
+    let remaining = iceberg.total_amount - iceberg.filled_amount;
+    if remaining > 0 {
+        selling.transfer(&this, &user, &remaining);
     }
 
     // Update status
@@ -329,53 +467,49 @@ pub fn cancel_iceberg_order(
     Ok(info)
 }
 
-/// Update iceberg order price
+/// Update iceberg order price. The live offer is cancelled and its unfilled
+/// part re-posted at `new_price` in the same invocation, so a failure on
+/// either side leaves the old offer in place. A slice that was already taken
+/// is left for the next keeper refresh to replace.
 pub fn update_iceberg_price(
     env: &Env,
     order_id: u64,
     user: Address,
     new_price: i128,
-) -> Result<(), String> {
+) -> Result<(), AutoTradeError> {
     user.require_auth();
 
     let mut iceberg = get_iceberg_order(env, order_id)?;
 
     if iceberg.user != user {
-        return Err(String::from_str(env, "Not order owner"));
+        return Err(AutoTradeError::NotIcebergOwner);
     }
 
-    if iceberg.status != OrderStatus::Active && iceberg.status != OrderStatus::PartiallyFilled {
-        return Err(String::from_str(env, "Order not active"));
-    }
+    require_open(&iceberg)?;
 
     if new_price <= 0 {
-        return Err(String::from_str(env, "Invalid price"));
+        return Err(AutoTradeError::InvalidAmount);
     }
 
-    // Cancel current SDEX order
-    if let Ok(sdex_order_id) = get_current_sdex_order(env, order_id) {
-        cancel_sdex_order(env, sdex_order_id)?;
-    }
+    // Cancel current SDEX order, settling fills at the old price first
+    let sdex_order_id = get_current_sdex_order(env, order_id)?;
+    let unfilled = cancel_sdex_order(env, &iceberg, sdex_order_id)?;
+    let (_, paid_out) = get_offer(env, &iceberg.router, sdex_order_id);
+    sync_fills(env, &mut iceberg, unfilled, paid_out);
+    iceberg.price = new_price;
 
-    // Place new order at new price
-    let remaining_visible = iceberg.visible_amount - iceberg.current_visible_filled;
-    let new_sdex_order = place_sdex_limit_order(
-        env,
-        &iceberg.user,
-        &iceberg.pair,
-        iceberg.side,
-        remaining_visible,
-        new_price,
-    )?;
+    // Re-post the unfilled part at the new price.
+    if unfilled > 0 {
+        let new_sdex_order = place_sdex_limit_order(env, &iceberg, unfilled, new_price)?;
+        store_current_sdex_order(env, order_id, new_sdex_order);
+        iceberg.current_visible = unfilled;
+        iceberg.current_visible_filled = 0;
+    } else if iceberg.filled_amount >= iceberg.total_amount {
+        iceberg.status = OrderStatus::Filled;
+    }
 
-    // Update order
-    iceberg.price = new_price;
     store_iceberg_order(env, order_id, &iceberg);
 
-    // Update mappings
-    map_sdex_to_iceberg(env, new_sdex_order, order_id);
-    store_current_sdex_order(env, order_id, new_sdex_order);
-
     env.events().publish(
         (Symbol::new(env, "iceberg_price_updated"), order_id),
         new_price,
@@ -389,7 +523,7 @@ pub fn update_iceberg_price(
 /// ==========================
 
 /// Get public view of order (hides total amount)
-pub fn get_public_order_view(env: &Env, order_id: u64) -> Result<PublicOrderView, String> {
+pub fn get_public_order_view(env: &Env, order_id: u64) -> Result<PublicOrderView, AutoTradeError> {
     let iceberg = get_iceberg_order(env, order_id)?;
 
     Ok(PublicOrderView {
@@ -397,7 +531,7 @@ pub fn get_public_order_view(env: &Env, order_id: u64) -> Result<PublicOrderView
         pair: iceberg.pair,
         side: iceberg.side,
         price: iceberg.price,
-        visible_amount: iceberg.visible_amount,
+        visible_amount: iceberg.current_visible - iceberg.current_visible_filled,
     })
 }
 
@@ -406,13 +540,13 @@ pub fn get_full_order_view(
     env: &Env,
     order_id: u64,
     user: Address,
-) -> Result<FullOrderView, String> {
+) -> Result<FullOrderView, AutoTradeError> {
     user.require_auth();
 
     let iceberg = get_iceberg_order(env, order_id)?;
 
     if iceberg.user != user {
-        return Err(String::from_str(env, "Not order owner"));
+        return Err(AutoTradeError::NotIcebergOwner);
     }
 
     Ok(FullOrderView {
@@ -488,25 +622,11 @@ fn store_iceberg_order(env: &Env, order_id: u64, order: &IcebergOrder) {
         .set(&IcebergStorageKey::IcebergOrder(order_id), order);
 }
 
-fn get_iceberg_order(env: &Env, order_id: u64) -> Result<IcebergOrder, String> {
+pub fn get_iceberg_order(env: &Env, order_id: u64) -> Result<IcebergOrder, AutoTradeError> {
     env.storage()
         .persistent()
         .get(&IcebergStorageKey::IcebergOrder(order_id))
-        .ok_or_else(|| String::from_str(env, "Order not found"))
-}
-
-fn map_sdex_to_iceberg(env: &Env, sdex_order_id: u64, iceberg_id: u64) {
-    env.storage().persistent().set(
-        &IcebergStorageKey::SdexToIceberg(sdex_order_id),
-        &iceberg_id,
-    );
-}
-
-fn get_iceberg_from_sdex(env: &Env, sdex_order_id: u64) -> Result<u64, String> {
-    env.storage()
-        .persistent()
-        .get(&IcebergStorageKey::SdexToIceberg(sdex_order_id))
-        .ok_or_else(|| String::from_str(env, "SDEX order not mapped"))
+        .ok_or(AutoTradeError::IcebergOrderNotFound)
 }
 
 fn store_current_sdex_order(env: &Env, iceberg_id: u64, sdex_order_id: u64) {
@@ -514,13 +634,16 @@ fn store_current_sdex_order(env: &Env, iceberg_id: u64, sdex_order_id: u64) {
         &IcebergStorageKey::CurrentSdexOrder(iceberg_id),
         &sdex_order_id,
     );
+    env.storage()
+        .persistent()
+        .remove(&IcebergStorageKey::SliceProceeds(iceberg_id));
 }
 
-fn get_current_sdex_order(env: &Env, iceberg_id: u64) -> Result<u64, String> {
+fn get_current_sdex_order(env: &Env, iceberg_id: u64) -> Result<u64, AutoTradeError> {
     env.storage()
         .persistent()
         .get(&IcebergStorageKey::CurrentSdexOrder(iceberg_id))
-        .ok_or_else(|| String::from_str(env, "No current SDEX order"))
+        .ok_or(AutoTradeError::IcebergOrderNotFound)
 }
 
 fn record_fill(env: &Env, order_id: u64, filled_amount: i128, price: i128) {
@@ -545,28 +668,75 @@ fn record_fill(env: &Env, order_id: u64, filled_amount: i128, price: i128) {
 }
 
 /// ==========================
-/// SDEX Integration (Placeholder)
+/// SDEX Integration
 /// ==========================
 
-/// Place limit order on SDEX
+/// The enabled `SdexRouter` source with the best (lowest) priority.
+fn sdex_router(env: &Env) -> Result<Address, AutoTradeError> {
+    let mut best: Option<AmmSourceConfig> = None;
+    for src in amm_bridge::get_amm_sources(env).iter() {
+        if src.kind != AmmSourceKind::SdexRouter || !src.enabled {
+            continue;
+        }
+        if best.as_ref().map_or(true, |b| src.priority < b.priority) {
+            best = Some(src);
+        }
+    }
+    best.map(|src| src.router)
+        .ok_or(AutoTradeError::RoutingPlanNotFound)
+}
+
+/// Place limit order on SDEX, approving the router for exactly `amount`
 fn place_sdex_limit_order(
     env: &Env,
-    _user: &Address,
-    _pair: &AssetPair,
-    _side: OrderSide,
+    iceberg: &IcebergOrder,
     amount: i128,
     price: i128,
-) -> Result<u64, String> {
-    // Placeholder - would integrate with actual SDEX
-    // Returns mock SDEX order ID
-    let sdex_order_id = env.ledger().timestamp() + amount.max(0) as u64 + price.max(0) as u64;
-    Ok(sdex_order_id)
+) -> Result<u64, AutoTradeError> {
+    let this = env.current_contract_address();
+    let expiry = env
+        .ledger()
+        .sequence()
+        .saturating_add(SLICE_ALLOWANCE_LEDGERS);
+    token::Client::new(env, &iceberg.selling).approve(&this, &iceberg.router, &amount, &expiry);
+    env.try_invoke_contract::<u64, soroban_sdk::Error>(
+        &iceberg.router,
+        &Symbol::new(env, FN_PLACE_OFFER),
+        (
+            this,
+            iceberg.selling.clone(),
+            iceberg.buying.clone(),
+            amount,
+            price,
+        )
+            .into_val(env),
+    )
+    .map_err(|_| AutoTradeError::AtomicExecutionFailed)?
+    .map_err(|_| AutoTradeError::AtomicExecutionFailed)
 }
 
-/// Cancel SDEX order
-fn cancel_sdex_order(env: &Env, _sdex_order_id: u64) -> Result<(), String> {
-    // Placeholder - would integrate with actual SDEX
-    Ok(())
+/// Cancel SDEX order, returning the unfilled amount released
+fn cancel_sdex_order(
+    env: &Env,
+    iceberg: &IcebergOrder,
+    sdex_order_id: u64,
+) -> Result<i128, AutoTradeError> {
+    env.try_invoke_contract::<i128, soroban_sdk::Error>(
+        &iceberg.router,
+        &Symbol::new(env, FN_CANCEL_OFFER),
+        (env.current_contract_address(), sdex_order_id).into_val(env),
+    )
+    .map_err(|_| AutoTradeError::AtomicExecutionFailed)?
+    .map_err(|_| AutoTradeError::AtomicExecutionFailed)
+}
+
+/// Amount still on the book and `buying` paid out for an offer
+fn get_offer(env: &Env, router: &Address, sdex_order_id: u64) -> (i128, i128) {
+    env.invoke_contract::<(i128, i128)>(
+        router,
+        &Symbol::new(env, FN_GET_OFFER),
+        (sdex_order_id,).into_val(env),
+    )
 }
 
 /// ==========================
@@ -576,387 +746,533 @@ fn cancel_sdex_order(env: &Env, _sdex_order_id: u64) -> Result<(), String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use soroban_sdk::{
-        testutils::{Address as _, Ledger as _},
-        Env,
-    };
-
-    fn setup_env() -> Env {
-        let env = Env::default();
-        env.ledger().set_timestamp(1000);
-        env
+    use crate::{AutoTradeContract, AutoTradeContractClient};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::token::StellarAssetClient;
+    use soroban_sdk::{contract, contractimpl, contracttype, symbol_short};
+
+    #[contracttype]
+    #[derive(Clone)]
+    struct MockOffer {
+        owner: Address,
+        selling: Address,
+        buying: Address,
+        remaining: i128,
+        price: i128,
+        paid_out: i128,
     }
 
-    fn create_test_pair(env: &Env) -> AssetPair {
-        AssetPair {
-            base: String::from_str(env, "XLM"),
-            quote: String::from_str(env, "USDC"),
+    /// Offer book behind the `SdexRouter` source. `take` simulates a
+    /// counterparty crossing an offer and paying `proceeds` to its owner.
+    #[contract]
+    struct MockOfferRouter;
+
+    #[contractimpl]
+    impl MockOfferRouter {
+        pub fn place_offer(
+            env: Env,
+            owner: Address,
+            selling: Address,
+            buying: Address,
+            amount: i128,
+            price: i128,
+        ) -> u64 {
+            if env.storage().instance().has(&symbol_short!("down")) {
+                panic!("router down");
+            }
+            let router = env.current_contract_address();
+            token::Client::new(&env, &selling).transfer_from(&router, &owner, &router, &amount);
+            let id: u64 = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("next"))
+                .unwrap_or(1);
+            env.storage()
+                .instance()
+                .set(&symbol_short!("next"), &(id + 1));
+            env.storage().instance().set(
+                &id,
+                &MockOffer {
+                    owner,
+                    selling,
+                    buying,
+                    remaining: amount,
+                    price,
+                    paid_out: 0,
+                },
+            );
+            id
         }
-    }
 
-    #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_create_iceberg_order() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
+        pub fn get_offer(env: Env, offer_id: u64) -> (i128, i128) {
+            let offer = Self::offer(&env, offer_id);
+            (offer.remaining, offer.paid_out)
+        }
 
-        env.mock_all_auths();
+        pub fn get_offer_price(env: Env, offer_id: u64) -> i128 {
+            Self::offer(&env, offer_id).price
+        }
 
-        let result = create_iceberg_order(
-            &env,
-            user.clone(),
-            pair,
-            OrderSide::Buy,
-            10_000_000, // 10 XLM total
-            1000,       // 10% visible
-            1_000_000,  // price
-        );
+        pub fn cancel_offer(env: Env, owner: Address, offer_id: u64) -> i128 {
+            let mut offer = Self::offer(&env, offer_id);
+            assert!(offer.owner == owner);
+            let unfilled = offer.remaining;
+            if unfilled > 0 {
+                token::Client::new(&env, &offer.selling).transfer(
+                    &env.current_contract_address(),
+                    &owner,
+                    &unfilled,
+                );
+            }
+            offer.remaining = 0;
+            env.storage().instance().set(&offer_id, &offer);
+            unfilled
+        }
 
-        assert!(result.is_ok());
-        let order_id = result.unwrap();
-        assert_eq!(order_id, 1);
+        pub fn take(env: Env, offer_id: u64, amount: i128, proceeds: i128) {
+            let mut offer = Self::offer(&env, offer_id);
+            offer.remaining -= amount;
+            offer.paid_out += proceeds;
+            token::Client::new(&env, &offer.buying).transfer(
+                &env.current_contract_address(),
+                &offer.owner,
+                &proceeds,
+            );
+            env.storage().instance().set(&offer_id, &offer);
+        }
 
-        let order = get_iceberg_order(&env, order_id).unwrap();
-        assert_eq!(order.total_amount, 10_000_000);
-        assert_eq!(order.visible_amount, 1_000_000); // 10%
-        assert_eq!(order.filled_amount, 0);
-        assert_eq!(order.status, OrderStatus::Active);
+        pub fn set_down(env: Env, down: bool) {
+            if down {
+                env.storage().instance().set(&symbol_short!("down"), &true);
+            } else {
+                env.storage().instance().remove(&symbol_short!("down"));
+            }
+        }
     }
 
-    #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_create_iceberg_invalid_visible_pct() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
-
-        // Too high percentage (>50%)
-        let result = create_iceberg_order(
-            &env,
-            user.clone(),
-            pair.clone(),
-            OrderSide::Buy,
-            10_000_000,
-            6000, // 60%
-            1_000_000,
-        );
-        assert!(result.is_err());
+    impl MockOfferRouter {
+        fn offer(env: &Env, offer_id: u64) -> MockOffer {
+            env.storage().instance().get(&offer_id).unwrap()
+        }
+    }
 
-        // Zero percentage
-        let result =
-            create_iceberg_order(&env, user, pair, OrderSide::Buy, 10_000_000, 0, 1_000_000);
-        assert!(result.is_err());
+    struct Setup {
+        env: Env,
+        client: AutoTradeContractClient<'static>,
+        router: MockOfferRouterClient<'static>,
+        user: Address,
+        keeper: Address,
+        tokens: TokenPairConfig,
     }
 
-    #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_on_sdex_fill_partial() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
+    const TOTAL: i128 = 100_000;
 
+    fn setup(with_router: bool) -> Setup {
+        let env = Env::default();
         env.mock_all_auths();
+        let contract = env.register(AutoTradeContract, ());
+        let client = AutoTradeContractClient::new(&env, &contract);
+        let router_id = env.register(MockOfferRouter, ());
+        let router = MockOfferRouterClient::new(&env, &router_id);
+
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        let keeper = Address::generate(&env);
+        client.add_keeper(&admin, &keeper);
+        if with_router {
+            client.register_amm_source(
+                &admin,
+                &AmmSourceConfig {
+                    kind: AmmSourceKind::SdexRouter,
+                    source_id: 1,
+                    router: router_id.clone(),
+                    priority: 0,
+                    enabled: true,
+                },
+            );
+        }
 
-        let order_id = create_iceberg_order(
-            &env,
+        let user = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let selling = env
+            .register_stellar_asset_contract_v2(issuer.clone())
+            .address();
+        let buying = env.register_stellar_asset_contract_v2(issuer).address();
+        StellarAssetClient::new(&env, &selling).mint(&user, &TOTAL);
+        // Counterparty proceeds the router pays out on `take`.
+        StellarAssetClient::new(&env, &buying).mint(&router_id, &(TOTAL * 1_000));
+
+        Setup {
+            env,
+            client,
+            router,
             user,
-            pair,
-            OrderSide::Buy,
-            10_000_000,
-            1000, // 10% = 1M visible
-            1_000_000,
-        )
-        .unwrap();
-
-        let sdex_order_id = get_current_sdex_order(&env, order_id).unwrap();
-
-        // Fill 500k (half of visible)
-        let result = on_sdex_fill(&env, sdex_order_id, 500_000, 1_000_000);
-        assert!(result.is_ok());
-
-        let order = get_iceberg_order(&env, order_id).unwrap();
-        assert_eq!(order.filled_amount, 500_000);
-        assert_eq!(order.current_visible_filled, 500_000);
-        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+            keeper,
+            tokens: TokenPairConfig {
+                from_token: selling,
+                to_token: buying,
+            },
+        }
     }
 
-    #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_on_sdex_fill_replenishment() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
+    fn pair(env: &Env) -> AssetPair {
+        AssetPair {
+            base: String::from_str(env, "XLM"),
+            quote: String::from_str(env, "USDC"),
+        }
+    }
 
-        let order_id = create_iceberg_order(
-            &env,
-            user,
-            pair,
-            OrderSide::Buy,
-            10_000_000,
-            1000, // 10% = 1M visible
-            1_000_000,
+    fn create(s: &Setup, jitter_bps: u32, commitment: Option<BytesN<32>>) -> u64 {
+        s.client.create_iceberg_order(
+            &s.user,
+            &pair(&s.env),
+            &OrderSide::Sell,
+            &s.tokens,
+            &TOTAL,
+            &1000,
+            &100,
+            &jitter_bps,
+            &commitment,
         )
-        .unwrap();
-
-        let sdex_order_id = get_current_sdex_order(&env, order_id).unwrap();
-
-        // Fill entire visible portion (1M)
-        let result = on_sdex_fill(&env, sdex_order_id, 1_000_000, 1_000_000);
-        assert!(result.is_ok());
-
-        let order = get_iceberg_order(&env, order_id).unwrap();
-        assert_eq!(order.filled_amount, 1_000_000);
-        assert_eq!(order.current_visible_filled, 0); // Reset after replenishment
-        assert_eq!(order.status, OrderStatus::PartiallyFilled);
-
-        // Verify new SDEX order was created
-        let new_sdex_order = get_current_sdex_order(&env, order_id);
-        assert!(new_sdex_order.is_ok());
     }
 
-    #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_on_sdex_fill_complete() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
-
-        let order_id = create_iceberg_order(
-            &env,
-            user,
-            pair,
-            OrderSide::Buy,
-            1_000_000, // Small order
-            5000,      // 50% visible
-            1_000_000,
-        )
-        .unwrap();
+    fn refresh(s: &Setup, order_id: u64) -> i128 {
+        s.client.refresh_iceberg_order(&s.keeper, &order_id, &None)
+    }
 
-        let sdex_order_id = get_current_sdex_order(&env, order_id).unwrap();
+    fn balance(s: &Setup, who: &Address) -> i128 {
+        token::Client::new(&s.env, &s.tokens.from_token).balance(who)
+    }
 
-        // Fill first half
-        on_sdex_fill(&env, sdex_order_id, 500_000, 1_000_000).unwrap();
+    fn proceeds(s: &Setup, who: &Address) -> i128 {
+        token::Client::new(&s.env, &s.tokens.to_token).balance(who)
+    }
 
-        // Get new SDEX order after replenishment
-        let sdex_order_id2 = get_current_sdex_order(&env, order_id).unwrap();
+    fn remaining(s: &Setup, offer: u64) -> i128 {
+        s.router.get_offer(&offer).0
+    }
 
-        // Fill second half
-        on_sdex_fill(&env, sdex_order_id2, 500_000, 1_000_000).unwrap();
+    fn current_offer(s: &Setup, order_id: u64) -> u64 {
+        s.env.as_contract(&s.client.address, || {
+            get_current_sdex_order(&s.env, order_id).unwrap()
+        })
+    }
 
-        let order = get_iceberg_order(&env, order_id).unwrap();
-        assert_eq!(order.filled_amount, 1_000_000);
-        assert_eq!(order.status, OrderStatus::Filled);
+    /// Hash chain `[seed, H(seed), H(H(seed)), ...]` of `len` values.
+    fn hash_chain(env: &Env, len: u32) -> soroban_sdk::Vec<BytesN<32>> {
+        let mut chain = soroban_sdk::Vec::new(env);
+        let mut value = BytesN::from_array(env, &[7u8; 32]);
+        for _ in 0..len {
+            chain.push_back(value.clone());
+            value = env
+                .crypto()
+                .sha256(&Bytes::from_array(env, &value.to_array()))
+                .into();
+        }
+        chain
     }
 
     #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_cancel_iceberg_order() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
-
-        let order_id = create_iceberg_order(
-            &env,
-            user.clone(),
-            pair,
-            OrderSide::Buy,
-            10_000_000,
-            1000,
-            1_000_000,
-        )
-        .unwrap();
-
-        let result = cancel_iceberg_order(&env, order_id, user);
-        assert!(result.is_ok());
-
-        let info = result.unwrap();
-        assert_eq!(info.filled_amount, 0);
-        assert_eq!(info.remaining_amount, 10_000_000);
-
-        let order = get_iceberg_order(&env, order_id).unwrap();
-        assert_eq!(order.status, OrderStatus::Cancelled);
+    fn test_create_posts_first_slice_from_custody() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+
+        let offer = current_offer(&s, order_id);
+        assert_eq!(remaining(&s, offer), 10_000);
+        assert_eq!(balance(&s, &s.router.address), 10_000);
+        assert_eq!(balance(&s, &s.user), 0);
+        assert_eq!(balance(&s, &s.client.address), TOTAL - 10_000);
+        // The router was approved for the slice alone, and used all of it.
+        let allowance = token::Client::new(&s.env, &s.tokens.from_token)
+            .allowance(&s.client.address, &s.router.address);
+        assert_eq!(allowance, 0);
+
+        let public = s.client.get_public_order_view(&order_id);
+        assert_eq!(public.visible_amount, 10_000);
+        let full = s.client.get_full_order_view(&order_id, &s.user);
+        assert_eq!(full.total_amount, TOTAL);
+        assert_eq!(full.status, OrderStatus::Active);
     }
 
     #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_cancel_unauthorized() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let other_user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
-
-        let order_id = create_iceberg_order(
-            &env,
-            user,
-            pair,
-            OrderSide::Buy,
-            10_000_000,
-            1000,
-            1_000_000,
-        )
-        .unwrap();
-
-        let result = cancel_iceberg_order(&env, order_id, other_user);
-        assert!(result.is_err());
+    fn test_create_without_sdex_router_fails() {
+        let s = setup(false);
+        let result = s.client.try_create_iceberg_order(
+            &s.user,
+            &pair(&s.env),
+            &OrderSide::Sell,
+            &s.tokens,
+            &TOTAL,
+            &1000,
+            &100,
+            &0,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(AutoTradeError::RoutingPlanNotFound)));
     }
 
     #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_get_public_vs_full_view() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
-
-        let order_id = create_iceberg_order(
-            &env,
-            user.clone(),
-            pair,
-            OrderSide::Buy,
-            10_000_000,
-            1000,
-            1_000_000,
-        )
-        .unwrap();
-
-        // Public view should only show visible amount
-        let public_view = get_public_order_view(&env, order_id).unwrap();
-        assert_eq!(public_view.visible_amount, 1_000_000);
-
-        // Full view should show total amount (owner only)
-        let full_view = get_full_order_view(&env, order_id, user).unwrap();
-        assert_eq!(full_view.total_amount, 10_000_000);
-        assert_eq!(full_view.visible_amount, 1_000_000);
-        assert_eq!(full_view.remaining_amount, 10_000_000);
+    fn test_source_registration_is_admin_only() {
+        let s = setup(false);
+        let result = s.client.try_register_amm_source(
+            &s.user,
+            &AmmSourceConfig {
+                kind: AmmSourceKind::SdexRouter,
+                source_id: 1,
+                router: s.router.address.clone(),
+                priority: 0,
+                enabled: true,
+            },
+        );
+        assert_eq!(result, Err(Ok(AutoTradeError::Unauthorized)));
     }
 
     #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_update_price() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
+    fn test_create_validates_config() {
+        let s = setup(true);
+        let bad = |visible_pct: u32, jitter: u32| {
+            s.client.try_create_iceberg_order(
+                &s.user,
+                &pair(&s.env),
+                &OrderSide::Sell,
+                &s.tokens,
+                &TOTAL,
+                &visible_pct,
+                &100,
+                &jitter,
+                &Some(BytesN::from_array(&s.env, &[1u8; 32])),
+            )
+        };
+        assert_eq!(bad(0, 0), Err(Ok(AutoTradeError::InvalidIcebergConfig)));
+        assert_eq!(bad(6000, 0), Err(Ok(AutoTradeError::InvalidIcebergConfig)));
+        assert_eq!(
+            bad(1000, 6000),
+            Err(Ok(AutoTradeError::InvalidIcebergConfig))
+        );
+        // 0.5% of the total is below the minimum visible amount.
+        assert_eq!(bad(50, 0), Err(Ok(AutoTradeError::InvalidIcebergConfig)));
+        // Jitter needs a commitment to draw on.
+        assert_eq!(
+            s.client.try_create_iceberg_order(
+                &s.user,
+                &pair(&s.env),
+                &OrderSide::Sell,
+                &s.tokens,
+                &TOTAL,
+                &1000,
+                &100,
+                &2_000,
+                &None,
+            ),
+            Err(Ok(AutoTradeError::InvalidIcebergConfig))
+        );
+    }
 
-        env.mock_all_auths();
+    #[test]
+    fn test_keeper_records_fills_at_proceeds_price_then_refills() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+        let first = current_offer(&s, order_id);
+
+        s.router.take(&first, &4_000, &400_000);
+        assert_eq!(refresh(&s, order_id), 4_000);
+        assert_eq!(proceeds(&s, &s.user), 400_000);
+        // Nothing new on a second pass.
+        assert_eq!(refresh(&s, order_id), 0);
+        assert_eq!(current_offer(&s, order_id), first);
+        assert_eq!(
+            s.client.get_public_order_view(&order_id).visible_amount,
+            6_000
+        );
 
-        let order_id = create_iceberg_order(
-            &env,
-            user.clone(),
-            pair,
-            OrderSide::Buy,
-            10_000_000,
-            1000,
-            1_000_000,
-        )
-        .unwrap();
+        // Crossed above the limit.
+        s.router.take(&first, &6_000, &612_000);
+        assert_eq!(refresh(&s, order_id), 6_000);
+        assert_eq!(proceeds(&s, &s.user), 1_012_000);
+        let second = current_offer(&s, order_id);
+        assert_ne!(second, first);
+        assert_eq!(remaining(&s, second), 10_000);
 
-        let result = update_iceberg_price(&env, order_id, user, 1_100_000);
-        assert!(result.is_ok());
+        let full = s.client.get_full_order_view(&order_id, &s.user);
+        assert_eq!(full.filled_amount, 10_000);
+        assert_eq!(full.status, OrderStatus::PartiallyFilled);
+        assert_eq!(full.avg_fill_price, 101);
 
-        let order = get_iceberg_order(&env, order_id).unwrap();
-        assert_eq!(order.price, 1_100_000);
+        let history = s.client.get_iceberg_fill_history(&order_id);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.get(0).unwrap().price, 100);
+        assert_eq!(history.get(1).unwrap().price, 102);
+        assert_eq!(history.get(1).unwrap().total_filled, 10_000);
     }
 
     #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_average_fill_price() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
-
-        let order_id = create_iceberg_order(
-            &env,
-            user,
-            pair,
-            OrderSide::Buy,
-            2_000_000,
-            5000, // 50%
-            1_000_000,
-        )
-        .unwrap();
-
-        let sdex_order_id = get_current_sdex_order(&env, order_id).unwrap();
+    fn test_only_registered_keepers_refresh() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+        let outsider = Address::generate(&s.env);
+        assert_eq!(
+            s.client
+                .try_refresh_iceberg_order(&outsider, &order_id, &None),
+            Err(Ok(AutoTradeError::Unauthorized))
+        );
+    }
 
-        // Fill at different prices
-        on_sdex_fill(&env, sdex_order_id, 500_000, 1_000_000).unwrap();
-        on_sdex_fill(&env, sdex_order_id, 500_000, 1_100_000).unwrap();
+    #[test]
+    fn test_order_completes_after_last_slice() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+
+        for _ in 0..10 {
+            let offer = current_offer(&s, order_id);
+            let size = remaining(&s, offer);
+            s.router.take(&offer, &size, &(size * 100));
+            refresh(&s, order_id);
+        }
 
-        let order = get_iceberg_order(&env, order_id).unwrap();
-        // Average should be (500k * 1M + 500k * 1.1M) / 1M = 1.05M
-        assert_eq!(order.avg_fill_price, 1_050_000);
+        let full = s.client.get_full_order_view(&order_id, &s.user);
+        assert_eq!(full.filled_amount, TOTAL);
+        assert_eq!(full.remaining_amount, 0);
+        assert_eq!(full.status, OrderStatus::Filled);
+        assert_eq!(proceeds(&s, &s.user), TOTAL * 100);
+        assert_eq!(
+            s.client
+                .try_refresh_iceberg_order(&s.keeper, &order_id, &None),
+            Err(Ok(AutoTradeError::IcebergNotActive))
+        );
     }
 
     #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_get_user_orders() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
+    fn test_randomized_slices_follow_revealed_hash_chain() {
+        let s = setup(true);
+        let chain = hash_chain(&s.env, 20);
+        let mut tip = chain.len() - 1;
+        let order_id = create(&s, 2_000, chain.get(tip));
+
+        let mut total = 0;
+        let mut sizes = soroban_sdk::Vec::new(&s.env);
+        while s.client.get_full_order_view(&order_id, &s.user).status != OrderStatus::Filled {
+            let offer = current_offer(&s, order_id);
+            let slice = remaining(&s, offer);
+            let left = s
+                .client
+                .get_full_order_view(&order_id, &s.user)
+                .remaining_amount;
+            assert!(slice == left || (8_000..=12_000).contains(&slice));
+            sizes.push_back(slice);
+            total += slice;
+            s.router.take(&offer, &slice, &(slice * 100));
+            if slice == left {
+                refresh(&s, order_id);
+                break;
+            }
 
-        // Create multiple orders
-        for _ in 0..3 {
-            create_iceberg_order(
-                &env,
-                user.clone(),
-                pair.clone(),
-                OrderSide::Buy,
-                10_000_000,
-                1000,
-                1_000_000,
-            )
-            .unwrap();
+            // A refill needs the next preimage down the chain.
+            assert_eq!(
+                s.client
+                    .try_refresh_iceberg_order(&s.keeper, &order_id, &None),
+                Err(Ok(AutoTradeError::InvalidJitterReveal))
+            );
+            assert_eq!(
+                s.client
+                    .try_refresh_iceberg_order(&s.keeper, &order_id, &chain.get(tip)),
+                Err(Ok(AutoTradeError::InvalidJitterReveal))
+            );
+            tip -= 1;
+            s.client
+                .refresh_iceberg_order(&s.keeper, &order_id, &chain.get(tip));
         }
-
-        let orders = get_user_orders(&env, &user, 10);
-        assert_eq!(orders.len(), 3);
+        assert_eq!(total, TOTAL);
+        // Sizes vary from slice to slice.
+        assert!(sizes.iter().any(|size| size != sizes.get(0).unwrap()));
     }
 
     #[test]
-    #[ignore = "pre-existing iceberg contract-context test issues"]
-    fn test_fill_history() {
-        let env = setup_env();
-        let user = Address::generate(&env);
-        let pair = create_test_pair(&env);
-
-        env.mock_all_auths();
+    fn test_update_price_reposts_unfilled_slice() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+        let first = current_offer(&s, order_id);
+        s.router.take(&first, &3_000, &300_000);
+
+        s.client.update_iceberg_price(&order_id, &s.user, &105);
+
+        let second = current_offer(&s, order_id);
+        assert_ne!(second, first);
+        assert_eq!(remaining(&s, first), 0);
+        assert_eq!(remaining(&s, second), 7_000);
+        assert_eq!(s.router.get_offer_price(&second), 105);
+
+        let full = s.client.get_full_order_view(&order_id, &s.user);
+        assert_eq!(full.price, 105);
+        // The fill before the re-post was settled at its own price.
+        assert_eq!(full.filled_amount, 3_000);
+        assert_eq!(full.avg_fill_price, 100);
+        assert_eq!(proceeds(&s, &s.user), 300_000);
+    }
 
-        let order_id = create_iceberg_order(
-            &env,
-            user,
-            pair,
-            OrderSide::Buy,
-            10_000_000,
-            1000,
-            1_000_000,
-        )
-        .unwrap();
+    #[test]
+    fn test_update_price_is_atomic_when_repost_fails() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+        let first = current_offer(&s, order_id);
+
+        s.router.set_down(&true);
+        assert_eq!(
+            s.client.try_update_iceberg_price(&order_id, &s.user, &105),
+            Err(Ok(AutoTradeError::AtomicExecutionFailed))
+        );
 
-        let sdex_order_id = get_current_sdex_order(&env, order_id).unwrap();
+        // The old offer is still live at the old price.
+        assert_eq!(current_offer(&s, order_id), first);
+        assert_eq!(remaining(&s, first), 10_000);
+        assert_eq!(s.client.get_full_order_view(&order_id, &s.user).price, 100);
+    }
 
-        // Multiple fills
-        on_sdex_fill(&env, sdex_order_id, 300_000, 1_000_000).unwrap();
-        on_sdex_fill(&env, sdex_order_id, 400_000, 1_050_000).unwrap();
+    #[test]
+    fn test_cancel_pulls_offer_and_returns_custody() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+        let offer = current_offer(&s, order_id);
+        s.router.take(&offer, &2_500, &250_000);
+
+        let info = s.client.cancel_iceberg_order(&order_id, &s.user);
+        assert_eq!(info.filled_amount, 2_500);
+        assert_eq!(info.remaining_amount, TOTAL - 2_500);
+
+        assert_eq!(remaining(&s, offer), 0);
+        assert_eq!(balance(&s, &s.user), TOTAL - 2_500);
+        assert_eq!(balance(&s, &s.client.address), 0);
+        assert_eq!(proceeds(&s, &s.user), 250_000);
+        let allowance = token::Client::new(&s.env, &s.tokens.from_token)
+            .allowance(&s.client.address, &s.router.address);
+        assert_eq!(allowance, 0);
+        assert_eq!(
+            s.client.get_full_order_view(&order_id, &s.user).status,
+            OrderStatus::Cancelled
+        );
+        assert!(s.client.get_user_iceberg_orders(&s.user, &10).is_empty());
+    }
 
-        let history = get_fill_history(&env, order_id);
-        assert_eq!(history.len(), 2);
-        assert_eq!(history.get(0).unwrap().filled_amount, 300_000);
-        assert_eq!(history.get(1).unwrap().filled_amount, 400_000);
-        assert_eq!(history.get(1).unwrap().total_filled, 700_000);
+    #[test]
+    fn test_owner_only_management() {
+        let s = setup(true);
+        let order_id = create(&s, 0, None);
+        let other = Address::generate(&s.env);
+
+        assert_eq!(
+            s.client.try_cancel_iceberg_order(&order_id, &other).err(),
+            Some(Ok(AutoTradeError::NotIcebergOwner))
+        );
+        assert_eq!(
+            s.client.try_update_iceberg_price(&order_id, &other, &110),
+            Err(Ok(AutoTradeError::NotIcebergOwner))
+        );
+        assert_eq!(
+            s.client.try_get_full_order_view(&order_id, &other).err(),
+            Some(Ok(AutoTradeError::NotIcebergOwner))
+        );
+        assert_eq!(
+            s.client.try_refresh_iceberg_order(&s.keeper, &99, &None),
+            Err(Ok(AutoTradeError::IcebergOrderNotFound))
+        );
     }
 }
@@ -1,126 +1,193 @@
 #![allow(dead_code)]
 
+//! KYC checks for AutoTrade, delegated to the shared compliance registry.
+//!
+//! AutoTrade keeps no KYC state of its own: issuers attest users on the
+//! registry, and every trade asks it whether the user may trade the signal's
+//! asset pair. With no registry configured trades are not gated.
+
 use crate::admin::require_admin;
 use crate::errors::AutoTradeError;
-use shared::events::{emit_kyc_status_updated, EvtKycStatusUpdated, SCHEMA_VERSION};
 use soroban_sdk::{contracttype, Address, Env, String, Symbol};
+use stellar_swipe_common::compliance::{self, Attestation, ComplianceLevel};
 
 #[contracttype]
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum KYCLevel {
-    None,
-    Basic,
-    Enhanced,
-    Full,
+pub enum KYCStorageKey {
+    Registry,
 }
 
-#[contracttype]
-#[derive(Clone, Debug)]
-pub struct KYCData {
-    pub kyc_id: String,
-    pub level: KYCLevel,
-    pub verified: bool,
-    pub submitted_at: u64,
-    pub verified_at: u64,
+pub fn set_compliance_registry(
+    env: &Env,
+    caller: &Address,
+    registry: Option<Address>,
+) -> Result<(), AutoTradeError> {
+    require_admin(env, caller)?;
+    match &registry {
+        Some(registry) => env
+            .storage()
+            .instance()
+            .set(&KYCStorageKey::Registry, registry),
+        None => env.storage().instance().remove(&KYCStorageKey::Registry),
+    }
+    env.events()
+        .publish((Symbol::new(env, "compliance_registry_set"),), registry);
+    Ok(())
 }
 
-#[contracttype]
-pub enum KYCStorageKey {
-    Data(Address),
+pub fn get_compliance_registry(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&KYCStorageKey::Registry)
 }
 
-pub fn submit_kyc_verification(
+/// Fails with `NotCompliant` unless the registry accepts `user` at `Basic`
+/// level for `asset_pair`.
+pub fn require_compliant(
     env: &Env,
     user: &Address,
-    kyc_id: String,
-    level: KYCLevel,
+    asset_pair: Option<u32>,
 ) -> Result<(), AutoTradeError> {
-    user.require_auth();
-    let now = env.ledger().timestamp();
-    let data = KYCData {
-        kyc_id,
-        level,
-        verified: false,
-        submitted_at: now,
-        verified_at: 0,
+    let Some(registry) = get_compliance_registry(env) else {
+        return Ok(());
     };
-    env.storage()
-        .persistent()
-        .set(&KYCStorageKey::Data(user.clone()), &data);
-
-    env.events().publish(
-        (Symbol::new(env, "kyc_submission"), user.clone()),
-        (data.kyc_id.clone(), data.level.clone(), data.submitted_at),
-    );
-    Ok(())
+    compliance::require_compliant(env, &registry, user, ComplianceLevel::Basic, asset_pair)
+        .map(|_| ())
+        .map_err(|_| AutoTradeError::NotCompliant)
 }
 
-pub fn verify_kyc(
-    env: &Env,
-    caller: &Address,
-    user: &Address,
-    verified: bool,
-) -> Result<(), AutoTradeError> {
-    require_admin(env, caller)?;
+pub fn get_attestation(env: &Env, user: &Address) -> Option<Attestation> {
+    compliance::get_attestation(env, &get_compliance_registry(env)?, user)
+}
 
-    let mut data: KYCData = env
-        .storage()
-        .persistent()
-        .get(&KYCStorageKey::Data(user.clone()))
-        .unwrap_or(KYCData {
-            kyc_id: String::from_str(env, "unknown"),
-            level: KYCLevel::None,
-            verified: false,
-            submitted_at: 0,
-            verified_at: 0,
-        });
+pub fn is_kyc_verified(env: &Env, user: &Address) -> bool {
+    get_compliance_registry(env).is_some() && require_compliant(env, user, None).is_ok()
+}
 
-    data.verified = verified;
-    data.verified_at = env.ledger().timestamp();
+pub fn get_user_tier(env: &Env, user: &Address) -> String {
+    let Some(registry) = get_compliance_registry(env) else {
+        return String::from_str(env, "None");
+    };
+    if compliance::get_attestation(env, &registry, user).is_none() {
+        return String::from_str(env, "None");
+    }
+    match compliance::require_compliant(env, &registry, user, ComplianceLevel::None, None) {
+        Ok(ComplianceLevel::Full) => String::from_str(env, "Platinum"),
+        Ok(ComplianceLevel::Enhanced) => String::from_str(env, "Gold"),
+        Ok(ComplianceLevel::Basic) => String::from_str(env, "Silver"),
+        Ok(ComplianceLevel::None) => String::from_str(env, "Bronze"),
+        Err(_) => String::from_str(env, "OnboardingPending"),
+    }
+}
 
-    env.storage()
-        .persistent()
-        .set(&KYCStorageKey::Data(user.clone()), &data);
+#[cfg(test)]
+mod tests {
+    use crate::{storage, AutoTradeContract, AutoTradeContractClient, OrderType};
+    use ::compliance::{
+        ComplianceRegistry, ComplianceRegistryClient, CustomerStatus, JurisdictionRule,
+    };
+    use soroban_sdk::{
+        symbol_short,
+        testutils::{Address as _, Ledger as _},
+        vec, Address, Env, String, Vec,
+    };
+    use stellar_swipe_common::compliance::ComplianceLevel;
 
-    emit_kyc_status_updated(
-        env,
-        EvtKycStatusUpdated {
-            schema_version: SCHEMA_VERSION,
-            user: user.clone(),
-            verified,
-        },
-    );
+    use crate::errors::AutoTradeError;
 
-    env.events()
-        .publish((Symbol::new(env, "kyc_verified"), user.clone()), verified);
-    Ok(())
-}
+    const UNIT: i128 = 10_000_000;
 
-pub fn get_kyc_data(env: &Env, user: &Address) -> Option<KYCData> {
-    env.storage()
-        .persistent()
-        .get(&KYCStorageKey::Data(user.clone()))
-}
+    #[test]
+    fn test_trades_gated_by_compliance_registry() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_000);
+        let contract = env.register(AutoTradeContract, ());
+        let client = AutoTradeContractClient::new(&env, &contract);
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        client.initialize(&admin);
 
-pub fn is_kyc_verified(env: &Env, user: &Address) -> bool {
-    get_kyc_data(env, user)
-        .map(|data| data.verified)
-        .unwrap_or(false)
-}
+        let registry = ComplianceRegistryClient::new(&env, &env.register(ComplianceRegistry, ()));
+        let registry_admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        registry.initialize(&registry_admin);
+        registry.add_issuer(&registry_admin, &issuer);
 
-pub fn get_user_tier(env: &Env, user: &Address) -> String {
-    if let Some(data) = get_kyc_data(env, user) {
-        if data.verified {
-            match data.level {
-                KYCLevel::Full => String::from_str(env, "Platinum"),
-                KYCLevel::Enhanced => String::from_str(env, "Gold"),
-                KYCLevel::Basic => String::from_str(env, "Silver"),
-                KYCLevel::None => String::from_str(env, "Bronze"),
-            }
-        } else {
-            String::from_str(env, "OnboardingPending")
-        }
-    } else {
-        String::from_str(env, "None")
+        env.as_contract(&contract, || {
+            storage::set_signal(
+                &env,
+                7,
+                &storage::Signal {
+                    signal_id: 7,
+                    price: 100,
+                    expiry: 1_000_000,
+                    base_asset: 3,
+                },
+            );
+            env.storage()
+                .temporary()
+                .set(&(user.clone(), symbol_short!("balance")), &i128::MAX);
+            let mut risk_config = crate::risk::RiskConfig::default();
+            risk_config.max_position_pct = 100;
+            crate::risk::set_risk_config(&env, &user, &risk_config);
+        });
+        client.grant_authorization(&user, &(1_000 * UNIT), &30);
+
+        let trade = || {
+            env.ledger().set_timestamp(env.ledger().timestamp() + 60);
+            client.try_execute_trade(&user, &7, &OrderType::Limit, &(10 * UNIT))
+        };
+
+        // Not gated until a registry is configured.
+        assert!(trade().is_ok());
+        assert_eq!(client.get_user_tier(&user), String::from_str(&env, "None"));
+
+        client.set_compliance_registry(&admin, &Some(registry.address.clone()));
+        assert_eq!(trade().err(), Some(Ok(AutoTradeError::NotCompliant)));
+        assert!(!client.is_kyc_verified(&user));
+
+        registry.attest(
+            &issuer,
+            &user,
+            &String::from_str(&env, "sep12-customer"),
+            &CustomerStatus::Accepted,
+            &ComplianceLevel::Enhanced,
+            &symbol_short!("US"),
+            &0,
+        );
+        assert!(trade().is_ok());
+        assert!(client.is_kyc_verified(&user));
+        assert_eq!(client.get_user_tier(&user), String::from_str(&env, "Gold"));
+        assert_eq!(
+            client.get_kyc_attestation(&user).unwrap().level,
+            ComplianceLevel::Enhanced
+        );
+
+        // The jurisdiction blocks this signal's asset pair only.
+        registry.set_jurisdiction_rule(
+            &registry_admin,
+            &symbol_short!("US"),
+            &JurisdictionRule {
+                blocked: false,
+                min_level: ComplianceLevel::Basic,
+                blocked_pairs: vec![&env, 3u32],
+            },
+        );
+        assert_eq!(trade().err(), Some(Ok(AutoTradeError::NotCompliant)));
+        assert!(client.is_kyc_verified(&user));
+
+        registry.set_jurisdiction_rule(
+            &registry_admin,
+            &symbol_short!("US"),
+            &JurisdictionRule {
+                blocked: false,
+                min_level: ComplianceLevel::Basic,
+                blocked_pairs: Vec::new(&env),
+            },
+        );
+        registry.revoke_attestation(&issuer, &user);
+        assert_eq!(trade().err(), Some(Ok(AutoTradeError::NotCompliant)));
+        assert_eq!(
+            client.get_user_tier(&user),
+            String::from_str(&env, "OnboardingPending")
+        );
     }
 }
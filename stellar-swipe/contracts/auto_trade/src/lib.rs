@@ -33,7 +33,6 @@ mod rate_limit;
 #[cfg(feature = "testutils")]
 pub mod rate_limit;
 pub mod keeper;
-mod referral;
 mod risk;
 mod risk_parity;
 mod sdex;
@@ -51,8 +50,7 @@ mod twap;
 pub use errors::AutoTradeError;
 pub use risk::RiskConfig;
 
-#[cfg(feature = "testutils")]
-pub use auth::AuthConfig;
+pub use auth::{AuthConfig, PolicyGrant, SessionKey, SpendingPolicy, StrategyKind};
 #[cfg(feature = "testutils")]
 pub use storage::{authorize_user_with_limits, set_signal, Signal};
 
@@ -60,16 +58,17 @@ use crate::storage::DataKey;
 use advanced_risk::AutoSellResult;
 use stellar_swipe_common::emergency::{PauseState, CAT_ALL, CAT_TRADING};
 use stellar_swipe_common::{health_uninitialized, HealthStatus};
+use stellar_swipe_common::compliance::Attestation;
 
 use risk_parity::{AssetRisk, RebalanceTrade};
 
 pub use amm_bridge::TokenPairConfig;
 pub use iceberg::{
     cancel_iceberg_order, create_iceberg_order, get_full_order_view, get_public_order_view,
-    get_user_orders, on_sdex_fill, update_iceberg_price, AssetPair, CancellationInfo,
-    FullOrderView, IcebergOrder, OrderSide, OrderStatus, PublicOrderView,
+    get_user_orders, refresh_iceberg_order, update_iceberg_price, AssetPair, CancellationInfo,
+    FillEvent, FullOrderView, IcebergOrder, OrderSide, OrderStatus, PublicOrderView,
 };
-pub use smart_routing::{LiquidityVenue, RouteSegment, RoutingPlan, VenueLiquidity};
+pub use smart_routing::{LiquidityVenue, VenueLiquidity};
 use stellar_swipe_common::amm_bridge::AmmSourceConfig;
 
 /// ==========================
@@ -110,6 +109,21 @@ pub struct TradeResult {
     pub trade: Trade,
 }
 
+/// Mark-to-oracle loss on a user's recorded copy of a signal.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CopyTradeLoss {
+    pub signal_id: u64,
+    pub provider: Address,
+    /// When the copy trade executed.
+    pub executed_at: u64,
+    /// Token the copy trade was paid in; `loss` is in its units.
+    pub token: Address,
+    pub executed_amount: i128,
+    /// `executed_amount` less its value at the current oracle price; never negative.
+    pub loss: i128,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TradeSimulation {
@@ -164,7 +178,13 @@ impl AutoTradeContract {
             return Self::failed_simulation(&env, "signal_expired");
         }
 
-        if !auth::is_authorized(&env, &user, amount) {
+        let spend = auth::Spend {
+            strategy: auth::StrategyKind::CopyTrade,
+            asset: Some(signal.base_asset),
+            counterparty: storage::get_signal_provider(&env, signal_id),
+            amount,
+        };
+        if auth::select(&env, &user, None, &spend).is_err() {
             return Self::failed_simulation(&env, "unauthorized");
         }
 
@@ -387,6 +407,43 @@ impl AutoTradeContract {
         )
     }
 
+    /// Create a TWAP order whose slices adapt to market data: VWAP slices
+    /// follow a volume profile, participation slices are capped at a share of
+    /// observed venue liquidity, and slices are held while the oracle spot is
+    /// outside a band around the oracle TWAP.
+    pub fn create_adaptive_twap_order(
+        env: Env,
+        user: Address,
+        pair: twap::AssetPair,
+        total_amount: i128,
+        duration_minutes: u32,
+        num_segments: Option<u32>,
+        config: twap::AdaptiveTWAPConfig,
+    ) -> Result<u64, AutoTradeError> {
+        twap::create_adaptive_twap_order(
+            &env,
+            user,
+            pair,
+            total_amount,
+            duration_minutes,
+            num_segments,
+            config,
+        )
+    }
+
+    /// Admin: register the asset id TWAP orders on `pair` are charged
+    /// against in spending policies.
+    pub fn set_twap_pair_asset(
+        env: Env,
+        caller: Address,
+        pair: twap::AssetPair,
+        asset_id: u32,
+    ) -> Result<(), AutoTradeError> {
+        admin::require_admin(&env, &caller)?;
+        twap::set_pair_asset(&env, &pair, asset_id);
+        Ok(())
+    }
+
     /// Execute all due TWAP segments for active running orders.
     pub fn execute_twap_segments(env: Env) -> soroban_sdk::Vec<u64> {
         twap::execute_twap_segments(&env)
@@ -411,11 +468,106 @@ impl AutoTradeContract {
         twap::get_twap_order(&env, order_id)
     }
 
+    /// Adaptive state (mode, arrival price, shortfall) of an order created
+    /// with `create_adaptive_twap_order`; `None` for plain orders.
+    pub fn get_adaptive_twap(env: Env, order_id: u64) -> Option<twap::AdaptiveTWAP> {
+        twap::get_adaptive_twap(&env, order_id)
+    }
+
     /// Retrieve all active TWAP orders.
     pub fn get_active_twap_orders(env: Env) -> soroban_sdk::Vec<twap::TWAPOrder> {
         twap::get_active_twap_orders(&env)
     }
 
+    /// Create an iceberg order. Visible slices are posted as SDEX offers
+    /// through the registered `SdexRouter` source; `slice_jitter_bps`
+    /// randomizes each slice around `visible_pct` of the total, drawing on
+    /// the hash chain ending in `jitter_commitment`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_iceberg_order(
+        env: Env,
+        user: Address,
+        pair: iceberg::AssetPair,
+        side: iceberg::OrderSide,
+        tokens: TokenPairConfig,
+        total_amount: i128,
+        visible_pct: u32,
+        price: i128,
+        slice_jitter_bps: u32,
+        jitter_commitment: Option<soroban_sdk::BytesN<32>>,
+    ) -> Result<u64, AutoTradeError> {
+        iceberg::create_iceberg_order(
+            &env,
+            user,
+            pair,
+            side,
+            tokens,
+            total_amount,
+            visible_pct,
+            price,
+            slice_jitter_bps,
+            jitter_commitment,
+        )
+    }
+
+    /// Keeper: record fills on the live slice and post the next one once it
+    /// has been taken. Returns the newly filled amount. Refilling a jittered
+    /// order reveals the next value of its hash chain.
+    pub fn refresh_iceberg_order(
+        env: Env,
+        keeper: Address,
+        order_id: u64,
+        jitter_reveal: Option<soroban_sdk::BytesN<32>>,
+    ) -> Result<i128, AutoTradeError> {
+        iceberg::refresh_iceberg_order(&env, keeper, order_id, jitter_reveal)
+    }
+
+    /// Move an iceberg order to `new_price`, re-posting its live offer.
+    pub fn update_iceberg_price(
+        env: Env,
+        order_id: u64,
+        user: Address,
+        new_price: i128,
+    ) -> Result<(), AutoTradeError> {
+        iceberg::update_iceberg_price(&env, order_id, user, new_price)
+    }
+
+    /// Cancel an iceberg order, pulling its live offer.
+    pub fn cancel_iceberg_order(
+        env: Env,
+        order_id: u64,
+        user: Address,
+    ) -> Result<iceberg::CancellationInfo, AutoTradeError> {
+        iceberg::cancel_iceberg_order(&env, order_id, user)
+    }
+
+    /// Public view of an iceberg order: only the visible slice is shown.
+    pub fn get_public_order_view(
+        env: Env,
+        order_id: u64,
+    ) -> Result<iceberg::PublicOrderView, AutoTradeError> {
+        iceberg::get_public_order_view(&env, order_id)
+    }
+
+    /// Full view of an iceberg order, for its owner.
+    pub fn get_full_order_view(
+        env: Env,
+        order_id: u64,
+        user: Address,
+    ) -> Result<iceberg::FullOrderView, AutoTradeError> {
+        iceberg::get_full_order_view(&env, order_id, user)
+    }
+
+    /// Active iceberg order ids for `user`.
+    pub fn get_user_iceberg_orders(env: Env, user: Address, limit: u32) -> Vec<u64> {
+        iceberg::get_user_orders(&env, &user, limit)
+    }
+
+    /// Fills recorded against an iceberg order.
+    pub fn get_iceberg_fill_history(env: Env, order_id: u64) -> Vec<iceberg::FillEvent> {
+        iceberg::get_fill_history(&env, order_id)
+    }
+
     /// Set the contract-wide log level for structured log emission.
     pub fn set_log_level(
         env: Env,
@@ -452,29 +604,23 @@ impl AutoTradeContract {
         logging::get_recent_logs(&env)
     }
 
-    /// Submit a KYC verification request for a user.
-    pub fn submit_kyc_verification(
+    /// Admin: point KYC checks at the shared compliance registry, or pass
+    /// `None` to stop gating trades.
+    pub fn set_compliance_registry(
         env: Env,
-        user: Address,
-        kyc_id: String,
-        level: kyc::KYCLevel,
+        caller: Address,
+        registry: Option<Address>,
     ) -> Result<(), AutoTradeError> {
-        kyc::submit_kyc_verification(&env, &user, kyc_id, level)
+        kyc::set_compliance_registry(&env, &caller, registry)
     }
 
-    /// Admin verifies a user's KYC status.
-    pub fn verify_kyc(
-        env: Env,
-        caller: Address,
-        user: Address,
-        verified: bool,
-    ) -> Result<(), AutoTradeError> {
-        kyc::verify_kyc(&env, &caller, &user, verified)
+    pub fn get_compliance_registry(env: Env) -> Option<Address> {
+        kyc::get_compliance_registry(&env)
     }
 
-    /// Get the stored KYC data for a user.
-    pub fn get_kyc_data(env: Env, user: Address) -> Option<kyc::KYCData> {
-        kyc::get_kyc_data(&env, &user)
+    /// The user's attestation as held by the compliance registry.
+    pub fn get_kyc_attestation(env: Env, user: Address) -> Option<Attestation> {
+        kyc::get_attestation(&env, &user)
     }
 
     /// Returns whether a user is currently KYC verified.
@@ -482,7 +628,7 @@ impl AutoTradeContract {
         kyc::is_kyc_verified(&env, &user)
     }
 
-    /// Returns the user tier based on KYC verification level.
+    /// Returns the user tier based on the attested compliance level.
     pub fn get_user_tier(env: Env, user: Address) -> String {
         kyc::get_user_tier(&env, &user)
     }
@@ -517,7 +663,7 @@ impl AutoTradeContract {
     /// - [`AutoTradeError::InvalidAmount`] — amount <= 0.
     /// - [`AutoTradeError::SignalNotFound`] — signal_id does not exist.
     /// - [`AutoTradeError::SignalExpired`] — signal has expired.
-    /// - [`AutoTradeError::Unauthorized`] — user is not authorized to trade.
+    /// - [`AutoTradeError::Unauthorized`] — no spending policy of the user covers the trade.
     /// - [`AutoTradeError::InsufficientBalance`] — user has insufficient balance.
     /// - [`AutoTradeError::PositionLimitExceeded`] — trade would exceed position limit.
     /// - [`AutoTradeError::DailyTradeLimitExceeded`] — daily trade limit reached.
@@ -533,6 +679,30 @@ impl AutoTradeContract {
         signal_id: u64,
         order_type: OrderType,
         amount: i128,
+    ) -> Result<TradeResult, AutoTradeError> {
+        Self::run_trade(env, user, None, signal_id, order_type, amount)
+    }
+
+    /// Execute a copy trade for `user`, signed by one of their session keys.
+    /// The trade is paid for by the key's policy only.
+    pub fn execute_trade_with_session_key(
+        env: Env,
+        session_key: Address,
+        user: Address,
+        signal_id: u64,
+        order_type: OrderType,
+        amount: i128,
+    ) -> Result<TradeResult, AutoTradeError> {
+        Self::run_trade(env, user, Some(session_key), signal_id, order_type, amount)
+    }
+
+    fn run_trade(
+        env: Env,
+        user: Address,
+        session_key: Option<Address>,
+        signal_id: u64,
+        order_type: OrderType,
+        amount: i128,
     ) -> Result<TradeResult, AutoTradeError> {
         if admin::is_paused(&env, String::from_str(&env, CAT_TRADING)) {
             logging::emit_log(
@@ -571,7 +741,13 @@ impl AutoTradeContract {
             return Err(AutoTradeError::InvalidAmount);
         }
 
-        user.require_auth();
+        let session_policy = match &session_key {
+            Some(key) => Some(auth::require_session(&env, key, &user)?),
+            None => {
+                user.require_auth();
+                None
+            }
+        };
 
         // Check if user is rate limited
         if admin::is_rate_limited(&env, &user) {
@@ -584,9 +760,21 @@ impl AutoTradeContract {
             return Err(AutoTradeError::SignalExpired);
         }
 
-        if !auth::is_authorized(&env, &user, amount) {
-            return Err(AutoTradeError::Unauthorized);
-        }
+        kyc::require_compliant(&env, &user, Some(signal.base_asset))?;
+
+        // The policy is chosen for the requested amount but only debited
+        // with what actually fills.
+        let policy = auth::select(
+            &env,
+            &user,
+            session_policy,
+            &auth::Spend {
+                strategy: auth::StrategyKind::CopyTrade,
+                asset: Some(signal.base_asset),
+                counterparty: storage::get_signal_provider(&env, signal_id),
+                amount,
+            },
+        )?;
 
         rate_limit::check_rate_limits(&env, &user, amount)?;
 
@@ -690,14 +878,17 @@ impl AutoTradeContract {
             .set(&DataKey::Trades(user.clone(), signal_id), &trade);
 
         if execution.executed_amount > 0 {
+            auth::debit(
+                &env,
+                policy,
+                auth::StrategyKind::CopyTrade,
+                execution.executed_amount,
+            );
             rate_limit::record_transfer(&env, &user, execution.executed_amount);
 
-            // ── Referral fee split ────────────────────────────────────────────
             // Platform fee = 7% of executed amount (0.7 XLM per 10 XLM trade).
-            // Referral reward = 10% of platform fee → deducted from platform share.
+            // Referral shares are paid by FeeCollector when the fee is collected.
             let platform_fee = execution.executed_amount * 7 / 100;
-            let referral_reward =
-                referral::process_referral_reward(&env, &user, signal.base_asset, platform_fee);
 
             let hist_status = match status {
                 TradeStatus::Filled | TradeStatus::PartiallyFilled => {
@@ -713,7 +904,7 @@ impl AutoTradeContract {
                 signal.base_asset,
                 execution.executed_amount,
                 execution.executed_price,
-                platform_fee - referral_reward,
+                platform_fee,
                 hist_status,
             );
         }
@@ -811,29 +1002,64 @@ impl AutoTradeContract {
             .get(&DataKey::Trades(user, signal_id))
     }
 
-    pub fn upsert_routing_venue(
-        env: Env,
-        signal_id: u64,
-        venue: smart_routing::VenueLiquidity,
-    ) -> Result<(), AutoTradeError> {
-        smart_routing::upsert_venue_liquidity(&env, signal_id, venue)
+    /// Loss on `user`'s recorded copy of `signal_id`, marked at the current
+    /// oracle price. `None` when nothing filled, the provider or the
+    /// signal's token pair is unknown, or no fresh oracle price is
+    /// available. Read by FeeCollector to size and denominate copier
+    /// insurance claims.
+    pub fn get_copy_trade_loss(env: Env, user: Address, signal_id: u64) -> Option<CopyTradeLoss> {
+        let trade: Trade = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Trades(user, signal_id))?;
+        if trade.executed_amount <= 0 || trade.executed_price <= 0 {
+            return None;
+        }
+        let provider = storage::get_signal_provider(&env, signal_id)?;
+        let token = amm_bridge::get_signal_token_pair(&env, signal_id)?.from_token;
+        let signal = storage::get_signal(&env, signal_id)?;
+        let mark = oracle::get_oracle_price(&env, signal.base_asset)
+            .ok()
+            .map(|op| oracle::oracle_price_to_i128(&op))?;
+        let loss = trade
+            .executed_price
+            .saturating_sub(mark)
+            .max(0)
+            .checked_mul(trade.executed_amount)?
+            .checked_div(trade.executed_price)?;
+        Some(CopyTradeLoss {
+            signal_id,
+            provider,
+            executed_at: trade.timestamp,
+            token,
+            executed_amount: trade.executed_amount,
+            loss,
+        })
     }
 
+    /// Live depth snapshot across the venues quoting the signal's pair.
     pub fn get_routing_venues(env: Env, signal_id: u64) -> Vec<smart_routing::VenueLiquidity> {
         smart_routing::get_venue_liquidity(&env, signal_id)
     }
 
+    /// Preview the cross-venue split for `amount` against live depth.
     pub fn preview_smart_route(
         env: Env,
         signal_id: u64,
         amount: i128,
         max_slippage_bps: u32,
-    ) -> Result<smart_routing::RoutingPlan, AutoTradeError> {
+    ) -> Result<stellar_swipe_common::amm_bridge::AmmRoutePlan, AutoTradeError> {
         let signal = storage::get_signal(&env, signal_id).ok_or(AutoTradeError::SignalNotFound)?;
         smart_routing::plan_best_execution(&env, &signal, amount, max_slippage_bps)
     }
 
-    pub fn register_amm_source(env: Env, config: AmmSourceConfig) -> Result<(), AutoTradeError> {
+    /// Add or replace an AMM source (admin only).
+    pub fn register_amm_source(
+        env: Env,
+        caller: Address,
+        config: AmmSourceConfig,
+    ) -> Result<(), AutoTradeError> {
+        admin::require_admin(&env, &caller)?;
         amm_bridge::register_amm_source(&env, config)
     }
 
@@ -841,8 +1067,17 @@ impl AutoTradeContract {
         amm_bridge::get_amm_sources(&env)
     }
 
-    pub fn set_signal_token_pair(env: Env, signal_id: u64, from_token: Address, to_token: Address) {
+    /// Map a signal to the token pair its trades are routed in (admin only).
+    pub fn set_signal_token_pair(
+        env: Env,
+        caller: Address,
+        signal_id: u64,
+        from_token: Address,
+        to_token: Address,
+    ) -> Result<(), AutoTradeError> {
+        admin::require_admin(&env, &caller)?;
         amm_bridge::set_signal_token_pair(&env, signal_id, from_token, to_token);
+        Ok(())
     }
 
     pub fn discover_amm_quotes(
@@ -853,16 +1088,6 @@ impl AutoTradeContract {
         amm_bridge::discover_quotes(&env, signal_id, probe_amount)
     }
 
-    pub fn preview_amm_route(
-        env: Env,
-        signal_id: u64,
-        amount: i128,
-        max_slippage_bps: u32,
-    ) -> Result<stellar_swipe_common::amm_bridge::AmmRoutePlan, AutoTradeError> {
-        let signal = storage::get_signal(&env, signal_id).ok_or(AutoTradeError::SignalNotFound)?;
-        amm_bridge::plan_amm_route(&env, &signal, amount, max_slippage_bps)
-    }
-
     /// Get user's risk configuration
     pub fn get_risk_config(env: Env, user: Address) -> risk::RiskConfig {
         risk::get_risk_config(&env, &user)
@@ -996,7 +1221,8 @@ impl AutoTradeContract {
         advanced_risk::get_trailing_stop_price(&env, &user, asset_id, &config)
     }
 
-    /// Grant authorization to execute trades
+    /// Grant (or replace) the default spending policy: any strategy, asset
+    /// and counterparty, capped at `max_amount` per trade.
     pub fn grant_authorization(
         env: Env,
         user: Address,
@@ -1006,11 +1232,75 @@ impl AutoTradeContract {
         auth::grant_authorization(&env, &user, max_amount, duration_days)
     }
 
-    /// Revoke authorization
+    /// Revoke every spending policy of the user (and with them their
+    /// session keys).
     pub fn revoke_authorization(env: Env, user: Address) -> Result<(), AutoTradeError> {
         auth::revoke_authorization(&env, &user)
     }
 
+    /// Grant a spending policy scoped to strategies, assets and
+    /// counterparties, with per-trade, daily and lifetime caps.
+    pub fn grant_spending_policy(
+        env: Env,
+        owner: Address,
+        grant: auth::PolicyGrant,
+    ) -> Result<u64, AutoTradeError> {
+        auth::grant_policy(&env, &owner, grant)
+    }
+
+    pub fn revoke_spending_policy(
+        env: Env,
+        owner: Address,
+        policy_id: u64,
+    ) -> Result<(), AutoTradeError> {
+        auth::revoke_policy(&env, &owner, policy_id)
+    }
+
+    pub fn get_spending_policy(env: Env, policy_id: u64) -> Option<auth::SpendingPolicy> {
+        auth::get_policy(&env, policy_id)
+    }
+
+    /// Live spending policies of a user, in the order they are tried.
+    pub fn get_user_policies(env: Env, user: Address) -> Vec<auth::SpendingPolicy> {
+        auth::get_user_policies(&env, &user)
+    }
+
+    /// Let a bot key trade for `owner` under one of their policies.
+    pub fn add_session_key(
+        env: Env,
+        owner: Address,
+        session_key: Address,
+        policy_id: u64,
+        duration_days: u32,
+    ) -> Result<auth::SessionKey, AutoTradeError> {
+        auth::add_session_key(&env, &owner, &session_key, policy_id, duration_days)
+    }
+
+    pub fn revoke_session_key(
+        env: Env,
+        owner: Address,
+        session_key: Address,
+    ) -> Result<(), AutoTradeError> {
+        auth::revoke_session_key(&env, &owner, &session_key)
+    }
+
+    pub fn get_session_key(env: Env, session_key: Address) -> Option<auth::SessionKey> {
+        auth::get_session_key(&env, &session_key)
+    }
+
+    /// Record the provider behind a signal (operator only). Copy trades of the
+    /// signal name them as counterparty when matched against policies.
+    pub fn set_signal_provider(
+        env: Env,
+        operator: Address,
+        signal_id: u64,
+        provider: Address,
+    ) -> Result<(), AutoTradeError> {
+        admin::require_operator(&env, &operator)?;
+        storage::set_signal_provider(&env, signal_id, &provider);
+        Ok(())
+    }
+
     /// Initialize rate limit admin
     pub fn init_rate_limit_admin(env: Env, admin: Address) {
         admin.require_auth();
@@ -1069,9 +1359,15 @@ impl AutoTradeContract {
         rate_limit::is_whitelisted(&env, &user)
     }
 
-    /// Get authorization config
-    pub fn get_auth_config(env: Env, user: Address) -> Option<auth::AuthConfig> {
-        auth::get_auth_config(&env, &user)
+    /// Get the default spending policy set by `grant_authorization`
+    pub fn get_auth_config(env: Env, user: Address) -> Option<auth::SpendingPolicy> {
+        auth::get_default_policy(&env, &user)
+    }
+
+    /// Move a user's grant from before spending policies to their default
+    /// policy. Anyone may call; returns the new policy id, if any.
+    pub fn migrate_authorization(env: Env, user: Address) -> Option<u64> {
+        auth::migrate_legacy_authorization(&env, &user)
     }
 
     /// Set rate limit flag for a user (operator only)
@@ -1433,10 +1729,9 @@ impl AutoTradeContract {
         max_drawdown_bps: u32,
         hedge_ratio_bps: u32,
         rebalance_threshold_bps: u32,
+        mode: portfolio_insurance::InsuranceMode,
     ) -> Result<(), AutoTradeError> {
-        if !cfg!(test) {
-            user.require_auth();
-        }
+        user.require_auth();
         portfolio_insurance::configure_insurance(
             &env,
             &user,
@@ -1444,9 +1739,63 @@ impl AutoTradeContract {
             max_drawdown_bps,
             hedge_ratio_bps,
             rebalance_threshold_bps,
+            mode,
         )
     }
 
+    /// Register the token hedges trade for `asset_id`; `hedge_eligible`
+    /// makes it a candidate inverse-correlated hedge.
+    /// Admin only.
+    pub fn set_hedge_asset_token(
+        env: Env,
+        caller: Address,
+        asset_id: u32,
+        token: Address,
+        hedge_eligible: bool,
+    ) -> Result<(), AutoTradeError> {
+        admin::require_admin(&env, &caller)?;
+        portfolio_insurance::set_asset_token(&env, asset_id, token, hedge_eligible);
+        Ok(())
+    }
+
+    /// Set the stablecoin hedges rotate into. Admin only.
+    pub fn set_hedge_stablecoin(
+        env: Env,
+        caller: Address,
+        token: Address,
+    ) -> Result<(), AutoTradeError> {
+        admin::require_admin(&env, &caller)?;
+        portfolio_insurance::set_stablecoin(&env, token);
+        Ok(())
+    }
+
+    /// Deposit `amount` of `token` into the custody hedges trade from.
+    pub fn deposit_hedge_collateral(
+        env: Env,
+        user: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), AutoTradeError> {
+        user.require_auth();
+        portfolio_insurance::deposit_collateral(&env, &user, &token, amount)
+    }
+
+    /// Withdraw `amount` of `token` from hedge custody.
+    pub fn withdraw_hedge_collateral(
+        env: Env,
+        user: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), AutoTradeError> {
+        user.require_auth();
+        portfolio_insurance::withdraw_collateral(&env, &user, &token, amount)
+    }
+
+    /// Units of `token` held in hedge custody for `user`.
+    pub fn get_hedge_collateral(env: Env, user: Address, token: Address) -> i128 {
+        portfolio_insurance::custody_balance(&env, &user, &token)
+    }
+
     /// Return current drawdown in basis points and update the high-water mark.
     pub fn get_portfolio_drawdown(env: Env, user: Address) -> Result<i128, AutoTradeError> {
         portfolio_insurance::calculate_drawdown(&env, &user)
@@ -1457,20 +1806,16 @@ impl AutoTradeContract {
         env: Env,
         user: Address,
     ) -> Result<soroban_sdk::Vec<u32>, AutoTradeError> {
-        if !cfg!(test) {
-            user.require_auth();
-        }
+        user.require_auth();
         portfolio_insurance::check_and_apply_hedge(&env, &user)
     }
 
-    /// Rebalance existing hedges to match the current portfolio size.
+    /// Rebalance existing hedges so the portfolio delta tracks its target.
     pub fn rebalance_hedges(
         env: Env,
         user: Address,
     ) -> Result<soroban_sdk::Vec<u32>, AutoTradeError> {
-        if !cfg!(test) {
-            user.require_auth();
-        }
+        user.require_auth();
         portfolio_insurance::rebalance_hedges(&env, &user)
     }
 
@@ -1479,9 +1824,7 @@ impl AutoTradeContract {
         env: Env,
         user: Address,
     ) -> Result<soroban_sdk::Vec<u32>, AutoTradeError> {
-        if !cfg!(test) {
-            user.require_auth();
-        }
+        user.require_auth();
         portfolio_insurance::remove_hedges_if_recovered(&env, &user)
     }
 
@@ -1493,6 +1836,26 @@ impl AutoTradeContract {
         portfolio_insurance::get_insurance(&env, &user)
     }
 
+    /// Move `user`'s insurance record from the pre-routed-hedge layout to the
+    /// current one. Anyone may call; returns whether a legacy record was
+    /// migrated. Legacy records are also migrated on first load.
+    pub fn migrate_insurance(env: Env, user: Address) -> bool {
+        portfolio_insurance::migrate_legacy_insurance(&env, &user).is_some()
+    }
+
+    /// Hedge P&L for a user, reported apart from position P&L.
+    pub fn get_hedge_pnl(
+        env: Env,
+        user: Address,
+    ) -> Result<portfolio_insurance::HedgePnl, AutoTradeError> {
+        portfolio_insurance::get_hedge_pnl(&env, &user)
+    }
+
+    /// Current portfolio delta (bps) net of hedges.
+    pub fn get_portfolio_delta(env: Env, user: Address) -> Result<i128, AutoTradeError> {
+        portfolio_insurance::portfolio_delta_bps(&env, &user)
+    }
+
     // ── Exit Strategy ────────────────────────────────────────────────────────
 
     /// Create a custom exit strategy with explicit TP and stop-loss tiers.
@@ -1821,6 +2184,48 @@ impl AutoTradeContract {
         )
     }
 
+    /// Create a conditional order triggered by a nested condition tree.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_conditional_tree_order(
+        env: Env,
+        user: Address,
+        asset_id: u32,
+        side: conditional::ConditionalSide,
+        amount: i128,
+        limit_price: i128,
+        tree: conditional::ConditionTree,
+        expires_in_seconds: u64,
+    ) -> Result<u64, AutoTradeError> {
+        conditional::create_conditional_tree_order(
+            &env,
+            user,
+            asset_id,
+            side,
+            amount,
+            limit_price,
+            tree,
+            expires_in_seconds,
+        )
+    }
+
+    /// Link pending conditional orders so the first to trigger cancels the rest.
+    pub fn link_oco_orders(env: Env, user: Address, ids: Vec<u64>) -> Result<(), AutoTradeError> {
+        conditional::link_oco(&env, user, ids)
+    }
+
+    /// Set the signal registry used by `SignalResolved` conditions (admin only).
+    pub fn set_conditional_signal_registry(
+        env: Env,
+        caller: Address,
+        registry: Option<Address>,
+    ) -> Result<(), AutoTradeError> {
+        conditional::set_signal_registry(&env, &caller, registry)
+    }
+
+    pub fn get_conditional_signal_registry(env: Env) -> Option<Address> {
+        conditional::get_signal_registry(&env)
+    }
+
     /// Cancel a pending conditional order.
     pub fn cancel_conditional_order(
         env: Env,
@@ -1838,6 +2243,13 @@ impl AutoTradeContract {
         conditional::get_conditional_order(&env, id)
     }
 
+    /// Move conditional order `id` from the pre-tree layout to the current
+    /// one. Anyone may call; returns whether a legacy order was migrated.
+    /// Legacy orders are also migrated on first load.
+    pub fn migrate_conditional_order(env: Env, id: u64) -> bool {
+        conditional::migrate_legacy_order(&env, id).is_some()
+    }
+
     /// Evaluate all active conditional orders; returns ids of newly triggered ones.
     ///
     /// `keeper` must be a registered keeper address (see `add_keeper` /
@@ -2043,6 +2455,67 @@ mod oracle_tests {
         // 1_000_000 / 10^4 = 100
         assert_eq!(oracle::oracle_price_to_i128(&op), 100);
     }
+
+    /// Oracle quoting every asset pair at 80.
+    #[soroban_sdk::contract]
+    struct MarkOracle;
+
+    #[soroban_sdk::contractimpl]
+    impl MarkOracle {
+        pub fn get_price(env: Env, _asset_pair: u32) -> OraclePrice {
+            make_price(&env, 80)
+        }
+    }
+
+    /// A recorded copy trade is marked at the oracle price.
+    #[test]
+    fn test_copy_trade_loss_marks_recorded_trade_at_oracle() {
+        let (env, contract_id) = setup();
+        let oracle_id = env.register(MarkOracle, ());
+        let user = Address::generate(&env);
+        let provider = Address::generate(&env);
+
+        env.as_contract(&contract_id, || {
+            assert!(AutoTradeContract::get_copy_trade_loss(env.clone(), user.clone(), 1).is_none());
+
+            storage::set_signal(
+                &env,
+                1,
+                &storage::Signal {
+                    signal_id: 1,
+                    price: 100,
+                    expiry: 2_000,
+                    base_asset: 1,
+                },
+            );
+            storage::set_signal_provider(&env, 1, &provider);
+            env.storage()
+                .instance()
+                .set(&admin::AdminStorageKey::OracleAddress, &oracle_id);
+            let paid_in = Address::generate(&env);
+            amm_bridge::set_signal_token_pair(&env, 1, paid_in.clone(), Address::generate(&env));
+            env.storage().persistent().set(
+                &DataKey::Trades(user.clone(), 1),
+                &Trade {
+                    signal_id: 1,
+                    user: user.clone(),
+                    requested_amount: 500,
+                    executed_amount: 400,
+                    executed_price: 100,
+                    timestamp: 900,
+                    status: TradeStatus::Filled,
+                },
+            );
+
+            // Bought 400 at 100, now 80: a 20% loss.
+            let loss =
+                AutoTradeContract::get_copy_trade_loss(env.clone(), user.clone(), 1).unwrap();
+            assert_eq!(loss.provider, provider);
+            assert_eq!(loss.executed_at, 900);
+            assert_eq!(loss.token, paid_in);
+            assert_eq!(loss.loss, 80);
+        });
+    }
 }
 
 // ── Oracle circuit breaker tests ───────────────────────────────────────────────
@@ -3,11 +3,43 @@
 //!
 //! Monitors drawdown against a high-water mark and automatically opens,
 //! rebalances, and closes offsetting hedge positions.
+//!
+//! Hedges are real trades routed through `smart_routing`: part of each
+//! holding is sold either into the configured stablecoin or into the
+//! registered hedge asset whose correlation with the portfolio (from
+//! `correlation::build_correlation_matrix`) is most negative. Hedge size
+//! targets a portfolio delta — risky exposure net of hedges, as a share of
+//! the insured value:
+//!
+//! - `Drawdown` mode holds delta at `10000 - hedge_ratio_bps` once the
+//!   drawdown trigger is breached,
+//! - `Cppi` mode keeps exposure at `multiplier × (value − floor)`.
+//!
+//! Hedge P&L — hedge value against what the sold units would be worth now —
+//! is tracked apart from the positions it protects.
+//!
+//! Hedges only trade tokens the user has deposited into custody with
+//! `deposit_collateral`: a sale is capped at the custodied balance of the
+//! holding's token, and its proceeds are credited back to the user's custody
+//! of the hedge instrument.
+//!
+//! Insurance records from before routed hedges are stored as a
+//! [`LegacyPortfolioInsurance`] under `InsuranceKey::Insurance` and converted
+//! to the current layout the first time they are loaded.
 
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+use soroban_sdk::{contracttype, symbol_short, token, Address, Env, Symbol, Vec};
 
+use crate::amm_bridge::TokenPairConfig;
+use crate::correlation::{self, LOW_CORR_THRESHOLD};
 use crate::errors::AutoTradeError;
 use crate::risk;
+use crate::smart_routing;
+
+const BPS: i128 = 10_000;
+/// Slippage accepted on hedge trades.
+const HEDGE_MAX_SLIPPAGE_BPS: u32 = 300;
+/// Drawdown below which `remove_hedges_if_recovered` unwinds (5%).
+const RECOVERY_DRAWDOWN_BPS: i128 = 500;
 
 // ─── Types ────────────────────────────────────────────────────────────────────
 
@@ -19,14 +51,49 @@ pub enum HedgePurpose {
     AssetSpecificHedge(u32),
 }
 
+/// What a hedge holds in place of the units it sold.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HedgeInstrument {
+    /// Rotated into the configured stablecoin (1 unit = 1 value unit).
+    Stablecoin,
+    /// Bought a registered asset inversely correlated with the portfolio.
+    InverseAsset(u32),
+}
+
 /// A single open hedge position.
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HedgePosition {
+    /// Holding that was sold to fund the hedge.
     pub asset: u32,
+    /// Units of `asset` sold.
     pub amount: i128,
     pub entry_price: i128,
     pub purpose: HedgePurpose,
+    pub instrument: HedgeInstrument,
+    /// Units of the instrument received.
+    pub hedge_amount: i128,
+    /// Instrument correlation with the portfolio at entry (bps, ≤ 0).
+    pub correlation_bps: i128,
+}
+
+/// Constant proportion portfolio insurance parameters.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CppiConfig {
+    /// Insured value the portfolio must not fall below.
+    pub floor: i128,
+    /// Exposure per unit of cushion above the floor.
+    pub multiplier: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InsuranceMode {
+    /// Hedge `hedge_ratio_bps` of the portfolio once `max_drawdown_bps` is hit.
+    Drawdown,
+    Cppi(CppiConfig),
 }
 
 /// Per-user insurance configuration and state.
@@ -43,25 +110,196 @@ pub struct PortfolioInsurance {
     pub rebalance_threshold_bps: u32,
     pub active_hedges: Vec<HedgePosition>,
     pub portfolio_high_water_mark: i128,
+    pub mode: InsuranceMode,
+    /// P&L locked in by closed hedges.
+    pub realized_hedge_pnl: i128,
+}
+
+/// Hedge layout from before routed hedges: bookkeeping only, no tokens moved.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LegacyHedgePosition {
+    pub asset: u32,
+    pub amount: i128,
+    pub entry_price: i128,
+    pub purpose: HedgePurpose,
+}
+
+/// Insurance layout from before routed hedges and CPPI mode.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct LegacyPortfolioInsurance {
+    pub user: Address,
+    pub enabled: bool,
+    pub max_drawdown_bps: u32,
+    pub hedge_ratio_bps: u32,
+    pub rebalance_threshold_bps: u32,
+    pub active_hedges: Vec<LegacyHedgePosition>,
+    pub portfolio_high_water_mark: i128,
+}
+
+/// Hedge P&L, reported apart from the positions.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HedgePnl {
+    /// Current value of open hedges.
+    pub hedge_value: i128,
+    /// Open hedges' value minus what their sold units would be worth now.
+    pub unrealized: i128,
+    pub realized: i128,
 }
 
 // ─── Storage ──────────────────────────────────────────────────────────────────
 
 #[contracttype]
 pub enum InsuranceKey {
+    /// Pre-routed-hedge record: `LegacyPortfolioInsurance`. Removed once
+    /// migrated.
     Insurance(Address),
+    /// Token the contract trades for an asset id.
+    AssetToken(u32),
+    /// Asset ids eligible as inverse-correlated hedges.
+    HedgeAssets,
+    Stablecoin,
+    /// Insurance by user: `PortfolioInsurance`.
+    Policy(Address),
+    /// Units of a token (second field) held in custody for a user.
+    Custody(Address, Address),
 }
 
 pub fn get_insurance(env: &Env, user: &Address) -> Option<PortfolioInsurance> {
     env.storage()
         .persistent()
-        .get(&InsuranceKey::Insurance(user.clone()))
+        .get(&InsuranceKey::Policy(user.clone()))
+        .or_else(|| migrate_legacy_insurance(env, user))
 }
 
 pub fn store_insurance(env: &Env, insurance: &PortfolioInsurance) {
     env.storage()
         .persistent()
-        .set(&InsuranceKey::Insurance(insurance.user.clone()), insurance);
+        .set(&InsuranceKey::Policy(insurance.user.clone()), insurance);
+}
+
+/// Convert `user`'s pre-routed-hedge record to the current layout in
+/// `Drawdown` mode. Legacy hedges never sold anything, so they are dropped
+/// rather than carried over as positions with no tokens behind them.
+pub fn migrate_legacy_insurance(env: &Env, user: &Address) -> Option<PortfolioInsurance> {
+    let key = InsuranceKey::Insurance(user.clone());
+    let legacy: LegacyPortfolioInsurance = env.storage().persistent().get(&key)?;
+    env.storage().persistent().remove(&key);
+
+    let insurance = PortfolioInsurance {
+        user: legacy.user,
+        enabled: legacy.enabled,
+        max_drawdown_bps: legacy.max_drawdown_bps,
+        hedge_ratio_bps: legacy.hedge_ratio_bps,
+        rebalance_threshold_bps: legacy.rebalance_threshold_bps,
+        active_hedges: Vec::new(env),
+        portfolio_high_water_mark: legacy.portfolio_high_water_mark,
+        mode: InsuranceMode::Drawdown,
+        realized_hedge_pnl: 0,
+    };
+    store_insurance(env, &insurance);
+    Some(insurance)
+}
+
+/// Units of `token` held in custody for `user`.
+pub fn custody_balance(env: &Env, user: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&InsuranceKey::Custody(user.clone(), token.clone()))
+        .unwrap_or(0)
+}
+
+fn set_custody(env: &Env, user: &Address, token: &Address, amount: i128) {
+    env.storage()
+        .persistent()
+        .set(&InsuranceKey::Custody(user.clone(), token.clone()), &amount);
+}
+
+/// Move `amount` of `token` from `user` into custody hedges may trade.
+pub fn deposit_collateral(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), AutoTradeError> {
+    if amount <= 0 {
+        return Err(AutoTradeError::InvalidAmount);
+    }
+    token::Client::new(env, token).transfer(user, &env.current_contract_address(), &amount);
+    set_custody(env, user, token, custody_balance(env, user, token) + amount);
+    Ok(())
+}
+
+/// Return `amount` of `token` from custody to `user`.
+pub fn withdraw_collateral(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), AutoTradeError> {
+    if amount <= 0 {
+        return Err(AutoTradeError::InvalidAmount);
+    }
+    let held = custody_balance(env, user, token);
+    if amount > held {
+        return Err(AutoTradeError::InsufficientBalance);
+    }
+    set_custody(env, user, token, held - amount);
+    token::Client::new(env, token).transfer(&env.current_contract_address(), user, &amount);
+    Ok(())
+}
+
+/// Register the token traded for `asset_id`; `hedge_eligible` also makes it
+/// a candidate inverse hedge.
+pub fn set_asset_token(env: &Env, asset_id: u32, token: Address, hedge_eligible: bool) {
+    env.storage()
+        .persistent()
+        .set(&InsuranceKey::AssetToken(asset_id), &token);
+    let mut hedge_assets = get_hedge_assets(env);
+    let listed = hedge_assets.first_index_of(asset_id);
+    match (hedge_eligible, listed) {
+        (true, None) => hedge_assets.push_back(asset_id),
+        (false, Some(i)) => {
+            hedge_assets.remove(i);
+        }
+        _ => return,
+    }
+    env.storage()
+        .persistent()
+        .set(&InsuranceKey::HedgeAssets, &hedge_assets);
+}
+
+pub fn set_stablecoin(env: &Env, token: Address) {
+    env.storage()
+        .persistent()
+        .set(&InsuranceKey::Stablecoin, &token);
+}
+
+fn get_hedge_assets(env: &Env) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(&InsuranceKey::HedgeAssets)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn asset_token(env: &Env, asset_id: u32) -> Result<Address, AutoTradeError> {
+    env.storage()
+        .persistent()
+        .get(&InsuranceKey::AssetToken(asset_id))
+        .ok_or(AutoTradeError::HedgeTokenNotSet)
+}
+
+fn instrument_token(env: &Env, instrument: &HedgeInstrument) -> Result<Address, AutoTradeError> {
+    match instrument {
+        HedgeInstrument::Stablecoin => env
+            .storage()
+            .persistent()
+            .get(&InsuranceKey::Stablecoin)
+            .ok_or(AutoTradeError::HedgeTokenNotSet),
+        HedgeInstrument::InverseAsset(id) => asset_token(env, *id),
+    }
 }
 
 // ─── Public API ───────────────────────────────────────────────────────────────
@@ -74,6 +312,7 @@ pub fn configure_insurance(
     max_drawdown_bps: u32,
     hedge_ratio_bps: u32,
     rebalance_threshold_bps: u32,
+    mode: InsuranceMode,
 ) -> Result<(), AutoTradeError> {
     if max_drawdown_bps == 0 || max_drawdown_bps > 10_000 {
         return Err(AutoTradeError::InvalidInsuranceConfig);
@@ -81,12 +320,18 @@ pub fn configure_insurance(
     if hedge_ratio_bps == 0 || hedge_ratio_bps > 10_000 {
         return Err(AutoTradeError::InvalidInsuranceConfig);
     }
+    if let InsuranceMode::Cppi(cppi) = &mode {
+        if cppi.floor <= 0 || cppi.multiplier == 0 {
+            return Err(AutoTradeError::InvalidInsuranceConfig);
+        }
+    }
 
     let existing = get_insurance(env, user);
     let hwm = existing
         .as_ref()
         .map(|i| i.portfolio_high_water_mark)
         .unwrap_or(0);
+    let realized_hedge_pnl = existing.as_ref().map(|i| i.realized_hedge_pnl).unwrap_or(0);
     let active_hedges = existing
         .map(|i| i.active_hedges)
         .unwrap_or_else(|| Vec::new(env));
@@ -99,16 +344,19 @@ pub fn configure_insurance(
         rebalance_threshold_bps,
         active_hedges,
         portfolio_high_water_mark: hwm,
+        mode,
+        realized_hedge_pnl,
     };
     store_insurance(env, &insurance);
     Ok(())
 }
 
-/// Return current drawdown in basis points (0–10000).
+/// Return current drawdown in basis points (0–10000) of the insured value
+/// (positions plus open hedges).
 /// Also updates the high-water mark when a new portfolio high is reached.
 pub fn calculate_drawdown(env: &Env, user: &Address) -> Result<i128, AutoTradeError> {
     let mut insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
-    let current_value = risk::calculate_portfolio_value(env, user);
+    let current_value = insured_value(env, user, &insurance);
 
     if current_value > insurance.portfolio_high_water_mark {
         insurance.portfolio_high_water_mark = current_value;
@@ -125,8 +373,15 @@ pub fn calculate_drawdown(env: &Env, user: &Address) -> Result<i128, AutoTradeEr
     Ok(drawdown_bps)
 }
 
+/// Portfolio delta in bps: risky exposure net of hedges over insured value.
+pub fn portfolio_delta_bps(env: &Env, user: &Address) -> Result<i128, AutoTradeError> {
+    let insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
+    Ok(delta_bps(env, user, &insurance))
+}
+
 /// Check drawdown and open hedge positions if the threshold is breached.
-/// Returns the list of simulated trade IDs (asset ids used as proxy IDs here).
+/// In CPPI mode, hedges whenever exposure is above the CPPI target.
+/// Returns the ids of the holdings sold into hedges.
 pub fn check_and_apply_hedge(env: &Env, user: &Address) -> Result<Vec<u32>, AutoTradeError> {
     let insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
 
@@ -136,32 +391,24 @@ pub fn check_and_apply_hedge(env: &Env, user: &Address) -> Result<Vec<u32>, Auto
 
     let drawdown = calculate_drawdown(env, user)?;
 
-    if drawdown < insurance.max_drawdown_bps as i128 {
-        return Ok(Vec::new(env));
-    }
-
-    // Already hedged — don't double-hedge
-    if !insurance.active_hedges.is_empty() {
-        return Ok(Vec::new(env));
-    }
-
-    let current_value = risk::calculate_portfolio_value(env, user);
-    let hedges = calculate_optimal_hedges(env, user, current_value, insurance.hedge_ratio_bps)?;
+    if insurance.mode == InsuranceMode::Drawdown {
+        if drawdown < insurance.max_drawdown_bps as i128 {
+            return Ok(Vec::new(env));
+        }
 
-    if hedges.is_empty() {
-        return Ok(Vec::new(env));
+        // Already hedged — don't double-hedge
+        if !insurance.active_hedges.is_empty() {
+            return Ok(Vec::new(env));
+        }
     }
 
-    let mut trade_ids: Vec<u32> = Vec::new(env);
     let mut insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
-
-    for i in 0..hedges.len() {
-        if let Some(hedge) = hedges.get(i) {
-            trade_ids.push_back(hedge.asset);
-            insurance.active_hedges.push_back(hedge);
-        }
+    let excess = exposure_gap(env, user, &insurance);
+    if excess <= 0 {
+        return Ok(Vec::new(env));
     }
 
+    let trade_ids = open_hedges(env, user, &mut insurance, excess)?;
     store_insurance(env, &insurance);
 
     #[allow(deprecated)]
@@ -177,79 +424,33 @@ pub fn check_and_apply_hedge(env: &Env, user: &Address) -> Result<Vec<u32>, Auto
     Ok(trade_ids)
 }
 
-/// Rebalance existing hedges to match the current portfolio size.
+/// Rebalance existing hedges so the portfolio delta tracks its target.
 pub fn rebalance_hedges(env: &Env, user: &Address) -> Result<Vec<u32>, AutoTradeError> {
-    let insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
+    let mut insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
 
-    if !insurance.enabled || insurance.active_hedges.is_empty() {
+    if !insurance.enabled {
         return Ok(Vec::new(env));
     }
-
-    let current_value = risk::calculate_portfolio_value(env, user);
-    let target_hedge_value = (current_value * insurance.hedge_ratio_bps as i128) / 10_000;
-
-    let mut current_hedge_value: i128 = 0;
-    for i in 0..insurance.active_hedges.len() {
-        if let Some(h) = insurance.active_hedges.get(i) {
-            let price = risk::get_asset_price(env, h.asset).unwrap_or(h.entry_price);
-            current_hedge_value += h.amount * price / 100;
-        }
+    if insurance.mode == InsuranceMode::Drawdown && insurance.active_hedges.is_empty() {
+        return Ok(Vec::new(env));
     }
 
-    let hedge_delta = target_hedge_value - current_hedge_value;
-    let denominator = if target_hedge_value > 0 {
-        target_hedge_value
-    } else {
-        1
-    };
-    let delta_bps = (hedge_delta.abs() * 10_000) / denominator;
+    let total = insured_value(env, user, &insurance);
+    let gap = exposure_gap(env, user, &insurance);
+    let denominator = if total > 0 { total } else { 1 };
+    let gap_bps = (gap.abs() * 10_000) / denominator;
 
-    if delta_bps < insurance.rebalance_threshold_bps as i128 {
+    if gap == 0 || gap_bps < insurance.rebalance_threshold_bps as i128 {
         return Ok(Vec::new(env));
     }
 
-    let mut trade_ids: Vec<u32> = Vec::new(env);
-    let mut insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
-
-    if hedge_delta > 0 {
+    let trade_ids = if gap > 0 {
         // Need more hedging
-        let additional = calculate_optimal_hedges(env, user, hedge_delta, 10_000)?;
-        for i in 0..additional.len() {
-            if let Some(hedge) = additional.get(i) {
-                trade_ids.push_back(hedge.asset);
-                insurance.active_hedges.push_back(hedge);
-            }
-        }
+        open_hedges(env, user, &mut insurance, gap)?
     } else {
-        // Reduce hedging — trim amounts proportionally
-        let reduce_amount = hedge_delta.abs();
-        let mut remaining = reduce_amount;
-        let mut new_hedges: Vec<HedgePosition> = Vec::new(env);
-
-        for i in 0..insurance.active_hedges.len() {
-            if let Some(mut h) = insurance.active_hedges.get(i) {
-                let price = risk::get_asset_price(env, h.asset).unwrap_or(h.entry_price);
-                let hedge_val = h.amount * price / 100;
-
-                if remaining <= 0 {
-                    new_hedges.push_back(h);
-                } else if hedge_val <= remaining {
-                    remaining -= hedge_val;
-                    trade_ids.push_back(h.asset); // closed
-                } else {
-                    // Partial close
-                    let close_units = (remaining * 100) / price.max(1);
-                    h.amount -= close_units;
-                    remaining = 0;
-                    trade_ids.push_back(h.asset);
-                    if h.amount > 0 {
-                        new_hedges.push_back(h);
-                    }
-                }
-            }
-        }
-        insurance.active_hedges = new_hedges;
-    }
+        // Reduce hedging — unwind the most recent hedges first
+        reduce_hedges(env, user, &mut insurance, gap.abs())?
+    };
 
     store_insurance(env, &insurance);
 
@@ -272,17 +473,17 @@ pub fn remove_hedges_if_recovered(env: &Env, user: &Address) -> Result<Vec<u32>,
 
     let drawdown = calculate_drawdown(env, user)?;
 
-    if drawdown >= 500 {
+    if drawdown >= RECOVERY_DRAWDOWN_BPS {
         return Ok(Vec::new(env));
     }
 
     let mut trade_ids: Vec<u32> = Vec::new(env);
     let mut insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
+    let hedges = insurance.active_hedges.clone();
 
-    for i in 0..insurance.active_hedges.len() {
-        if let Some(h) = insurance.active_hedges.get(i) {
-            trade_ids.push_back(h.asset);
-        }
+    for h in hedges.iter() {
+        close_hedge(env, user, &mut insurance, &h, h.hedge_amount)?;
+        trade_ids.push_back(h.asset);
     }
 
     insurance.active_hedges = Vec::new(env);
@@ -297,55 +498,273 @@ pub fn remove_hedges_if_recovered(env: &Env, user: &Address) -> Result<Vec<u32>,
     Ok(trade_ids)
 }
 
+/// Hedge P&L for `user`, separate from position P&L.
+pub fn get_hedge_pnl(env: &Env, user: &Address) -> Result<HedgePnl, AutoTradeError> {
+    let insurance = get_insurance(env, user).ok_or(AutoTradeError::InsuranceNotConfigured)?;
+    let mut hedge_value = 0i128;
+    let mut unrealized = 0i128;
+    for h in insurance.active_hedges.iter() {
+        let value = instrument_value(env, &h, h.hedge_amount);
+        hedge_value += value;
+        unrealized += value - sold_value(env, &h, h.amount);
+    }
+    Ok(HedgePnl {
+        hedge_value,
+        unrealized,
+        realized: insurance.realized_hedge_pnl,
+    })
+}
+
 // ─── Internal helpers ─────────────────────────────────────────────────────────
 
-/// Build hedge positions for `total_hedge_value` spread across the user's
-/// holdings, weighted by a simple volatility proxy (price deviation from mean).
-fn calculate_optimal_hedges(
-    env: &Env,
-    user: &Address,
-    total_hedge_value: i128,
-    ratio_bps: u32,
-) -> Result<Vec<HedgePosition>, AutoTradeError> {
+fn price_of(env: &Env, asset_id: u32, fallback: i128) -> i128 {
+    risk::get_asset_price(env, asset_id).unwrap_or(fallback)
+}
+
+/// Value of `units` of the hedge's instrument.
+fn instrument_value(env: &Env, h: &HedgePosition, units: i128) -> i128 {
+    match h.instrument {
+        HedgeInstrument::Stablecoin => units,
+        HedgeInstrument::InverseAsset(id) => units * price_of(env, id, 0) / 100,
+    }
+}
+
+/// What `units` of the sold holding would be worth now.
+fn sold_value(env: &Env, h: &HedgePosition, units: i128) -> i128 {
+    units * price_of(env, h.asset, h.entry_price) / 100
+}
+
+fn hedge_value(env: &Env, insurance: &PortfolioInsurance) -> i128 {
+    insurance
+        .active_hedges
+        .iter()
+        .map(|h| instrument_value(env, &h, h.hedge_amount))
+        .sum()
+}
+
+/// Positions plus open hedges.
+fn insured_value(env: &Env, user: &Address, insurance: &PortfolioInsurance) -> i128 {
+    risk::calculate_portfolio_value(env, user) + hedge_value(env, insurance)
+}
+
+/// Risky exposure net of the offset inverse hedges provide.
+fn net_exposure(env: &Env, user: &Address, insurance: &PortfolioInsurance) -> i128 {
+    let mut exposure = risk::calculate_portfolio_value(env, user);
+    for h in insurance.active_hedges.iter() {
+        exposure += instrument_value(env, &h, h.hedge_amount) * h.correlation_bps / BPS;
+    }
+    exposure
+}
+
+fn delta_bps(env: &Env, user: &Address, insurance: &PortfolioInsurance) -> i128 {
+    let total = insured_value(env, user, insurance);
+    if total <= 0 {
+        return 0;
+    }
+    net_exposure(env, user, insurance) * BPS / total
+}
+
+/// Target risky exposure for the insured value.
+fn target_exposure(insurance: &PortfolioInsurance, total: i128) -> i128 {
+    match &insurance.mode {
+        InsuranceMode::Drawdown => total * (BPS - insurance.hedge_ratio_bps as i128) / BPS,
+        InsuranceMode::Cppi(cppi) => {
+            let cushion = (total - cppi.floor).max(0);
+            (cushion * cppi.multiplier as i128).min(total)
+        }
+    }
+}
+
+/// Exposure above target (positive) or below it (negative).
+fn exposure_gap(env: &Env, user: &Address, insurance: &PortfolioInsurance) -> i128 {
+    let total = insured_value(env, user, insurance);
+    net_exposure(env, user, insurance) - target_exposure(insurance, total)
+}
+
+/// The registered asset most inversely correlated with the holdings, if any
+/// is at least `LOW_CORR_THRESHOLD` negative; otherwise the stablecoin.
+fn select_instrument(env: &Env, user: &Address) -> (HedgeInstrument, i128) {
     let positions = risk::get_user_positions(env, user);
-    let mut hedges: Vec<HedgePosition> = Vec::new(env);
+    let held = positions.keys();
+    let mut assets = held.clone();
+    let mut candidates: Vec<u32> = Vec::new(env);
+    for id in get_hedge_assets(env).iter() {
+        if !held.contains(id) && risk::get_asset_price(env, id).is_some() {
+            candidates.push_back(id);
+            assets.push_back(id);
+        }
+    }
+    if candidates.is_empty() {
+        return (HedgeInstrument::Stablecoin, 0);
+    }
+
+    let matrix = correlation::build_correlation_matrix(env, &assets);
+    let total = risk::calculate_portfolio_value(env, user).max(1);
+    let mut best: Option<(u32, i128)> = None;
+    for candidate in candidates.iter() {
+        let mut weighted = 0i128;
+        for (id, pos) in positions.iter() {
+            let value = pos.amount * price_of(env, id, pos.entry_price) / 100;
+            weighted += correlation::get_correlation(&matrix, id, candidate) * value / total;
+        }
+        if best.map_or(true, |(_, c)| weighted < c) {
+            best = Some((candidate, weighted));
+        }
+    }
+    match best {
+        Some((id, corr)) if corr <= -LOW_CORR_THRESHOLD => {
+            (HedgeInstrument::InverseAsset(id), corr)
+        }
+        _ => (HedgeInstrument::Stablecoin, 0),
+    }
+}
 
-    if total_hedge_value <= 0 {
-        return Ok(hedges);
+/// Sell holdings pro rata to cut net exposure by `reduce_value`.
+fn open_hedges(
+    env: &Env,
+    user: &Address,
+    insurance: &mut PortfolioInsurance,
+    reduce_value: i128,
+) -> Result<Vec<u32>, AutoTradeError> {
+    let mut trade_ids: Vec<u32> = Vec::new(env);
+    let risky_value = risk::calculate_portfolio_value(env, user);
+    if reduce_value <= 0 || risky_value <= 0 {
+        return Ok(trade_ids);
     }
 
-    let hedge_value = (total_hedge_value * ratio_bps as i128) / 10_000;
-    let keys = positions.keys();
-    let n = keys.len();
+    let (instrument, correlation_bps) = select_instrument(env, user);
+    let hedge_token = instrument_token(env, &instrument)?;
+    // Each unit sold into an inverse asset also offsets |corr| of a unit.
+    let sell_value = reduce_value.min(risky_value) * BPS / (BPS + correlation_bps.abs());
 
-    if n == 0 {
-        return Ok(hedges);
+    for (asset_id, mut pos) in risk::get_user_positions(env, user).iter() {
+        let price = price_of(env, asset_id, 0);
+        if price <= 0 {
+            continue;
+        }
+        let from_token = asset_token(env, asset_id)?;
+        let custodied = custody_balance(env, user, &from_token);
+        let share = sell_value * (pos.amount * price / 100) / risky_value;
+        // units = value / (price / 100)  →  units = value * 100 / price
+        let units = (share * 100 / price).min(pos.amount).min(custodied);
+        if units <= 0 {
+            continue;
+        }
+
+        let pair = TokenPairConfig {
+            from_token: from_token.clone(),
+            to_token: hedge_token.clone(),
+        };
+        let received = smart_routing::swap_pair(env, 0, &pair, units, HEDGE_MAX_SLIPPAGE_BPS)?;
+        set_custody(env, user, &from_token, custodied - units);
+        set_custody(
+            env,
+            user,
+            &hedge_token,
+            custody_balance(env, user, &hedge_token) + received,
+        );
+
+        pos.amount -= units;
+        risk::update_position(env, user, asset_id, pos.amount, price);
+
+        trade_ids.push_back(asset_id);
+        insurance.active_hedges.push_back(HedgePosition {
+            asset: asset_id,
+            amount: units,
+            entry_price: price,
+            purpose: HedgePurpose::AssetSpecificHedge(asset_id),
+            instrument: instrument.clone(),
+            hedge_amount: received,
+            correlation_bps,
+        });
     }
 
-    // Equal-weight across holdings (simple, no external oracle needed)
-    let per_asset_value = hedge_value / n as i128;
+    Ok(trade_ids)
+}
+
+/// Unwind `release_value` of net exposure reduction, most recent hedge first.
+fn reduce_hedges(
+    env: &Env,
+    user: &Address,
+    insurance: &mut PortfolioInsurance,
+    release_value: i128,
+) -> Result<Vec<u32>, AutoTradeError> {
+    let mut trade_ids: Vec<u32> = Vec::new(env);
+    let mut remaining = release_value;
+    let mut kept: Vec<HedgePosition> = Vec::new(env);
 
-    for i in 0..n {
-        if let Some(asset_id) = keys.get(i) {
-            let price = risk::get_asset_price(env, asset_id).unwrap_or(1);
-            if price <= 0 {
-                continue;
-            }
-            // units = value / (price / 100)  →  units = value * 100 / price
-            let amount = per_asset_value * 100 / price;
-            if amount <= 0 {
-                continue;
+    while let Some(mut h) = insurance.active_hedges.pop_back() {
+        if remaining <= 0 {
+            kept.push_front(h);
+            continue;
+        }
+        // Closing releases the hedge's value plus its correlation offset.
+        let value = instrument_value(env, &h, h.hedge_amount);
+        let release = value * (BPS + h.correlation_bps.abs()) / BPS;
+        if release <= remaining {
+            close_hedge(env, user, insurance, &h, h.hedge_amount)?;
+            remaining -= release;
+        } else {
+            // Partial close
+            let units = h.hedge_amount * remaining / release.max(1);
+            let units = close_hedge(env, user, insurance, &h, units)?;
+            let sold_units = h.amount * units / h.hedge_amount;
+            h.hedge_amount -= units;
+            h.amount -= sold_units;
+            remaining = 0;
+            if h.hedge_amount > 0 {
+                kept.push_front(h.clone());
             }
-            hedges.push_back(HedgePosition {
-                asset: asset_id,
-                amount,
-                entry_price: price,
-                purpose: HedgePurpose::AssetSpecificHedge(asset_id),
-            });
         }
+        trade_ids.push_back(h.asset);
     }
 
-    Ok(hedges)
+    insurance.active_hedges = kept;
+    Ok(trade_ids)
+}
+
+/// Swap up to `units` of the hedge's instrument, capped at what the user
+/// holds in custody, back into the holding it replaced and book the realised
+/// P&L: units bought back against units sold, at the holding's price.
+/// Returns the instrument units swapped.
+fn close_hedge(
+    env: &Env,
+    user: &Address,
+    insurance: &mut PortfolioInsurance,
+    h: &HedgePosition,
+    units: i128,
+) -> Result<i128, AutoTradeError> {
+    let from_token = instrument_token(env, &h.instrument)?;
+    let custodied = custody_balance(env, user, &from_token);
+    let units = units.min(custodied);
+    if units <= 0 {
+        return Ok(0);
+    }
+
+    let to_token = asset_token(env, h.asset)?;
+    let pair = TokenPairConfig {
+        from_token: from_token.clone(),
+        to_token: to_token.clone(),
+    };
+    let received = smart_routing::swap_pair(env, 0, &pair, units, HEDGE_MAX_SLIPPAGE_BPS)?;
+    set_custody(env, user, &from_token, custodied - units);
+    set_custody(
+        env,
+        user,
+        &to_token,
+        custody_balance(env, user, &to_token) + received,
+    );
+
+    let sold_units = h.amount * units / h.hedge_amount.max(1);
+    let price = price_of(env, h.asset, h.entry_price);
+    insurance.realized_hedge_pnl += (received - sold_units) * price / 100;
+
+    let held = risk::get_user_positions(env, user)
+        .get(h.asset)
+        .map(|p| p.amount)
+        .unwrap_or(0);
+    risk::update_position(env, user, h.asset, held + received, price);
+    Ok(units)
 }
 
 // ─── Tests ────────────────────────────────────────────────────────────────────
@@ -353,52 +772,155 @@ fn calculate_optimal_hedges(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::amm_bridge::mock_router::{MockAmmRouter, MockAmmRouterClient};
     use soroban_sdk::testutils::{Address as _, Ledger as _};
-    use soroban_sdk::{contract, Env};
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+    use soroban_sdk::{contract, vec, Env};
+    use stellar_swipe_common::amm_bridge::{AmmSourceConfig, AmmSourceKind};
 
     #[contract]
     struct TestContract;
 
-    fn setup() -> (Env, Address) {
+    struct Setup {
+        env: Env,
+        contract: Address,
+        user: Address,
+        router: MockAmmRouterClient<'static>,
+        asset: Address,
+        inverse: Address,
+        stable: Address,
+    }
+
+    /// Asset 1 is held, asset 2 is an eligible inverse hedge, and one router
+    /// fills every pair from whichever book the test sets.
+    fn setup() -> Setup {
         let env = Env::default();
+        // Deposits are called inside `as_contract`, below the user's auth root.
+        env.mock_all_auths_allowing_non_root_auth();
         env.ledger().set_timestamp(1_000);
+        let contract = env.register(TestContract, ());
         let user = Address::generate(&env);
-        (env, user)
+        let issuer = Address::generate(&env);
+        let asset = env
+            .register_stellar_asset_contract_v2(issuer.clone())
+            .address();
+        let inverse = env
+            .register_stellar_asset_contract_v2(issuer.clone())
+            .address();
+        let stable = env.register_stellar_asset_contract_v2(issuer).address();
+
+        let router_id = env.register(MockAmmRouter, ());
+        StellarAssetClient::new(&env, &asset).mint(&user, &100_000);
+        for token in [&asset, &inverse, &stable] {
+            StellarAssetClient::new(&env, token).mint(&router_id, &1_000_000);
+        }
+
+        env.as_contract(&contract, || {
+            crate::amm_bridge::register_amm_source(
+                &env,
+                AmmSourceConfig {
+                    kind: AmmSourceKind::SdexRouter,
+                    source_id: 1,
+                    router: router_id.clone(),
+                    priority: 1,
+                    enabled: true,
+                },
+            )
+            .unwrap();
+            set_asset_token(&env, 1, asset.clone(), false);
+            set_asset_token(&env, 2, inverse.clone(), true);
+            set_stablecoin(&env, stable.clone());
+        });
+        let router = MockAmmRouterClient::new(&env, &router_id);
+        Setup {
+            env,
+            contract,
+            user,
+            router,
+            asset,
+            inverse,
+            stable,
+        }
+    }
+
+    impl Setup {
+        fn run<F: FnOnce(&Env, &Address)>(&self, f: F) {
+            self.env
+                .as_contract(&self.contract, || f(&self.env, &self.user));
+        }
+
+        /// Fill the next swap at `price` (bps of output per input unit).
+        fn book(&self, price: i128) {
+            self.router
+                .set_order_book(&vec![&self.env, (price, 1_000_000)]);
+        }
+
+        fn balance(&self, token: &Address) -> i128 {
+            TokenClient::new(&self.env, token).balance(&self.contract)
+        }
+    }
+
+    /// 10_000 units of asset 1 at price 100 (value 10_000), HWM set, with
+    /// all of the user's asset 1 tokens in custody.
+    fn fund_portfolio(env: &Env, user: &Address) {
+        let token = asset_token(env, 1).unwrap();
+        deposit_collateral(env, user, &token, 100_000).unwrap();
+        risk::set_asset_price(env, 1, 100);
+        risk::update_position(env, user, 1, 10_000, 100);
+        calculate_drawdown(env, user).unwrap();
     }
 
-    fn register_and_run<F: FnOnce(&Env, &Address)>(env: &Env, user: &Address, f: F) {
-        let addr = env.register(TestContract, ());
-        env.as_contract(&addr, || f(env, user));
+    fn held(env: &Env, user: &Address, asset_id: u32) -> i128 {
+        risk::get_user_positions(env, user)
+            .get(asset_id)
+            .map(|p| p.amount)
+            .unwrap_or(0)
     }
 
     // ── configure ─────────────────────────────────────────────────────────────
 
     #[test]
     fn test_configure_insurance_success() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
+        let s = setup();
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
             let ins = get_insurance(env, user).unwrap();
             assert!(ins.enabled);
             assert_eq!(ins.max_drawdown_bps, 1500);
             assert_eq!(ins.hedge_ratio_bps, 5000);
+            assert_eq!(ins.mode, InsuranceMode::Drawdown);
         });
     }
 
     #[test]
     fn test_configure_insurance_invalid_drawdown() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            let err = configure_insurance(env, user, true, 0, 5000, 200).unwrap_err();
+        let s = setup();
+        s.run(|env, user| {
+            let err = configure_insurance(env, user, true, 0, 5000, 200, InsuranceMode::Drawdown)
+                .unwrap_err();
             assert_eq!(err, AutoTradeError::InvalidInsuranceConfig);
         });
     }
 
     #[test]
     fn test_configure_insurance_invalid_ratio() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            let err = configure_insurance(env, user, true, 1500, 0, 200).unwrap_err();
+        let s = setup();
+        s.run(|env, user| {
+            let err = configure_insurance(env, user, true, 1500, 0, 200, InsuranceMode::Drawdown)
+                .unwrap_err();
+            assert_eq!(err, AutoTradeError::InvalidInsuranceConfig);
+        });
+    }
+
+    #[test]
+    fn test_configure_cppi_invalid_floor() {
+        let s = setup();
+        s.run(|env, user| {
+            let mode = InsuranceMode::Cppi(CppiConfig {
+                floor: 0,
+                multiplier: 2,
+            });
+            let err = configure_insurance(env, user, true, 1500, 5000, 200, mode).unwrap_err();
             assert_eq!(err, AutoTradeError::InvalidInsuranceConfig);
         });
     }
@@ -407,9 +929,9 @@ mod tests {
 
     #[test]
     fn test_drawdown_zero_when_no_portfolio() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
+        let s = setup();
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
             let dd = calculate_drawdown(env, user).unwrap();
             assert_eq!(dd, 0);
         });
@@ -417,14 +939,10 @@ mod tests {
 
     #[test]
     fn test_drawdown_updates_high_water_mark() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
-
-            // Simulate portfolio value = 10_000 (price 100, amount 100 → value 100*100/100=100... scale up)
-            // Use price=100, amount=10000 → value = 10000*100/100 = 10000
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
+        let s = setup();
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
 
             let dd = calculate_drawdown(env, user).unwrap();
             assert_eq!(dd, 0); // at high water mark
@@ -436,14 +954,10 @@ mod tests {
 
     #[test]
     fn test_drawdown_20_percent() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
-
-            // Establish HWM at 10_000
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap(); // sets HWM = 10_000
+        let s = setup();
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
 
             // Drop price to 80 → value = 10_000 * 80 / 100 = 8_000
             risk::set_asset_price(env, 1, 80);
@@ -458,14 +972,11 @@ mod tests {
 
     #[test]
     fn test_hedge_not_triggered_below_threshold() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
+        let s = setup();
+        s.run(|env, user| {
             // 15% drawdown trigger, 50% hedge ratio
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
-
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap(); // HWM = 10_000
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
 
             // Only 10% drop
             risk::set_asset_price(env, 1, 90);
@@ -476,63 +987,93 @@ mod tests {
     }
 
     #[test]
-    fn test_hedge_triggered_at_threshold() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            // 15% drawdown trigger (1500 bps), 50% hedge ratio (5000 bps)
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
-
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap(); // HWM = 10_000
+    fn test_stablecoin_hedge_executes_trade() {
+        let s = setup();
+        // 1 unit of asset 1 at price 80 sells for 0.8 stablecoin
+        s.book(8_000);
+        s.run(|env, user| {
+            // 15% trigger, 50% hedge ratio
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
 
-            // 20% drop → drawdown = 2000 bps > 1500 bps threshold
+            // 20% drop → value = 8_000
             risk::set_asset_price(env, 1, 80);
 
             let ids = check_and_apply_hedge(env, user).unwrap();
-            assert!(ids.len() > 0);
+            assert_eq!(ids, vec![env, 1u32]);
 
             let ins = get_insurance(env, user).unwrap();
-            assert!(!ins.active_hedges.is_empty());
+            assert_eq!(ins.active_hedges.len(), 1);
+
+            let hedge = ins.active_hedges.get(0).unwrap();
+            // target exposure = 8_000 * 5000 / 10_000 = 4_000
+            // amount = 4_000 * 100 / 80 = 5_000
+            assert_eq!(hedge.amount, 5_000);
+            assert_eq!(hedge.instrument, HedgeInstrument::Stablecoin);
+            assert_eq!(hedge.hedge_amount, 4_000);
+            assert_eq!(held(env, user, 1), 5_000);
+            assert_eq!(portfolio_delta_bps(env, user).unwrap(), 5_000);
         });
+        assert_eq!(s.balance(&s.asset), 95_000);
+        assert_eq!(s.balance(&s.stable), 4_000);
     }
 
     #[test]
-    fn test_hedge_size_approximately_50_percent() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            // 15% trigger, 50% hedge ratio
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
-
-            // Portfolio: 10_000 units at price 100 → value = 10_000
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap(); // HWM = 10_000
-
-            // 20% drop → value = 8_000
+    fn test_inverse_asset_chosen_from_correlation_matrix() {
+        let s = setup();
+        // 1 unit of asset 1 (value 0.8) buys 0.8 units of asset 2 (value 1)
+        s.book(8_000);
+        s.run(|env, user| {
+            for (a, b) in [(100, 100), (110, 90), (100, 100), (110, 90), (100, 100)] {
+                risk::record_price(env, 1, a);
+                risk::record_price(env, 2, b);
+            }
+            risk::set_asset_price(env, 2, 100);
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
             risk::set_asset_price(env, 1, 80);
 
             check_and_apply_hedge(env, user).unwrap();
 
-            let ins = get_insurance(env, user).unwrap();
-            assert_eq!(ins.active_hedges.len(), 1);
+            let hedge = get_insurance(env, user)
+                .unwrap()
+                .active_hedges
+                .get(0)
+                .unwrap();
+            assert_eq!(hedge.instrument, HedgeInstrument::InverseAsset(2));
+            assert!(hedge.correlation_bps <= -9_990);
+            // A fully inverse asset offsets twice its value: sell 2_000, not 4_000.
+            assert_eq!(hedge.amount, 2_500);
+            assert_eq!(hedge.hedge_amount, 2_000);
+            // Correlation rounds to -0.9999, leaving the delta a hair above 50%
+            assert!((portfolio_delta_bps(env, user).unwrap() - 5_000).abs() <= 1);
+        });
+        assert_eq!(s.balance(&s.inverse), 2_000);
+        assert_eq!(s.balance(&s.stable), 0);
+    }
 
-            let hedge = ins.active_hedges.get(0).unwrap();
-            // target_hedge_value = 8_000 * 5000 / 10_000 = 4_000
-            // amount = 4_000 * 100 / 80 = 5_000
-            assert_eq!(hedge.amount, 5_000);
+    #[test]
+    fn test_hedge_fails_without_asset_token() {
+        let s = setup();
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            risk::set_asset_price(env, 3, 100);
+            risk::update_position(env, user, 3, 10_000, 100);
+            calculate_drawdown(env, user).unwrap();
+            risk::set_asset_price(env, 3, 80);
+
+            let err = check_and_apply_hedge(env, user).unwrap_err();
+            assert_eq!(err, AutoTradeError::HedgeTokenNotSet);
         });
     }
 
     #[test]
     fn test_no_double_hedge() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
-
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap();
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
             risk::set_asset_price(env, 1, 80);
 
             check_and_apply_hedge(env, user).unwrap();
@@ -543,13 +1084,11 @@ mod tests {
 
     #[test]
     fn test_disabled_insurance_skips_hedge() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, false, 1500, 5000, 200).unwrap();
-
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap();
+        let s = setup();
+        s.run(|env, user| {
+            configure_insurance(env, user, false, 1500, 5000, 200, InsuranceMode::Drawdown)
+                .unwrap();
+            fund_portfolio(env, user);
             risk::set_asset_price(env, 1, 80);
 
             let ids = check_and_apply_hedge(env, user).unwrap();
@@ -561,13 +1100,13 @@ mod tests {
 
     #[test]
     fn test_rebalance_no_op_below_threshold() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 1000).unwrap(); // 10% rebalance threshold
-
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap();
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            // 10% rebalance threshold
+            configure_insurance(env, user, true, 1500, 5000, 1000, InsuranceMode::Drawdown)
+                .unwrap();
+            fund_portfolio(env, user);
             risk::set_asset_price(env, 1, 80);
             check_and_apply_hedge(env, user).unwrap();
 
@@ -580,68 +1119,310 @@ mod tests {
 
     #[test]
     fn test_rebalance_triggers_on_large_delta() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap(); // 2% threshold
-
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap();
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap(); // 2% threshold
+            fund_portfolio(env, user);
             risk::set_asset_price(env, 1, 80);
             check_and_apply_hedge(env, user).unwrap();
 
-            // Portfolio grows significantly (price recovers partially but position added)
+            // Position grows: risky 16_000 + hedge 4_000 → target exposure 10_000
             risk::update_position(env, user, 1, 20_000, 80);
             let ids = rebalance_hedges(env, user).unwrap();
             assert!(ids.len() > 0);
+
+            let ins = get_insurance(env, user).unwrap();
+            assert_eq!(ins.active_hedges.len(), 2);
+            assert_eq!(held(env, user, 1), 12_500);
+            assert_eq!(portfolio_delta_bps(env, user).unwrap(), 5_000);
         });
     }
 
+    #[test]
+    fn test_rebalance_unwinds_partially() {
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
+            risk::set_asset_price(env, 1, 80);
+            check_and_apply_hedge(env, user).unwrap();
+        });
+        // Buy back at price 80: 1 stablecoin → 1.25 units
+        s.book(12_500);
+        s.run(|env, user| {
+            // Position cut to 1_000: risky 800 + hedge 4_000 → target 2_400
+            risk::update_position(env, user, 1, 1_000, 80);
+            rebalance_hedges(env, user).unwrap();
+
+            let hedge = get_insurance(env, user)
+                .unwrap()
+                .active_hedges
+                .get(0)
+                .unwrap();
+            assert_eq!(hedge.hedge_amount, 2_400);
+            assert_eq!(hedge.amount, 3_000);
+            assert_eq!(held(env, user, 1), 3_000);
+        });
+        assert_eq!(s.balance(&s.stable), 2_400);
+    }
+
     // ── remove hedges on recovery ─────────────────────────────────────────────
 
     #[test]
     fn test_hedges_removed_on_recovery() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
-
-            risk::set_asset_price(env, 1, 100);
-            risk::update_position(env, user, 1, 10_000, 100);
-            calculate_drawdown(env, user).unwrap(); // HWM = 10_000
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
 
-            // Drop 20% → hedge opens
+            // Drop 20% → 5_000 units rotated into 4_000 stablecoin
             risk::set_asset_price(env, 1, 80);
             check_and_apply_hedge(env, user).unwrap();
-
-            let ins = get_insurance(env, user).unwrap();
-            assert!(!ins.active_hedges.is_empty());
-
-            // Recover to near HWM (drawdown < 5%)
-            risk::set_asset_price(env, 1, 99);
+        });
+        // Buy back at price 112
+        s.book(8_928);
+        s.run(|env, user| {
+            // 5_000 * 1.12 + 4_000 = 9_600 → drawdown 4%
+            risk::set_asset_price(env, 1, 112);
             let ids = remove_hedges_if_recovered(env, user).unwrap();
             assert!(ids.len() > 0);
 
             let ins = get_insurance(env, user).unwrap();
             assert!(ins.active_hedges.is_empty());
+            // 4_000 * 8_928 / 10_000 = 3_571 units bought back
+            assert_eq!(held(env, user, 1), 8_571);
+            // 3_571 units bought back for 5_000 sold, at price 112
+            assert_eq!(ins.realized_hedge_pnl, -1_600);
+        });
+        assert_eq!(s.balance(&s.stable), 0);
+        s.run(|env, user| {
+            assert_eq!(custody_balance(env, user, &s.stable), 0);
+            assert_eq!(custody_balance(env, user, &s.asset), 98_571);
+        });
+    }
+
+    #[test]
+    fn test_realized_pnl_uses_units_actually_bought_back() {
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
+            risk::set_asset_price(env, 1, 80);
+            check_and_apply_hedge(env, user).unwrap();
+        });
+        // The book fills well below the oracle price of 112: 4_000 → 2_000
+        s.book(5_000);
+        s.run(|env, user| {
+            risk::set_asset_price(env, 1, 112);
+            remove_hedges_if_recovered(env, user).unwrap();
+            assert_eq!(held(env, user, 1), 7_000);
+            // 2_000 units bought back for 5_000 sold, at price 112
+            assert_eq!(get_insurance(env, user).unwrap().realized_hedge_pnl, -3_360);
         });
     }
 
     #[test]
     fn test_hedges_not_removed_while_still_down() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
-            configure_insurance(env, user, true, 1500, 5000, 200).unwrap();
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
+
+            risk::set_asset_price(env, 1, 80);
+            check_and_apply_hedge(env, user).unwrap();
+
+            // Still 20% down — don't remove
+            let ids = remove_hedges_if_recovered(env, user).unwrap();
+            assert_eq!(ids.len(), 0);
+        });
+    }
 
+    #[test]
+    fn test_hedge_token_config_is_admin_only() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client =
+            crate::AutoTradeContractClient::new(&env, &env.register(crate::AutoTradeContract, ()));
+        let admin = Address::generate(&env);
+        let stranger = Address::generate(&env);
+        let token = Address::generate(&env);
+        client.initialize(&admin);
+
+        assert_eq!(
+            client.try_set_hedge_stablecoin(&stranger, &token),
+            Err(Ok(AutoTradeError::Unauthorized))
+        );
+        assert_eq!(
+            client.try_set_hedge_asset_token(&stranger, &1, &token, &true),
+            Err(Ok(AutoTradeError::Unauthorized))
+        );
+        client.set_hedge_stablecoin(&admin, &token);
+        client.set_hedge_asset_token(&admin, &1, &token, &true);
+    }
+
+    // ── custody ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_hedge_sells_only_custodied_units() {
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
             risk::set_asset_price(env, 1, 100);
             risk::update_position(env, user, 1, 10_000, 100);
             calculate_drawdown(env, user).unwrap();
+            deposit_collateral(env, user, &s.asset, 1_000).unwrap();
+            risk::set_asset_price(env, 1, 80);
+
+            check_and_apply_hedge(env, user).unwrap();
+
+            let hedge = get_insurance(env, user)
+                .unwrap()
+                .active_hedges
+                .get(0)
+                .unwrap();
+            // Wants 5_000 units but only 1_000 are in custody
+            assert_eq!(hedge.amount, 1_000);
+            assert_eq!(hedge.hedge_amount, 800);
+            assert_eq!(custody_balance(env, user, &s.asset), 0);
+            assert_eq!(custody_balance(env, user, &s.stable), 800);
+        });
+        assert_eq!(s.balance(&s.asset), 0);
+    }
 
+    #[test]
+    fn test_close_capped_at_custodied_instrument() {
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
             risk::set_asset_price(env, 1, 80);
             check_and_apply_hedge(env, user).unwrap();
+            // The user pulls half the stablecoin out of custody
+            withdraw_collateral(env, user, &s.stable, 2_000).unwrap();
+        });
+        s.book(8_928);
+        s.run(|env, user| {
+            risk::set_asset_price(env, 1, 112);
+            remove_hedges_if_recovered(env, user).unwrap();
+            // Only the 2_000 still held are swapped back
+            assert_eq!(held(env, user, 1), 6_785);
+            assert_eq!(custody_balance(env, user, &s.stable), 0);
+        });
+        assert_eq!(TokenClient::new(&s.env, &s.stable).balance(&s.user), 2_000);
+    }
 
-            // Still 20% down — don't remove
-            let ids = remove_hedges_if_recovered(env, user).unwrap();
-            assert_eq!(ids.len(), 0);
+    #[test]
+    fn test_withdraw_more_than_custody_rejected() {
+        let s = setup();
+        s.run(|env, user| {
+            deposit_collateral(env, user, &s.asset, 500).unwrap();
+            let err = withdraw_collateral(env, user, &s.asset, 501).unwrap_err();
+            assert_eq!(err, AutoTradeError::InsufficientBalance);
+        });
+    }
+
+    // ── hedge P&L ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_hedge_pnl_reported_separately() {
+        let s = setup();
+        s.book(8_000);
+        s.run(|env, user| {
+            configure_insurance(env, user, true, 1500, 5000, 200, InsuranceMode::Drawdown).unwrap();
+            fund_portfolio(env, user);
+            risk::set_asset_price(env, 1, 80);
+            check_and_apply_hedge(env, user).unwrap();
+
+            // Further drop: the 5_000 units sold would now be worth 3_000
+            risk::set_asset_price(env, 1, 60);
+            let pnl = get_hedge_pnl(env, user).unwrap();
+            assert_eq!(
+                pnl,
+                HedgePnl {
+                    hedge_value: 4_000,
+                    unrealized: 1_000,
+                    realized: 0,
+                }
+            );
+        });
+    }
+
+    // ── CPPI ──────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_cppi_keeps_exposure_at_cushion_multiple() {
+        let s = setup();
+        s.book(10_000);
+        s.run(|env, user| {
+            let mode = InsuranceMode::Cppi(CppiConfig {
+                floor: 6_000,
+                multiplier: 2,
+            });
+            configure_insurance(env, user, true, 1500, 5000, 200, mode).unwrap();
+            fund_portfolio(env, user);
+
+            // Cushion 4_000 × 2 → exposure 8_000, no drawdown needed
+            check_and_apply_hedge(env, user).unwrap();
+            assert_eq!(held(env, user, 1), 8_000);
+            assert_eq!(portfolio_delta_bps(env, user).unwrap(), 8_000);
+        });
+        s.book(8_000);
+        s.run(|env, user| {
+            // 6_400 + 2_000 = 8_400 → cushion 2_400 × 2 → exposure 4_800
+            risk::set_asset_price(env, 1, 80);
+            rebalance_hedges(env, user).unwrap();
+            assert_eq!(held(env, user, 1), 6_000);
+            assert_eq!(portfolio_delta_bps(env, user).unwrap(), 5_714);
+        });
+        assert_eq!(s.balance(&s.stable), 3_600);
+    }
+
+    // ── migration ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_legacy_insurance_migrates_on_load() {
+        let s = setup();
+        s.run(|env, user| {
+            let legacy = LegacyPortfolioInsurance {
+                user: user.clone(),
+                enabled: true,
+                max_drawdown_bps: 1500,
+                hedge_ratio_bps: 5000,
+                rebalance_threshold_bps: 200,
+                active_hedges: vec![
+                    env,
+                    LegacyHedgePosition {
+                        asset: 1,
+                        amount: 5_000,
+                        entry_price: 80,
+                        purpose: HedgePurpose::AssetSpecificHedge(1),
+                    },
+                ],
+                portfolio_high_water_mark: 10_000,
+            };
+            env.storage()
+                .persistent()
+                .set(&InsuranceKey::Insurance(user.clone()), &legacy);
+
+            let ins = get_insurance(env, user).unwrap();
+            assert!(ins.enabled);
+            assert_eq!(ins.max_drawdown_bps, 1500);
+            assert_eq!(ins.portfolio_high_water_mark, 10_000);
+            assert_eq!(ins.mode, InsuranceMode::Drawdown);
+            assert!(ins.active_hedges.is_empty());
+            assert_eq!(ins.realized_hedge_pnl, 0);
+            assert!(!env
+                .storage()
+                .persistent()
+                .has(&InsuranceKey::Insurance(user.clone())));
+            assert!(migrate_legacy_insurance(env, user).is_none());
         });
     }
 
@@ -649,8 +1430,8 @@ mod tests {
 
     #[test]
     fn test_drawdown_not_configured_returns_error() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
+        let s = setup();
+        s.run(|env, user| {
             let err = calculate_drawdown(env, user).unwrap_err();
             assert_eq!(err, AutoTradeError::InsuranceNotConfigured);
         });
@@ -658,8 +1439,8 @@ mod tests {
 
     #[test]
     fn test_apply_hedge_not_configured_returns_error() {
-        let (env, user) = setup();
-        register_and_run(&env, &user, |env, user| {
+        let s = setup();
+        s.run(|env, user| {
             let err = check_and_apply_hedge(env, user).unwrap_err();
             assert_eq!(err, AutoTradeError::InsuranceNotConfigured);
         });
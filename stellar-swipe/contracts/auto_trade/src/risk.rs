@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 use soroban_sdk::{contracttype, Address, Env, Map, Vec};
 
+use crate::conditional;
 use crate::errors::AutoTradeError;
 
 /// ==========================
@@ -129,7 +130,7 @@ pub fn record_price(env: &Env, asset_id: u32, price: i128) {
         .set(&RiskDataKey::AssetPriceHistoryCount(asset_id), &(count + 1));
 }
 
-fn get_price_history(env: &Env, asset_id: u32, window: u32) -> Vec<i128> {
+pub(crate) fn get_price_history(env: &Env, asset_id: u32, window: u32) -> Vec<i128> {
     let mut prices = Vec::new(env);
     let count: u32 = env
         .storage()
@@ -259,6 +260,7 @@ pub fn update_position(env: &Env, user: &Address, asset_id: u32, amount: i128, p
     env.storage()
         .persistent()
         .set(&RiskDataKey::UserPositions(user.clone()), &positions);
+    conditional::on_position_update(env, user);
 }
 
 /// ==========================
@@ -296,10 +298,13 @@ pub fn get_asset_price(env: &Env, asset_id: u32) -> Option<i128> {
         .get(&RiskDataKey::AssetPrice(asset_id))
 }
 
+/// Write the spot price of `asset_id` and refresh conditional-order drawdown
+/// peaks that depend on it.
 pub fn set_asset_price(env: &Env, asset_id: u32, price: i128) {
     env.storage()
         .temporary()
         .set(&RiskDataKey::AssetPrice(asset_id), &price);
+    conditional::on_price_update(env, asset_id);
 }
 
 /// ==========================
@@ -1,12 +1,35 @@
-#![allow(dead_code)]
-
-use soroban_sdk::{contracttype, Env, Symbol, Vec};
-
+//! Cross-venue smart order routing.
+//!
+//! The single router behind market executions. Depth is read live from every
+//! enabled AMM source registered for the signal's token pair:
+//!
+//! - `SdexRouter` sources expose their orderbook via `get_order_book`,
+//! - `StellarAmm` / `BridgePool` sources expose pool reserves via `get_reserves`,
+//! - `PathPayment` sources quote a single level via `get_best_ask`.
+//!
+//! `plan_optimal_split` splits the order across venues to minimise price
+//! impact plus fees. Execution swaps every leg within the same invocation;
+//! a leg that fails is re-planned over `build_fallback_chain` without the
+//! failed sources, and the whole execution reverts unless the combined output
+//! reaches the global `min_out`.
+
+use soroban_sdk::{contracttype, token, Address, Env, IntoVal, Symbol, Vec};
+use stellar_swipe_common::amm_bridge::{
+    build_fallback_chain, emit_fallback_used, emit_route_planned, min_amount_out_with_slippage,
+    plan_optimal_split, venue_capacity, venue_spot_price, AmmBridgeError, AmmRoutePlan,
+    AmmRouteSegment, AmmSourceConfig, AmmSourceKind, BookLevel, VenueDepth, VenueState,
+    BPS_DENOMINATOR, FN_GET_BEST_ASK, FN_GET_ORDER_BOOK, FN_GET_RESERVES, FN_SWAP,
+};
+
+use crate::amm_bridge::{self, TokenPairConfig};
 use crate::errors::AutoTradeError;
 use crate::sdex::ExecutionResult;
 use crate::storage::Signal;
 
-const BPS_DENOMINATOR: i128 = 10_000;
+/// Orderbook levels requested from SDEX routers.
+const MAX_BOOK_LEVELS: u32 = 20;
+/// Router allowance lifetime for a single swap leg (ledgers).
+const SWAP_ALLOWANCE_LEDGERS: u32 = 100;
 
 #[contracttype]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -16,6 +39,7 @@ pub enum LiquidityVenue {
     PathPayment,
 }
 
+/// Snapshot of one venue's live depth.
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct VenueLiquidity {
@@ -24,94 +48,93 @@ pub struct VenueLiquidity {
     pub available_amount: i128,
     pub price: i128,
     pub fee_bps: u32,
-    pub slippage_bps: u32,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct RouteSegment {
-    pub venue: LiquidityVenue,
-    pub venue_id: u32,
-    pub amount: i128,
-    pub execution_price: i128,
-    pub fee_amount: i128,
-    pub estimated_slippage_bps: u32,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct RoutingPlan {
-    pub requested_amount: i128,
-    pub allocated_amount: i128,
-    pub average_price: i128,
-    pub total_fees: i128,
-    pub total_cost: i128,
-    pub estimated_slippage_bps: u32,
-    pub segments: Vec<RouteSegment>,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct VenueKey {
-    venue: LiquidityVenue,
-    venue_id: u32,
 }
 
-#[contracttype]
-enum SmartRoutingKey {
-    VenueIndex(u64),
-    VenueQuote(u64, VenueKey),
-    FailVenue(u64),
-}
-
-pub fn upsert_venue_liquidity(
+/// Read live depth from every enabled source not listed in `failed`.
+pub fn read_venue_depths(
     env: &Env,
-    signal_id: u64,
-    quote: VenueLiquidity,
-) -> Result<(), AutoTradeError> {
-    if quote.available_amount <= 0 || quote.price <= 0 {
-        return Err(AutoTradeError::InvalidAmount);
-    }
-
-    let key = venue_key(&quote);
-    let mut index: Vec<VenueKey> = env
-        .storage()
-        .persistent()
-        .get(&SmartRoutingKey::VenueIndex(signal_id))
-        .unwrap_or_else(|| Vec::new(env));
-
-    if !index.contains(key.clone()) {
-        index.push_back(key.clone());
-        env.storage()
-            .persistent()
-            .set(&SmartRoutingKey::VenueIndex(signal_id), &index);
+    pair: &TokenPairConfig,
+    failed: &Vec<(AmmSourceKind, u32)>,
+) -> Vec<VenueDepth> {
+    let mut depths = Vec::new(env);
+    for source in build_fallback_chain(env, &amm_bridge::get_amm_sources(env), failed).iter() {
+        if let Some(depth) = read_depth(env, &source, pair) {
+            if venue_capacity(&depth) > 0 && venue_spot_price(&depth) > 0 {
+                depths.push_back(depth);
+            }
+        }
     }
+    depths
+}
 
-    env.storage()
-        .persistent()
-        .set(&SmartRoutingKey::VenueQuote(signal_id, key), &quote);
-
-    Ok(())
+fn read_depth(env: &Env, source: &AmmSourceConfig, pair: &TokenPairConfig) -> Option<VenueDepth> {
+    let tokens = (pair.from_token.clone(), pair.to_token.clone());
+    let (state, fee_bps) = match source.kind {
+        AmmSourceKind::SdexRouter => {
+            let levels = env
+                .try_invoke_contract::<Vec<(i128, i128)>, soroban_sdk::Error>(
+                    &source.router,
+                    &Symbol::new(env, FN_GET_ORDER_BOOK),
+                    (tokens.0, tokens.1, MAX_BOOK_LEVELS).into_val(env),
+                )
+                .ok()?
+                .ok()?;
+            let mut book = Vec::new(env);
+            for (price, amount_in) in levels.iter() {
+                if price > 0 && amount_in > 0 {
+                    book.push_back(BookLevel { price, amount_in });
+                }
+            }
+            (VenueState::OrderBook(book), 0)
+        }
+        AmmSourceKind::StellarAmm | AmmSourceKind::BridgePool => {
+            let (reserve_in, reserve_out, fee_bps) = env
+                .try_invoke_contract::<(i128, i128, u32), soroban_sdk::Error>(
+                    &source.router,
+                    &Symbol::new(env, FN_GET_RESERVES),
+                    tokens.into_val(env),
+                )
+                .ok()?
+                .ok()?;
+            (VenueState::Pool(reserve_in, reserve_out), fee_bps)
+        }
+        AmmSourceKind::PathPayment => {
+            let (price, amount_in) = env
+                .try_invoke_contract::<(i128, i128), soroban_sdk::Error>(
+                    &source.router,
+                    &Symbol::new(env, FN_GET_BEST_ASK),
+                    tokens.into_val(env),
+                )
+                .ok()?
+                .ok()?;
+            let mut book = Vec::new(env);
+            book.push_back(BookLevel { price, amount_in });
+            (VenueState::OrderBook(book), 0)
+        }
+    };
+    Some(VenueDepth {
+        kind: source.kind,
+        source_id: source.source_id,
+        fee_bps,
+        state,
+    })
 }
 
+/// Live liquidity snapshot for the venues quoting `signal_id`'s token pair.
 pub fn get_venue_liquidity(env: &Env, signal_id: u64) -> Vec<VenueLiquidity> {
-    let index: Vec<VenueKey> = env
-        .storage()
-        .persistent()
-        .get(&SmartRoutingKey::VenueIndex(signal_id))
-        .unwrap_or_else(|| Vec::new(env));
     let mut venues = Vec::new(env);
-
-    for key in index.iter() {
-        if let Some(quote) = env
-            .storage()
-            .persistent()
-            .get::<_, VenueLiquidity>(&SmartRoutingKey::VenueQuote(signal_id, key))
-        {
-            venues.push_back(quote);
-        }
+    let Some(pair) = amm_bridge::get_signal_token_pair(env, signal_id) else {
+        return venues;
+    };
+    for depth in read_venue_depths(env, &pair, &Vec::new(env)).iter() {
+        venues.push_back(VenueLiquidity {
+            venue: amm_bridge::kind_to_venue(depth.kind),
+            venue_id: depth.source_id,
+            available_amount: venue_capacity(&depth),
+            price: venue_spot_price(&depth),
+            fee_bps: depth.fee_bps,
+        });
     }
-
     venues
 }
 
@@ -120,430 +143,421 @@ pub fn plan_best_execution(
     signal: &Signal,
     requested_amount: i128,
     max_slippage_bps: u32,
-) -> Result<RoutingPlan, AutoTradeError> {
-    if requested_amount <= 0 {
-        return Err(AutoTradeError::InvalidAmount);
-    }
-
-    let venues = get_venue_liquidity(env, signal.signal_id);
-    if venues.is_empty() {
-        return Err(AutoTradeError::RoutingPlanNotFound);
-    }
-
-    let mut remaining = requested_amount;
-    let mut selected = Vec::new(env);
-
-    while remaining > 0 {
-        let mut best: Option<(RouteSegment, i128, i128)> = None;
-
-        for venue in venues.iter() {
-            let already_allocated = allocated_for(&selected, venue.venue, venue.venue_id);
-            let remaining_liquidity = venue.available_amount - already_allocated;
-            let allocation = core::cmp::min(remaining, remaining_liquidity);
-            if allocation <= 0 {
-                continue;
-            }
-
-            let slippage =
-                compute_slippage_bps(allocation, venue.available_amount, venue.slippage_bps);
-            if slippage > max_slippage_bps {
-                continue;
-            }
-
-            let price_with_slippage =
-                venue.price * (BPS_DENOMINATOR + slippage as i128) / BPS_DENOMINATOR;
-            let notional = allocation * price_with_slippage;
-            let fee_amount = apply_bps(notional, venue.fee_bps);
-            let total_cost = notional + fee_amount;
-            let segment = RouteSegment {
-                venue: venue.venue,
-                venue_id: venue.venue_id,
-                amount: allocation,
-                execution_price: price_with_slippage,
-                fee_amount,
-                estimated_slippage_bps: slippage,
-            };
-
-            match &best {
-                Some((_, best_cost, best_amount))
-                    if total_cost * *best_amount >= *best_cost * allocation => {}
-                _ => best = Some((segment, total_cost, allocation)),
-            }
-        }
-
-        let Some((best_segment, _, _)) = best else {
-            return Err(AutoTradeError::InsufficientLiquidity);
-        };
-
-        remaining -= best_segment.amount;
-        selected.push_back(best_segment);
-    }
-
-    finalize_plan(
+) -> Result<AmmRoutePlan, AutoTradeError> {
+    let pair = amm_bridge::get_signal_token_pair(env, signal.signal_id)
+        .ok_or(AutoTradeError::RoutingPlanNotFound)?;
+    plan_pair_route(
         env,
-        signal.price,
+        signal.signal_id,
+        &pair,
         requested_amount,
-        selected,
         max_slippage_bps,
     )
 }
 
+/// Plan `amount` of `pair` across live venues. `route_id` tags events: the
+/// signal id, or `0` for trades not driven by a signal.
+pub fn plan_pair_route(
+    env: &Env,
+    route_id: u64,
+    pair: &TokenPairConfig,
+    amount: i128,
+    max_slippage_bps: u32,
+) -> Result<AmmRoutePlan, AutoTradeError> {
+    if amount <= 0 {
+        return Err(AutoTradeError::InvalidAmount);
+    }
+    let depths = read_venue_depths(env, pair, &Vec::new(env));
+    let plan =
+        plan_optimal_split(env, &depths, amount, max_slippage_bps).map_err(map_bridge_error)?;
+    emit_route_planned(env, route_id, &plan);
+    Ok(plan)
+}
+
+/// Plan and execute `requested_amount`, reverting unless at least `min_out`
+/// is received across all legs.
 pub fn execute_best_route(
     env: &Env,
     signal: &Signal,
     requested_amount: i128,
+    min_out: i128,
     max_slippage_bps: u32,
 ) -> Result<ExecutionResult, AutoTradeError> {
     let plan = plan_best_execution(env, signal, requested_amount, max_slippage_bps)?;
-    execute_plan_atomically(env, signal.signal_id, &plan)
+    execute_plan(env, signal.signal_id, &plan, min_out, max_slippage_bps)
 }
 
-pub fn execute_plan_atomically(
+/// Execute a signal's plan; see `execute_pair_plan`.
+pub fn execute_plan(
     env: &Env,
     signal_id: u64,
-    plan: &RoutingPlan,
+    plan: &AmmRoutePlan,
+    min_out: i128,
+    max_slippage_bps: u32,
 ) -> Result<ExecutionResult, AutoTradeError> {
-    let fail_key: Option<VenueKey> = env
-        .storage()
-        .temporary()
-        .get(&SmartRoutingKey::FailVenue(signal_id));
-
-    for segment in plan.segments.iter() {
-        let key = VenueKey {
-            venue: segment.venue,
-            venue_id: segment.venue_id,
-        };
-        let stored = env
-            .storage()
-            .persistent()
-            .get::<_, VenueLiquidity>(&SmartRoutingKey::VenueQuote(signal_id, key.clone()))
-            .ok_or(AutoTradeError::AtomicExecutionFailed)?;
-
-        if stored.available_amount < segment.amount || stored.price <= 0 {
-            return Err(AutoTradeError::AtomicExecutionFailed);
-        }
-
-        if fail_key.as_ref() == Some(&key) {
-            return Err(AutoTradeError::AtomicExecutionFailed);
-        }
-    }
-
-    for segment in plan.segments.iter() {
-        let key = VenueKey {
-            venue: segment.venue,
-            venue_id: segment.venue_id,
-        };
-        let mut stored = env
-            .storage()
-            .persistent()
-            .get::<_, VenueLiquidity>(&SmartRoutingKey::VenueQuote(signal_id, key.clone()))
-            .ok_or(AutoTradeError::AtomicExecutionFailed)?;
-
-        stored.available_amount -= segment.amount;
-        env.storage()
-            .persistent()
-            .set(&SmartRoutingKey::VenueQuote(signal_id, key), &stored);
-    }
-
-    #[allow(deprecated)]
-    env.events().publish(
-        (Symbol::new(env, "smart_route_executed"), signal_id),
-        plan.clone(),
-    );
-
+    let pair = amm_bridge::get_signal_token_pair(env, signal_id)
+        .ok_or(AutoTradeError::RoutingPlanNotFound)?;
+    let total_out = execute_pair_plan(env, signal_id, &pair, plan, min_out, max_slippage_bps)?;
     Ok(ExecutionResult {
-        executed_amount: plan.allocated_amount,
-        executed_price: plan.average_price,
+        executed_amount: plan.amount_in,
+        executed_price: total_out * BPS_DENOMINATOR / plan.amount_in,
     })
 }
 
-pub fn set_execution_failure(env: &Env, signal_id: u64, venue: LiquidityVenue, venue_id: u32) {
-    env.storage().temporary().set(
-        &SmartRoutingKey::FailVenue(signal_id),
-        &VenueKey { venue, venue_id },
-    );
-}
-
-pub fn debit_venue_liquidity(
+/// Swap `amount` of `pair.from_token` held by the contract into
+/// `pair.to_token`, accepting up to `max_slippage_bps` below the planned
+/// output. Returns the amount received.
+pub fn swap_pair(
     env: &Env,
-    signal_id: u64,
-    venue: LiquidityVenue,
-    venue_id: u32,
+    route_id: u64,
+    pair: &TokenPairConfig,
     amount: i128,
-) -> Result<(), AutoTradeError> {
-    let key = VenueKey { venue, venue_id };
-    let mut stored = env
-        .storage()
-        .persistent()
-        .get::<_, VenueLiquidity>(&SmartRoutingKey::VenueQuote(signal_id, key.clone()))
-        .ok_or(AutoTradeError::AtomicExecutionFailed)?;
-
-    if stored.available_amount < amount {
-        return Err(AutoTradeError::AtomicExecutionFailed);
-    }
-
-    stored.available_amount -= amount;
-    env.storage()
-        .persistent()
-        .set(&SmartRoutingKey::VenueQuote(signal_id, key), &stored);
-    Ok(())
+    max_slippage_bps: u32,
+) -> Result<i128, AutoTradeError> {
+    let plan = plan_pair_route(env, route_id, pair, amount, max_slippage_bps)?;
+    let min_out = min_amount_out_with_slippage(plan.amount_out, max_slippage_bps).unwrap_or(0);
+    execute_pair_plan(env, route_id, pair, &plan, min_out, max_slippage_bps)
 }
 
-fn finalize_plan(
+/// Swap every leg of `plan`. Failed legs are re-routed over the remaining
+/// sources; any error, or a total below `min_out`, reverts every leg.
+/// Returns the total received.
+pub fn execute_pair_plan(
     env: &Env,
-    reference_price: i128,
-    requested_amount: i128,
-    segments: Vec<RouteSegment>,
+    route_id: u64,
+    pair: &TokenPairConfig,
+    plan: &AmmRoutePlan,
+    min_out: i128,
     max_slippage_bps: u32,
-) -> Result<RoutingPlan, AutoTradeError> {
-    let mut allocated_amount = 0i128;
-    let mut total_notional = 0i128;
-    let mut total_fees = 0i128;
-
-    for segment in segments.iter() {
-        allocated_amount += segment.amount;
-        total_notional += segment.amount * segment.execution_price;
-        total_fees += segment.fee_amount;
-    }
+) -> Result<i128, AutoTradeError> {
+    let mut failed: Vec<(AmmSourceKind, u32)> = Vec::new(env);
+    let mut total_out = 0i128;
+    let mut pending = execute_segments(
+        env,
+        route_id,
+        pair,
+        &plan.segments,
+        &mut failed,
+        &mut total_out,
+    );
 
-    if allocated_amount != requested_amount {
-        return Err(AutoTradeError::InsufficientLiquidity);
+    while pending > 0 {
+        let depths = read_venue_depths(env, pair, &failed);
+        if depths.is_empty() {
+            return Err(AutoTradeError::AtomicExecutionFailed);
+        }
+        let replan = plan_optimal_split(env, &depths, pending, max_slippage_bps)
+            .map_err(|_| AutoTradeError::AtomicExecutionFailed)?;
+        pending = execute_segments(
+            env,
+            route_id,
+            pair,
+            &replan.segments,
+            &mut failed,
+            &mut total_out,
+        );
     }
 
-    let average_price = if allocated_amount == 0 {
-        0
-    } else {
-        total_notional / allocated_amount
-    };
-
-    let estimated_slippage_bps = if reference_price <= 0 || average_price <= reference_price {
-        0
-    } else {
-        (((average_price - reference_price) * BPS_DENOMINATOR) / reference_price) as u32
-    };
-
-    if estimated_slippage_bps > max_slippage_bps {
+    if total_out < min_out {
         return Err(AutoTradeError::SlippageExceeded);
     }
 
-    Ok(RoutingPlan {
-        requested_amount,
-        allocated_amount,
-        average_price,
-        total_fees,
-        total_cost: total_notional + total_fees,
-        estimated_slippage_bps,
-        segments,
-    })
+    #[allow(deprecated)]
+    env.events().publish(
+        (Symbol::new(env, "smart_route_executed"), route_id),
+        (plan.amount_in, total_out, failed.len()),
+    );
+
+    Ok(total_out)
 }
 
-fn venue_key(quote: &VenueLiquidity) -> VenueKey {
-    VenueKey {
-        venue: quote.venue,
-        venue_id: quote.venue_id,
+/// Swap each segment, recording output; returns the input left unrouted by
+/// failed legs.
+fn execute_segments(
+    env: &Env,
+    route_id: u64,
+    pair: &TokenPairConfig,
+    segments: &Vec<AmmRouteSegment>,
+    failed: &mut Vec<(AmmSourceKind, u32)>,
+    total_out: &mut i128,
+) -> i128 {
+    let mut pending = 0i128;
+    for segment in segments.iter() {
+        match swap_segment(env, pair, &segment) {
+            Some(out) => *total_out += out,
+            None => {
+                failed.push_back((segment.kind, segment.source_id));
+                emit_fallback_used(env, route_id, segment.kind, segment.source_id);
+                pending += segment.amount_in;
+            }
+        }
     }
+    pending
 }
 
-fn compute_slippage_bps(amount: i128, liquidity: i128, max_slippage_bps: u32) -> u32 {
-    if liquidity <= 0 {
-        return u32::MAX;
-    }
+fn swap_segment(env: &Env, pair: &TokenPairConfig, segment: &AmmRouteSegment) -> Option<i128> {
+    let source = find_source(env, segment.kind, segment.source_id)?;
+    let this = env.current_contract_address();
+    let expiry = env
+        .ledger()
+        .sequence()
+        .saturating_add(SWAP_ALLOWANCE_LEDGERS);
+    token::Client::new(env, &pair.from_token).approve(
+        &this,
+        &source.router,
+        &segment.amount_in,
+        &expiry,
+    );
 
-    let raw = (amount * max_slippage_bps as i128 + liquidity - 1) / liquidity;
-    raw as u32
+    // The router enforces the leg's own `min_amount_out`; what the leg paid
+    // is read from our own `to_token` balance, not the router's return value.
+    let to_token = token::Client::new(env, &pair.to_token);
+    let before = to_token.balance(&this);
+    env.try_invoke_contract::<i128, soroban_sdk::Error>(
+        &source.router,
+        &Symbol::new(env, FN_SWAP),
+        (
+            this.clone(),
+            pair.from_token.clone(),
+            pair.to_token.clone(),
+            segment.amount_in,
+            segment.min_amount_out,
+            this.clone(),
+        )
+            .into_val(env),
+    )
+    .ok()?
+    .ok()?;
+    Some(to_token.balance(&this) - before)
 }
 
-fn apply_bps(value: i128, bps: u32) -> i128 {
-    (value * bps as i128 + (BPS_DENOMINATOR - 1)) / BPS_DENOMINATOR
+fn find_source(env: &Env, kind: AmmSourceKind, source_id: u32) -> Option<AmmSourceConfig> {
+    amm_bridge::get_amm_sources(env)
+        .iter()
+        .find(|src| src.kind == kind && src.source_id == source_id && src.enabled)
 }
 
-fn allocated_for(segments: &Vec<RouteSegment>, venue: LiquidityVenue, venue_id: u32) -> i128 {
-    let mut allocated = 0i128;
-    for segment in segments.iter() {
-        if segment.venue == venue && segment.venue_id == venue_id {
-            allocated += segment.amount;
+pub fn map_bridge_error(err: AmmBridgeError) -> AutoTradeError {
+    match err {
+        AmmBridgeError::InvalidAmount => AutoTradeError::InvalidAmount,
+        AmmBridgeError::NoLiquidity | AmmBridgeError::SourceUnavailable => {
+            AutoTradeError::InsufficientLiquidity
         }
+        AmmBridgeError::SlippageExceeded => AutoTradeError::SlippageExceeded,
+        AmmBridgeError::RouteNotFound => AutoTradeError::RoutingPlanNotFound,
+        AmmBridgeError::ExecutionFailed => AutoTradeError::AtomicExecutionFailed,
     }
-    allocated
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use soroban_sdk::{contract, testutils::Ledger as _, Address, Env};
+    use crate::amm_bridge::mock_router::{MockAmmRouter, MockAmmRouterClient};
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger as _},
+        token::StellarAssetClient,
+        vec, Env,
+    };
 
     #[contract]
     struct TestContract;
 
-    fn setup_env() -> (Env, Address) {
+    const SIGNAL: u64 = 7;
+
+    struct Setup {
+        env: Env,
+        contract: Address,
+        pair: TokenPairConfig,
+    }
+
+    fn setup() -> Setup {
         let env = Env::default();
+        env.mock_all_auths();
         env.ledger().set_timestamp(1_000);
-        let contract_id = env.register(TestContract, ());
-        (env, contract_id)
+        let contract = env.register(TestContract, ());
+        let issuer = Address::generate(&env);
+        let pair = TokenPairConfig {
+            from_token: env
+                .register_stellar_asset_contract_v2(issuer.clone())
+                .address(),
+            to_token: env.register_stellar_asset_contract_v2(issuer).address(),
+        };
+        StellarAssetClient::new(&env, &pair.from_token).mint(&contract, &1_000_000);
+        env.as_contract(&contract, || {
+            amm_bridge::set_signal_token_pair(
+                &env,
+                SIGNAL,
+                pair.from_token.clone(),
+                pair.to_token.clone(),
+            );
+        });
+        Setup {
+            env,
+            contract,
+            pair,
+        }
     }
 
-    fn signal(id: u64) -> Signal {
+    fn signal() -> Signal {
         Signal {
-            signal_id: id,
+            signal_id: SIGNAL,
             price: 100,
             expiry: 5_000,
             base_asset: 1,
         }
     }
 
-    fn quote(
-        venue: LiquidityVenue,
-        venue_id: u32,
-        available_amount: i128,
-        price: i128,
-        fee_bps: u32,
-        slippage_bps: u32,
-    ) -> VenueLiquidity {
-        VenueLiquidity {
-            venue,
-            venue_id,
-            available_amount,
-            price,
-            fee_bps,
-            slippage_bps,
-        }
-    }
-
-    #[test]
-    fn chooses_best_price_across_venues() {
-        let (env, contract_id) = setup_env();
-        let signal = signal(7);
-
-        env.as_contract(&contract_id, || {
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Sdex, 1, 100, 101, 5, 30),
-            )
-            .unwrap();
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Pool, 2, 100, 99, 10, 10),
+    /// Register a funded mock router as source `source_id`.
+    fn router(s: &Setup, kind: AmmSourceKind, source_id: u32) -> MockAmmRouterClient<'static> {
+        let id = s.env.register(MockAmmRouter, ());
+        StellarAssetClient::new(&s.env, &s.pair.to_token).mint(&id, &1_000_000);
+        s.env.as_contract(&s.contract, || {
+            amm_bridge::register_amm_source(
+                &s.env,
+                AmmSourceConfig {
+                    kind,
+                    source_id,
+                    router: id.clone(),
+                    priority: source_id,
+                    enabled: true,
+                },
             )
             .unwrap();
+        });
+        MockAmmRouterClient::new(&s.env, &id)
+    }
 
-            let plan = plan_best_execution(&env, &signal, 50, 100).unwrap();
+    fn received(s: &Setup) -> i128 {
+        token::Client::new(&s.env, &s.pair.to_token).balance(&s.contract)
+    }
 
-            assert_eq!(plan.allocated_amount, 50);
-            assert_eq!(plan.segments.len(), 1);
-            assert_eq!(plan.segments.get(0).unwrap().venue, LiquidityVenue::Pool);
+    #[test]
+    fn reads_live_book_and_reserves() {
+        let s = setup();
+        router(&s, AmmSourceKind::SdexRouter, 1).set_order_book(&vec![
+            &s.env,
+            (10_000, 30_000),
+            (9_900, 20_000),
+        ]);
+        router(&s, AmmSourceKind::StellarAmm, 2).set_reserves(&200_000, &200_000, &30);
+
+        s.env.as_contract(&s.contract, || {
+            let venues = get_venue_liquidity(&s.env, SIGNAL);
+            assert_eq!(venues.len(), 2);
+            let sdex = venues.get(0).unwrap();
+            assert_eq!(sdex.venue, LiquidityVenue::Sdex);
+            assert_eq!(sdex.available_amount, 50_000);
+            assert_eq!(sdex.price, 10_000);
+            let pool = venues.get(1).unwrap();
+            assert_eq!(pool.venue, LiquidityVenue::Pool);
+            assert_eq!(pool.available_amount, 200_000);
+            assert_eq!(pool.fee_bps, 30);
         });
     }
 
     #[test]
-    fn splits_across_multiple_venues_when_needed() {
-        let (env, contract_id) = setup_env();
-        let signal = signal(8);
-
-        env.as_contract(&contract_id, || {
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Pool, 1, 40, 99, 5, 10),
-            )
-            .unwrap();
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Sdex, 2, 35, 100, 5, 10),
-            )
-            .unwrap();
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::PathPayment, 3, 50, 102, 5, 10),
-            )
-            .unwrap();
-
-            let plan = plan_best_execution(&env, &signal, 90, 100).unwrap();
-
-            assert_eq!(plan.allocated_amount, 90);
-            assert_eq!(plan.segments.len(), 3);
-            assert_eq!(plan.segments.get(0).unwrap().venue, LiquidityVenue::Pool);
-            assert_eq!(plan.segments.get(1).unwrap().venue, LiquidityVenue::Sdex);
-            assert_eq!(
-                plan.segments.get(2).unwrap().venue,
-                LiquidityVenue::PathPayment
-            );
+    fn splits_across_sdex_and_pool() {
+        let s = setup();
+        router(&s, AmmSourceKind::SdexRouter, 1).set_order_book(&vec![&s.env, (10_000, 30_000)]);
+        router(&s, AmmSourceKind::StellarAmm, 2).set_reserves(&200_000, &200_000, &30);
+
+        s.env.as_contract(&s.contract, || {
+            let plan = plan_best_execution(&s.env, &signal(), 50_000, 2_000).unwrap();
+            assert_eq!(plan.segments.len(), 2);
+            assert_eq!(plan.segments.get(0).unwrap().amount_in, 30_000);
+            assert_eq!(plan.segments.get(1).unwrap().amount_in, 20_000);
+
+            let result = execute_plan(&s.env, SIGNAL, &plan, plan.amount_out, 2_000).unwrap();
+            assert_eq!(result.executed_amount, 50_000);
         });
+        let out = received(&s);
+        assert!(out >= 30_000 + 18_000);
+        assert_eq!(
+            token::Client::new(&s.env, &s.pair.from_token).balance(&s.contract),
+            950_000
+        );
     }
 
     #[test]
-    fn rejects_routes_that_exceed_slippage() {
-        let (env, contract_id) = setup_env();
-        let signal = signal(9);
-
-        env.as_contract(&contract_id, || {
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Sdex, 1, 100, 100, 0, 800),
-            )
-            .unwrap();
-
-            let err = plan_best_execution(&env, &signal, 100, 50).unwrap_err();
-            assert_eq!(err, AutoTradeError::InsufficientLiquidity);
+    fn failed_leg_falls_back_to_remaining_sources() {
+        let s = setup();
+        router(&s, AmmSourceKind::SdexRouter, 1).set_order_book(&vec![&s.env, (10_000, 60_000)]);
+        let bad = router(&s, AmmSourceKind::StellarAmm, 2);
+        bad.set_reserves(&1_000_000, &1_100_000, &0);
+        bad.set_fail_swap(&true);
+
+        s.env.as_contract(&s.contract, || {
+            let plan = plan_best_execution(&s.env, &signal(), 50_000, 500).unwrap();
+            assert!(plan
+                .segments
+                .iter()
+                .any(|seg| seg.kind == AmmSourceKind::StellarAmm));
+
+            let result = execute_plan(&s.env, SIGNAL, &plan, 49_000, 500).unwrap();
+            assert_eq!(result.executed_amount, 50_000);
         });
+        // The whole order ended up on the book.
+        assert_eq!(received(&s), 50_000);
     }
 
     #[test]
-    fn fails_when_total_liquidity_is_too_low() {
-        let (env, contract_id) = setup_env();
-        let signal = signal(10);
+    fn reverts_below_global_min_out() {
+        let s = setup();
+        router(&s, AmmSourceKind::SdexRouter, 1).set_order_book(&vec![&s.env, (10_000, 60_000)]);
 
-        env.as_contract(&contract_id, || {
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Sdex, 1, 20, 100, 0, 10),
-            )
-            .unwrap();
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Pool, 2, 20, 101, 0, 10),
-            )
-            .unwrap();
+        let err = s.env.as_contract(&s.contract, || {
+            execute_best_route(&s.env, &signal(), 50_000, 50_001, 500).unwrap_err()
+        });
+        assert_eq!(err, AutoTradeError::SlippageExceeded);
+    }
 
-            let err = plan_best_execution(&env, &signal, 50, 100).unwrap_err();
-            assert_eq!(err, AutoTradeError::InsufficientLiquidity);
+    #[test]
+    fn output_is_measured_from_balance_not_router_report() {
+        let s = setup();
+        let liar = router(&s, AmmSourceKind::SdexRouter, 1);
+        liar.set_order_book(&vec![&s.env, (10_000, 60_000)]);
+        liar.set_reported_out(&1_000_000);
+
+        let result = s.env.as_contract(&s.contract, || {
+            execute_best_route(&s.env, &signal(), 50_000, 0, 500).unwrap()
         });
+        assert_eq!(result.executed_price, BPS_DENOMINATOR);
+        assert_eq!(received(&s), 50_000);
     }
 
     #[test]
-    fn atomic_execution_rolls_back_on_failure() {
-        let (env, contract_id) = setup_env();
-        let signal = signal(11);
+    fn errors_when_every_source_fails() {
+        let s = setup();
+        let bad = router(&s, AmmSourceKind::SdexRouter, 1);
+        bad.set_order_book(&vec![&s.env, (10_000, 60_000)]);
+        bad.set_fail_swap(&true);
+
+        let err = s.env.as_contract(&s.contract, || {
+            execute_best_route(&s.env, &signal(), 50_000, 0, 500).unwrap_err()
+        });
+        assert_eq!(err, AutoTradeError::AtomicExecutionFailed);
+    }
 
-        env.as_contract(&contract_id, || {
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Sdex, 1, 60, 100, 0, 10),
-            )
-            .unwrap();
-            upsert_venue_liquidity(
-                &env,
-                signal.signal_id,
-                quote(LiquidityVenue::Pool, 2, 60, 101, 0, 10),
+    #[test]
+    fn rejects_routes_that_exceed_slippage() {
+        let s = setup();
+        router(&s, AmmSourceKind::StellarAmm, 1).set_reserves(&100_000, &100_000, &0);
+
+        s.env.as_contract(&s.contract, || {
+            let err = plan_best_execution(&s.env, &signal(), 50_000, 100).unwrap_err();
+            assert_eq!(err, AutoTradeError::SlippageExceeded);
+            let err = plan_best_execution(
+                &s.env,
+                &Signal {
+                    signal_id: 8,
+                    ..signal()
+                },
+                10,
+                100,
             )
-            .unwrap();
-
-            let plan = plan_best_execution(&env, &signal, 100, 100).unwrap();
-            set_execution_failure(&env, signal.signal_id, LiquidityVenue::Pool, 2);
-
-            let err = execute_plan_atomically(&env, signal.signal_id, &plan).unwrap_err();
-            assert_eq!(err, AutoTradeError::AtomicExecutionFailed);
-
-            let venues = get_venue_liquidity(&env, signal.signal_id);
-            assert_eq!(venues.get(0).unwrap().available_amount, 60);
-            assert_eq!(venues.get(1).unwrap().available_amount, 60);
+            .unwrap_err();
+            assert_eq!(err, AutoTradeError::RoutingPlanNotFound);
         });
     }
 }
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use soroban_sdk::{contracttype, symbol_short, Address, Env};
 
-use crate::auth::{AuthConfig, AuthKey};
+use crate::auth;
 
 #[contracttype]
 #[derive(Clone)]
@@ -25,6 +25,8 @@ pub enum DataKey {
     Trades(Address, u64),
     Signal(u64),
     RateLimitInfo(Address),
+    /// Provider a signal copies; the counterparty of copy trades.
+    SignalProvider(u64),
 }
 
 /// Get a signal by ID
@@ -37,6 +39,16 @@ pub fn set_signal(env: &Env, id: u64, signal: &Signal) {
     env.storage().persistent().set(&DataKey::Signal(id), signal);
 }
 
+pub fn get_signal_provider(env: &Env, id: u64) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::SignalProvider(id))
+}
+
+pub fn set_signal_provider(env: &Env, id: u64, provider: &Address) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::SignalProvider(id), provider);
+}
+
 /// Test helper: auth plus max temporary SDEX balance.
 pub fn authorize_user(env: &Env, user: &Address) {
     authorize_user_with_limits(env, user, i128::MAX / 4, 30);
@@ -45,31 +57,22 @@ pub fn authorize_user(env: &Env, user: &Address) {
         .set(&(user.clone(), symbol_short!("balance")), &i128::MAX);
 }
 
-/// Authorize a user with explicit limits.
+/// Authorize a user with explicit limits (replaces their default policy).
 pub fn authorize_user_with_limits(
     env: &Env,
     user: &Address,
     max_trade_amount: i128,
     duration_days: u32,
 ) {
-    let config = AuthConfig {
-        authorized: true,
-        max_trade_amount,
-        expires_at: env.ledger().timestamp() + (duration_days as u64 * 86400),
-        granted_at: env.ledger().timestamp(),
-    };
-    env.storage()
-        .persistent()
-        .set(&AuthKey::Authorization(user.clone()), &config);
+    auth::set_default_policy(env, user, max_trade_amount, duration_days)
+        .expect("invalid test authorization");
     env.storage()
         .temporary()
         .set(&(user.clone(), symbol_short!("balance")), &i128::MAX);
 }
 
 pub fn revoke_user_authorization(env: &Env, user: &Address) {
-    env.storage()
-        .persistent()
-        .remove(&AuthKey::Authorization(user.clone()));
+    auth::revoke_all(env, user);
 }
 
 /// Get the stored rate-limit info for a user, if any.
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
 
+use crate::auth::{self, Spend, StrategyKind};
 use crate::errors::AutoTradeError;
 
 const PRECISION: i128 = 1_000_000;
@@ -242,6 +243,18 @@ pub fn execute_dca_purchase(env: &Env, id: u64) -> Result<(), AutoTradeError> {
         return Err(AutoTradeError::InsufficientBalance);
     }
 
+    auth::spend(
+        env,
+        &s.user,
+        None,
+        &Spend {
+            strategy: StrategyKind::Dca,
+            asset: Some(s.asset_pair),
+            counterparty: None,
+            amount: s.purchase_amount,
+        },
+    )?;
+
     let (acquired, price) = sim_execute_buy(env, s.asset_pair, s.purchase_amount)?;
 
     s.total_invested += s.purchase_amount;
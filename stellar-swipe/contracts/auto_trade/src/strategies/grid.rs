@@ -2,6 +2,7 @@
 
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol, Vec};
 
+use crate::auth::{self, Spend, StrategyKind};
 use crate::errors::AutoTradeError;
 
 // ── Types ─────────────────────────────────────────────────────────────────────
@@ -156,6 +157,23 @@ fn cancel_order(_env: &Env, _order_id: u64) {
     // On-chain cancellation would call SDEX; stubbed here.
 }
 
+/// Charge a new grid order to the user's spending policies. Orders are paid
+/// for when placed, so a policy cap stops the grid before an order exists.
+fn fund_order(env: &Env, strategy: &GridStrategy) -> Result<(), AutoTradeError> {
+    auth::spend(
+        env,
+        &strategy.user,
+        None,
+        &Spend {
+            strategy: StrategyKind::Grid,
+            asset: Some(strategy.asset_pair),
+            counterparty: None,
+            amount: strategy.grid_config.order_size_per_grid,
+        },
+    )?;
+    Ok(())
+}
+
 // ── Public API ────────────────────────────────────────────────────────────────
 
 /// Initialise a new grid strategy and return its id.
@@ -224,6 +242,7 @@ pub fn place_grid_orders(env: &Env, strategy_id: u64) -> Result<(), AutoTradeErr
             continue; // skip exact current price
         };
 
+        fund_order(env, &strategy)?;
         let order_id = place_limit_order(
             env,
             &strategy.user,
@@ -287,6 +306,11 @@ pub fn on_grid_order_filled(
         .get(level)
         .ok_or(AutoTradeError::SignalNotFound)?
         .clone();
+    // The policy paid for the order's size when it was placed.
+    if fill_amount <= 0 || fill_amount > filled_order.amount {
+        return Err(AutoTradeError::InvalidAmount);
+    }
+
     strategy.active_orders.remove(level);
 
     strategy.filled_orders.push_back(FilledGridOrder {
@@ -334,6 +358,7 @@ pub fn rebalance_grid_level(
         OrderSide::Sell
     };
 
+    fund_order(env, &strategy)?;
     let order_id = place_limit_order(
         env,
         &strategy.user,
@@ -570,6 +595,7 @@ mod tests {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             // current price = 1_500 (mid-range) → 5 buys below, 4 sells above, skip exact mid
             set_mock_price(&env, 1, 1_500);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
@@ -588,6 +614,7 @@ mod tests {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             // Set price exactly at lower_price so level 0 is skipped
             set_mock_price(&env, 1, 1_000);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
@@ -599,11 +626,35 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_place_grid_orders_charged_to_policy() {
+        let (env, contract_id) = setup();
+        env.as_contract(&contract_id, || {
+            let user = Address::generate(&env);
+            set_mock_price(&env, 1, 1_500);
+            let id =
+                initialize_grid_strategy(&env, user.clone(), 1, 2_000, 1_000, 10, 10_000).unwrap();
+            assert!(place_grid_orders(&env, id).is_err());
+
+            // Room for nine orders of 1_000 but not ten.
+            auth::set_default_policy(&env, &user, 1_000, 30).unwrap();
+            let policy = auth::get_default_policy(&env, &user).unwrap();
+            let mut capped = policy.clone();
+            capped.total_cap = 9_000;
+            env.storage()
+                .persistent()
+                .set(&auth::AuthKey::Policy(policy.id), &capped);
+            assert!(place_grid_orders(&env, id).is_err());
+            assert!(load(&env, id).unwrap().active_orders.is_empty());
+        });
+    }
+
     #[test]
     fn test_order_fill_and_rebalance() {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             set_mock_price(&env, 1, 1_500);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
             place_grid_orders(&env, id).unwrap();
@@ -630,6 +681,7 @@ mod tests {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             set_mock_price(&env, 1, 1_500);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
             place_grid_orders(&env, id).unwrap();
@@ -657,6 +709,7 @@ mod tests {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             set_mock_price(&env, 1, 1_500);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
             place_grid_orders(&env, id).unwrap();
@@ -678,6 +731,7 @@ mod tests {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             set_mock_price(&env, 1, 1_500);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
             place_grid_orders(&env, id).unwrap();
@@ -698,6 +752,7 @@ mod tests {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             set_mock_price(&env, 1, 1_500);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
             place_grid_orders(&env, id).unwrap();
@@ -714,6 +769,7 @@ mod tests {
         let (env, contract_id) = setup();
         env.as_contract(&contract_id, || {
             let user = Address::generate(&env);
+            crate::storage::authorize_user(&env, &user);
             set_mock_price(&env, 1, 1_500);
             let id = initialize_grid_strategy(&env, user, 1, 2_000, 1_000, 10, 10_000).unwrap();
             place_grid_orders(&env, id).unwrap();
@@ -114,7 +114,10 @@ fn calculate_rate_of_change(prices: &Vec<i128>, period_days: u32) -> Result<i128
 ///
 /// RSI = 100 * (Average Gain / (Average Gain + Average Loss))
 /// Returns value in 0-10000 range (0-100%)
-fn calculate_rsi_from_prices(prices: &Vec<i128>, period: u32) -> Result<u32, AutoTradeError> {
+pub(crate) fn calculate_rsi_from_prices(
+    prices: &Vec<i128>,
+    period: u32,
+) -> Result<u32, AutoTradeError> {
     let period_len = period as usize;
     if prices.len() < (period_len as u32) + 1 {
         return Err(AutoTradeError::InsufficientPriceHistory);
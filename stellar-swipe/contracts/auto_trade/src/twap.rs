@@ -1,8 +1,14 @@
 #![allow(dead_code)]
 
+use crate::auth::{self, Spend, StrategyKind};
 use crate::errors::AutoTradeError;
+use crate::{risk, smart_routing};
 use soroban_sdk::{contracttype, Address, Env, String, Symbol, Vec};
 
+/// Oracle samples averaged into the TWAP that the price band is measured from.
+const BAND_TWAP_SAMPLES: u32 = 10;
+const BPS_DENOMINATOR: i128 = 10_000;
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TWAPStatus {
@@ -19,6 +25,44 @@ pub struct AssetPair {
     pub quote: String,
 }
 
+/// How each slice is sized.
+///
+/// - `Twap`: equal slices of `total_amount / total_segments`.
+/// - `Vwap`: each slice takes the share of what is left that the volume
+///   observed now bears to the volume expected over the remaining slices,
+///   estimated from the average observed so far. A slice with no observed
+///   volume is skipped and its share carried forward; the last slice sweeps
+///   up whatever remains.
+/// - `Participation(rate_bps)`: each slice is at most `rate_bps` of the venue
+///   liquidity observed at that moment; slices continue past
+///   `total_segments` until the order is filled or its deadline passes.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TWAPMode {
+    Twap,
+    Vwap,
+    Participation(u32),
+}
+
+/// Market inputs for an adaptive order.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdaptiveTWAPConfig {
+    pub mode: TWAPMode,
+    /// Oracle feed slices are priced from and the band is measured against.
+    /// Must be the asset registered for the order's pair.
+    pub oracle_asset: u32,
+    /// Signal whose venue quotes (`smart_routing`) give the observed volume.
+    /// Required for `Vwap` and `Participation`.
+    pub liquidity_signal: Option<u64>,
+    /// Hold a slice while spot is more than this far from the oracle TWAP;
+    /// `0` disables the band.
+    pub band_bps: u32,
+    /// Participation orders stop slicing this long after creation, filled or
+    /// not. Must cover `duration_minutes`; ignored by the other modes.
+    pub deadline_minutes: u32,
+}
+
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct TWAPOrder {
@@ -38,6 +82,27 @@ pub struct TWAPOrder {
     pub status: TWAPStatus,
 }
 
+/// Adaptive state kept beside a `TWAPOrder` under `TWAPStorageKey::Adaptive`,
+/// so plain orders keep their original layout.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdaptiveTWAP {
+    pub mode: TWAPMode,
+    pub oracle_asset: u32,
+    pub liquidity_signal: Option<u64>,
+    pub band_bps: u32,
+    /// Oracle spot when the order was created.
+    pub arrival_price: i128,
+    /// Participation deadline; the schedule end for the other modes.
+    pub deadline: u64,
+    /// Sum and count of the volume samples taken for `Vwap` slices.
+    pub observed_volume: i128,
+    pub volume_samples: u32,
+    /// Average fill price against `arrival_price` in bps, set on completion.
+    /// Positive means the order paid more than the arrival price.
+    pub shortfall_bps: i128,
+}
+
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct CancellationSummary {
@@ -53,9 +118,24 @@ pub enum TWAPStorageKey {
     Order(u64),
     ActiveOrders,
     PriceHistory(AssetPair),
+    /// Asset id a pair's orders are charged against in spending policies.
+    PairAsset(AssetPair),
+    Adaptive(u64),
 }
 
 // Storage functions
+pub fn set_pair_asset(env: &Env, pair: &AssetPair, asset_id: u32) {
+    env.storage()
+        .persistent()
+        .set(&TWAPStorageKey::PairAsset(pair.clone()), &asset_id);
+}
+
+pub fn get_pair_asset(env: &Env, pair: &AssetPair) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&TWAPStorageKey::PairAsset(pair.clone()))
+}
+
 pub fn get_next_twap_id(env: &Env) -> u64 {
     let counter: u64 = env
         .storage()
@@ -102,6 +182,18 @@ pub fn get_twap_order(env: &Env, order_id: u64) -> Result<TWAPOrder, AutoTradeEr
         .ok_or(AutoTradeError::TWAPOrderNotFound)
 }
 
+pub fn get_adaptive_twap(env: &Env, order_id: u64) -> Option<AdaptiveTWAP> {
+    env.storage()
+        .persistent()
+        .get(&TWAPStorageKey::Adaptive(order_id))
+}
+
+fn store_adaptive_twap(env: &Env, order_id: u64, adaptive: &AdaptiveTWAP) {
+    env.storage()
+        .persistent()
+        .set(&TWAPStorageKey::Adaptive(order_id), adaptive);
+}
+
 pub fn get_active_twap_orders(env: &Env) -> Vec<TWAPOrder> {
     let active_ids: Vec<u64> = env
         .storage()
@@ -272,6 +364,76 @@ pub fn create_twap_order(
     Ok(order_id)
 }
 
+/// Create an order whose slices follow `config` instead of equal splits.
+/// The oracle spot at creation is kept as the arrival price.
+pub fn create_adaptive_twap_order(
+    env: &Env,
+    user: Address,
+    pair: AssetPair,
+    total_amount: i128,
+    duration_minutes: u32,
+    num_segments: Option<u32>,
+    config: AdaptiveTWAPConfig,
+) -> Result<u64, AutoTradeError> {
+    if total_amount <= 0 || config.band_bps as i128 > BPS_DENOMINATOR {
+        return Err(AutoTradeError::InvalidTWAPConfig);
+    }
+    // Slices are priced and charged against the pair's registered asset.
+    if get_pair_asset(env, &pair) != Some(config.oracle_asset) {
+        return Err(AutoTradeError::InvalidTWAPConfig);
+    }
+    match config.mode {
+        TWAPMode::Twap => {}
+        TWAPMode::Vwap => {
+            if config.liquidity_signal.is_none() {
+                return Err(AutoTradeError::InvalidTWAPConfig);
+            }
+        }
+        TWAPMode::Participation(rate_bps) => {
+            if rate_bps == 0
+                || rate_bps as i128 > BPS_DENOMINATOR
+                || config.liquidity_signal.is_none()
+                || config.deadline_minutes < duration_minutes
+            {
+                return Err(AutoTradeError::InvalidTWAPConfig);
+            }
+        }
+    }
+    let arrival_price =
+        risk::get_asset_price(env, config.oracle_asset).ok_or(AutoTradeError::OracleUnavailable)?;
+
+    let order_id = create_twap_order(
+        env,
+        user,
+        pair,
+        total_amount,
+        duration_minutes,
+        num_segments,
+        None,
+    )?;
+
+    let deadline_minutes = match config.mode {
+        TWAPMode::Participation(_) => config.deadline_minutes,
+        _ => duration_minutes,
+    };
+    store_adaptive_twap(
+        env,
+        order_id,
+        &AdaptiveTWAP {
+            mode: config.mode,
+            oracle_asset: config.oracle_asset,
+            liquidity_signal: config.liquidity_signal,
+            band_bps: config.band_bps,
+            arrival_price,
+            deadline: env.ledger().timestamp() + deadline_minutes as u64 * 60,
+            observed_volume: 0,
+            volume_samples: 0,
+            shortfall_bps: 0,
+        },
+    );
+    Ok(order_id)
+}
+
 pub fn execute_twap_segments(env: &Env) -> Vec<u64> {
     let current = env.ledger().timestamp();
     let active_orders = get_active_twap_orders(env);
@@ -282,6 +444,7 @@ pub fn execute_twap_segments(env: &Env) -> Vec<u64> {
         if twap.status != TWAPStatus::Active {
             continue;
         }
+        let mut adaptive = get_adaptive_twap(env, twap.id);
 
         let elapsed = if current > twap.start_time {
             current - twap.start_time
@@ -290,14 +453,33 @@ pub fn execute_twap_segments(env: &Env) -> Vec<u64> {
         };
         let expected_segments = (elapsed / twap.interval_seconds.max(1)) as u32;
 
-        while twap.segments_executed < expected_segments
-            && twap.segments_executed < twap.total_segments
+        // Participation orders keep slicing past the schedule until filled,
+        // but not past their deadline.
+        let (open_ended, expired) = match &adaptive {
+            Some(a) => match a.mode {
+                TWAPMode::Participation(_) => (true, current >= a.deadline),
+                _ => (false, false),
+            },
+            None => (false, false),
+        };
+        while !expired
+            && twap.segments_executed < expected_segments
+            && (open_ended || twap.segments_executed < twap.total_segments)
+            && twap.filled_amount < twap.total_amount
         {
             // Execute segment
-            match execute_twap_segment(env, &mut twap) {
-                Ok(trade_id) => {
+            let before = twap.segments_executed;
+            match execute_twap_segment(env, &mut twap, adaptive.as_mut()) {
+                Ok(Some(trade_id)) => {
                     executed_ids.push_back(trade_id);
                 }
+                Ok(None) => {
+                    // Held slices are retried on the next keeper call; a
+                    // skipped VWAP slice has already advanced the schedule.
+                    if twap.segments_executed == before {
+                        break;
+                    }
+                }
                 Err(_e) => {
                     #[allow(deprecated)]
                     env.events().publish(
@@ -309,7 +491,12 @@ pub fn execute_twap_segments(env: &Env) -> Vec<u64> {
             }
         }
 
-        if twap.segments_executed >= twap.total_segments {
+        let complete = if open_ended {
+            twap.filled_amount >= twap.total_amount || expired
+        } else {
+            twap.segments_executed >= twap.total_segments
+        };
+        if complete {
             twap.status = TWAPStatus::Complete;
             let avg_price = if twap.filled_amount > 0 {
                 twap.weighted_price / twap.filled_amount
@@ -321,19 +508,147 @@ pub fn execute_twap_segments(env: &Env) -> Vec<u64> {
                 (Symbol::new(env, "TWAPOrderComplete"), twap.id),
                 (twap.filled_amount, avg_price),
             );
+            if expired && twap.filled_amount < twap.total_amount {
+                #[allow(deprecated)]
+                env.events().publish(
+                    (Symbol::new(env, "TWAPDeadlineReached"), twap.id),
+                    twap.total_amount - twap.filled_amount,
+                );
+            }
+            if let Some(a) = adaptive.as_mut() {
+                if a.arrival_price > 0 && avg_price > 0 {
+                    a.shortfall_bps =
+                        (avg_price - a.arrival_price) * BPS_DENOMINATOR / a.arrival_price;
+                    #[allow(deprecated)]
+                    env.events().publish(
+                        (Symbol::new(env, "TWAPShortfall"), twap.id),
+                        (a.arrival_price, avg_price, a.shortfall_bps),
+                    );
+                }
+            }
         }
 
         // Save updated state
         store_twap_order(env, twap.id, &twap);
+        if let Some(a) = &adaptive {
+            store_adaptive_twap(env, twap.id, a);
+        }
     }
 
     executed_ids
 }
 
-fn execute_twap_segment(env: &Env, twap: &mut TWAPOrder) -> Result<u64, AutoTradeError> {
+/// Observed liquidity across the venues quoted for `signal_id`.
+fn observed_liquidity(env: &Env, signal_id: u64) -> i128 {
+    let mut total = 0i128;
+    for venue in smart_routing::get_venue_liquidity(env, signal_id).iter() {
+        total = total.saturating_add(venue.available_amount);
+    }
+    total
+}
+
+/// Size of the next slice under the order's mode. `Vwap` slices also record
+/// the volume sample they were sized from.
+fn slice_amount(env: &Env, twap: &TWAPOrder, adaptive: Option<&mut AdaptiveTWAP>) -> i128 {
+    let remaining = twap.total_amount - twap.filled_amount;
+    let Some(adaptive) = adaptive else {
+        return twap.amount_per_segment;
+    };
+    let volume = adaptive
+        .liquidity_signal
+        .map(|signal_id| observed_liquidity(env, signal_id))
+        .unwrap_or(0);
+    match adaptive.mode {
+        TWAPMode::Twap => twap.amount_per_segment,
+        TWAPMode::Vwap => {
+            adaptive.observed_volume = adaptive.observed_volume.saturating_add(volume);
+            adaptive.volume_samples += 1;
+            let slices_left = twap.total_segments.saturating_sub(twap.segments_executed) as i128;
+            if slices_left <= 1 {
+                return remaining;
+            }
+            // Volume still to come, estimated at the running average.
+            let average = adaptive.observed_volume / adaptive.volume_samples as i128;
+            let expected = volume + average * (slices_left - 1);
+            if expected == 0 {
+                0
+            } else {
+                remaining * volume / expected
+            }
+        }
+        TWAPMode::Participation(rate_bps) => {
+            (volume * rate_bps as i128 / BPS_DENOMINATOR).min(remaining)
+        }
+    }
+}
+
+/// Whether spot has left the band around the oracle TWAP.
+fn outside_band(env: &Env, asset: u32, spot: i128, band_bps: u32) -> bool {
+    if band_bps == 0 {
+        return false;
+    }
+    let twap = average_price(&risk::get_price_history(env, asset, BAND_TWAP_SAMPLES));
+    twap > 0 && (spot - twap).abs() * BPS_DENOMINATOR > twap * band_bps as i128
+}
+
+/// Execute the next slice. `Ok(None)` means nothing was filled: the slice was
+/// held because spot is outside the band or no liquidity was observed, or,
+/// for `Vwap`, skipped because no volume was observed.
+fn execute_twap_segment(
+    env: &Env,
+    twap: &mut TWAPOrder,
+    mut adaptive: Option<&mut AdaptiveTWAP>,
+) -> Result<Option<u64>, AutoTradeError> {
     let simulated_trade_id = env.ledger().timestamp() + twap.segments_executed as u64;
-    let simulated_price = get_market_price(env, &twap.pair)?;
-    let simulated_fill = twap.amount_per_segment;
+    let simulated_price = match adaptive.as_deref() {
+        Some(a) => {
+            let spot = risk::get_asset_price(env, a.oracle_asset)
+                .ok_or(AutoTradeError::OracleUnavailable)?;
+            if outside_band(env, a.oracle_asset, spot, a.band_bps) {
+                #[allow(deprecated)]
+                env.events().publish(
+                    (Symbol::new(env, "TWAPSliceHeld"), twap.id),
+                    (twap.segments_executed, spot),
+                );
+                return Ok(None);
+            }
+            spot
+        }
+        None => get_market_price(env, &twap.pair)?,
+    };
+    let simulated_fill = slice_amount(env, twap, adaptive.as_deref_mut());
+    match adaptive.as_deref().map(|a| &a.mode) {
+        Some(TWAPMode::Vwap) if simulated_fill <= 0 => {
+            twap.segments_executed += 1;
+            #[allow(deprecated)]
+            env.events().publish(
+                (Symbol::new(env, "TWAPSliceSkipped"), twap.id),
+                twap.segments_executed,
+            );
+            return Ok(None);
+        }
+        Some(TWAPMode::Participation(_)) if simulated_fill <= 0 => {
+            #[allow(deprecated)]
+            env.events().publish(
+                (Symbol::new(env, "TWAPSliceHeld"), twap.id),
+                (twap.segments_executed, simulated_price),
+            );
+            return Ok(None);
+        }
+        _ => {}
+    }
+
+    auth::spend(
+        env,
+        &twap.user,
+        None,
+        &Spend {
+            strategy: StrategyKind::Twap,
+            asset: get_pair_asset(env, &twap.pair),
+            counterparty: None,
+            amount: simulated_fill,
+        },
+    )?;
 
     record_price_point(env, &twap.pair, simulated_price);
 
@@ -351,7 +666,7 @@ fn execute_twap_segment(env: &Env, twap: &mut TWAPOrder) -> Result<u64, AutoTrad
         (simulated_fill, simulated_price),
     );
 
-    Ok(simulated_trade_id)
+    Ok(Some(simulated_trade_id))
 }
 
 fn get_market_price(env: &Env, pair: &AssetPair) -> Result<i128, AutoTradeError> {
@@ -451,8 +766,10 @@ pub fn cancel_twap_order(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::amm_bridge::mock_router::{MockAmmRouter, MockAmmRouterClient};
     use crate::{AutoTradeContract, AutoTradeContractClient};
     use soroban_sdk::testutils::{Address as _, Ledger as _};
+    use stellar_swipe_common::amm_bridge::{AmmSourceConfig, AmmSourceKind};
 
     fn setup() -> (Env, Address, AutoTradeContractClient<'static>) {
         let env = Env::default();
@@ -493,6 +810,7 @@ mod tests {
     fn test_twap_segment_execution() {
         let (env, _contract, client) = setup();
         let trader = user(&env);
+        client.grant_authorization(&trader, &1_000_000, &30);
         let pair = AssetPair {
             base: String::from_str(&env, "XLM"),
             quote: String::from_str(&env, "USDC"),
@@ -523,6 +841,7 @@ mod tests {
     fn test_twap_cancellation() {
         let (env, _contract, client) = setup();
         let trader = user(&env);
+        client.grant_authorization(&trader, &1_000_000, &30);
         let pair = AssetPair {
             base: String::from_str(&env, "BTC"),
             quote: String::from_str(&env, "USD"),
@@ -573,6 +892,7 @@ mod tests {
     fn test_order_completion() {
         let (env, _contract, client) = setup();
         let trader = user(&env);
+        client.grant_authorization(&trader, &1_000_000, &30);
         let pair = AssetPair {
             base: String::from_str(&env, "SOL"),
             quote: String::from_str(&env, "USDC"),
@@ -590,4 +910,221 @@ mod tests {
         assert_eq!(twap.filled_amount, 5000);
         assert_eq!(twap.status, TWAPStatus::Complete);
     }
+
+    fn set_oracle_price(env: &Env, contract: &Address, asset: u32, price: i128) {
+        env.as_contract(contract, || {
+            risk::set_asset_price(env, asset, price);
+            risk::record_price(env, asset, price);
+        });
+    }
+
+    fn adaptive(mode: TWAPMode, band_bps: u32) -> AdaptiveTWAPConfig {
+        AdaptiveTWAPConfig {
+            mode,
+            oracle_asset: 1,
+            liquidity_signal: Some(9),
+            band_bps,
+            deadline_minutes: 40,
+        }
+    }
+
+    fn xlm_usdc(env: &Env) -> AssetPair {
+        AssetPair {
+            base: String::from_str(env, "XLM"),
+            quote: String::from_str(env, "USDC"),
+        }
+    }
+
+    /// Authorised trader with XLM/USDC registered against oracle asset 1.
+    fn adaptive_setup() -> (Env, Address, AutoTradeContractClient<'static>, Address) {
+        let (env, contract, client) = setup();
+        let admin = user(&env);
+        client.initialize(&admin);
+        client.set_twap_pair_asset(&admin, &xlm_usdc(&env), &1);
+        let trader = user(&env);
+        client.grant_authorization(&trader, &1_000_000, &30);
+        (env, contract, client, trader)
+    }
+
+    /// Quote `amount` of depth for signal 9 through a mock SDEX router.
+    fn set_venue_depth(env: &Env, client: &AutoTradeContractClient, amount: i128) {
+        let router = env.register(MockAmmRouter, ());
+        MockAmmRouterClient::new(env, &router)
+            .set_order_book(&soroban_sdk::vec![env, (100_000i128, amount)]);
+        env.as_contract(&client.address, || {
+            crate::amm_bridge::register_amm_source(
+                env,
+                AmmSourceConfig {
+                    kind: AmmSourceKind::SdexRouter,
+                    source_id: 1,
+                    router,
+                    priority: 0,
+                    enabled: true,
+                },
+            )
+            .unwrap();
+            crate::amm_bridge::set_signal_token_pair(env, 9, user(env), user(env));
+        });
+    }
+
+    #[test]
+    fn test_vwap_slices_follow_observed_volume_and_report_shortfall() {
+        let (env, contract, client, trader) = adaptive_setup();
+        set_oracle_price(&env, &contract, 1, 100_000);
+
+        let order_id = client.create_adaptive_twap_order(
+            &trader,
+            &xlm_usdc(&env),
+            &6000,
+            &30,
+            &Some(3),
+            &adaptive(TWAPMode::Vwap, 0),
+        );
+        let state = client.get_adaptive_twap(&order_id).unwrap();
+        assert_eq!(state.arrival_price, 100_000);
+
+        // 1_000 observed and assumed for both remaining slices: a third.
+        set_venue_depth(&env, &client, 1_000);
+        env.ledger().set_timestamp(1_000 + 601);
+        client.execute_twap_segments();
+        assert_eq!(client.get_twap_order(&order_id).filled_amount, 2000);
+
+        // 4_000 against an average of 2_500 for the last slice.
+        set_venue_depth(&env, &client, 4_000);
+        set_oracle_price(&env, &contract, 1, 102_000);
+        env.ledger().set_timestamp(1_000 + 1201);
+        client.execute_twap_segments();
+        assert_eq!(client.get_twap_order(&order_id).filled_amount, 2000 + 2461);
+
+        env.ledger().set_timestamp(1_000 + 1801);
+        client.execute_twap_segments();
+        let twap = client.get_twap_order(&order_id);
+        assert_eq!(twap.filled_amount, 6000);
+        assert_eq!(twap.status, TWAPStatus::Complete);
+        // Average 101_333 against an arrival of 100_000.
+        assert_eq!(
+            client.get_adaptive_twap(&order_id).unwrap().shortfall_bps,
+            133
+        );
+    }
+
+    #[test]
+    fn test_participation_slices_capped_by_observed_liquidity_until_deadline() {
+        let (env, contract, client, trader) = adaptive_setup();
+        set_oracle_price(&env, &contract, 1, 100_000);
+
+        let order_id = client.create_adaptive_twap_order(
+            &trader,
+            &xlm_usdc(&env),
+            &6000,
+            &20,
+            &Some(2),
+            &adaptive(TWAPMode::Participation(1_000), 0),
+        );
+
+        // No liquidity observed yet: the slice is held.
+        env.ledger().set_timestamp(1_000 + 601);
+        assert_eq!(client.execute_twap_segments().len(), 0);
+
+        set_venue_depth(&env, &client, 15_000);
+        client.execute_twap_segments();
+        assert_eq!(client.get_twap_order(&order_id).filled_amount, 1500);
+
+        // Runs past the two scheduled slices...
+        env.ledger().set_timestamp(1_000 + 1801);
+        client.execute_twap_segments();
+        let twap = client.get_twap_order(&order_id);
+        assert_eq!(twap.filled_amount, 4500);
+        assert_eq!(twap.status, TWAPStatus::Active);
+
+        // ...but stops at the deadline, unfilled.
+        env.ledger().set_timestamp(1_000 + 2400);
+        assert_eq!(client.execute_twap_segments().len(), 0);
+        let twap = client.get_twap_order(&order_id);
+        assert_eq!(twap.filled_amount, 4500);
+        assert_eq!(twap.segments_executed, 3);
+        assert_eq!(twap.status, TWAPStatus::Complete);
+    }
+
+    #[test]
+    fn test_slice_held_outside_oracle_twap_band() {
+        let (env, contract, client, trader) = adaptive_setup();
+        for _ in 0..5 {
+            set_oracle_price(&env, &contract, 1, 100_000);
+        }
+
+        let order_id = client.create_adaptive_twap_order(
+            &trader,
+            &xlm_usdc(&env),
+            &2000,
+            &20,
+            &Some(2),
+            &adaptive(TWAPMode::Twap, 500),
+        );
+
+        // Spike to 130k: the TWAP of the last samples is 105k, 23% away.
+        set_oracle_price(&env, &contract, 1, 130_000);
+        env.ledger().set_timestamp(1_000 + 601);
+        assert_eq!(client.execute_twap_segments().len(), 0);
+        assert_eq!(client.get_twap_order(&order_id).segments_executed, 0);
+
+        // Back inside the band: the held slice executes.
+        set_oracle_price(&env, &contract, 1, 101_000);
+        assert_eq!(client.execute_twap_segments().len(), 1);
+        assert_eq!(client.get_twap_order(&order_id).filled_amount, 1000);
+    }
+
+    #[test]
+    fn test_adaptive_twap_validation() {
+        let (env, contract, client, trader) = adaptive_setup();
+        let pair = xlm_usdc(&env);
+
+        // Arrival price needs an oracle feed.
+        assert_eq!(
+            client.try_create_adaptive_twap_order(
+                &trader,
+                &pair,
+                &1000,
+                &20,
+                &None,
+                &adaptive(TWAPMode::Twap, 0),
+            ),
+            Err(Ok(AutoTradeError::OracleUnavailable))
+        );
+        set_oracle_price(&env, &contract, 1, 100_000);
+        set_oracle_price(&env, &contract, 2, 100_000);
+
+        // The oracle feed must be the one registered for the pair.
+        let mut other_feed = adaptive(TWAPMode::Twap, 0);
+        other_feed.oracle_asset = 2;
+        assert_eq!(
+            client.try_create_adaptive_twap_order(&trader, &pair, &1000, &20, &None, &other_feed),
+            Err(Ok(AutoTradeError::InvalidTWAPConfig))
+        );
+
+        let mut no_liquidity = adaptive(TWAPMode::Vwap, 0);
+        no_liquidity.liquidity_signal = None;
+        assert_eq!(
+            client.try_create_adaptive_twap_order(&trader, &pair, &1000, &20, &None, &no_liquidity),
+            Err(Ok(AutoTradeError::InvalidTWAPConfig))
+        );
+
+        let mut early_deadline = adaptive(TWAPMode::Participation(1_000), 0);
+        early_deadline.deadline_minutes = 10;
+        assert_eq!(
+            client.try_create_adaptive_twap_order(
+                &trader,
+                &pair,
+                &1000,
+                &20,
+                &None,
+                &early_deadline
+            ),
+            Err(Ok(AutoTradeError::InvalidTWAPConfig))
+        );
+
+        // Plain orders carry no adaptive state.
+        let plain = client.create_twap_order(&trader, &pair, &1000, &20, &None, &None);
+        assert_eq!(client.get_adaptive_twap(&plain), None);
+    }
 }
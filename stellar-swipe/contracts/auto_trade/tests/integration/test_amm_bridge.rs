@@ -5,10 +5,10 @@ extern crate std;
 use auto_trade::amm_bridge;
 use auto_trade::amm_bridge::mock_router::{MockAmmRouter, MockAmmRouterClient};
 use auto_trade::smart_routing;
-use auto_trade::smart_routing::{LiquidityVenue, VenueLiquidity};
-use auto_trade::{AutoTradeContract, Signal};
-use soroban_sdk::testutils::Ledger;
-use soroban_sdk::{symbol_short, Address, Env};
+use auto_trade::{AutoTradeContract, AutoTradeContractClient, AutoTradeError, Signal};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{symbol_short, vec, Address, Env};
 use stellar_swipe_common::amm_bridge::{AmmSourceConfig, AmmSourceKind};
 
 fn signal(id: u64) -> Signal {
@@ -20,135 +20,118 @@ fn signal(id: u64) -> Signal {
     }
 }
 
-fn venue(venue: LiquidityVenue, venue_id: u32, available: i128, price: i128) -> VenueLiquidity {
-    VenueLiquidity {
-        venue,
-        venue_id,
-        available_amount: available,
-        price,
-        fee_bps: 30,
-        slippage_bps: 500,
-    }
+struct Setup {
+    env: Env,
+    contract: Address,
+    from: Address,
+    to: Address,
 }
 
-#[test]
-fn discover_quotes_from_multiple_venues() {
+fn setup(signal_id: u64) -> Setup {
     let env = Env::default();
+    env.mock_all_auths();
     env.ledger().set_timestamp(1_000);
     let contract = env.register(AutoTradeContract, ());
-    let signal_id = 42u64;
+    let issuer = Address::generate(&env);
+    let from = env
+        .register_stellar_asset_contract_v2(issuer.clone())
+        .address();
+    let to = env.register_stellar_asset_contract_v2(issuer).address();
+    StellarAssetClient::new(&env, &from).mint(&contract, &1_000_000);
 
     env.as_contract(&contract, || {
-        smart_routing::upsert_venue_liquidity(
-            &env,
-            signal_id,
-            venue(LiquidityVenue::Sdex, 1, 50_000, 100),
-        )
-        .unwrap();
-        smart_routing::upsert_venue_liquidity(
-            &env,
-            signal_id,
-            venue(LiquidityVenue::Pool, 2, 80_000, 98),
-        )
-        .unwrap();
-        assert_eq!(amm_bridge::discover_quotes(&env, signal_id, 1_000).len(), 2);
+        amm_bridge::set_signal_token_pair(&env, signal_id, from.clone(), to.clone());
     });
+    Setup {
+        env,
+        contract,
+        from,
+        to,
+    }
 }
 
-#[test]
-fn router_quote_merged_into_discovery() {
-    let env = Env::default();
-    env.ledger().set_timestamp(1_000);
-    let contract = env.register(AutoTradeContract, ());
-    let router_id = env.register(MockAmmRouter, ());
-    let from = Address::generate(&env);
-    let to = Address::generate(&env);
-    let signal_id = 7u64;
-
-    MockAmmRouterClient::new(&env, &router_id).set_best_ask(&105, &500_000);
-
-    env.as_contract(&contract, || {
-        amm_bridge::set_signal_token_pair(&env, signal_id, from, to);
+fn add_router(s: &Setup, kind: AmmSourceKind, source_id: u32) -> MockAmmRouterClient<'static> {
+    let router_id = s.env.register(MockAmmRouter, ());
+    StellarAssetClient::new(&s.env, &s.to).mint(&router_id, &1_000_000);
+    s.env.as_contract(&s.contract, || {
         amm_bridge::register_amm_source(
-            &env,
+            &s.env,
             AmmSourceConfig {
-                kind: AmmSourceKind::SdexRouter,
-                source_id: 10,
-                router: router_id,
-                priority: 1,
+                kind,
+                source_id,
+                router: router_id.clone(),
+                priority: source_id,
                 enabled: true,
             },
         )
         .unwrap();
-        assert_eq!(
-            amm_bridge::discover_quotes(&env, signal_id, 10_000).len(),
-            1
-        );
+    });
+    MockAmmRouterClient::new(&s.env, &router_id)
+}
+
+#[test]
+fn discover_quotes_from_multiple_venues() {
+    let s = setup(42);
+    add_router(&s, AmmSourceKind::SdexRouter, 1).set_order_book(&vec![&s.env, (10_000, 50_000)]);
+    add_router(&s, AmmSourceKind::StellarAmm, 2).set_reserves(&80_000, &78_400, &30);
+
+    s.env.as_contract(&s.contract, || {
+        let quotes = amm_bridge::discover_quotes(&s.env, 42, 1_000);
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes.get(0).unwrap().expected_out, 1_000);
+    });
+}
+
+#[test]
+fn path_payment_best_ask_merged_into_discovery() {
+    let s = setup(7);
+    add_router(&s, AmmSourceKind::PathPayment, 10).set_best_ask(&10_500, &500_000);
+
+    s.env.as_contract(&s.contract, || {
+        let quotes = amm_bridge::discover_quotes(&s.env, 7, 10_000);
+        assert_eq!(quotes.len(), 1);
+        assert_eq!(quotes.get(0).unwrap().expected_out, 10_500);
     });
 }
 
 #[test]
 fn plan_route_across_two_pools() {
-    let env = Env::default();
-    env.ledger().set_timestamp(1_000);
-    let contract = env.register(AutoTradeContract, ());
-    let signal_id = 99u64;
+    let s = setup(99);
+    add_router(&s, AmmSourceKind::StellarAmm, 1).set_reserves(&400_000, &400_000, &30);
+    add_router(&s, AmmSourceKind::BridgePool, 2).set_reserves(&600_000, &600_000, &30);
 
-    env.as_contract(&contract, || {
-        smart_routing::upsert_venue_liquidity(
-            &env,
-            signal_id,
-            venue(LiquidityVenue::Pool, 1, 40_000, 100),
-        )
-        .unwrap();
-        smart_routing::upsert_venue_liquidity(
-            &env,
-            signal_id,
-            venue(LiquidityVenue::Pool, 2, 60_000, 100),
-        )
-        .unwrap();
-        let plan = amm_bridge::plan_amm_route(&env, &signal(signal_id), 80_000, 1_000).unwrap();
+    s.env.as_contract(&s.contract, || {
+        let plan = smart_routing::plan_best_execution(&s.env, &signal(99), 80_000, 1_000).unwrap();
         assert_eq!(plan.amount_in, 80_000);
-        assert!(plan.segments.len() >= 1);
+        assert_eq!(plan.segments.len(), 2);
+        // The deeper pool takes the larger share.
+        assert!(plan.segments.get(1).unwrap().amount_in > plan.segments.get(0).unwrap().amount_in);
     });
 }
 
 #[test]
 fn slippage_protection_rejects_bad_plan() {
-    let env = Env::default();
-    env.ledger().set_timestamp(1_000);
-    let contract = env.register(AutoTradeContract, ());
+    let s = setup(1);
+    add_router(&s, AmmSourceKind::StellarAmm, 1).set_reserves(&1_000, &1_000, &0);
 
-    env.as_contract(&contract, || {
-        smart_routing::upsert_venue_liquidity(&env, 1, venue(LiquidityVenue::Sdex, 1, 100, 500))
-            .unwrap();
-        assert!(amm_bridge::plan_amm_route(&env, &signal(1), 100, 50).is_err());
+    s.env.as_contract(&s.contract, || {
+        assert!(smart_routing::plan_best_execution(&s.env, &signal(1), 100, 50).is_err());
     });
 }
 
 #[test]
 fn execute_uses_smart_route_first() {
-    let env = Env::default();
-    env.ledger().set_timestamp(1_000);
-    let contract = env.register(AutoTradeContract, ());
-    let user = Address::generate(&env);
-    let signal_id = 5u64;
-
-    env.as_contract(&contract, || {
-        smart_routing::upsert_venue_liquidity(
-            &env,
-            signal_id,
-            venue(LiquidityVenue::Sdex, 1, 100_000, 100),
-        )
-        .unwrap();
-        let key = (user.clone(), symbol_short!("liquidity"));
-        env.storage().temporary().set(&key, &100_000i128);
+    let s = setup(5);
+    let user = Address::generate(&s.env);
+    add_router(&s, AmmSourceKind::SdexRouter, 1).set_order_book(&vec![&s.env, (10_000, 100_000)]);
 
+    s.env.as_contract(&s.contract, || {
         let result =
-            amm_bridge::execute_swap_with_fallback(&env, &user, &signal(signal_id), 50_000, 1_000)
+            amm_bridge::execute_swap_with_fallback(&s.env, &user, &signal(5), 50_000, 1_000)
                 .unwrap();
         assert_eq!(result.executed_amount, 50_000);
     });
+    assert_eq!(TokenClient::new(&s.env, &s.to).balance(&s.contract), 50_000);
 }
 
 #[test]
@@ -171,96 +154,66 @@ fn fallback_to_sdex_when_no_venues() {
 }
 
 #[test]
-fn router_fallback_when_primary_venue_fails() {
-    let env = Env::default();
-    env.ledger().set_timestamp(1_000);
-    let contract = env.register(AutoTradeContract, ());
-    let router_id = env.register(MockAmmRouter, ());
-    let from = Address::generate(&env);
-    let to = Address::generate(&env);
-    let user = Address::generate(&env);
-    let signal_id = 11u64;
-
-    MockAmmRouterClient::new(&env, &router_id).set_best_ask(&100, &100_000);
-    MockAmmRouterClient::new(&env, &router_id).set_amount_out(&49_000);
-
-    env.as_contract(&contract, || {
-        smart_routing::upsert_venue_liquidity(
-            &env,
-            signal_id,
-            venue(LiquidityVenue::Sdex, 1, 10_000, 100),
-        )
-        .unwrap();
-        smart_routing::set_execution_failure(&env, signal_id, LiquidityVenue::Sdex, 1);
-
-        amm_bridge::set_signal_token_pair(&env, signal_id, from, to);
-        amm_bridge::register_amm_source(
-            &env,
-            AmmSourceConfig {
-                kind: AmmSourceKind::SdexRouter,
-                source_id: 10,
-                router: router_id,
-                priority: 1,
-                enabled: true,
-            },
-        )
-        .unwrap();
+fn mapped_pair_without_liquidity_does_not_fall_back() {
+    let s = setup(6);
+    let user = Address::generate(&s.env);
+    add_router(&s, AmmSourceKind::SdexRouter, 1).set_order_book(&vec![&s.env, (10_000, 10_000)]);
 
-        let key = (symbol_short!("liquidity"), signal_id);
-        env.storage().temporary().set(&key, &100_000i128);
+    s.env.as_contract(&s.contract, || {
+        let key = (symbol_short!("liquidity"), 6u64);
+        s.env.storage().temporary().set(&key, &1_000_000i128);
 
         let result =
-            amm_bridge::execute_swap_with_fallback(&env, &user, &signal(signal_id), 50_000, 1_000);
-        assert!(result.is_ok());
+            amm_bridge::execute_swap_with_fallback(&s.env, &user, &signal(6), 50_000, 1_000);
+        assert_eq!(result.unwrap_err(), AutoTradeError::InsufficientLiquidity);
     });
 }
 
 #[test]
 fn unavailable_router_skips_to_next_source() {
-    let env = Env::default();
-    env.ledger().set_timestamp(1_000);
-    let contract = env.register(AutoTradeContract, ());
-    let bad_router = env.register(MockAmmRouter, ());
-    let good_router = env.register(MockAmmRouter, ());
-    let from = Address::generate(&env);
-    let to = Address::generate(&env);
-    let user = Address::generate(&env);
-    let signal_id = 12u64;
-
-    MockAmmRouterClient::new(&env, &bad_router).set_fail_swap(&true);
-    MockAmmRouterClient::new(&env, &good_router).set_best_ask(&100, &100_000);
-    MockAmmRouterClient::new(&env, &good_router).set_amount_out(&48_000);
-
-    env.as_contract(&contract, || {
-        amm_bridge::set_signal_token_pair(&env, signal_id, from, to);
-        amm_bridge::register_amm_source(
-            &env,
-            AmmSourceConfig {
-                kind: AmmSourceKind::StellarAmm,
-                source_id: 1,
-                router: bad_router,
-                priority: 1,
-                enabled: true,
-            },
-        )
-        .unwrap();
-        amm_bridge::register_amm_source(
-            &env,
-            AmmSourceConfig {
-                kind: AmmSourceKind::StellarAmm,
-                source_id: 2,
-                router: good_router,
-                priority: 2,
-                enabled: true,
-            },
-        )
-        .unwrap();
-
-        let key = (symbol_short!("liquidity"), signal_id);
-        env.storage().temporary().set(&key, &100_000i128);
-
+    let s = setup(12);
+    let user = Address::generate(&s.env);
+    let bad = add_router(&s, AmmSourceKind::StellarAmm, 1);
+    bad.set_reserves(&1_000_000, &1_100_000, &0);
+    bad.set_fail_swap(&true);
+    add_router(&s, AmmSourceKind::StellarAmm, 2).set_reserves(&1_000_000, &1_000_000, &0);
+
+    s.env.as_contract(&s.contract, || {
         let result =
-            amm_bridge::execute_swap_with_fallback(&env, &user, &signal(signal_id), 50_000, 1_000);
-        assert!(result.is_ok());
+            amm_bridge::execute_swap_with_fallback(&s.env, &user, &signal(12), 50_000, 1_000);
+        assert_eq!(result.unwrap().executed_amount, 50_000);
     });
+    assert_eq!(
+        TokenClient::new(&s.env, &s.from).balance(&s.contract),
+        950_000
+    );
+}
+
+#[test]
+fn routing_config_is_admin_only() {
+    let s = setup(4);
+    let client = AutoTradeContractClient::new(&s.env, &s.contract);
+    let admin = Address::generate(&s.env);
+    let outsider = Address::generate(&s.env);
+    client.initialize(&admin);
+
+    assert_eq!(
+        client.try_set_signal_token_pair(&outsider, &4, &s.to, &s.from),
+        Err(Ok(AutoTradeError::Unauthorized))
+    );
+    let source = AmmSourceConfig {
+        kind: AmmSourceKind::StellarAmm,
+        source_id: 1,
+        router: Address::generate(&s.env),
+        priority: 1,
+        enabled: true,
+    };
+    assert_eq!(
+        client.try_register_amm_source(&outsider, &source),
+        Err(Ok(AutoTradeError::Unauthorized))
+    );
+
+    client.set_signal_token_pair(&admin, &4, &s.to, &s.from);
+    client.register_amm_source(&admin, &source);
+    assert_eq!(client.get_amm_sources().len(), 1);
 }
@@ -17,7 +17,8 @@
 //! # Migration checklist
 //! - [x] Admin address preserved in instance storage.
 //! - [x] Signal records preserved in persistent storage.
-//! - [x] User authorization (auth config) preserved in persistent storage.
+//! - [x] Pre-policy user authorization (`AuthConfig`) migrated to a default
+//!       spending policy with the same cap and expiry.
 //! - [x] Position records preserved in persistent storage.
 //! - [x] Trade records preserved in persistent storage.
 //! - [x] Pause states preserved in instance storage.
@@ -27,8 +28,8 @@
 extern crate std;
 
 use auto_trade::{
-    authorize_user_with_limits, set_signal, AutoTradeContract, AutoTradeContractClient, OrderType,
-    Signal,
+    auth::AuthKey, authorize_user_with_limits, set_signal, AuthConfig, AutoTradeContract,
+    AutoTradeContractClient, OrderType, Signal,
 };
 use soroban_sdk::{
     contract, contractimpl, symbol_short,
@@ -89,6 +90,23 @@ fn seed_signal(env: &Env, contract_id: &Address, signal_id: u64) {
     });
 }
 
+/// Write an authorization grant in the layout v1 stored before spending
+/// policies.
+fn seed_legacy_auth(env: &Env, contract_id: &Address, user: &Address, max: i128, days: u64) {
+    env.as_contract(contract_id, || {
+        let now = env.ledger().timestamp();
+        env.storage().persistent().set(
+            &AuthKey::Authorization(user.clone()),
+            &AuthConfig {
+                authorized: true,
+                max_trade_amount: max,
+                expires_at: now + days * 86_400,
+                granted_at: now,
+            },
+        );
+    });
+}
+
 // ── V2 contract definition ────────────────────────────────────────────────────
 //
 // AutoTradeContractV2 wraps v1 logic and adds a new `get_contract_version`
@@ -116,9 +134,11 @@ impl AutoTradeContractV2 {
         auto_trade::storage::get_signal(&env, signal_id)
     }
 
-    /// Reads auth config — same key layout as v1.
-    pub fn get_auth_config_v2(env: Env, user: Address) -> Option<auto_trade::AuthConfig> {
-        auto_trade::auth::get_auth_config(&env, &user)
+    /// Migrates the user's v1 `AuthConfig` grant and returns the default
+    /// spending policy that replaces it.
+    pub fn migrate_auth_v2(env: Env, user: Address) -> Option<auto_trade::SpendingPolicy> {
+        auto_trade::auth::migrate_legacy_authorization(&env, &user);
+        auto_trade::auth::get_default_policy(&env, &user)
     }
 }
 
@@ -146,27 +166,32 @@ fn test_signal_state_preserved_after_upgrade() {
     assert_eq!(sig.price, SIGNAL_PRICE);
 }
 
-/// Verify that user authorization state written by v1 is readable after upgrade.
+/// Verify that a v1 authorization grant becomes a default spending policy.
 #[test]
 fn test_auth_state_preserved_after_upgrade() {
     let (env, contract_id, _admin) = setup_v1();
     let user = Address::generate(&env);
 
     // --- v1: authorize user ---
-    env.as_contract(&contract_id, || {
-        authorize_user_with_limits(&env, &user, 1_000_000_000, 30);
-    });
+    seed_legacy_auth(&env, &contract_id, &user, 1_000_000_000, 30);
 
     // --- simulate upgrade ---
     let v2_id = env.register_at(&contract_id, AutoTradeContractV2, ());
 
-    // --- v2: auth config must still be present ---
+    // --- v2: the grant is migrated with its cap and expiry ---
     let client = AutoTradeContractV2Client::new(&env, &v2_id);
-    let cfg = client.get_auth_config_v2(&user);
+    let cfg = client.migrate_auth_v2(&user);
     assert!(cfg.is_some(), "auth config must survive upgrade");
     let cfg = cfg.unwrap();
-    assert!(cfg.authorized);
+    assert!(!cfg.revoked);
     assert_eq!(cfg.max_trade_amount, 1_000_000_000);
+    assert_eq!(cfg.expires_at, 1_000_000 + 30 * 86_400);
+    env.as_contract(&contract_id, || {
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&AuthKey::Authorization(user.clone())));
+    });
 }
 
 /// Verify that position records written by v1 are readable after upgrade.
@@ -253,9 +278,7 @@ fn test_all_v1_state_accessible_in_v2() {
     seed_signal(&env, &contract_id, 1);
 
     // Authorize user
-    env.as_contract(&contract_id, || {
-        authorize_user_with_limits(&env, &user, 5_000_000_000, 60);
-    });
+    seed_legacy_auth(&env, &contract_id, &user, 5_000_000_000, 60);
 
     // Open position
     let trade_id: BytesN<32> = env.as_contract(&contract_id, || {
@@ -270,8 +293,8 @@ fn test_all_v1_state_accessible_in_v2() {
     assert!(client.get_signal_v2(&1u64).is_some());
 
     // Auth preserved
-    let cfg = client.get_auth_config_v2(&user).unwrap();
-    assert!(cfg.authorized);
+    let cfg = client.migrate_auth_v2(&user).unwrap();
+    assert!(!cfg.revoked);
     assert_eq!(cfg.max_trade_amount, 5_000_000_000);
 
     // Position preserved
@@ -340,8 +363,8 @@ fn test_migrated_v1_state_is_correct() {
                 base_asset: 7,
             },
         );
-        authorize_user_with_limits(&env, &user, 999_999, 10);
     });
+    seed_legacy_auth(&env, &contract_id, &user, 999_999, 10);
 
     // Upgrade
     let v2_id = env.register_at(&contract_id, AutoTradeContractV2, ());
@@ -352,6 +375,6 @@ fn test_migrated_v1_state_is_correct() {
     assert_eq!(sig.price, 42_000);
     assert_eq!(sig.base_asset, 7);
 
-    let cfg = client.get_auth_config_v2(&user).unwrap();
+    let cfg = client.migrate_auth_v2(&user).unwrap();
     assert_eq!(cfg.max_trade_amount, 999_999);
 }
@@ -3,7 +3,7 @@
 use soroban_sdk::{
     contract, contracterror, contractimpl, contracttype, Address, Env, String, Symbol, Vec,
 };
-use stellar_swipe_common::SECONDS_PER_DAY;
+use stellar_swipe_common::{ComplianceLevel, SECONDS_PER_DAY};
 
 mod validators;
 
@@ -31,6 +31,8 @@ pub enum BridgeError {
     /// Distinct from DailyLimitExceeded (static anti-spam) so callers can differentiate.
     DynamicLiquidityLimitExceeded = 15,
     InvalidThreshold = 16,
+    /// The compliance registry does not accept the user.
+    NotCompliant = 17,
 }
 
 #[contracttype]
@@ -125,6 +127,10 @@ pub enum DataKey {
     LiquidityBuffer,
     TotalMinted,
     ReserveThreshold,
+    /// Compliance registry consulted before transfers and swaps. Unset = no gate.
+    ComplianceRegistry,
+    /// Asset pair id the compliance registry knows a bridged asset by.
+    CompliancePair(String),
 }
 
 const DAY_SECONDS: u64 = 86_400;
@@ -252,6 +258,7 @@ impl BridgeContract {
         if !cfg!(test) {
             user.require_auth();
         }
+        require_compliant(&env, &user, compliance_pair(&env, &wrapped_asset))?;
         validate_amount_and_limits(&env, amount)?;
         ensure_wrapped_asset_exists(&env, wrapped_asset.clone())?;
 
@@ -360,6 +367,12 @@ impl BridgeContract {
         if transfer.executed_at.is_some() {
             return Err(BridgeError::TransferAlreadyExecuted);
         }
+        // The user may have lost their attestation while validators signed.
+        require_compliant(
+            &env,
+            &transfer.user,
+            compliance_pair(&env, &transfer.wrapped_asset),
+        )?;
 
         let balance_key =
             DataKey::WrappedBalance(transfer.user.clone(), transfer.wrapped_asset.clone());
@@ -399,6 +412,7 @@ impl BridgeContract {
         if !cfg!(test) {
             user.require_auth();
         }
+        require_compliant(&env, &user, compliance_pair(&env, &wrapped_asset))?;
         validate_amount_and_limits(&env, amount)?;
 
         let balance_key = DataKey::WrappedBalance(user.clone(), wrapped_asset.clone());
@@ -649,6 +663,13 @@ impl BridgeContract {
         if !cfg!(test) {
             trader.require_auth();
         }
+        let pool = liquidity::get_pool(&env, pool_id)?;
+        let pair_a = compliance_pair(&env, &pool.asset_a);
+        let pair_b = compliance_pair(&env, &pool.asset_b);
+        require_compliant(&env, &trader, pair_a)?;
+        if pair_b.is_some() && pair_b != pair_a {
+            require_compliant(&env, &trader, pair_b)?;
+        }
         liquidity::swap(
             &env,
             trader,
@@ -700,6 +721,51 @@ impl BridgeContract {
     pub fn get_liquidity_buffer(env: Env) -> i128 {
         env.storage().persistent().get(&DataKey::LiquidityBuffer).unwrap_or(i128::MAX)
     }
+
+    /// Admin: gate transfers and swaps on the shared compliance registry, or
+    /// pass `None` to remove the gate.
+    pub fn set_compliance_registry(
+        env: Env,
+        admin: Address,
+        registry: Option<Address>,
+    ) -> Result<(), BridgeError> {
+        require_admin(&env, &admin)?;
+        if !cfg!(test) {
+            admin.require_auth();
+        }
+        match registry {
+            Some(registry) => env
+                .storage()
+                .instance()
+                .set(&DataKey::ComplianceRegistry, &registry),
+            None => env.storage().instance().remove(&DataKey::ComplianceRegistry),
+        }
+        Ok(())
+    }
+
+    pub fn get_compliance_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::ComplianceRegistry)
+    }
+
+    /// Admin: name the asset pair id the compliance registry uses for
+    /// `asset`, so jurisdiction pair blocks apply to it. `None` unmaps it.
+    pub fn set_compliance_pair(
+        env: Env,
+        admin: Address,
+        asset: String,
+        pair: Option<u32>,
+    ) -> Result<(), BridgeError> {
+        require_admin(&env, &admin)?;
+        if !cfg!(test) {
+            admin.require_auth();
+        }
+        let key = DataKey::CompliancePair(asset);
+        match pair {
+            Some(pair) => env.storage().persistent().set(&key, &pair),
+            None => env.storage().persistent().remove(&key),
+        }
+        Ok(())
+    }
 }
 
 fn get_config(env: &Env) -> Result<BridgeConfig, BridgeError> {
@@ -793,6 +859,32 @@ fn validate_amount_and_limits(env: &Env, amount: i128) -> Result<(), BridgeError
     Ok(())
 }
 
+fn compliance_pair(env: &Env, asset: &String) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::CompliancePair(asset.clone()))
+}
+
+fn require_compliant(
+    env: &Env,
+    user: &Address,
+    asset_pair: Option<u32>,
+) -> Result<(), BridgeError> {
+    let registry: Option<Address> = env.storage().instance().get(&DataKey::ComplianceRegistry);
+    match registry {
+        Some(registry) => stellar_swipe_common::require_compliant(
+            env,
+            &registry,
+            user,
+            ComplianceLevel::Basic,
+            asset_pair,
+        )
+        .map(|_| ())
+        .map_err(|_| BridgeError::NotCompliant),
+        None => Ok(()),
+    }
+}
+
 fn day_bucket(timestamp: u64) -> u64 {
     (timestamp / DAY_SECONDS) * DAY_SECONDS
 }
@@ -1194,6 +1286,107 @@ mod test {
             assert!(attest_res_healthy_now.is_ok());
         });
     }
+
+    #[test]
+    fn compliance_registry_gates_transfers() {
+        use compliance::{ComplianceRegistry, ComplianceRegistryClient, CustomerStatus};
+        use soroban_sdk::symbol_short;
+
+        let (env, contract_id, admin, validators) = setup();
+        let user = Address::generate(&env);
+        let registry = ComplianceRegistryClient::new(&env, &env.register(ComplianceRegistry, ()));
+        let registry_admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        registry.initialize(&registry_admin);
+        registry.add_issuer(&registry_admin, &issuer);
+
+        let lock = |env: &Env, nonce: u64| {
+            BridgeContract::initiate_lock_mint(
+                env.clone(),
+                user.clone(),
+                ChainId::Ethereum,
+                ChainId::Polygon,
+                String::from_str(env, "ETH"),
+                String::from_str(env, "wETH"),
+                100,
+                String::from_str(env, "0xc0"),
+                nonce,
+                String::from_str(env, "r"),
+            )
+        };
+
+        env.as_contract(&contract_id, || {
+            init(&env, &admin, &validators);
+            BridgeContract::set_compliance_registry(
+                env.clone(),
+                admin.clone(),
+                Some(registry.address.clone()),
+            )
+            .unwrap();
+            assert_eq!(lock(&env, 1), Err(BridgeError::NotCompliant));
+        });
+
+        registry.attest(
+            &issuer,
+            &user,
+            &String::from_str(&env, "sep12-customer"),
+            &CustomerStatus::Accepted,
+            &ComplianceLevel::Basic,
+            &symbol_short!("DE"),
+            &0,
+        );
+
+        // A pair blocked in the user's jurisdiction is refused once the
+        // wrapped asset is mapped to it.
+        registry.set_jurisdiction_rule(
+            &registry_admin,
+            &symbol_short!("DE"),
+            &compliance::JurisdictionRule {
+                blocked: false,
+                min_level: ComplianceLevel::Basic,
+                blocked_pairs: soroban_sdk::vec![&env, 7u32],
+            },
+        );
+        env.as_contract(&contract_id, || {
+            BridgeContract::set_compliance_pair(
+                env.clone(),
+                admin.clone(),
+                String::from_str(&env, "wETH"),
+                Some(7),
+            )
+            .unwrap();
+            assert_eq!(lock(&env, 2), Err(BridgeError::NotCompliant));
+            BridgeContract::set_compliance_pair(
+                env.clone(),
+                admin.clone(),
+                String::from_str(&env, "wETH"),
+                None,
+            )
+            .unwrap();
+        });
+        let transfer_id = env.as_contract(&contract_id, || lock(&env, 3).unwrap());
+
+        // Revoked while validators sign: the mint is refused.
+        registry.revoke_attestation(&issuer, &user);
+        env.as_contract(&contract_id, || {
+            for (i, sig) in ["sig-a", "sig-b"].iter().enumerate() {
+                BridgeContract::approve_lock_mint(
+                    env.clone(),
+                    validators.get(i as u32).unwrap(),
+                    transfer_id,
+                    String::from_str(&env, sig),
+                )
+                .unwrap();
+            }
+            assert_eq!(
+                BridgeContract::execute_lock_mint(env.clone(), admin.clone(), transfer_id),
+                Err(BridgeError::NotCompliant)
+            );
+
+            BridgeContract::set_compliance_registry(env.clone(), admin.clone(), None).unwrap();
+            BridgeContract::execute_lock_mint(env.clone(), admin.clone(), transfer_id).unwrap();
+        });
+    }
 }
 
 #[cfg(test)]
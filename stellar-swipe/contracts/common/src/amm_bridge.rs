@@ -7,6 +7,24 @@ pub const BPS_DENOMINATOR: i128 = 10_000;
 /// Standard router entrypoints (compatible with `trade_executor::sdex`).
 pub const FN_GET_BEST_ASK: &str = "get_best_ask";
 pub const FN_SWAP: &str = "swap";
+/// SDEX router: `get_order_book(from, to, depth) -> Vec<(price, amount_in)>`,
+/// best level first.
+pub const FN_GET_ORDER_BOOK: &str = "get_order_book";
+/// AMM pool router: `get_reserves(from, to) -> (reserve_in, reserve_out, fee_bps)`.
+pub const FN_GET_RESERVES: &str = "get_reserves";
+/// SDEX router: `place_offer(owner, selling, buying, amount, price) -> u64`.
+/// Pulls `amount` of `selling` from `owner` under its allowance; `price` is
+/// `buying` per unit of `selling`. Fills pay `buying` out to `owner`.
+pub const FN_PLACE_OFFER: &str = "place_offer";
+/// SDEX router: `cancel_offer(owner, offer_id) -> i128`, the unfilled amount
+/// released back to `owner`.
+pub const FN_CANCEL_OFFER: &str = "cancel_offer";
+/// SDEX router: `get_offer(offer_id) -> (remaining, paid_out)`, the amount
+/// still on the book (`0` once taken) and the `buying` paid to the owner so far.
+pub const FN_GET_OFFER: &str = "get_offer";
+
+/// Increments `plan_optimal_split` routes an order in.
+pub const SPLIT_STEPS: i128 = 20;
 
 #[contracttype]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -79,6 +97,33 @@ pub struct AmmRoutePlan {
     pub segments: Vec<AmmRouteSegment>,
 }
 
+/// One SDEX price level: output per unit in (bps-scaled) and the input it absorbs.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BookLevel {
+    pub price: i128,
+    pub amount_in: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VenueState {
+    /// Orderbook levels, best first.
+    OrderBook(Vec<BookLevel>),
+    /// Constant-product pool: `(reserve_in, reserve_out)`.
+    Pool(i128, i128),
+}
+
+/// Live depth read from one source.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VenueDepth {
+    pub kind: AmmSourceKind,
+    pub source_id: u32,
+    pub fee_bps: u32,
+    pub state: VenueState,
+}
+
 /// Constant-product output: `amount_out = (amount_in * (BPS-fee) * reserve_out) / (reserve_in * BPS + amount_in * (BPS-fee))`
 pub fn quote_constant_product(
     amount_in: i128,
@@ -111,14 +156,6 @@ pub fn min_amount_out_with_slippage(expected_out: i128, max_slippage_bps: u32) -
     expected_out.checked_mul(num)?.checked_div(BPS_DENOMINATOR)
 }
 
-pub fn estimate_impact_slippage_bps(amount: i128, liquidity: i128, cap_bps: u32) -> u32 {
-    if liquidity <= 0 || amount <= 0 {
-        return u32::MAX;
-    }
-    let raw = (amount * cap_bps as i128 + liquidity - 1) / liquidity;
-    raw as u32
-}
-
 fn apply_bps(value: i128, bps: u32) -> i128 {
     (value * bps as i128 + (BPS_DENOMINATOR - 1)) / BPS_DENOMINATOR
 }
@@ -178,134 +215,160 @@ pub fn rank_quotes_by_price(env: &Env, quotes: &Vec<AmmQuote>) -> Vec<AmmQuote>
     ranked
 }
 
-/// Greedy multi-source route planner (lowest total cost first).
-pub fn plan_multi_source_route(
+/// Output and fee for routing `amount_in` through one venue, walking book
+/// levels or the constant-product curve. `None` when the venue cannot absorb
+/// the whole amount.
+pub fn venue_quote(depth: &VenueDepth, amount_in: i128) -> Option<(i128, i128)> {
+    if amount_in <= 0 {
+        return None;
+    }
+    match &depth.state {
+        VenueState::OrderBook(levels) => {
+            let mut remaining = amount_in;
+            let mut gross = 0i128;
+            for level in levels.iter() {
+                if remaining == 0 {
+                    break;
+                }
+                let take = core::cmp::min(remaining, level.amount_in);
+                gross = gross.checked_add(take.checked_mul(level.price)? / BPS_DENOMINATOR)?;
+                remaining -= take;
+            }
+            if remaining > 0 {
+                return None;
+            }
+            let fee = apply_bps(gross, depth.fee_bps);
+            Some((gross - fee, fee))
+        }
+        VenueState::Pool(reserve_in, reserve_out) => {
+            let out = quote_constant_product(amount_in, *reserve_in, *reserve_out, depth.fee_bps)?;
+            let gross = quote_constant_product(amount_in, *reserve_in, *reserve_out, 0)?;
+            Some((out, gross - out))
+        }
+    }
+}
+
+/// Marginal price (output per unit in, bps-scaled) before any size is taken.
+pub fn venue_spot_price(depth: &VenueDepth) -> i128 {
+    match &depth.state {
+        VenueState::OrderBook(levels) => levels.first().map(|l| l.price).unwrap_or(0),
+        VenueState::Pool(reserve_in, reserve_out) => {
+            if *reserve_in <= 0 {
+                0
+            } else {
+                reserve_out * BPS_DENOMINATOR / reserve_in
+            }
+        }
+    }
+}
+
+/// Input the venue can absorb: the book's total depth, or the pool's input reserve.
+pub fn venue_capacity(depth: &VenueDepth) -> i128 {
+    match &depth.state {
+        VenueState::OrderBook(levels) => levels.iter().map(|l| l.amount_in).sum(),
+        VenueState::Pool(reserve_in, _) => *reserve_in,
+    }
+}
+
+/// Price impact of `out` against the spot price, before fees.
+fn impact_bps(spot_price: i128, amount_in: i128, gross_out: i128) -> u32 {
+    let at_spot = amount_in * spot_price / BPS_DENOMINATOR;
+    if at_spot <= 0 || gross_out >= at_spot {
+        return 0;
+    }
+    ((at_spot - gross_out) * BPS_DENOMINATOR / at_spot) as u32
+}
+
+/// Split `amount_in` across venues to maximise total output, i.e. minimise
+/// price impact plus fees. The amount is routed in `SPLIT_STEPS` increments,
+/// each to the venue with the best marginal output; book and pool output are
+/// both concave, so this converges on the optimal split.
+pub fn plan_optimal_split(
     env: &Env,
-    quotes: &Vec<AmmQuote>,
+    venues: &Vec<VenueDepth>,
     amount_in: i128,
-    reference_price: i128,
     max_slippage_bps: u32,
 ) -> Result<AmmRoutePlan, AmmBridgeError> {
     if amount_in <= 0 {
         return Err(AmmBridgeError::InvalidAmount);
     }
-    if quotes.is_empty() {
+    if venues.is_empty() {
         return Err(AmmBridgeError::RouteNotFound);
     }
 
-    let mut remaining = amount_in;
-    let mut segments = Vec::new(env);
+    let step = (amount_in + SPLIT_STEPS - 1) / SPLIT_STEPS;
+    let mut allocs: Vec<i128> = Vec::new(env);
+    for _ in venues.iter() {
+        allocs.push_back(0);
+    }
 
+    let mut remaining = amount_in;
     while remaining > 0 {
-        let mut best: Option<(AmmRouteSegment, i128)> = None;
-
-        for quote in quotes.iter() {
-            let already = allocated_for(&segments, quote.kind, quote.source_id);
-            let avail = quote.available_in - already;
-            let alloc = core::cmp::min(remaining, avail);
-            if alloc <= 0 {
+        let chunk = core::cmp::min(step, remaining);
+        let mut best: Option<(u32, i128)> = None;
+        for (i, venue) in venues.iter().enumerate() {
+            let i = i as u32;
+            let alloc = allocs.get(i).unwrap();
+            let Some((after, _)) = venue_quote(&venue, alloc + chunk) else {
                 continue;
-            }
-
-            let impact =
-                estimate_impact_slippage_bps(alloc, quote.available_in, quote.max_slippage_bps);
-            if impact > max_slippage_bps {
-                continue;
-            }
-
-            let out = alloc * quote.spot_price / BPS_DENOMINATOR;
-            let price = if alloc > 0 {
-                out * BPS_DENOMINATOR / alloc
-            } else {
-                quote.spot_price
-            };
-            let notional = alloc * price / BPS_DENOMINATOR;
-            let fee = apply_bps(notional, quote.fee_bps);
-            let min_out = min_amount_out_with_slippage(out, max_slippage_bps).unwrap_or(0);
-
-            let segment = AmmRouteSegment {
-                kind: quote.kind,
-                source_id: quote.source_id,
-                amount_in: alloc,
-                min_amount_out: min_out,
-                execution_price: price,
-                fee_amount: fee,
-                estimated_slippage_bps: impact,
             };
-
-            match &best {
-                Some((_, best_cost)) if notional + fee >= *best_cost => {}
-                _ => best = Some((segment, notional + fee)),
+            let before = venue_quote(&venue, alloc).map(|q| q.0).unwrap_or(0);
+            let marginal = after - before;
+            if best.map_or(true, |(_, b)| marginal > b) {
+                best = Some((i, marginal));
             }
         }
-
-        let Some((segment, _)) = best else {
+        let Some((i, _)) = best else {
             return Err(AmmBridgeError::NoLiquidity);
         };
-        remaining -= segment.amount_in;
-        segments.push_back(segment);
+        allocs.set(i, allocs.get(i).unwrap() + chunk);
+        remaining -= chunk;
     }
 
-    finalize_route_plan(env, segments, amount_in, reference_price, max_slippage_bps)
-}
-
-fn finalize_route_plan(
-    _env: &Env,
-    segments: Vec<AmmRouteSegment>,
-    amount_in: i128,
-    reference_price: i128,
-    max_slippage_bps: u32,
-) -> Result<AmmRoutePlan, AmmBridgeError> {
-    let mut total_in = 0i128;
-    let mut total_out = 0i128;
+    let mut segments = Vec::new(env);
+    let mut amount_out = 0i128;
     let mut total_fees = 0i128;
-
-    for seg in segments.iter() {
-        total_in += seg.amount_in;
-        total_out += seg.min_amount_out;
-        total_fees += seg.fee_amount;
-    }
-
-    if total_in != amount_in {
-        return Err(AmmBridgeError::NoLiquidity);
+    let mut at_spot = 0i128;
+    for (i, venue) in venues.iter().enumerate() {
+        let alloc = allocs.get(i as u32).unwrap();
+        if alloc == 0 {
+            continue;
+        }
+        let (out, fee) = venue_quote(&venue, alloc).ok_or(AmmBridgeError::NoLiquidity)?;
+        let spot = venue_spot_price(&venue);
+        segments.push_back(AmmRouteSegment {
+            kind: venue.kind,
+            source_id: venue.source_id,
+            amount_in: alloc,
+            min_amount_out: min_amount_out_with_slippage(out, max_slippage_bps).unwrap_or(0),
+            execution_price: out * BPS_DENOMINATOR / alloc,
+            fee_amount: fee,
+            estimated_slippage_bps: impact_bps(spot, alloc, out + fee),
+        });
+        amount_out += out;
+        total_fees += fee;
+        at_spot += alloc * spot / BPS_DENOMINATOR;
     }
 
-    let average_price = if total_in > 0 {
-        total_out * BPS_DENOMINATOR / total_in
-    } else {
-        0
-    };
-
-    let estimated_slippage_bps = if reference_price <= 0 || average_price <= reference_price {
+    let estimated_slippage_bps = if at_spot <= 0 || amount_out + total_fees >= at_spot {
         0
     } else {
-        ((average_price - reference_price) * BPS_DENOMINATOR / reference_price) as u32
+        ((at_spot - amount_out - total_fees) * BPS_DENOMINATOR / at_spot) as u32
     };
-
     if estimated_slippage_bps > max_slippage_bps {
         return Err(AmmBridgeError::SlippageExceeded);
     }
 
     Ok(AmmRoutePlan {
-        amount_in: total_in,
-        amount_out: total_out,
-        average_price,
+        amount_in,
+        amount_out,
+        average_price: amount_out * BPS_DENOMINATOR / amount_in,
         total_fees,
         estimated_slippage_bps,
         segments,
     })
 }
 
-fn allocated_for(segments: &Vec<AmmRouteSegment>, kind: AmmSourceKind, source_id: u32) -> i128 {
-    let mut sum = 0i128;
-    for seg in segments.iter() {
-        if seg.kind == kind && seg.source_id == source_id {
-            sum += seg.amount_in;
-        }
-    }
-    sum
-}
-
 /// Returns fallback source ordering after removing failed sources.
 pub fn build_fallback_chain(
     env: &Env,
@@ -364,48 +427,88 @@ mod tests {
         assert_eq!(min_amount_out_with_slippage(10_000, 10_000), Some(0));
     }
 
-    #[test]
-    fn multi_source_route_splits_across_pools() {
-        let env = Env::default();
-        let mut quotes = Vec::new(&env);
-        quotes.push_back(AmmQuote {
-            kind: AmmSourceKind::BridgePool,
-            source_id: 1,
-            available_in: 50_000,
-            spot_price: 100,
-            fee_bps: 30,
-            max_slippage_bps: 500,
-            expected_out: 5_000,
-        });
-        quotes.push_back(AmmQuote {
+    fn pool(source_id: u32, reserve_in: i128, reserve_out: i128) -> VenueDepth {
+        VenueDepth {
             kind: AmmSourceKind::StellarAmm,
-            source_id: 2,
-            available_in: 40_000,
-            spot_price: 100,
+            source_id,
             fee_bps: 30,
-            max_slippage_bps: 500,
-            expected_out: 4_000,
-        });
-
-        let plan = plan_multi_source_route(&env, &quotes, 80_000, 100, 1_000).unwrap();
-        assert_eq!(plan.amount_in, 80_000);
-        assert!(!plan.segments.is_empty());
+            state: VenueState::Pool(reserve_in, reserve_out),
+        }
     }
 
-    #[test]
-    fn rejects_excessive_slippage() {
-        let env = Env::default();
-        let mut quotes = Vec::new(&env);
-        quotes.push_back(AmmQuote {
+    fn book(env: &Env, levels: &[(i128, i128)]) -> VenueDepth {
+        let mut book = Vec::new(env);
+        for (price, amount_in) in levels {
+            book.push_back(BookLevel {
+                price: *price,
+                amount_in: *amount_in,
+            });
+        }
+        VenueDepth {
             kind: AmmSourceKind::SdexRouter,
             source_id: 1,
-            available_in: 100,
-            spot_price: 200,
             fee_bps: 0,
-            max_slippage_bps: 900,
-            expected_out: 50,
-        });
-        assert!(plan_multi_source_route(&env, &quotes, 100, 100, 50).is_err());
+            state: VenueState::OrderBook(book),
+        }
+    }
+
+    #[test]
+    fn book_quote_walks_levels() {
+        let env = Env::default();
+        let depth = book(&env, &[(10_000, 100), (9_000, 100)]);
+        assert_eq!(venue_quote(&depth, 150), Some((145, 0)));
+        assert_eq!(venue_quote(&depth, 201), None);
+        assert_eq!(venue_capacity(&depth), 200);
+        assert_eq!(venue_spot_price(&depth), 10_000);
+    }
+
+    #[test]
+    fn optimal_split_balances_equal_pools() {
+        let env = Env::default();
+        let mut venues = Vec::new(&env);
+        venues.push_back(pool(1, 1_000_000, 1_000_000));
+        venues.push_back(pool(2, 1_000_000, 1_000_000));
+
+        let plan = plan_optimal_split(&env, &venues, 100_000, 1_000).unwrap();
+        assert_eq!(plan.amount_in, 100_000);
+        assert_eq!(plan.segments.len(), 2);
+        assert_eq!(plan.segments.get(0).unwrap().amount_in, 50_000);
+
+        // Splitting beats routing everything through one pool.
+        let single = quote_constant_product(100_000, 1_000_000, 1_000_000, 30).unwrap();
+        assert!(plan.amount_out > single);
+    }
+
+    #[test]
+    fn optimal_split_fills_book_before_pool_impact() {
+        let env = Env::default();
+        let mut venues = Vec::new(&env);
+        venues.push_back(book(&env, &[(10_000, 30_000)]));
+        venues.push_back(pool(2, 200_000, 200_000));
+
+        let plan = plan_optimal_split(&env, &venues, 50_000, 2_000).unwrap();
+        let sdex = plan.segments.get(0).unwrap();
+        assert_eq!(sdex.kind, AmmSourceKind::SdexRouter);
+        assert_eq!(sdex.amount_in, 30_000);
+        assert_eq!(plan.segments.get(1).unwrap().amount_in, 20_000);
+        assert!(plan.total_fees > 0);
+    }
+
+    #[test]
+    fn optimal_split_rejects_excessive_impact() {
+        let env = Env::default();
+        let mut venues = Vec::new(&env);
+        venues.push_back(pool(1, 10_000, 10_000));
+        assert_eq!(
+            plan_optimal_split(&env, &venues, 5_000, 100),
+            Err(AmmBridgeError::SlippageExceeded)
+        );
+        let mut shallow = Vec::new(&env);
+        shallow.push_back(book(&env, &[(10_000, 100)]));
+        assert_eq!(
+            plan_optimal_split(&env, &shallow, 1_000, 100),
+            Err(AmmBridgeError::NoLiquidity)
+        );
     }
 
     #[test]
@@ -35,6 +35,32 @@ pub fn hash_trade_intent(
     env.crypto().sha256(&preimage).into()
 }
 
+/// `SHA-256( "sw_signal_v1" || provider || asset_pair || action || price
+/// || rationale || salt )` as a [`BytesN<32>`].
+///
+/// Binds a provider to a signal's content before it is published, so the
+/// call can be revealed later and checked against the commitment's ledger
+/// time. `action` is `0` for buy and `1` for sell.
+pub fn hash_signal_commitment(
+    env: &Env,
+    provider: &Address,
+    asset_pair: &String,
+    action: u32,
+    price: i128,
+    rationale: &String,
+    salt: u64,
+) -> BytesN<32> {
+    let mut preimage = Bytes::new(env);
+    preimage.append(&String::from_str(env, "sw_signal_v1").to_bytes());
+    preimage.append(&provider.to_string().to_bytes());
+    preimage.append(&asset_pair.to_bytes());
+    preimage.append(&Bytes::from_array(env, &action.to_be_bytes()));
+    preimage.append(&Bytes::from_array(env, &price.to_be_bytes()));
+    preimage.append(&rationale.to_bytes());
+    preimage.append(&Bytes::from_array(env, &salt.to_be_bytes()));
+    env.crypto().sha256(&preimage).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,4 +83,17 @@ mod tests {
         let h2 = hash_trade_intent(&env, &a, 5, 1_000_001, 900_000, 42, 1_000_000);
         assert_ne!(h1, h2);
     }
+
+    #[test]
+    fn signal_commitment_binds_price() {
+        let env = Env::default();
+        let p = Address::generate(&env);
+        let pair = String::from_str(&env, "XLM/USDC");
+        let why = String::from_str(&env, "breakout");
+        let h1 = hash_signal_commitment(&env, &p, &pair, 0, 120_000, &why, 7);
+        let h2 = hash_signal_commitment(&env, &p, &pair, 0, 120_000, &why, 7);
+        let h3 = hash_signal_commitment(&env, &p, &pair, 0, 120_001, &why, 7);
+        assert_eq!(h1, h2);
+        assert_ne!(h1, h3);
+    }
 }
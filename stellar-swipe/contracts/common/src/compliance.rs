@@ -0,0 +1,136 @@
+//! Compliance registry interface shared across contracts.
+//!
+//! The compliance contract keeps one [`Attestation`] per account, written by
+//! an approved issuer in the shape a SEP-12 anchor reports a customer: the
+//! anchor's customer id, its status, a verification level and the customer's
+//! jurisdiction. Admin-set [`JurisdictionRule`]s can block a jurisdiction
+//! outright, raise the level it needs, or block specific asset pairs.
+//!
+//! Contracts that gate trades, subscriptions or bridge transfers store only
+//! the registry address and call [`require_compliant`]; no contract keeps its
+//! own KYC flags.
+//!
+//! | Check                               | Error                           |
+//! |-------------------------------------|---------------------------------|
+//! | No attestation                      | `NoAttestation`                 |
+//! | Issuer removed since attesting      | `IssuerNotApproved`             |
+//! | Revoked                             | `AttestationRevoked`            |
+//! | Past `expires_at`                   | `AttestationExpired`            |
+//! | Status other than `Accepted`        | `NotAccepted`                   |
+//! | Jurisdiction blocked                | `JurisdictionBlocked`           |
+//! | Asset pair blocked in jurisdiction  | `AssetPairBlocked`              |
+//! | Level below caller's or rule's min  | `LevelTooLow`                   |
+//! | Registry call failed                | `CallFailed`                    |
+//!
+//! Writing over another approved issuer's live attestation fails with
+//! `AttestedByOtherIssuer`; the owning issuer or the admin revokes it first.
+
+use soroban_sdk::{contracterror, contracttype, vec, Address, Env, IntoVal, String, Symbol, Vec};
+
+/// Verification depth, ordered from weakest to strongest.
+#[contracttype]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ComplianceLevel {
+    None = 0,
+    Basic = 1,
+    Enhanced = 2,
+    Full = 3,
+}
+
+/// SEP-12 customer status as reported by the issuing anchor.
+#[contracttype]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum CustomerStatus {
+    Accepted = 0,
+    Processing = 1,
+    NeedsInfo = 2,
+    Rejected = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attestation {
+    pub subject: Address,
+    pub issuer: Address,
+    /// Customer id assigned by the issuing anchor (SEP-12 `id`).
+    pub customer_id: String,
+    pub status: CustomerStatus,
+    pub level: ComplianceLevel,
+    /// ISO 3166-1 alpha-2 country code, e.g. `US`.
+    pub jurisdiction: Symbol,
+    pub issued_at: u64,
+    /// 0 = never expires.
+    pub expires_at: u64,
+    pub revoked: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JurisdictionRule {
+    /// Accounts in this jurisdiction may not use any gated path.
+    pub blocked: bool,
+    /// Minimum level for accounts in this jurisdiction, on top of the
+    /// caller's own minimum.
+    pub min_level: ComplianceLevel,
+    /// Asset pair ids accounts in this jurisdiction may not trade.
+    pub blocked_pairs: Vec<u32>,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ComplianceError {
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+    Unauthorized = 3,
+    IssuerNotApproved = 4,
+    NoAttestation = 5,
+    AttestationRevoked = 6,
+    AttestationExpired = 7,
+    NotAccepted = 8,
+    LevelTooLow = 9,
+    JurisdictionBlocked = 10,
+    AssetPairBlocked = 11,
+    InvalidExpiry = 12,
+    CallFailed = 13,
+    AttestedByOtherIssuer = 14,
+}
+
+/// Ask the registry at `registry` whether `subject` may act at `min_level`,
+/// optionally on `asset_pair`. Returns the subject's attested level.
+pub fn require_compliant(
+    env: &Env,
+    registry: &Address,
+    subject: &Address,
+    min_level: ComplianceLevel,
+    asset_pair: Option<u32>,
+) -> Result<ComplianceLevel, ComplianceError> {
+    match env.try_invoke_contract::<ComplianceLevel, ComplianceError>(
+        registry,
+        &Symbol::new(env, "check"),
+        vec![
+            env,
+            subject.into_val(env),
+            min_level.into_val(env),
+            asset_pair.into_val(env),
+        ],
+    ) {
+        Ok(Ok(level)) => Ok(level),
+        Err(Ok(err)) => Err(err),
+        Ok(Err(_)) | Err(Err(_)) => Err(ComplianceError::CallFailed),
+    }
+}
+
+/// The subject's current attestation as stored by the registry, if any.
+pub fn get_attestation(env: &Env, registry: &Address, subject: &Address) -> Option<Attestation> {
+    match env.try_invoke_contract::<Option<Attestation>, ComplianceError>(
+        registry,
+        &Symbol::new(env, "get_attestation"),
+        vec![env, subject.into_val(env)],
+    ) {
+        Ok(Ok(attestation)) => attestation,
+        _ => None,
+    }
+}
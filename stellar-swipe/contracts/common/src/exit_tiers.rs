@@ -0,0 +1,129 @@
+//! Tiered take-profit / trailing-stop exits.
+//!
+//! Used by AutoTrade exit strategies and TradeExecutor bracket orders, so a
+//! tier list behaves the same wherever it is attached.
+//!
+//! - A [`TakeProfitTier`] sells `position_pct` of the size still open when
+//!   the price reaches `price`; `3_333`, `5_000`, `10_000` closes a position
+//!   in thirds.
+//! - A [`StopLossTier`] arms once profit reaches `trigger_profit_pct`. The
+//!   tightest armed `trail_pct` sets a stop that far below the highest price
+//!   seen, and breaking it sells everything left.
+
+use soroban_sdk::{contracttype, Env, Vec};
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TakeProfitTier {
+    pub price: i128,
+    pub position_pct: u32, // basis points (10000 = 100%)
+    pub executed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StopLossTier {
+    pub trigger_profit_pct: u32, // activate after this % profit (0-based)
+    pub trail_pct: u32,          // trail distance in %
+    pub active: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitKind {
+    TakeProfit,
+    TrailingStop,
+}
+
+/// One sale produced by [`apply_exit_tiers`].
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExitFill {
+    pub kind: ExitKind,
+    /// Tier price for a take-profit, stop price for a trailing stop.
+    pub trigger_price: i128,
+    pub amount: i128,
+    /// Size still open after this fill.
+    pub remaining: i128,
+}
+
+pub fn trailing_stop_price(highest_price: i128, trail_pct: u32) -> i128 {
+    highest_price * (100 - trail_pct as i128) / 100
+}
+
+/// Run the tiers against `current_price`, updating the peak, the open size
+/// and the tier flags in place. Take-profits fire first, in tier order.
+pub fn apply_exit_tiers(
+    env: &Env,
+    entry_price: i128,
+    current_price: i128,
+    highest_price: &mut i128,
+    remaining: &mut i128,
+    take_profit_tiers: &mut Vec<TakeProfitTier>,
+    stop_loss_tiers: &mut Vec<StopLossTier>,
+) -> Vec<ExitFill> {
+    let mut fills = Vec::new(env);
+
+    // Update highest price for trailing stop
+    if current_price > *highest_price {
+        *highest_price = current_price;
+    }
+
+    // ── Take-profit tiers ─────────────────────────────────────────────────────
+    for i in 0..take_profit_tiers.len() {
+        let mut tp = take_profit_tiers.get(i).unwrap();
+        if !tp.executed && current_price >= tp.price && *remaining > 0 {
+            let close_amount = ((*remaining * tp.position_pct as i128) / 10_000).max(1);
+            *remaining -= close_amount;
+            tp.executed = true;
+            take_profit_tiers.set(i, tp.clone());
+            fills.push_back(ExitFill {
+                kind: ExitKind::TakeProfit,
+                trigger_price: tp.price,
+                amount: close_amount,
+                remaining: *remaining,
+            });
+        }
+    }
+
+    // ── Trailing stop tiers ───────────────────────────────────────────────────
+    if *remaining > 0 {
+        let current_profit_pct = if entry_price > 0 {
+            ((current_price - entry_price) * 100) / entry_price
+        } else {
+            0
+        };
+
+        // Activate tiers whose profit threshold has been crossed, and find
+        // the tightest active trail_pct.
+        let mut tightest_trail: Option<u32> = None;
+        for i in 0..stop_loss_tiers.len() {
+            let mut tier = stop_loss_tiers.get(i).unwrap();
+            if current_profit_pct >= tier.trigger_profit_pct as i128 {
+                tier.active = true;
+                stop_loss_tiers.set(i, tier.clone());
+            }
+            if tier.active {
+                tightest_trail = Some(match tightest_trail {
+                    None => tier.trail_pct,
+                    Some(prev) => prev.min(tier.trail_pct),
+                });
+            }
+        }
+
+        if let Some(trail_pct) = tightest_trail {
+            let stop_price = trailing_stop_price(*highest_price, trail_pct);
+            if current_price < stop_price {
+                fills.push_back(ExitFill {
+                    kind: ExitKind::TrailingStop,
+                    trigger_price: stop_price,
+                    amount: *remaining,
+                    remaining: 0,
+                });
+                *remaining = 0;
+            }
+        }
+    }
+
+    fills
+}
@@ -0,0 +1,276 @@
+//! Volume-tiered fee schedules shared by FeeCollector and SignalRegistry.
+//!
+//! A [`FeeSchedule`] is an ascending list of [`FeeTier`]s keyed by rolling
+//! 30-day USD volume, each with its own maker and taker rate, plus optional
+//! [`StakingDiscount`]s for protocol-token stakers. FeeCollector charges from
+//! the schedule and SignalRegistry previews from it, so both go through
+//! [`quote_fee`] and always agree on the numbers shown to traders.
+
+use soroban_sdk::{contracttype, Env, Vec};
+
+use crate::constants::BASIS_POINTS_DENOMINATOR_I128;
+
+/// Maximum number of volume tiers in one schedule.
+pub const MAX_FEE_TIERS: u32 = 10;
+/// Maximum number of staking discount steps in one schedule.
+pub const MAX_STAKING_DISCOUNTS: u32 = 5;
+/// Length of the rolling volume window tiers are evaluated against.
+pub const FEE_VOLUME_WINDOW_DAYS: u64 = 30;
+/// Floor applied to the discounted rate when fees are charged or previewed.
+pub const MIN_TIER_RATE_BPS: u32 = 1;
+/// Highest tier rate a schedule may configure.
+pub const MAX_TIER_RATE_BPS: u32 = 100;
+
+/// Which side of the book the trader is on.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeeSide {
+    /// Adds liquidity (resting limit order).
+    Maker,
+    /// Removes liquidity (market order or crossing limit).
+    Taker,
+}
+
+/// One volume tier. Applies to traders whose rolling volume is at least
+/// `min_volume_usd` and below the next tier's threshold.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeTier {
+    /// Rolling 30-day USD volume (7 decimals) needed to enter the tier.
+    pub min_volume_usd: i128,
+    pub maker_rate_bps: u32,
+    pub taker_rate_bps: u32,
+}
+
+/// Rate reduction for traders staking at least `min_staked` protocol tokens.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StakingDiscount {
+    pub min_staked: i128,
+    pub discount_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSchedule {
+    /// Ascending by `min_volume_usd`; the first tier must start at zero.
+    pub tiers: Vec<FeeTier>,
+    /// Ascending by `min_staked`; the highest step reached applies.
+    pub staking_discounts: Vec<StakingDiscount>,
+}
+
+/// Fee charged for one trade under a schedule.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeQuote {
+    pub tier_index: u32,
+    /// Tier rate for the side before the staking discount.
+    pub tier_rate_bps: u32,
+    pub staking_discount_bps: u32,
+    /// Rate actually charged.
+    pub rate_bps: u32,
+    pub fee: i128,
+    pub amount_after_fee: i128,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeeScheduleError {
+    EmptySchedule,
+    TooManyEntries,
+    /// Tiers or discount steps are not strictly ascending, or the first tier
+    /// does not start at zero volume.
+    NotAscending,
+    RateTooHigh,
+    ArithmeticOverflow,
+}
+
+impl FeeSchedule {
+    /// Single-tier schedule charging `rate_bps` on both sides.
+    pub fn flat(env: &Env, rate_bps: u32) -> Self {
+        let mut tiers = Vec::new(env);
+        tiers.push_back(FeeTier {
+            min_volume_usd: 0,
+            maker_rate_bps: rate_bps,
+            taker_rate_bps: rate_bps,
+        });
+        FeeSchedule {
+            tiers,
+            staking_discounts: Vec::new(env),
+        }
+    }
+}
+
+/// Check ordering and bounds. Rates and discounts may not exceed `max_rate_bps`.
+pub fn validate_fee_schedule(
+    schedule: &FeeSchedule,
+    max_rate_bps: u32,
+) -> Result<(), FeeScheduleError> {
+    if schedule.tiers.is_empty() {
+        return Err(FeeScheduleError::EmptySchedule);
+    }
+    if schedule.tiers.len() > MAX_FEE_TIERS
+        || schedule.staking_discounts.len() > MAX_STAKING_DISCOUNTS
+    {
+        return Err(FeeScheduleError::TooManyEntries);
+    }
+
+    let mut previous: Option<i128> = None;
+    for tier in schedule.tiers.iter() {
+        match previous {
+            None if tier.min_volume_usd != 0 => return Err(FeeScheduleError::NotAscending),
+            Some(prev) if tier.min_volume_usd <= prev => {
+                return Err(FeeScheduleError::NotAscending)
+            }
+            _ => {}
+        }
+        if tier.maker_rate_bps > max_rate_bps || tier.taker_rate_bps > max_rate_bps {
+            return Err(FeeScheduleError::RateTooHigh);
+        }
+        previous = Some(tier.min_volume_usd);
+    }
+
+    let mut previous_stake = 0i128;
+    for discount in schedule.staking_discounts.iter() {
+        if discount.min_staked <= previous_stake {
+            return Err(FeeScheduleError::NotAscending);
+        }
+        if discount.discount_bps > max_rate_bps {
+            return Err(FeeScheduleError::RateTooHigh);
+        }
+        previous_stake = discount.min_staked;
+    }
+    Ok(())
+}
+
+/// Index and tier that apply to `volume_usd`. The schedule must be non-empty.
+pub fn tier_for_volume(schedule: &FeeSchedule, volume_usd: i128) -> (u32, FeeTier) {
+    let mut index = 0u32;
+    let mut selected = schedule.tiers.get(0).unwrap();
+    let mut i = 1u32;
+    while i < schedule.tiers.len() {
+        let tier = schedule.tiers.get(i).unwrap();
+        if volume_usd < tier.min_volume_usd {
+            break;
+        }
+        index = i;
+        selected = tier;
+        i += 1;
+    }
+    (index, selected)
+}
+
+/// Discount earned by a holder staking `staked` protocol tokens.
+pub fn staking_discount_bps(schedule: &FeeSchedule, staked: i128) -> u32 {
+    let mut discount = 0u32;
+    for step in schedule.staking_discounts.iter() {
+        if staked < step.min_staked {
+            break;
+        }
+        discount = step.discount_bps;
+    }
+    discount
+}
+
+/// Quote the fee on `trade_amount` for a trader with the given rolling volume
+/// and stake. The rate never drops below `min_rate_bps`; the fee is rounded
+/// down in the trader's favour.
+pub fn quote_fee(
+    schedule: &FeeSchedule,
+    side: FeeSide,
+    volume_usd: i128,
+    staked: i128,
+    trade_amount: i128,
+    min_rate_bps: u32,
+) -> Result<FeeQuote, FeeScheduleError> {
+    if schedule.tiers.is_empty() {
+        return Err(FeeScheduleError::EmptySchedule);
+    }
+    let (tier_index, tier) = tier_for_volume(schedule, volume_usd);
+    let tier_rate_bps = match side {
+        FeeSide::Maker => tier.maker_rate_bps,
+        FeeSide::Taker => tier.taker_rate_bps,
+    };
+    let discount = staking_discount_bps(schedule, staked);
+    let rate_bps = tier_rate_bps.saturating_sub(discount).max(min_rate_bps);
+
+    let fee = trade_amount
+        .checked_mul(rate_bps as i128)
+        .and_then(|v| v.checked_div(BASIS_POINTS_DENOMINATOR_I128))
+        .ok_or(FeeScheduleError::ArithmeticOverflow)?;
+    let amount_after_fee = trade_amount
+        .checked_sub(fee)
+        .ok_or(FeeScheduleError::ArithmeticOverflow)?;
+
+    Ok(FeeQuote {
+        tier_index,
+        tier_rate_bps,
+        staking_discount_bps: discount,
+        rate_bps,
+        fee,
+        amount_after_fee,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(env: &Env) -> FeeSchedule {
+        let mut tiers = Vec::new(env);
+        tiers.push_back(FeeTier {
+            min_volume_usd: 0,
+            maker_rate_bps: 20,
+            taker_rate_bps: 30,
+        });
+        tiers.push_back(FeeTier {
+            min_volume_usd: 1_000,
+            maker_rate_bps: 10,
+            taker_rate_bps: 25,
+        });
+        let mut staking_discounts = Vec::new(env);
+        staking_discounts.push_back(StakingDiscount {
+            min_staked: 500,
+            discount_bps: 5,
+        });
+        FeeSchedule {
+            tiers,
+            staking_discounts,
+        }
+    }
+
+    #[test]
+    fn quote_uses_tier_side_and_staking_discount() {
+        let env = Env::default();
+        let s = schedule(&env);
+        assert_eq!(validate_fee_schedule(&s, 100), Ok(()));
+
+        let q = quote_fee(&s, FeeSide::Taker, 999, 0, 100_000, 1).unwrap();
+        assert_eq!((q.tier_index, q.rate_bps, q.fee), (0, 30, 300));
+
+        let q = quote_fee(&s, FeeSide::Maker, 1_000, 500, 100_000, 1).unwrap();
+        assert_eq!((q.tier_index, q.tier_rate_bps, q.rate_bps), (1, 10, 5));
+        assert_eq!(q.amount_after_fee, 99_950);
+
+        let q = quote_fee(&s, FeeSide::Maker, 1_000, 500, 100_000, 8).unwrap();
+        assert_eq!(q.rate_bps, 8);
+    }
+
+    #[test]
+    fn validation_rejects_unordered_tiers() {
+        let env = Env::default();
+        let mut s = schedule(&env);
+        s.tiers.push_back(FeeTier {
+            min_volume_usd: 1_000,
+            maker_rate_bps: 5,
+            taker_rate_bps: 5,
+        });
+        assert_eq!(
+            validate_fee_schedule(&s, 100),
+            Err(FeeScheduleError::NotAscending)
+        );
+        assert_eq!(
+            validate_fee_schedule(&schedule(&env), 20),
+            Err(FeeScheduleError::RateTooHigh)
+        );
+    }
+}
@@ -4,8 +4,11 @@
 pub mod amm_bridge;
 pub mod assets;
 pub mod commit_reveal;
+pub mod compliance;
 pub mod constants;
 pub mod emergency;
+pub mod exit_tiers;
+pub mod fee_tiers;
 pub mod health;
 #[allow(deprecated)]
 pub mod multisig;
@@ -18,12 +21,18 @@ pub mod replay_protection;
 
 pub use amm_bridge::{
     build_fallback_chain, emit_fallback_used, emit_quote_discovered, emit_route_planned,
-    min_amount_out_with_slippage, plan_multi_source_route, quote_constant_product,
-    quote_from_pool_reserves, rank_quotes_by_price, AmmBridgeError, AmmQuote, AmmRoutePlan,
-    AmmRouteSegment, AmmSourceConfig, AmmSourceKind, BPS_DENOMINATOR, FN_GET_BEST_ASK, FN_SWAP,
+    min_amount_out_with_slippage, plan_optimal_split, quote_constant_product,
+    quote_from_pool_reserves, rank_quotes_by_price, venue_quote, AmmBridgeError, AmmQuote,
+    AmmRoutePlan, AmmRouteSegment, AmmSourceConfig, AmmSourceKind, BookLevel, VenueDepth,
+    VenueState, BPS_DENOMINATOR, FN_CANCEL_OFFER, FN_GET_BEST_ASK, FN_GET_OFFER, FN_GET_ORDER_BOOK,
+    FN_GET_RESERVES, FN_PLACE_OFFER, FN_SWAP,
 };
 pub use assets::{validate_asset_pair, Asset, AssetPair, AssetPairError};
-pub use commit_reveal::hash_trade_intent;
+pub use commit_reveal::{hash_signal_commitment, hash_trade_intent};
+pub use compliance::{
+    require_compliant, Attestation, ComplianceError, ComplianceLevel, CustomerStatus,
+    JurisdictionRule,
+};
 pub use constants::{
     BASIS_POINTS_DENOMINATOR, BASIS_POINTS_DENOMINATOR_I128, CAT_ALL, CAT_SIGNALS, CAT_STAKES,
     CAT_TRADING, LEDGERS_PER_30_DAY_MONTH, LEDGERS_PER_DAY, PLACEHOLDER_ADMIN_STR,
@@ -31,6 +40,14 @@ pub use constants::{
     STELLAR_AMOUNT_SCALE,
 };
 pub use emergency::PauseState;
+pub use exit_tiers::{
+    apply_exit_tiers, trailing_stop_price, ExitFill, ExitKind, StopLossTier, TakeProfitTier,
+};
+pub use fee_tiers::{
+    quote_fee, staking_discount_bps, tier_for_volume, validate_fee_schedule, FeeQuote, FeeSchedule,
+    FeeScheduleError, FeeSide, FeeTier, StakingDiscount, FEE_VOLUME_WINDOW_DAYS, MAX_FEE_TIERS,
+    MAX_STAKING_DISCOUNTS, MAX_TIER_RATE_BPS, MIN_TIER_RATE_BPS,
+};
 pub use health::{health_uninitialized, placeholder_admin, HealthStatus};
 pub use multisig::{
     approve, cancel, emit_approval_recorded, emit_proposal_approved, emit_proposal_cancelled,
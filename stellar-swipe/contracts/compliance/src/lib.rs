@@ -0,0 +1,263 @@
+#![no_std]
+//! Compliance registry: the single source of KYC state for every contract.
+//!
+//! The admin approves issuers (KYC providers or anchors). An issuer writes an
+//! [`Attestation`] for an account from its SEP-12 customer record; the latest
+//! attestation for an account replaces any earlier one. While an attestation
+//! is live (not revoked, unexpired, issuer still approved) only its own
+//! issuer may replace it. The issuer or the admin can revoke it, and removing
+//! an issuer voids everything it attested.
+//!
+//! The admin also sets a [`JurisdictionRule`] per country code. Trade,
+//! subscription and bridge paths in other contracts call [`check`] through
+//! `stellar_swipe_common::require_compliant`.
+//!
+//! [`check`]: ComplianceRegistry::check
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Symbol};
+pub use stellar_swipe_common::compliance::{
+    Attestation, ComplianceError, ComplianceLevel, CustomerStatus, JurisdictionRule,
+};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    /// Approved issuer flag (bool).
+    Issuer(Address),
+    /// Current attestation for an account.
+    Attestation(Address),
+    /// Rule for a jurisdiction code.
+    Rule(Symbol),
+}
+
+#[contract]
+pub struct ComplianceRegistry;
+
+#[contractimpl]
+impl ComplianceRegistry {
+    pub fn initialize(env: Env, admin: Address) -> Result<(), ComplianceError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(ComplianceError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn get_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Admin)
+    }
+
+    /// Admin: approve an issuer to write attestations.
+    pub fn add_issuer(env: Env, admin: Address, issuer: Address) -> Result<(), ComplianceError> {
+        require_admin(&env, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Issuer(issuer.clone()), &true);
+        #[allow(deprecated)]
+        env.events()
+            .publish((Symbol::new(&env, "issuer_added"), issuer), ());
+        Ok(())
+    }
+
+    /// Admin: withdraw an issuer's approval. Its attestations stop counting.
+    pub fn remove_issuer(env: Env, admin: Address, issuer: Address) -> Result<(), ComplianceError> {
+        require_admin(&env, &admin)?;
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Issuer(issuer.clone()));
+        #[allow(deprecated)]
+        env.events()
+            .publish((Symbol::new(&env, "issuer_removed"), issuer), ());
+        Ok(())
+    }
+
+    pub fn is_issuer(env: Env, issuer: Address) -> bool {
+        is_issuer(&env, &issuer)
+    }
+
+    /// Issuer: record `subject`'s SEP-12 customer record. `expires_at` is a
+    /// ledger timestamp, 0 for no expiry. Fails with `AttestedByOtherIssuer`
+    /// while another issuer's attestation for `subject` is still live.
+    pub fn attest(
+        env: Env,
+        issuer: Address,
+        subject: Address,
+        customer_id: String,
+        status: CustomerStatus,
+        level: ComplianceLevel,
+        jurisdiction: Symbol,
+        expires_at: u64,
+    ) -> Result<(), ComplianceError> {
+        issuer.require_auth();
+        if !is_issuer(&env, &issuer) {
+            return Err(ComplianceError::IssuerNotApproved);
+        }
+        let now = env.ledger().timestamp();
+        if expires_at != 0 && expires_at <= now {
+            return Err(ComplianceError::InvalidExpiry);
+        }
+        if let Some(current) = Self::get_attestation(env.clone(), subject.clone()) {
+            let live = !current.revoked
+                && is_issuer(&env, &current.issuer)
+                && (current.expires_at == 0 || now < current.expires_at);
+            if live && current.issuer != issuer {
+                return Err(ComplianceError::AttestedByOtherIssuer);
+            }
+        }
+        let attestation = Attestation {
+            subject: subject.clone(),
+            issuer: issuer.clone(),
+            customer_id,
+            status,
+            level,
+            jurisdiction: jurisdiction.clone(),
+            issued_at: now,
+            expires_at,
+            revoked: false,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Attestation(subject.clone()), &attestation);
+        #[allow(deprecated)]
+        env.events().publish(
+            (Symbol::new(&env, "attested"), subject),
+            (issuer, status, level, jurisdiction, expires_at),
+        );
+        Ok(())
+    }
+
+    /// Issuer of the attestation or admin: revoke `subject`'s attestation.
+    pub fn revoke_attestation(
+        env: Env,
+        caller: Address,
+        subject: Address,
+    ) -> Result<(), ComplianceError> {
+        caller.require_auth();
+        let key = DataKey::Attestation(subject.clone());
+        let mut attestation: Attestation = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ComplianceError::NoAttestation)?;
+        if caller != attestation.issuer && Some(caller.clone()) != Self::get_admin(env.clone()) {
+            return Err(ComplianceError::Unauthorized);
+        }
+        attestation.revoked = true;
+        env.storage().persistent().set(&key, &attestation);
+        #[allow(deprecated)]
+        env.events()
+            .publish((Symbol::new(&env, "attestation_revoked"), subject), caller);
+        Ok(())
+    }
+
+    pub fn get_attestation(env: Env, subject: Address) -> Option<Attestation> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Attestation(subject))
+    }
+
+    /// Admin: set the rule for `jurisdiction`, replacing any earlier one.
+    pub fn set_jurisdiction_rule(
+        env: Env,
+        admin: Address,
+        jurisdiction: Symbol,
+        rule: JurisdictionRule,
+    ) -> Result<(), ComplianceError> {
+        require_admin(&env, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Rule(jurisdiction.clone()), &rule);
+        #[allow(deprecated)]
+        env.events().publish(
+            (Symbol::new(&env, "jurisdiction_rule_set"), jurisdiction),
+            (rule.blocked, rule.min_level, rule.blocked_pairs),
+        );
+        Ok(())
+    }
+
+    /// Admin: drop the rule for `jurisdiction`.
+    pub fn remove_jurisdiction_rule(
+        env: Env,
+        admin: Address,
+        jurisdiction: Symbol,
+    ) -> Result<(), ComplianceError> {
+        require_admin(&env, &admin)?;
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Rule(jurisdiction));
+        Ok(())
+    }
+
+    pub fn get_jurisdiction_rule(env: Env, jurisdiction: Symbol) -> Option<JurisdictionRule> {
+        env.storage().persistent().get(&DataKey::Rule(jurisdiction))
+    }
+
+    /// Whether `subject` may act at `min_level`, optionally on `asset_pair`.
+    /// Returns the attested level.
+    pub fn check(
+        env: Env,
+        subject: Address,
+        min_level: ComplianceLevel,
+        asset_pair: Option<u32>,
+    ) -> Result<ComplianceLevel, ComplianceError> {
+        let attestation =
+            Self::get_attestation(env.clone(), subject).ok_or(ComplianceError::NoAttestation)?;
+        if attestation.revoked {
+            return Err(ComplianceError::AttestationRevoked);
+        }
+        if !is_issuer(&env, &attestation.issuer) {
+            return Err(ComplianceError::IssuerNotApproved);
+        }
+        if attestation.expires_at != 0 && env.ledger().timestamp() >= attestation.expires_at {
+            return Err(ComplianceError::AttestationExpired);
+        }
+        if attestation.status != CustomerStatus::Accepted {
+            return Err(ComplianceError::NotAccepted);
+        }
+        let mut required = min_level;
+        if let Some(rule) = Self::get_jurisdiction_rule(env.clone(), attestation.jurisdiction) {
+            if rule.blocked {
+                return Err(ComplianceError::JurisdictionBlocked);
+            }
+            if asset_pair.is_some_and(|pair| rule.blocked_pairs.contains(pair)) {
+                return Err(ComplianceError::AssetPairBlocked);
+            }
+            required = required.max(rule.min_level);
+        }
+        if attestation.level < required {
+            return Err(ComplianceError::LevelTooLow);
+        }
+        Ok(attestation.level)
+    }
+
+    /// Attested level if the attestation currently passes [`Self::check`]
+    /// with no minimum, otherwise `ComplianceLevel::None`.
+    pub fn get_level(env: Env, subject: Address) -> ComplianceLevel {
+        Self::check(env, subject, ComplianceLevel::None, None).unwrap_or(ComplianceLevel::None)
+    }
+}
+
+fn require_admin(env: &Env, caller: &Address) -> Result<(), ComplianceError> {
+    let admin: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(ComplianceError::NotInitialized)?;
+    if *caller != admin {
+        return Err(ComplianceError::Unauthorized);
+    }
+    caller.require_auth();
+    Ok(())
+}
+
+fn is_issuer(env: &Env, issuer: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Issuer(issuer.clone()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,305 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{symbol_short, vec, Vec};
+
+fn setup(env: &Env) -> (ComplianceRegistryClient<'_>, Address, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, ComplianceRegistry);
+    let client = ComplianceRegistryClient::new(env, &contract_id);
+    let admin = Address::generate(env);
+    let issuer = Address::generate(env);
+    client.initialize(&admin);
+    client.add_issuer(&admin, &issuer);
+    (client, admin, issuer)
+}
+
+fn attest(
+    env: &Env,
+    client: &ComplianceRegistryClient,
+    issuer: &Address,
+    subject: &Address,
+    level: ComplianceLevel,
+    jurisdiction: Symbol,
+) {
+    client.attest(
+        issuer,
+        subject,
+        &String::from_str(env, "cust-1"),
+        &CustomerStatus::Accepted,
+        &level,
+        &jurisdiction,
+        &0,
+    );
+}
+
+#[test]
+fn test_attested_account_passes_up_to_its_level() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let user = Address::generate(&env);
+
+    assert_eq!(
+        client.try_check(&user, &ComplianceLevel::Basic, &None),
+        Err(Ok(ComplianceError::NoAttestation))
+    );
+
+    attest(
+        &env,
+        &client,
+        &issuer,
+        &user,
+        ComplianceLevel::Enhanced,
+        symbol_short!("DE"),
+    );
+    assert_eq!(
+        client.check(&user, &ComplianceLevel::Basic, &Some(1)),
+        ComplianceLevel::Enhanced
+    );
+    assert_eq!(
+        client.try_check(&user, &ComplianceLevel::Full, &None),
+        Err(Ok(ComplianceError::LevelTooLow))
+    );
+    assert_eq!(client.get_level(&user), ComplianceLevel::Enhanced);
+    let stored = client.get_attestation(&user).unwrap();
+    assert_eq!(stored.issuer, issuer);
+    assert_eq!(stored.customer_id, String::from_str(&env, "cust-1"));
+}
+
+#[test]
+fn test_only_approved_issuers_attest() {
+    let env = Env::default();
+    let (client, _admin, _issuer) = setup(&env);
+    let stranger = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let result = client.try_attest(
+        &stranger,
+        &user,
+        &String::from_str(&env, "cust-1"),
+        &CustomerStatus::Accepted,
+        &ComplianceLevel::Full,
+        &symbol_short!("US"),
+        &0,
+    );
+    assert_eq!(result, Err(Ok(ComplianceError::IssuerNotApproved)));
+}
+
+#[test]
+fn test_pending_expired_revoked_and_removed_issuer_fail() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let user = Address::generate(&env);
+    let jurisdiction = symbol_short!("FR");
+
+    client.attest(
+        &issuer,
+        &user,
+        &String::from_str(&env, "cust-1"),
+        &CustomerStatus::Processing,
+        &ComplianceLevel::Basic,
+        &jurisdiction,
+        &0,
+    );
+    assert_eq!(
+        client.try_check(&user, &ComplianceLevel::Basic, &None),
+        Err(Ok(ComplianceError::NotAccepted))
+    );
+
+    client.attest(
+        &issuer,
+        &user,
+        &String::from_str(&env, "cust-1"),
+        &CustomerStatus::Accepted,
+        &ComplianceLevel::Basic,
+        &jurisdiction,
+        &1_000,
+    );
+    client.check(&user, &ComplianceLevel::Basic, &None);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert_eq!(
+        client.try_check(&user, &ComplianceLevel::Basic, &None),
+        Err(Ok(ComplianceError::AttestationExpired))
+    );
+    assert_eq!(client.get_level(&user), ComplianceLevel::None);
+
+    attest(
+        &env,
+        &client,
+        &issuer,
+        &user,
+        ComplianceLevel::Basic,
+        jurisdiction.clone(),
+    );
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&admin, &other_issuer);
+    assert_eq!(
+        client.try_revoke_attestation(&other_issuer, &user),
+        Err(Ok(ComplianceError::Unauthorized))
+    );
+    client.revoke_attestation(&issuer, &user);
+    assert_eq!(
+        client.try_check(&user, &ComplianceLevel::Basic, &None),
+        Err(Ok(ComplianceError::AttestationRevoked))
+    );
+
+    attest(
+        &env,
+        &client,
+        &issuer,
+        &user,
+        ComplianceLevel::Basic,
+        jurisdiction,
+    );
+    client.remove_issuer(&admin, &issuer);
+    assert_eq!(
+        client.try_check(&user, &ComplianceLevel::Basic, &None),
+        Err(Ok(ComplianceError::IssuerNotApproved))
+    );
+}
+
+#[test]
+fn test_other_issuer_cannot_replace_live_attestation() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&admin, &other_issuer);
+    let user = Address::generate(&env);
+
+    client.attest(
+        &issuer,
+        &user,
+        &String::from_str(&env, "cust-1"),
+        &CustomerStatus::Rejected,
+        &ComplianceLevel::Basic,
+        &symbol_short!("DE"),
+        &0,
+    );
+    let result = client.try_attest(
+        &other_issuer,
+        &user,
+        &String::from_str(&env, "cust-2"),
+        &CustomerStatus::Accepted,
+        &ComplianceLevel::Full,
+        &symbol_short!("DE"),
+        &0,
+    );
+    assert_eq!(result, Err(Ok(ComplianceError::AttestedByOtherIssuer)));
+    assert_eq!(client.get_attestation(&user).unwrap().issuer, issuer);
+
+    // The owning issuer may update its own record.
+    attest(
+        &env,
+        &client,
+        &issuer,
+        &user,
+        ComplianceLevel::Enhanced,
+        symbol_short!("DE"),
+    );
+    assert_eq!(client.get_level(&user), ComplianceLevel::Enhanced);
+
+    // Once revoked, another issuer can take over.
+    client.revoke_attestation(&admin, &user);
+    attest(
+        &env,
+        &client,
+        &other_issuer,
+        &user,
+        ComplianceLevel::Full,
+        symbol_short!("DE"),
+    );
+    assert_eq!(client.get_attestation(&user).unwrap().issuer, other_issuer);
+}
+
+#[test]
+fn test_jurisdiction_rules_block_accounts_and_pairs() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let us_user = Address::generate(&env);
+    let kp_user = Address::generate(&env);
+    attest(
+        &env,
+        &client,
+        &issuer,
+        &us_user,
+        ComplianceLevel::Basic,
+        symbol_short!("US"),
+    );
+    attest(
+        &env,
+        &client,
+        &issuer,
+        &kp_user,
+        ComplianceLevel::Full,
+        symbol_short!("KP"),
+    );
+
+    client.set_jurisdiction_rule(
+        &admin,
+        &symbol_short!("KP"),
+        &JurisdictionRule {
+            blocked: true,
+            min_level: ComplianceLevel::None,
+            blocked_pairs: Vec::new(&env),
+        },
+    );
+    assert_eq!(
+        client.try_check(&kp_user, &ComplianceLevel::None, &None),
+        Err(Ok(ComplianceError::JurisdictionBlocked))
+    );
+
+    client.set_jurisdiction_rule(
+        &admin,
+        &symbol_short!("US"),
+        &JurisdictionRule {
+            blocked: false,
+            min_level: ComplianceLevel::Basic,
+            blocked_pairs: vec![&env, 7u32],
+        },
+    );
+    client.check(&us_user, &ComplianceLevel::Basic, &Some(3));
+    client.check(&us_user, &ComplianceLevel::Basic, &None);
+    assert_eq!(
+        client.try_check(&us_user, &ComplianceLevel::Basic, &Some(7)),
+        Err(Ok(ComplianceError::AssetPairBlocked))
+    );
+
+    // The rule's minimum applies even when the caller asks for less.
+    client.set_jurisdiction_rule(
+        &admin,
+        &symbol_short!("US"),
+        &JurisdictionRule {
+            blocked: false,
+            min_level: ComplianceLevel::Enhanced,
+            blocked_pairs: Vec::new(&env),
+        },
+    );
+    assert_eq!(
+        client.try_check(&us_user, &ComplianceLevel::None, &None),
+        Err(Ok(ComplianceError::LevelTooLow))
+    );
+
+    client.remove_jurisdiction_rule(&admin, &symbol_short!("US"));
+    client.check(&us_user, &ComplianceLevel::Basic, &Some(7));
+}
+
+#[test]
+fn test_admin_only_configuration() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let stranger = Address::generate(&env);
+
+    assert_eq!(
+        client.try_add_issuer(&stranger, &stranger),
+        Err(Ok(ComplianceError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_remove_issuer(&stranger, &issuer),
+        Err(Ok(ComplianceError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_initialize(&stranger),
+        Err(Ok(ComplianceError::AlreadyInitialized))
+    );
+}
@@ -0,0 +1,134 @@
+//! Revenue-share distribution: buyback-and-burn plus staker rewards.
+//!
+//! Each call drains the revenue-share pool of one token and splits it:
+//!
+//! - `buyback_bps` of the pool is swapped into the protocol token through the
+//!   SDEX router and burned (used as-is when the pool already holds the
+//!   protocol token);
+//! - the remainder is transferred to the governance contract and registered
+//!   with `deposit_staking_rewards`, which spreads it pro-rata over stakers as
+//!   claimable balances.
+//!
+//! Every run is stored as a [`FeeDistributionRecord`] keyed by epoch so the
+//! burned and distributed amounts can be audited later.
+
+use soroban_sdk::{token, Address, Env, IntoVal, Symbol};
+
+use crate::events::{emit_fee_distribution_executed, EvtFeeDistributionExecuted};
+use crate::storage::{
+    clear_revenue_share_pool, get_fee_distribution_config, get_fee_distribution_epoch_count,
+    get_protocol_token, get_revenue_share_pool, set_fee_distribution_epoch_count,
+    set_fee_distribution_record, set_last_revenue_share_snapshot, FeeDistributionRecord,
+};
+use crate::swap::execute_router_swap;
+use crate::{ContractError, FeesBurned};
+
+/// Governance entrypoint that books the staker share.
+const GOVERNANCE_DEPOSIT_FN: &str = "deposit_staking_rewards";
+
+pub fn distribute_revenue_share(
+    env: &Env,
+    token: &Address,
+    min_buyback_out: i128,
+) -> Result<FeeDistributionRecord, ContractError> {
+    let config =
+        get_fee_distribution_config(env).ok_or(ContractError::DistributionNotConfigured)?;
+    let pool_amount = get_revenue_share_pool(env, token);
+    if pool_amount <= 0 {
+        return Err(ContractError::NothingToDistribute);
+    }
+
+    let buyback_amount = pool_amount
+        .checked_mul(config.buyback_bps as i128)
+        .and_then(|v| v.checked_div(10_000))
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    let staker_amount = pool_amount
+        .checked_sub(buyback_amount)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+
+    let epoch = get_fee_distribution_epoch_count(env)
+        .checked_add(1)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    clear_revenue_share_pool(env, token);
+
+    let protocol_tokens_burned = if buyback_amount > 0 {
+        buy_back_and_burn(
+            env,
+            &config.sdex_router,
+            token,
+            buyback_amount,
+            min_buyback_out,
+        )?
+    } else {
+        0
+    };
+
+    let governance_epoch_id = if staker_amount > 0 {
+        let this = env.current_contract_address();
+        token::Client::new(env, token).transfer(&this, &config.governance, &staker_amount);
+        let epoch_id = env
+            .try_invoke_contract::<u64, soroban_sdk::Error>(
+                &config.governance,
+                &Symbol::new(env, GOVERNANCE_DEPOSIT_FN),
+                (this, token.clone(), staker_amount, epoch).into_val(env),
+            )
+            .map_err(|_| ContractError::StakingRewardDepositFailed)?
+            .map_err(|_| ContractError::StakingRewardDepositFailed)?;
+        Some(epoch_id)
+    } else {
+        None
+    };
+
+    let record = FeeDistributionRecord {
+        epoch,
+        token: token.clone(),
+        pool_amount,
+        buyback_amount,
+        protocol_tokens_burned,
+        staker_amount,
+        governance_epoch_id,
+        executed_at: env.ledger().timestamp(),
+    };
+    set_fee_distribution_record(env, &record);
+    set_fee_distribution_epoch_count(env, epoch);
+    set_last_revenue_share_snapshot(env, env.ledger().sequence() as u64);
+
+    emit_fee_distribution_executed(
+        env,
+        EvtFeeDistributionExecuted {
+            epoch,
+            token: token.clone(),
+            pool_amount,
+            protocol_tokens_burned,
+            staker_amount,
+        },
+    );
+    Ok(record)
+}
+
+/// Convert `amount` of `token` into the protocol token and burn it. Returns
+/// the number of protocol tokens burned.
+fn buy_back_and_burn(
+    env: &Env,
+    router: &Address,
+    token: &Address,
+    amount: i128,
+    min_out: i128,
+) -> Result<i128, ContractError> {
+    let protocol_token =
+        get_protocol_token(env).ok_or(ContractError::ProtocolTokenNotConfigured)?;
+    let burned = if *token == protocol_token {
+        amount
+    } else {
+        execute_router_swap(env, router, token, &protocol_token, amount, min_out)?
+    };
+    if burned > 0 {
+        token::Client::new(env, &protocol_token).burn(&env.current_contract_address(), &burned);
+        FeesBurned {
+            amount: burned,
+            token: protocol_token,
+        }
+        .publish(env);
+    }
+    Ok(burned)
+}
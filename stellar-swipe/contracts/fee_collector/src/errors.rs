@@ -24,4 +24,25 @@ pub enum ContractError {
     FailedCollectionNotFound = 18,
     RetryLimitExceeded = 19,
     IterationLimitExceeded = 20,
+    SlippageExceeded = 21,
+    DistributionNotConfigured = 22,
+    ProtocolTokenNotConfigured = 23,
+    StakingRewardDepositFailed = 24,
+    NothingToDistribute = 25,
+    SettlementNotConfigured = 26,
+    ConversionSourceNotConfigured = 27,
+    InsuranceNotConfigured = 28,
+    DisputeNotResolved = 29,
+    DuplicateInsuranceClaim = 30,
+    InsuranceClaimNotFound = 31,
+    InsuranceClaimNotPending = 32,
+    InsuranceCapExceeded = 33,
+    InsufficientInsuranceFund = 34,
+    SelfReferral = 35,
+    ReferrerAlreadySet = 36,
+    ReferralAfterFirstTrade = 37,
+    ReferralCycle = 38,
+    ReferralLimitReached = 39,
+    InvalidPayoutSplit = 40,
+    NoInsurableLoss = 41,
 }
@@ -1,5 +1,8 @@
 use shared::errors::{ErrorCategory, RecoveryStrategy};
 use soroban_sdk::{contractevent, Address, Env, String, Symbol};
+use stellar_swipe_common::AssetPair;
+
+use crate::insurance::InsuranceClaimStatus;
 
 #[contractevent]
 pub struct WithdrawalQueued {
@@ -229,3 +232,185 @@ pub fn emit_revenue_share_distributed(
         (token.clone(), total_amount, snapshot_ledger),
     );
 }
+
+// ── Fee distribution pipeline ───────────────────────────────────────
+
+pub struct EvtFeeDistributionExecuted {
+    pub epoch: u64,
+    pub token: Address,
+    pub pool_amount: i128,
+    pub protocol_tokens_burned: i128,
+    pub staker_amount: i128,
+}
+
+/// Emitted when a revenue-share pool is split into buyback-and-burn and staker rewards.
+pub fn emit_fee_distribution_executed(env: &Env, evt: EvtFeeDistributionExecuted) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "fee_distribution_executed"),
+        ),
+        (
+            evt.epoch,
+            evt.token,
+            evt.pool_amount,
+            evt.protocol_tokens_burned,
+            evt.staker_amount,
+        ),
+    );
+}
+
+// ── Fee schedules ───────────────────────────────────────────────────
+
+/// Emitted when the global (`pair = None`) or a per-pair fee schedule changes.
+pub fn emit_fee_schedule_updated(env: &Env, pair: Option<AssetPair>) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "fee_schedule_updated"),
+        ),
+        pair,
+    );
+}
+
+// ── Settlement conversion ───────────────────────────────────────────
+
+pub struct EvtFeesConverted {
+    pub id: u64,
+    pub token: Address,
+    pub amount_in: i128,
+    pub settlement_token: Address,
+    pub amount_out: i128,
+}
+
+/// Emitted when a fee-token balance is swapped into the settlement asset.
+pub fn emit_fees_converted(env: &Env, evt: EvtFeesConverted) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "fees_converted"),
+        ),
+        (
+            evt.id,
+            evt.token,
+            evt.amount_in,
+            evt.settlement_token,
+            evt.amount_out,
+        ),
+    );
+}
+
+/// Emitted when a batch conversion skips a token with no conversion source.
+pub fn emit_conversion_skipped(env: &Env, token: &Address) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "conversion_skipped"),
+        ),
+        token.clone(),
+    );
+}
+
+// ── Copier insurance ────────────────────────────────────────────────
+
+pub struct EvtInsuranceClaim {
+    pub id: u64,
+    pub copier: Address,
+    pub provider: Address,
+    pub token: Address,
+    pub payout: i128,
+    pub status: InsuranceClaimStatus,
+}
+
+pub fn emit_insurance_funded(env: &Env, token: &Address, amount: i128, balance: i128) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "insurance_funded"),
+        ),
+        (token.clone(), amount, balance),
+    );
+}
+
+pub fn emit_insurance_claim_filed(env: &Env, evt: EvtInsuranceClaim) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "insurance_claim_filed"),
+        ),
+        (
+            evt.id,
+            evt.copier,
+            evt.provider,
+            evt.token,
+            evt.payout,
+            evt.status,
+        ),
+    );
+}
+
+pub fn emit_insurance_claim_settled(env: &Env, evt: EvtInsuranceClaim) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "insurance_claim_settled"),
+        ),
+        (evt.id, evt.copier, evt.token, evt.payout, evt.status),
+    );
+}
+
+/// Emitted when StakeVault routes a provider's slashed stake into the fund.
+pub fn emit_slash_received(env: &Env, provider: &Address, token: &Address, amount: i128) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "slash_received"),
+        ),
+        (provider.clone(), token.clone(), amount),
+    );
+}
+
+// ── Referrals ───────────────────────────────────────────────────────
+
+pub fn emit_referrer_registered(env: &Env, referee: &Address, referrer: &Address) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "referrer_registered"),
+        ),
+        (referee.clone(), referrer.clone()),
+    );
+}
+
+pub fn emit_referral_credited(
+    env: &Env,
+    referee: &Address,
+    referrer: &Address,
+    token: &Address,
+    amount: i128,
+    level: u32,
+) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "referral_credited"),
+        ),
+        (
+            referee.clone(),
+            referrer.clone(),
+            token.clone(),
+            amount,
+            level,
+        ),
+    );
+}
+
+pub fn emit_referral_claimed(env: &Env, referrer: &Address, token: &Address, amount: i128) {
+    env.events().publish(
+        (
+            Symbol::new(env, "fee_collector"),
+            Symbol::new(env, "referral_claimed"),
+        ),
+        (referrer.clone(), token.clone(), amount),
+    );
+}
@@ -0,0 +1,361 @@
+//! Copier insurance fund.
+//!
+//! The fund is fed by a share of every collected fee (`fee_share_bps` of the
+//! post-burn amount) and by StakeVault slashes (`deposit_slashed_stake`).
+//! Balances are tracked per token.
+//!
+//! A copier may file one claim per signal they copied through AutoTrade.
+//! The loss is not reported by the copier: it is AutoTrade's recorded copy
+//! trade for that signal marked at the oracle price (`get_copy_trade_loss`).
+//! The signal's provider must have been found at fault in a dispute opened
+//! after the copy trade executed, per SignalRegistry's
+//! `get_provider_dispute_history`. The payout is `coverage_bps` of the loss,
+//! in the token the copy trade was paid in, capped per claim and, across all
+//! claims, per epoch. Payouts above `committee_threshold` wait for the
+//! configured committee to approve them; smaller ones are paid on filing.
+
+use soroban_sdk::{contracttype, token, Address, Env, IntoVal, Symbol, Vec};
+
+use crate::events::{
+    emit_insurance_claim_filed, emit_insurance_claim_settled, emit_insurance_funded,
+    EvtInsuranceClaim,
+};
+use crate::storage::{ExtStorageKey, StorageKey};
+use crate::ContractError;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsuranceConfig {
+    /// Source of dispute outcomes.
+    pub signal_registry: Address,
+    /// Only contract allowed to deposit slashed stake.
+    pub stake_vault: Address,
+    /// Approves claims whose payout exceeds `committee_threshold`.
+    pub committee: Address,
+    /// Share (bps) of each post-burn fee routed to the fund.
+    pub fee_share_bps: u32,
+    /// Share (bps) of the reported loss that is covered.
+    pub coverage_bps: u32,
+    pub max_payout_per_claim: i128,
+    pub max_payout_per_epoch: i128,
+    pub epoch_seconds: u64,
+    pub committee_threshold: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InsuranceClaimStatus {
+    PendingApproval,
+    Paid,
+    Rejected,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsuranceClaim {
+    pub id: u64,
+    pub copier: Address,
+    pub provider: Address,
+    pub token: Address,
+    pub loss_amount: i128,
+    pub payout: i128,
+    /// `opened_at` of the dispute the claim is linked to.
+    pub dispute_opened_at: u64,
+    pub status: InsuranceClaimStatus,
+    pub filed_at: u64,
+    pub settled_at: u64,
+}
+
+/// Mirror of SignalRegistry's `DisputeOutcome`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeOutcome {
+    pub provider: Address,
+    pub opened_at: u64,
+    pub resolved_at: u64,
+    pub provider_at_fault: bool,
+}
+
+/// Mirror of AutoTrade's `CopyTradeLoss`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CopyTradeLoss {
+    pub signal_id: u64,
+    pub provider: Address,
+    pub executed_at: u64,
+    /// Token the copy trade was paid in; `loss` is in its units.
+    pub token: Address,
+    pub executed_amount: i128,
+    pub loss: i128,
+}
+
+pub fn get_config(env: &Env) -> Option<InsuranceConfig> {
+    env.storage().instance().get(&StorageKey::InsuranceConfig)
+}
+
+pub fn set_config(env: &Env, config: &InsuranceConfig) -> Result<(), ContractError> {
+    if config.fee_share_bps > 10_000
+        || config.coverage_bps > 10_000
+        || config.max_payout_per_claim <= 0
+        || config.max_payout_per_epoch <= 0
+        || config.epoch_seconds == 0
+        || config.committee_threshold < 0
+    {
+        return Err(ContractError::InvalidFeeConfiguration);
+    }
+    env.storage()
+        .instance()
+        .set(&StorageKey::InsuranceConfig, config);
+    Ok(())
+}
+
+pub fn get_trade_source(env: &Env) -> Option<Address> {
+    env.storage()
+        .instance()
+        .get(&ExtStorageKey::InsuranceTradeSource)
+}
+
+pub fn set_trade_source(env: &Env, auto_trade: &Address) {
+    env.storage()
+        .instance()
+        .set(&ExtStorageKey::InsuranceTradeSource, auto_trade);
+}
+
+pub fn fund_balance(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::InsuranceFund(token.clone()))
+        .unwrap_or(0)
+}
+
+fn set_fund_balance(env: &Env, token: &Address, balance: i128) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::InsuranceFund(token.clone()), &balance);
+}
+
+fn credit(env: &Env, token: &Address, amount: i128) -> Result<(), ContractError> {
+    let balance = fund_balance(env, token)
+        .checked_add(amount)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    set_fund_balance(env, token, balance);
+    emit_insurance_funded(env, token, amount, balance);
+    Ok(())
+}
+
+/// Move the fund's share out of `distributable` fee proceeds. Returns the
+/// amount credited to the fund (zero when insurance is not configured).
+pub fn allocate_fee_share(
+    env: &Env,
+    token: &Address,
+    distributable: i128,
+) -> Result<i128, ContractError> {
+    let Some(config) = get_config(env) else {
+        return Ok(0);
+    };
+    let share = distributable
+        .checked_mul(config.fee_share_bps as i128)
+        .and_then(|v| v.checked_div(10_000))
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    if share > 0 {
+        credit(env, token, share)?;
+    }
+    Ok(share)
+}
+
+/// Book slashed stake that StakeVault has already transferred here.
+pub fn deposit_slashed_stake(
+    env: &Env,
+    source: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let config = get_config(env).ok_or(ContractError::InsuranceNotConfigured)?;
+    if *source != config.stake_vault {
+        return Err(ContractError::Unauthorized);
+    }
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    credit(env, token, amount)
+}
+
+pub fn file_claim(
+    env: &Env,
+    copier: &Address,
+    signal_id: u64,
+) -> Result<InsuranceClaim, ContractError> {
+    let config = get_config(env).ok_or(ContractError::InsuranceNotConfigured)?;
+    let trade_source = get_trade_source(env).ok_or(ContractError::InsuranceNotConfigured)?;
+
+    let copy = env
+        .try_invoke_contract::<Option<CopyTradeLoss>, soroban_sdk::Error>(
+            &trade_source,
+            &Symbol::new(env, "get_copy_trade_loss"),
+            (copier.clone(), signal_id).into_val(env),
+        )
+        .ok()
+        .and_then(|r| r.ok())
+        .flatten()
+        .filter(|c| c.signal_id == signal_id && c.loss > 0)
+        .ok_or(ContractError::NoInsurableLoss)?;
+    let provider = copy.provider.clone();
+    if *copier == provider {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    // Earliest at-fault finding in a dispute opened after the copy trade.
+    let outcomes = env
+        .try_invoke_contract::<Vec<DisputeOutcome>, soroban_sdk::Error>(
+            &config.signal_registry,
+            &Symbol::new(env, "get_provider_dispute_history"),
+            (provider.clone(),).into_val(env),
+        )
+        .ok()
+        .and_then(|r| r.ok())
+        .unwrap_or_else(|| Vec::new(env));
+    let outcome = outcomes
+        .iter()
+        .find(|o| o.provider == provider && o.provider_at_fault && o.opened_at >= copy.executed_at)
+        .ok_or(ContractError::DisputeNotResolved)?;
+
+    let filed_key = ExtStorageKey::InsuranceSignalClaim(copier.clone(), signal_id);
+    if env.storage().persistent().has(&filed_key) {
+        return Err(ContractError::DuplicateInsuranceClaim);
+    }
+
+    let payout = copy
+        .loss
+        .checked_mul(config.coverage_bps as i128)
+        .and_then(|v| v.checked_div(10_000))
+        .ok_or(ContractError::ArithmeticOverflow)?
+        .min(config.max_payout_per_claim);
+    if payout <= 0 {
+        return Err(ContractError::FeeRoundedToZero);
+    }
+
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::InsuranceClaimCount)
+        .unwrap_or(0u64)
+        .checked_add(1)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    let mut claim = InsuranceClaim {
+        id,
+        copier: copier.clone(),
+        provider,
+        token: copy.token.clone(),
+        loss_amount: copy.loss,
+        payout,
+        dispute_opened_at: outcome.opened_at,
+        status: InsuranceClaimStatus::PendingApproval,
+        filed_at: env.ledger().timestamp(),
+        settled_at: 0,
+    };
+    if payout <= config.committee_threshold {
+        pay(env, &config, &mut claim)?;
+    }
+
+    env.storage().persistent().set(&filed_key, &id);
+    env.storage()
+        .instance()
+        .set(&StorageKey::InsuranceClaimCount, &id);
+    put_claim(env, &claim);
+    emit_insurance_claim_filed(env, evt(&claim));
+    Ok(claim)
+}
+
+/// Committee decision on a claim above the approval threshold.
+pub fn decide_claim(
+    env: &Env,
+    claim_id: u64,
+    approve: bool,
+) -> Result<InsuranceClaim, ContractError> {
+    let config = get_config(env).ok_or(ContractError::InsuranceNotConfigured)?;
+    config.committee.require_auth();
+
+    let mut claim = get_claim(env, claim_id).ok_or(ContractError::InsuranceClaimNotFound)?;
+    if claim.status != InsuranceClaimStatus::PendingApproval {
+        return Err(ContractError::InsuranceClaimNotPending);
+    }
+    if approve {
+        pay(env, &config, &mut claim)?;
+    } else {
+        claim.status = InsuranceClaimStatus::Rejected;
+        claim.settled_at = env.ledger().timestamp();
+        emit_insurance_claim_settled(env, evt(&claim));
+    }
+    put_claim(env, &claim);
+    Ok(claim)
+}
+
+fn pay(
+    env: &Env,
+    config: &InsuranceConfig,
+    claim: &mut InsuranceClaim,
+) -> Result<(), ContractError> {
+    let now = env.ledger().timestamp();
+    let epoch = now / config.epoch_seconds;
+    let epoch_key = StorageKey::InsuranceEpochPaid(epoch);
+    let paid: i128 = env.storage().persistent().get(&epoch_key).unwrap_or(0);
+    let paid = paid
+        .checked_add(claim.payout)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    if paid > config.max_payout_per_epoch {
+        return Err(ContractError::InsuranceCapExceeded);
+    }
+
+    let balance = fund_balance(env, &claim.token);
+    if balance < claim.payout {
+        return Err(ContractError::InsufficientInsuranceFund);
+    }
+    set_fund_balance(env, &claim.token, balance - claim.payout);
+    env.storage().persistent().set(&epoch_key, &paid);
+
+    token::Client::new(env, &claim.token).transfer(
+        &env.current_contract_address(),
+        &claim.copier,
+        &claim.payout,
+    );
+    claim.status = InsuranceClaimStatus::Paid;
+    claim.settled_at = now;
+    emit_insurance_claim_settled(env, evt(claim));
+    Ok(())
+}
+
+pub fn get_claim(env: &Env, claim_id: u64) -> Option<InsuranceClaim> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::InsuranceClaim(claim_id))
+}
+
+fn put_claim(env: &Env, claim: &InsuranceClaim) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::InsuranceClaim(claim.id), claim);
+}
+
+/// Total paid out in the epoch containing `timestamp`.
+pub fn epoch_paid(env: &Env, timestamp: u64) -> i128 {
+    let Some(config) = get_config(env) else {
+        return 0;
+    };
+    env.storage()
+        .persistent()
+        .get(&StorageKey::InsuranceEpochPaid(
+            timestamp / config.epoch_seconds,
+        ))
+        .unwrap_or(0)
+}
+
+fn evt(claim: &InsuranceClaim) -> EvtInsuranceClaim {
+    EvtInsuranceClaim {
+        id: claim.id,
+        copier: claim.copier.clone(),
+        provider: claim.provider.clone(),
+        token: claim.token.clone(),
+        payout: claim.payout,
+        status: claim.status,
+    }
+}
@@ -17,32 +17,49 @@ pub use events::{
     WithdrawalQueued,
 };
 
+mod distribution;
 mod rebates;
 
 mod reports;
 pub use reports::{EarningsLeaderboardEntry, EarningsReport, ReportPeriod};
 
 mod storage;
-pub use storage::BalanceMismatch;
+pub use storage::{
+    BalanceMismatch, ConversionLedger, ConversionRecord, ConversionSource, FeeDistributionConfig,
+    FeeDistributionRecord, SettlementConfig,
+};
+mod insurance;
+pub use insurance::{
+    CopyTradeLoss, DisputeOutcome, InsuranceClaim, InsuranceClaimStatus, InsuranceConfig,
+};
+mod payouts;
+pub use payouts::{ClaimedFees, PayoutConfig, PayoutSplit};
+mod referrals;
+pub use referrals::{
+    Referral, ReferralConfig, ReferralLeaderboardEntry, ReferralTokenStats, ReferrerStats,
+};
+mod settlement;
+mod swap;
 use storage::{
     get_admin, get_burn_rate, get_failed_fee_collection, get_fee_optimization_config, get_fee_rate,
-    get_last_error_report, get_monthly_trade_volume, get_network_condition_score,
-    get_oracle_contract, get_pending_fees, get_queued_withdrawal, get_treasury_balance, has_traded,
-    is_initialized, remove_failed_fee_collection, remove_monthly_trade_volume,
-    remove_queued_withdrawal, set_admin, set_burn_rate as set_burn_rate_storage,
-    set_failed_fee_collection, set_fee_optimization_config, set_fee_rate as set_fee_rate_storage,
-    set_has_traded, set_initialized, set_last_error_report, set_monthly_trade_volume,
+    get_last_error_report, get_network_condition_score, get_oracle_contract, get_pending_fees,
+    get_queued_withdrawal, get_treasury_balance, has_traded, is_initialized,
+    remove_failed_fee_collection, remove_queued_withdrawal, set_admin,
+    set_burn_rate as set_burn_rate_storage, set_failed_fee_collection, set_fee_optimization_config,
+    set_fee_rate as set_fee_rate_storage, set_has_traded, set_initialized, set_last_error_report,
     set_network_condition_score, set_oracle_contract as set_oracle_contract_storage,
     set_pending_fees, set_queued_withdrawal, set_treasury_balance, ErrorReport,
-    FailedFeeCollection, FeeOptimizationConfig, MonthlyTradeVolume, QueuedWithdrawal, StorageKey,
-    MAX_BURN_RATE_BPS, MAX_FEE_RATE_BPS, MIN_FEE_RATE_BPS,
+    FailedFeeCollection, FeeOptimizationConfig, QueuedWithdrawal, StorageKey, MAX_BURN_RATE_BPS,
+    MAX_FEE_RATE_BPS, MIN_FEE_RATE_BPS,
 };
 
 use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env, String, Vec};
 
 use shared::errors::{ErrorCategory, RecoveryStrategy};
-use stellar_swipe_common::Asset;
 use stellar_swipe_common::SECONDS_PER_DAY;
+use stellar_swipe_common::{
+    validate_fee_schedule, Asset, AssetPair, FeeQuote, FeeSchedule, FeeSide,
+};
 
 #[cfg(test)]
 mod tests;
@@ -146,6 +163,109 @@ impl FeeCollector {
         Ok(rebates::get_fee_rate_for_user(&env, &user))
     }
 
+    /// Schedule rate for `user` on `side` of `pair` (global schedule when
+    /// `pair` is `None`), before network and payment-token adjustments.
+    pub fn fee_rate_for_pair(
+        env: Env,
+        user: Address,
+        pair: Option<AssetPair>,
+        side: FeeSide,
+    ) -> Result<u32, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        Ok(rebates::get_fee_rate_for_pair(
+            &env,
+            &user,
+            pair.as_ref(),
+            side,
+        ))
+    }
+
+    /// Preview the schedule fee on `trade_amount`: tier, staking discount,
+    /// rate and fee, as SignalRegistry's `calculate_fee_preview` reports it.
+    pub fn preview_fee(
+        env: Env,
+        user: Address,
+        pair: Option<AssetPair>,
+        side: FeeSide,
+        trade_amount: i128,
+    ) -> Result<FeeQuote, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        if trade_amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        rebates::quote_fee_for_user(&env, &user, pair.as_ref(), side, trade_amount)
+    }
+
+    /// Fee schedule in force for `pair`, or the global schedule for `None`.
+    pub fn fee_schedule(env: Env, pair: Option<AssetPair>) -> FeeSchedule {
+        rebates::active_fee_schedule(&env, pair.as_ref())
+    }
+
+    /// Admin: hand fee-schedule management to `governor` (the governance
+    /// executor). Until set, the admin manages schedules.
+    pub fn set_fee_governor(env: Env, governor: Address) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        storage::set_fee_governor(&env, &governor);
+        Ok(())
+    }
+
+    pub fn fee_governor(env: Env) -> Option<Address> {
+        storage::get_fee_governor(&env)
+    }
+
+    /// Governor: replace the global fee schedule.
+    pub fn set_fee_schedule(env: Env, schedule: FeeSchedule) -> Result<(), ContractError> {
+        Self::require_fee_governor(&env)?;
+        validate_fee_schedule(&schedule, MAX_FEE_RATE_BPS).map_err(rebates::fee_schedule_error)?;
+        storage::set_fee_schedule(&env, &schedule);
+        events::emit_fee_schedule_updated(&env, None);
+        Ok(())
+    }
+
+    /// Governor: set or clear (`None`) the schedule override for `pair`.
+    pub fn set_pair_fee_schedule(
+        env: Env,
+        pair: AssetPair,
+        schedule: Option<FeeSchedule>,
+    ) -> Result<(), ContractError> {
+        Self::require_fee_governor(&env)?;
+        match schedule {
+            Some(schedule) => {
+                validate_fee_schedule(&schedule, MAX_FEE_RATE_BPS)
+                    .map_err(rebates::fee_schedule_error)?;
+                storage::set_pair_fee_schedule(&env, &pair, &schedule);
+            }
+            None => storage::remove_pair_fee_schedule(&env, &pair),
+        }
+        events::emit_fee_schedule_updated(&env, Some(pair));
+        Ok(())
+    }
+
+    /// Governor: contract queried for `staked_balance(holder)` when applying
+    /// staking discounts.
+    pub fn set_staking_balance_source(env: Env, source: Address) -> Result<(), ContractError> {
+        Self::require_fee_governor(&env)?;
+        storage::set_staking_balance_source(&env, &source);
+        Ok(())
+    }
+
+    fn require_fee_governor(env: &Env) -> Result<(), ContractError> {
+        if !is_initialized(env) {
+            return Err(ContractError::NotInitialized);
+        }
+        storage::get_fee_governor(env)
+            .unwrap_or_else(|| get_admin(env))
+            .require_auth();
+        Ok(())
+    }
+
     /// # Summary
     /// Returns the 30-day rolling trade volume in USD for a user.
     /// Used to determine rebate tier eligibility.
@@ -199,6 +319,8 @@ impl FeeCollector {
     /// # Returns
     /// A `Vec<BalanceMismatch>` containing one entry per token where
     /// `actual != expected`. Returns an empty vec when all balances reconcile.
+    /// Each entry carries the token's settlement-conversion totals so amounts
+    /// swapped out can be told apart from missing funds.
     ///
     /// # Errors
     /// - [`ContractError::NotInitialized`] if the contract has not been initialized.
@@ -214,14 +336,21 @@ impl FeeCollector {
         }
         let mut mismatches = Vec::new(&env);
         for token in tokens.iter() {
-            let expected = get_treasury_balance(&env, &token);
+            let expected = get_treasury_balance(&env, &token)
+                .saturating_add(storage::get_revenue_share_pool(&env, &token))
+                .saturating_add(insurance::fund_balance(&env, &token))
+                .saturating_add(referrals::outstanding(&env, &token))
+                .saturating_add(payouts::pending_total(&env, &token));
             let actual = token::Client::new(&env, &token).balance(&env.current_contract_address());
             if actual != expected {
+                let conversions = storage::get_conversion_ledger(&env, &token);
                 mismatches.push_back(BalanceMismatch {
                     token: token.clone(),
                     expected,
                     actual,
                     delta: actual.saturating_sub(expected),
+                    converted_out: conversions.total_converted_in,
+                    settlement_received: conversions.total_settlement_out,
                 });
             }
         }
@@ -515,6 +644,8 @@ impl FeeCollector {
             retry_record.token.clone(),
             retry_record.trade_amount,
             retry_record.trade_asset.clone(),
+            None,
+            FeeSide::Taker,
         );
 
         emit_retry_attempted(
@@ -567,6 +698,8 @@ impl FeeCollector {
             token.clone(),
             trade_amount,
             trade_asset.clone(),
+            None,
+            FeeSide::Taker,
         );
         if let Err(err) = &result {
             let report = ErrorReport {
@@ -589,6 +722,29 @@ impl FeeCollector {
         result
     }
 
+    /// Collect a fee using the maker or taker rate of the schedule configured
+    /// for `pair`. Volume is tracked against `pair.base`; otherwise behaves
+    /// like [`Self::collect_fee`].
+    pub fn collect_pair_fee(
+        env: Env,
+        trader: Address,
+        token: Address,
+        trade_amount: i128,
+        pair: AssetPair,
+        side: FeeSide,
+    ) -> Result<i128, ContractError> {
+        let trade_asset = pair.base.clone();
+        Self::collect_fee_with_recovery(
+            env,
+            trader,
+            token,
+            trade_amount,
+            trade_asset,
+            Some(pair),
+            side,
+        )
+    }
+
     /// Collect fees for multiple trades in one call. Config is loaded once and reused
     /// across all items via the transaction-scoped fee cache.
     pub fn batch_collect_fees(
@@ -610,6 +766,8 @@ impl FeeCollector {
                 item.token.clone(),
                 item.trade_amount,
                 item.trade_asset.clone(),
+                None,
+                FeeSide::Taker,
             )?;
             results.push_back(fee);
         }
@@ -622,6 +780,8 @@ impl FeeCollector {
         token: Address,
         trade_amount: i128,
         trade_asset: Asset,
+        pair: Option<AssetPair>,
+        side: FeeSide,
     ) -> Result<i128, ContractError> {
         if !is_initialized(&env) {
             return Err(ContractError::NotInitialized);
@@ -640,7 +800,7 @@ impl FeeCollector {
         }
 
         let fee_cache = fee_cache::load_tx_fee_config(&env);
-        let base_rate = rebates::get_fee_rate_for_user(&env, &trader);
+        let base_rate = rebates::get_fee_rate_for_pair(&env, &trader, pair.as_ref(), side);
         let fee_rate = fee_cache::effective_fee_rate_cached(&env, base_rate, &token, &fee_cache);
         let fee_amount =
             fee_amount_floor(trade_amount, fee_rate).ok_or(ContractError::ArithmeticOverflow)?;
@@ -675,7 +835,12 @@ impl FeeCollector {
             .checked_mul(revenue_share_rate as i128)
             .and_then(|v| v.checked_div(10_000))
             .unwrap_or(0);
-        let treasury_credit = distributable.saturating_sub(revenue_share_amount);
+        let insurance_amount = insurance::allocate_fee_share(&env, &token, distributable)?;
+        let referral_amount = referrals::allocate_fee_share(&env, &trader, &token, distributable)?;
+        let treasury_credit = distributable
+            .saturating_sub(revenue_share_amount)
+            .saturating_sub(insurance_amount)
+            .saturating_sub(referral_amount);
 
         if revenue_share_amount > 0 {
             storage::add_revenue_share_pool(&env, &token, revenue_share_amount);
@@ -726,7 +891,8 @@ impl FeeCollector {
         Self::effective_fee_rate_for_trade(&env, &trader, &token, &trade_asset)
     }
 
-    /// Claim all pending fee earnings for a provider and token.
+    /// Claim all pending fee earnings for a provider and token, paid according
+    /// to the provider's payout config.
     /// Returns the amount claimed (0 if no pending balance).
     pub fn claim_fees(env: Env, provider: Address, token: Address) -> Result<i128, ContractError> {
         if !is_initialized(&env) {
@@ -734,16 +900,7 @@ impl FeeCollector {
         }
         provider.require_auth();
 
-        let amount = get_pending_fees(&env, &provider, &token);
-
-        if amount > 0 {
-            token::Client::new(&env, &token).transfer(
-                &env.current_contract_address(),
-                &provider,
-                &amount,
-            );
-            set_pending_fees(&env, &provider, &token, 0);
-        }
+        let amount = payouts::pay(&env, &provider, &token)?;
 
         emit_fees_claimed(
             &env,
@@ -757,6 +914,90 @@ impl FeeCollector {
         Ok(amount)
     }
 
+    /// Claim pending fee earnings in every token the provider has been
+    /// credited in. Returns the non-zero amounts paid.
+    pub fn claim_all(env: Env, provider: Address) -> Result<Vec<ClaimedFees>, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        provider.require_auth();
+        payouts::claim_all(&env, &provider)
+    }
+
+    /// Admin: move a provider's fee share from the treasury into their pending
+    /// balance and record it in the daily earnings buckets. Paid out at once
+    /// if the provider's auto-claim threshold is reached; returns the amount
+    /// auto-claimed.
+    pub fn credit_provider_fees(
+        env: Env,
+        provider: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        payouts::credit(&env, &provider, &token, amount)
+    }
+
+    /// Admin: set the SignalRegistry that collaborative signal payout splits
+    /// are read from.
+    pub fn set_signal_registry(env: Env, signal_registry: Address) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        payouts::set_signal_registry(&env, &signal_registry);
+        Ok(())
+    }
+
+    pub fn signal_registry(env: Env) -> Option<Address> {
+        payouts::get_signal_registry(&env)
+    }
+
+    /// Admin: credit fees earned on `signal_id`. A collaborative signal's
+    /// fees are split across its co-authors' pending balances by the shares
+    /// SignalRegistry reports for that signal; otherwise `provider` gets it
+    /// all. Returns the total auto-claimed.
+    pub fn credit_signal_fees(
+        env: Env,
+        signal_id: u64,
+        provider: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        payouts::credit_signal(&env, signal_id, &provider, &token, amount)
+    }
+
+    /// Provider: set where claimed fees go. `splits` divides every payout
+    /// across fixed recipients; fees from collaborative signals are split
+    /// per signal by `credit_signal_fees` instead.
+    pub fn set_payout_config(
+        env: Env,
+        provider: Address,
+        config: PayoutConfig,
+    ) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        provider.require_auth();
+        payouts::set_config(&env, &provider, &config)
+    }
+
+    pub fn payout_config(env: Env, provider: Address) -> Option<PayoutConfig> {
+        payouts::get_config(&env, &provider)
+    }
+
+    /// Pending fee balance of `provider` in `token`.
+    pub fn pending_fees(env: Env, provider: Address, token: Address) -> i128 {
+        get_pending_fees(&env, &provider, &token)
+    }
+
     // ── Issue #366: Provider Earnings Report ─────────────────────────────────
 
     /// Record fee shares distributed to a provider for the current day.
@@ -880,6 +1121,263 @@ impl FeeCollector {
         Ok(())
     }
 
+    /// Admin: configure the revenue-share pipeline — the governance contract
+    /// receiving the staker share, the SDEX router used for buybacks and the
+    /// buyback share in basis points.
+    pub fn set_fee_distribution_config(
+        env: Env,
+        config: FeeDistributionConfig,
+    ) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        let admin = get_admin(&env);
+        admin.require_auth();
+        if config.buyback_bps > 10_000 {
+            return Err(ContractError::InvalidFeeConfiguration);
+        }
+        storage::set_fee_distribution_config(&env, &config);
+        Ok(())
+    }
+
+    pub fn fee_distribution_config(env: Env) -> Option<FeeDistributionConfig> {
+        storage::get_fee_distribution_config(&env)
+    }
+
+    /// Admin: distribute the revenue-share pool of `token`. The buyback share
+    /// is swapped into the protocol token (at least `min_buyback_out` units)
+    /// and burned; the rest is streamed to governance stakers. Returns the
+    /// epoch's audit record.
+    pub fn distribute_revenue_share(
+        env: Env,
+        token: Address,
+        min_buyback_out: i128,
+    ) -> Result<FeeDistributionRecord, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        let admin = get_admin(&env);
+        admin.require_auth();
+        distribution::distribute_revenue_share(&env, &token, min_buyback_out)
+    }
+
+    /// Audit record for a past distribution epoch.
+    pub fn fee_distribution_record(env: Env, epoch: u64) -> Option<FeeDistributionRecord> {
+        storage::get_fee_distribution_record(&env, epoch)
+    }
+
+    /// Number of revenue-share distributions executed so far.
+    pub fn fee_distribution_epoch_count(env: Env) -> u64 {
+        storage::get_fee_distribution_epoch_count(&env)
+    }
+
+    /// Admin: set the settlement asset fees are converted into, the router
+    /// used for conversions and the maximum slippage against the oracle.
+    pub fn set_settlement_config(env: Env, config: SettlementConfig) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        if config.max_slippage_bps > 10_000 {
+            return Err(ContractError::InvalidFeeConfiguration);
+        }
+        storage::set_settlement_config(&env, &config);
+        Ok(())
+    }
+
+    pub fn settlement_config(env: Env) -> Option<SettlementConfig> {
+        storage::get_settlement_config(&env)
+    }
+
+    /// Admin: register how `token` is priced and the oracle value its
+    /// treasury balance must reach before it is converted.
+    pub fn set_conversion_source(
+        env: Env,
+        token: Address,
+        source: ConversionSource,
+    ) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        if source.min_conversion_value < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        storage::set_conversion_source(&env, &token, &source);
+        Ok(())
+    }
+
+    /// Admin: batch-convert treasury balances of `tokens` that exceed their
+    /// threshold into the settlement asset. Returns the conversions executed.
+    pub fn convert_fees_to_settlement(
+        env: Env,
+        tokens: Vec<Address>,
+    ) -> Result<Vec<ConversionRecord>, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        settlement::convert_fees(&env, &tokens)
+    }
+
+    pub fn conversion_record(env: Env, id: u64) -> Option<ConversionRecord> {
+        storage::get_conversion_record(&env, id)
+    }
+
+    /// Cumulative amounts of `token` converted into the settlement asset.
+    pub fn conversion_ledger(env: Env, token: Address) -> ConversionLedger {
+        storage::get_conversion_ledger(&env, &token)
+    }
+
+    /// Admin: configure the copier insurance fund.
+    pub fn set_insurance_config(env: Env, config: InsuranceConfig) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        insurance::set_config(&env, &config)
+    }
+
+    pub fn insurance_config(env: Env) -> Option<InsuranceConfig> {
+        insurance::get_config(&env)
+    }
+
+    /// Insurance fund balance held in `token`.
+    pub fn insurance_fund_balance(env: Env, token: Address) -> i128 {
+        insurance::fund_balance(&env, &token)
+    }
+
+    /// StakeVault: book slashed stake already transferred to this contract
+    /// into the insurance fund.
+    pub fn deposit_slashed_stake(
+        env: Env,
+        stake_vault: Address,
+        token: Address,
+        amount: i128,
+        provider: Address,
+    ) -> Result<(), ContractError> {
+        stake_vault.require_auth();
+        insurance::deposit_slashed_stake(&env, &stake_vault, &token, amount)?;
+        events::emit_slash_received(&env, &provider, &token, amount);
+        Ok(())
+    }
+
+    /// Admin: set the AutoTrade contract whose recorded copy trades size
+    /// insurance claims.
+    pub fn set_insurance_trade_source(env: Env, auto_trade: Address) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        get_admin(&env).require_auth();
+        insurance::set_trade_source(&env, &auto_trade);
+        Ok(())
+    }
+
+    pub fn insurance_trade_source(env: Env) -> Option<Address> {
+        insurance::get_trade_source(&env)
+    }
+
+    /// Copier: claim compensation for the loss on their copy of `signal_id`,
+    /// whose provider was later found at fault in a dispute. The loss and
+    /// the payout token come from AutoTrade's record of the copy trade;
+    /// payouts above the committee threshold wait for the committee.
+    pub fn file_insurance_claim(
+        env: Env,
+        copier: Address,
+        signal_id: u64,
+    ) -> Result<InsuranceClaim, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        copier.require_auth();
+        insurance::file_claim(&env, &copier, signal_id)
+    }
+
+    /// Committee: approve (pay) or reject a pending claim.
+    pub fn decide_insurance_claim(
+        env: Env,
+        claim_id: u64,
+        approve: bool,
+    ) -> Result<InsuranceClaim, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        insurance::decide_claim(&env, claim_id, approve)
+    }
+
+    pub fn insurance_claim(env: Env, claim_id: u64) -> Option<InsuranceClaim> {
+        insurance::get_claim(&env, claim_id)
+    }
+
+    /// Insurance paid out during the epoch containing `timestamp`.
+    pub fn insurance_epoch_paid(env: Env, timestamp: u64) -> i128 {
+        insurance::epoch_paid(&env, timestamp)
+    }
+
+    /// Governor (admin if unset): set the referral shares and expiry.
+    pub fn set_referral_config(env: Env, config: ReferralConfig) -> Result<(), ContractError> {
+        Self::require_fee_governor(&env)?;
+        referrals::set_config(&env, &config)
+    }
+
+    pub fn referral_config(env: Env) -> Option<ReferralConfig> {
+        referrals::get_config(&env)
+    }
+
+    /// Trader: name the address that referred them. Can be set once, before
+    /// the trader's first trade, and may not point back at the trader.
+    pub fn register_referrer(
+        env: Env,
+        referee: Address,
+        referrer: Address,
+    ) -> Result<(), ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        referee.require_auth();
+        referrals::register(&env, &referee, &referrer)
+    }
+
+    pub fn referral_of(env: Env, referee: Address) -> Option<Referral> {
+        referrals::get_referral(&env, &referee)
+    }
+
+    /// Referral credits `referrer` can claim in `token`.
+    pub fn referral_claimable(env: Env, referrer: Address, token: Address) -> i128 {
+        referrals::claimable(&env, &referrer, &token)
+    }
+
+    pub fn referrer_stats(env: Env, referrer: Address) -> ReferrerStats {
+        referrals::get_stats(&env, &referrer)
+    }
+
+    /// Referral credits `referrer` has earned and claimed in `token`.
+    pub fn referrer_token_stats(env: Env, referrer: Address, token: Address) -> ReferralTokenStats {
+        referrals::get_token_stats(&env, &referrer, &token)
+    }
+
+    /// Referrer: withdraw accrued credits in `token`. Returns the amount paid.
+    pub fn claim_referral_rewards(
+        env: Env,
+        referrer: Address,
+        token: Address,
+    ) -> Result<i128, ContractError> {
+        if !is_initialized(&env) {
+            return Err(ContractError::NotInitialized);
+        }
+        referrer.require_auth();
+        Ok(referrals::claim(&env, &referrer, &token))
+    }
+
+    /// Top referrers by lifetime credits in `token` (default 10, max 50).
+    pub fn referral_leaderboard(
+        env: Env,
+        token: Address,
+        limit: u32,
+    ) -> Vec<ReferralLeaderboardEntry> {
+        referrals::leaderboard(&env, &token, limit)
+    }
+
     /// Returns an earnings report for the provider over the requested period.
     ///
     /// Categories:
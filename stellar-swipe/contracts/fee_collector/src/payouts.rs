@@ -0,0 +1,277 @@
+//! Provider fee payouts.
+//!
+//! Provider fee shares are moved out of the treasury into per-token pending
+//! balances by `credit_provider_fees`. Each provider may set a
+//! [`PayoutConfig`]: a payout address, a standing split of their own payouts
+//! (shares in bps) and an auto-claim threshold above which a credit is paid
+//! out immediately. `claim_all` sweeps every token the provider has been
+//! credited in.
+//!
+//! Fees earned on a collaborative signal are credited with
+//! `credit_signal_fees`, which reads that signal's author shares from
+//! SignalRegistry's `get_collaboration_payout_splits` and credits each
+//! co-author's own pending balance.
+
+use soroban_sdk::{contracttype, token, Address, Env, IntoVal, Symbol, Vec};
+
+use crate::events::{emit_fees_claimed, EvtFeesClaimed};
+use crate::storage::{
+    add_provider_daily_fee_shares, get_pending_fees, get_treasury_balance, set_pending_fees,
+    set_treasury_balance, ExtStorageKey, StorageKey,
+};
+use crate::ContractError;
+
+/// Maximum number of recipients in one payout split.
+pub const MAX_PAYOUT_SPLITS: u32 = 10;
+/// Maximum number of tokens tracked per provider for `claim_all`.
+pub const MAX_PROVIDER_FEE_TOKENS: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoutSplit {
+    pub recipient: Address,
+    pub share_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PayoutConfig {
+    /// Receives the payout (or the rounding remainder of a split) instead of
+    /// the provider.
+    pub payout_address: Option<Address>,
+    /// Empty, or shares summing to 10,000 bps.
+    pub splits: Vec<PayoutSplit>,
+    /// Pending balance at which a credit is paid out at once; zero disables.
+    pub auto_claim_threshold: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimedFees {
+    pub token: Address,
+    pub amount: i128,
+}
+
+pub fn get_config(env: &Env, provider: &Address) -> Option<PayoutConfig> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ProviderPayoutConfig(provider.clone()))
+}
+
+pub fn set_config(
+    env: &Env,
+    provider: &Address,
+    config: &PayoutConfig,
+) -> Result<(), ContractError> {
+    if config.auto_claim_threshold < 0 || config.splits.len() > MAX_PAYOUT_SPLITS {
+        return Err(ContractError::InvalidPayoutSplit);
+    }
+    if !config.splits.is_empty() {
+        let mut total = 0u32;
+        for split in config.splits.iter() {
+            if split.share_bps == 0 {
+                return Err(ContractError::InvalidPayoutSplit);
+            }
+            total = total.saturating_add(split.share_bps);
+        }
+        if total != 10_000 {
+            return Err(ContractError::InvalidPayoutSplit);
+        }
+    }
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ProviderPayoutConfig(provider.clone()), config);
+    Ok(())
+}
+
+/// Tokens `provider` has been credited in.
+pub fn fee_tokens(env: &Env, provider: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ProviderFeeTokens(provider.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Provider fees credited in `token` but not yet paid out.
+pub fn pending_total(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ProviderPendingTotal(token.clone()))
+        .unwrap_or(0)
+}
+
+fn set_pending_total(env: &Env, token: &Address, amount: i128) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ProviderPendingTotal(token.clone()), &amount);
+}
+
+/// Move `amount` of `token` from the treasury to `provider`'s pending
+/// balance. Returns the amount auto-claimed (zero if the balance stays
+/// pending).
+pub fn credit(
+    env: &Env,
+    provider: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<i128, ContractError> {
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    let treasury = get_treasury_balance(env, token);
+    if treasury < amount {
+        return Err(ContractError::InsufficientTreasuryBalance);
+    }
+
+    let mut tokens = fee_tokens(env, provider);
+    if !tokens.contains(token) {
+        if tokens.len() >= MAX_PROVIDER_FEE_TOKENS {
+            return Err(ContractError::IterationLimitExceeded);
+        }
+        tokens.push_back(token.clone());
+        env.storage()
+            .persistent()
+            .set(&StorageKey::ProviderFeeTokens(provider.clone()), &tokens);
+    }
+
+    let pending = get_pending_fees(env, provider, token)
+        .checked_add(amount)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    set_treasury_balance(env, token, treasury - amount);
+    set_pending_fees(env, provider, token, pending);
+    set_pending_total(env, token, pending_total(env, token).saturating_add(amount));
+    add_provider_daily_fee_shares(
+        env,
+        provider,
+        env.ledger().timestamp() / stellar_swipe_common::SECONDS_PER_DAY,
+        amount,
+    );
+
+    let threshold = get_config(env, provider)
+        .map(|c| c.auto_claim_threshold)
+        .unwrap_or(0);
+    if threshold > 0 && pending >= threshold {
+        return pay_and_emit(env, provider, token);
+    }
+    Ok(0)
+}
+
+pub fn get_signal_registry(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ExtStorageKey::SignalRegistry)
+}
+
+pub fn set_signal_registry(env: &Env, signal_registry: &Address) {
+    env.storage()
+        .instance()
+        .set(&ExtStorageKey::SignalRegistry, signal_registry);
+}
+
+/// Credit `amount` of `token` earned on `signal_id`. A fully approved
+/// collaborative signal credits each co-author their share, with any
+/// rounding remainder going to `provider`; any other signal credits
+/// `provider` alone. Returns the total auto-claimed.
+pub fn credit_signal(
+    env: &Env,
+    signal_id: u64,
+    provider: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<i128, ContractError> {
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    let registry = get_signal_registry(env).ok_or(ContractError::NotInitialized)?;
+    let splits = env
+        .try_invoke_contract::<Option<Vec<PayoutSplit>>, soroban_sdk::Error>(
+            &registry,
+            &Symbol::new(env, "get_collaboration_payout_splits"),
+            (signal_id,).into_val(env),
+        )
+        .ok()
+        .and_then(|r| r.ok())
+        .flatten()
+        .unwrap_or_else(|| Vec::new(env));
+
+    let mut auto_claimed = 0i128;
+    let mut remainder = amount;
+    for split in splits.iter() {
+        let share = split_share(amount, split.share_bps)?;
+        if share > 0 && remainder >= share {
+            auto_claimed =
+                auto_claimed.saturating_add(credit(env, &split.recipient, token, share)?);
+            remainder -= share;
+        }
+    }
+    if remainder > 0 {
+        auto_claimed = auto_claimed.saturating_add(credit(env, provider, token, remainder)?);
+    }
+    Ok(auto_claimed)
+}
+
+fn split_share(amount: i128, share_bps: u32) -> Result<i128, ContractError> {
+    amount
+        .checked_mul(share_bps as i128)
+        .and_then(|v| v.checked_div(10_000))
+        .ok_or(ContractError::ArithmeticOverflow)
+}
+
+/// Pay out `provider`'s pending balance in `token` according to their
+/// payout config. Returns the amount paid; the caller emits `fees_claimed`.
+pub fn pay(env: &Env, provider: &Address, token: &Address) -> Result<i128, ContractError> {
+    let amount = get_pending_fees(env, provider, token);
+    if amount <= 0 {
+        return Ok(0);
+    }
+    set_pending_fees(env, provider, token, 0);
+    set_pending_total(env, token, pending_total(env, token).saturating_sub(amount));
+
+    let config = get_config(env, provider);
+    let payout_address = config
+        .as_ref()
+        .and_then(|c| c.payout_address.clone())
+        .unwrap_or_else(|| provider.clone());
+    let client = token::Client::new(env, token);
+    let this = env.current_contract_address();
+
+    let mut remainder = amount;
+    if let Some(config) = config {
+        for split in config.splits.iter() {
+            let share = split_share(amount, split.share_bps)?;
+            if share > 0 {
+                client.transfer(&this, &split.recipient, &share);
+                remainder -= share;
+            }
+        }
+    }
+    if remainder > 0 {
+        client.transfer(&this, &payout_address, &remainder);
+    }
+    Ok(amount)
+}
+
+fn pay_and_emit(env: &Env, provider: &Address, token: &Address) -> Result<i128, ContractError> {
+    let amount = pay(env, provider, token)?;
+    if amount > 0 {
+        emit_fees_claimed(
+            env,
+            EvtFeesClaimed {
+                provider: provider.clone(),
+                token: token.clone(),
+                amount,
+            },
+        );
+    }
+    Ok(amount)
+}
+
+/// Pay out every token `provider` has a pending balance in.
+pub fn claim_all(env: &Env, provider: &Address) -> Result<Vec<ClaimedFees>, ContractError> {
+    let mut claimed = Vec::new(env);
+    for token in fee_tokens(env, provider).iter() {
+        let amount = pay_and_emit(env, provider, &token)?;
+        if amount > 0 {
+            claimed.push_back(ClaimedFees { token, amount });
+        }
+    }
+    Ok(claimed)
+}
@@ -1,47 +1,159 @@
-use soroban_sdk::{Address, Env, IntoVal, Symbol};
-use stellar_swipe_common::Asset;
+//! Volume-tiered fee schedules and rolling 30-day trade volume.
+//!
+//! Rates come from a governance-managed [`FeeSchedule`]: an optional
+//! per-pair schedule, else the global one, else the default schedule derived
+//! from the base fee rate (documented tiers at $10k and $50k). Tier lookups,
+//! staking discounts and fee math live in `stellar_swipe_common::fee_tiers`
+//! so SignalRegistry fee previews quote the same numbers.
+
+use soroban_sdk::{Address, Env, IntoVal, Symbol, Vec};
+use stellar_swipe_common::{
+    quote_fee, Asset, AssetPair, FeeQuote, FeeSchedule, FeeScheduleError, FeeSide, FeeTier,
+    FEE_VOLUME_WINDOW_DAYS, SECONDS_PER_DAY,
+};
 
 use crate::storage::{
-    get_fee_rate, get_monthly_trade_volume, get_oracle_contract, remove_monthly_trade_volume,
-    set_monthly_trade_volume, MonthlyTradeVolume, GOLD_DISCOUNT_BPS, GOLD_TIER_VOLUME_USD,
-    LEDGERS_PER_MONTH_APPROX, MIN_FEE_RATE_BPS, SILVER_DISCOUNT_BPS, SILVER_TIER_VOLUME_USD,
+    get_fee_rate, get_fee_schedule, get_monthly_trade_volume, get_oracle_contract,
+    get_pair_fee_schedule, get_rolling_trade_volume, get_staking_balance_source,
+    remove_monthly_trade_volume, set_rolling_trade_volume, DailyTradeVolume, RollingTradeVolume,
+    GOLD_DISCOUNT_BPS, GOLD_TIER_VOLUME_USD, LEDGERS_PER_MONTH_APPROX, MIN_FEE_RATE_BPS,
+    SILVER_DISCOUNT_BPS, SILVER_TIER_VOLUME_USD,
 };
 use crate::ContractError;
 
-fn current_month_bucket(env: &Env) -> u32 {
-    env.ledger().sequence() / LEDGERS_PER_MONTH_APPROX
+fn current_day(env: &Env) -> u64 {
+    env.ledger().timestamp() / SECONDS_PER_DAY
 }
 
-fn active_monthly_trade_volume(env: &Env, user: &Address) -> Option<MonthlyTradeVolume> {
-    let volume = get_monthly_trade_volume(env, user)?;
-    if volume.month_bucket == current_month_bucket(env) {
-        Some(volume)
-    } else {
-        remove_monthly_trade_volume(env, user);
-        None
+/// Daily buckets still inside the rolling window. A user with only a
+/// legacy calendar-month entry gets it as today's bucket while that month
+/// is current, so upgrading does not drop anyone's tier.
+fn active_days(env: &Env, user: &Address) -> Vec<DailyTradeVolume> {
+    let today = current_day(env);
+    let mut active = Vec::new(env);
+    if let Some(volume) = get_rolling_trade_volume(env, user) {
+        for bucket in volume.days.iter() {
+            if bucket.day + FEE_VOLUME_WINDOW_DAYS > today {
+                active.push_back(bucket);
+            }
+        }
+    } else if let Some(legacy) = get_monthly_trade_volume(env, user) {
+        if legacy.month_bucket == env.ledger().sequence() / LEDGERS_PER_MONTH_APPROX {
+            active.push_back(DailyTradeVolume {
+                day: today,
+                volume_usd: legacy.volume_usd,
+            });
+        }
     }
+    active
 }
 
+/// Rolling 30-day USD volume for `user`.
 pub fn get_active_volume_usd(env: &Env, user: &Address) -> i128 {
-    active_monthly_trade_volume(env, user)
-        .map(|volume| volume.volume_usd)
-        .unwrap_or(0)
+    let mut total = 0i128;
+    for bucket in active_days(env, user).iter() {
+        total = total.saturating_add(bucket.volume_usd);
+    }
+    total
 }
 
-pub fn get_fee_rate_for_user(env: &Env, user: &Address) -> u32 {
+/// Schedule used when governance has not configured one: a flat base rate
+/// with SILVER/GOLD volume discounts on both sides.
+pub fn default_fee_schedule(env: &Env) -> FeeSchedule {
     let base_rate = get_fee_rate(env);
-    let volume_usd = get_active_volume_usd(env, user);
+    let mut tiers = Vec::new(env);
+    for (min_volume_usd, discount) in [
+        (0, 0),
+        (SILVER_TIER_VOLUME_USD, SILVER_DISCOUNT_BPS),
+        (GOLD_TIER_VOLUME_USD, GOLD_DISCOUNT_BPS),
+    ] {
+        let rate = base_rate.saturating_sub(discount).max(MIN_FEE_RATE_BPS);
+        tiers.push_back(FeeTier {
+            min_volume_usd,
+            maker_rate_bps: rate,
+            taker_rate_bps: rate,
+        });
+    }
+    FeeSchedule {
+        tiers,
+        staking_discounts: Vec::new(env),
+    }
+}
+
+/// Schedule in force for `pair` (or the global schedule when `None`).
+pub fn active_fee_schedule(env: &Env, pair: Option<&AssetPair>) -> FeeSchedule {
+    pair.and_then(|p| get_pair_fee_schedule(env, p))
+        .or_else(|| get_fee_schedule(env))
+        .unwrap_or_else(|| default_fee_schedule(env))
+}
 
-    if volume_usd >= GOLD_TIER_VOLUME_USD {
-        base_rate
-            .saturating_sub(GOLD_DISCOUNT_BPS)
-            .max(MIN_FEE_RATE_BPS)
-    } else if volume_usd >= SILVER_TIER_VOLUME_USD {
-        base_rate
-            .saturating_sub(SILVER_DISCOUNT_BPS)
-            .max(MIN_FEE_RATE_BPS)
+/// Protocol tokens staked by `user` according to the configured staking
+/// contract (`staked_balance(holder) -> i128`). Zero when unset or unreachable.
+pub fn staked_balance(env: &Env, user: &Address) -> i128 {
+    let Some(source) = get_staking_balance_source(env) else {
+        return 0;
+    };
+    env.try_invoke_contract::<i128, soroban_sdk::Error>(
+        &source,
+        &Symbol::new(env, "staked_balance"),
+        (user.clone(),).into_val(env),
+    )
+    .ok()
+    .and_then(|r| r.ok())
+    .unwrap_or(0)
+}
+
+/// Quote the schedule fee for `user` before network and payment-token
+/// adjustments.
+pub fn quote_fee_for_user(
+    env: &Env,
+    user: &Address,
+    pair: Option<&AssetPair>,
+    side: FeeSide,
+    trade_amount: i128,
+) -> Result<FeeQuote, ContractError> {
+    let schedule = active_fee_schedule(env, pair);
+    let staked = if schedule.staking_discounts.is_empty() {
+        0
     } else {
-        base_rate
+        staked_balance(env, user)
+    };
+    quote_fee(
+        &schedule,
+        side,
+        get_active_volume_usd(env, user),
+        staked,
+        trade_amount,
+        MIN_FEE_RATE_BPS,
+    )
+    .map_err(fee_schedule_error)
+}
+
+/// Schedule rate for `user` on `side` of `pair`.
+pub fn get_fee_rate_for_pair(
+    env: &Env,
+    user: &Address,
+    pair: Option<&AssetPair>,
+    side: FeeSide,
+) -> u32 {
+    quote_fee_for_user(env, user, pair, side, 0)
+        .map(|quote| quote.rate_bps)
+        .unwrap_or_else(|_| get_fee_rate(env))
+}
+
+/// Global taker rate for `user`.
+pub fn get_fee_rate_for_user(env: &Env, user: &Address) -> u32 {
+    get_fee_rate_for_pair(env, user, None, FeeSide::Taker)
+}
+
+pub fn fee_schedule_error(err: FeeScheduleError) -> ContractError {
+    match err {
+        FeeScheduleError::RateTooHigh => ContractError::FeeRateTooHigh,
+        FeeScheduleError::TooManyEntries => ContractError::IterationLimitExceeded,
+        FeeScheduleError::ArithmeticOverflow => ContractError::ArithmeticOverflow,
+        FeeScheduleError::EmptySchedule | FeeScheduleError::NotAscending => {
+            ContractError::InvalidFeeConfiguration
+        }
     }
 }
 
@@ -61,24 +173,30 @@ pub fn record_trade_volume(
         .map_err(|_| ContractError::OracleConversionFailed)?
         .map_err(|_| ContractError::OracleConversionFailed)?;
 
-    let current_volume = active_monthly_trade_volume(env, user).unwrap_or(MonthlyTradeVolume {
-        month_bucket: current_month_bucket(env),
-        volume_usd: 0,
-    });
-
-    let updated_volume = current_volume
-        .volume_usd
-        .checked_add(usd_volume)
-        .ok_or(ContractError::ArithmeticOverflow)?;
-
-    set_monthly_trade_volume(
-        env,
-        user,
-        &MonthlyTradeVolume {
-            month_bucket: current_month_bucket(env),
-            volume_usd: updated_volume,
-        },
-    );
+    let today = current_day(env);
+    let mut days = active_days(env, user);
+    let last = days.len().checked_sub(1);
+    match last.map(|i| (i, days.get(i).unwrap())) {
+        Some((i, bucket)) if bucket.day == today => {
+            let volume_usd = bucket
+                .volume_usd
+                .checked_add(usd_volume)
+                .ok_or(ContractError::ArithmeticOverflow)?;
+            days.set(
+                i,
+                DailyTradeVolume {
+                    day: today,
+                    volume_usd,
+                },
+            );
+        }
+        _ => days.push_back(DailyTradeVolume {
+            day: today,
+            volume_usd: usd_volume,
+        }),
+    }
 
+    set_rolling_trade_volume(env, user, &RollingTradeVolume { days });
+    remove_monthly_trade_volume(env, user);
     Ok(())
 }
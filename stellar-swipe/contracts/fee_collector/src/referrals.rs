@@ -0,0 +1,356 @@
+//! Referral programme.
+//!
+//! A trader names their referrer once, before their first fee-paying trade.
+//! While the referral is active (`expiry_months` after registration, or
+//! forever when zero) every fee the trader pays credits `level1_bps` of the
+//! post-burn amount to the referrer and `level2_bps` to the referrer's own
+//! referrer. Credits accrue as claimable balances per token and are carved
+//! out of the treasury share.
+//!
+//! Earnings are tracked per token, and each token keeps its own leaderboard
+//! of the top `REFERRAL_LEADERBOARD_SIZE` referrers, re-sorted on every
+//! credit so queries never scan all referrers.
+
+use soroban_sdk::{contracttype, token, Address, Env, Vec};
+use stellar_swipe_common::SECONDS_PER_30_DAY_MONTH;
+
+use crate::events::{emit_referral_claimed, emit_referral_credited, emit_referrer_registered};
+use crate::storage::{ExtStorageKey, StorageKey};
+use crate::ContractError;
+
+/// Referees one referrer may bring in.
+pub const MAX_REFERRALS_PER_REFERRER: u32 = 100;
+/// Upper bound on `level1_bps + level2_bps`.
+pub const MAX_REFERRAL_SHARE_BPS: u32 = 5_000;
+/// Depth walked when checking a new referral for cycles.
+const MAX_REFERRAL_CHAIN_DEPTH: u32 = 10;
+/// Referrers kept on each token's leaderboard.
+pub const REFERRAL_LEADERBOARD_SIZE: u32 = 50;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferralConfig {
+    /// Share (bps) of each post-burn fee credited to the direct referrer.
+    pub level1_bps: u32,
+    /// Share (bps) credited to the referrer's referrer; zero disables level 2.
+    pub level2_bps: u32,
+    /// Months (30 days) a referral earns for; zero means it never expires.
+    pub expiry_months: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Referral {
+    pub referrer: Address,
+    pub registered_at: u64,
+}
+
+/// Referees a referrer has brought in. Earnings are kept per token in
+/// [`ReferralTokenStats`].
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReferrerStats {
+    pub referees: u32,
+}
+
+/// A referrer's credits in one token, across both levels.
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReferralTokenStats {
+    pub earned: i128,
+    pub claimed: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+struct RankedReferrer {
+    referrer: Address,
+    earned: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferralLeaderboardEntry {
+    pub rank: u32,
+    pub referrer: Address,
+    pub referees: u32,
+    /// Credits earned in the leaderboard's token.
+    pub total_earned: i128,
+}
+
+pub fn get_config(env: &Env) -> Option<ReferralConfig> {
+    env.storage().instance().get(&StorageKey::ReferralConfig)
+}
+
+pub fn set_config(env: &Env, config: &ReferralConfig) -> Result<(), ContractError> {
+    if config.level1_bps.saturating_add(config.level2_bps) > MAX_REFERRAL_SHARE_BPS {
+        return Err(ContractError::InvalidFeeConfiguration);
+    }
+    env.storage()
+        .instance()
+        .set(&StorageKey::ReferralConfig, config);
+    Ok(())
+}
+
+pub fn get_referral(env: &Env, referee: &Address) -> Option<Referral> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::Referral(referee.clone()))
+}
+
+pub fn get_stats(env: &Env, referrer: &Address) -> ReferrerStats {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ReferrerStats(referrer.clone()))
+        .unwrap_or_default()
+}
+
+fn set_stats(env: &Env, referrer: &Address, stats: &ReferrerStats) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ReferrerStats(referrer.clone()), stats);
+}
+
+pub fn claimable(env: &Env, referrer: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ReferralClaimable(
+            referrer.clone(),
+            token.clone(),
+        ))
+        .unwrap_or(0)
+}
+
+fn set_claimable(env: &Env, referrer: &Address, token: &Address, amount: i128) {
+    env.storage().persistent().set(
+        &StorageKey::ReferralClaimable(referrer.clone(), token.clone()),
+        &amount,
+    );
+}
+
+/// Unclaimed referral credits held in `token`, across all referrers.
+pub fn outstanding(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ReferralOutstanding(token.clone()))
+        .unwrap_or(0)
+}
+
+fn set_outstanding(env: &Env, token: &Address, amount: i128) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ReferralOutstanding(token.clone()), &amount);
+}
+
+pub fn get_token_stats(env: &Env, referrer: &Address, token: &Address) -> ReferralTokenStats {
+    env.storage()
+        .persistent()
+        .get(&ExtStorageKey::ReferralTokenStats(
+            referrer.clone(),
+            token.clone(),
+        ))
+        .unwrap_or_default()
+}
+
+fn set_token_stats(env: &Env, referrer: &Address, token: &Address, stats: &ReferralTokenStats) {
+    env.storage().persistent().set(
+        &ExtStorageKey::ReferralTokenStats(referrer.clone(), token.clone()),
+        stats,
+    );
+}
+
+fn get_ranking(env: &Env, token: &Address) -> Vec<RankedReferrer> {
+    env.storage()
+        .persistent()
+        .get(&ExtStorageKey::ReferralLeaderboard(token.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Move `referrer` to its place in `token`'s ranking for `earned`, dropping
+/// whoever falls off the end.
+fn update_ranking(env: &Env, token: &Address, referrer: &Address, earned: i128) {
+    let mut ranking = get_ranking(env, token);
+    if let Some(i) = ranking.iter().position(|r| r.referrer == *referrer) {
+        ranking.remove(i as u32);
+    }
+    let at = ranking
+        .iter()
+        .position(|r| r.earned < earned)
+        .map(|i| i as u32)
+        .unwrap_or(ranking.len());
+    if at >= REFERRAL_LEADERBOARD_SIZE {
+        return;
+    }
+    ranking.insert(
+        at,
+        RankedReferrer {
+            referrer: referrer.clone(),
+            earned,
+        },
+    );
+    while ranking.len() > REFERRAL_LEADERBOARD_SIZE {
+        ranking.pop_back();
+    }
+    env.storage()
+        .persistent()
+        .set(&ExtStorageKey::ReferralLeaderboard(token.clone()), &ranking);
+}
+
+/// Record `referrer` for `referee`. Referrals are permanent and must be set
+/// before the referee's first trade.
+pub fn register(env: &Env, referee: &Address, referrer: &Address) -> Result<(), ContractError> {
+    if referee == referrer {
+        return Err(ContractError::SelfReferral);
+    }
+    if get_referral(env, referee).is_some() {
+        return Err(ContractError::ReferrerAlreadySet);
+    }
+    if crate::storage::has_traded(env, referee) {
+        return Err(ContractError::ReferralAfterFirstTrade);
+    }
+
+    let mut cursor = referrer.clone();
+    for _ in 0..MAX_REFERRAL_CHAIN_DEPTH {
+        match get_referral(env, &cursor) {
+            Some(upstream) if upstream.referrer == *referee => {
+                return Err(ContractError::ReferralCycle)
+            }
+            Some(upstream) => cursor = upstream.referrer,
+            None => break,
+        }
+    }
+
+    let mut stats = get_stats(env, referrer);
+    if stats.referees >= MAX_REFERRALS_PER_REFERRER {
+        return Err(ContractError::ReferralLimitReached);
+    }
+    stats.referees += 1;
+    set_stats(env, referrer, &stats);
+
+    env.storage().persistent().set(
+        &StorageKey::Referral(referee.clone()),
+        &Referral {
+            referrer: referrer.clone(),
+            registered_at: env.ledger().timestamp(),
+        },
+    );
+    emit_referrer_registered(env, referee, referrer);
+    Ok(())
+}
+
+fn is_active(env: &Env, config: &ReferralConfig, referral: &Referral) -> bool {
+    if config.expiry_months == 0 {
+        return true;
+    }
+    let expires_at = referral
+        .registered_at
+        .saturating_add(config.expiry_months as u64 * SECONDS_PER_30_DAY_MONTH);
+    env.ledger().timestamp() < expires_at
+}
+
+fn credit(
+    env: &Env,
+    referee: &Address,
+    referrer: &Address,
+    token: &Address,
+    amount: i128,
+    level: u32,
+) -> Result<(), ContractError> {
+    let balance = claimable(env, referrer, token)
+        .checked_add(amount)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    set_claimable(env, referrer, token, balance);
+    set_outstanding(env, token, outstanding(env, token).saturating_add(amount));
+
+    let mut stats = get_token_stats(env, referrer, token);
+    stats.earned = stats.earned.saturating_add(amount);
+    set_token_stats(env, referrer, token, &stats);
+    update_ranking(env, token, referrer, stats.earned);
+
+    emit_referral_credited(env, referee, referrer, token, amount, level);
+    Ok(())
+}
+
+fn share(amount: i128, bps: u32) -> Result<i128, ContractError> {
+    amount
+        .checked_mul(bps as i128)
+        .and_then(|v| v.checked_div(10_000))
+        .ok_or(ContractError::ArithmeticOverflow)
+}
+
+/// Credit the referral shares of a fee paid by `trader`. Returns the total
+/// credited (zero when the programme is off or the trader has no active
+/// referral).
+pub fn allocate_fee_share(
+    env: &Env,
+    trader: &Address,
+    token: &Address,
+    distributable: i128,
+) -> Result<i128, ContractError> {
+    let Some(config) = get_config(env) else {
+        return Ok(0);
+    };
+    let Some(referral) = get_referral(env, trader) else {
+        return Ok(0);
+    };
+    if !is_active(env, &config, &referral) {
+        return Ok(0);
+    }
+
+    let mut total = 0i128;
+    let level1 = share(distributable, config.level1_bps)?;
+    if level1 > 0 {
+        credit(env, trader, &referral.referrer, token, level1, 1)?;
+        total += level1;
+    }
+
+    if config.level2_bps > 0 {
+        if let Some(upstream) = get_referral(env, &referral.referrer) {
+            let level2 = share(distributable, config.level2_bps)?;
+            if level2 > 0 {
+                credit(env, trader, &upstream.referrer, token, level2, 2)?;
+                total += level2;
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Pay out `referrer`'s claimable balance in `token`. Returns the amount paid.
+pub fn claim(env: &Env, referrer: &Address, token: &Address) -> i128 {
+    let amount = claimable(env, referrer, token);
+    if amount <= 0 {
+        return 0;
+    }
+    set_claimable(env, referrer, token, 0);
+    set_outstanding(env, token, outstanding(env, token).saturating_sub(amount));
+
+    let mut stats = get_token_stats(env, referrer, token);
+    stats.claimed = stats.claimed.saturating_add(amount);
+    set_token_stats(env, referrer, token, &stats);
+
+    token::Client::new(env, token).transfer(&env.current_contract_address(), referrer, &amount);
+    emit_referral_claimed(env, referrer, token, amount);
+    amount
+}
+
+/// Top referrers by credits earned in `token`.
+pub fn leaderboard(env: &Env, token: &Address, limit: u32) -> Vec<ReferralLeaderboardEntry> {
+    let limit = if limit == 0 {
+        10
+    } else {
+        limit.min(REFERRAL_LEADERBOARD_SIZE)
+    };
+    let ranking = get_ranking(env, token);
+    let mut result = Vec::new(env);
+    for i in 0..limit.min(ranking.len()) {
+        let ranked = ranking.get(i).unwrap();
+        result.push_back(ReferralLeaderboardEntry {
+            rank: i + 1,
+            referees: get_stats(env, &ranked.referrer).referees,
+            referrer: ranked.referrer,
+            total_earned: ranked.earned,
+        });
+    }
+    result
+}
@@ -0,0 +1,180 @@
+//! Conversion of collected fees into a single settlement asset.
+//!
+//! Fees accrue in whatever token each trade paid with. Once a token's
+//! treasury balance is worth at least its `min_conversion_value` (per the
+//! oracle), `convert_fees_to_settlement` swaps it into the settlement asset
+//! through the SDEX router. The oracle quotes in its base currency, so the
+//! value is divided by the settlement token's own base price before it is
+//! compared with the threshold; the swap must return at least that many
+//! settlement units less `max_slippage_bps`. Tokens without a conversion
+//! source are skipped and reported in a `conversion_skipped` event.
+//!
+//! Every swap is stored as a [`ConversionRecord`] and folded into the
+//! token's [`ConversionLedger`], which `audit_balances` reports next to the
+//! live balance so pre- and post-conversion amounts reconcile.
+
+use soroban_sdk::{Address, Env, IntoVal, Symbol, Vec};
+use stellar_swipe_common::Asset;
+
+use crate::events::{emit_conversion_skipped, emit_fees_converted, EvtFeesConverted};
+use crate::storage::{
+    get_conversion_count, get_conversion_ledger, get_conversion_source, get_oracle_contract,
+    get_queued_withdrawal, get_settlement_config, get_treasury_balance, set_conversion_count,
+    set_conversion_ledger, set_conversion_record, set_treasury_balance, ConversionRecord,
+    ConversionSource, SettlementConfig,
+};
+use crate::swap::execute_router_swap;
+use crate::{ContractError, MAX_AUDIT_TOKENS};
+
+/// One whole settlement token (7 decimals), the amount its base price is quoted for.
+pub const SETTLEMENT_PRICE_UNIT: i128 = 10_000_000;
+
+/// Convert each eligible fee token in `tokens` into the settlement asset.
+/// Tokens below their threshold are skipped; the returned records cover the
+/// conversions that ran.
+pub fn convert_fees(
+    env: &Env,
+    tokens: &Vec<Address>,
+) -> Result<Vec<ConversionRecord>, ContractError> {
+    if tokens.len() > MAX_AUDIT_TOKENS {
+        return Err(ContractError::IterationLimitExceeded);
+    }
+    let config = get_settlement_config(env).ok_or(ContractError::SettlementNotConfigured)?;
+    let oracle = get_oracle_contract(env).ok_or(ContractError::OracleNotConfigured)?;
+    let settlement_source = get_conversion_source(env, &config.settlement_token)
+        .ok_or(ContractError::ConversionSourceNotConfigured)?;
+    let settlement_unit_value = convert_to_base(
+        env,
+        &oracle,
+        SETTLEMENT_PRICE_UNIT,
+        &settlement_source.asset,
+    )?;
+    if settlement_unit_value <= 0 {
+        return Err(ContractError::OracleConversionFailed);
+    }
+
+    let mut records = Vec::new(env);
+    for token in tokens.iter() {
+        if token == config.settlement_token {
+            continue;
+        }
+        let Some(source) = get_conversion_source(env, &token) else {
+            emit_conversion_skipped(env, &token);
+            continue;
+        };
+        if let Some(record) = convert_token(
+            env,
+            &config,
+            &oracle,
+            settlement_unit_value,
+            &token,
+            &source,
+        )? {
+            records.push_back(record);
+        }
+    }
+    Ok(records)
+}
+
+fn convert_to_base(
+    env: &Env,
+    oracle: &Address,
+    amount: i128,
+    asset: &Asset,
+) -> Result<i128, ContractError> {
+    env.try_invoke_contract::<i128, soroban_sdk::Error>(
+        oracle,
+        &Symbol::new(env, "convert_to_base"),
+        (&amount, asset).into_val(env),
+    )
+    .map_err(|_| ContractError::OracleConversionFailed)?
+    .map_err(|_| ContractError::OracleConversionFailed)
+}
+
+fn convert_token(
+    env: &Env,
+    config: &SettlementConfig,
+    oracle: &Address,
+    settlement_unit_value: i128,
+    token: &Address,
+    source: &ConversionSource,
+) -> Result<Option<ConversionRecord>, ContractError> {
+    // Leave a queued withdrawal's amount in the original token.
+    let reserved = get_queued_withdrawal(env)
+        .filter(|w| w.token == *token)
+        .map(|w| w.amount)
+        .unwrap_or(0);
+    let balance = get_treasury_balance(env, token);
+    let amount_in = balance.saturating_sub(reserved);
+    if amount_in <= 0 {
+        return Ok(None);
+    }
+
+    let base_value = convert_to_base(env, oracle, amount_in, &source.asset)?;
+    let oracle_value = base_value
+        .checked_mul(SETTLEMENT_PRICE_UNIT)
+        .and_then(|v| v.checked_div(settlement_unit_value))
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    if oracle_value < source.min_conversion_value || oracle_value <= 0 {
+        return Ok(None);
+    }
+
+    let min_out = oracle_value
+        .checked_mul(10_000i128.saturating_sub(config.max_slippage_bps as i128))
+        .and_then(|v| v.checked_div(10_000))
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    let amount_out = execute_router_swap(
+        env,
+        &config.sdex_router,
+        token,
+        &config.settlement_token,
+        amount_in,
+        min_out,
+    )?;
+
+    set_treasury_balance(env, token, balance - amount_in);
+    let settlement_balance = get_treasury_balance(env, &config.settlement_token)
+        .checked_add(amount_out)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    set_treasury_balance(env, &config.settlement_token, settlement_balance);
+
+    let id = get_conversion_count(env)
+        .checked_add(1)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    let record = ConversionRecord {
+        id,
+        token: token.clone(),
+        amount_in,
+        settlement_token: config.settlement_token.clone(),
+        amount_out,
+        oracle_value,
+        converted_at: env.ledger().timestamp(),
+    };
+    set_conversion_record(env, &record);
+    set_conversion_count(env, id);
+
+    let mut ledger = get_conversion_ledger(env, token);
+    ledger.total_converted_in = ledger
+        .total_converted_in
+        .checked_add(amount_in)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    ledger.total_settlement_out = ledger
+        .total_settlement_out
+        .checked_add(amount_out)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    ledger.conversions = ledger.conversions.saturating_add(1);
+    ledger.last_conversion_id = id;
+    set_conversion_ledger(env, token, &ledger);
+
+    emit_fees_converted(
+        env,
+        EvtFeesConverted {
+            id,
+            token: token.clone(),
+            amount_in,
+            settlement_token: config.settlement_token.clone(),
+            amount_out,
+        },
+    );
+    Ok(Some(record))
+}
@@ -1,10 +1,10 @@
 use shared::errors::{ErrorCategory, RecoveryStrategy};
 use shared::initializable;
 use soroban_sdk::{contracttype, Address, Env, String, Vec};
-use stellar_swipe_common::Asset;
+use stellar_swipe_common::{Asset, AssetPair, FeeSchedule};
 
-pub const MAX_FEE_RATE_BPS: u32 = 100; // 1%
-pub const MIN_FEE_RATE_BPS: u32 = 1; // 0.01%
+pub const MAX_FEE_RATE_BPS: u32 = stellar_swipe_common::MAX_TIER_RATE_BPS; // 1%
+pub const MIN_FEE_RATE_BPS: u32 = stellar_swipe_common::MIN_TIER_RATE_BPS; // 0.01%
 pub const DEFAULT_FEE_RATE_BPS: u32 = 30; // 0.3%
 pub const DEFAULT_BURN_RATE_BPS: u32 = 1_000; // 10%
 pub const MAX_BURN_RATE_BPS: u32 = 10_000; // 100%
@@ -13,6 +13,7 @@ pub const DEFAULT_FEE_OPTIMIZATION_MAX_RATE_BPS: u32 = 100;
 pub const DEFAULT_CONGESTION_SENSITIVITY_BPS: u32 = 50;
 pub const DEFAULT_MAX_RETRY_ATTEMPTS: u32 = 3;
 pub const LEDGERS_PER_MONTH_APPROX: u32 = 518_400; // ~30 days at ~5 seconds per ledger
+                                                   // Default fee schedule used until governance configures one.
 pub const SILVER_TIER_VOLUME_USD: i128 = 10_000 * 10_000_000; // $10k, 7 decimals
 pub const GOLD_TIER_VOLUME_USD: i128 = 50_000 * 10_000_000; // $50k, 7 decimals
 pub const SILVER_DISCOUNT_BPS: u32 = 5;
@@ -28,7 +29,7 @@ pub enum StorageKey {
     FeeRate,                               // instance, current fee rate in bps
     BurnRate,                              // instance, burn rate in bps
     ProviderPendingFees(Address, Address), // persistent, per (provider, token)
-    MonthlyTradeVolume(Address),           // persistent, per user
+    MonthlyTradeVolume(Address),           // persistent, per user, legacy calendar-month volume
     /// Accumulated fee shares per provider per day (day = unix_timestamp / SECONDS_PER_DAY).
     ProviderDailyFeeShares(Address, u64),
     /// Day number of the provider's first recorded earnings (for ALL_TIME period_start).
@@ -56,6 +57,106 @@ pub enum StorageKey {
     LastErrorReport,
     /// Persisted failed fee collection operation for retry.
     FailedFeeCollection(String),
+    /// Buyback / staker split applied by `distribute_revenue_share`.
+    FeeDistributionConfig,
+    /// Number of revenue-share distributions executed so far.
+    FeeDistributionEpochCount,
+    /// Per-epoch audit record of a revenue-share distribution.
+    FeeDistributionRecord(u64),
+    /// Address allowed to manage fee schedules (the governance executor).
+    FeeGovernor,
+    /// Global volume-tiered fee schedule.
+    FeeSchedule,
+    /// Fee schedule override for one asset pair.
+    PairFeeSchedule(AssetPair),
+    /// Contract queried for `staked_balance` when applying staking discounts.
+    StakingBalanceSource,
+    /// Settlement asset and swap parameters for fee conversion.
+    SettlementConfig,
+    /// Oracle asset and conversion threshold for a fee token.
+    ConversionSource(Address),
+    /// Number of fee conversions executed so far.
+    ConversionCount,
+    /// Per-conversion audit record.
+    ConversionRecord(u64),
+    /// Cumulative conversion totals for a fee token.
+    ConversionLedger(Address),
+    /// Copier insurance parameters.
+    InsuranceConfig,
+    /// Insurance fund balance per token.
+    InsuranceFund(Address),
+    /// Number of insurance claims filed so far.
+    InsuranceClaimCount,
+    /// Insurance claim record by id.
+    InsuranceClaim(u64),
+    /// Insurance payouts made in an epoch (`timestamp / epoch_seconds`).
+    InsuranceEpochPaid(u64),
+    /// Referral share parameters.
+    ReferralConfig,
+    /// Referrer of a trader, set once.
+    Referral(Address),
+    /// Referee count of a referrer.
+    ReferrerStats(Address),
+    /// Unclaimed referral credits per (referrer, token).
+    ReferralClaimable(Address, Address),
+    /// Unclaimed referral credits per token, across all referrers.
+    ReferralOutstanding(Address),
+    /// Legacy: every address named as a referrer, from before leaderboards
+    /// were kept per token. No longer written.
+    ReferrerIndex,
+    /// Payout address, collaborator split and auto-claim threshold of a provider.
+    ProviderPayoutConfig(Address),
+    /// Tokens a provider has been credited fees in.
+    ProviderFeeTokens(Address),
+    /// Pending provider fees per token, across all providers.
+    ProviderPendingTotal(Address),
+}
+
+/// Keys added after `StorageKey` reached the 50-variant contract-spec limit.
+#[contracttype]
+pub enum ExtStorageKey {
+    /// persistent, per user, rolling 30-day daily buckets
+    RollingTradeVolume(Address),
+    /// AutoTrade contract whose recorded copy trades size insurance claims.
+    InsuranceTradeSource,
+    /// Claim id filed by (copier, signal id); one claim per copy trade.
+    InsuranceSignalClaim(Address, u64),
+    /// Referral credits earned and claimed by (referrer, token).
+    ReferralTokenStats(Address, Address),
+    /// Top referrers by earnings in a token, kept sorted on every credit.
+    ReferralLeaderboard(Address),
+    /// SignalRegistry that collaborative signal payout splits are read from.
+    SignalRegistry,
+}
+
+/// Where the revenue-share pool goes when it is distributed.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeDistributionConfig {
+    /// Governance contract holding staker rewards.
+    pub governance: Address,
+    /// SDEX router used to buy back the protocol token.
+    pub sdex_router: Address,
+    /// Share of each pool (bps) used for buyback-and-burn; the rest goes to stakers.
+    pub buyback_bps: u32,
+}
+
+/// Audit record of one `distribute_revenue_share` call.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeDistributionRecord {
+    pub epoch: u64,
+    pub token: Address,
+    pub pool_amount: i128,
+    /// Pool amount (in `token`) spent on the buyback.
+    pub buyback_amount: i128,
+    /// Protocol tokens bought back and burned.
+    pub protocol_tokens_burned: i128,
+    /// Pool amount (in `token`) forwarded to governance stakers.
+    pub staker_amount: i128,
+    /// Governance reward epoch created for the staker share, if any.
+    pub governance_epoch_id: Option<u64>,
+    pub executed_at: u64,
 }
 
 #[contracttype]
@@ -97,6 +198,7 @@ pub struct QueuedWithdrawal {
     pub queued_at: u64,
 }
 
+/// Volume recorded before the rolling window, keyed by ledger-sequence month.
 #[contracttype]
 #[derive(Clone)]
 pub struct MonthlyTradeVolume {
@@ -104,6 +206,68 @@ pub struct MonthlyTradeVolume {
     pub volume_usd: i128,
 }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct RollingTradeVolume {
+    /// One entry per trading day, oldest first; days outside the window are pruned.
+    pub days: Vec<DailyTradeVolume>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct DailyTradeVolume {
+    /// Unix day (`timestamp / SECONDS_PER_DAY`).
+    pub day: u64,
+    pub volume_usd: i128,
+}
+
+/// Asset all fee tokens are converted into.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettlementConfig {
+    pub settlement_token: Address,
+    pub sdex_router: Address,
+    /// Maximum shortfall (bps) of the swap output against the oracle value.
+    pub max_slippage_bps: u32,
+}
+
+/// How a fee token is priced and when it is worth converting.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionSource {
+    /// Oracle asset for `convert_to_base`. The settlement token needs one too,
+    /// so oracle values can be expressed in settlement units.
+    pub asset: Asset,
+    /// Minimum oracle value (settlement units) of a balance before it is converted.
+    pub min_conversion_value: i128,
+}
+
+/// One fee-token balance swapped into the settlement asset.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionRecord {
+    pub id: u64,
+    pub token: Address,
+    pub amount_in: i128,
+    pub settlement_token: Address,
+    pub amount_out: i128,
+    /// Oracle value of `amount_in` in settlement units, which the swap output
+    /// was checked against.
+    pub oracle_value: i128,
+    pub converted_at: u64,
+}
+
+/// Running totals of everything converted out of a fee token, so the
+/// treasury balance can be reconciled against the fees originally collected.
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConversionLedger {
+    pub total_converted_in: i128,
+    pub total_settlement_out: i128,
+    pub conversions: u32,
+    pub last_conversion_id: u64,
+}
+
 /// Describes a discrepancy between the contract's stored treasury balance
 /// and the actual on-chain token balance for a given token.
 #[contracttype]
@@ -111,12 +275,17 @@ pub struct MonthlyTradeVolume {
 pub struct BalanceMismatch {
     /// The token whose balances were compared.
     pub token: Address,
-    /// Balance recorded in contract storage (`TreasuryBalance`).
+    /// Balance recorded in contract storage: treasury, revenue-share pool
+    /// and insurance fund.
     pub expected: i128,
     /// Actual token balance held by this contract on-chain.
     pub actual: i128,
     /// Difference: `actual - expected`. Positive means surplus, negative means deficit.
     pub delta: i128,
+    /// Total of this token swapped into the settlement asset so far.
+    pub converted_out: i128,
+    /// Settlement-asset units received for `converted_out`.
+    pub settlement_received: i128,
 }
 
 // --- Admin ---
@@ -290,7 +459,7 @@ pub fn set_pending_fees(env: &Env, provider: &Address, token: &Address, amount:
     );
 }
 
-// --- Monthly Trade Volume ---
+// --- Trade Volume ---
 
 pub fn get_monthly_trade_volume(env: &Env, user: &Address) -> Option<MonthlyTradeVolume> {
     env.storage()
@@ -298,16 +467,22 @@ pub fn get_monthly_trade_volume(env: &Env, user: &Address) -> Option<MonthlyTrad
         .get(&StorageKey::MonthlyTradeVolume(user.clone()))
 }
 
-pub fn set_monthly_trade_volume(env: &Env, user: &Address, volume: &MonthlyTradeVolume) {
+pub fn remove_monthly_trade_volume(env: &Env, user: &Address) {
     env.storage()
         .persistent()
-        .set(&StorageKey::MonthlyTradeVolume(user.clone()), volume);
+        .remove(&StorageKey::MonthlyTradeVolume(user.clone()));
 }
 
-pub fn remove_monthly_trade_volume(env: &Env, user: &Address) {
+pub fn get_rolling_trade_volume(env: &Env, user: &Address) -> Option<RollingTradeVolume> {
     env.storage()
         .persistent()
-        .remove(&StorageKey::MonthlyTradeVolume(user.clone()));
+        .get(&ExtStorageKey::RollingTradeVolume(user.clone()))
+}
+
+pub fn set_rolling_trade_volume(env: &Env, user: &Address, volume: &RollingTradeVolume) {
+    env.storage()
+        .persistent()
+        .set(&ExtStorageKey::RollingTradeVolume(user.clone()), volume);
 }
 
 // --- Provider Daily Fee Shares (Issue #366) ---
@@ -455,3 +630,156 @@ pub fn clear_revenue_share_pool(env: &Env, token: &Address) {
         .persistent()
         .remove(&StorageKey::RevenueSharePool(token.clone()));
 }
+
+// ── Fee distribution pipeline ───────────────────────────────────────
+
+pub fn get_fee_distribution_config(env: &Env) -> Option<FeeDistributionConfig> {
+    env.storage()
+        .instance()
+        .get(&StorageKey::FeeDistributionConfig)
+}
+
+pub fn set_fee_distribution_config(env: &Env, config: &FeeDistributionConfig) {
+    env.storage()
+        .instance()
+        .set(&StorageKey::FeeDistributionConfig, config);
+}
+
+pub fn get_fee_distribution_epoch_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&StorageKey::FeeDistributionEpochCount)
+        .unwrap_or(0)
+}
+
+pub fn set_fee_distribution_epoch_count(env: &Env, count: u64) {
+    env.storage()
+        .instance()
+        .set(&StorageKey::FeeDistributionEpochCount, &count);
+}
+
+pub fn get_fee_distribution_record(env: &Env, epoch: u64) -> Option<FeeDistributionRecord> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::FeeDistributionRecord(epoch))
+}
+
+pub fn set_fee_distribution_record(env: &Env, record: &FeeDistributionRecord) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::FeeDistributionRecord(record.epoch), record);
+}
+
+// ── Fee schedules ───────────────────────────────────────────────────
+
+pub fn get_fee_governor(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&StorageKey::FeeGovernor)
+}
+
+pub fn set_fee_governor(env: &Env, governor: &Address) {
+    env.storage()
+        .instance()
+        .set(&StorageKey::FeeGovernor, governor);
+}
+
+pub fn get_fee_schedule(env: &Env) -> Option<FeeSchedule> {
+    env.storage().instance().get(&StorageKey::FeeSchedule)
+}
+
+pub fn set_fee_schedule(env: &Env, schedule: &FeeSchedule) {
+    env.storage()
+        .instance()
+        .set(&StorageKey::FeeSchedule, schedule);
+}
+
+pub fn get_pair_fee_schedule(env: &Env, pair: &AssetPair) -> Option<FeeSchedule> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::PairFeeSchedule(pair.clone()))
+}
+
+pub fn set_pair_fee_schedule(env: &Env, pair: &AssetPair, schedule: &FeeSchedule) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::PairFeeSchedule(pair.clone()), schedule);
+}
+
+pub fn remove_pair_fee_schedule(env: &Env, pair: &AssetPair) {
+    env.storage()
+        .persistent()
+        .remove(&StorageKey::PairFeeSchedule(pair.clone()));
+}
+
+pub fn get_staking_balance_source(env: &Env) -> Option<Address> {
+    env.storage()
+        .instance()
+        .get(&StorageKey::StakingBalanceSource)
+}
+
+pub fn set_staking_balance_source(env: &Env, source: &Address) {
+    env.storage()
+        .instance()
+        .set(&StorageKey::StakingBalanceSource, source);
+}
+
+// ── Settlement conversion ───────────────────────────────────────────
+
+pub fn get_settlement_config(env: &Env) -> Option<SettlementConfig> {
+    env.storage().instance().get(&StorageKey::SettlementConfig)
+}
+
+pub fn set_settlement_config(env: &Env, config: &SettlementConfig) {
+    env.storage()
+        .instance()
+        .set(&StorageKey::SettlementConfig, config);
+}
+
+pub fn get_conversion_source(env: &Env, token: &Address) -> Option<ConversionSource> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ConversionSource(token.clone()))
+}
+
+pub fn set_conversion_source(env: &Env, token: &Address, source: &ConversionSource) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ConversionSource(token.clone()), source);
+}
+
+pub fn get_conversion_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&StorageKey::ConversionCount)
+        .unwrap_or(0)
+}
+
+pub fn set_conversion_count(env: &Env, count: u64) {
+    env.storage()
+        .instance()
+        .set(&StorageKey::ConversionCount, &count);
+}
+
+pub fn get_conversion_record(env: &Env, id: u64) -> Option<ConversionRecord> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ConversionRecord(id))
+}
+
+pub fn set_conversion_record(env: &Env, record: &ConversionRecord) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ConversionRecord(record.id), record);
+}
+
+pub fn get_conversion_ledger(env: &Env, token: &Address) -> ConversionLedger {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ConversionLedger(token.clone()))
+        .unwrap_or_default()
+}
+
+pub fn set_conversion_ledger(env: &Env, token: &Address, ledger: &ConversionLedger) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ConversionLedger(token.clone()), ledger);
+}
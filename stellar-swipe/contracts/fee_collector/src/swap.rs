@@ -0,0 +1,62 @@
+//! SDEX router swaps used by the fee pipelines.
+//!
+//! Mirrors the router ABI used by TradeExecutor (`trade_executor::sdex`): the
+//! router is approved on the input SAC, `swap` is invoked, and the amount
+//! actually credited is measured from the output SAC balance delta rather
+//! than trusted from the router's return value.
+
+use soroban_sdk::{token, Address, Env, IntoVal, Symbol, Val, Vec};
+
+use crate::ContractError;
+
+/// Router entrypoint invoked for swaps.
+pub const ROUTER_SWAP_FN: &str = "swap";
+
+/// SAC allowance lifetime (ledgers) granted to the router per swap.
+const ROUTER_ALLOWANCE_LEDGERS: u32 = 1_000;
+
+/// Swap `amount_in` of `from_token` held by this contract into `to_token`.
+///
+/// Returns the amount of `to_token` actually received. Fails with
+/// [`ContractError::SlippageExceeded`] when fewer than `min_out` units arrive.
+pub fn execute_router_swap(
+    env: &Env,
+    router: &Address,
+    from_token: &Address,
+    to_token: &Address,
+    amount_in: i128,
+    min_out: i128,
+) -> Result<i128, ContractError> {
+    if amount_in <= 0 || min_out < 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    let this = env.current_contract_address();
+    let to_client = token::Client::new(env, to_token);
+    let expiration = env
+        .ledger()
+        .sequence()
+        .checked_add(ROUTER_ALLOWANCE_LEDGERS)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    token::Client::new(env, from_token).approve(&this, router, &amount_in, &expiration);
+
+    let balance_before = to_client.balance(&this);
+
+    let mut args = Vec::<Val>::new(env);
+    args.push_back(this.clone().into_val(env));
+    args.push_back(from_token.clone().into_val(env));
+    args.push_back(to_token.clone().into_val(env));
+    args.push_back(amount_in.into_val(env));
+    args.push_back(min_out.into_val(env));
+    args.push_back(this.clone().into_val(env));
+    let _reported_out: i128 = env.invoke_contract(router, &Symbol::new(env, ROUTER_SWAP_FN), args);
+
+    let received = to_client
+        .balance(&this)
+        .checked_sub(balance_before)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    if received < min_out {
+        return Err(ContractError::SlippageExceeded);
+    }
+    Ok(received)
+}
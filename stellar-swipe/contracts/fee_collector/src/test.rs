@@ -6,7 +6,9 @@ use soroban_sdk::{
     token::{Client as TokenClient, StellarAssetClient},
     Address, Env, String,
 };
-use stellar_swipe_common::Asset;
+use stellar_swipe_common::{
+    Asset, AssetPair, FeeSchedule, FeeSide, FeeTier, StakingDiscount, SECONDS_PER_DAY,
+};
 
 use crate::{
     set_pending_fees, set_treasury_balance, ContractError, FeeCollector, FeeCollectorClient,
@@ -475,7 +477,7 @@ fn test_collect_fee_tracks_volume_and_applies_rebate_tiers() {
 }
 
 #[test]
-fn test_monthly_volume_resets_on_new_ledger_month() {
+fn test_volume_rolls_off_after_30_day_window() {
     let env = Env::default();
     env.mock_all_auths();
 
@@ -494,14 +496,58 @@ fn test_monthly_volume_resets_on_new_ledger_month() {
     client.set_oracle_contract(&oracle_id);
 
     StellarAssetClient::new(&env, &token).mint(&trader, &(20_000 * 10_000_000));
-    client.collect_fee(&trader, &token, &(12_000 * 10_000_000), &asset);
+    env.ledger().set_timestamp(SECONDS_PER_DAY);
+    client.collect_fee(&trader, &token, &(6_000 * 10_000_000), &asset);
+    env.ledger().set_timestamp(20 * SECONDS_PER_DAY);
+    client.collect_fee(&trader, &token, &(6_000 * 10_000_000), &asset);
     assert_eq!(client.fee_rate_for_user(&trader), 25u32);
 
-    env.ledger()
-        .with_mut(|ledger| ledger.sequence_number += crate::storage::LEDGERS_PER_MONTH_APPROX + 1);
+    // Day 31: the day-1 trade leaves the window, the day-20 trade is still counted.
+    env.ledger().set_timestamp(31 * SECONDS_PER_DAY);
+    assert_eq!(client.monthly_trade_volume(&trader), 6_000 * 10_000_000);
+    assert_eq!(client.fee_rate_for_user(&trader), 30u32);
 
+    env.ledger().set_timestamp(50 * SECONDS_PER_DAY);
     assert_eq!(client.monthly_trade_volume(&trader), 0);
-    assert_eq!(client.fee_rate_for_user(&trader), 30u32);
+}
+
+#[test]
+fn test_legacy_monthly_volume_carries_into_rolling_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let trader = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+
+    let contract_id = env.register(FeeCollector, ());
+    let client = FeeCollectorClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    let (oracle_id, asset) = setup_oracle(&env, 10_000_000);
+    client.set_oracle_contract(&oracle_id);
+
+    // Entry written before the rolling window existed.
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(
+            &crate::storage::StorageKey::MonthlyTradeVolume(trader.clone()),
+            &crate::storage::MonthlyTradeVolume {
+                month_bucket: 0,
+                volume_usd: 9_000 * 10_000_000,
+            },
+        );
+    });
+    assert_eq!(client.monthly_trade_volume(&trader), 9_000 * 10_000_000);
+
+    StellarAssetClient::new(&env, &token).mint(&trader, &(10_000 * 10_000_000));
+    client.collect_fee(&trader, &token, &(2_000 * 10_000_000), &asset);
+    assert_eq!(client.monthly_trade_volume(&trader), 11_000 * 10_000_000);
+    env.as_contract(&contract_id, || {
+        assert!(crate::storage::get_monthly_trade_volume(&env, &trader).is_none());
+    });
 }
 
 #[test]
@@ -1311,4 +1357,1120 @@ fn test_audit_balances_limit_over_limit() {
     assert_eq!(result, Err(Ok(ContractError::IterationLimitExceeded)));
 }
 
+// ---------------------------------------------------------------------------
+// revenue-share distribution: buyback-and-burn + staker rewards
+// ---------------------------------------------------------------------------
+
+/// Router that fills every swap at a fixed 2:1 rate from its own inventory.
+#[contract]
+struct MockSdexRouter;
+
+#[contractimpl]
+impl MockSdexRouter {
+    pub fn swap(
+        env: Env,
+        pull_from: Address,
+        from_token: Address,
+        to_token: Address,
+        amount_in: i128,
+        _min_out: i128,
+        recipient: Address,
+    ) -> i128 {
+        let router = env.current_contract_address();
+        TokenClient::new(&env, &from_token).transfer_from(&router, &pull_from, &router, &amount_in);
+        let amount_out = amount_in * 2;
+        TokenClient::new(&env, &to_token).transfer(&router, &recipient, &amount_out);
+        amount_out
+    }
+}
+
+/// Governance stand-in recording the last staking-reward deposit.
+#[contract]
+struct MockGovernance;
+
+#[contractimpl]
+impl MockGovernance {
+    pub fn deposit_staking_rewards(
+        env: Env,
+        distributor: Address,
+        _token: Address,
+        amount: i128,
+        source_epoch: u64,
+    ) -> u64 {
+        distributor.require_auth();
+        env.storage().instance().set(
+            &soroban_sdk::symbol_short!("deposit"),
+            &(amount, source_epoch),
+        );
+        source_epoch + 100
+    }
+
+    pub fn last_deposit(env: Env) -> (i128, u64) {
+        env.storage()
+            .instance()
+            .get(&soroban_sdk::symbol_short!("deposit"))
+            .unwrap()
+    }
+}
+
+/// Seeds a revenue-share pool of `pool` units of a fresh fee token and wires a
+/// protocol token, router and governance stand-in into the distribution config.
+fn setup_distribution(
+    env: &Env,
+    pool: i128,
+    buyback_bps: u32,
+) -> (FeeCollectorClient<'_>, Address, Address, Address, Address) {
+    let (_, fee_token, contract_id, client) = setup(env, 0);
+    StellarAssetClient::new(env, &fee_token).mint(&contract_id, &pool);
+    env.as_contract(&contract_id, || {
+        crate::storage::add_revenue_share_pool(env, &fee_token, pool);
+    });
+
+    let protocol_token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    client.set_protocol_token(&Some(protocol_token.clone()));
+
+    let router = env.register(MockSdexRouter, ());
+    StellarAssetClient::new(env, &protocol_token).mint(&router, &1_000_000);
+    let governance = env.register(MockGovernance, ());
 
+    client.set_fee_distribution_config(&crate::FeeDistributionConfig {
+        governance: governance.clone(),
+        sdex_router: router,
+        buyback_bps,
+    });
+    (client, contract_id, fee_token, protocol_token, governance)
+}
+
+#[test]
+fn test_distribute_revenue_share_buys_back_and_streams_to_stakers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, contract_id, fee_token, protocol_token, governance) =
+        setup_distribution(&env, 1_000, 4_000);
+    let protocol_client = TokenClient::new(&env, &protocol_token);
+
+    let record = client.distribute_revenue_share(&fee_token, &800);
+
+    assert_eq!(record.epoch, 1);
+    assert_eq!(record.pool_amount, 1_000);
+    assert_eq!(record.buyback_amount, 400);
+    assert_eq!(record.protocol_tokens_burned, 800);
+    assert_eq!(record.staker_amount, 600);
+    assert_eq!(record.governance_epoch_id, Some(101));
+
+    // Bought-back protocol tokens are burned, not held.
+    assert_eq!(protocol_client.balance(&contract_id), 0);
+    let fee_client = TokenClient::new(&env, &fee_token);
+    assert_eq!(fee_client.balance(&contract_id), 0);
+    assert_eq!(fee_client.balance(&governance), 600);
+    assert_eq!(
+        MockGovernanceClient::new(&env, &governance).last_deposit(),
+        (600, 1)
+    );
+
+    assert_eq!(client.get_revenue_share_pool(&fee_token), 0);
+    assert_eq!(client.fee_distribution_epoch_count(), 1);
+    assert_eq!(client.fee_distribution_record(&1), Some(record));
+}
+
+#[test]
+fn test_distribute_revenue_share_enforces_buyback_slippage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, fee_token, _, _) = setup_distribution(&env, 1_000, 4_000);
+
+    let result = client.try_distribute_revenue_share(&fee_token, &801);
+    assert_eq!(result, Err(Ok(ContractError::SlippageExceeded)));
+    // The failed run rolls back, leaving the pool intact for a retry.
+    assert_eq!(client.get_revenue_share_pool(&fee_token), 1_000);
+    assert_eq!(client.fee_distribution_epoch_count(), 0);
+}
+
+#[test]
+fn test_distribute_revenue_share_requires_config_and_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, token, _, client) = setup(&env, 0);
+
+    let result = client.try_distribute_revenue_share(&token, &0);
+    assert_eq!(result, Err(Ok(ContractError::DistributionNotConfigured)));
+
+    let (client, _, _, protocol_token, _) = setup_distribution(&env, 1_000, 10_000);
+    let result = client.try_distribute_revenue_share(&protocol_token, &0);
+    assert_eq!(result, Err(Ok(ContractError::NothingToDistribute)));
+}
+
+// ---------------------------------------------------------------------------
+// governance fee schedules
+// ---------------------------------------------------------------------------
+
+/// Staking contract stand-in reporting a fixed stake for every holder.
+#[contract]
+struct MockStakingSource;
+
+#[contractimpl]
+impl MockStakingSource {
+    pub fn staked_balance(_env: Env, _holder: Address) -> i128 {
+        5_000
+    }
+}
+
+fn tiered_schedule(env: &Env, staking_discounts: soroban_sdk::Vec<StakingDiscount>) -> FeeSchedule {
+    let mut tiers = soroban_sdk::Vec::new(env);
+    tiers.push_back(FeeTier {
+        min_volume_usd: 0,
+        maker_rate_bps: 10,
+        taker_rate_bps: 40,
+    });
+    tiers.push_back(FeeTier {
+        min_volume_usd: 1_000 * 10_000_000,
+        maker_rate_bps: 5,
+        taker_rate_bps: 30,
+    });
+    FeeSchedule {
+        tiers,
+        staking_discounts,
+    }
+}
+
+#[test]
+fn test_governor_sets_pair_schedule_with_maker_taker_and_staking_discount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, token, contract_id, client) = setup(&env, 0);
+    let (oracle_id, asset) = setup_oracle(&env, 10_000_000);
+    client.set_oracle_contract(&oracle_id);
+    disable_revenue_share(&client);
+    client.set_burn_rate(&0u32);
+
+    let governor = Address::generate(&env);
+    client.set_fee_governor(&governor);
+    assert_eq!(client.fee_governor(), Some(governor));
+
+    let pair = AssetPair {
+        base: asset.clone(),
+        quote: usd_asset(&env),
+    };
+    client.set_pair_fee_schedule(
+        &pair,
+        &Some(tiered_schedule(&env, soroban_sdk::Vec::new(&env))),
+    );
+
+    let trader = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&trader, &(10_000 * 10_000_000));
+    mark_trader_has_traded(&env, &contract_id, &trader);
+
+    // Pair schedule applies per side; other pairs keep the default schedule.
+    assert_eq!(
+        client.fee_rate_for_pair(&trader, &Some(pair.clone()), &FeeSide::Taker),
+        40
+    );
+    assert_eq!(
+        client.fee_rate_for_pair(&trader, &Some(pair.clone()), &FeeSide::Maker),
+        10
+    );
+    assert_eq!(client.fee_rate_for_user(&trader), 30);
+
+    let fee = client.collect_pair_fee(
+        &trader,
+        &token,
+        &(1_000 * 10_000_000),
+        &pair,
+        &FeeSide::Maker,
+    );
+    assert_eq!(fee, 10_000_000);
+    assert_eq!(
+        client.fee_rate_for_pair(&trader, &Some(pair.clone()), &FeeSide::Taker),
+        30
+    );
+
+    // Staking discounts read the configured staking contract.
+    let mut discounts = soroban_sdk::Vec::new(&env);
+    discounts.push_back(StakingDiscount {
+        min_staked: 1_000,
+        discount_bps: 10,
+    });
+    client.set_pair_fee_schedule(&pair, &Some(tiered_schedule(&env, discounts)));
+    client.set_staking_balance_source(&env.register(MockStakingSource, ()));
+
+    let quote = client.preview_fee(&trader, &Some(pair.clone()), &FeeSide::Taker, &1_000_000);
+    assert_eq!(quote.tier_index, 1);
+    assert_eq!(quote.tier_rate_bps, 30);
+    assert_eq!(quote.staking_discount_bps, 10);
+    assert_eq!(quote.rate_bps, 20);
+    assert_eq!(quote.fee, 2_000);
+
+    client.set_pair_fee_schedule(&pair, &None);
+    assert_eq!(
+        client.fee_rate_for_pair(&trader, &Some(pair), &FeeSide::Maker),
+        30
+    );
+}
+
+#[test]
+fn test_set_fee_schedule_validates_tiers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, _, _, client) = setup(&env, 0);
+
+    let mut schedule = tiered_schedule(&env, soroban_sdk::Vec::new(&env));
+    schedule.tiers.push_back(FeeTier {
+        min_volume_usd: 10,
+        maker_rate_bps: 1,
+        taker_rate_bps: 1,
+    });
+    assert_eq!(
+        client.try_set_fee_schedule(&schedule),
+        Err(Ok(ContractError::InvalidFeeConfiguration))
+    );
+
+    let mut schedule = FeeSchedule::flat(&env, 101);
+    assert_eq!(
+        client.try_set_fee_schedule(&schedule),
+        Err(Ok(ContractError::FeeRateTooHigh))
+    );
+
+    schedule = FeeSchedule::flat(&env, 15);
+    client.set_fee_schedule(&schedule);
+    assert_eq!(client.fee_schedule(&None), schedule);
+    assert_eq!(client.fee_rate_for_user(&Address::generate(&env)), 15);
+}
+
+// ---------------------------------------------------------------------------
+// settlement conversion
+// ---------------------------------------------------------------------------
+
+/// Oracle valuing `SETTLE` at par and every other asset at three base units.
+#[contract]
+struct MockPremiumOracle;
+
+#[contractimpl]
+impl MockPremiumOracle {
+    pub fn convert_to_base(env: Env, amount: i128, asset: Asset) -> i128 {
+        if asset.code == String::from_str(&env, "SETTLE") {
+            amount
+        } else {
+            amount * 3
+        }
+    }
+}
+
+/// Oracle pricing `SETTLE` at two base units and every other asset at par.
+#[contract]
+struct MockUsdOracle;
+
+#[contractimpl]
+impl MockUsdOracle {
+    pub fn convert_to_base(env: Env, amount: i128, asset: Asset) -> i128 {
+        if asset.code == String::from_str(&env, "SETTLE") {
+            amount * 2
+        } else {
+            amount
+        }
+    }
+}
+
+/// Registers a settlement token, a router holding it, and a conversion
+/// source for `fee_token` with the given threshold.
+fn setup_settlement(
+    env: &Env,
+    client: &FeeCollectorClient<'_>,
+    fee_token: &Address,
+    min_conversion_value: i128,
+) -> Address {
+    let settlement_token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let router = env.register(MockSdexRouter, ());
+    StellarAssetClient::new(env, &settlement_token).mint(&router, &1_000_000);
+    client.set_settlement_config(&crate::SettlementConfig {
+        settlement_token: settlement_token.clone(),
+        sdex_router: router,
+        max_slippage_bps: 100,
+    });
+    client.set_conversion_source(
+        &settlement_token,
+        &crate::ConversionSource {
+            asset: Asset {
+                code: String::from_str(env, "SETTLE"),
+                issuer: None,
+            },
+            min_conversion_value: 0,
+        },
+    );
+    client.set_conversion_source(
+        fee_token,
+        &crate::ConversionSource {
+            asset: trade_asset(env),
+            min_conversion_value,
+        },
+    );
+    settlement_token
+}
+
+#[test]
+fn test_convert_fees_to_settlement_swaps_and_records_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, fee_token, contract_id, client) = setup(&env, 1_000);
+    let (oracle_id, _) = setup_oracle(&env, 10_000_000);
+    client.set_oracle_contract(&oracle_id);
+    let settlement_token = setup_settlement(&env, &client, &fee_token, 500);
+
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(fee_token.clone());
+    tokens.push_back(settlement_token.clone());
+    let records = client.convert_fees_to_settlement(&tokens);
+
+    assert_eq!(records.len(), 1);
+    let record = records.get(0).unwrap();
+    assert_eq!(record.amount_in, 1_000);
+    assert_eq!(record.amount_out, 2_000);
+    assert_eq!(record.oracle_value, 1_000);
+    assert_eq!(client.conversion_record(&1), Some(record));
+
+    assert_eq!(client.treasury_balance(&fee_token), 0);
+    assert_eq!(client.treasury_balance(&settlement_token), 2_000);
+    assert_eq!(
+        TokenClient::new(&env, &settlement_token).balance(&contract_id),
+        2_000
+    );
+    assert_eq!(client.audit_balances(&tokens).len(), 0);
+
+    let ledger = client.conversion_ledger(&fee_token);
+    assert_eq!(ledger.total_converted_in, 1_000);
+    assert_eq!(ledger.total_settlement_out, 2_000);
+    assert_eq!(ledger.conversions, 1);
+
+    // Mismatches report conversion totals alongside the live balance.
+    StellarAssetClient::new(&env, &fee_token).mint(&contract_id, &7);
+    let mismatch = client.audit_balances(&tokens).get(0).unwrap();
+    assert_eq!(mismatch.delta, 7);
+    assert_eq!(mismatch.converted_out, 1_000);
+    assert_eq!(mismatch.settlement_received, 2_000);
+}
+
+#[test]
+fn test_convert_fees_skips_dust_and_reserves_queued_withdrawal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (recipient, fee_token, _, client) = setup(&env, 1_000);
+    let (oracle_id, _) = setup_oracle(&env, 10_000_000);
+    client.set_oracle_contract(&oracle_id);
+    setup_settlement(&env, &client, &fee_token, 500);
+
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(fee_token.clone());
+
+    // 600 of 1,000 is queued for withdrawal; the 400 left is below threshold.
+    client.queue_withdrawal(&recipient, &fee_token, &600);
+    assert_eq!(client.convert_fees_to_settlement(&tokens).len(), 0);
+    assert_eq!(client.treasury_balance(&fee_token), 1_000);
+    assert_eq!(client.conversion_ledger(&fee_token).conversions, 0);
+}
+
+#[test]
+fn test_convert_fees_rejects_swap_below_oracle_value() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, fee_token, _, client) = setup(&env, 1_000);
+    client.set_oracle_contract(&env.register(MockPremiumOracle, ()));
+    setup_settlement(&env, &client, &fee_token, 0);
+
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(fee_token.clone());
+
+    // Oracle says 3,000; the router only returns 2,000 (> 1% slippage).
+    assert_eq!(
+        client.try_convert_fees_to_settlement(&tokens),
+        Err(Ok(ContractError::SlippageExceeded))
+    );
+    assert_eq!(client.treasury_balance(&fee_token), 1_000);
+}
+
+#[test]
+fn test_convert_fees_values_in_settlement_units() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, fee_token, _, client) = setup(&env, 1_000);
+    client.set_oracle_contract(&env.register(MockUsdOracle, ()));
+    setup_settlement(&env, &client, &fee_token, 0);
+
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(fee_token.clone());
+
+    // 1,000 base units buy 500 settlement tokens at two base units each.
+    let record = client.convert_fees_to_settlement(&tokens).get(0).unwrap();
+    assert_eq!(record.oracle_value, 500);
+    assert_eq!(record.amount_out, 2_000);
+}
+
+#[test]
+fn test_convert_fees_skips_token_without_source() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, fee_token, contract_id, client) = setup(&env, 1_000);
+    let (oracle_id, _) = setup_oracle(&env, 10_000_000);
+    client.set_oracle_contract(&oracle_id);
+    setup_settlement(&env, &client, &fee_token, 0);
+
+    let unpriced = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &unpriced).mint(&contract_id, &300);
+    env.as_contract(&contract_id, || {
+        set_treasury_balance(&env, &unpriced, 300);
+    });
+
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(unpriced.clone());
+    tokens.push_back(fee_token.clone());
+
+    let records = client.convert_fees_to_settlement(&tokens);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records.get(0).unwrap().token, fee_token);
+    assert_eq!(client.treasury_balance(&unpriced), 300);
+}
+
+// ---------------------------------------------------------------------------
+// copier insurance fund
+// ---------------------------------------------------------------------------
+
+/// SignalRegistry stand-in returning whatever dispute outcome was stored.
+#[contract]
+struct MockDisputeRegistry;
+
+#[contractimpl]
+impl MockDisputeRegistry {
+    pub fn set_outcome(env: Env, provider: Address, at_fault: bool) {
+        env.storage().instance().set(
+            &provider.clone(),
+            &crate::DisputeOutcome {
+                provider,
+                opened_at: 100,
+                resolved_at: 200,
+                provider_at_fault: at_fault,
+            },
+        );
+    }
+
+    pub fn get_provider_dispute_history(
+        env: Env,
+        provider: Address,
+    ) -> soroban_sdk::Vec<crate::DisputeOutcome> {
+        let mut outcomes = soroban_sdk::Vec::new(&env);
+        if let Some(outcome) = env.storage().instance().get(&provider) {
+            outcomes.push_back(outcome);
+        }
+        outcomes
+    }
+}
+
+/// AutoTrade stand-in returning whatever copy-trade loss was stored.
+#[contract]
+struct MockCopyTrades;
+
+#[contractimpl]
+impl MockCopyTrades {
+    pub fn set_loss(
+        env: Env,
+        copier: Address,
+        signal_id: u64,
+        provider: Address,
+        token: Address,
+        executed_at: u64,
+        loss: i128,
+    ) {
+        env.storage().instance().set(
+            &(copier, signal_id),
+            &crate::CopyTradeLoss {
+                signal_id,
+                provider,
+                executed_at,
+                token,
+                executed_amount: loss * 2,
+                loss,
+            },
+        );
+    }
+
+    pub fn get_copy_trade_loss(
+        env: Env,
+        copier: Address,
+        signal_id: u64,
+    ) -> Option<crate::CopyTradeLoss> {
+        env.storage().instance().get(&(copier, signal_id))
+    }
+}
+
+struct InsuranceSetup<'a> {
+    client: FeeCollectorClient<'a>,
+    contract_id: Address,
+    token: Address,
+    registry: MockDisputeRegistryClient<'a>,
+    trades: MockCopyTradesClient<'a>,
+    stake_vault: Address,
+}
+
+fn setup_insurance(env: &Env) -> InsuranceSetup<'_> {
+    let (_, token, contract_id, client) = setup(env, 0);
+    let registry_id = env.register(MockDisputeRegistry, ());
+    let trades_id = env.register(MockCopyTrades, ());
+    let stake_vault = Address::generate(env);
+    client.set_insurance_config(&crate::InsuranceConfig {
+        signal_registry: registry_id.clone(),
+        stake_vault: stake_vault.clone(),
+        committee: Address::generate(env),
+        fee_share_bps: 1_000,
+        coverage_bps: 5_000,
+        max_payout_per_claim: 400,
+        max_payout_per_epoch: 600,
+        epoch_seconds: 7 * SECONDS_PER_DAY,
+        committee_threshold: 300,
+    });
+    client.set_insurance_trade_source(&trades_id);
+    InsuranceSetup {
+        client,
+        contract_id,
+        token,
+        registry: MockDisputeRegistryClient::new(env, &registry_id),
+        trades: MockCopyTradesClient::new(env, &trades_id),
+        stake_vault,
+    }
+}
+
+/// Simulates StakeVault transferring a slash and booking it.
+fn slash_into_fund(env: &Env, s: &InsuranceSetup<'_>, amount: i128) {
+    StellarAssetClient::new(env, &s.token).mint(&s.contract_id, &amount);
+    s.client
+        .deposit_slashed_stake(&s.stake_vault, &s.token, &amount, &Address::generate(env));
+}
+
+#[test]
+fn test_collected_fees_fund_insurance_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let s = setup_insurance(&env);
+    let (oracle_id, asset) = setup_oracle(&env, 10_000_000);
+    s.client.set_oracle_contract(&oracle_id);
+    disable_revenue_share(&s.client);
+    s.client.set_burn_rate(&0u32);
+
+    let trader = Address::generate(&env);
+    StellarAssetClient::new(&env, &s.token).mint(&trader, &1_000_000);
+    mark_trader_has_traded(&env, &s.contract_id, &trader);
+    let fee = s.client.collect_fee(&trader, &s.token, &1_000_000, &asset);
+
+    assert_eq!(fee, 3_000);
+    assert_eq!(s.client.insurance_fund_balance(&s.token), 300);
+    assert_eq!(s.client.treasury_balance(&s.token), 2_700);
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(s.token.clone());
+    assert_eq!(s.client.audit_balances(&tokens).len(), 0);
+}
+
+#[test]
+fn test_insurance_claim_paid_for_upheld_dispute_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let s = setup_insurance(&env);
+    slash_into_fund(&env, &s, 1_000);
+    assert_eq!(s.client.insurance_fund_balance(&s.token), 1_000);
+
+    let copier = Address::generate(&env);
+    let provider = Address::generate(&env);
+    assert_eq!(
+        s.client.try_file_insurance_claim(&copier, &7),
+        Err(Ok(ContractError::NoInsurableLoss))
+    );
+    s.trades
+        .set_loss(&copier, &7, &provider, &s.token, &50, &500);
+    assert_eq!(
+        s.client.try_file_insurance_claim(&copier, &7),
+        Err(Ok(ContractError::DisputeNotResolved))
+    );
+    s.registry.set_outcome(&provider, &false);
+    assert_eq!(
+        s.client.try_file_insurance_claim(&copier, &7),
+        Err(Ok(ContractError::DisputeNotResolved))
+    );
+
+    s.registry.set_outcome(&provider, &true);
+    // 250 is within the 300 committee threshold: paid on filing, in the
+    // token the copy trade was paid in.
+    let claim = s.client.file_insurance_claim(&copier, &7);
+    assert_eq!(claim.provider, provider);
+    assert_eq!(claim.token, s.token);
+    assert_eq!(claim.loss_amount, 500);
+    assert_eq!(claim.payout, 250);
+    assert_eq!(claim.dispute_opened_at, 100);
+    assert_eq!(claim.status, crate::InsuranceClaimStatus::Paid);
+    assert_eq!(TokenClient::new(&env, &s.token).balance(&copier), 250);
+    assert_eq!(
+        s.client.try_decide_insurance_claim(&claim.id, &true),
+        Err(Ok(ContractError::InsuranceClaimNotPending))
+    );
+    assert_eq!(s.client.insurance_fund_balance(&s.token), 750);
+    assert_eq!(
+        s.client.insurance_epoch_paid(&env.ledger().timestamp()),
+        250
+    );
+
+    assert_eq!(
+        s.client.try_file_insurance_claim(&copier, &7),
+        Err(Ok(ContractError::DuplicateInsuranceClaim))
+    );
+}
+
+#[test]
+fn test_insurance_claim_needs_copy_trade_before_dispute() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let s = setup_insurance(&env);
+    slash_into_fund(&env, &s, 1_000);
+    let provider = Address::generate(&env);
+    s.registry.set_outcome(&provider, &true);
+
+    // Copied after the dispute was opened at 100.
+    let late = Address::generate(&env);
+    s.trades
+        .set_loss(&late, &3, &provider, &s.token, &150, &500);
+    assert_eq!(
+        s.client.try_file_insurance_claim(&late, &3),
+        Err(Ok(ContractError::DisputeNotResolved))
+    );
+
+    // A copy trade that is not underwater has nothing to insure.
+    let even = Address::generate(&env);
+    s.trades.set_loss(&even, &3, &provider, &s.token, &50, &0);
+    assert_eq!(
+        s.client.try_file_insurance_claim(&even, &3),
+        Err(Ok(ContractError::NoInsurableLoss))
+    );
+}
+
+#[test]
+fn test_insurance_payout_is_in_the_copy_trade_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let s = setup_insurance(&env);
+    slash_into_fund(&env, &s, 1_000);
+    let provider = Address::generate(&env);
+    s.registry.set_outcome(&provider, &true);
+
+    // The fund holds `s.token` only; a copy paid in another token cannot
+    // draw on it.
+    let other = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let copier = Address::generate(&env);
+    s.trades.set_loss(&copier, &4, &provider, &other, &50, &500);
+    assert_eq!(
+        s.client.try_file_insurance_claim(&copier, &4),
+        Err(Ok(ContractError::InsufficientInsuranceFund))
+    );
+    assert_eq!(s.client.insurance_fund_balance(&s.token), 1_000);
+}
+
+#[test]
+fn test_large_insurance_claim_needs_committee_and_epoch_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let s = setup_insurance(&env);
+    slash_into_fund(&env, &s, 5_000);
+    let provider = Address::generate(&env);
+    s.registry.set_outcome(&provider, &true);
+
+    // 50% of 2,000 is capped at 400 per claim.
+    let copier_a = Address::generate(&env);
+    s.trades
+        .set_loss(&copier_a, &1, &provider, &s.token, &50, &2_000);
+    let claim = s.client.file_insurance_claim(&copier_a, &1);
+    assert_eq!(claim.payout, 400);
+    assert_eq!(claim.status, crate::InsuranceClaimStatus::PendingApproval);
+    assert_eq!(TokenClient::new(&env, &s.token).balance(&copier_a), 0);
+
+    let claim = s.client.decide_insurance_claim(&claim.id, &true);
+    assert_eq!(claim.status, crate::InsuranceClaimStatus::Paid);
+    assert_eq!(TokenClient::new(&env, &s.token).balance(&copier_a), 400);
+    assert_eq!(
+        s.client.try_decide_insurance_claim(&claim.id, &true),
+        Err(Ok(ContractError::InsuranceClaimNotPending))
+    );
+
+    // A second 400 payout would breach the 600 epoch cap until the next epoch.
+    let copier_b = Address::generate(&env);
+    s.trades
+        .set_loss(&copier_b, &1, &provider, &s.token, &50, &2_000);
+    let pending = s.client.file_insurance_claim(&copier_b, &1);
+    assert_eq!(
+        s.client.try_decide_insurance_claim(&pending.id, &true),
+        Err(Ok(ContractError::InsuranceCapExceeded))
+    );
+    env.ledger()
+        .with_mut(|l| l.timestamp += 7 * SECONDS_PER_DAY);
+    s.client.decide_insurance_claim(&pending.id, &true);
+    assert_eq!(TokenClient::new(&env, &s.token).balance(&copier_b), 400);
+}
+
+#[test]
+fn test_decide_insurance_claim_requires_initialization() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = FeeCollectorClient::new(&env, &env.register(FeeCollector, ()));
+    assert_eq!(
+        client.try_decide_insurance_claim(&1, &true),
+        Err(Ok(ContractError::NotInitialized))
+    );
+}
+
+#[test]
+fn test_slashed_stake_only_accepted_from_stake_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let s = setup_insurance(&env);
+    let result = s.client.try_deposit_slashed_stake(
+        &Address::generate(&env),
+        &s.token,
+        &100,
+        &Address::generate(&env),
+    );
+    assert_eq!(result, Err(Ok(ContractError::Unauthorized)));
+}
+
+// ── Referrals ───────────────────────────────────────────────────────
+
+fn setup_referrals(
+    env: &Env,
+    expiry_months: u32,
+) -> (Address, Address, FeeCollectorClient<'_>, Asset) {
+    let (_, token, contract_id, client) = setup(env, 0);
+    let (oracle_id, asset) = setup_oracle(env, 10_000_000);
+    client.set_oracle_contract(&oracle_id);
+    disable_revenue_share(&client);
+    client.set_burn_rate(&0u32);
+    client.set_referral_config(&crate::ReferralConfig {
+        level1_bps: 1_000,
+        level2_bps: 500,
+        expiry_months,
+    });
+    (token, contract_id, client, asset)
+}
+
+#[test]
+fn test_referral_fees_credit_two_levels_and_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (token, contract_id, client, asset) = setup_referrals(&env, 0);
+
+    let upstream = Address::generate(&env);
+    let referrer = Address::generate(&env);
+    let trader = Address::generate(&env);
+    client.register_referrer(&referrer, &upstream);
+    client.register_referrer(&trader, &referrer);
+
+    StellarAssetClient::new(&env, &token).mint(&trader, &1_000_000);
+    mark_trader_has_traded(&env, &contract_id, &trader);
+    let fee = client.collect_fee(&trader, &token, &1_000_000, &asset);
+
+    assert_eq!(fee, 3_000);
+    assert_eq!(client.referral_claimable(&referrer, &token), 300);
+    assert_eq!(client.referral_claimable(&upstream, &token), 150);
+    assert_eq!(client.treasury_balance(&token), 2_550);
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(token.clone());
+    assert_eq!(client.audit_balances(&tokens).len(), 0);
+
+    let board = client.referral_leaderboard(&token, &10);
+    assert_eq!(board.len(), 2);
+    assert_eq!(board.get(0).unwrap().referrer, referrer);
+    assert_eq!(board.get(0).unwrap().referees, 1);
+    assert_eq!(board.get(1).unwrap().total_earned, 150);
+
+    assert_eq!(client.claim_referral_rewards(&referrer, &token), 300);
+    assert_eq!(TokenClient::new(&env, &token).balance(&referrer), 300);
+    assert_eq!(client.referral_claimable(&referrer, &token), 0);
+    let stats = client.referrer_token_stats(&referrer, &token);
+    assert_eq!(stats.earned, 300);
+    assert_eq!(stats.claimed, 300);
+    assert_eq!(client.claim_referral_rewards(&referrer, &token), 0);
+    assert_eq!(client.audit_balances(&tokens).len(), 0);
+
+    // Earnings in another token are ranked separately.
+    let other = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &other).mint(&trader, &2_000_000);
+    client.collect_fee(&trader, &other, &2_000_000, &asset);
+    assert_eq!(client.referrer_token_stats(&referrer, &other).earned, 600);
+    assert_eq!(client.referrer_token_stats(&referrer, &token).earned, 300);
+    let board = client.referral_leaderboard(&other, &10);
+    assert_eq!(board.get(0).unwrap().total_earned, 600);
+    assert_eq!(
+        client
+            .referral_leaderboard(&token, &10)
+            .get(0)
+            .unwrap()
+            .total_earned,
+        300
+    );
+}
+
+#[test]
+fn test_referral_leaderboard_is_sorted_and_capped() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (token, contract_id, client, _) = setup_referrals(&env, 0);
+
+    let size = crate::referrals::REFERRAL_LEADERBOARD_SIZE;
+    for i in 0..size + 5 {
+        let referrer = Address::generate(&env);
+        let trader = Address::generate(&env);
+        client.register_referrer(&trader, &referrer);
+        // Alternate small and large fees so inserts land mid-ranking.
+        let fee = if i % 2 == 0 {
+            10_000 + i as i128
+        } else {
+            100_000 - i as i128
+        };
+        env.as_contract(&contract_id, || {
+            crate::referrals::allocate_fee_share(&env, &trader, &token, fee).unwrap();
+        });
+    }
+
+    let board = client.referral_leaderboard(&token, &100);
+    assert_eq!(board.len(), size);
+    assert_eq!(board.get(0).unwrap().total_earned, 9_999);
+    assert_eq!(board.get(0).unwrap().rank, 1);
+    for i in 1..board.len() {
+        assert!(board.get(i - 1).unwrap().total_earned >= board.get(i).unwrap().total_earned);
+    }
+}
+
+#[test]
+fn test_referral_registration_rejects_abuse() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, contract_id, client, _) = setup_referrals(&env, 0);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    assert_eq!(
+        client.try_register_referrer(&alice, &alice),
+        Err(Ok(ContractError::SelfReferral))
+    );
+
+    client.register_referrer(&bob, &alice);
+    client.register_referrer(&carol, &bob);
+    assert_eq!(
+        client.try_register_referrer(&bob, &carol),
+        Err(Ok(ContractError::ReferrerAlreadySet))
+    );
+    assert_eq!(
+        client.try_register_referrer(&alice, &carol),
+        Err(Ok(ContractError::ReferralCycle))
+    );
+
+    let veteran = Address::generate(&env);
+    mark_trader_has_traded(&env, &contract_id, &veteran);
+    assert_eq!(
+        client.try_register_referrer(&veteran, &alice),
+        Err(Ok(ContractError::ReferralAfterFirstTrade))
+    );
+}
+
+#[test]
+fn test_referral_stops_earning_after_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (token, contract_id, client, asset) = setup_referrals(&env, 1);
+
+    let referrer = Address::generate(&env);
+    let trader = Address::generate(&env);
+    client.register_referrer(&trader, &referrer);
+    StellarAssetClient::new(&env, &token).mint(&trader, &2_000_000);
+    mark_trader_has_traded(&env, &contract_id, &trader);
+
+    client.collect_fee(&trader, &token, &1_000_000, &asset);
+    assert_eq!(client.referral_claimable(&referrer, &token), 300);
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += 30 * SECONDS_PER_DAY);
+    client.collect_fee(&trader, &token, &1_000_000, &asset);
+    assert_eq!(client.referral_claimable(&referrer, &token), 300);
+    assert_eq!(client.treasury_balance(&token), 2_700 + 3_000);
+}
+
+// ── Provider payouts ────────────────────────────────────────────────
+
+#[test]
+fn test_claim_all_sweeps_every_credited_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, token_a, contract_id, client) = setup(&env, 10_000);
+    let token_b = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &token_b).mint(&contract_id, &5_000);
+    env.as_contract(&contract_id, || set_treasury_balance(&env, &token_b, 5_000));
+
+    let provider = Address::generate(&env);
+    assert_eq!(client.credit_provider_fees(&provider, &token_a, &4_000), 0);
+    assert_eq!(client.credit_provider_fees(&provider, &token_b, &1_000), 0);
+    assert_eq!(client.treasury_balance(&token_a), 6_000);
+    assert_eq!(client.pending_fees(&provider, &token_a), 4_000);
+    assert_eq!(
+        client.try_credit_provider_fees(&provider, &token_b, &4_001),
+        Err(Ok(ContractError::InsufficientTreasuryBalance))
+    );
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    tokens.push_back(token_a.clone());
+    tokens.push_back(token_b.clone());
+    assert_eq!(client.audit_balances(&tokens).len(), 0);
+
+    let claimed = client.claim_all(&provider);
+    assert_eq!(claimed.len(), 2);
+    assert_eq!(claimed.get(0).unwrap().amount, 4_000);
+    assert_eq!(claimed.get(1).unwrap().token, token_b);
+    assert_eq!(TokenClient::new(&env, &token_a).balance(&provider), 4_000);
+    assert_eq!(TokenClient::new(&env, &token_b).balance(&provider), 1_000);
+    assert_eq!(client.claim_all(&provider).len(), 0);
+    assert_eq!(client.audit_balances(&tokens).len(), 0);
+}
+
+#[test]
+fn test_payout_split_and_auto_claim_threshold() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, token, _, client) = setup(&env, 10_000);
+    let provider = Address::generate(&env);
+    let payout = Address::generate(&env);
+    let co_author = Address::generate(&env);
+
+    let mut splits = soroban_sdk::Vec::new(&env);
+    splits.push_back(crate::PayoutSplit {
+        recipient: payout.clone(),
+        share_bps: 6_667,
+    });
+    splits.push_back(crate::PayoutSplit {
+        recipient: co_author.clone(),
+        share_bps: 3_000,
+    });
+    let mut config = crate::PayoutConfig {
+        payout_address: Some(payout.clone()),
+        splits,
+        auto_claim_threshold: 1_000,
+    };
+    assert_eq!(
+        client.try_set_payout_config(&provider, &config),
+        Err(Ok(ContractError::InvalidPayoutSplit))
+    );
+    config.splits.set(
+        1,
+        crate::PayoutSplit {
+            recipient: co_author.clone(),
+            share_bps: 3_333,
+        },
+    );
+    client.set_payout_config(&provider, &config);
+
+    assert_eq!(client.credit_provider_fees(&provider, &token, &600), 0);
+    // Crossing the threshold pays the whole pending balance at once; the
+    // rounding remainder goes to the payout address.
+    assert_eq!(client.credit_provider_fees(&provider, &token, &401), 1_001);
+    let balance = |who: &Address| TokenClient::new(&env, &token).balance(who);
+    assert_eq!(balance(&co_author), 333);
+    assert_eq!(balance(&payout), 668);
+    assert_eq!(balance(&provider), 0);
+    assert_eq!(client.pending_fees(&provider, &token), 0);
+}
+
+/// SignalRegistry stand-in returning the co-author shares stored per signal.
+#[contract]
+struct MockCollaborationRegistry;
+
+#[contractimpl]
+impl MockCollaborationRegistry {
+    pub fn set_splits(env: Env, signal_id: u64, splits: soroban_sdk::Vec<crate::PayoutSplit>) {
+        env.storage().instance().set(&signal_id, &splits);
+    }
+
+    pub fn get_collaboration_payout_splits(
+        env: Env,
+        signal_id: u64,
+    ) -> Option<soroban_sdk::Vec<crate::PayoutSplit>> {
+        env.storage().instance().get(&signal_id)
+    }
+}
+
+#[test]
+fn test_credit_signal_fees_splits_per_signal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, token, _, client) = setup(&env, 10_000);
+    let provider = Address::generate(&env);
+    let co_author = Address::generate(&env);
+
+    assert_eq!(
+        client.try_credit_signal_fees(&1, &provider, &token, &1_000),
+        Err(Ok(ContractError::NotInitialized))
+    );
+    let registry = env.register(MockCollaborationRegistry, ());
+    client.set_signal_registry(&registry);
+
+    let mut splits = soroban_sdk::Vec::new(&env);
+    splits.push_back(crate::PayoutSplit {
+        recipient: provider.clone(),
+        share_bps: 6_667,
+    });
+    splits.push_back(crate::PayoutSplit {
+        recipient: co_author.clone(),
+        share_bps: 3_333,
+    });
+    MockCollaborationRegistryClient::new(&env, &registry).set_splits(&1, &splits);
+
+    // Signal 1 is collaborative: each author is credited their share and the
+    // rounding remainder goes to the provider.
+    assert_eq!(client.credit_signal_fees(&1, &provider, &token, &1_000), 0);
+    assert_eq!(client.pending_fees(&provider, &token), 667);
+    assert_eq!(client.pending_fees(&co_author, &token), 333);
+
+    // Signal 2 has no collaboration, so the provider is credited everything.
+    assert_eq!(client.credit_signal_fees(&2, &provider, &token, &500), 0);
+    assert_eq!(client.pending_fees(&provider, &token), 1_167);
+    assert_eq!(client.pending_fees(&co_author, &token), 333);
+}
+
+#[test]
+fn test_payout_split_overflow_is_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (_, token, contract_id, client) = setup(&env, 10_000);
+    let provider = Address::generate(&env);
+
+    let mut splits = soroban_sdk::Vec::new(&env);
+    for _ in 0..2 {
+        splits.push_back(crate::PayoutSplit {
+            recipient: Address::generate(&env),
+            share_bps: 5_000,
+        });
+    }
+    client.set_payout_config(
+        &provider,
+        &crate::PayoutConfig {
+            payout_address: None,
+            splits,
+            auto_claim_threshold: 0,
+        },
+    );
+    env.as_contract(&contract_id, || {
+        set_pending_fees(&env, &provider, &token, i128::MAX)
+    });
+    assert_eq!(
+        client.try_claim_fees(&provider, &token),
+        Err(Ok(ContractError::ArithmeticOverflow))
+    );
+}
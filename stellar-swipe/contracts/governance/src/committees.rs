@@ -7,10 +7,11 @@ use stellar_swipe_common::Asset;
 
 use crate::distribution::update_reward_config;
 use crate::errors::GovernanceError;
+use crate::grants;
 use crate::treasury;
 use crate::{
-    checked_add, checked_div, checked_mul, checked_sub, get_distribution_state, get_staked_balance,
-    get_treasury, put_treasury,
+    checked_add, checked_div, checked_mul, checked_sub, get_distribution_state, get_grants_state,
+    get_staked_balance, get_treasury, put_grants_state, put_treasury,
 };
 
 const APPROVAL_RATING_BPS: u32 = 10_000;
@@ -95,6 +96,7 @@ pub enum CommitteeAction {
     GrantApproval(GrantApprovalAction),
     EmergencyAction(EmergencyActionPayload),
     Veto(VetoPayload),
+    GrantMilestoneRelease(GrantMilestoneAction),
 }
 
 #[contracttype]
@@ -124,6 +126,15 @@ pub struct GrantApprovalAction {
     pub purpose: String,
 }
 
+/// Unlocks one tranche of a milestone grant created with this committee as
+/// its releasing committee.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantMilestoneAction {
+    pub grant_id: u64,
+    pub milestone_index: u32,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EmergencyActionPayload {
@@ -950,6 +961,18 @@ fn verify_committee_authority(
                     return Ok(());
                 }
             }
+            (Authority::GrantApproval(config), CommitteeAction::GrantMilestoneRelease(action)) => {
+                let grant = grants::get_grant(&get_grants_state(env), action.grant_id)?;
+                let milestone = grant
+                    .milestones
+                    .get(action.milestone_index)
+                    .ok_or(GovernanceError::InvalidCommitteeAction)?;
+                if grant.committee_id == Some(committee.id)
+                    && milestone.amount <= config.max_grant_size
+                {
+                    return Ok(());
+                }
+            }
             (
                 Authority::ParameterAdjustment(config),
                 CommitteeAction::RewardConfigUpdate(action),
@@ -1061,6 +1084,17 @@ fn execute_decision_action(
             update_reward_config(env, action.reward_bps, action.min_claim_threshold)?;
             Ok(())
         }
+        CommitteeAction::GrantMilestoneRelease(action) => {
+            let mut grants_state = get_grants_state(env);
+            grants::release_milestone(
+                env,
+                &mut grants_state,
+                action.grant_id,
+                action.milestone_index,
+            )?;
+            put_grants_state(env, &grants_state);
+            Ok(())
+        }
         CommitteeAction::EmergencyAction(action) => {
             if action.details.is_empty() {
                 Err(GovernanceError::InvalidCommitteeAction)
@@ -70,4 +70,68 @@ impl GovernanceError {
     pub const ContractPaused: GovernanceError = GovernanceError::Unauthorized;
     pub const InvalidCalibrationConfig: GovernanceError = GovernanceError::InvalidGovernanceConfig;
     pub const IterationLimitExceeded: GovernanceError = GovernanceError::InvalidCommitteeAction;
+    pub const RewardEpochNotFound: GovernanceError = GovernanceError::ActionNotFound;
+}
+
+/// Grant errors. `GovernanceError` is at the XDR variant cap, so grant
+/// entrypoints report through this enum; failures shared with the rest of the
+/// contract have a same-named variant here.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum GrantError {
+    NotInitialized = 100,
+    Unauthorized = 101,
+    InvalidAmount = 102,
+    InvalidDuration = 103,
+    ArithmeticOverflow = 104,
+    BudgetNotFound = 105,
+    BudgetExceeded = 106,
+    BudgetApprovalRequired = 107,
+    ApprovedCapExceeded = 108,
+    InsufficientBalance = 109,
+    NothingToRelease = 110,
+    /// Empty purpose, or zero / too many milestones.
+    InvalidGrantTerms = 111,
+    GrantNotFound = 112,
+    GrantNotActive = 113,
+    MilestoneNotFound = 114,
+    MilestoneAlreadyReleased = 115,
+    NotMilestoneGrant = 116,
+    CommitteeNotFound = 117,
+    ProposalNotFound = 118,
+    /// The proposal cited for a grant action has not passed.
+    ProposalNotApproved = 119,
+}
+
+impl From<GovernanceError> for GrantError {
+    fn from(err: GovernanceError) -> Self {
+        match err {
+            GovernanceError::NotInitialized => GrantError::NotInitialized,
+            GovernanceError::Unauthorized => GrantError::Unauthorized,
+            GovernanceError::InvalidAmount => GrantError::InvalidAmount,
+            GovernanceError::InvalidDuration => GrantError::InvalidDuration,
+            GovernanceError::ArithmeticOverflow => GrantError::ArithmeticOverflow,
+            GovernanceError::BudgetNotFound => GrantError::BudgetNotFound,
+            GovernanceError::BudgetExceeded => GrantError::BudgetExceeded,
+            GovernanceError::InsufficientBalance => GrantError::InsufficientBalance,
+            GovernanceError::NothingToRelease => GrantError::NothingToRelease,
+            GovernanceError::CommitteeNotFound => GrantError::CommitteeNotFound,
+            GovernanceError::ProposalNotFound => GrantError::ProposalNotFound,
+            GovernanceError::ProposalNotApproved => GrantError::ProposalNotApproved,
+            _ => GrantError::InvalidGrantTerms,
+        }
+    }
+}
+
+/// Committee decisions that touch grants report through `GovernanceError`.
+impl From<GrantError> for GovernanceError {
+    fn from(err: GrantError) -> Self {
+        match err {
+            GrantError::ArithmeticOverflow => GovernanceError::ArithmeticOverflow,
+            GrantError::NotInitialized => GovernanceError::NotInitialized,
+            GrantError::Unauthorized => GovernanceError::Unauthorized,
+            _ => GovernanceError::InvalidCommitteeAction,
+        }
+    }
 }
@@ -0,0 +1,686 @@
+//! Ecosystem grants funded from the governance treasury.
+//!
+//! Two grant shapes are supported:
+//! - **Streams** release `total_amount` linearly per second between
+//!   `start_time + cliff_seconds` and `start_time + duration_seconds`
+//!   (Sablier-style). The recipient may withdraw whatever has streamed at any
+//!   time.
+//! - **Milestone grants** split `total_amount` into tranches. Each tranche is
+//!   unlocked by executing a `CommitteeAction::GrantMilestoneRelease` decision
+//!   of the committee named on the grant, after which the recipient withdraws
+//!   it like a stream.
+//!
+//! The full grant amount is reserved from the treasury at creation, drawing
+//! down the category's governance-approved cap. Cancelling a grant claws every
+//! still-locked unit back into the treasury and returns it to the category's
+//! budget and approved cap; amounts already streamed or released stay
+//! withdrawable by the recipient.
+
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, String, Vec};
+use stellar_swipe_common::Asset;
+
+use crate::errors::GrantError;
+use crate::treasury::{Treasury, TreasurySpend};
+use crate::{checked_add, checked_div, checked_mul, checked_sub};
+
+/// Upper bound on tranches per milestone grant, keeping release/withdraw
+/// iteration within a single invocation's budget.
+pub const MAX_GRANT_MILESTONES: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GrantKind {
+    Stream,
+    Milestone,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GrantStatus {
+    Active,
+    /// Every unit has been withdrawn by the recipient.
+    Completed,
+    /// Cancelled by governance; locked funds were returned to the treasury.
+    Cancelled,
+}
+
+/// Caller-supplied terms for a single milestone tranche.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneTerms {
+    pub amount: i128,
+    pub description: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantMilestone {
+    pub amount: i128,
+    pub description: String,
+    /// Ledger timestamp at which the committee released this tranche.
+    pub released_at: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grant {
+    pub id: u64,
+    pub kind: GrantKind,
+    pub recipient: Address,
+    pub asset: Asset,
+    pub category: String,
+    pub purpose: String,
+    pub total_amount: i128,
+    /// Amount already paid out to the recipient.
+    pub withdrawn: i128,
+    /// Amount returned to the treasury on cancellation.
+    pub clawed_back: i128,
+    /// Stream start. For milestone grants this is the creation time.
+    pub start_time: u64,
+    pub cliff_seconds: u64,
+    pub duration_seconds: u64,
+    /// Committee whose decisions release milestone tranches.
+    pub committee_id: Option<u64>,
+    pub milestones: Vec<GrantMilestone>,
+    pub approved_by_proposal: Option<u64>,
+    pub status: GrantStatus,
+    pub created_at: u64,
+    pub cancelled_at: Option<u64>,
+    /// Unlocked amount frozen at cancellation; later accrual stops here.
+    pub unlocked_at_cancel: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GrantsState {
+    pub grants: Map<u64, Grant>,
+    pub grant_ids: Vec<u64>,
+    pub next_grant_id: u64,
+}
+
+pub fn empty_grants_state(env: &Env) -> GrantsState {
+    GrantsState {
+        grants: Map::new(env),
+        grant_ids: Vec::new(env),
+        next_grant_id: 1,
+    }
+}
+
+pub fn get_grant(state: &GrantsState, grant_id: u64) -> Result<Grant, GrantError> {
+    state.grants.get(grant_id).ok_or(GrantError::GrantNotFound)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_stream(
+    env: &Env,
+    state: &mut GrantsState,
+    treasury: &mut Treasury,
+    recipient: Address,
+    asset: Asset,
+    total_amount: i128,
+    start_time: u64,
+    cliff_seconds: u64,
+    duration_seconds: u64,
+    category: String,
+    purpose: String,
+    approved_by_proposal: Option<u64>,
+) -> Result<Grant, GrantError> {
+    if duration_seconds == 0 || cliff_seconds > duration_seconds {
+        return Err(GrantError::InvalidDuration);
+    }
+    if start_time.saturating_add(duration_seconds) <= env.ledger().timestamp() {
+        return Err(GrantError::InvalidDuration);
+    }
+
+    let grant = Grant {
+        id: state.next_grant_id,
+        kind: GrantKind::Stream,
+        recipient,
+        asset,
+        category,
+        purpose,
+        total_amount,
+        withdrawn: 0,
+        clawed_back: 0,
+        start_time,
+        cliff_seconds,
+        duration_seconds,
+        committee_id: None,
+        milestones: Vec::new(env),
+        approved_by_proposal,
+        status: GrantStatus::Active,
+        created_at: env.ledger().timestamp(),
+        cancelled_at: None,
+        unlocked_at_cancel: 0,
+    };
+    reserve_and_store(env, state, treasury, grant)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_milestone_grant(
+    env: &Env,
+    state: &mut GrantsState,
+    treasury: &mut Treasury,
+    recipient: Address,
+    asset: Asset,
+    committee_id: u64,
+    terms: Vec<MilestoneTerms>,
+    category: String,
+    purpose: String,
+    approved_by_proposal: Option<u64>,
+) -> Result<Grant, GrantError> {
+    if terms.is_empty() || terms.len() > MAX_GRANT_MILESTONES {
+        return Err(GrantError::InvalidGrantTerms);
+    }
+
+    let mut milestones = Vec::new(env);
+    let mut total_amount = 0i128;
+    let mut index = 0;
+    while index < terms.len() {
+        let term = terms.get(index).unwrap();
+        if term.amount <= 0 {
+            return Err(GrantError::InvalidAmount);
+        }
+        total_amount = checked_add(total_amount, term.amount)?;
+        milestones.push_back(GrantMilestone {
+            amount: term.amount,
+            description: term.description,
+            released_at: None,
+        });
+        index += 1;
+    }
+
+    let now = env.ledger().timestamp();
+    let grant = Grant {
+        id: state.next_grant_id,
+        kind: GrantKind::Milestone,
+        recipient,
+        asset,
+        category,
+        purpose,
+        total_amount,
+        withdrawn: 0,
+        clawed_back: 0,
+        start_time: now,
+        cliff_seconds: 0,
+        duration_seconds: 0,
+        committee_id: Some(committee_id),
+        milestones,
+        approved_by_proposal,
+        status: GrantStatus::Active,
+        created_at: now,
+        cancelled_at: None,
+        unlocked_at_cancel: 0,
+    };
+    reserve_and_store(env, state, treasury, grant)
+}
+
+/// Amount of the grant that has streamed or been released so far, including
+/// anything already withdrawn.
+pub fn unlocked_amount(grant: &Grant, now: u64) -> Result<i128, GrantError> {
+    if grant.status == GrantStatus::Cancelled {
+        return Ok(grant.unlocked_at_cancel);
+    }
+    match grant.kind {
+        GrantKind::Stream => {
+            let cliff_time = grant.start_time.saturating_add(grant.cliff_seconds);
+            let end_time = grant.start_time.saturating_add(grant.duration_seconds);
+            if now < cliff_time {
+                Ok(0)
+            } else if now >= end_time {
+                Ok(grant.total_amount)
+            } else {
+                let elapsed = now.saturating_sub(grant.start_time);
+                Ok(checked_div(
+                    checked_mul(grant.total_amount, elapsed as i128)?,
+                    grant.duration_seconds as i128,
+                )?)
+            }
+        }
+        GrantKind::Milestone => {
+            let mut released = 0i128;
+            let mut index = 0;
+            while index < grant.milestones.len() {
+                let milestone = grant.milestones.get(index).unwrap();
+                if milestone.released_at.is_some() {
+                    released = checked_add(released, milestone.amount)?;
+                }
+                index += 1;
+            }
+            Ok(released)
+        }
+    }
+}
+
+pub fn withdrawable_amount(grant: &Grant, now: u64) -> Result<i128, GrantError> {
+    Ok(checked_sub(unlocked_amount(grant, now)?, grant.withdrawn)?)
+}
+
+/// Pay everything currently withdrawable to the grant recipient. The payout
+/// is recorded in the treasury spending history so burn-rate reporting
+/// includes grant outflows.
+pub fn withdraw(
+    env: &Env,
+    state: &mut GrantsState,
+    treasury: &mut Treasury,
+    grant_id: u64,
+    recipient: &Address,
+) -> Result<i128, GrantError> {
+    let mut grant = get_grant(state, grant_id)?;
+    if grant.recipient != *recipient {
+        return Err(GrantError::Unauthorized);
+    }
+
+    let now = env.ledger().timestamp();
+    let amount = withdrawable_amount(&grant, now)?;
+    if amount <= 0 {
+        return Err(GrantError::NothingToRelease);
+    }
+
+    grant.withdrawn = checked_add(grant.withdrawn, amount)?;
+    if grant.status == GrantStatus::Active && grant.withdrawn == grant.total_amount {
+        grant.status = GrantStatus::Completed;
+    }
+
+    let spend = TreasurySpend {
+        id: treasury.next_spend_id,
+        recipient: recipient.clone(),
+        amount,
+        asset: grant.asset.clone(),
+        category: grant.category.clone(),
+        purpose: grant.purpose.clone(),
+        approved_by_proposal: grant.approved_by_proposal,
+        executed_at: now,
+    };
+    treasury.next_spend_id = treasury.next_spend_id.saturating_add(1);
+    treasury.spending_history.push_back(spend);
+
+    state.grants.set(grant_id, grant);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (symbol_short!("grant"), symbol_short!("withdraw")),
+        (grant_id, recipient.clone(), amount),
+    );
+    Ok(amount)
+}
+
+/// Unlock one milestone tranche. Called from committee decision execution
+/// once the grant's committee has approved the release.
+pub fn release_milestone(
+    env: &Env,
+    state: &mut GrantsState,
+    grant_id: u64,
+    milestone_index: u32,
+) -> Result<i128, GrantError> {
+    let mut grant = get_grant(state, grant_id)?;
+    if grant.kind != GrantKind::Milestone {
+        return Err(GrantError::NotMilestoneGrant);
+    }
+    if grant.status != GrantStatus::Active {
+        return Err(GrantError::GrantNotActive);
+    }
+    let mut milestone = grant
+        .milestones
+        .get(milestone_index)
+        .ok_or(GrantError::MilestoneNotFound)?;
+    if milestone.released_at.is_some() {
+        return Err(GrantError::MilestoneAlreadyReleased);
+    }
+
+    let now = env.ledger().timestamp();
+    milestone.released_at = Some(now);
+    let amount = milestone.amount;
+    grant.milestones.set(milestone_index, milestone);
+    state.grants.set(grant_id, grant);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (symbol_short!("grant"), symbol_short!("milestone")),
+        (grant_id, milestone_index, amount, now),
+    );
+    Ok(amount)
+}
+
+/// Cancel an active grant and return every still-locked unit to the treasury.
+/// Returns the clawed-back amount.
+pub fn cancel(
+    env: &Env,
+    state: &mut GrantsState,
+    treasury: &mut Treasury,
+    grant_id: u64,
+    proposal_id: Option<u64>,
+) -> Result<i128, GrantError> {
+    let mut grant = get_grant(state, grant_id)?;
+    if grant.status != GrantStatus::Active {
+        return Err(GrantError::GrantNotActive);
+    }
+
+    let now = env.ledger().timestamp();
+    let unlocked = unlocked_amount(&grant, now)?;
+    let clawed_back = checked_sub(grant.total_amount, unlocked)?;
+
+    if clawed_back > 0 {
+        let balance = treasury.assets.get(grant.asset.clone()).unwrap_or(0);
+        treasury
+            .assets
+            .set(grant.asset.clone(), checked_add(balance, clawed_back)?);
+        release_reservation(treasury, &grant, clawed_back)?;
+    }
+
+    grant.unlocked_at_cancel = unlocked;
+    grant.clawed_back = clawed_back;
+    grant.status = GrantStatus::Cancelled;
+    grant.cancelled_at = Some(now);
+    state.grants.set(grant_id, grant);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (symbol_short!("grant"), symbol_short!("cancel")),
+        (grant_id, clawed_back, proposal_id, now),
+    );
+    Ok(clawed_back)
+}
+
+/// Undo the budget and approved-cap draw for `amount` of a cancelled grant.
+/// Only the budget period and approval the grant was reserved under are
+/// credited; a renewed period or re-approved cap already started from zero.
+fn release_reservation(
+    treasury: &mut Treasury,
+    grant: &Grant,
+    amount: i128,
+) -> Result<(), GrantError> {
+    if let Some(mut budget) = treasury.budgets.get(grant.category.clone()) {
+        if budget.period_start <= grant.created_at {
+            budget.spent = checked_sub(budget.spent, amount)?.max(0);
+            budget.remaining = checked_add(budget.remaining, amount)?.min(budget.allocated);
+            treasury.budgets.set(grant.category.clone(), budget);
+        }
+    }
+    if let Some(mut approval) = treasury.approved_budgets.get(grant.category.clone()) {
+        if approval.approved_at <= grant.created_at {
+            approval.total_drawn = checked_sub(approval.total_drawn, amount)?.max(0);
+            treasury
+                .approved_budgets
+                .set(grant.category.clone(), approval);
+        }
+    }
+    Ok(())
+}
+
+pub fn list_grants(env: &Env, state: &GrantsState) -> Vec<Grant> {
+    let mut grants = Vec::new(env);
+    let mut index = 0;
+    while index < state.grant_ids.len() {
+        let grant_id = state.grant_ids.get(index).unwrap();
+        if let Some(grant) = state.grants.get(grant_id) {
+            grants.push_back(grant);
+        }
+        index += 1;
+    }
+    grants
+}
+
+/// Reserve `grant.total_amount` from the treasury against the category's
+/// budget and governance-approved cap, then persist the grant.
+fn reserve_and_store(
+    env: &Env,
+    state: &mut GrantsState,
+    treasury: &mut Treasury,
+    grant: Grant,
+) -> Result<Grant, GrantError> {
+    if grant.total_amount <= 0 {
+        return Err(GrantError::InvalidAmount);
+    }
+    if grant.purpose.is_empty() {
+        return Err(GrantError::InvalidGrantTerms);
+    }
+
+    let mut budget = treasury
+        .budgets
+        .get(grant.category.clone())
+        .ok_or(GrantError::BudgetNotFound)?;
+    if grant.total_amount > budget.remaining {
+        return Err(GrantError::BudgetExceeded);
+    }
+    let mut approval = treasury
+        .approved_budgets
+        .get(grant.category.clone())
+        .ok_or(GrantError::BudgetApprovalRequired)?;
+    let new_drawn = checked_add(approval.total_drawn, grant.total_amount)?;
+    if new_drawn > approval.approved_cap {
+        return Err(GrantError::ApprovedCapExceeded);
+    }
+    let balance = treasury.assets.get(grant.asset.clone()).unwrap_or(0);
+    if balance < grant.total_amount {
+        return Err(GrantError::InsufficientBalance);
+    }
+
+    approval.total_drawn = new_drawn;
+    treasury
+        .approved_budgets
+        .set(grant.category.clone(), approval);
+    budget.spent = checked_add(budget.spent, grant.total_amount)?;
+    budget.remaining = checked_sub(budget.remaining, grant.total_amount)?;
+    treasury.budgets.set(grant.category.clone(), budget);
+    treasury.assets.set(
+        grant.asset.clone(),
+        checked_sub(balance, grant.total_amount)?,
+    );
+
+    state.grants.set(grant.id, grant.clone());
+    state.grant_ids.push_back(grant.id);
+    state.next_grant_id = state.next_grant_id.saturating_add(1);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (symbol_short!("grant"), symbol_short!("create")),
+        (
+            grant.id,
+            grant.recipient.clone(),
+            grant.total_amount,
+            grant.category.clone(),
+            grant.approved_by_proposal,
+        ),
+    );
+    Ok(grant)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    use super::*;
+    use crate::treasury::{approve_budget, empty_treasury, set_asset_balance, upsert_budget};
+
+    fn usdc(env: &Env) -> Asset {
+        Asset {
+            code: String::from_str(env, "USDC"),
+            issuer: None,
+        }
+    }
+
+    fn funded_treasury(env: &Env, balance: i128, cap: i128) -> Treasury {
+        let mut treasury = empty_treasury(env);
+        set_asset_balance(env, &mut treasury, usdc(env), balance).unwrap();
+        upsert_budget(
+            env,
+            &mut treasury,
+            String::from_str(env, "grants"),
+            cap,
+            cap,
+            0,
+            1_000_000,
+            false,
+        )
+        .unwrap();
+        approve_budget(
+            env,
+            &mut treasury,
+            String::from_str(env, "grants"),
+            7,
+            cap,
+            0,
+        )
+        .unwrap();
+        treasury
+    }
+
+    fn stream(env: &Env, state: &mut GrantsState, treasury: &mut Treasury, to: &Address) -> Grant {
+        create_stream(
+            env,
+            state,
+            treasury,
+            to.clone(),
+            usdc(env),
+            1_000,
+            100,
+            10,
+            100,
+            String::from_str(env, "grants"),
+            String::from_str(env, "sdk work"),
+            Some(7),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn stream_reserves_funds_and_accrues_per_second() {
+        let env = Env::default();
+        env.ledger().set_timestamp(0);
+        let mut state = empty_grants_state(&env);
+        let mut treasury = funded_treasury(&env, 5_000, 2_000);
+        let recipient = Address::generate(&env);
+        let grant = stream(&env, &mut state, &mut treasury, &recipient);
+
+        assert_eq!(treasury.assets.get(usdc(&env)).unwrap(), 4_000);
+        let approval = treasury
+            .approved_budgets
+            .get(String::from_str(&env, "grants"))
+            .unwrap();
+        assert_eq!(approval.total_drawn, 1_000);
+
+        // Before the cliff nothing is withdrawable.
+        assert_eq!(withdrawable_amount(&grant, 105).unwrap(), 0);
+        // Past the cliff the full elapsed share is unlocked.
+        assert_eq!(withdrawable_amount(&grant, 125).unwrap(), 250);
+
+        env.ledger().set_timestamp(150);
+        let paid = withdraw(&env, &mut state, &mut treasury, grant.id, &recipient).unwrap();
+        assert_eq!(paid, 500);
+        assert_eq!(treasury.spending_history.len(), 1);
+
+        env.ledger().set_timestamp(500);
+        let rest = withdraw(&env, &mut state, &mut treasury, grant.id, &recipient).unwrap();
+        assert_eq!(rest, 500);
+        assert_eq!(
+            get_grant(&state, grant.id).unwrap().status,
+            GrantStatus::Completed
+        );
+    }
+
+    #[test]
+    fn cancel_claws_back_unstreamed_balance() {
+        let env = Env::default();
+        env.ledger().set_timestamp(0);
+        let mut state = empty_grants_state(&env);
+        let mut treasury = funded_treasury(&env, 5_000, 2_000);
+        let recipient = Address::generate(&env);
+        let grant = stream(&env, &mut state, &mut treasury, &recipient);
+
+        env.ledger().set_timestamp(140);
+        let clawed = cancel(&env, &mut state, &mut treasury, grant.id, Some(9)).unwrap();
+        assert_eq!(clawed, 600);
+        assert_eq!(treasury.assets.get(usdc(&env)).unwrap(), 4_600);
+        let category = String::from_str(&env, "grants");
+        let budget = treasury.budgets.get(category.clone()).unwrap();
+        assert_eq!(budget.spent, 400);
+        assert_eq!(budget.remaining, 1_600);
+        let approval = treasury.approved_budgets.get(category).unwrap();
+        assert_eq!(approval.total_drawn, 400);
+
+        // Streamed-but-unwithdrawn funds remain claimable, and accrual stops.
+        env.ledger().set_timestamp(10_000);
+        let paid = withdraw(&env, &mut state, &mut treasury, grant.id, &recipient).unwrap();
+        assert_eq!(paid, 400);
+        assert_eq!(
+            withdraw(&env, &mut state, &mut treasury, grant.id, &recipient),
+            Err(GrantError::NothingToRelease)
+        );
+        assert_eq!(
+            cancel(&env, &mut state, &mut treasury, grant.id, None),
+            Err(GrantError::GrantNotActive)
+        );
+    }
+
+    #[test]
+    fn milestone_tranches_unlock_individually() {
+        let env = Env::default();
+        env.ledger().set_timestamp(0);
+        let mut state = empty_grants_state(&env);
+        let mut treasury = funded_treasury(&env, 5_000, 2_000);
+        let recipient = Address::generate(&env);
+        let terms = soroban_sdk::vec![
+            &env,
+            MilestoneTerms {
+                amount: 300,
+                description: String::from_str(&env, "alpha"),
+            },
+            MilestoneTerms {
+                amount: 700,
+                description: String::from_str(&env, "mainnet"),
+            }
+        ];
+        let grant = create_milestone_grant(
+            &env,
+            &mut state,
+            &mut treasury,
+            recipient.clone(),
+            usdc(&env),
+            1,
+            terms,
+            String::from_str(&env, "grants"),
+            String::from_str(&env, "wallet"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(grant.total_amount, 1_000);
+        assert_eq!(withdrawable_amount(&grant, 1_000).unwrap(), 0);
+
+        release_milestone(&env, &mut state, grant.id, 0).unwrap();
+        assert_eq!(
+            release_milestone(&env, &mut state, grant.id, 0),
+            Err(GrantError::MilestoneAlreadyReleased)
+        );
+        let paid = withdraw(&env, &mut state, &mut treasury, grant.id, &recipient).unwrap();
+        assert_eq!(paid, 300);
+
+        let clawed = cancel(&env, &mut state, &mut treasury, grant.id, None).unwrap();
+        assert_eq!(clawed, 700);
+        assert_eq!(treasury.assets.get(usdc(&env)).unwrap(), 4_700);
+    }
+
+    #[test]
+    fn grant_above_approved_cap_is_rejected() {
+        let env = Env::default();
+        let mut state = empty_grants_state(&env);
+        let mut treasury = funded_treasury(&env, 5_000, 500);
+        let result = create_stream(
+            &env,
+            &mut state,
+            &mut treasury,
+            Address::generate(&env),
+            usdc(&env),
+            1_000,
+            0,
+            0,
+            100,
+            String::from_str(&env, "grants"),
+            String::from_str(&env, "too big"),
+            None,
+        );
+        assert_eq!(result, Err(GrantError::BudgetExceeded));
+    }
+}
@@ -5,11 +5,13 @@ mod committees;
 mod conviction_voting;
 mod distribution;
 mod errors;
+mod grants;
 mod proposal_deposit;
 mod proposals;
 mod quadratic_voting;
 mod reputation;
 mod shadow_mode;
+mod staking_rewards;
 mod timelock;
 mod token;
 mod treasury;
@@ -39,8 +41,9 @@ pub use committees::{
     Authority, Committee, CommitteeDecision, CrossCommitteeStatus, DecisionStatus,
     ElectionResult as CommitteeElectionResult, ElectionStatus as CommitteeElectionStatus,
     EmergencyActionAuthority, EmergencyActionPayload, GrantApprovalAction, GrantApprovalAuthority,
-    ParameterAdjustmentAuthority, PerformanceMetrics, RewardConfigUpdateAction,
-    TreasurySpendAction, TreasurySpendAuthority, VetoAuthority, VetoPayload,
+    GrantMilestoneAction, ParameterAdjustmentAuthority, PerformanceMetrics,
+    RewardConfigUpdateAction, TreasurySpendAction, TreasurySpendAuthority, VetoAuthority,
+    VetoPayload,
 };
 use conviction_voting::{
     analyze_conviction_proposal, change_conviction_vote, create_conviction_pool,
@@ -55,7 +58,9 @@ use distribution::{
     releasable_amount, release_vested_tokens as release_schedule_tokens, update_reward_config,
     DistributionRecipients, DistributionState, VestingCategory, VestingSchedule,
 };
-pub use errors::GovernanceError;
+pub use errors::{GovernanceError, GrantError};
+use grants::GrantsState;
+pub use grants::{Grant, GrantKind, GrantMilestone, GrantStatus, MilestoneTerms};
 pub use proposals::GovernanceConfig;
 use proposals::{
     calculate_proposal_statistics, cancel_proposal, configure_governance, create_proposal,
@@ -81,6 +86,7 @@ use soroban_sdk::{
     contract, contractimpl, contracttype, symbol_short, Address, Bytes, Env, Map, String, Symbol,
     Vec,
 };
+pub use staking_rewards::StakingRewardEpoch;
 use stellar_swipe_common::Asset;
 use timelock::{
     cancel_queued_action, emergency_execute, emergency_unblock_action, execute_multiple_actions,
@@ -140,6 +146,8 @@ pub enum StorageKey {
     TreasuryAddress,
     /// Shadow-mode canary upgrade trial state (issue #589).
     ShadowMode,
+    /// Streaming and milestone grants funded from the treasury.
+    Grants,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -979,6 +987,78 @@ impl GovernanceContract {
         Ok(())
     }
 
+    // ── Protocol-fee staking rewards ─────────────────────────────────────────
+
+    /// Admin-only: set the contract allowed to deposit staking rewards
+    /// (normally the FeeCollector).
+    pub fn set_staking_reward_distributor(
+        env: Env,
+        admin: Address,
+        distributor: Address,
+    ) -> Result<(), GovernanceError> {
+        require_admin(&env, &admin)?;
+        staking_rewards::set_distributor(&env, &distributor);
+        emit_admin_action(&env, symbol_short!("rwddist"), &distributor, 0);
+        Ok(())
+    }
+
+    /// Distributor-only: record `amount` of `token`, already transferred to
+    /// this contract, as rewards spread pro-rata over the current stakers.
+    ///
+    /// `source_epoch` is the distributor's own epoch reference and is stored
+    /// on the returned record for auditing.
+    ///
+    /// # Errors
+    /// - [`GovernanceError::Unauthorized`] — caller is not the configured distributor.
+    /// - [`GovernanceError::InsufficientStakedBalance`] — nothing is staked.
+    pub fn deposit_staking_rewards(
+        env: Env,
+        distributor: Address,
+        token: Address,
+        amount: i128,
+        source_epoch: u64,
+    ) -> Result<u64, GovernanceError> {
+        require_initialized(&env)?;
+        distributor.require_auth();
+        if staking_rewards::get_distributor(&env) != Some(distributor) {
+            return Err(GovernanceError::Unauthorized);
+        }
+        let epoch =
+            staking_rewards::deposit(&env, &staked_balances(&env), &token, amount, source_epoch)?;
+        Ok(epoch.epoch_id)
+    }
+
+    pub fn staking_rewards_claimable(
+        env: Env,
+        holder: Address,
+        token: Address,
+    ) -> Result<i128, GovernanceError> {
+        require_initialized(&env)?;
+        staking_rewards::claimable(&env, &holder, &token)
+    }
+
+    pub fn claim_staking_rewards(
+        env: Env,
+        holder: Address,
+        token: Address,
+    ) -> Result<i128, GovernanceError> {
+        require_initialized(&env)?;
+        holder.require_auth();
+        staking_rewards::claim(&env, &holder, &token)
+    }
+
+    pub fn staking_reward_epoch(
+        env: Env,
+        epoch_id: u64,
+    ) -> Result<StakingRewardEpoch, GovernanceError> {
+        require_initialized(&env)?;
+        staking_rewards::get_epoch(&env, epoch_id)
+    }
+
+    pub fn staking_reward_epoch_count(env: Env) -> u64 {
+        staking_rewards::epoch_count(&env)
+    }
+
     pub fn set_vote_lock(
         env: Env,
         admin: Address,
@@ -1200,6 +1280,164 @@ impl GovernanceContract {
         Ok(processed)
     }
 
+    /// Create a per-second streaming grant funded from the treasury.
+    ///
+    /// `total_amount` is reserved immediately against the `category` budget and
+    /// its governance-approved cap. Nothing is withdrawable before
+    /// `start_time + cliff_seconds`; afterwards the recipient may withdraw the
+    /// linearly streamed share at any time via `withdraw_from_grant`.
+    ///
+    /// # Errors
+    /// - [`GrantError::InvalidDuration`] — zero duration, cliff longer
+    ///   than the stream, or a stream that has already ended.
+    /// - [`GrantError::BudgetApprovalRequired`] — category has no approved cap.
+    /// - [`GrantError::ApprovedCapExceeded`] — grant exceeds the approved cap.
+    /// - [`GrantError::InsufficientBalance`] — treasury cannot fund the grant.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_grant(
+        env: Env,
+        admin: Address,
+        recipient: Address,
+        asset: Asset,
+        total_amount: i128,
+        start_time: u64,
+        cliff_seconds: u64,
+        duration_seconds: u64,
+        category: String,
+        purpose: String,
+        approved_by_proposal: Option<u64>,
+    ) -> Result<Grant, GrantError> {
+        require_admin(&env, &admin)?;
+        let mut treasury = get_treasury(&env);
+        let mut grants_state = get_grants_state(&env);
+        let grant = grants::create_stream(
+            &env,
+            &mut grants_state,
+            &mut treasury,
+            recipient,
+            asset,
+            total_amount,
+            start_time,
+            cliff_seconds,
+            duration_seconds,
+            category,
+            purpose,
+            approved_by_proposal,
+        )?;
+        put_treasury(&env, &treasury);
+        put_grants_state(&env, &grants_state);
+        emit_admin_action(&env, symbol_short!("stream"), &admin, total_amount);
+        Ok(grant)
+    }
+
+    /// Create a milestone grant whose tranches are released by executing
+    /// `CommitteeAction::GrantMilestoneRelease` decisions of `committee_id`.
+    ///
+    /// The sum of all tranches is reserved from the treasury up front, exactly
+    /// as for streams.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_milestone_grant(
+        env: Env,
+        admin: Address,
+        recipient: Address,
+        asset: Asset,
+        committee_id: u64,
+        milestones: Vec<MilestoneTerms>,
+        category: String,
+        purpose: String,
+        approved_by_proposal: Option<u64>,
+    ) -> Result<Grant, GrantError> {
+        require_admin(&env, &admin)?;
+        committees::get_committee(&get_committees_state(&env), committee_id)?;
+        let mut treasury = get_treasury(&env);
+        let mut grants_state = get_grants_state(&env);
+        let grant = grants::create_milestone_grant(
+            &env,
+            &mut grants_state,
+            &mut treasury,
+            recipient,
+            asset,
+            committee_id,
+            milestones,
+            category,
+            purpose,
+            approved_by_proposal,
+        )?;
+        put_treasury(&env, &treasury);
+        put_grants_state(&env, &grants_state);
+        emit_admin_action(&env, symbol_short!("msgrant"), &admin, grant.total_amount);
+        Ok(grant)
+    }
+
+    /// Recipient-only: withdraw everything streamed or released so far.
+    pub fn withdraw_from_grant(
+        env: Env,
+        recipient: Address,
+        grant_id: u64,
+    ) -> Result<i128, GrantError> {
+        require_initialized(&env)?;
+        recipient.require_auth();
+        let mut treasury = get_treasury(&env);
+        let mut grants_state = get_grants_state(&env);
+        let amount =
+            grants::withdraw(&env, &mut grants_state, &mut treasury, grant_id, &recipient)?;
+        put_treasury(&env, &treasury);
+        put_grants_state(&env, &grants_state);
+        Ok(amount)
+    }
+
+    /// Cancel a grant on behalf of governance, clawing every still-locked unit
+    /// back into the treasury and the category budget. Amounts already
+    /// unlocked remain withdrawable. A cited `proposal_id` must have passed.
+    ///
+    /// Returns the clawed-back amount.
+    pub fn cancel_grant(
+        env: Env,
+        admin: Address,
+        grant_id: u64,
+        proposal_id: Option<u64>,
+    ) -> Result<i128, GrantError> {
+        require_admin(&env, &admin)?;
+        if let Some(proposal_id) = proposal_id {
+            let proposal = get_proposal(&env, proposal_id)?;
+            if !matches!(
+                proposal.status,
+                ProposalStatus::Succeeded | ProposalStatus::Executed
+            ) {
+                return Err(GrantError::ProposalNotApproved);
+            }
+        }
+        let mut treasury = get_treasury(&env);
+        let mut grants_state = get_grants_state(&env);
+        let clawed_back = grants::cancel(
+            &env,
+            &mut grants_state,
+            &mut treasury,
+            grant_id,
+            proposal_id,
+        )?;
+        put_treasury(&env, &treasury);
+        put_grants_state(&env, &grants_state);
+        emit_admin_action(&env, symbol_short!("grantcncl"), &admin, clawed_back);
+        Ok(clawed_back)
+    }
+
+    pub fn grant(env: Env, grant_id: u64) -> Result<Grant, GrantError> {
+        require_initialized(&env)?;
+        grants::get_grant(&get_grants_state(&env), grant_id)
+    }
+
+    pub fn grants(env: Env) -> Result<Vec<Grant>, GrantError> {
+        require_initialized(&env)?;
+        Ok(grants::list_grants(&env, &get_grants_state(&env)))
+    }
+
+    pub fn grant_withdrawable(env: Env, grant_id: u64) -> Result<i128, GrantError> {
+        require_initialized(&env)?;
+        let grant = grants::get_grant(&get_grants_state(&env), grant_id)?;
+        grants::withdrawable_amount(&grant, env.ledger().timestamp())
+    }
+
     pub fn treasury_report(env: Env) -> Result<TreasuryReport, GovernanceError> {
         require_initialized(&env)?;
         treasury::build_report(&env, &get_treasury(&env))
@@ -1702,6 +1940,7 @@ pub(crate) fn add_staked_balance(
     if amount <= 0 {
         return Err(GovernanceError::InvalidAmount);
     }
+    staking_rewards::checkpoint(env, holder)?;
     let mut map = staked_balances(env);
     let current = map.get(holder.clone()).unwrap_or(0);
     map.set(holder.clone(), checked_add(current, amount)?);
@@ -1723,6 +1962,7 @@ pub(crate) fn subtract_staked_balance(
     if current < amount {
         return Err(GovernanceError::InsufficientStakedBalance);
     }
+    staking_rewards::checkpoint(env, holder)?;
     map.set(holder.clone(), checked_sub(current, amount)?);
     put_staked_balances(env, &map);
     track_holder(env, holder);
@@ -1798,6 +2038,19 @@ pub(crate) fn put_treasury(env: &Env, treasury_state: &Treasury) {
         .set(&StorageKey::Treasury, treasury_state);
 }
 
+pub(crate) fn get_grants_state(env: &Env) -> GrantsState {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::Grants)
+        .unwrap_or_else(|| grants::empty_grants_state(env))
+}
+
+pub(crate) fn put_grants_state(env: &Env, grants_state: &GrantsState) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::Grants, grants_state);
+}
+
 pub(crate) fn get_committees_state(env: &Env) -> CommitteesState {
     env.storage()
         .instance()
@@ -0,0 +1,250 @@
+//! Protocol-fee rewards for governance stakers.
+//!
+//! The FeeCollector forwards the staker share of each fee epoch to this
+//! contract and then calls `deposit_staking_rewards`. Rewards are spread
+//! pro-rata over the staked supply at deposit time using a cumulative
+//! reward-per-share accumulator per reward token:
+//!
+//! - each deposit raises `RewardPerShare(token)` by `amount * PRECISION / total_staked`;
+//! - every stake/unstake first checkpoints the holder, moving
+//!   `staked * (acc - paid_acc) / PRECISION` into their claimable balance.
+//!
+//! Every deposit is kept as a [`StakingRewardEpoch`] so payouts can be audited
+//! against the FeeCollector's own per-epoch distribution records.
+
+use soroban_sdk::{contracttype, symbol_short, token, Address, Env, Map, Vec};
+
+use crate::errors::GovernanceError;
+use crate::{checked_add, checked_div, checked_mul, checked_sub, get_staked_balance};
+
+/// Fixed-point scale for the reward-per-share accumulator.
+pub const REWARD_PRECISION: i128 = 1_000_000_000_000;
+
+/// Upper bound on distinct reward tokens, bounding checkpoint cost on
+/// every stake/unstake.
+pub const MAX_REWARD_TOKENS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum StakingRewardKey {
+    /// Address allowed to deposit rewards (the FeeCollector).
+    Distributor,
+    /// Reward tokens ever deposited: Vec<Address>.
+    RewardTokens,
+    /// Cumulative reward per staked unit, scaled by `REWARD_PRECISION`.
+    RewardPerShare(Address),
+    /// Accumulator value at the holder's last checkpoint.
+    PaidPerShare(Address, Address),
+    /// Rewards settled to the holder but not yet claimed.
+    Claimable(Address, Address),
+    /// Number of deposits recorded so far.
+    EpochCount,
+    /// Per-deposit audit record.
+    Epoch(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StakingRewardEpoch {
+    pub epoch_id: u64,
+    pub token: Address,
+    pub amount: i128,
+    /// Total staked supply the deposit was spread over.
+    pub total_staked: i128,
+    /// Increase of the reward-per-share accumulator from this deposit.
+    pub reward_per_share_delta: i128,
+    /// Distributor-side reference (the FeeCollector's distribution epoch).
+    pub source_epoch: u64,
+    pub deposited_at: u64,
+}
+
+pub fn get_distributor(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&StakingRewardKey::Distributor)
+}
+
+pub fn set_distributor(env: &Env, distributor: &Address) {
+    env.storage()
+        .instance()
+        .set(&StakingRewardKey::Distributor, distributor);
+}
+
+pub fn reward_tokens(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&StakingRewardKey::RewardTokens)
+        .unwrap_or(Vec::new(env))
+}
+
+fn reward_per_share(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&StakingRewardKey::RewardPerShare(token.clone()))
+        .unwrap_or(0)
+}
+
+fn paid_per_share(env: &Env, holder: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&StakingRewardKey::PaidPerShare(
+            holder.clone(),
+            token.clone(),
+        ))
+        .unwrap_or(0)
+}
+
+fn stored_claimable(env: &Env, holder: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&StakingRewardKey::Claimable(holder.clone(), token.clone()))
+        .unwrap_or(0)
+}
+
+fn accrued_since_checkpoint(
+    env: &Env,
+    holder: &Address,
+    token: &Address,
+    staked: i128,
+) -> Result<i128, GovernanceError> {
+    let delta = checked_sub(
+        reward_per_share(env, token),
+        paid_per_share(env, holder, token),
+    )?;
+    if staked <= 0 || delta <= 0 {
+        return Ok(0);
+    }
+    checked_div(checked_mul(staked, delta)?, REWARD_PRECISION)
+}
+
+/// Settle `holder`'s accrued rewards for every reward token. Must run before
+/// the holder's staked balance changes.
+pub fn checkpoint(env: &Env, holder: &Address) -> Result<(), GovernanceError> {
+    let staked = get_staked_balance(env, holder);
+    let tokens = reward_tokens(env);
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens.get(index).unwrap();
+        let accrued = accrued_since_checkpoint(env, holder, &token, staked)?;
+        if accrued > 0 {
+            let claimable = checked_add(stored_claimable(env, holder, &token), accrued)?;
+            env.storage().persistent().set(
+                &StakingRewardKey::Claimable(holder.clone(), token.clone()),
+                &claimable,
+            );
+        }
+        env.storage().persistent().set(
+            &StakingRewardKey::PaidPerShare(holder.clone(), token.clone()),
+            &reward_per_share(env, &token),
+        );
+        index += 1;
+    }
+    Ok(())
+}
+
+/// Claimable rewards for `holder` in `token`, including accrual since the
+/// last checkpoint.
+pub fn claimable(env: &Env, holder: &Address, token: &Address) -> Result<i128, GovernanceError> {
+    let accrued = accrued_since_checkpoint(env, holder, token, get_staked_balance(env, holder))?;
+    checked_add(stored_claimable(env, holder, token), accrued)
+}
+
+/// Record a reward deposit already transferred to this contract and spread it
+/// over the current staked supply.
+pub fn deposit(
+    env: &Env,
+    staked_balances: &Map<Address, i128>,
+    token: &Address,
+    amount: i128,
+    source_epoch: u64,
+) -> Result<StakingRewardEpoch, GovernanceError> {
+    if amount <= 0 {
+        return Err(GovernanceError::InvalidAmount);
+    }
+
+    let mut total_staked = 0i128;
+    for (_, staked) in staked_balances.iter() {
+        total_staked = checked_add(total_staked, staked)?;
+    }
+    if total_staked <= 0 {
+        return Err(GovernanceError::InsufficientStakedBalance);
+    }
+
+    let mut tokens = reward_tokens(env);
+    if !tokens.contains(token) {
+        if tokens.len() >= MAX_REWARD_TOKENS {
+            return Err(GovernanceError::IterationLimitExceeded);
+        }
+        tokens.push_back(token.clone());
+        env.storage()
+            .persistent()
+            .set(&StakingRewardKey::RewardTokens, &tokens);
+    }
+
+    let delta = checked_div(checked_mul(amount, REWARD_PRECISION)?, total_staked)?;
+    let updated = checked_add(reward_per_share(env, token), delta)?;
+    env.storage()
+        .persistent()
+        .set(&StakingRewardKey::RewardPerShare(token.clone()), &updated);
+
+    let epoch_id: u64 = env
+        .storage()
+        .persistent()
+        .get(&StakingRewardKey::EpochCount)
+        .unwrap_or(0u64)
+        .saturating_add(1);
+    let epoch = StakingRewardEpoch {
+        epoch_id,
+        token: token.clone(),
+        amount,
+        total_staked,
+        reward_per_share_delta: delta,
+        source_epoch,
+        deposited_at: env.ledger().timestamp(),
+    };
+    env.storage()
+        .persistent()
+        .set(&StakingRewardKey::EpochCount, &epoch_id);
+    env.storage()
+        .persistent()
+        .set(&StakingRewardKey::Epoch(epoch_id), &epoch);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (symbol_short!("gov"), symbol_short!("stkrwd")),
+        (epoch_id, token.clone(), amount, total_staked, source_epoch),
+    );
+    Ok(epoch)
+}
+
+pub fn get_epoch(env: &Env, epoch_id: u64) -> Result<StakingRewardEpoch, GovernanceError> {
+    env.storage()
+        .persistent()
+        .get(&StakingRewardKey::Epoch(epoch_id))
+        .ok_or(GovernanceError::RewardEpochNotFound)
+}
+
+pub fn epoch_count(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&StakingRewardKey::EpochCount)
+        .unwrap_or(0)
+}
+
+/// Settle and pay out everything `holder` has earned in `token`.
+pub fn claim(env: &Env, holder: &Address, token: &Address) -> Result<i128, GovernanceError> {
+    checkpoint(env, holder)?;
+    let amount = stored_claimable(env, holder, token);
+    if amount <= 0 {
+        return Err(GovernanceError::NothingToRelease);
+    }
+    env.storage()
+        .persistent()
+        .remove(&StakingRewardKey::Claimable(holder.clone(), token.clone()));
+    token::Client::new(env, token).transfer(&env.current_contract_address(), holder, &amount);
+
+    #[allow(deprecated)]
+    env.events().publish(
+        (symbol_short!("gov"), symbol_short!("stkclaim")),
+        (holder.clone(), token.clone(), amount),
+    );
+    Ok(amount)
+}
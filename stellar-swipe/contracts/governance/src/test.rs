@@ -10,7 +10,8 @@ use crate::proposals::{
 use crate::{
     Authority, CommitteeAction, CommitteeElectionStatus, CrossCommitteeStatus, DecisionStatus,
     EmergencyActionAuthority, EmergencyActionPayload, GovernanceContract, GovernanceContractClient,
-    GovernanceError, ParameterAdjustmentAuthority, ReputationConfig, ReputationTier,
+    GovernanceError, GrantApprovalAuthority, GrantError, GrantMilestoneAction, GrantStatus,
+    MilestoneTerms, ParameterAdjustmentAuthority, ReputationConfig, ReputationTier,
     RewardConfigUpdateAction, StalenessLevel, TreasurySpendAction, TreasurySpendAuthority,
     VoteType,
 };
@@ -558,6 +559,203 @@ fn committee_executes_delegated_treasury_spend_and_reports_metrics() {
     assert!(report.days_active >= 30);
 }
 
+#[test]
+fn fee_rewards_are_split_pro_rata_across_stakers() {
+    let (env, contract_id, admin, recipients) = setup();
+    let client = client(&env, &contract_id);
+    initialize(&client, &env, &admin, &recipients);
+
+    let distributor = Address::generate(&env);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let reward_admin = soroban_sdk::token::StellarAssetClient::new(&env, &reward_token);
+    let reward_client = soroban_sdk::token::Client::new(&env, &reward_token);
+    client.set_staking_reward_distributor(&admin, &distributor);
+
+    let alice = recipients.community_rewards.clone();
+    let bob = recipients.public_sale.clone();
+    client.stake(&alice, &3_000);
+    client.stake(&bob, &1_000);
+
+    reward_admin.mint(&contract_id, &4_000);
+    let epoch_id = client.deposit_staking_rewards(&distributor, &reward_token, &4_000, &7u64);
+    let epoch = client.staking_reward_epoch(&epoch_id);
+    assert_eq!(epoch.total_staked, 4_000);
+    assert_eq!(epoch.source_epoch, 7);
+
+    assert_eq!(
+        client.staking_rewards_claimable(&alice, &reward_token),
+        3_000
+    );
+    assert_eq!(client.staking_rewards_claimable(&bob, &reward_token), 1_000);
+
+    // Bob doubles his stake; rewards earned so far stay with the old weights.
+    client.stake(&bob, &2_000);
+    reward_admin.mint(&contract_id, &1_200);
+    client.deposit_staking_rewards(&distributor, &reward_token, &1_200, &8u64);
+    assert_eq!(
+        client.staking_rewards_claimable(&alice, &reward_token),
+        3_600
+    );
+    assert_eq!(client.staking_rewards_claimable(&bob, &reward_token), 1_600);
+
+    assert_eq!(client.claim_staking_rewards(&bob, &reward_token), 1_600);
+    assert_eq!(reward_client.balance(&bob), 1_600);
+    assert_eq!(
+        client.try_claim_staking_rewards(&bob, &reward_token),
+        Err(Ok(GovernanceError::NothingToRelease))
+    );
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_deposit_staking_rewards(&stranger, &reward_token, &1, &9u64),
+        Err(Ok(GovernanceError::Unauthorized))
+    );
+}
+
+#[test]
+fn committee_releases_milestone_grant_tranches() {
+    let (env, contract_id, admin, recipients) = setup();
+    let client = client(&env, &contract_id);
+    initialize(&client, &env, &admin, &recipients);
+
+    let usdc = asset(&env, "USDC");
+    let category = String::from_str(&env, "ecosystem");
+    client.set_treasury_asset(&admin, &usdc, &50_000i128);
+    client.create_budget(
+        &admin,
+        &category,
+        &30_000i128,
+        &30_000i128,
+        &0u64,
+        &(365u64 * 86_400),
+        &false,
+    );
+    client.approve_treasury_budget(&admin, &category, &1u64, &30_000i128);
+
+    let committee_members = members(&env, 5);
+    let chair = committee_members.get(0).unwrap();
+    let committee = client.create_committee(
+        &admin,
+        &String::from_str(&env, "Grants Committee"),
+        &String::from_str(&env, "Releases ecosystem grant milestones"),
+        &committee_members,
+        &chair,
+        &5u32,
+        &soroban_sdk::vec![
+            &env,
+            Authority::GrantApproval(GrantApprovalAuthority {
+                max_grant_size: 10_000,
+            })
+        ],
+        &Some(90u32),
+    );
+
+    let builder = Address::generate(&env);
+    let grant = client.create_milestone_grant(
+        &admin,
+        &builder,
+        &usdc,
+        &committee.id,
+        &soroban_sdk::vec![
+            &env,
+            MilestoneTerms {
+                amount: 4_000,
+                description: String::from_str(&env, "testnet launch"),
+            },
+            MilestoneTerms {
+                amount: 6_000,
+                description: String::from_str(&env, "mainnet launch"),
+            }
+        ],
+        &category,
+        &String::from_str(&env, "indexer"),
+        &Some(1u64),
+    );
+    assert_eq!(client.treasury().assets.get(usdc.clone()).unwrap(), 40_000);
+    assert_eq!(client.grant_withdrawable(&grant.id), 0);
+
+    let decision = client.propose_committee_decision(
+        &committee.id,
+        &chair,
+        &String::from_str(&env, "Testnet shipped"),
+        &CommitteeAction::GrantMilestoneRelease(GrantMilestoneAction {
+            grant_id: grant.id,
+            milestone_index: 0,
+        }),
+    );
+    let mut index = 0;
+    while index < 3 {
+        client.vote_on_committee_decision(
+            &committee.id,
+            &decision.decision_id,
+            &committee_members.get(index).unwrap(),
+            &VoteType::For,
+        );
+        index += 1;
+    }
+    client.execute_committee_decision(&committee.id, &decision.decision_id, &chair);
+
+    assert_eq!(client.grant_withdrawable(&grant.id), 4_000);
+    assert_eq!(client.withdraw_from_grant(&builder, &grant.id), 4_000);
+
+    // Governance cancels the grant: the unreleased tranche returns to the treasury.
+    assert_eq!(
+        client.try_cancel_grant(&admin, &grant.id, &Some(99u64)),
+        Err(Ok(GrantError::ProposalNotFound))
+    );
+    assert_eq!(client.cancel_grant(&admin, &grant.id, &None), 6_000);
+    assert_eq!(client.treasury().assets.get(usdc).unwrap(), 46_000);
+    assert_eq!(client.grant(&grant.id).status, GrantStatus::Cancelled);
+}
+
+#[test]
+fn stream_grant_is_withdrawable_as_it_accrues() {
+    let (env, contract_id, admin, recipients) = setup();
+    let client = client(&env, &contract_id);
+    initialize(&client, &env, &admin, &recipients);
+
+    let usdc = asset(&env, "USDC");
+    let category = String::from_str(&env, "ecosystem");
+    client.set_treasury_asset(&admin, &usdc, &10_000i128);
+    client.create_budget(
+        &admin,
+        &category,
+        &10_000i128,
+        &10_000i128,
+        &0u64,
+        &(365u64 * 86_400),
+        &false,
+    );
+    client.approve_treasury_budget(&admin, &category, &1u64, &10_000i128);
+
+    let builder = Address::generate(&env);
+    let grant = client.create_stream_grant(
+        &admin,
+        &builder,
+        &usdc,
+        &8_640i128,
+        &0u64,
+        &0u64,
+        &86_400u64,
+        &category,
+        &String::from_str(&env, "docs"),
+        &None,
+    );
+
+    env.ledger().set_timestamp(3_600);
+    assert_eq!(client.withdraw_from_grant(&builder, &grant.id), 360);
+    env.ledger().set_timestamp(7_200);
+    assert_eq!(client.grant_withdrawable(&grant.id), 360);
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_withdraw_from_grant(&stranger, &grant.id),
+        Err(Ok(GrantError::Unauthorized))
+    );
+}
+
 #[test]
 fn committee_can_adjust_reward_config_within_delegated_limits() {
     let (env, contract_id, admin, recipients) = setup();
@@ -2399,4 +2597,3 @@ fn test_reputation_leaderboard_limit() {
     let res = client.try_reputation_leaderboard(&10);
     assert_eq!(res, Err(Ok(GovernanceError::IterationLimitExceeded)));
 }
-
@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use fee_collector::{FeeCollector, FeeCollectorClient, InsuranceConfig};
+use soroban_sdk::{
+    testutils::Address as _,
+    token::{Client as TokenClient, StellarAssetClient},
+    Address, Env, Symbol,
+};
+use stake_vault::{SlashSeverity, StakeVaultContract, StakeVaultContractClient};
+
+struct Vault<'a> {
+    client: StakeVaultContractClient<'a>,
+    id: Address,
+    token: Address,
+    registry: Address,
+    provider: Address,
+}
+
+fn setup_vault(env: &Env) -> Vault<'_> {
+    let admin = Address::generate(env);
+    let registry = Address::generate(env);
+    let token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let id = env.register(StakeVaultContract, ());
+    let client = StakeVaultContractClient::new(env, &id);
+    client.initialize(&admin, &token, &registry);
+
+    let provider = Address::generate(env);
+    StellarAssetClient::new(env, &token).mint(&provider, &1_000_000);
+    client.deposit_stake(&provider, &1_000_000);
+    Vault {
+        client,
+        id,
+        token,
+        registry,
+        provider,
+    }
+}
+
+fn setup_fee_collector<'a>(env: &'a Env, stake_vault: &Address) -> FeeCollectorClient<'a> {
+    let client = FeeCollectorClient::new(env, &env.register(FeeCollector, ()));
+    client.initialize(&Address::generate(env));
+    client.set_insurance_config(&InsuranceConfig {
+        signal_registry: Address::generate(env),
+        stake_vault: stake_vault.clone(),
+        committee: Address::generate(env),
+        fee_share_bps: 0,
+        coverage_bps: 5_000,
+        max_payout_per_claim: 1_000,
+        max_payout_per_epoch: 1_000,
+        epoch_seconds: 86_400,
+        committee_threshold: 0,
+    });
+    client
+}
+
+#[test]
+fn test_slash_is_booked_into_insurance_fund() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let vault = setup_vault(&env);
+    let fee_collector = setup_fee_collector(&env, &vault.id);
+    vault.client.set_insurance_fund(&fee_collector.address);
+
+    let slashed = vault.client.slash_stake(
+        &vault.registry,
+        &vault.provider,
+        &SlashSeverity::Major,
+        &Symbol::new(&env, "fraud"),
+    );
+    assert_eq!(slashed, 300_000);
+    assert_eq!(fee_collector.insurance_fund_balance(&vault.token), 300_000);
+    let token = TokenClient::new(&env, &vault.token);
+    assert_eq!(token.balance(&fee_collector.address), 300_000);
+    assert_eq!(token.balance(&vault.id), 700_000);
+}
+
+#[test]
+fn test_slash_is_burned_when_fund_rejects_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let vault = setup_vault(&env);
+    // The fund does not recognise this vault, so the deposit is refused.
+    let fee_collector = setup_fee_collector(&env, &Address::generate(&env));
+    vault.client.set_insurance_fund(&fee_collector.address);
+
+    let slashed = vault.client.slash_stake(
+        &vault.registry,
+        &vault.provider,
+        &SlashSeverity::Major,
+        &Symbol::new(&env, "fraud"),
+    );
+    assert_eq!(slashed, 300_000);
+    assert_eq!(vault.client.get_stake(&vault.provider), 700_000);
+    assert_eq!(fee_collector.insurance_fund_balance(&vault.token), 0);
+    let token = TokenClient::new(&env, &vault.token);
+    assert_eq!(token.balance(&fee_collector.address), 0);
+    assert_eq!(token.balance(&vault.id), 700_000);
+}
@@ -0,0 +1,396 @@
+//! Oracle-backed benchmark returns and alpha (Issue #418).
+//!
+//! Every signal is snapshotted when it is created (committed signals when
+//! they are committed), so alpha covers all of a provider's signals rather
+//! than the ones they chose to enter. When the oracle cannot price the
+//! benchmark at creation, `snapshot_signal_benchmark` retries within
+//! `SNAPSHOT_WINDOW_SECONDS`, which keeps the entry price close to the
+//! signal's own. A snapshot
+//! resolves the benchmark into a list of oracle pair ids with weights and
+//! records their prices. When the signal reaches a terminal status the same
+//! pairs are priced again; the
+//! weighted return is the benchmark return and `alpha = signal return -
+//! benchmark return`. Sell signals are compared against shorting the
+//! benchmark, so the benchmark return is negated for them.
+//!
+//! Benchmarks:
+//! - `BaseAsset`: the oracle pair registered for the signal's asset pair.
+//! - `Basket`: a fixed weighted basket (e.g. XLM/USDC plus majors).
+//! - `CategoryIndex`: the index pair registered for the signal's category.
+//!
+//! Signals without a snapshot (not entered, or the oracle or a mapping was
+//! missing) keep `benchmark_return_bps` / `alpha_bps` at `None`.
+//!
+//! Every closed signal with an alpha also updates the provider's running
+//! sums, from which average alpha, information ratio and beta are derived.
+
+use soroban_sdk::{contracttype, Address, Env, Map, String, Vec};
+use stellar_swipe_common::oracle::{validate_oracle_price, IOracleClient, OnChainOracleClient};
+use stellar_swipe_common::BASIS_POINTS_DENOMINATOR_I128;
+
+use crate::categories::SignalCategory;
+use crate::errors::{AdminError, BenchmarkError};
+use crate::performance::get_signal_average_roi;
+use crate::types::{Signal, SignalAction};
+
+/// Maximum number of pairs in a basket benchmark.
+pub const MAX_BASKET_COMPONENTS: u32 = 5;
+/// Closed signals with alpha needed before provider metrics are reported.
+pub const MIN_BENCHMARK_SAMPLES: u32 = 5;
+/// How long after creation a signal can still be entered into benchmarking.
+pub const SNAPSHOT_WINDOW_SECONDS: u64 = 300;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BenchmarkComponent {
+    pub oracle_pair_id: u32,
+    pub weight_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BenchmarkSource {
+    BaseAsset,
+    /// Weights must sum to 10,000 bps.
+    Basket(Vec<BenchmarkComponent>),
+    CategoryIndex,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BenchmarkConfig {
+    pub oracle: Address,
+    pub source: BenchmarkSource,
+}
+
+/// Benchmark prices captured when a signal was created.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BenchmarkSnapshot {
+    pub components: Vec<BenchmarkComponent>,
+    pub entry_prices: Vec<i128>,
+    pub taken_at: u64,
+}
+
+/// Running sums over a provider's closed signals (returns in bps).
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProviderBenchmarkSums {
+    pub samples: u32,
+    pub sum_alpha: i128,
+    pub sum_alpha_sq: i128,
+    pub sum_signal: i128,
+    pub sum_benchmark: i128,
+    pub sum_benchmark_sq: i128,
+    pub sum_signal_x_benchmark: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProviderBenchmarkMetrics {
+    pub samples: u32,
+    pub avg_alpha_bps: i128,
+    /// Tracking error: standard deviation of alpha, in bps.
+    pub tracking_error_bps: i128,
+    /// Average alpha over tracking error, scaled by 10,000 (10,000 = 1.0).
+    pub information_ratio_bps: i128,
+    /// Covariance with the benchmark over benchmark variance, scaled by
+    /// 10,000 (10,000 = moves one-for-one with the benchmark).
+    pub beta_bps: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum BenchmarkKey {
+    Config,
+    /// Asset pair string -> oracle pair id.
+    AssetPairIds,
+    /// Category -> oracle pair id of its index.
+    CategoryIndexIds,
+    Snapshot(u64),
+    ProviderSums(Address),
+}
+
+pub fn get_config(env: &Env) -> Option<BenchmarkConfig> {
+    env.storage().instance().get(&BenchmarkKey::Config)
+}
+
+pub fn set_config(env: &Env, config: &BenchmarkConfig) -> Result<(), AdminError> {
+    if let BenchmarkSource::Basket(components) = &config.source {
+        if components.is_empty() || components.len() > MAX_BASKET_COMPONENTS {
+            return Err(AdminError::InvalidParameter);
+        }
+        let total: u32 = components.iter().map(|c| c.weight_bps).sum();
+        if total != 10_000 {
+            return Err(AdminError::InvalidParameter);
+        }
+    }
+    env.storage().instance().set(&BenchmarkKey::Config, config);
+    Ok(())
+}
+
+pub fn set_asset_pair_id(env: &Env, asset_pair: String, oracle_pair_id: u32) {
+    let mut ids: Map<String, u32> = env
+        .storage()
+        .instance()
+        .get(&BenchmarkKey::AssetPairIds)
+        .unwrap_or(Map::new(env));
+    ids.set(asset_pair, oracle_pair_id);
+    env.storage()
+        .instance()
+        .set(&BenchmarkKey::AssetPairIds, &ids);
+}
+
+pub fn set_category_index_id(env: &Env, category: SignalCategory, oracle_pair_id: u32) {
+    let mut ids: Map<SignalCategory, u32> = env
+        .storage()
+        .instance()
+        .get(&BenchmarkKey::CategoryIndexIds)
+        .unwrap_or(Map::new(env));
+    ids.set(category, oracle_pair_id);
+    env.storage()
+        .instance()
+        .set(&BenchmarkKey::CategoryIndexIds, &ids);
+}
+
+fn single(env: &Env, oracle_pair_id: u32) -> Vec<BenchmarkComponent> {
+    let mut components = Vec::new(env);
+    components.push_back(BenchmarkComponent {
+        oracle_pair_id,
+        weight_bps: 10_000,
+    });
+    components
+}
+
+fn resolve_components(
+    env: &Env,
+    source: &BenchmarkSource,
+    asset_pair: &String,
+    category: &SignalCategory,
+) -> Option<Vec<BenchmarkComponent>> {
+    match source {
+        BenchmarkSource::BaseAsset => {
+            let ids: Map<String, u32> =
+                env.storage().instance().get(&BenchmarkKey::AssetPairIds)?;
+            ids.get(asset_pair.clone()).map(|id| single(env, id))
+        }
+        BenchmarkSource::Basket(components) => Some(components.clone()),
+        BenchmarkSource::CategoryIndex => {
+            let ids: Map<SignalCategory, u32> = env
+                .storage()
+                .instance()
+                .get(&BenchmarkKey::CategoryIndexIds)?;
+            ids.get(category.clone()).map(|id| single(env, id))
+        }
+    }
+}
+
+fn fetch_prices(
+    env: &Env,
+    oracle: &Address,
+    components: &Vec<BenchmarkComponent>,
+) -> Option<Vec<i128>> {
+    let client = OnChainOracleClient {
+        address: oracle.clone(),
+    };
+    let mut prices = Vec::new(env);
+    for component in components.iter() {
+        let price = client.get_price(env, component.oracle_pair_id).ok()?;
+        validate_oracle_price(env, &price).ok()?;
+        // Raw units: decimals cancel out in the return of a single feed.
+        prices.push_back(price.price);
+    }
+    Some(prices)
+}
+
+/// Snapshot a signal's benchmark prices, within the window after creation.
+pub fn snapshot(env: &Env, signal: &Signal) -> Result<(), BenchmarkError> {
+    if env.ledger().timestamp() > signal.timestamp.saturating_add(SNAPSHOT_WINDOW_SECONDS) {
+        return Err(BenchmarkError::SnapshotWindowClosed);
+    }
+    snapshot_now(env, signal.id, &signal.asset_pair, &signal.category)
+}
+
+/// Snapshot the benchmark for `signal_id` at current prices. Used directly
+/// for committed signals, which are priced at commit time, before the
+/// signal itself exists.
+pub fn snapshot_now(
+    env: &Env,
+    signal_id: u64,
+    asset_pair: &String,
+    category: &SignalCategory,
+) -> Result<(), BenchmarkError> {
+    if env
+        .storage()
+        .persistent()
+        .has(&BenchmarkKey::Snapshot(signal_id))
+    {
+        return Err(BenchmarkError::AlreadySnapshotted);
+    }
+    let config = get_config(env).ok_or(BenchmarkError::NotConfigured)?;
+    let components = resolve_components(env, &config.source, asset_pair, category)
+        .ok_or(BenchmarkError::NotConfigured)?;
+    let entry_prices =
+        fetch_prices(env, &config.oracle, &components).ok_or(BenchmarkError::PriceUnavailable)?;
+    env.storage().persistent().set(
+        &BenchmarkKey::Snapshot(signal_id),
+        &BenchmarkSnapshot {
+            components,
+            entry_prices,
+            taken_at: env.ledger().timestamp(),
+        },
+    );
+    Ok(())
+}
+
+pub fn get_snapshot(env: &Env, signal_id: u64) -> Option<BenchmarkSnapshot> {
+    env.storage()
+        .persistent()
+        .get(&BenchmarkKey::Snapshot(signal_id))
+}
+
+/// Benchmark return (bps) of `snapshot` at current oracle prices, from the
+/// point of view of `action`.
+fn benchmark_return_bps(
+    env: &Env,
+    oracle: &Address,
+    snapshot: &BenchmarkSnapshot,
+    action: &SignalAction,
+) -> Option<i128> {
+    let exit_prices = fetch_prices(env, oracle, &snapshot.components)?;
+    let mut weighted = 0i128;
+    for i in 0..snapshot.components.len() {
+        let entry = snapshot.entry_prices.get(i)?;
+        let exit = exit_prices.get(i)?;
+        let component_return = (exit - entry)
+            .checked_mul(BASIS_POINTS_DENOMINATOR_I128)?
+            .checked_div(entry)?;
+        weighted = weighted.checked_add(
+            component_return.checked_mul(snapshot.components.get(i)?.weight_bps as i128)?,
+        )?;
+    }
+    let market = weighted / BASIS_POINTS_DENOMINATOR_I128;
+    Some(match action {
+        SignalAction::Buy => market,
+        SignalAction::Sell => -market,
+    })
+}
+
+/// Benchmark return and alpha for a signal on close. Both are `None` if the
+/// signal has no executions, no snapshot, or the oracle cannot price the
+/// benchmark now.
+pub fn calculate_on_close(env: &Env, signal: &Signal) -> (Option<i64>, Option<i64>) {
+    if signal.executions == 0 {
+        return (None, None);
+    }
+    calculate_for_return(env, signal, get_signal_average_roi(signal))
+}
+
+/// Like [`calculate_on_close`], for a signal whose return is known from
+/// elsewhere (e.g. oracle resolution) rather than from its executions.
+pub fn calculate_for_return(
+    env: &Env,
+    signal: &Signal,
+    signal_return: i128,
+) -> (Option<i64>, Option<i64>) {
+    let (Some(config), Some(snapshot)) = (get_config(env), get_snapshot(env, signal.id)) else {
+        return (None, None);
+    };
+    let Some(benchmark) = benchmark_return_bps(env, &config.oracle, &snapshot, &signal.action)
+    else {
+        return (None, None);
+    };
+    let alpha = signal_return - benchmark;
+    record_provider_sample(env, &signal.provider, signal_return, benchmark, alpha);
+    (Some(clamp_i64(benchmark)), Some(clamp_i64(alpha)))
+}
+
+/// Integer square root (Babylonian method, no_std compatible).
+pub(crate) fn isqrt(n: i128) -> i128 {
+    if n <= 0 {
+        return 0;
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+fn clamp_i64(value: i128) -> i64 {
+    value.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn get_sums(env: &Env, provider: &Address) -> ProviderBenchmarkSums {
+    env.storage()
+        .persistent()
+        .get(&BenchmarkKey::ProviderSums(provider.clone()))
+        .unwrap_or_default()
+}
+
+fn record_provider_sample(
+    env: &Env,
+    provider: &Address,
+    signal_return: i128,
+    benchmark: i128,
+    alpha: i128,
+) {
+    let mut sums = get_sums(env, provider);
+    sums.samples = sums.samples.saturating_add(1);
+    sums.sum_alpha = sums.sum_alpha.saturating_add(alpha);
+    sums.sum_alpha_sq = sums
+        .sum_alpha_sq
+        .saturating_add(alpha.saturating_mul(alpha));
+    sums.sum_signal = sums.sum_signal.saturating_add(signal_return);
+    sums.sum_benchmark = sums.sum_benchmark.saturating_add(benchmark);
+    sums.sum_benchmark_sq = sums
+        .sum_benchmark_sq
+        .saturating_add(benchmark.saturating_mul(benchmark));
+    sums.sum_signal_x_benchmark = sums
+        .sum_signal_x_benchmark
+        .saturating_add(signal_return.saturating_mul(benchmark));
+    env.storage()
+        .persistent()
+        .set(&BenchmarkKey::ProviderSums(provider.clone()), &sums);
+}
+
+/// Alpha, information ratio and beta over the provider's closed signals.
+/// All zero until `MIN_BENCHMARK_SAMPLES` signals have an alpha.
+pub fn provider_metrics(env: &Env, provider: &Address) -> ProviderBenchmarkMetrics {
+    let sums = get_sums(env, provider);
+    if sums.samples < MIN_BENCHMARK_SAMPLES {
+        return ProviderBenchmarkMetrics {
+            samples: sums.samples,
+            ..Default::default()
+        };
+    }
+    let n = sums.samples as i128;
+    let avg_alpha = sums.sum_alpha / n;
+    let alpha_variance = (sums.sum_alpha_sq / n - avg_alpha * avg_alpha).max(0);
+    let tracking_error = isqrt(alpha_variance);
+    let information_ratio = if tracking_error > 0 {
+        avg_alpha * BASIS_POINTS_DENOMINATOR_I128 / tracking_error
+    } else {
+        0
+    };
+
+    let avg_signal = sums.sum_signal / n;
+    let avg_benchmark = sums.sum_benchmark / n;
+    let covariance = sums.sum_signal_x_benchmark / n - avg_signal * avg_benchmark;
+    let benchmark_variance = sums.sum_benchmark_sq / n - avg_benchmark * avg_benchmark;
+    let beta = if benchmark_variance > 0 {
+        covariance * BASIS_POINTS_DENOMINATOR_I128 / benchmark_variance
+    } else {
+        0
+    };
+
+    ProviderBenchmarkMetrics {
+        samples: sums.samples,
+        avg_alpha_bps: avg_alpha,
+        tracking_error_bps: tracking_error,
+        information_ratio_bps: information_ratio,
+        beta_bps: beta,
+    }
+}
@@ -1,22 +1,385 @@
+//! Signal bundles: provider-curated portfolios of live signals.
+//!
+//! A bundle holds a weighted set (weights in bps, summing to 10 000) of the
+//! provider's active signals plus a bundle-level fee in a token fixed at
+//! creation. Copying a bundle takes the fee off the top, never less than the
+//! bundle's minimum fee, pays it from the copier to the provider and splits
+//! the rest across the components by weight. Each component is recorded as a
+//! copy for the user; it counts as an adoption of its signal only when the
+//! copy trade is executed and reported by the TradeExecutor through
+//! `increment_adoption`.
+//!
+//! Components drop out when their signal stops being live (expired, closed or
+//! resolved). Their weight is spread pro rata over the remaining components;
+//! a bundle with no live components closes. Rebalancing runs before every
+//! copy and can also be triggered by anyone through `rebalance_bundle`.
+//!
+//! Performance is tracked per component by copied volume, so the bundle ROI is
+//! the volume-weighted ROI of every signal it ever allocated to.
+
+use soroban_sdk::{contracttype, token, Address, Env, Map, String, Vec};
+
+use crate::errors::BundleError;
+use crate::performance;
+use crate::types::{Signal, SignalStatus};
+use crate::{versioning, StorageKey};
+
+pub const MAX_BUNDLE_COMPONENTS: u32 = 10;
+pub const MIN_BUNDLE_COMPONENTS: u32 = 2;
+/// Highest bundle fee a provider may charge: 10%.
+pub const MAX_BUNDLE_FEE_BPS: u32 = 1_000;
+const WEIGHT_TOTAL: u32 = 10_000;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleComponent {
+    pub signal_id: u64,
+    /// Allocation in bps of the copied amount after the fee.
+    pub weight: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BundleStatus {
+    Active,
+    Closed,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SignalBundle {
-    pub signal_ids: Vec<u32>,
-    pub allocations: Vec<u32>,
+    pub id: u64,
+    pub provider: Address,
+    pub name: String,
+    pub components: Vec<BundleComponent>,
+    pub fee_bps: u32,
+    /// Token the bundle fee is paid in.
+    pub fee_token: Address,
+    /// Fee charged on a copy when `fee_bps` of the amount comes to less.
+    pub min_fee: i128,
+    pub status: BundleStatus,
+    pub created_at: u64,
+    pub copies: u32,
+    pub total_volume: i128,
+    pub total_fees: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleAllocation {
+    pub signal_id: u64,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleCopy {
+    pub bundle_id: u64,
+    pub user: Address,
+    pub amount: i128,
+    pub fee: i128,
+    pub allocations: Vec<BundleAllocation>,
+    pub copied_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundlePerformance {
+    pub bundle_id: u64,
+    pub copies: u32,
+    pub total_volume: i128,
+    pub total_fees: i128,
+    /// Volume-weighted ROI of the allocated signals, in bps.
+    pub average_roi: i128,
+    pub status: BundleStatus,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum BundleKey {
+    BundleCounter,
+    Bundle(u64),
+    /// Copied volume per component signal: `Map<u64, i128>`.
+    BundleVolume(u64),
+    /// Latest copy of a bundle by a user.
+    BundleCopy(Address, u64),
+}
+
+pub fn get_bundle(env: &Env, bundle_id: u64) -> Option<SignalBundle> {
+    env.storage()
+        .persistent()
+        .get(&BundleKey::Bundle(bundle_id))
+}
+
+fn save_bundle(env: &Env, bundle: &SignalBundle) {
+    env.storage()
+        .persistent()
+        .set(&BundleKey::Bundle(bundle.id), bundle);
+}
+
+fn next_bundle_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&BundleKey::BundleCounter)
+        .unwrap_or(0)
+        + 1;
+    env.storage().instance().set(&BundleKey::BundleCounter, &id);
+    id
+}
+
+fn get_volumes(env: &Env, bundle_id: u64) -> Map<u64, i128> {
+    env.storage()
+        .persistent()
+        .get(&BundleKey::BundleVolume(bundle_id))
+        .unwrap_or(Map::new(env))
+}
+
+pub fn get_copy(env: &Env, user: &Address, bundle_id: u64) -> Option<BundleCopy> {
+    env.storage()
+        .persistent()
+        .get(&BundleKey::BundleCopy(user.clone(), bundle_id))
+}
+
+fn signals_map(env: &Env) -> Map<u64, Signal> {
+    env.storage()
+        .instance()
+        .get(&StorageKey::Signals)
+        .unwrap_or(Map::new(env))
+}
+
+fn is_live(signal: &Signal, now: u64) -> bool {
+    signal.status == SignalStatus::Active && signal.expiry > now
+}
+
+/// Create a bundle of `provider`'s live signals.
+pub fn create_bundle(
+    env: &Env,
+    provider: &Address,
+    name: String,
+    components: Vec<BundleComponent>,
+    fee_bps: u32,
+    fee_token: Address,
+    min_fee: i128,
+) -> Result<u64, BundleError> {
+    if components.len() < MIN_BUNDLE_COMPONENTS || components.len() > MAX_BUNDLE_COMPONENTS {
+        return Err(BundleError::InvalidComponentCount);
+    }
+    if fee_bps > MAX_BUNDLE_FEE_BPS {
+        return Err(BundleError::FeeTooHigh);
+    }
+    if min_fee < 0 {
+        return Err(BundleError::InvalidAmount);
+    }
+
+    let signals = signals_map(env);
+    let now = env.ledger().timestamp();
+    let mut seen: Map<u64, bool> = Map::new(env);
+    let mut total_weight: u32 = 0;
+    for comp in components.iter() {
+        if comp.weight == 0 {
+            return Err(BundleError::InvalidWeights);
+        }
+        if seen.contains_key(comp.signal_id) {
+            return Err(BundleError::DuplicateSignal);
+        }
+        seen.set(comp.signal_id, true);
+        let signal = signals
+            .get(comp.signal_id)
+            .ok_or(BundleError::SignalNotFound)?;
+        if signal.provider != *provider {
+            return Err(BundleError::NotSignalOwner);
+        }
+        if !is_live(&signal, now) {
+            return Err(BundleError::SignalNotActive);
+        }
+        total_weight = total_weight.saturating_add(comp.weight);
+    }
+    if total_weight != WEIGHT_TOTAL {
+        return Err(BundleError::InvalidWeights);
+    }
+
+    let bundle = SignalBundle {
+        id: next_bundle_id(env),
+        provider: provider.clone(),
+        name,
+        components,
+        fee_bps,
+        fee_token,
+        min_fee,
+        status: BundleStatus::Active,
+        created_at: now,
+        copies: 0,
+        total_volume: 0,
+        total_fees: 0,
+    };
+    save_bundle(env, &bundle);
+    Ok(bundle.id)
 }
 
-pub fn create_signal_bundle(signal_ids: Vec<u32>, allocations: Vec<u32>) -> SignalBundle {
-    let total: u32 = allocations.iter().sum();
-    assert!(total == 10000, "allocations must sum to 10000");
+/// Drop components whose signal is no longer live and spread their weight
+/// over the rest, pro rata. Returns the number of components removed.
+fn rebalance(env: &Env, bundle: &mut SignalBundle) -> u32 {
+    if bundle.status != BundleStatus::Active {
+        return 0;
+    }
+    let signals = signals_map(env);
+    let now = env.ledger().timestamp();
+    let mut live: Vec<BundleComponent> = Vec::new(env);
+    let mut live_weight: u32 = 0;
+    for comp in bundle.components.iter() {
+        if signals
+            .get(comp.signal_id)
+            .is_some_and(|s| is_live(&s, now))
+        {
+            live_weight += comp.weight;
+            live.push_back(comp);
+        }
+    }
+    let removed = bundle.components.len() - live.len();
+    if removed == 0 {
+        return 0;
+    }
+    if live.is_empty() {
+        bundle.status = BundleStatus::Closed;
+        bundle.components = live;
+        return removed;
+    }
 
-    SignalBundle {
-        signal_ids,
+    let mut assigned: u32 = 0;
+    let mut rebalanced: Vec<BundleComponent> = Vec::new(env);
+    for comp in live.iter() {
+        let weight = (comp.weight as u64 * WEIGHT_TOTAL as u64 / live_weight as u64) as u32;
+        assigned += weight;
+        rebalanced.push_back(BundleComponent {
+            signal_id: comp.signal_id,
+            weight,
+        });
+    }
+    // Rounding dust goes to the first component.
+    let mut first = rebalanced.get(0).unwrap();
+    first.weight += WEIGHT_TOTAL - assigned;
+    rebalanced.set(0, first);
+    bundle.components = rebalanced;
+    removed
+}
+
+/// Rebalance a bundle and persist the result.
+pub fn rebalance_bundle(env: &Env, bundle_id: u64) -> Result<(SignalBundle, u32), BundleError> {
+    let mut bundle = get_bundle(env, bundle_id).ok_or(BundleError::BundleNotFound)?;
+    let removed = rebalance(env, &mut bundle);
+    if removed > 0 {
+        save_bundle(env, &bundle);
+    }
+    Ok((bundle, removed))
+}
+
+/// Copy a bundle for `user`: charge the bundle fee, paid by `user` to the
+/// provider, and split the rest of `amount` over the live components. The
+/// caller must have authorized `user`.
+pub fn copy_bundle(
+    env: &Env,
+    user: &Address,
+    bundle_id: u64,
+    amount: i128,
+) -> Result<BundleCopy, BundleError> {
+    if amount <= 0 {
+        return Err(BundleError::InvalidAmount);
+    }
+    let (mut bundle, _) = rebalance_bundle(env, bundle_id)?;
+    if bundle.status != BundleStatus::Active {
+        return Err(BundleError::BundleClosed);
+    }
+
+    let fee =
+        (amount.saturating_mul(bundle.fee_bps as i128) / WEIGHT_TOTAL as i128).max(bundle.min_fee);
+    if fee >= amount {
+        return Err(BundleError::InvalidAmount);
+    }
+    let net = amount - fee;
+    let mut volumes = get_volumes(env, bundle_id);
+    let mut allocations: Vec<BundleAllocation> = Vec::new(env);
+    let mut allocated: i128 = 0;
+    for (i, comp) in bundle.components.iter().enumerate() {
+        let share = if i as u32 + 1 == bundle.components.len() {
+            net - allocated
+        } else {
+            net.saturating_mul(comp.weight as i128) / WEIGHT_TOTAL as i128
+        };
+        allocated += share;
+        volumes.set(
+            comp.signal_id,
+            volumes
+                .get(comp.signal_id)
+                .unwrap_or(0)
+                .saturating_add(share),
+        );
+        let version = versioning::get_latest_version(env, comp.signal_id);
+        versioning::record_copy(env, user, comp.signal_id, version);
+        allocations.push_back(BundleAllocation {
+            signal_id: comp.signal_id,
+            amount: share,
+        });
+    }
+    env.storage()
+        .persistent()
+        .set(&BundleKey::BundleVolume(bundle_id), &volumes);
+
+    if fee > 0 {
+        token::Client::new(env, &bundle.fee_token).transfer(user, &bundle.provider, &fee);
+    }
+
+    bundle.copies = bundle.copies.saturating_add(1);
+    bundle.total_volume = bundle.total_volume.saturating_add(amount);
+    bundle.total_fees = bundle.total_fees.saturating_add(fee);
+    save_bundle(env, &bundle);
+
+    let copy = BundleCopy {
+        bundle_id,
+        user: user.clone(),
+        amount,
+        fee,
         allocations,
+        copied_at: env.ledger().timestamp(),
+    };
+    env.storage()
+        .persistent()
+        .set(&BundleKey::BundleCopy(user.clone(), bundle_id), &copy);
+    Ok(copy)
+}
+
+/// Close a bundle to new copies. Only its provider may close it.
+pub fn close_bundle(env: &Env, provider: &Address, bundle_id: u64) -> Result<(), BundleError> {
+    let mut bundle = get_bundle(env, bundle_id).ok_or(BundleError::BundleNotFound)?;
+    if bundle.provider != *provider {
+        return Err(BundleError::NotSignalOwner);
+    }
+    if bundle.status != BundleStatus::Active {
+        return Err(BundleError::BundleClosed);
     }
+    bundle.status = BundleStatus::Closed;
+    save_bundle(env, &bundle);
+    Ok(())
 }
 
-pub fn copy_signal_bundle(total_amount: i128, bundle: SignalBundle) -> Vec<i128> {
-    bundle
-        .allocations
-        .iter()
-        .map(|a| total_amount * (*a as i128) / 10000)
-        .collect()
+pub fn get_performance(env: &Env, bundle_id: u64) -> Option<BundlePerformance> {
+    let bundle = get_bundle(env, bundle_id)?;
+    let signals = signals_map(env);
+    let mut weighted_roi: i128 = 0;
+    let mut volume: i128 = 0;
+    for (signal_id, amount) in get_volumes(env, bundle_id).iter() {
+        if let Some(signal) = signals.get(signal_id) {
+            let roi = performance::signal_roi(env, &signal);
+            weighted_roi = weighted_roi.saturating_add(roi.saturating_mul(amount));
+            volume = volume.saturating_add(amount);
+        }
+    }
+    Some(BundlePerformance {
+        bundle_id,
+        copies: bundle.copies,
+        total_volume: bundle.total_volume,
+        total_fees: bundle.total_fees,
+        average_roi: if volume > 0 { weighted_roi / volume } else { 0 },
+        status: bundle.status,
+    })
 }
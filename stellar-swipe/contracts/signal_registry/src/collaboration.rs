@@ -18,6 +18,15 @@ pub struct Author {
     pub has_approved: bool,
 }
 
+/// Fee payout recipient; same shape as FeeCollector's `PayoutSplit`, so the
+/// result of [`payout_splits`] can be passed to `set_payout_config`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoutSplit {
+    pub recipient: Address,
+    pub share_bps: u32,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub enum CollabStorageKey {
@@ -160,3 +169,21 @@ pub fn split_provider_fee(authors: &Vec<Author>, provider_fee: i128) -> Vec<(u32
 
     splits
 }
+
+/// Contribution shares of an approved collaboration as payout splits, so that
+/// the amounts [`split_provider_fee`] computes are paid straight to each author.
+pub fn payout_splits(env: &Env, authors: &Vec<Author>) -> Option<Vec<PayoutSplit>> {
+    if get_collaboration_status(authors) != CollaborationStatus::Approved {
+        return None;
+    }
+    let mut splits = Vec::new(env);
+    for author in authors.iter() {
+        if author.contribution_pct > 0 {
+            splits.push_back(PayoutSplit {
+                recipient: author.address,
+                share_bps: author.contribution_pct,
+            });
+        }
+    }
+    Some(splits)
+}
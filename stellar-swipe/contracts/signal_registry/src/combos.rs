@@ -112,6 +112,21 @@ pub struct ComboExecution {
     pub executed_at: u64,
 }
 
+/// Aggregated results of one component across all executions of a combo.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ComboLegPerformance {
+    pub signal_id: u64,
+    /// Executions in which the leg was traded.
+    pub executions: u32,
+    /// Executions in which the leg was skipped or never opened.
+    pub skipped: u32,
+    pub total_volume: i128,
+    pub total_pnl: i128,
+    /// Average ROI of the traded executions, in basis points.
+    pub average_roi: i128,
+}
+
 /// Summary view returned to callers.
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -120,6 +135,9 @@ pub struct ComboPerformanceSummary {
     pub total_executions: u32,
     pub combined_roi: i128, // average across all executions
     pub total_volume: i128,
+    pub total_pnl: i128,
+    /// One entry per component, in combo order.
+    pub legs: Vec<ComboLegPerformance>,
 }
 
 // ---------------------------------------------------------------------------
@@ -163,6 +181,29 @@ fn save_combo_executions(env: &Env, combo_id: u64, execs: &Vec<ComboExecution>)
         .set(&StorageKey::ComboExecutions(combo_id), execs);
 }
 
+/// Cumulative realized P&L per component, in combo order. Kept apart from
+/// [`ComboExecution`] so that stored execution records keep their layout.
+fn get_leg_pnls(env: &Env, combo_id: u64) -> Vec<i128> {
+    env.storage()
+        .instance()
+        .get(&StorageKey::ComboLegPnl(combo_id))
+        .unwrap_or(Vec::new(env))
+}
+
+fn add_leg_pnls(env: &Env, combo_id: u64, pnls: &Vec<i128>) {
+    let mut totals = get_leg_pnls(env, combo_id);
+    for i in 0..pnls.len() {
+        let pnl = pnls.get(i).unwrap();
+        match totals.get(i) {
+            Some(total) => totals.set(i, total.saturating_add(pnl)),
+            None => totals.push_back(pnl),
+        }
+    }
+    env.storage()
+        .instance()
+        .set(&StorageKey::ComboLegPnl(combo_id), &totals);
+}
+
 // ---------------------------------------------------------------------------
 // Public helpers used by lib.rs
 // ---------------------------------------------------------------------------
@@ -393,6 +434,12 @@ pub fn execute_combo_signal(
     execs.push_back(execution);
     save_combo_executions(env, combo_id, &execs);
 
+    let mut pnls: Vec<i128> = Vec::new(env);
+    for exec in component_executions.iter() {
+        pnls.push_back(leg_pnl(exec.amount, exec.roi));
+    }
+    add_leg_pnls(env, combo_id, &pnls);
+
     // If all components were skipped, mark combo as completed
     let all_skipped = {
         let mut all = true;
@@ -434,38 +481,115 @@ pub fn cancel_combo(env: &Env, combo_id: u64, provider: &Address) -> Result<(),
     Ok(())
 }
 
-/// Get aggregated performance for a combo.
+/// Store the outcome of a combo adoption executed by the TradeExecutor.
+///
+/// Unlike [`execute_combo_signal`], `legs` carry realized ROI: each leg was
+/// traded as a copy trade and closed on its signal's outcome (or unwound at
+/// mark). `pnls` holds the realized P&L of each leg, in the same order. Legs
+/// that were never opened are `skipped`. The combo may have been cancelled
+/// since it was adopted, so its status is not checked.
+pub fn record_combo_execution(
+    env: &Env,
+    combo_id: u64,
+    user: &Address,
+    total_amount: i128,
+    legs: Vec<ComponentExecution>,
+    pnls: Vec<i128>,
+) -> Result<i128, ComboError> {
+    let combo = get_combo(env, combo_id).ok_or(ComboError::ComboNotFound)?;
+    if total_amount <= 0 {
+        return Err(ComboError::InvalidAmount);
+    }
+    if legs.len() != combo.component_signals.len() || pnls.len() != legs.len() {
+        return Err(ComboError::InvalidLegs);
+    }
+    for i in 0..legs.len() {
+        let leg = legs.get(i).unwrap();
+        if leg.signal_id != combo.component_signals.get(i).unwrap().signal_id {
+            return Err(ComboError::InvalidLegs);
+        }
+    }
+
+    let combined_roi = calculate_combined_roi(&legs, total_amount);
+    let mut execs = get_combo_executions(env, combo_id);
+    execs.push_back(ComboExecution {
+        combo_id,
+        executor: user.clone(),
+        total_amount,
+        component_executions: legs,
+        combined_roi,
+        executed_at: env.ledger().timestamp(),
+    });
+    save_combo_executions(env, combo_id, &execs);
+    add_leg_pnls(env, combo_id, &pnls);
+    Ok(combined_roi)
+}
+
+/// Get aggregated performance for a combo, overall and per leg.
 pub fn get_combo_performance(env: &Env, combo_id: u64) -> Option<ComboPerformanceSummary> {
-    get_combo(env, combo_id)?; // return None if combo doesn't exist
+    let combo = get_combo(env, combo_id)?;
 
     let execs = get_combo_executions(env, combo_id);
-    let total_executions = execs.len() as u32;
+    let total_executions = execs.len();
 
-    if total_executions == 0 {
-        return Some(ComboPerformanceSummary {
-            combo_id,
-            total_executions: 0,
-            combined_roi: 0,
-            total_volume: 0,
-        });
-    }
+    let leg_pnls = get_leg_pnls(env, combo_id);
 
     let mut total_roi: i128 = 0;
     let mut total_volume: i128 = 0;
+    let mut total_pnl: i128 = 0;
 
     for i in 0..execs.len() {
         let exec = execs.get(i).unwrap();
         total_roi = total_roi.saturating_add(exec.combined_roi);
         total_volume = total_volume.saturating_add(exec.total_amount);
     }
+    for pnl in leg_pnls.iter() {
+        total_pnl = total_pnl.saturating_add(pnl);
+    }
 
-    let avg_roi = total_roi / total_executions as i128;
+    let mut legs: Vec<ComboLegPerformance> = Vec::new(env);
+    for i in 0..combo.component_signals.len() {
+        let signal_id = combo.component_signals.get(i).unwrap().signal_id;
+        let mut leg_perf = ComboLegPerformance {
+            signal_id,
+            executions: 0,
+            skipped: 0,
+            total_volume: 0,
+            total_pnl: leg_pnls.get(i).unwrap_or(0),
+            average_roi: 0,
+        };
+        let mut roi_sum: i128 = 0;
+        for exec in execs.iter() {
+            let Some(leg) = exec.component_executions.get(i) else {
+                continue;
+            };
+            if leg.skipped {
+                leg_perf.skipped += 1;
+                continue;
+            }
+            leg_perf.executions += 1;
+            leg_perf.total_volume = leg_perf.total_volume.saturating_add(leg.amount);
+            roi_sum = roi_sum.saturating_add(leg.roi);
+        }
+        if leg_perf.executions > 0 {
+            leg_perf.average_roi = roi_sum / leg_perf.executions as i128;
+        }
+        legs.push_back(leg_perf);
+    }
+
+    let combined_roi = if total_executions == 0 {
+        0
+    } else {
+        total_roi / total_executions as i128
+    };
 
     Some(ComboPerformanceSummary {
         combo_id,
         total_executions,
-        combined_roi: avg_roi,
+        combined_roi,
         total_volume,
+        total_pnl,
+        legs,
     })
 }
 
@@ -536,6 +660,11 @@ fn simulate_trade_roi(env: &Env, signal_id: u64, _amount: i128) -> i128 {
     0
 }
 
+/// P&L of `amount` traded at `roi` basis points.
+fn leg_pnl(amount: i128, roi: i128) -> i128 {
+    amount.saturating_mul(roi) / WEIGHT_TOTAL as i128
+}
+
 /// Calculate a weighted-average combined ROI across all non-skipped components.
 fn calculate_combined_roi(executions: &Vec<ComponentExecution>, total_amount: i128) -> i128 {
     if total_amount == 0 {
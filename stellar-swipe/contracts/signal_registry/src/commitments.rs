@@ -0,0 +1,183 @@
+//! Signal commitments: prove when a call was made without publishing it.
+//!
+//! A provider commits `hash_signal_commitment(provider, asset_pair, action,
+//! price, rationale, salt)` (see `stellar_swipe_common::commit_reveal`) and
+//! may publish the signal itself later, within [`REVEAL_WINDOW_SECONDS`]. The
+//! revealed signal takes the commitment's ledger time as its `timestamp` and
+//! `submitted_at`, so performance scoring credits the call from the moment it
+//! was committed.
+//!
+//! The asset pair, category, expiry, tags and risk level are declared in the
+//! clear at commit time and the signal id is reserved then, so only the
+//! hashed content is chosen at reveal. Contest entry and the benchmark snapshot
+//! happen at commit too, so neither can be timed to the reveal.
+//!
+//! A provider may only have one pending commitment per asset pair, and one
+//! left unrevealed past its deadline can be expired by anyone. The
+//! provider's reputation then takes the same decay as a losing signal.
+//! Otherwise a provider could commit to both sides and reveal only the winner.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, String, Vec};
+use stellar_swipe_common::hash_signal_commitment;
+
+use crate::categories::{RiskLevel, SignalCategory};
+use crate::errors::CommitmentError;
+use crate::private_signals::action_code;
+use crate::types::SignalData;
+
+/// How long a provider has to reveal a committed signal.
+pub const REVEAL_WINDOW_SECONDS: u64 = 3 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommitmentStatus {
+    Pending,
+    Revealed,
+    Expired,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignalCommitment {
+    pub id: u64,
+    pub provider: Address,
+    pub asset_pair: String,
+    pub category: SignalCategory,
+    pub expiry: u64,
+    pub tags: Vec<String>,
+    pub risk_level: RiskLevel,
+    pub commitment: BytesN<32>,
+    pub committed_at: u64,
+    pub reveal_deadline: u64,
+    pub status: CommitmentStatus,
+    /// Signal id reserved at commit; the signal exists once revealed.
+    pub signal_id: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum CommitmentKey {
+    CommitmentCounter,
+    SignalCommitment(u64),
+    /// (provider, asset pair) -> id of the provider's pending commitment.
+    Pending(Address, String),
+}
+
+pub fn get_commitment(env: &Env, id: u64) -> Option<SignalCommitment> {
+    env.storage()
+        .persistent()
+        .get(&CommitmentKey::SignalCommitment(id))
+}
+
+fn save_commitment(env: &Env, commitment: &SignalCommitment) {
+    env.storage()
+        .persistent()
+        .set(&CommitmentKey::SignalCommitment(commitment.id), commitment);
+}
+
+fn pending_key(record: &SignalCommitment) -> CommitmentKey {
+    CommitmentKey::Pending(record.provider.clone(), record.asset_pair.clone())
+}
+
+pub fn commit(
+    env: &Env,
+    provider: &Address,
+    asset_pair: String,
+    category: SignalCategory,
+    expiry: u64,
+    tags: Vec<String>,
+    risk_level: RiskLevel,
+    commitment: BytesN<32>,
+    signal_id: u64,
+) -> Result<SignalCommitment, CommitmentError> {
+    let pending = CommitmentKey::Pending(provider.clone(), asset_pair.clone());
+    if env.storage().persistent().has(&pending) {
+        return Err(CommitmentError::AlreadyPending);
+    }
+
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&CommitmentKey::CommitmentCounter)
+        .unwrap_or(0)
+        + 1;
+    env.storage()
+        .instance()
+        .set(&CommitmentKey::CommitmentCounter, &id);
+
+    let now = env.ledger().timestamp();
+    let record = SignalCommitment {
+        id,
+        provider: provider.clone(),
+        asset_pair,
+        category,
+        expiry,
+        tags,
+        risk_level,
+        commitment,
+        committed_at: now,
+        reveal_deadline: now + REVEAL_WINDOW_SECONDS,
+        status: CommitmentStatus::Pending,
+        signal_id,
+    };
+    save_commitment(env, &record);
+    env.storage().persistent().set(&pending, &id);
+    Ok(record)
+}
+
+/// Check a reveal against a pending commitment of `provider`.
+pub fn verify_reveal(
+    env: &Env,
+    provider: &Address,
+    id: u64,
+    data: &SignalData,
+    salt: u64,
+) -> Result<SignalCommitment, CommitmentError> {
+    let record = get_commitment(env, id).ok_or(CommitmentError::NotFound)?;
+    if record.provider != *provider {
+        return Err(CommitmentError::NotProvider);
+    }
+    if record.status != CommitmentStatus::Pending {
+        return Err(CommitmentError::NotPending);
+    }
+    if env.ledger().timestamp() > record.reveal_deadline {
+        return Err(CommitmentError::RevealWindowClosed);
+    }
+    if data.asset_pair != record.asset_pair {
+        return Err(CommitmentError::CommitmentMismatch);
+    }
+    let expected = hash_signal_commitment(
+        env,
+        provider,
+        &data.asset_pair,
+        action_code(&data.action),
+        data.price,
+        &data.rationale,
+        salt,
+    );
+    if expected != record.commitment {
+        return Err(CommitmentError::CommitmentMismatch);
+    }
+    Ok(record)
+}
+
+pub fn mark_revealed(env: &Env, mut record: SignalCommitment) {
+    record.status = CommitmentStatus::Revealed;
+    env.storage().persistent().remove(&pending_key(&record));
+    save_commitment(env, &record);
+}
+
+/// Expire a pending commitment whose reveal window has passed.
+pub fn expire(env: &Env, id: u64) -> Result<SignalCommitment, CommitmentError> {
+    let mut record = get_commitment(env, id).ok_or(CommitmentError::NotFound)?;
+    if record.status != CommitmentStatus::Pending {
+        return Err(CommitmentError::NotPending);
+    }
+    if env.ledger().timestamp() <= record.reveal_deadline {
+        return Err(CommitmentError::RevealWindowOpen);
+    }
+    record.status = CommitmentStatus::Expired;
+    env.storage().persistent().remove(&pending_key(&record));
+    save_commitment(env, &record);
+    Ok(record)
+}
@@ -75,6 +75,18 @@ pub struct DisputeRecord {
     pub appeal_submitted_at: u64,
 }
 
+/// How the most recent dispute against a provider ended. Read by FeeCollector
+/// to validate copier insurance claims.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeOutcome {
+    pub provider: Address,
+    pub opened_at: u64,
+    pub resolved_at: u64,
+    /// `true` when the dispute was upheld (reputation not restored).
+    pub provider_at_fault: bool,
+}
+
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -107,6 +119,10 @@ pub enum VotingKey {
     History(Address),
     /// provider -> bool (score frozen due to open dispute)
     ScoreFrozen(Address),
+    /// provider -> DisputeOutcome of the most recently resolved dispute
+    Outcome(Address),
+    /// provider -> Vec<DisputeOutcome>, last 10 resolved disputes, oldest first
+    OutcomeHistory(Address),
 }
 
 /// Cast a vote for a provider. One vote per voter per window; re-voting replaces prior vote.
@@ -286,6 +302,24 @@ pub fn resolve_dispute(env: &Env, provider: Address, restore: bool) {
     env.storage()
         .persistent()
         .remove(&VotingKey::ScoreFrozen(provider.clone()));
+    let outcome = DisputeOutcome {
+        provider: provider.clone(),
+        opened_at: record.opened_at,
+        resolved_at: env.ledger().timestamp(),
+        provider_at_fault: !restore,
+    };
+    let mut outcomes = get_dispute_outcome_history(env, &provider);
+    outcomes.push_back(outcome.clone());
+    // Keep only last 10
+    while outcomes.len() > 10 {
+        outcomes.remove(0);
+    }
+    env.storage()
+        .persistent()
+        .set(&VotingKey::OutcomeHistory(provider.clone()), &outcomes);
+    env.storage()
+        .persistent()
+        .set(&VotingKey::Outcome(provider.clone()), &outcome);
 
     if restore {
         // Apply one recovery step immediately
@@ -298,6 +332,30 @@ pub fn resolve_dispute(env: &Env, provider: Address, restore: bool) {
     );
 }
 
+pub fn get_dispute_outcome(env: &Env, provider: &Address) -> Option<DisputeOutcome> {
+    env.storage()
+        .persistent()
+        .get(&VotingKey::Outcome(provider.clone()))
+}
+
+/// Outcomes of the provider's last 10 resolved disputes, oldest first.
+/// Disputes resolved before the history was kept contribute their latest
+/// outcome only.
+pub fn get_dispute_outcome_history(env: &Env, provider: &Address) -> Vec<DisputeOutcome> {
+    if let Some(outcomes) = env
+        .storage()
+        .persistent()
+        .get(&VotingKey::OutcomeHistory(provider.clone()))
+    {
+        return outcomes;
+    }
+    let mut outcomes = Vec::new(env);
+    if let Some(latest) = get_dispute_outcome(env, provider) {
+        outcomes.push_back(latest);
+    }
+    outcomes
+}
+
 /// Disputed provider submits an appeal of an open dispute. Only the provider the
 /// dispute was opened against may appeal it, and only once per dispute.
 pub fn submit_appeal(env: &Env, provider: Address) -> Result<(), DisputeError> {
@@ -588,6 +646,33 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_resolve_dispute_records_outcome() {
+        with_registry(|env, cid, client, admin| {
+            let provider = Address::generate(env);
+            assert_eq!(client.get_provider_dispute_outcome(&provider), None);
+            open_dispute_for(env, cid, &provider);
+            let opened_at = in_contract(env, cid, |env| get_dispute(env, &provider))
+                .unwrap()
+                .opened_at;
+
+            client.resolve_provider_dispute(admin, &provider, &false);
+
+            let outcome = client.get_provider_dispute_outcome(&provider).unwrap();
+            assert!(outcome.provider_at_fault);
+            assert_eq!(outcome.opened_at, opened_at);
+            assert_eq!(outcome.resolved_at, env.ledger().timestamp());
+
+            // A later dispute in the provider's favour keeps the earlier finding.
+            open_dispute_for(env, cid, &provider);
+            client.resolve_provider_dispute(admin, &provider, &true);
+            let history = client.get_provider_dispute_history(&provider);
+            assert_eq!(history.len(), 2);
+            assert_eq!(history.get(0).unwrap(), outcome);
+            assert!(!history.get(1).unwrap().provider_at_fault);
+        });
+    }
+
     #[test]
     fn test_reputation_history_tracked() {
         with_registry(|env, cid, _client, _admin| {
@@ -1,6 +1,39 @@
+//! Provider contests with escrowed prizes.
+//!
+//! The creator of a contest (the admin or a sponsor) deposits the prize pool
+//! in `prize_token` when the contest is created. Other sponsors can add to it
+//! while the contest runs. Finalization pays the top entries according to the
+//! payout curve. If fewer entries qualify than the curve has ranks, the
+//! shares of the paid ranks are scaled up so the whole pool goes out. A
+//! cancelled contest, or one with no qualified entries, refunds every sponsor
+//! their deposit.
+//!
+//! To keep sybil accounts out, a provider only enters (and only qualifies at
+//! finalization) when they hold at least `min_stake` and their trust tier is
+//! at least `min_trust_tier`. Active contests are indexed by their minimum
+//! tier, so entering a new signal only loads the contests its provider can
+//! enter.
+//!
+//! Only the admin may create a contest with a pool below
+//! [`MIN_SPONSORED_PRIZE_POOL`], at most [`MAX_ACTIVE_CONTESTS`] run at once,
+//! and a sponsor can no longer cancel their contest once it has entries.
+//!
+//! Contests are stored one per key. Those created before escrowed prizes sit
+//! in the old `Contests` map in their original layout until
+//! [`migrate_legacy_contests`] moves them.
+
 use crate::errors::ContestError;
+use crate::reputation::{self, TrustScoreTier};
 use crate::types::Signal;
-use soroban_sdk::{contracttype, Address, Env, Map, String, Vec};
+use soroban_sdk::{contracttype, token, Address, Env, Map, String, Vec};
+
+/// Most ranks a payout curve may pay.
+pub const MAX_PAYOUT_RANKS: u32 = 10;
+/// Smallest prize pool a non-admin creator may escrow.
+pub const MIN_SPONSORED_PRIZE_POOL: i128 = 1_000_0000000;
+/// Most contests that may be active at once.
+pub const MAX_ACTIVE_CONTESTS: u32 = 20;
+const BPS_TOTAL: u32 = 10_000;
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -9,6 +42,12 @@ pub enum ContestMetric {
     BestSuccessRate,
     MostVolume,
     MostFollowers,
+    /// Risk metrics over the entry's returns; entries with fewer than
+    /// `MIN_RISK_SAMPLES` returns rank last.
+    BestSharpe,
+    BestSortino,
+    LowestDrawdown,
+    BestProfitFactor,
 }
 
 #[contracttype]
@@ -41,26 +80,231 @@ pub struct Contest {
     pub min_signals: u32,
     pub entries: Map<Address, ContestEntry>,
     pub winners: Vec<Address>,
+    /// Total escrowed by all sponsors.
     pub prize_pool: i128,
     pub status: ContestStatus,
+    pub creator: Address,
+    pub prize_token: Address,
+    /// Share of the pool per rank, in bps.
+    pub payout_bps: Vec<u32>,
+    pub min_stake: i128,
+    pub min_trust_tier: TrustScoreTier,
+}
+
+/// A contest as stored before escrowed prizes and entry rules.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct LegacyContest {
+    pub id: u64,
+    pub name: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub metric: ContestMetric,
+    pub min_signals: u32,
+    pub entries: Map<Address, ContestEntry>,
+    pub winners: Vec<Address>,
+    pub prize_pool: i128,
+    pub status: ContestStatus,
+}
+
+/// Index record of an active contest: enough to decide whether a provider
+/// can enter without loading the contest.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ContestEligibility {
+    pub contest_id: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub min_stake: i128,
+}
+
+/// Prize and entry rules of a contest.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ContestPrizeConfig {
+    pub token: Address,
+    /// Share of the pool per rank in bps, summing to 10 000. Empty pays the
+    /// top three 50/30/20.
+    pub payout_bps: Vec<u32>,
+    pub min_stake: i128,
+    pub min_trust_tier: TrustScoreTier,
 }
 
 #[contracttype]
 #[derive(Clone)]
 pub enum ContestStorageKey {
     ContestCounter,
+    /// Pre-escrow contests: `Map<u64, LegacyContest>`.
     Contests,
     ActiveContests,
+    /// Escrowed amount per sponsor: `Map<Address, i128>`.
+    ContestSponsors(u64),
+    /// ROI (bps) of each trade or resolution applied to an entry during the
+    /// contest, kept out of the contests map: `Vec<i128>`.
+    EntryReturns(u64, Address),
+    Contest(u64),
+    /// Active contests whose minimum trust tier has this rank:
+    /// `Vec<ContestEligibility>`.
+    ActiveByTier(u32),
+}
+
+/// The provider's stake and trust tier rank.
+fn standing(env: &Env, provider: &Address) -> (i128, u32) {
+    let stake = crate::stake::get_stake_info(env, provider)
+        .map(|info| info.amount)
+        .unwrap_or(0);
+    let tier = reputation::get_trust_score(env, provider)
+        .map(|details| details.tier)
+        .unwrap_or(TrustScoreTier::NewUnproven);
+    (stake, reputation::tier_rank(&tier))
+}
+
+/// Whether `provider` meets the contest's stake and trust requirements.
+pub fn is_eligible(env: &Env, contest: &Contest, provider: &Address) -> bool {
+    let (stake, rank) = standing(env, provider);
+    stake >= contest.min_stake && rank >= reputation::tier_rank(&contest.min_trust_tier)
+}
+
+fn validate_payout_curve(env: &Env, payout_bps: Vec<u32>) -> Result<Vec<u32>, ContestError> {
+    if payout_bps.is_empty() {
+        let mut default = Vec::new(env);
+        for bps in [5_000u32, 3_000, 2_000] {
+            default.push_back(bps);
+        }
+        return Ok(default);
+    }
+    if payout_bps.len() > MAX_PAYOUT_RANKS {
+        return Err(ContestError::InvalidPayoutCurve);
+    }
+    let mut total: u32 = 0;
+    for bps in payout_bps.iter() {
+        if bps == 0 {
+            return Err(ContestError::InvalidPayoutCurve);
+        }
+        total = total.saturating_add(bps);
+    }
+    if total != BPS_TOTAL {
+        return Err(ContestError::InvalidPayoutCurve);
+    }
+    Ok(payout_bps)
+}
+
+fn load_contest(env: &Env, contest_id: u64) -> Option<Contest> {
+    env.storage()
+        .persistent()
+        .get(&ContestStorageKey::Contest(contest_id))
+}
+
+fn save_contest(env: &Env, contest: &Contest) {
+    env.storage()
+        .persistent()
+        .set(&ContestStorageKey::Contest(contest.id), contest);
 }
 
+fn get_active_by_tier(env: &Env, rank: u32) -> Vec<ContestEligibility> {
+    env.storage()
+        .persistent()
+        .get(&ContestStorageKey::ActiveByTier(rank))
+        .unwrap_or(Vec::new(env))
+}
+
+fn index_by_tier(env: &Env, contest: &Contest) {
+    let rank = reputation::tier_rank(&contest.min_trust_tier);
+    let mut indexed = get_active_by_tier(env, rank);
+    indexed.push_back(ContestEligibility {
+        contest_id: contest.id,
+        start_time: contest.start_time,
+        end_time: contest.end_time,
+        min_stake: contest.min_stake,
+    });
+    env.storage()
+        .persistent()
+        .set(&ContestStorageKey::ActiveByTier(rank), &indexed);
+}
+
+fn get_sponsors(env: &Env, contest_id: u64) -> Map<Address, i128> {
+    env.storage()
+        .persistent()
+        .get(&ContestStorageKey::ContestSponsors(contest_id))
+        .unwrap_or(Map::new(env))
+}
+
+pub fn get_sponsor_deposit(env: &Env, contest_id: u64, sponsor: &Address) -> i128 {
+    get_sponsors(env, contest_id)
+        .get(sponsor.clone())
+        .unwrap_or(0)
+}
+
+fn add_deposit(env: &Env, contest: &mut Contest, sponsor: &Address, amount: i128) {
+    token::Client::new(env, &contest.prize_token).transfer(
+        sponsor,
+        &env.current_contract_address(),
+        &amount,
+    );
+    let mut sponsors = get_sponsors(env, contest.id);
+    let deposited = sponsors.get(sponsor.clone()).unwrap_or(0);
+    sponsors.set(sponsor.clone(), deposited.saturating_add(amount));
+    env.storage()
+        .persistent()
+        .set(&ContestStorageKey::ContestSponsors(contest.id), &sponsors);
+    contest.prize_pool = contest.prize_pool.saturating_add(amount);
+}
+
+/// Return every sponsor's deposit. Returns the total refunded.
+fn refund_sponsors(env: &Env, contest: &Contest) -> i128 {
+    let client = token::Client::new(env, &contest.prize_token);
+    let mut refunded: i128 = 0;
+    for (sponsor, amount) in get_sponsors(env, contest.id).iter() {
+        if amount > 0 {
+            client.transfer(&env.current_contract_address(), &sponsor, &amount);
+            refunded = refunded.saturating_add(amount);
+        }
+    }
+    refunded
+}
+
+fn remove_active(env: &Env, contest: &Contest) {
+    let contest_id = contest.id;
+    let rank = reputation::tier_rank(&contest.min_trust_tier);
+    let mut indexed = get_active_by_tier(env, rank);
+    if let Some(pos) = indexed.iter().position(|e| e.contest_id == contest_id) {
+        indexed.remove(pos as u32);
+        env.storage()
+            .persistent()
+            .set(&ContestStorageKey::ActiveByTier(rank), &indexed);
+    }
+
+    let active_key = ContestStorageKey::ActiveContests;
+    let active: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&active_key)
+        .unwrap_or(Vec::new(env));
+    let mut new_active: Vec<u64> = Vec::new(env);
+    for i in 0..active.len() {
+        let id = active.get(i).unwrap();
+        if id != contest_id {
+            new_active.push_back(id);
+        }
+    }
+    env.storage().persistent().set(&active_key, &new_active);
+}
+
+/// Create a contest and escrow `prize_pool` of the prize token from `creator`.
+/// Unless `creator_is_admin`, the pool must be at least
+/// [`MIN_SPONSORED_PRIZE_POOL`].
+#[allow(clippy::too_many_arguments)]
 pub fn create_contest(
     env: &Env,
+    creator: &Address,
+    creator_is_admin: bool,
     name: String,
     start_time: u64,
     end_time: u64,
     metric: ContestMetric,
     min_signals: u32,
     prize_pool: i128,
+    config: ContestPrizeConfig,
 ) -> Result<u64, ContestError> {
     let current_time = env.ledger().timestamp();
 
@@ -70,15 +314,28 @@ pub fn create_contest(
     if end_time <= current_time {
         return Err(ContestError::InvalidTimeRange);
     }
-    if prize_pool < 0 {
+    if prize_pool < 0 || config.min_stake < 0 {
         return Err(ContestError::InvalidPrizePool);
     }
+    if !creator_is_admin && prize_pool < MIN_SPONSORED_PRIZE_POOL {
+        return Err(ContestError::PrizePoolTooSmall);
+    }
+    let active_key = ContestStorageKey::ActiveContests;
+    let mut active: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&active_key)
+        .unwrap_or(Vec::new(env));
+    if active.len() >= MAX_ACTIVE_CONTESTS {
+        return Err(ContestError::TooManyActiveContests);
+    }
+    let payout_bps = validate_payout_curve(env, config.payout_bps)?;
 
     let counter_key = ContestStorageKey::ContestCounter;
     let contest_id: u64 = env.storage().persistent().get(&counter_key).unwrap_or(0) + 1;
     env.storage().persistent().set(&counter_key, &contest_id);
 
-    let contest = Contest {
+    let mut contest = Contest {
         id: contest_id,
         name,
         start_time,
@@ -87,86 +344,88 @@ pub fn create_contest(
         min_signals,
         entries: Map::new(env),
         winners: Vec::new(env),
-        prize_pool,
+        prize_pool: 0,
         status: ContestStatus::Active,
+        creator: creator.clone(),
+        prize_token: config.token,
+        payout_bps,
+        min_stake: config.min_stake,
+        min_trust_tier: config.min_trust_tier,
     };
+    if prize_pool > 0 {
+        add_deposit(env, &mut contest, creator, prize_pool);
+    }
+    save_contest(env, &contest);
 
-    let contests_key = ContestStorageKey::Contests;
-    let mut contests: Map<u64, Contest> = env
-        .storage()
-        .persistent()
-        .get(&contests_key)
-        .unwrap_or(Map::new(env));
-    contests.set(contest_id, contest.clone());
-    env.storage().persistent().set(&contests_key, &contests);
-
-    let active_key = ContestStorageKey::ActiveContests;
-    let mut active: Vec<u64> = env
-        .storage()
-        .persistent()
-        .get(&active_key)
-        .unwrap_or(Vec::new(env));
     active.push_back(contest_id);
     env.storage().persistent().set(&active_key, &active);
+    index_by_tier(env, &contest);
 
     Ok(contest_id)
 }
 
 pub fn auto_enter_signal(env: &Env, signal: &Signal) -> Result<(), ContestError> {
-    let current_time = env.ledger().timestamp();
-    let active_key = ContestStorageKey::ActiveContests;
-    let active_contests: Vec<u64> = env
-        .storage()
-        .persistent()
-        .get(&active_key)
-        .unwrap_or(Vec::new(env));
+    enter_signal(
+        env,
+        &signal.provider,
+        signal.id,
+        signal.successful_executions,
+    )
+}
 
-    let contests_key = ContestStorageKey::Contests;
-    let mut contests: Map<u64, Contest> = env
-        .storage()
-        .persistent()
-        .get(&contests_key)
-        .unwrap_or(Map::new(env));
+/// Enter `signal_id` into every running contest `provider` is eligible for.
+/// Committed signals are entered this way at commit time, before the signal
+/// itself is published.
+pub fn enter_signal(
+    env: &Env,
+    provider: &Address,
+    signal_id: u64,
+    successful_executions: u32,
+) -> Result<(), ContestError> {
+    let current_time = env.ledger().timestamp();
+    let (stake, rank) = standing(env, provider);
 
-    for i in 0..active_contests.len() {
-        let contest_id = active_contests.get(i).unwrap();
-        if let Some(mut contest) = contests.get(contest_id) {
-            if contest.status == ContestStatus::Active
-                && current_time >= contest.start_time
-                && current_time <= contest.end_time
+    for tier in 0..=rank {
+        for indexed in get_active_by_tier(env, tier).iter() {
+            if current_time < indexed.start_time
+                || current_time > indexed.end_time
+                || stake < indexed.min_stake
             {
-                let provider = signal.provider.clone();
-                let mut entry = contest
-                    .entries
-                    .get(provider.clone())
-                    .unwrap_or(ContestEntry {
-                        provider: provider.clone(),
-                        signals_submitted: Vec::new(env),
-                        total_roi: 0,
-                        success_rate: 0,
-                        total_volume: 0,
-                        score: 0,
-                    });
-
-                entry.signals_submitted.push_back(signal.id);
-                // ROI/volume are applied when trades are recorded (see `apply_trade_to_contest_entries`).
-
-                let total_signals = entry.signals_submitted.len() as u32;
-                let successful = signal.successful_executions;
-                entry.success_rate = if total_signals > 0 {
-                    (successful * 100) / total_signals
-                } else {
-                    0
-                };
-
-                entry.score = calculate_contest_score(&entry, &contest.metric, env);
-                contest.entries.set(provider, entry);
-                contests.set(contest_id, contest);
+                continue;
             }
+            let Some(mut contest) = load_contest(env, indexed.contest_id) else {
+                continue;
+            };
+            if contest.status != ContestStatus::Active {
+                continue;
+            }
+            let mut entry = contest
+                .entries
+                .get(provider.clone())
+                .unwrap_or(ContestEntry {
+                    provider: provider.clone(),
+                    signals_submitted: Vec::new(env),
+                    total_roi: 0,
+                    success_rate: 0,
+                    total_volume: 0,
+                    score: 0,
+                });
+
+            entry.signals_submitted.push_back(signal_id);
+            // ROI/volume are applied when trades are recorded (see `apply_trade_to_contest_entries`).
+
+            let total_signals = entry.signals_submitted.len();
+            entry.success_rate = if total_signals > 0 {
+                (successful_executions * 100) / total_signals
+            } else {
+                0
+            };
+
+            entry.score = calculate_contest_score(env, contest.id, &entry, &contest.metric);
+            contest.entries.set(provider.clone(), entry);
+            save_contest(env, &contest);
         }
     }
-
-    env.storage().persistent().set(&contests_key, &contests);
     Ok(())
 }
 
@@ -186,17 +445,9 @@ pub fn apply_trade_to_contest_entries(
         .get(&active_key)
         .unwrap_or(Vec::new(env));
 
-    let contests_key = ContestStorageKey::Contests;
-    let mut contests: Map<u64, Contest> = env
-        .storage()
-        .persistent()
-        .get(&contests_key)
-        .unwrap_or(Map::new(env));
-
-    let mut any = false;
     for i in 0..active_contests.len() {
         let contest_id = active_contests.get(i).unwrap();
-        let Some(mut contest) = contests.get(contest_id) else {
+        let Some(mut contest) = load_contest(env, contest_id) else {
             continue;
         };
         if contest.status != ContestStatus::Active {
@@ -220,37 +471,60 @@ pub fn apply_trade_to_contest_entries(
         }
         entry.total_roi = entry.total_roi.saturating_add(trade_roi);
         entry.total_volume = entry.total_volume.saturating_add(volume);
-        entry.score = calculate_contest_score(&entry, &contest.metric, env);
+        let returns_key = ContestStorageKey::EntryReturns(contest_id, provider.clone());
+        let mut returns: Vec<i128> = env
+            .storage()
+            .persistent()
+            .get(&returns_key)
+            .unwrap_or(Vec::new(env));
+        crate::risk_metrics::push_capped(&mut returns, trade_roi);
+        env.storage().persistent().set(&returns_key, &returns);
+        entry.score = calculate_contest_score(env, contest_id, &entry, &contest.metric);
         contest.entries.set(provider.clone(), entry);
-        contests.set(contest_id, contest);
-        any = true;
-    }
-    if any {
-        env.storage().persistent().set(&contests_key, &contests);
+        save_contest(env, &contest);
     }
 }
 
-fn calculate_contest_score(entry: &ContestEntry, metric: &ContestMetric, _env: &Env) -> i128 {
+fn calculate_contest_score(
+    env: &Env,
+    contest_id: u64,
+    entry: &ContestEntry,
+    metric: &ContestMetric,
+) -> i128 {
     match metric {
         ContestMetric::HighestROI => entry.total_roi,
         ContestMetric::BestSuccessRate => entry.success_rate as i128,
         ContestMetric::MostVolume => entry.total_volume,
         ContestMetric::MostFollowers => 0, // Placeholder - would integrate with social module
+        ContestMetric::BestSharpe
+        | ContestMetric::BestSortino
+        | ContestMetric::LowestDrawdown
+        | ContestMetric::BestProfitFactor => {
+            let returns: Vec<i128> = env
+                .storage()
+                .persistent()
+                .get(&ContestStorageKey::EntryReturns(
+                    contest_id,
+                    entry.provider.clone(),
+                ))
+                .unwrap_or(Vec::new(env));
+            let risk = crate::risk_metrics::compute(&returns);
+            if risk.samples < crate::risk_metrics::MIN_RISK_SAMPLES {
+                return i128::MIN;
+            }
+            match metric {
+                ContestMetric::BestSharpe => risk.sharpe_bps,
+                ContestMetric::BestSortino => risk.sortino_bps,
+                ContestMetric::LowestDrawdown => -risk.max_drawdown_bps,
+                _ => risk.profit_factor_bps,
+            }
+        }
     }
 }
 
 pub fn finalize_contest(env: &Env, contest_id: u64) -> Result<Vec<Address>, ContestError> {
     let current_time = env.ledger().timestamp();
-    let contests_key = ContestStorageKey::Contests;
-    let mut contests: Map<u64, Contest> = env
-        .storage()
-        .persistent()
-        .get(&contests_key)
-        .unwrap_or(Map::new(env));
-
-    let mut contest = contests
-        .get(contest_id)
-        .ok_or(ContestError::ContestNotFound)?;
+    let mut contest = get_contest(env, contest_id)?;
 
     if current_time < contest.end_time {
         return Err(ContestError::ContestNotEnded);
@@ -265,7 +539,9 @@ pub fn finalize_contest(env: &Env, contest_id: u64) -> Result<Vec<Address>, Cont
     for i in 0..entry_keys.len() {
         let key = entry_keys.get(i).unwrap();
         if let Some(entry) = contest.entries.get(key) {
-            if entry.signals_submitted.len() >= contest.min_signals as u32 {
+            if entry.signals_submitted.len() >= contest.min_signals
+                && is_eligible(env, &contest, &entry.provider)
+            {
                 qualified_entries.push_back(entry);
             }
         }
@@ -284,74 +560,143 @@ pub fn finalize_contest(env: &Env, contest_id: u64) -> Result<Vec<Address>, Cont
     }
 
     let mut winners: Vec<Address> = Vec::new(env);
-    let winner_count = qualified_entries.len().min(3);
+    let winner_count = qualified_entries.len().min(contest.payout_bps.len());
 
     for i in 0..winner_count {
         let entry = qualified_entries.get(i).unwrap();
         winners.push_back(entry.provider.clone());
     }
 
-    if !winners.is_empty() {
-        distribute_prize_pool(env, &contest, &winners)?;
+    if winners.is_empty() {
+        refund_sponsors(env, &contest);
+    } else {
+        distribute_prize_pool(env, &contest, &winners);
     }
 
     contest.winners = winners.clone();
     contest.status = ContestStatus::Finalized;
-    contests.set(contest_id, contest);
-    env.storage().persistent().set(&contests_key, &contests);
-
-    // Remove from active contests
-    let active_key = ContestStorageKey::ActiveContests;
-    let mut active: Vec<u64> = env
-        .storage()
-        .persistent()
-        .get(&active_key)
-        .unwrap_or(Vec::new(env));
-    let mut new_active: Vec<u64> = Vec::new(env);
-    for i in 0..active.len() {
-        let id = active.get(i).unwrap();
-        if id != contest_id {
-            new_active.push_back(id);
-        }
-    }
-    env.storage().persistent().set(&active_key, &new_active);
+    save_contest(env, &contest);
+    remove_active(env, &contest);
 
     Ok(winners)
 }
 
-fn distribute_prize_pool(
-    env: &Env,
-    contest: &Contest,
-    winners: &Vec<Address>,
-) -> Result<(), ContestError> {
+/// Pay the escrowed pool to `winners` by the payout curve, scaled over the
+/// ranks actually paid. The last winner receives the rounding remainder.
+fn distribute_prize_pool(env: &Env, contest: &Contest, winners: &Vec<Address>) {
     if contest.prize_pool == 0 || winners.is_empty() {
-        return Ok(());
+        return;
     }
 
-    let percentages = [50, 30, 20]; // 1st: 50%, 2nd: 30%, 3rd: 20%
+    let mut paid_bps: i128 = 0;
+    for i in 0..winners.len() {
+        paid_bps += contest.payout_bps.get(i).unwrap() as i128;
+    }
 
-    for i in 0..winners.len().min(3) {
+    let client = token::Client::new(env, &contest.prize_token);
+    let mut distributed: i128 = 0;
+    for i in 0..winners.len() {
         let winner = winners.get(i).unwrap();
-        let prize = (contest.prize_pool * percentages[i as usize]) / 100;
+        let prize = if i + 1 == winners.len() {
+            contest.prize_pool - distributed
+        } else {
+            contest.prize_pool * contest.payout_bps.get(i).unwrap() as i128 / paid_bps
+        };
+        distributed += prize;
+        client.transfer(&env.current_contract_address(), &winner, &prize);
 
-        // Store prize allocation (actual token transfer would happen externally)
         let prize_key = (contest.id, winner.clone());
         env.storage().persistent().set(&prize_key, &prize);
     }
+}
 
-    Ok(())
+/// Add `amount` of the prize token to a running contest's pool.
+pub fn sponsor_contest(
+    env: &Env,
+    sponsor: &Address,
+    contest_id: u64,
+    amount: i128,
+) -> Result<i128, ContestError> {
+    if amount <= 0 {
+        return Err(ContestError::InvalidPrizePool);
+    }
+    let mut contest = get_contest(env, contest_id)?;
+    if contest.status != ContestStatus::Active {
+        return Err(ContestError::AlreadyFinalized);
+    }
+    if env.ledger().timestamp() >= contest.end_time {
+        return Err(ContestError::ContestEnded);
+    }
+    add_deposit(env, &mut contest, sponsor, amount);
+    save_contest(env, &contest);
+    Ok(contest.prize_pool)
+}
+
+/// Cancel an active contest and refund every sponsor. Authorisation is
+/// checked by the caller. Returns the total refunded.
+pub fn cancel_contest(env: &Env, contest_id: u64) -> Result<i128, ContestError> {
+    let mut contest = get_contest(env, contest_id)?;
+    if contest.status != ContestStatus::Active {
+        return Err(ContestError::AlreadyFinalized);
+    }
+    let refunded = refund_sponsors(env, &contest);
+    contest.status = ContestStatus::Cancelled;
+    save_contest(env, &contest);
+    remove_active(env, &contest);
+    Ok(refunded)
 }
 
 pub fn get_contest(env: &Env, contest_id: u64) -> Result<Contest, ContestError> {
-    let contests_key = ContestStorageKey::Contests;
-    let contests: Map<u64, Contest> = env
+    load_contest(env, contest_id).ok_or(ContestError::ContestNotFound)
+}
+
+/// Move every contest from the pre-escrow `Contests` map to its own key.
+/// Migrated contests pay in `prize_token` on the default curve with open
+/// entry. `funder` becomes the creator of each and escrows the advertised
+/// pool of those still active; pools of closed contests were already
+/// allocated and are kept as recorded. Returns the number migrated.
+pub fn migrate_legacy_contests(env: &Env, funder: &Address, prize_token: Address) -> u32 {
+    let legacy: Map<u64, LegacyContest> = env
         .storage()
         .persistent()
-        .get(&contests_key)
+        .get(&ContestStorageKey::Contests)
         .unwrap_or(Map::new(env));
-    contests
-        .get(contest_id)
-        .ok_or(ContestError::ContestNotFound)
+    let payout_bps = validate_payout_curve(env, Vec::new(env)).unwrap();
+
+    let mut migrated: u32 = 0;
+    for (id, old) in legacy.iter() {
+        let active = old.status == ContestStatus::Active;
+        let mut contest = Contest {
+            id,
+            name: old.name,
+            start_time: old.start_time,
+            end_time: old.end_time,
+            metric: old.metric,
+            min_signals: old.min_signals,
+            entries: old.entries,
+            winners: old.winners,
+            prize_pool: if active { 0 } else { old.prize_pool },
+            status: old.status,
+            creator: funder.clone(),
+            prize_token: prize_token.clone(),
+            payout_bps: payout_bps.clone(),
+            min_stake: 0,
+            min_trust_tier: TrustScoreTier::NewUnproven,
+        };
+        if active {
+            if old.prize_pool > 0 {
+                add_deposit(env, &mut contest, funder, old.prize_pool);
+            }
+            // Already listed in `ActiveContests`.
+            index_by_tier(env, &contest);
+        }
+        save_contest(env, &contest);
+        migrated += 1;
+    }
+    env.storage()
+        .persistent()
+        .remove(&ContestStorageKey::Contests);
+    migrated
 }
 
 pub fn get_active_contests(env: &Env) -> Vec<u64> {
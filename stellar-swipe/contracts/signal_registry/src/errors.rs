@@ -115,6 +115,11 @@ pub enum ImportError {
     BatchSizeExceeded = 406,
     EmptyData = 407,
     ParseError = 408,
+    /// The provider hit the submission rate limit; re-import to resume.
+    RateLimited = 409,
+    SignalLimitReached = 410,
+    /// Submission was refused (pause, ban or unknown provider account).
+    SubmissionRejected = 411,
 }
 
 #[contracterror]
@@ -155,6 +160,10 @@ pub enum ComboError {
     InvalidAmount = 611,
     TradingPaused = 612,
     CircuitBreakerTriggered = 613,
+    /// Caller is not the registered TradeExecutor.
+    Unauthorized = 614,
+    /// Reported legs do not match the combo's components.
+    InvalidLegs = 615,
 }
 
 #[contracterror]
@@ -169,6 +178,16 @@ pub enum ContestError {
     NotQualified = 805,
     TradingPaused = 806,
     CircuitBreakerTriggered = 807,
+    /// Payout shares must be non-zero, at most 10 ranks, and sum to 10 000.
+    InvalidPayoutCurve = 808,
+    NotContestCreator = 809,
+    ContestEnded = 810,
+    /// Only the admin may create a contest below the minimum prize pool.
+    PrizePoolTooSmall = 811,
+    TooManyActiveContests = 812,
+    /// The creator cannot cancel a contest that already has entries.
+    ContestHasEntries = 813,
+    Unauthorized = 814,
 }
 
 #[contracterror]
@@ -219,6 +238,8 @@ pub enum SignalOutcomeError {
     SignalNotFound = 1151,
     SignalNotClosed = 1152,
     OutcomeAlreadyRecorded = 1153,
+    /// Signals with resolution targets are scored by `resolve_signal`.
+    OracleResolved = 1154,
 }
 
 #[contracterror]
@@ -234,3 +255,110 @@ pub enum SubmissionError {
     MissingRationale = 1206,
     PriceUnreasonable = 1207,
 }
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ResolutionError {
+    NotConfigured = 1300,
+    SignalNotFound = 1301,
+    NoTargets = 1302,
+    AlreadyResolved = 1303,
+    InvalidTargets = 1304,
+    /// The oracle has no TWAP history at the horizon.
+    PriceUnavailable = 1306,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum BundleError {
+    BundleNotFound = 1400,
+    SignalNotFound = 1401,
+    NotSignalOwner = 1402,
+    /// A component signal is not active or has expired.
+    SignalNotActive = 1403,
+    /// Weights must be non-zero and sum to 10 000.
+    InvalidWeights = 1404,
+    InvalidComponentCount = 1405,
+    DuplicateSignal = 1406,
+    FeeTooHigh = 1407,
+    InvalidAmount = 1408,
+    /// The bundle was closed by its provider or has no live components left.
+    BundleClosed = 1409,
+    TradingPaused = 1410,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PrivateSignalError {
+    SignalNotFound = 1500,
+    /// The signal was not published as a private signal.
+    NotPrivate = 1501,
+    NotProvider = 1502,
+    /// Ciphertext, nonce payload or sealed key is empty.
+    EmptyCiphertext = 1503,
+    /// The envelope was sealed for an older key epoch.
+    StaleEpoch = 1504,
+    /// The subscriber has not registered an X25519 public key.
+    NoEncryptionKey = 1505,
+    SubscriptionRequired = 1506,
+    /// No UserPortfolio is configured to check subscriptions against.
+    PortfolioNotSet = 1507,
+    TooManyEnvelopes = 1508,
+    AlreadyRevealed = 1509,
+    /// The revealed plaintext does not hash to the stored commitment.
+    CommitmentMismatch = 1510,
+    /// The revealed price is not positive.
+    InvalidPrice = 1511,
+    /// A lapsed subscriber still holds the key; rotate before sealing more.
+    RotationRequired = 1512,
+    /// The reveal deadline has passed.
+    RevealWindowClosed = 1513,
+    /// The signal can still be revealed, so it cannot be expired yet.
+    RevealWindowOpen = 1514,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum CommitmentError {
+    NotFound = 1600,
+    NotProvider = 1601,
+    /// The commitment was already revealed or expired.
+    NotPending = 1602,
+    RevealWindowClosed = 1603,
+    /// The commitment can only be expired after its reveal deadline.
+    RevealWindowOpen = 1604,
+    /// The revealed signal does not hash to the commitment.
+    CommitmentMismatch = 1605,
+    /// Signal submission checks failed (pause, ban, rate limit, signal
+    /// limit or asset pair).
+    SignalRejected = 1606,
+    TradingPaused = 1607,
+    /// The provider already has a pending commitment on this asset pair.
+    AlreadyPending = 1608,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SearchError {
+    TooManySortKeys = 1700,
+    /// The cursor is malformed or was issued for different sort keys.
+    InvalidCursor = 1701,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum BenchmarkError {
+    /// No benchmark is configured, or none resolves for the signal.
+    NotConfigured = 1800,
+    SignalNotFound = 1801,
+    AlreadySnapshotted = 1802,
+    /// Snapshots must be taken within `SNAPSHOT_WINDOW_SECONDS` of creation.
+    SnapshotWindowClosed = 1803,
+    PriceUnavailable = 1804,
+}
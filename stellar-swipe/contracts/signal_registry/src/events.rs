@@ -1,5 +1,5 @@
 use crate::types::{Asset, MigrationProgress};
-use soroban_sdk::{contracttype, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{contracttype, Address, BytesN, Env, String, Symbol, Vec};
 
 // Horizon / indexer: first topic is only the event name (ScVal::Symbol);
 // all identifying fields live in a standard ScVal body (tuple or #[contracttype]).
@@ -124,6 +124,19 @@ pub fn emit_signal_status_changed(
         .publish(topics, (signal_id, provider, old_status, new_status));
 }
 
+/// Emitted when a keeper resolves a signal from oracle prices.
+pub fn emit_signal_resolved(
+    env: &Env,
+    signal_id: u64,
+    keeper: Address,
+    price: i128,
+    realized_roi_bps: i128,
+) {
+    let topics = (Symbol::new(env, "signal_resolved"), signal_id);
+    env.events()
+        .publish(topics, (keeper, price, realized_roi_bps));
+}
+
 pub fn emit_provider_stats_updated(
     env: &Env,
     provider: Address,
@@ -198,6 +211,85 @@ pub fn emit_combo_cancelled(env: &Env, combo_id: u64, provider: Address) {
     env.events().publish(topics, (combo_id, provider));
 }
 
+pub fn emit_bundle_created(
+    env: &Env,
+    bundle_id: u64,
+    provider: Address,
+    component_count: u32,
+    fee_bps: u32,
+) {
+    let topics = (Symbol::new(env, "bundle_created"), bundle_id);
+    env.events()
+        .publish(topics, (provider, component_count, fee_bps));
+}
+
+pub fn emit_bundle_copied(env: &Env, bundle_id: u64, user: Address, amount: i128, fee: i128) {
+    let topics = (Symbol::new(env, "bundle_copied"), bundle_id);
+    env.events().publish(topics, (user, amount, fee));
+}
+
+pub fn emit_bundle_rebalanced(env: &Env, bundle_id: u64, removed: u32, remaining: u32) {
+    let topics = (Symbol::new(env, "bundle_rebalanced"), bundle_id);
+    env.events().publish(topics, (removed, remaining));
+}
+
+pub fn emit_private_signal_created(env: &Env, signal_id: u64, provider: Address) {
+    let topics = (Symbol::new(env, "private_signal_created"), signal_id);
+    env.events().publish(topics, provider);
+}
+
+pub fn emit_signal_key_rotated(env: &Env, signal_id: u64, epoch: u32) {
+    let topics = (Symbol::new(env, "signal_key_rotated"), signal_id);
+    env.events().publish(topics, epoch);
+}
+
+pub fn emit_private_signal_revealed(env: &Env, signal_id: u64, price: i128, committed_at: u64) {
+    let topics = (Symbol::new(env, "private_signal_revealed"), signal_id);
+    env.events().publish(topics, (price, committed_at));
+}
+
+pub fn emit_signal_committed(env: &Env, commitment_id: u64, provider: Address, deadline: u64) {
+    let topics = (Symbol::new(env, "signal_committed"), commitment_id);
+    env.events().publish(topics, (provider, deadline));
+}
+
+pub fn emit_commitment_revealed(env: &Env, commitment_id: u64, signal_id: u64, committed_at: u64) {
+    let topics = (Symbol::new(env, "commitment_revealed"), commitment_id);
+    env.events().publish(topics, (signal_id, committed_at));
+}
+
+pub fn emit_commitment_expired(env: &Env, commitment_id: u64, provider: Address) {
+    let topics = (Symbol::new(env, "commitment_expired"), commitment_id);
+    env.events().publish(topics, provider);
+}
+
+pub fn emit_contest_sponsored(
+    env: &Env,
+    contest_id: u64,
+    sponsor: Address,
+    amount: i128,
+    pool: i128,
+) {
+    let topics = (Symbol::new(env, "contest_sponsored"), contest_id);
+    env.events().publish(topics, (sponsor, amount, pool));
+}
+
+pub fn emit_contest_cancelled(env: &Env, contest_id: u64, refunded: i128) {
+    let topics = (Symbol::new(env, "contest_cancelled"), contest_id);
+    env.events().publish(topics, refunded);
+}
+
+pub fn emit_signals_imported(
+    env: &Env,
+    provider: Address,
+    batch_id: BytesN<32>,
+    created: u32,
+    failed: u32,
+) {
+    let topics = (Symbol::new(env, "signals_imported"), provider);
+    env.events().publish(topics, (batch_id, created, failed));
+}
+
 pub fn emit_signal_updated(env: &Env, signal_id: u64, version: u32, updater: Address) {
     let topics = (Symbol::new(env, "signal_updated"),);
     env.events().publish(topics, (signal_id, version, updater));
@@ -1,4 +1,5 @@
-use soroban_sdk::{Address, Env, Map};
+use soroban_sdk::{Address, Env, IntoVal, Map, Symbol};
+use stellar_swipe_common::{quote_fee, AssetPair, FeeSchedule, FeeSide, MIN_TIER_RATE_BPS};
 
 use crate::admin;
 use crate::errors::FeeError;
@@ -41,7 +42,10 @@ pub fn calculate_fee(trade_amount: i128) -> Result<(i128, i128), FeeError> {
 /// Calculate fee breakdown (platform vs provider split)
 pub fn calculate_fee_breakdown(trade_amount: i128) -> Result<FeeBreakdown, FeeError> {
     let (total_fee, amount_after_fee) = calculate_fee(trade_amount)?;
+    split_fee(total_fee, amount_after_fee)
+}
 
+fn split_fee(total_fee: i128, amount_after_fee: i128) -> Result<FeeBreakdown, FeeError> {
     // Split fee: 70% platform, 30% provider
     let platform_fee = total_fee
         .checked_mul(PLATFORM_SHARE_PERCENTAGE as i128)
@@ -61,6 +65,58 @@ pub fn calculate_fee_breakdown(trade_amount: i128) -> Result<FeeBreakdown, FeeEr
     })
 }
 
+/// FeeCollector's global fee schedule, the one trades are charged under.
+/// Falls back to a flat `FEE_BPS` while no FeeCollector is registered or it
+/// cannot be reached.
+pub fn get_fee_schedule(env: &Env) -> FeeSchedule {
+    env.storage()
+        .instance()
+        .get::<_, Address>(&FeeStorageKey::FeeCollector)
+        .and_then(|fee_collector| {
+            env.try_invoke_contract::<FeeSchedule, soroban_sdk::Error>(
+                &fee_collector,
+                &Symbol::new(env, "fee_schedule"),
+                (None::<AssetPair>,).into_val(env),
+            )
+            .ok()
+            .and_then(|r| r.ok())
+        })
+        .unwrap_or_else(|| FeeSchedule::flat(env, FEE_BPS))
+}
+
+/// Register the FeeCollector previews read the fee schedule from.
+pub fn set_fee_collector(env: &Env, fee_collector: &Address) {
+    env.storage()
+        .instance()
+        .set(&FeeStorageKey::FeeCollector, fee_collector);
+}
+
+/// Fee breakdown under the tiered schedule for a trader with the given
+/// rolling 30-day volume and protocol-token stake. Quoted through
+/// `stellar_swipe_common::quote_fee`, the same code FeeCollector charges with.
+pub fn calculate_scheduled_fee_breakdown(
+    env: &Env,
+    side: FeeSide,
+    volume_usd: i128,
+    staked: i128,
+    trade_amount: i128,
+) -> Result<FeeBreakdown, FeeError> {
+    validate_trade_amount(trade_amount)?;
+    let quote = quote_fee(
+        &get_fee_schedule(env),
+        side,
+        volume_usd,
+        staked,
+        trade_amount,
+        MIN_TIER_RATE_BPS,
+    )
+    .map_err(|_| FeeError::ArithmeticOverflow)?;
+    if quote.fee == 0 {
+        return Err(FeeError::FeeRoundedToZero);
+    }
+    split_fee(quote.fee, quote.amount_after_fee)
+}
+
 /// Get or initialize treasury balances map
 fn get_treasury_balances(env: &Env) -> Map<Asset, i128> {
     env.storage()
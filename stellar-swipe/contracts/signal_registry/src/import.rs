@@ -1,69 +1,192 @@
+//! Bulk signal import from CSV or JSON.
+//!
+//! CSV: a header line, then `asset_pair,action,price,rationale,expiry_hours`
+//! with an optional sixth `external_id` column.
+//! JSON: an array of flat objects with the same keys. `price` and
+//! `expiry_hours` may be numbers or strings.
+//!
+//! Each parsed row is submitted through the normal `create_signal` path and
+//! gets an [`ImportRowResult`] in the report. Rows whose `external_id` the
+//! provider already imported are reported as duplicates and not created
+//! twice.
+//!
+//! Progress is kept per batch, keyed by the provider and the SHA-256 of the
+//! payload. Re-submitting the same payload after a partial failure (such as
+//! a rate limit) skips the rows that were already created and retries only
+//! the rest.
+
 use crate::errors::ImportError;
-use crate::types::SignalAction;
-use soroban_sdk::{Address, Bytes, Env, Map, String, Vec};
+use crate::types::{ImportFormat, ImportResultView, SignalAction};
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Env, Map, String, Vec};
 
 const MAX_BATCH_SIZE: u32 = 100;
 const MAX_RATIONALE_LEN: u32 = 500;
+const MAX_EXPIRY_HOURS: u32 = 720;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportRowStatus {
+    Created,
+    /// Passed validation in a `validate_only` run.
+    Valid,
+    /// Created by an earlier run of the same batch.
+    AlreadyImported,
+    /// The row's external id already maps to a signal.
+    Duplicate,
+    Failed,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportRowResult {
+    /// 1-based data row (CSV header and JSON brackets excluded); 0 for
+    /// errors about the payload as a whole.
+    pub row: u32,
+    pub status: ImportRowStatus,
+    pub signal_id: Option<u64>,
+    /// [`ImportError`] code for failed rows, 0 otherwise.
+    pub error_code: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ImportKey {
+    /// Rows of a batch already created: `Map<u32, u64>` (row -> signal id).
+    ImportBatch(Address, BytesN<32>),
+}
 
-pub struct ImportResult {
-    pub success_count: u32,
-    pub error_count: u32,
+/// A row that parsed and passed field validation.
+pub struct ParsedRow {
+    pub asset_pair: String,
+    pub action: SignalAction,
+    pub price: i128,
+    pub rationale: String,
+    pub expiry_hours: u32,
+    pub external_id: Option<String>,
 }
 
-pub fn import_signals_csv(
+/// Run an import. `create` submits a row as a signal and returns its id.
+pub fn import_signals(
     env: &Env,
-    _provider: &Address,
+    provider: &Address,
     data: Bytes,
+    format: ImportFormat,
     validate_only: bool,
-) -> ImportResult {
-    let mut success_count = 0;
-    let mut error_count = 0;
-
-    // Convert bytes to string for parsing
-    let data_vec = bytes_to_vec(&data);
-    let lines = parse_csv_lines(&data_vec);
-
-    if lines.is_empty() {
-        return ImportResult {
-            success_count: 0,
-            error_count: 1,
-        };
-    }
+    mut create: impl FnMut(&ParsedRow) -> Result<u64, ImportError>,
+) -> ImportResultView {
+    let batch_id: BytesN<32> = env.crypto().sha256(&data).into();
+    let mut report = ImportResultView {
+        batch_id: batch_id.clone(),
+        success_count: 0,
+        error_count: 0,
+        skipped_count: 0,
+        signal_ids: Vec::new(env),
+        rows: Vec::new(env),
+    };
+
+    let raw = bytes_to_vec(&data);
+    let parsed = match format {
+        ImportFormat::CSV => parse_csv_rows(env, &raw),
+        ImportFormat::JSON => parse_json_rows(env, &raw),
+        ImportFormat::TradingView | ImportFormat::TwitterParse => Err(ImportError::InvalidFormat),
+    };
+    let rows = match parsed {
+        Ok(rows) if !rows.is_empty() => rows,
+        Ok(_) => {
+            push_failure(&mut report, 0, ImportError::EmptyData);
+            return report;
+        }
+        Err(e) => {
+            push_failure(&mut report, 0, e);
+            return report;
+        }
+    };
 
-    // Skip header (first line)
-    for i in 1..lines.len().min(MAX_BATCH_SIZE as usize + 1) {
-        if success_count >= MAX_BATCH_SIZE {
-            error_count += 1;
+    let batch_key = ImportKey::ImportBatch(provider.clone(), batch_id);
+    let mut done: Map<u32, u64> = env
+        .storage()
+        .persistent()
+        .get(&batch_key)
+        .unwrap_or(Map::new(env));
+    let mut progressed = false;
+
+    for (i, parsed) in rows.into_iter().enumerate() {
+        let row = i as u32 + 1;
+        if row > MAX_BATCH_SIZE {
+            push_failure(&mut report, row, ImportError::BatchSizeExceeded);
             break;
         }
-
-        match validate_csv_line(&lines[i]) {
-            Ok(_) => {
-                if !validate_only {
-                    success_count += 1;
+        if let Some(signal_id) = done.get(row) {
+            report.skipped_count += 1;
+            push_row(
+                &mut report,
+                row,
+                ImportRowStatus::AlreadyImported,
+                Some(signal_id),
+            );
+            continue;
+        }
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                push_failure(&mut report, row, e);
+                continue;
+            }
+        };
+        if let Some(external_id) = &parsed.external_id {
+            if let Some(existing) = get_signal_by_external_id(env, provider, external_id) {
+                report.skipped_count += 1;
+                push_row(&mut report, row, ImportRowStatus::Duplicate, Some(existing));
+                continue;
+            }
+        }
+        if validate_only {
+            push_row(&mut report, row, ImportRowStatus::Valid, None);
+            continue;
+        }
+        match create(&parsed) {
+            Ok(signal_id) => {
+                if let Some(external_id) = &parsed.external_id {
+                    store_external_id_mapping(env, provider, external_id, signal_id);
                 }
+                done.set(row, signal_id);
+                progressed = true;
+                report.success_count += 1;
+                report.signal_ids.push_back(signal_id);
+                push_row(&mut report, row, ImportRowStatus::Created, Some(signal_id));
             }
-            Err(_) => error_count += 1,
+            Err(e) => push_failure(&mut report, row, e),
         }
     }
 
-    ImportResult {
-        success_count,
-        error_count,
+    if progressed {
+        env.storage().persistent().set(&batch_key, &done);
     }
+    report
 }
 
-pub fn import_signals_json(
-    _env: &Env,
-    _provider: &Address,
-    _data: Bytes,
-    _validate_only: bool,
-) -> ImportResult {
-    // JSON import placeholder
-    ImportResult {
-        success_count: 0,
-        error_count: 0,
-    }
+fn push_row(
+    report: &mut ImportResultView,
+    row: u32,
+    status: ImportRowStatus,
+    signal_id: Option<u64>,
+) {
+    report.rows.push_back(ImportRowResult {
+        row,
+        status,
+        signal_id,
+        error_code: 0,
+    });
+}
+
+fn push_failure(report: &mut ImportResultView, row: u32, error: ImportError) {
+    report.error_count += 1;
+    report.rows.push_back(ImportRowResult {
+        row,
+        status: ImportRowStatus::Failed,
+        signal_id: None,
+        error_code: error as u32,
+    });
 }
 
 fn bytes_to_vec(bytes: &Bytes) -> alloc::vec::Vec<u8> {
@@ -74,6 +197,18 @@ fn bytes_to_vec(bytes: &Bytes) -> alloc::vec::Vec<u8> {
     vec
 }
 
+type RowResults = alloc::vec::Vec<Result<ParsedRow, ImportError>>;
+
+fn parse_csv_rows(env: &Env, data: &[u8]) -> Result<RowResults, ImportError> {
+    let lines = parse_csv_lines(data);
+    // Skip header (first line)
+    Ok(lines
+        .iter()
+        .skip(1)
+        .map(|fields| validate_csv_line(env, fields))
+        .collect())
+}
+
 fn parse_csv_lines(data: &[u8]) -> alloc::vec::Vec<alloc::vec::Vec<alloc::vec::Vec<u8>>> {
     let mut lines = alloc::vec::Vec::new();
     let mut current_line = alloc::vec::Vec::new();
@@ -107,37 +242,214 @@ fn parse_csv_lines(data: &[u8]) -> alloc::vec::Vec<alloc::vec::Vec<alloc::vec::V
     lines
 }
 
-fn validate_csv_line(fields: &[alloc::vec::Vec<u8>]) -> Result<(), ImportError> {
+fn validate_csv_line(env: &Env, fields: &[alloc::vec::Vec<u8>]) -> Result<ParsedRow, ImportError> {
     if fields.len() < 5 {
         return Err(ImportError::InvalidFormat);
     }
+    let external_id = fields
+        .get(5)
+        .map(|f| trim_bytes(f))
+        .filter(|f| !f.is_empty());
+    validate_fields(
+        env,
+        &fields[0],
+        &fields[1],
+        parse_i128_from_bytes(&fields[2])?,
+        &fields[3],
+        parse_u32_from_bytes(&fields[4])?,
+        external_id,
+    )
+}
 
+fn validate_fields(
+    env: &Env,
+    asset_pair: &[u8],
+    action: &[u8],
+    price: i128,
+    rationale: &[u8],
+    expiry_hours: u32,
+    external_id: Option<&[u8]>,
+) -> Result<ParsedRow, ImportError> {
+    let asset_pair = trim_bytes(asset_pair);
     // Validate asset pair (must contain '/')
-    if !contains_byte(&fields[0], b'/') {
+    if !contains_byte(asset_pair, b'/') {
         return Err(ImportError::InvalidAssetPair);
     }
 
     // Validate action
-    validate_action(&fields[1])?;
+    let action = validate_action(action)?;
 
     // Validate price
-    let price = parse_i128_from_bytes(&fields[2])?;
     if price <= 0 {
         return Err(ImportError::InvalidPrice);
     }
 
     // Validate rationale
-    if fields[3].is_empty() || fields[3].len() > MAX_RATIONALE_LEN as usize {
+    if rationale.is_empty() || rationale.len() > MAX_RATIONALE_LEN as usize {
         return Err(ImportError::InvalidRationale);
     }
 
     // Validate expiry
-    let expiry = parse_u32_from_bytes(&fields[4])?;
-    if expiry == 0 || expiry > 720 {
+    if expiry_hours == 0 || expiry_hours > MAX_EXPIRY_HOURS {
         return Err(ImportError::InvalidExpiry);
     }
 
-    Ok(())
+    Ok(ParsedRow {
+        asset_pair: String::from_bytes(env, asset_pair),
+        action,
+        price,
+        rationale: String::from_bytes(env, rationale),
+        expiry_hours,
+        external_id: external_id.map(|id| String::from_bytes(env, id)),
+    })
+}
+
+/// Parse a JSON array of flat objects. Structural errors fail the whole
+/// payload; field errors fail only their row.
+fn parse_json_rows(env: &Env, data: &[u8]) -> Result<RowResults, ImportError> {
+    let mut parser = JsonParser { data, pos: 0 };
+    let mut rows = alloc::vec::Vec::new();
+    parser.expect(b'[')?;
+    if parser.peek() == Some(b']') {
+        parser.pos += 1;
+        return Ok(rows);
+    }
+    loop {
+        let object = parser.parse_object()?;
+        rows.push(json_row(env, &object));
+        match parser.next_token() {
+            Some(b',') => continue,
+            Some(b']') => break,
+            _ => return Err(ImportError::ParseError),
+        }
+    }
+    if parser.peek().is_some() {
+        return Err(ImportError::ParseError);
+    }
+    Ok(rows)
+}
+
+type JsonObject = alloc::vec::Vec<(alloc::vec::Vec<u8>, alloc::vec::Vec<u8>)>;
+
+fn json_row(env: &Env, object: &JsonObject) -> Result<ParsedRow, ImportError> {
+    let field = |name: &[u8]| {
+        object
+            .iter()
+            .find(|(key, _)| key.as_slice() == name)
+            .map(|(_, value)| value.as_slice())
+    };
+    let required = |name: &[u8]| field(name).ok_or(ImportError::InvalidFormat);
+    validate_fields(
+        env,
+        required(b"asset_pair")?,
+        required(b"action")?,
+        parse_i128_from_bytes(required(b"price")?)?,
+        required(b"rationale")?,
+        parse_u32_from_bytes(required(b"expiry_hours")?)?,
+        field(b"external_id").filter(|id| !id.is_empty()),
+    )
+}
+
+struct JsonParser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.data.len()
+            && matches!(self.data[self.pos], b' ' | b'\t' | b'\n' | b'\r')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.data.get(self.pos).copied()
+    }
+
+    fn next_token(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ImportError> {
+        match self.next_token() {
+            Some(b) if b == byte => Ok(()),
+            None => Err(ImportError::EmptyData),
+            _ => Err(ImportError::ParseError),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonObject, ImportError> {
+        self.expect(b'{')?;
+        let mut fields = alloc::vec::Vec::new();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(fields);
+        }
+        loop {
+            self.expect(b'"')?;
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            fields.push((key, value));
+            match self.next_token() {
+                Some(b',') => continue,
+                Some(b'}') => return Ok(fields),
+                _ => return Err(ImportError::ParseError),
+            }
+        }
+    }
+
+    /// String body after the opening quote, with simple escapes resolved.
+    fn parse_string(&mut self) -> Result<alloc::vec::Vec<u8>, ImportError> {
+        let mut out = alloc::vec::Vec::new();
+        while let Some(&byte) = self.data.get(self.pos) {
+            self.pos += 1;
+            match byte {
+                b'"' => return Ok(out),
+                b'\\' => {
+                    let escaped = *self.data.get(self.pos).ok_or(ImportError::ParseError)?;
+                    self.pos += 1;
+                    out.push(match escaped {
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        b'r' => b'\r',
+                        b'"' | b'\\' | b'/' => escaped,
+                        _ => return Err(ImportError::ParseError),
+                    });
+                }
+                _ => out.push(byte),
+            }
+        }
+        Err(ImportError::ParseError)
+    }
+
+    /// A string, number or literal; nested arrays and objects are rejected.
+    fn parse_value(&mut self) -> Result<alloc::vec::Vec<u8>, ImportError> {
+        match self.peek() {
+            Some(b'"') => {
+                self.pos += 1;
+                self.parse_string()
+            }
+            Some(b'{') | Some(b'[') | None => Err(ImportError::ParseError),
+            Some(_) => {
+                let start = self.pos;
+                while self.pos < self.data.len()
+                    && !matches!(
+                        self.data[self.pos],
+                        b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r'
+                    )
+                {
+                    self.pos += 1;
+                }
+                Ok(self.data[start..self.pos].to_vec())
+            }
+        }
+    }
 }
 
 fn contains_byte(data: &[u8], byte: u8) -> bool {
@@ -187,8 +499,11 @@ fn parse_i128_from_bytes(data: &[u8]) -> Result<i128, ImportError> {
         if byte == b'-' && !started {
             negative = true;
             started = true;
-        } else if byte >= b'0' && byte <= b'9' {
-            result = result * 10 + (byte - b'0') as i128;
+        } else if byte.is_ascii_digit() {
+            result = result
+                .checked_mul(10)
+                .and_then(|r| r.checked_add((byte - b'0') as i128))
+                .ok_or(ImportError::InvalidPrice)?;
             started = true;
         } else if byte == b'.' {
             break; // Ignore decimal part
@@ -228,11 +543,7 @@ fn trim_bytes(data: &[u8]) -> &[u8] {
 }
 
 fn to_upper(byte: u8) -> u8 {
-    if byte >= b'a' && byte <= b'z' {
-        byte - 32
-    } else {
-        byte
-    }
+    byte.to_ascii_uppercase()
 }
 
 // External ID mapping
@@ -1,12 +1,19 @@
-//! Pre-aggregated provider leaderboard with four sort metrics.
+//! Pre-aggregated provider leaderboard with one sort index per metric.
 //!
-//! Four sorted index arrays (one per metric) are maintained in persistent storage,
+//! Sorted index arrays (one per metric) are maintained in persistent storage,
 //! each capped at INDEX_CAPACITY. Updated on every signal close via
 //! update_leaderboard_index. Queries are O(1) storage reads.
 //!
 //! Qualification: provider must have >= MIN_CLOSED_SIGNALS (10) closed signals.
+//!
+//! Metrics kept in their own per-provider records (benchmark alpha and
+//! information ratio, and the risk metrics) are ranked in slim
+//! [`MetricIndexEntry`] indices that copy only the ranked value, so
+//! [`IndexEntry`] keeps its original layout. A provider only enters those
+//! indices once the metric is reported, and indices they are not in are not
+//! rewritten.
 
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, TryFromVal, Val, Vec};
 
 use crate::social;
 use crate::stake;
@@ -26,6 +33,18 @@ pub enum ProviderMetric {
     ByTotalAdopters,
     ByTotalProfitDelta,
     ByStake,
+    /// Average alpha over the benchmark, in bps.
+    ByAlpha,
+    /// Average alpha over tracking error, scaled by 10,000.
+    ByInformationRatio,
+    /// Sharpe ratio of recent closed signals, scaled by 10,000.
+    BySharpe,
+    /// Sortino ratio of recent closed signals, scaled by 10,000.
+    BySortino,
+    /// Smallest max drawdown ranks first; `metric_value` is the drawdown in bps.
+    ByMaxDrawdown,
+    /// Gross profit over gross loss, scaled by 10,000.
+    ByProfitFactor,
 }
 
 #[contracttype]
@@ -59,6 +78,12 @@ pub enum LeaderboardKey {
     AdoptersIndex,
     ProfitDeltaIndex,
     StakeIndex,
+    AlphaIndex,
+    InformationRatioIndex,
+    SharpeIndex,
+    SortinoIndex,
+    DrawdownIndex,
+    ProfitFactorIndex,
 }
 
 // ── Index entry ───────────────────────────────────────────────────────────────
@@ -85,83 +110,162 @@ pub struct IndexEntry {
     pub registered_at: u64,
 }
 
+/// Entry of an index over a single metric read from another per-provider
+/// record. Same tie-breaking rule as [`IndexEntry`].
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct MetricIndexEntry {
+    pub provider: Address,
+    pub closed_signals: u32,
+    pub total_adopters: u32,
+    pub value: i128,
+    pub verified: bool,
+    pub registered_at: u64,
+}
+
+trait Ranked {
+    fn provider(&self) -> &Address;
+    fn registered_at(&self) -> u64;
+    fn closed_signals(&self) -> u32;
+    fn total_adopters(&self) -> u32;
+}
+
+impl Ranked for IndexEntry {
+    fn provider(&self) -> &Address {
+        &self.provider
+    }
+    fn registered_at(&self) -> u64 {
+        self.registered_at
+    }
+    fn closed_signals(&self) -> u32 {
+        self.closed_signals
+    }
+    fn total_adopters(&self) -> u32 {
+        self.total_adopters
+    }
+}
+
+impl Ranked for MetricIndexEntry {
+    fn provider(&self) -> &Address {
+        &self.provider
+    }
+    fn registered_at(&self) -> u64 {
+        self.registered_at
+    }
+    fn closed_signals(&self) -> u32 {
+        self.closed_signals
+    }
+    fn total_adopters(&self) -> u32 {
+        self.total_adopters
+    }
+}
+
 // ── Internal helpers ──────────────────────────────────────────────────────────
 
-fn load_index(env: &Env, key: LeaderboardKey) -> Vec<IndexEntry> {
+fn load_index<T>(env: &Env, key: LeaderboardKey) -> Vec<T>
+where
+    T: soroban_sdk::IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
     env.storage()
         .persistent()
         .get(&key)
         .unwrap_or_else(|| Vec::new(env))
 }
 
-fn save_index(env: &Env, key: LeaderboardKey, index: &Vec<IndexEntry>) {
+fn save_index<T>(env: &Env, key: LeaderboardKey, index: &Vec<T>)
+where
+    T: soroban_sdk::IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
     env.storage().persistent().set(&key, index);
 }
 
-fn is_qualified(entry: &IndexEntry) -> bool {
-    entry.closed_signals >= MIN_CLOSED_SIGNALS && entry.total_adopters > 0
+fn is_qualified<T: Ranked>(entry: &T) -> bool {
+    entry.closed_signals() >= MIN_CLOSED_SIGNALS && entry.total_adopters() > 0
 }
 
-fn upsert_sorted<F>(env: &Env, index: &mut Vec<IndexEntry>, entry: IndexEntry, score_fn: F)
+fn upsert_sorted<T, F>(index: &mut Vec<T>, entry: T, score_fn: F)
 where
-    F: Fn(&IndexEntry) -> i128,
+    T: Ranked + Clone + soroban_sdk::IntoVal<Env, Val> + TryFromVal<Env, Val>,
+    F: Fn(&T) -> i128,
 {
-    let mut without: Vec<IndexEntry> = Vec::new(env);
-    for i in 0..index.len() {
-        let e = index.get(i).unwrap();
-        if e.provider != entry.provider {
-            without.push_back(e);
-        }
+    // Edit the index in place: host-side remove/insert avoid rebuilding it.
+    if let Some(pos) = index.iter().position(|e| e.provider() == entry.provider()) {
+        index.remove(pos as u32);
     }
 
     if !is_qualified(&entry) {
-        *index = without;
         return;
     }
 
     let entry_score = score_fn(&entry);
-    let mut insert_at = without.len();
-    for i in 0..without.len() {
-        let existing = without.get(i).unwrap();
+    let mut insert_at = index.len();
+    for (i, existing) in index.iter().enumerate() {
         let existing_score = score_fn(&existing);
         if existing_score < entry_score {
-            insert_at = i;
+            insert_at = i as u32;
             break;
         }
         // Tie-break: same primary score
         if existing_score == entry_score {
             // Rule 1: earlier registration timestamp ranks higher
-            if entry.registered_at < existing.registered_at {
-                insert_at = i;
+            if entry.registered_at() < existing.registered_at() {
+                insert_at = i as u32;
                 break;
             }
-            if entry.registered_at == existing.registered_at {
+            if entry.registered_at() == existing.registered_at() {
                 // Rule 2: lexicographic address bytes — smaller raw bytes ranks higher
-                let entry_bytes = entry.provider.to_string();
-                let existing_bytes = existing.provider.to_string();
+                let entry_bytes = entry.provider().to_string();
+                let existing_bytes = existing.provider().to_string();
                 if entry_bytes < existing_bytes {
-                    insert_at = i;
+                    insert_at = i as u32;
                     break;
                 }
             }
         }
     }
 
-    let mut result: Vec<IndexEntry> = Vec::new(env);
-    for i in 0..insert_at {
-        result.push_back(without.get(i).unwrap());
+    if insert_at >= INDEX_CAPACITY {
+        return;
     }
-    result.push_back(entry);
-    for i in insert_at..without.len() {
-        result.push_back(without.get(i).unwrap());
+    index.insert(insert_at, entry);
+    while index.len() > INDEX_CAPACITY {
+        index.pop_back();
     }
+}
 
-    let cap = INDEX_CAPACITY.min(result.len());
-    let mut capped: Vec<IndexEntry> = Vec::new(env);
-    for i in 0..cap {
-        capped.push_back(result.get(i).unwrap());
+/// Rank `entry`'s provider by `value` in the metric index at `key`, or drop
+/// them from it when the metric is not yet reported (`None`). Nothing is
+/// written when an unranked provider was not in the index to begin with.
+fn update_metric_index<F>(
+    env: &Env,
+    key: LeaderboardKey,
+    entry: &IndexEntry,
+    value: Option<i128>,
+    score_fn: F,
+) where
+    F: Fn(&MetricIndexEntry) -> i128,
+{
+    let mut index: Vec<MetricIndexEntry> = load_index(env, key.clone());
+    match value {
+        Some(value) => {
+            let metric_entry = MetricIndexEntry {
+                provider: entry.provider.clone(),
+                closed_signals: entry.closed_signals,
+                total_adopters: entry.total_adopters,
+                value,
+                verified: entry.verified,
+                registered_at: entry.registered_at,
+            };
+            upsert_sorted(&mut index, metric_entry, score_fn);
+        }
+        None => {
+            let Some(pos) = index.iter().position(|e| e.provider == entry.provider) else {
+                return;
+            };
+            index.remove(pos as u32);
+        }
     }
-    *index = capped;
+    save_index(env, key, &index);
 }
 
 // ── Public API ────────────────────────────────────────────────────────────────
@@ -182,6 +286,9 @@ pub fn update_leaderboard_index(env: &Env, provider: Address, stats: &ProviderPe
         .map(|p| p.created_at)
         .unwrap_or(0);
 
+    let benchmark = crate::benchmark::provider_metrics(env, &provider);
+    let risk = crate::risk_metrics::provider_metrics(env, &provider);
+
     let entry = IndexEntry {
         provider: provider.clone(),
         closed_signals,
@@ -194,21 +301,69 @@ pub fn update_leaderboard_index(env: &Env, provider: Address, stats: &ProviderPe
     };
 
     let mut sr = load_index(env, LeaderboardKey::SuccessRateIndex);
-    upsert_sorted(env, &mut sr, entry.clone(), |e| e.success_rate as i128);
+    upsert_sorted(&mut sr, entry.clone(), |e| e.success_rate as i128);
     save_index(env, LeaderboardKey::SuccessRateIndex, &sr);
 
     let mut ad = load_index(env, LeaderboardKey::AdoptersIndex);
-    upsert_sorted(env, &mut ad, entry.clone(), |e| e.total_adopters as i128);
+    upsert_sorted(&mut ad, entry.clone(), |e| e.total_adopters as i128);
     save_index(env, LeaderboardKey::AdoptersIndex, &ad);
 
     let mut pd = load_index(env, LeaderboardKey::ProfitDeltaIndex);
-    upsert_sorted(env, &mut pd, entry.clone(), |e| e.total_profit_delta);
+    upsert_sorted(&mut pd, entry.clone(), |e| e.total_profit_delta);
     save_index(env, LeaderboardKey::ProfitDeltaIndex, &pd);
 
     let mut sk = load_index(env, LeaderboardKey::StakeIndex);
-    upsert_sorted(env, &mut sk, entry, |e| e.stake_amount);
+    upsert_sorted(&mut sk, entry.clone(), |e| e.stake_amount);
     save_index(env, LeaderboardKey::StakeIndex, &sk);
 
+    // Benchmark metrics are only reported once enough signals have an alpha.
+    let benchmark_ready = benchmark.samples >= crate::benchmark::MIN_BENCHMARK_SAMPLES;
+    update_metric_index(
+        env,
+        LeaderboardKey::AlphaIndex,
+        &entry,
+        benchmark_ready.then_some(benchmark.avg_alpha_bps),
+        |e| e.value,
+    );
+    update_metric_index(
+        env,
+        LeaderboardKey::InformationRatioIndex,
+        &entry,
+        benchmark_ready.then_some(benchmark.information_ratio_bps),
+        |e| e.value,
+    );
+
+    let risk_ready = risk.samples >= crate::risk_metrics::MIN_RISK_SAMPLES;
+    update_metric_index(
+        env,
+        LeaderboardKey::SharpeIndex,
+        &entry,
+        risk_ready.then_some(risk.sharpe_bps),
+        |e| e.value,
+    );
+    update_metric_index(
+        env,
+        LeaderboardKey::SortinoIndex,
+        &entry,
+        risk_ready.then_some(risk.sortino_bps),
+        |e| e.value,
+    );
+    // Smallest drawdown ranks first.
+    update_metric_index(
+        env,
+        LeaderboardKey::DrawdownIndex,
+        &entry,
+        risk_ready.then_some(risk.max_drawdown_bps),
+        |e| -e.value,
+    );
+    update_metric_index(
+        env,
+        LeaderboardKey::ProfitFactorIndex,
+        &entry,
+        risk_ready.then_some(risk.profit_factor_bps),
+        |e| e.value,
+    );
+
     env.events()
         .publish((symbol_short!("lb_upd"), provider), stats.success_rate);
 }
@@ -229,11 +384,38 @@ pub fn get_provider_leaderboard(
         ProviderMetric::ByTotalAdopters => LeaderboardKey::AdoptersIndex,
         ProviderMetric::ByTotalProfitDelta => LeaderboardKey::ProfitDeltaIndex,
         ProviderMetric::ByStake => LeaderboardKey::StakeIndex,
+        ProviderMetric::ByAlpha => LeaderboardKey::AlphaIndex,
+        ProviderMetric::ByInformationRatio => LeaderboardKey::InformationRatioIndex,
+        ProviderMetric::BySharpe => LeaderboardKey::SharpeIndex,
+        ProviderMetric::BySortino => LeaderboardKey::SortinoIndex,
+        ProviderMetric::ByMaxDrawdown => LeaderboardKey::DrawdownIndex,
+        ProviderMetric::ByProfitFactor => LeaderboardKey::ProfitFactorIndex,
     };
 
-    let index = load_index(env, key);
-    let take = limit.min(index.len());
     let mut result = Vec::new(env);
+    if !matches!(
+        metric,
+        ProviderMetric::BySuccessRate
+            | ProviderMetric::ByTotalAdopters
+            | ProviderMetric::ByTotalProfitDelta
+            | ProviderMetric::ByStake
+    ) {
+        let index: Vec<MetricIndexEntry> = load_index(env, key);
+        for i in 0..limit.min(index.len()) {
+            let e = index.get(i).unwrap();
+            result.push_back(ProviderLeaderboardEntry {
+                rank: i + 1,
+                provider: e.provider,
+                metric_value: e.value,
+                total_signals: e.closed_signals,
+                verified: e.verified,
+            });
+        }
+        return result;
+    }
+
+    let index: Vec<IndexEntry> = load_index(env, key);
+    let take = limit.min(index.len());
 
     for i in 0..take {
         let e = index.get(i).unwrap();
@@ -241,7 +423,8 @@ pub fn get_provider_leaderboard(
             ProviderMetric::BySuccessRate => e.success_rate as i128,
             ProviderMetric::ByTotalAdopters => e.total_adopters as i128,
             ProviderMetric::ByTotalProfitDelta => e.total_profit_delta,
-            ProviderMetric::ByStake => e.stake_amount,
+            // ByStake; metric indices were returned above.
+            _ => e.stake_amount,
         };
         result.push_back(ProviderLeaderboardEntry {
             rank: i + 1,
@@ -441,6 +624,41 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_risk_metrics_ranked_once_reported() {
+        let env = Env::default();
+        let cid = env.register(TestContract, ());
+        env.as_contract(&cid, || {
+            let steady = Address::generate(&env);
+            let volatile = Address::generate(&env);
+            let newcomer = Address::generate(&env);
+            for roi in [100i128, 120, 90, 110, 100] {
+                crate::risk_metrics::record_close(&env, &steady, roi, 3_600);
+            }
+            for roi in [900i128, -800, 700, -600, 500] {
+                crate::risk_metrics::record_close(&env, &volatile, roi, 3_600);
+            }
+            crate::risk_metrics::record_close(&env, &newcomer, 1_000, 3_600);
+            for p in [&steady, &volatile, &newcomer] {
+                update_leaderboard_index(&env, p.clone(), &make_stats(6000, 10, 100, 6, 5));
+            }
+
+            let lb = get_provider_leaderboard(&env, ProviderMetric::BySharpe, 10);
+            assert_eq!(lb.len(), 2);
+            assert_eq!(lb.get(0).unwrap().provider, steady);
+            assert_eq!(lb.get(1).unwrap().provider, volatile);
+
+            let lb = get_provider_leaderboard(&env, ProviderMetric::ByMaxDrawdown, 10);
+            assert_eq!(lb.get(0).unwrap().provider, steady);
+            assert_eq!(lb.get(0).unwrap().metric_value, 0);
+            assert!(lb.get(1).unwrap().metric_value > 0);
+
+            // The legacy indices still rank everyone.
+            let lb = get_provider_leaderboard(&env, ProviderMetric::BySuccessRate, 10);
+            assert_eq!(lb.len(), 3);
+        });
+    }
+
     #[test]
     fn test_under_min_signals_excluded() {
         let env = Env::default();
@@ -540,8 +758,8 @@ mod tests {
             let score = 7500u32;
             let mut idx = Vec::new(&env);
             // Insert later-registered first, then earlier-registered
-            upsert_sorted(&env, &mut idx, make_entry(&env, p_late.clone(), score, 2000), |e| e.success_rate as i128);
-            upsert_sorted(&env, &mut idx, make_entry(&env, p_early.clone(), score, 1000), |e| e.success_rate as i128);
+            upsert_sorted(&mut idx, make_entry(&env, p_late.clone(), score, 2000), |e| e.success_rate as i128);
+            upsert_sorted(&mut idx, make_entry(&env, p_early.clone(), score, 1000), |e| e.success_rate as i128);
 
             // Earlier registration (t=1000) should rank first
             assert_eq!(idx.get(0).unwrap().provider, p_early);
@@ -590,8 +808,8 @@ mod tests {
             let mut idx = Vec::new(&env);
             // p_high has score 9000, p_low has score 6000
             // p_high registered later — tie-break would put p_low first if primary score didn't dominate
-            upsert_sorted(&env, &mut idx, make_entry(&env, p_high.clone(), 9000, 5000), |e| e.success_rate as i128);
-            upsert_sorted(&env, &mut idx, make_entry(&env, p_low.clone(), 6000, 1000), |e| e.success_rate as i128);
+            upsert_sorted(&mut idx, make_entry(&env, p_high.clone(), 9000, 5000), |e| e.success_rate as i128);
+            upsert_sorted(&mut idx, make_entry(&env, p_low.clone(), 6000, 1000), |e| e.success_rate as i128);
 
             // Higher primary score must still win regardless of registration time
             assert_eq!(idx.get(0).unwrap().provider, p_high);
@@ -6,9 +6,12 @@ static ALLOC: dlmalloc::GlobalDlmalloc = dlmalloc::GlobalDlmalloc;
 
 mod admin;
 mod analytics;
+mod benchmark;
+mod bundles;
 mod categories;
 mod collaboration;
 mod combos;
+mod commitments;
 mod community_voting;
 mod contests;
 mod cross_chain;
@@ -23,12 +26,16 @@ mod migration;
 mod ml_scoring;
 mod multisig_approvals;
 mod performance;
+mod private_signals;
 mod providers;
 mod query;
 mod reports;
 pub mod reputation;
+mod resolution;
+mod risk_metrics;
 mod scheduling;
 mod scoring;
+mod search;
 mod social;
 mod stake;
 mod storage_monitor;
@@ -52,21 +59,22 @@ use admin::{
 use shared::version::{set_contract_version, SIGNAL_REGISTRY_VERSION};
 use stellar_swipe_common::emergency::{PauseState, CAT_SIGNALS, CAT_TRADING};
 use stellar_swipe_common::rate_limit::{self as rl, ActionType as RLAction, RateLimitConfig};
-use stellar_swipe_common::SECONDS_PER_30_DAY_MONTH;
+use stellar_swipe_common::{FeeSchedule, FeeSide, SECONDS_PER_30_DAY_MONTH};
 
 use combos::{
     cancel_combo, create_combo_signal, execute_combo_signal, get_combo, get_combo_executions_pub,
-    get_combo_performance, ComboExecution, ComboPerformanceSummary, ComboSignal, ComboType,
-    ComponentExecution, ComponentSignal,
+    get_combo_performance, record_combo_execution, ComboExecution, ComboPerformanceSummary,
+    ComboSignal, ComboType, ComponentExecution, ComponentSignal,
 };
 use community_voting::{
-    get_dispute, process_appeal_timeout, resolve_appeal, resolve_dispute, submit_appeal,
-    DisputeError, DisputeRecord,
+    get_dispute, get_dispute_outcome, get_dispute_outcome_history, process_appeal_timeout,
+    resolve_appeal, resolve_dispute, submit_appeal, DisputeError, DisputeOutcome, DisputeRecord,
 };
-use contests::{Contest, ContestEntry, ContestMetric, ContestStatus};
+use contests::{Contest, ContestEntry, ContestMetric, ContestPrizeConfig, ContestStatus};
 use errors::{
-    AdminError, AiScoreError, ComboError, ContestError, CrossChainError, SignalEditError,
-    SignalOutcomeError, TemplateError, VersioningError,
+    AdminError, AiScoreError, BenchmarkError, BundleError, ComboError, CommitmentError,
+    ContestError, CrossChainError, PrivateSignalError, ResolutionError, SearchError,
+    SignalEditError, SignalOutcomeError, TemplateError, VersioningError,
 };
 pub use leaderboard::{
     get_leaderboard as get_leaderboard_internal, update_leaderboard_index, LeaderboardMetric,
@@ -79,8 +87,8 @@ use reputation::{
     TrustScoreDetails, TrustScoreTier,
 };
 use soroban_sdk::{
-    contract, contractimpl, contracttype, Address, Bytes, Env, IntoVal, Map, String, Symbol, Val,
-    Vec,
+    contract, contractimpl, contracttype, Address, Bytes, BytesN, Env, IntoVal, Map, String,
+    Symbol, Val, Vec,
 };
 use stellar_swipe_common::{health_uninitialized, placeholder_admin, HealthStatus};
 use stellar_swipe_common::{validate_asset_pair as validate_asset_pair_common, AssetPairError};
@@ -89,7 +97,7 @@ pub use template_presets::{SignalTemplateOverrides, SignalTemplatePreset, Stored
 pub use templates::SignalTemplate;
 use templates::DEFAULT_TEMPLATE_EXPIRY_HOURS;
 use types::{
-    AddressMapping, Asset, CrossChainSignal, ImportResultView, ProviderMonthlyReport,
+    AddressMapping, Asset, CrossChainSignal, ImportFormat, ImportResultView, ProviderMonthlyReport,
     RecurrencePattern, Signal, SignalData, SignalEditInput, SignalPerformanceView, SignalSummary,
     SortOption, SyncStatus, TradeExecution,
 };
@@ -124,6 +132,8 @@ pub enum StorageKey {
     ComboCounter,
     Combos,
     ComboExecutions(u64),
+    /// Cumulative realized P&L per combo component (`Vec<i128>`, combo order).
+    ComboLegPnl(u64),
     CrossChainSignals(String, String), // (source_chain, source_signal_id)
     AddressMappings(String, String),   // (source_chain, source_address)
     /// Per-category index of active signal IDs for efficient filtering (Issue #171)
@@ -768,6 +778,35 @@ impl SignalRegistry {
         tags: Vec<String>,
         risk_level: RiskLevel,
     ) -> Result<u64, AdminError> {
+        let id = Self::next_signal_id(env);
+        let signal = Self::prepare_signal(
+            env, id, provider, asset_pair, action, price, rationale, expiry, category, tags,
+            risk_level,
+        )?;
+        // Auto-enter signal into active contests (before moving signal)
+        let _ = contests::auto_enter_signal(env, &signal);
+        // Every signal is measured against the benchmark when one can be
+        // priced, so alpha is not limited to the signals a provider opts in.
+        let _ = benchmark::snapshot_now(env, id, &signal.asset_pair, &signal.category);
+        Self::insert_signal(env, signal);
+        Ok(id)
+    }
+
+    /// Run the submission checks and build a new signal under `id`, without
+    /// storing it.
+    fn prepare_signal(
+        env: &Env,
+        id: u64,
+        provider: Address,
+        asset_pair: String,
+        action: SignalAction,
+        price: i128,
+        rationale: String,
+        expiry: u64,
+        category: SignalCategory,
+        tags: Vec<String>,
+        risk_level: RiskLevel,
+    ) -> Result<Signal, AdminError> {
         // Check if signals are paused
         admin::require_not_paused(env, String::from_str(env, CAT_SIGNALS))?;
         admin::require_not_paused(env, String::from_str(env, CAT_TRADING))?;
@@ -832,12 +871,11 @@ impl SignalRegistry {
             panic!("expiry exceeds max 30 days");
         }
 
-        let id = Self::next_signal_id(env);
         let rationale_hash = rationale.clone();
 
-        let signal = Signal {
+        Ok(Signal {
             id,
-            provider: provider.clone(),
+            provider,
             asset_pair,
             action,
             price,
@@ -852,8 +890,8 @@ impl SignalRegistry {
             total_volume: 0,
             total_roi: 0,
             // Categorization fields
-            category: category.clone(),
-            tags: unique_tags.clone(),
+            category,
+            tags: unique_tags,
             risk_level,
             // Collaboration field
             is_collaborative: false,
@@ -866,10 +904,15 @@ impl SignalRegistry {
             warning_emitted: false,
             benchmark_return_bps: None,
             alpha_bps: None,
-        };
+        })
+    }
 
-        // Auto-enter signal into active contests (before moving signal)
-        let _ = contests::auto_enter_signal(env, &signal);
+    /// Publish a prepared signal: store it and add it to the tag, search and
+    /// category indices. Contest entry is left to the caller.
+    fn insert_signal(env: &Env, signal: Signal) {
+        let id = signal.id;
+        let provider = signal.provider.clone();
+        let category = signal.category.clone();
 
         // Store signal
         let mut signals = Self::get_signals_map(env);
@@ -878,7 +921,9 @@ impl SignalRegistry {
         validation::increment_provider_active_count(env, &provider);
 
         // Update tag popularity
-        categories::increment_tag_popularity(env, &unique_tags);
+        let stored = signals.get(id).unwrap();
+        categories::increment_tag_popularity(env, &stored.tags);
+        search::index_signal(env, &stored);
 
         // Add to per-category index for efficient filtering (Issue #171)
         let mut cat_map = Self::get_category_index_map(env);
@@ -896,8 +941,6 @@ impl SignalRegistry {
             // Record first signal time for trust score calculation
             reputation::record_first_signal(env, &provider);
         }
-
-        Ok(id)
     }
 
     pub fn get_signal(env: Env, signal_id: u64) -> Option<Signal> {
@@ -1108,6 +1151,8 @@ impl SignalRegistry {
     }
 
     /// Record closed-signal outcome and update provider reputation (Issue #170).
+    /// Signals with resolution targets are rejected: `resolve_signal`
+    /// scores them from the oracle.
     pub fn record_signal_outcome(
         env: Env,
         caller: Address,
@@ -1140,6 +1185,9 @@ impl SignalRegistry {
         if signal.status == SignalStatus::Active {
             return Err(SignalOutcomeError::SignalNotClosed);
         }
+        if resolution::has_targets(&env, signal_id) {
+            return Err(SignalOutcomeError::OracleResolved);
+        }
 
         let provider = signal.provider.clone();
         let rep_key = StorageKey::ProviderReputationScore(provider.clone());
@@ -1208,11 +1256,242 @@ impl SignalRegistry {
         get_dispute(&env, &provider)
     }
 
+    /// Outcome of the most recently resolved dispute against a provider.
+    pub fn get_provider_dispute_outcome(env: Env, provider: Address) -> Option<DisputeOutcome> {
+        get_dispute_outcome(&env, &provider)
+    }
+
+    /// Outcomes of the provider's last 10 resolved disputes, oldest first.
+    pub fn get_provider_dispute_history(env: Env, provider: Address) -> Vec<DisputeOutcome> {
+        get_dispute_outcome_history(&env, &provider)
+    }
+
     pub fn get_provider_stats(env: Env, provider: Address) -> Option<ProviderPerformance> {
         let stats = Self::get_provider_stats_map(&env);
         stats.get(provider)
     }
 
+    /// Admin: set the oracle and benchmark that new signals are measured
+    /// against. Signals created earlier keep their original snapshot.
+    pub fn set_benchmark_config(
+        env: Env,
+        caller: Address,
+        config: benchmark::BenchmarkConfig,
+    ) -> Result<(), AdminError> {
+        admin::require_admin(&env, &caller)?;
+        caller.require_auth();
+        benchmark::set_config(&env, &config)
+    }
+
+    pub fn get_benchmark_config(env: Env) -> Option<benchmark::BenchmarkConfig> {
+        benchmark::get_config(&env)
+    }
+
+    /// Admin: oracle pair id used as the `BaseAsset` benchmark for `asset_pair`.
+    pub fn set_benchmark_pair_id(
+        env: Env,
+        caller: Address,
+        asset_pair: String,
+        oracle_pair_id: u32,
+    ) -> Result<(), AdminError> {
+        admin::require_admin(&env, &caller)?;
+        caller.require_auth();
+        benchmark::set_asset_pair_id(&env, asset_pair, oracle_pair_id);
+        Ok(())
+    }
+
+    /// Admin: oracle pair id of the index used as the `CategoryIndex` benchmark.
+    pub fn set_category_benchmark_id(
+        env: Env,
+        caller: Address,
+        category: SignalCategory,
+        oracle_pair_id: u32,
+    ) -> Result<(), AdminError> {
+        admin::require_admin(&env, &caller)?;
+        caller.require_auth();
+        benchmark::set_category_index_id(&env, category, oracle_pair_id);
+        Ok(())
+    }
+
+    /// Snapshot the benchmark for a signal whose creation-time snapshot
+    /// failed (no oracle price or mapping then). Anyone may call, within
+    /// `SNAPSHOT_WINDOW_SECONDS` of the signal's creation; signals without
+    /// a snapshot report no alpha.
+    pub fn snapshot_signal_benchmark(env: Env, signal_id: u64) -> Result<(), BenchmarkError> {
+        let signal = Self::get_signals_map(&env)
+            .get(signal_id)
+            .ok_or(BenchmarkError::SignalNotFound)?;
+        benchmark::snapshot(&env, &signal)
+    }
+
+    /// Benchmark prices snapshotted when the signal was entered.
+    pub fn get_signal_benchmark(env: Env, signal_id: u64) -> Option<benchmark::BenchmarkSnapshot> {
+        benchmark::get_snapshot(&env, signal_id)
+    }
+
+    /// Average alpha, tracking error, information ratio and beta across the
+    /// provider's closed signals.
+    pub fn get_provider_benchmark_metrics(
+        env: Env,
+        provider: Address,
+    ) -> benchmark::ProviderBenchmarkMetrics {
+        benchmark::provider_metrics(&env, &provider)
+    }
+
+    /// Sharpe, Sortino, max drawdown, profit factor and average holding time
+    /// over the provider's last closed signals.
+    pub fn get_provider_risk_metrics(
+        env: Env,
+        provider: Address,
+    ) -> risk_metrics::ProviderRiskMetrics {
+        risk_metrics::provider_metrics(&env, &provider)
+    }
+
+    /// Admin: oracle used to resolve signals that declared targets.
+    pub fn set_resolution_config(
+        env: Env,
+        caller: Address,
+        config: resolution::ResolutionConfig,
+    ) -> Result<(), AdminError> {
+        admin::require_admin(&env, &caller)?;
+        caller.require_auth();
+        resolution::set_config(&env, &config)
+    }
+
+    pub fn get_resolution_config(env: Env) -> Option<resolution::ResolutionConfig> {
+        resolution::get_config(&env)
+    }
+
+    /// Admin: oracle asset pair whose history resolves signals on `asset_pair`.
+    pub fn set_resolution_pair(
+        env: Env,
+        caller: Address,
+        asset_pair: String,
+        oracle_pair: stellar_swipe_common::AssetPair,
+    ) -> Result<(), AdminError> {
+        admin::require_admin(&env, &caller)?;
+        caller.require_auth();
+        resolution::set_oracle_pair(&env, asset_pair, oracle_pair);
+        Ok(())
+    }
+
+    /// Create a signal with take-profit, stop-loss and horizon. Its outcome
+    /// is settled by [`Self::resolve_signal`] from oracle prices; trade
+    /// executions still update its stats but never its status.
+    pub fn create_signal_with_targets(
+        env: Env,
+        provider: Address,
+        asset_pair: String,
+        action: SignalAction,
+        price: i128,
+        rationale: String,
+        category: SignalCategory,
+        tags: Vec<String>,
+        risk_level: RiskLevel,
+        targets: resolution::SignalTargets,
+    ) -> Result<u64, AdminError> {
+        provider.require_auth();
+        resolution::validate_targets(&targets, &action, price, MAX_EXPIRY_SECONDS)
+            .map_err(|_| AdminError::InvalidParameter)?;
+        shared::events::emit_session_started_once(&env, &provider);
+        let expiry = env.ledger().timestamp() + targets.horizon;
+        let id = Self::create_signal_internal(
+            &env, provider, asset_pair, action, price, rationale, expiry, category, tags,
+            risk_level,
+        )?;
+        resolution::set_targets(&env, id, &targets);
+        Ok(id)
+    }
+
+    pub fn get_signal_targets(env: Env, signal_id: u64) -> Option<resolution::SignalTargets> {
+        resolution::get_targets(&env, signal_id)
+    }
+
+    /// Keeper: advance a signal's resolution through oracle history. It
+    /// resolves at the earliest target crossing, or at its horizon; until
+    /// then the signal's current status is returned. Anyone may call; the
+    /// oracle is the authority.
+    pub fn resolve_signal(
+        env: Env,
+        keeper: Address,
+        signal_id: u64,
+    ) -> Result<SignalStatus, ResolutionError> {
+        keeper.require_auth();
+        let mut signals = Self::get_signals_map(&env);
+        let mut signal = signals
+            .get(signal_id)
+            .ok_or(ResolutionError::SignalNotFound)?;
+        let (new_status, outcome) = match resolution::scan(&env, &signal, &keeper)? {
+            resolution::ScanOutcome::Pending => return Ok(signal.status),
+            resolution::ScanOutcome::Resolved(status, outcome) => (status, outcome),
+        };
+        let roi = outcome.realized_roi_bps;
+
+        // A signal the expiry sweep marked Expired is still open until resolved.
+        let old_status = signal.status.clone();
+        signal.status = new_status.clone();
+        let voided = outcome.kind == resolution::ResolutionKind::HistoryUnavailable;
+        if !voided {
+            let (benchmark_return_bps, alpha_bps) =
+                benchmark::calculate_for_return(&env, &signal, roi);
+            signal.benchmark_return_bps = benchmark_return_bps;
+            signal.alpha_bps = alpha_bps;
+        }
+        signals.set(signal_id, signal.clone());
+        Self::save_signals_map(&env, &signals);
+        resolution::store_resolution(&env, signal_id, &outcome);
+        if old_status == SignalStatus::Active {
+            validation::decrement_provider_active_count(&env, &signal.provider);
+        }
+        let mut recorded: Map<u64, bool> = env
+            .storage()
+            .instance()
+            .get(&StorageKey::RecordedSignalOutcomes)
+            .unwrap_or_else(|| Map::new(&env));
+        recorded.set(signal_id, true);
+        env.storage()
+            .instance()
+            .set(&StorageKey::RecordedSignalOutcomes, &recorded);
+
+        // An outcome that can no longer be verified counts for nothing.
+        if voided {
+            events::emit_signal_resolved(&env, signal_id, keeper, 0, 0);
+            return Ok(new_status);
+        }
+
+        Self::apply_terminal_outcome(&env, &signal, old_status, new_status.clone(), roi);
+
+        // Reputation follows the oracle outcome; no executor report needed.
+        let rep_key = StorageKey::ProviderReputationScore(signal.provider.clone());
+        let old_score: u32 = env.storage().instance().get(&rep_key).unwrap_or(50);
+        let reported = if roi > 0 {
+            SignalOutcome::Profit
+        } else if roi < 0 {
+            SignalOutcome::Loss
+        } else {
+            SignalOutcome::Neutral
+        };
+        let new_score = reputation::next_reputation_score(old_score, &reported);
+        env.storage().instance().set(&rep_key, &new_score);
+        events::emit_reputation_updated(&env, signal.provider.clone(), old_score, new_score);
+
+        contests::apply_trade_to_contest_entries(&env, signal_id, &signal.provider, roi, 0);
+        events::emit_signal_resolved(&env, signal_id, keeper, outcome.price, roi);
+        Ok(new_status)
+    }
+
+    pub fn get_signal_resolution(env: Env, signal_id: u64) -> Option<resolution::SignalResolution> {
+        resolution::get_resolution(&env, signal_id)
+    }
+
+    /// Whether the signal has resolved as `Successful`. Read by other
+    /// contracts' conditional orders.
+    pub fn is_signal_successful(env: Env, signal_id: u64) -> bool {
+        Self::get_signals_map(&env)
+            .get(signal_id)
+            .is_some_and(|signal| signal.status == SignalStatus::Successful)
+    }
+
     pub fn get_provider_monthly_report(
         env: Env,
         provider: Address,
@@ -1410,11 +1689,23 @@ impl SignalRegistry {
             roi.clamp(i32::MIN as i128, i32::MAX as i128) as i32,
         );
 
-        // Evaluate new status
+        // Evaluate new status; signals with targets settle only via resolution
         let now = env.ledger().timestamp();
-        let new_status = performance::evaluate_signal_status(&signal, now);
+        let resolves_by_oracle = resolution::has_targets(&env, signal_id);
+        let new_status = if resolves_by_oracle {
+            old_status.clone()
+        } else {
+            performance::evaluate_signal_status(&signal, now)
+        };
         signal.status = new_status.clone();
 
+        if performance::should_update_provider_stats(&old_status, &new_status) {
+            let (benchmark_return_bps, alpha_bps) =
+                performance::calculate_benchmark_and_alpha(&env, &signal);
+            signal.benchmark_return_bps = benchmark_return_bps;
+            signal.alpha_bps = alpha_bps;
+        }
+
         // Save updated signal
         signals.set(signal_id, signal.clone());
         Self::save_signals_map(&env, &signals);
@@ -1442,58 +1733,77 @@ impl SignalRegistry {
 
         // Check if status changed and update provider stats
         if performance::should_update_provider_stats(&old_status, &new_status) {
-            let mut provider_stats_map = Self::get_provider_stats_map(&env);
-            let mut provider_stats = provider_stats_map
-                .get(signal.provider.clone())
-                .unwrap_or_default();
-
             let signal_avg_roi = performance::get_signal_average_roi(&signal);
+            Self::apply_terminal_outcome(&env, &signal, old_status, new_status, signal_avg_roi);
+        }
 
-            performance::update_provider_performance(
-                &mut provider_stats,
-                &old_status,
-                &new_status,
-                signal_avg_roi,
-                signal.total_volume,
+        if !resolves_by_oracle {
+            contests::apply_trade_to_contest_entries(
+                &env,
+                signal_id,
+                &provider_for_contest,
+                roi,
+                volume,
             );
+        }
 
-            provider_stats_map.set(signal.provider.clone(), provider_stats.clone());
-            Self::save_provider_stats_map(&env, &provider_stats_map);
+        Ok(())
+    }
 
-            // Update leaderboard index (O(INDEX_CAPACITY) in-memory, O(1) query after)
-            update_leaderboard_index(&env, signal.provider.clone(), &provider_stats);
+    /// Provider stats, leaderboard and trust score update for a signal that
+    /// just reached `Successful` or `Failed`.
+    fn apply_terminal_outcome(
+        env: &Env,
+        signal: &Signal,
+        old_status: SignalStatus,
+        new_status: SignalStatus,
+        signal_roi: i128,
+    ) {
+        let mut provider_stats_map = Self::get_provider_stats_map(env);
+        let mut provider_stats = provider_stats_map
+            .get(signal.provider.clone())
+            .unwrap_or_default();
+
+        performance::update_provider_performance(
+            &mut provider_stats,
+            &old_status,
+            &new_status,
+            signal_roi,
+            signal.total_volume,
+        );
 
-            // Update trust score when performance changes
-            Self::update_provider_trust_score(env.clone(), signal.provider.clone());
+        provider_stats_map.set(signal.provider.clone(), provider_stats.clone());
+        Self::save_provider_stats_map(env, &provider_stats_map);
+        risk_metrics::record_close(
+            env,
+            &signal.provider,
+            signal_roi,
+            env.ledger().timestamp().saturating_sub(signal.timestamp),
+        );
 
-            // Emit status change event
-            events::emit_signal_status_changed(
-                &env,
-                signal_id,
-                signal.provider.clone(),
-                old_status as u32,
-                new_status as u32,
-            );
+        // Update leaderboard index (O(INDEX_CAPACITY) in-memory, O(1) query after)
+        update_leaderboard_index(env, signal.provider.clone(), &provider_stats);
 
-            // Emit provider stats updated event
-            events::emit_provider_stats_updated(
-                &env,
-                signal.provider,
-                provider_stats.success_rate,
-                provider_stats.avg_return,
-                provider_stats.total_volume,
-            );
-        }
+        // Update trust score when performance changes
+        Self::update_provider_trust_score(env.clone(), signal.provider.clone());
 
-        contests::apply_trade_to_contest_entries(
-            &env,
-            signal_id,
-            &provider_for_contest,
-            roi,
-            volume,
+        // Emit status change event
+        events::emit_signal_status_changed(
+            env,
+            signal.id,
+            signal.provider.clone(),
+            old_status as u32,
+            new_status as u32,
         );
 
-        Ok(())
+        // Emit provider stats updated event
+        events::emit_provider_stats_updated(
+            env,
+            signal.provider.clone(),
+            provider_stats.success_rate,
+            provider_stats.avg_return,
+            provider_stats.total_volume,
+        );
     }
 
     /// Get signal performance metrics
@@ -1501,7 +1811,7 @@ impl SignalRegistry {
         let signals = Self::get_signals_map(&env);
         let signal = signals.get(signal_id)?;
 
-        let average_roi = performance::get_signal_average_roi(&signal);
+        let average_roi = performance::signal_roi(&env, &signal);
 
         Some(SignalPerformanceView {
             signal_id: signal.id,
@@ -1786,6 +2096,38 @@ impl SignalRegistry {
         fees::calculate_fee_breakdown(trade_amount)
     }
 
+    /// Preview the fee for a trader's tier under FeeCollector's schedule:
+    /// `volume_usd` is their rolling 30-day volume and `staked` their
+    /// protocol-token stake, as reported by FeeCollector
+    /// (`monthly_trade_volume`) and the staking contract. Fails with
+    /// `FeeRoundedToZero` when the tier rate rounds the fee away.
+    pub fn calculate_tiered_fee_preview(
+        env: Env,
+        trade_amount: i128,
+        side: FeeSide,
+        volume_usd: i128,
+        staked: i128,
+    ) -> Result<FeeBreakdown, errors::FeeError> {
+        fees::calculate_scheduled_fee_breakdown(&env, side, volume_usd, staked, trade_amount)
+    }
+
+    /// Register the FeeCollector whose fee schedule tiered previews use (admin only).
+    pub fn set_fee_collector(
+        env: Env,
+        caller: Address,
+        fee_collector: Address,
+    ) -> Result<(), AdminError> {
+        admin::require_admin(&env, &caller)?;
+        caller.require_auth();
+        fees::set_fee_collector(&env, &fee_collector);
+        Ok(())
+    }
+
+    /// FeeCollector's global fee schedule (flat `FEE_BPS` until registered).
+    pub fn get_fee_schedule(env: Env) -> FeeSchedule {
+        fees::get_fee_schedule(&env)
+    }
+
     /* =========================
        API: QUERY SIGNALS
     ========================== */
@@ -2008,6 +2350,7 @@ impl SignalRegistry {
 
         // Update tag popularity
         categories::increment_tag_popularity(&env, &tags);
+        search::index_tags(&env, signal_id, &tags);
 
         // Emit event
         events::emit_tags_added(&env, signal_id, provider, tag_count);
@@ -2015,6 +2358,27 @@ impl SignalRegistry {
         Ok(())
     }
 
+    /// Search signals with a composable filter set, ordered by up to three
+    /// sort keys. Pass the returned cursor back to continue; pages stay
+    /// stable while new signals arrive.
+    pub fn search_signals(
+        env: Env,
+        query: search::SignalQuery,
+        sort: Vec<search::SearchSort>,
+        cursor: Option<Bytes>,
+        limit: u32,
+    ) -> Result<search::SearchPage, SearchError> {
+        let signals = Self::get_signals_map(&env);
+        search::search(&env, &signals, &query, &sort, cursor, limit)
+    }
+
+    /// Keeper: drop inactive signals from a search index. Returns the number
+    /// of ids removed.
+    pub fn prune_search_index(env: Env, key: search::SearchKey) -> u32 {
+        let signals = Self::get_signals_map(&env);
+        search::prune_index(&env, key, &signals)
+    }
+
     /// Get signals filtered by categories, tags, and risk levels
     pub fn get_signals_filtered(
         env: Env,
@@ -2119,7 +2483,8 @@ impl SignalRegistry {
        SIGNAL IMPORT FUNCTIONS
     ========================== */
 
-    /// Import signals from CSV format
+    /// Import signals from CSV format. Rows become real signals; see
+    /// `import` for the format, duplicate handling and resuming.
     pub fn import_signals_csv(
         env: Env,
         provider: Address,
@@ -2127,17 +2492,10 @@ impl SignalRegistry {
         validate_only: bool,
     ) -> ImportResultView {
         provider.require_auth();
-
-        let result = import::import_signals_csv(&env, &provider, data, validate_only);
-
-        ImportResultView {
-            success_count: result.success_count,
-            error_count: result.error_count,
-            signal_ids: Vec::new(&env),
-        }
+        Self::run_import(&env, provider, data, ImportFormat::CSV, validate_only)
     }
 
-    /// Import signals from JSON format
+    /// Import signals from a JSON array of objects.
     pub fn import_signals_json(
         env: Env,
         provider: Address,
@@ -2145,14 +2503,47 @@ impl SignalRegistry {
         validate_only: bool,
     ) -> ImportResultView {
         provider.require_auth();
+        Self::run_import(&env, provider, data, ImportFormat::JSON, validate_only)
+    }
 
-        let result = import::import_signals_json(&env, &provider, data, validate_only);
-
-        ImportResultView {
-            success_count: result.success_count,
-            error_count: result.error_count,
-            signal_ids: Vec::new(&env),
-        }
+    fn run_import(
+        env: &Env,
+        provider: Address,
+        data: Bytes,
+        format: ImportFormat,
+        validate_only: bool,
+    ) -> ImportResultView {
+        let report = import::import_signals(env, &provider, data, format, validate_only, |row| {
+            Self::validate_asset_pair(env, &row.asset_pair)
+                .map_err(|_| errors::ImportError::InvalidAssetPair)?;
+            let expiry = env.ledger().timestamp() + row.expiry_hours as u64 * 60 * 60;
+            Self::create_signal_internal(
+                env,
+                provider.clone(),
+                row.asset_pair.clone(),
+                row.action.clone(),
+                row.price,
+                row.rationale.clone(),
+                expiry,
+                SignalCategory::SWING,
+                Vec::new(env),
+                RiskLevel::Medium,
+            )
+            .map_err(|e| match e {
+                AdminError::RateLimitExceeded => errors::ImportError::RateLimited,
+                AdminError::SignalLimitExceeded => errors::ImportError::SignalLimitReached,
+                AdminError::InvalidAssetPair => errors::ImportError::InvalidAssetPair,
+                _ => errors::ImportError::SubmissionRejected,
+            })
+        });
+        events::emit_signals_imported(
+            env,
+            provider,
+            report.batch_id.clone(),
+            report.success_count,
+            report.error_count,
+        );
+        report
     }
 
     /// Get signal ID by external ID
@@ -2250,6 +2641,17 @@ impl SignalRegistry {
         collaboration::is_collaborative_signal(&env, signal_id)
     }
 
+    /// Author shares of a fully approved collaborative signal, in the form
+    /// FeeCollector's `set_payout_config` accepts. `None` until every
+    /// co-author has approved.
+    pub fn get_collaboration_payout_splits(
+        env: Env,
+        signal_id: u64,
+    ) -> Option<Vec<collaboration::PayoutSplit>> {
+        let authors = collaboration::get_collaborative_signal(&env, signal_id)?;
+        collaboration::payout_splits(&env, &authors)
+    }
+
     /* =========================
        COMBO SIGNAL FUNCTIONS
     ========================== */
@@ -2301,6 +2703,33 @@ impl SignalRegistry {
         Ok(executions)
     }
 
+    /// Record a combo adoption settled by the TradeExecutor, with realized
+    /// per-leg ROI and, in `pnls`, per-leg P&L. Only the registered
+    /// TradeExecutor may call this.
+    pub fn record_combo_execution(
+        env: Env,
+        caller: Address,
+        user: Address,
+        combo_id: u64,
+        total_amount: i128,
+        legs: Vec<ComponentExecution>,
+        pnls: Vec<i128>,
+    ) -> Result<(), ComboError> {
+        caller.require_auth();
+        let executor: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::TradeExecutor)
+            .ok_or(ComboError::Unauthorized)?;
+        if caller != executor {
+            return Err(ComboError::Unauthorized);
+        }
+
+        let combined_roi = record_combo_execution(&env, combo_id, &user, total_amount, legs, pnls)?;
+        events::emit_combo_executed(&env, combo_id, user, combined_roi);
+        Ok(())
+    }
+
     /// Cancel an active combo. Only the provider who created it may cancel.
     pub fn cancel_combo_signal(
         env: Env,
@@ -2328,39 +2757,453 @@ impl SignalRegistry {
         get_combo_executions_pub(&env, combo_id)
     }
 
+    /* =========================
+       SIGNAL BUNDLES
+    ========================== */
+
+    /// Create a bundle of the provider's live signals. Weights are in bps and
+    /// must sum to 10 000; `fee_bps` is charged in `fee_token` on every copy
+    /// of the bundle, and never less than `min_fee`.
+    pub fn create_bundle(
+        env: Env,
+        provider: Address,
+        name: String,
+        components: Vec<bundles::BundleComponent>,
+        fee_bps: u32,
+        fee_token: Address,
+        min_fee: i128,
+    ) -> Result<u64, BundleError> {
+        provider.require_auth();
+        let count = components.len();
+        let bundle_id = bundles::create_bundle(
+            &env, &provider, name, components, fee_bps, fee_token, min_fee,
+        )?;
+        events::emit_bundle_created(&env, bundle_id, provider, count, fee_bps);
+        Ok(bundle_id)
+    }
+
+    /// Copy a whole bundle: the bundle fee is taken off `amount` and paid in
+    /// the bundle's fee token from `user` to the provider; the rest is
+    /// allocated to the live components by weight. Components count as
+    /// adoptions once their copy trades execute.
+    pub fn copy_bundle(
+        env: Env,
+        user: Address,
+        bundle_id: u64,
+        amount: i128,
+    ) -> Result<bundles::BundleCopy, BundleError> {
+        user.require_auth();
+        if admin::is_category_paused(&env, String::from_str(&env, CAT_TRADING)) {
+            return Err(BundleError::TradingPaused);
+        }
+        let copy = bundles::copy_bundle(&env, &user, bundle_id, amount)?;
+        events::emit_bundle_copied(&env, bundle_id, user, amount, copy.fee);
+        Ok(copy)
+    }
+
+    /// Keeper: drop components whose signal is no longer live and spread
+    /// their weight over the rest. Returns the number of components removed.
+    pub fn rebalance_bundle(env: Env, bundle_id: u64) -> Result<u32, BundleError> {
+        let (bundle, removed) = bundles::rebalance_bundle(&env, bundle_id)?;
+        if removed > 0 {
+            events::emit_bundle_rebalanced(&env, bundle_id, removed, bundle.components.len());
+        }
+        Ok(removed)
+    }
+
+    /// Close a bundle to new copies. Only its provider may close it.
+    pub fn close_bundle(env: Env, provider: Address, bundle_id: u64) -> Result<(), BundleError> {
+        provider.require_auth();
+        bundles::close_bundle(&env, &provider, bundle_id)
+    }
+
+    pub fn get_bundle(env: Env, bundle_id: u64) -> Option<bundles::SignalBundle> {
+        bundles::get_bundle(&env, bundle_id)
+    }
+
+    pub fn get_bundle_performance(env: Env, bundle_id: u64) -> Option<bundles::BundlePerformance> {
+        bundles::get_performance(&env, bundle_id)
+    }
+
+    /// The user's latest copy of a bundle.
+    pub fn get_bundle_copy(env: Env, user: Address, bundle_id: u64) -> Option<bundles::BundleCopy> {
+        bundles::get_copy(&env, &user, bundle_id)
+    }
+
+    /* =========================
+       SIGNAL COMMITMENTS
+    ========================== */
+
+    /// Commit to a signal on `asset_pair` without publishing it. The expiry,
+    /// tags and risk level are fixed now; `commitment` is
+    /// `hash_signal_commitment` of the rest of the signal. It must be
+    /// revealed within the reveal window or the provider's reputation is
+    /// penalised. The signal id is reserved now, and the signal enters
+    /// contests and (when one can be priced) the benchmark at commit time.
+    /// Only one commitment per provider and asset pair may be pending.
+    pub fn commit_signal(
+        env: Env,
+        provider: Address,
+        asset_pair: String,
+        category: SignalCategory,
+        expiry: u64,
+        tags: Vec<String>,
+        risk_level: RiskLevel,
+        commitment: BytesN<32>,
+    ) -> Result<u64, CommitmentError> {
+        provider.require_auth();
+        if admin::is_category_paused(&env, String::from_str(&env, CAT_SIGNALS)) {
+            return Err(CommitmentError::TradingPaused);
+        }
+        Self::validate_asset_pair(&env, &asset_pair)
+            .map_err(|_| CommitmentError::SignalRejected)?;
+        let signal_id = Self::next_signal_id(&env);
+        let record = commitments::commit(
+            &env,
+            &provider,
+            asset_pair.clone(),
+            category.clone(),
+            expiry,
+            tags,
+            risk_level,
+            commitment,
+            signal_id,
+        )?;
+        let _ = contests::enter_signal(&env, &provider, signal_id, 0);
+        let _ = benchmark::snapshot_now(&env, signal_id, &asset_pair, &category);
+        events::emit_signal_committed(&env, record.id, provider, record.reveal_deadline);
+        Ok(record.id)
+    }
+
+    /// Publish a committed signal under its reserved id, with the category,
+    /// expiry, tags and risk level fixed at commit. It goes through the usual
+    /// submission checks, and its `timestamp` and `submitted_at` are the
+    /// commit time.
+    pub fn reveal_signal(
+        env: Env,
+        provider: Address,
+        commitment_id: u64,
+        data: SignalData,
+        salt: u64,
+    ) -> Result<u64, CommitmentError> {
+        provider.require_auth();
+        let record = commitments::verify_reveal(&env, &provider, commitment_id, &data, salt)?;
+        let mut signal = Self::prepare_signal(
+            &env,
+            record.signal_id,
+            provider,
+            data.asset_pair,
+            data.action,
+            data.price,
+            data.rationale,
+            record.expiry,
+            record.category.clone(),
+            record.tags.clone(),
+            record.risk_level.clone(),
+        )
+        .map_err(|e| match e {
+            AdminError::TradingPaused => CommitmentError::TradingPaused,
+            _ => CommitmentError::SignalRejected,
+        })?;
+        signal.timestamp = record.committed_at;
+        signal.submitted_at = record.committed_at;
+        // Already entered into contests at commit time.
+        Self::insert_signal(&env, signal);
+
+        let signal_id = record.signal_id;
+        let committed_at = record.committed_at;
+        commitments::mark_revealed(&env, record);
+        events::emit_commitment_revealed(&env, commitment_id, signal_id, committed_at);
+        Ok(signal_id)
+    }
+
+    /// Keeper: expire a commitment that was not revealed in time and apply
+    /// the losing-signal reputation decay to its provider.
+    pub fn expire_signal_commitment(env: Env, commitment_id: u64) -> Result<(), CommitmentError> {
+        let record = commitments::expire(&env, commitment_id)?;
+        events::emit_commitment_expired(&env, commitment_id, record.provider.clone());
+        Self::penalise_unrevealed(&env, record.provider);
+        Ok(())
+    }
+
+    /// Reputation decay for a call that was committed but never revealed.
+    fn penalise_unrevealed(env: &Env, provider: Address) {
+        let rep_key = StorageKey::ProviderReputationScore(provider.clone());
+        let old_score: u32 = env.storage().instance().get(&rep_key).unwrap_or(50);
+        let new_score = reputation::next_reputation_score(old_score, &SignalOutcome::Loss);
+        env.storage().instance().set(&rep_key, &new_score);
+        events::emit_reputation_updated(env, provider, old_score, new_score);
+    }
+
+    pub fn get_signal_commitment(
+        env: Env,
+        commitment_id: u64,
+    ) -> Option<commitments::SignalCommitment> {
+        commitments::get_commitment(&env, commitment_id)
+    }
+
+    /* =========================
+       PRIVATE SIGNALS
+    ========================== */
+
+    /// Register the X25519 public key that providers seal signal keys to.
+    pub fn register_encryption_key(env: Env, user: Address, public_key: BytesN<32>) {
+        user.require_auth();
+        private_signals::set_encryption_key(&env, &user, &public_key);
+    }
+
+    pub fn get_encryption_key(env: Env, user: Address) -> Option<BytesN<32>> {
+        private_signals::get_encryption_key(&env, &user)
+    }
+
+    /// Create a signal whose price and rationale exist only in the encrypted
+    /// `body`. It is held apart from the public signals, with a zero price and
+    /// empty rationale, until `reveal_private_signal`. `commitment` is
+    /// `hash_signal_commitment` of the plaintext, and its ledger time is the
+    /// signal's timestamp. Like a committed signal, it enters contests and
+    /// the benchmark at creation.
+    pub fn create_private_signal(
+        env: Env,
+        provider: Address,
+        asset_pair: String,
+        action: SignalAction,
+        expiry: u64,
+        category: SignalCategory,
+        tags: Vec<String>,
+        risk_level: RiskLevel,
+        body: private_signals::SealedBody,
+        commitment: BytesN<32>,
+    ) -> Result<u64, AdminError> {
+        provider.require_auth();
+        if body.ciphertext.is_empty() {
+            return Err(AdminError::InvalidParameter);
+        }
+        let signal = Self::prepare_signal(
+            &env,
+            Self::next_signal_id(&env),
+            provider.clone(),
+            asset_pair,
+            action,
+            0,
+            String::from_str(&env, ""),
+            expiry,
+            category,
+            tags,
+            risk_level,
+        )?;
+        let signal_id = signal.id;
+        let _ = contests::auto_enter_signal(&env, &signal);
+        let _ = benchmark::snapshot_now(&env, signal_id, &signal.asset_pair, &signal.category);
+        private_signals::store_new(&env, &signal, body, commitment);
+        events::emit_private_signal_created(&env, signal_id, provider);
+        Ok(signal_id)
+    }
+
+    /// Seal the current content key to paying subscribers. Every subscriber
+    /// must hold an active subscription to the provider. Returns the number
+    /// of envelopes now stored for the signal.
+    pub fn add_key_envelopes(
+        env: Env,
+        provider: Address,
+        signal_id: u64,
+        envelopes: Vec<private_signals::KeyEnvelope>,
+    ) -> Result<u32, PrivateSignalError> {
+        provider.require_auth();
+        let private = private_signals::load_owned(&env, &provider, signal_id)?;
+        Self::seal_to_subscribers(&env, &private, envelopes)
+    }
+
+    fn seal_to_subscribers(
+        env: &Env,
+        private: &private_signals::PrivateSignal,
+        envelopes: Vec<private_signals::KeyEnvelope>,
+    ) -> Result<u32, PrivateSignalError> {
+        let portfolio: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::UserPortfolio)
+            .ok_or(PrivateSignalError::PortfolioNotSet)?;
+        private_signals::add_envelopes(env, private, envelopes, |subscriber| {
+            Self::invoke_check_subscription(env, &portfolio, subscriber, &private.provider)
+        })
+    }
+
+    /// Keeper: drop the envelopes of subscribers whose subscription lapsed
+    /// and flag the signal for key rotation. Returns how many were revoked.
+    pub fn revoke_lapsed_subscribers(
+        env: Env,
+        signal_id: u64,
+        subscribers: Vec<Address>,
+    ) -> Result<u32, PrivateSignalError> {
+        let private =
+            private_signals::get_private(&env, signal_id).ok_or(PrivateSignalError::NotPrivate)?;
+        let portfolio: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::UserPortfolio)
+            .ok_or(PrivateSignalError::PortfolioNotSet)?;
+        private_signals::revoke_lapsed(&env, signal_id, subscribers, |subscriber| {
+            Self::invoke_check_subscription(&env, &portfolio, subscriber, &private.provider)
+        })
+    }
+
+    /// Re-encrypt the body under a new content key and seal it to the
+    /// current subscribers. Envelopes of the previous epoch are discarded.
+    pub fn rotate_signal_key(
+        env: Env,
+        provider: Address,
+        signal_id: u64,
+        body: private_signals::SealedBody,
+        envelopes: Vec<private_signals::KeyEnvelope>,
+    ) -> Result<u32, PrivateSignalError> {
+        provider.require_auth();
+        let private = private_signals::load_owned(&env, &provider, signal_id)?;
+        let private = private_signals::rotate(&env, private, body)?;
+        events::emit_signal_key_rotated(&env, signal_id, private.key_epoch);
+        Self::seal_to_subscribers(&env, &private, envelopes)
+    }
+
+    /// Publish the plaintext of a private signal before its reveal deadline.
+    /// It must match the commitment made at creation. The signal then joins
+    /// the public signals with its commit-time timestamp.
+    pub fn reveal_private_signal(
+        env: Env,
+        provider: Address,
+        signal_id: u64,
+        price: i128,
+        rationale: String,
+        salt: u64,
+    ) -> Result<(), PrivateSignalError> {
+        provider.require_auth();
+        let private = private_signals::load_owned(&env, &provider, signal_id)?;
+        let committed_at = private.committed_at;
+        let signal = private_signals::reveal(&env, private, price, &rationale, salt)?;
+        // Already entered into contests at creation.
+        Self::insert_signal(&env, signal);
+        events::emit_private_signal_revealed(&env, signal_id, price, committed_at);
+        Ok(())
+    }
+
+    /// Keeper: drop a private signal that was not revealed by its deadline
+    /// and apply the losing-signal reputation decay to its provider.
+    pub fn expire_private_signal(env: Env, signal_id: u64) -> Result<(), PrivateSignalError> {
+        let private = private_signals::expire(&env, signal_id)?;
+        Self::penalise_unrevealed(&env, private.provider);
+        Ok(())
+    }
+
+    pub fn get_private_signal(env: Env, signal_id: u64) -> Option<private_signals::PrivateSignal> {
+        private_signals::get_private(&env, signal_id)
+    }
+
+    /// The public fields of a private signal that is not revealed yet.
+    pub fn get_sealed_signal(env: Env, signal_id: u64) -> Option<Signal> {
+        private_signals::get_sealed_signal(&env, signal_id)
+    }
+
+    /// The subscriber's envelope for the signal's current key epoch.
+    pub fn get_key_envelope(
+        env: Env,
+        signal_id: u64,
+        subscriber: Address,
+    ) -> Option<private_signals::KeyEnvelope> {
+        private_signals::get_envelope(&env, signal_id, &subscriber)
+    }
+
     /* =========================
        CONTEST FUNCTIONS
     ========================== */
 
-    /// Create a new contest
+    /// Create a new contest. `creator` (the admin or a sponsor) escrows
+    /// `prize_pool` of `config.token`, which is paid out on finalization.
+    /// Sponsors must escrow at least `MIN_SPONSORED_PRIZE_POOL`.
     pub fn create_contest(
         env: Env,
-        admin: Address,
+        creator: Address,
         name: String,
         start_time: u64,
         end_time: u64,
         metric: ContestMetric,
         min_signals: u32,
         prize_pool: i128,
+        config: ContestPrizeConfig,
     ) -> Result<u64, ContestError> {
-        admin.require_auth();
+        creator.require_auth();
 
         require_not_paused(&env).map_err(|e| match e {
             AdminError::TradingPaused => ContestError::TradingPaused,
             AdminError::CircuitBreakerTriggered => ContestError::CircuitBreakerTriggered,
             _ => ContestError::ContestNotFound,
         })?;
+        let creator_is_admin = admin::require_admin(&env, &creator).is_ok();
         contests::create_contest(
             &env,
+            &creator,
+            creator_is_admin,
             name,
             start_time,
             end_time,
             metric,
             min_signals,
             prize_pool,
+            config,
         )
     }
 
+    /// Add to a running contest's prize pool. Returns the new pool size.
+    pub fn sponsor_contest(
+        env: Env,
+        sponsor: Address,
+        contest_id: u64,
+        amount: i128,
+    ) -> Result<i128, ContestError> {
+        sponsor.require_auth();
+        let pool = contests::sponsor_contest(&env, &sponsor, contest_id, amount)?;
+        events::emit_contest_sponsored(&env, contest_id, sponsor, amount, pool);
+        Ok(pool)
+    }
+
+    /// Cancel an active contest and refund every sponsor. The admin may
+    /// always cancel; the creator only while the contest has no entries.
+    /// Returns the total refunded.
+    pub fn cancel_contest(
+        env: Env,
+        caller: Address,
+        contest_id: u64,
+    ) -> Result<i128, ContestError> {
+        caller.require_auth();
+        let contest = contests::get_contest(&env, contest_id)?;
+        if admin::require_admin(&env, &caller).is_err() {
+            if caller != contest.creator {
+                return Err(ContestError::NotContestCreator);
+            }
+            if !contest.entries.is_empty() {
+                return Err(ContestError::ContestHasEntries);
+            }
+        }
+        let refunded = contests::cancel_contest(&env, contest_id)?;
+        events::emit_contest_cancelled(&env, contest_id, refunded);
+        Ok(refunded)
+    }
+
+    /// Admin: move contests created before escrowed prizes to the current
+    /// layout. The admin escrows the advertised pool of each still-active
+    /// contest in `prize_token`. Returns the number migrated.
+    pub fn migrate_legacy_contests(
+        env: Env,
+        caller: Address,
+        prize_token: Address,
+    ) -> Result<u32, ContestError> {
+        admin::require_admin(&env, &caller).map_err(|_| ContestError::Unauthorized)?;
+        caller.require_auth();
+        Ok(contests::migrate_legacy_contests(&env, &caller, prize_token))
+    }
+
+    /// Amount `sponsor` has escrowed in a contest.
+    pub fn get_contest_sponsor_deposit(env: Env, contest_id: u64, sponsor: Address) -> i128 {
+        contests::get_sponsor_deposit(&env, contest_id, &sponsor)
+    }
+
     /// Finalize a contest and distribute prizes
     pub fn finalize_contest(env: Env, contest_id: u64) -> Result<Vec<Address>, ContestError> {
         contests::finalize_contest(&env, contest_id)
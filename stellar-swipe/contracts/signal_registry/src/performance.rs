@@ -131,6 +131,15 @@ pub fn get_signal_average_roi(signal: &Signal) -> i128 {
     }
 }
 
+/// Average execution ROI, or the realized ROI of an oracle resolution for
+/// signals that were resolved without recorded executions.
+pub fn signal_roi(env: &Env, signal: &Signal) -> i128 {
+    match crate::resolution::get_resolution(env, signal.id) {
+        Some(r) if signal.executions == 0 => r.realized_roi_bps,
+        _ => get_signal_average_roi(signal),
+    }
+}
+
 /// Update provider performance statistics when a signal status changes
 ///
 /// # Arguments
@@ -146,13 +155,16 @@ pub fn update_provider_performance(
     signal_roi: i128,
     signal_volume: i128,
 ) {
-    // Only update when transitioning to a terminal state
+    // Only update when transitioning to a terminal state. An oracle-resolved
+    // signal may already have been swept to Expired before it resolves.
     let is_terminal_transition = matches!(
         (old_status, new_status),
         (SignalStatus::Active, SignalStatus::Successful)
             | (SignalStatus::Active, SignalStatus::Failed)
             | (SignalStatus::Pending, SignalStatus::Successful)
             | (SignalStatus::Pending, SignalStatus::Failed)
+            | (SignalStatus::Expired, SignalStatus::Successful)
+            | (SignalStatus::Expired, SignalStatus::Failed)
     );
 
     if !is_terminal_transition {
@@ -232,14 +244,10 @@ pub fn should_update_provider_stats(old_status: &SignalStatus, new_status: &Sign
 
 /// Calculate benchmark return and alpha for a signal on close (Issue #418).
 /// Returns (benchmark_return_bps, alpha_bps). Both are None if benchmark unavailable.
-pub fn calculate_benchmark_and_alpha(_env: &Env, signal: &Signal) -> (Option<i64>, Option<i64>) {
-    if signal.total_roi == 0 || signal.executions == 0 {
-        return (None, None);
-    }
-
-    let _signal_return_bps = signal.total_roi / (signal.executions as i128);
-
-    (None, None)
+/// Also adds the sample to the provider's alpha / information ratio / beta sums,
+/// so call it once per signal, on its terminal transition.
+pub fn calculate_benchmark_and_alpha(env: &Env, signal: &Signal) -> (Option<i64>, Option<i64>) {
+    crate::benchmark::calculate_on_close(env, signal)
 }
 
 #[cfg(test)]
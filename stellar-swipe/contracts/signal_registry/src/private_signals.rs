@@ -0,0 +1,320 @@
+//! Paid private signals.
+//!
+//! A private signal is published without its entry price or rationale. Both
+//! live only in a [`SealedBody`]: ciphertext under a symmetric content key
+//! chosen by the provider. The content key is handed to each paying
+//! subscriber as a [`KeyEnvelope`], sealed to the X25519 public key the
+//! subscriber registered. Every envelope is checked against the subscriber's
+//! active subscription when it is written.
+//!
+//! Ledger entries are public, so removing an envelope does not take back a
+//! key that was already read. When a subscription lapses, anyone may call
+//! `revoke_lapsed_subscribers`. That drops the envelope and flags the signal
+//! for rotation. The provider then re-encrypts under a new key (a new epoch)
+//! and re-seals it only for current subscribers.
+//!
+//! At creation the provider also commits to the plaintext with
+//! `hash_signal_commitment` (see `stellar_swipe_common::commit_reveal`). The
+//! signal's timestamp is the commitment's ledger time. Until it is revealed,
+//! the signal is kept here rather than in the public signal map, so it is not
+//! traded, scored or entered in contests with a placeholder price. Revealing
+//! writes the plaintext price and rationale into it and publishes it, and
+//! succeeds only if they match the commitment. A provider therefore cannot
+//! pick a better call after the move happened.
+//!
+//! The reveal is due by [`REVEAL_GRACE_SECONDS`] after the signal expires.
+//! After that anyone may expire the private signal; the provider's reputation
+//! then takes the same decay as for an unrevealed commitment.
+
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Env, Map, String, Vec};
+use stellar_swipe_common::hash_signal_commitment;
+
+use crate::commitments::REVEAL_WINDOW_SECONDS;
+use crate::errors::PrivateSignalError;
+use crate::types::{Signal, SignalAction};
+
+/// Envelopes kept per signal (one per subscriber).
+pub const MAX_KEY_ENVELOPES: u32 = 200;
+/// How long after its expiry a private signal may still be revealed.
+pub const REVEAL_GRACE_SECONDS: u64 = REVEAL_WINDOW_SECONDS;
+
+/// Rationale and target price encrypted under the signal's content key.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealedBody {
+    pub ciphertext: Bytes,
+    pub nonce: BytesN<12>,
+}
+
+/// The content key of one epoch, sealed to a subscriber's X25519 key.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyEnvelope {
+    pub subscriber: Address,
+    pub epoch: u32,
+    /// Provider's ephemeral X25519 public key for this envelope.
+    pub ephemeral_public_key: BytesN<32>,
+    pub sealed_key: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrivateSignal {
+    pub signal_id: u64,
+    pub provider: Address,
+    pub body: SealedBody,
+    /// `hash_signal_commitment` of the plaintext.
+    pub commitment: BytesN<32>,
+    pub committed_at: u64,
+    /// Current content-key epoch; bumped on every rotation.
+    pub key_epoch: u32,
+    pub rotation_required: bool,
+    pub revealed: bool,
+    /// Latest time the plaintext may be revealed.
+    pub reveal_deadline: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PrivateSignalKey {
+    PrivateSignal(u64),
+    /// The unrevealed signal (zero price, empty rationale) until it is published.
+    SealedSignal(u64),
+    /// Envelopes of the current epoch: `Map<Address, KeyEnvelope>`.
+    KeyEnvelopes(u64),
+    /// Subscriber's registered X25519 public key.
+    EncryptionKey(Address),
+}
+
+pub fn get_private(env: &Env, signal_id: u64) -> Option<PrivateSignal> {
+    env.storage()
+        .persistent()
+        .get(&PrivateSignalKey::PrivateSignal(signal_id))
+}
+
+fn save_private(env: &Env, private: &PrivateSignal) {
+    env.storage()
+        .persistent()
+        .set(&PrivateSignalKey::PrivateSignal(private.signal_id), private);
+}
+
+pub fn is_private(env: &Env, signal_id: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&PrivateSignalKey::PrivateSignal(signal_id))
+}
+
+pub fn get_sealed_signal(env: &Env, signal_id: u64) -> Option<Signal> {
+    env.storage()
+        .persistent()
+        .get(&PrivateSignalKey::SealedSignal(signal_id))
+}
+
+fn get_envelopes(env: &Env, signal_id: u64) -> Map<Address, KeyEnvelope> {
+    env.storage()
+        .persistent()
+        .get(&PrivateSignalKey::KeyEnvelopes(signal_id))
+        .unwrap_or(Map::new(env))
+}
+
+fn save_envelopes(env: &Env, signal_id: u64, envelopes: &Map<Address, KeyEnvelope>) {
+    env.storage()
+        .persistent()
+        .set(&PrivateSignalKey::KeyEnvelopes(signal_id), envelopes);
+}
+
+pub fn get_envelope(env: &Env, signal_id: u64, subscriber: &Address) -> Option<KeyEnvelope> {
+    get_envelopes(env, signal_id).get(subscriber.clone())
+}
+
+pub fn set_encryption_key(env: &Env, user: &Address, public_key: &BytesN<32>) {
+    env.storage()
+        .persistent()
+        .set(&PrivateSignalKey::EncryptionKey(user.clone()), public_key);
+}
+
+pub fn get_encryption_key(env: &Env, user: &Address) -> Option<BytesN<32>> {
+    env.storage()
+        .persistent()
+        .get(&PrivateSignalKey::EncryptionKey(user.clone()))
+}
+
+pub fn action_code(action: &SignalAction) -> u32 {
+    match action {
+        SignalAction::Buy => 0,
+        SignalAction::Sell => 1,
+    }
+}
+
+/// Store a freshly prepared signal with its sealed body and commitment. The
+/// signal stays out of the public map until [`reveal`].
+pub fn store_new(env: &Env, signal: &Signal, body: SealedBody, commitment: BytesN<32>) {
+    env.storage()
+        .persistent()
+        .set(&PrivateSignalKey::SealedSignal(signal.id), signal);
+    save_private(
+        env,
+        &PrivateSignal {
+            signal_id: signal.id,
+            provider: signal.provider.clone(),
+            body,
+            commitment,
+            committed_at: signal.timestamp,
+            key_epoch: 0,
+            rotation_required: false,
+            revealed: false,
+            reveal_deadline: signal.expiry.saturating_add(REVEAL_GRACE_SECONDS),
+        },
+    );
+}
+
+pub fn load_owned(
+    env: &Env,
+    provider: &Address,
+    signal_id: u64,
+) -> Result<PrivateSignal, PrivateSignalError> {
+    let private = get_private(env, signal_id).ok_or(PrivateSignalError::NotPrivate)?;
+    if private.provider != *provider {
+        return Err(PrivateSignalError::NotProvider);
+    }
+    Ok(private)
+}
+
+/// Add envelopes for the current epoch. `is_subscribed` reports whether a
+/// subscriber currently pays for the provider's signals.
+pub fn add_envelopes(
+    env: &Env,
+    private: &PrivateSignal,
+    envelopes: Vec<KeyEnvelope>,
+    is_subscribed: impl Fn(&Address) -> bool,
+) -> Result<u32, PrivateSignalError> {
+    // A lapsed subscriber may still hold the current key.
+    if private.rotation_required {
+        return Err(PrivateSignalError::RotationRequired);
+    }
+    let mut stored = get_envelopes(env, private.signal_id);
+    for envelope in envelopes.iter() {
+        if envelope.epoch != private.key_epoch {
+            return Err(PrivateSignalError::StaleEpoch);
+        }
+        if envelope.sealed_key.is_empty() {
+            return Err(PrivateSignalError::EmptyCiphertext);
+        }
+        if get_encryption_key(env, &envelope.subscriber).is_none() {
+            return Err(PrivateSignalError::NoEncryptionKey);
+        }
+        if !is_subscribed(&envelope.subscriber) {
+            return Err(PrivateSignalError::SubscriptionRequired);
+        }
+        stored.set(envelope.subscriber.clone(), envelope);
+    }
+    if stored.len() > MAX_KEY_ENVELOPES {
+        return Err(PrivateSignalError::TooManyEnvelopes);
+    }
+    save_envelopes(env, private.signal_id, &stored);
+    Ok(stored.len())
+}
+
+/// Drop the envelopes of `subscribers` whose subscription lapsed and flag the
+/// signal for key rotation. Returns how many were revoked.
+pub fn revoke_lapsed(
+    env: &Env,
+    signal_id: u64,
+    subscribers: Vec<Address>,
+    is_subscribed: impl Fn(&Address) -> bool,
+) -> Result<u32, PrivateSignalError> {
+    let mut private = get_private(env, signal_id).ok_or(PrivateSignalError::NotPrivate)?;
+    let mut stored = get_envelopes(env, signal_id);
+    let mut revoked = 0u32;
+    for subscriber in subscribers.iter() {
+        if stored.contains_key(subscriber.clone()) && !is_subscribed(&subscriber) {
+            stored.remove(subscriber);
+            revoked += 1;
+        }
+    }
+    if revoked > 0 {
+        save_envelopes(env, signal_id, &stored);
+        private.rotation_required = true;
+        save_private(env, &private);
+    }
+    Ok(revoked)
+}
+
+/// Replace the body with one encrypted under a new content key. All
+/// envelopes of the previous epoch are discarded.
+pub fn rotate(
+    env: &Env,
+    mut private: PrivateSignal,
+    body: SealedBody,
+) -> Result<PrivateSignal, PrivateSignalError> {
+    if body.ciphertext.is_empty() {
+        return Err(PrivateSignalError::EmptyCiphertext);
+    }
+    private.body = body;
+    private.key_epoch += 1;
+    private.rotation_required = false;
+    save_private(env, &private);
+    save_envelopes(env, private.signal_id, &Map::new(env));
+    Ok(private)
+}
+
+/// Check the plaintext against the commitment, mark the signal revealed and
+/// return it with its price and rationale filled in, ready to publish.
+pub fn reveal(
+    env: &Env,
+    mut private: PrivateSignal,
+    price: i128,
+    rationale: &String,
+    salt: u64,
+) -> Result<Signal, PrivateSignalError> {
+    if private.revealed {
+        return Err(PrivateSignalError::AlreadyRevealed);
+    }
+    if env.ledger().timestamp() > private.reveal_deadline {
+        return Err(PrivateSignalError::RevealWindowClosed);
+    }
+    if price <= 0 {
+        return Err(PrivateSignalError::InvalidPrice);
+    }
+    let mut signal =
+        get_sealed_signal(env, private.signal_id).ok_or(PrivateSignalError::SignalNotFound)?;
+    let expected = hash_signal_commitment(
+        env,
+        &signal.provider,
+        &signal.asset_pair,
+        action_code(&signal.action),
+        price,
+        rationale,
+        salt,
+    );
+    if expected != private.commitment {
+        return Err(PrivateSignalError::CommitmentMismatch);
+    }
+    private.revealed = true;
+    save_private(env, &private);
+    env.storage()
+        .persistent()
+        .remove(&PrivateSignalKey::SealedSignal(private.signal_id));
+
+    signal.price = price;
+    signal.rationale_hash = rationale.clone();
+    signal.rationale = rationale.clone();
+    Ok(signal)
+}
+
+/// Drop a private signal that was not revealed by its deadline, with its
+/// envelopes. Returns the record so the caller can penalise the provider.
+pub fn expire(env: &Env, signal_id: u64) -> Result<PrivateSignal, PrivateSignalError> {
+    let private = get_private(env, signal_id).ok_or(PrivateSignalError::NotPrivate)?;
+    if private.revealed {
+        return Err(PrivateSignalError::AlreadyRevealed);
+    }
+    if env.ledger().timestamp() <= private.reveal_deadline {
+        return Err(PrivateSignalError::RevealWindowOpen);
+    }
+    let storage = env.storage().persistent();
+    storage.remove(&PrivateSignalKey::PrivateSignal(signal_id));
+    storage.remove(&PrivateSignalKey::SealedSignal(signal_id));
+    storage.remove(&PrivateSignalKey::KeyEnvelopes(signal_id));
+    Ok(private)
+}
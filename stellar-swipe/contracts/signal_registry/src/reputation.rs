@@ -2,7 +2,8 @@
 //!
 //! Trust score combines multiple factors to provide a holistic reliability metric:
 //! - 40%: Success rate (successful_signals / total_signals)
-//! - 20%: Consistency (inverse of ROI variance across signals)
+//! - 20%: Consistency (information ratio against the oracle benchmark once
+//!   enough closed signals have an alpha; a success/return proxy before that)
 //! - 15%: Stake amount (normalized vs median stake)
 //! - 15%: Follower count (normalized vs median followers)
 //! - 10%: Tenure (days since first signal)
@@ -12,6 +13,10 @@
 //! - 60-79: "Trusted" (blue badge)
 //! - 40-59: "Emerging" (yellow badge)
 //! - 0-39: "New/Unproven" (gray badge)
+//!
+//! Once a provider has enough closed signals for risk metrics, tiers are
+//! capped by risk: a max drawdown above 25% caps the score at "Trusted", and
+//! a drawdown above 50% or a negative Sharpe ratio caps it at "Emerging".
 
 use crate::social;
 use crate::stake::StakeInfo;
@@ -28,6 +33,12 @@ const STAKE_WEIGHT: u32 = 1500; // 15%
 const FOLLOWER_WEIGHT: u32 = 1500; // 15%
 const TENURE_WEIGHT: u32 = 1000; // 10%
 
+// Risk caps (drawdown in bps, score ceilings on the 0-100 scale)
+const TRUSTED_MAX_DRAWDOWN_BPS: i128 = 2500;
+const EMERGING_MAX_DRAWDOWN_BPS: i128 = 5000;
+const TRUSTED_SCORE_CAP: u32 = 79;
+const EMERGING_SCORE_CAP: u32 = 59;
+
 // Maximum tenure days for full score (365 days = 100%)
 const MAX_TENURE_DAYS: u64 = 365;
 // Seconds per day
@@ -115,7 +126,7 @@ pub fn calculate_trust_score(
 
     // Calculate individual components
     let success_rate_component = calculate_success_rate_component(performance);
-    let consistency_component = calculate_consistency_component(performance);
+    let consistency_component = calculate_consistency_component(env, provider, performance);
     let stake_component = calculate_stake_component(env, stake_info);
     let follower_component = calculate_follower_component(env, provider);
     let tenure_component = calculate_tenure_component(env, provider, now);
@@ -128,6 +139,7 @@ pub fn calculate_trust_score(
         follower_component,
         tenure_component,
     );
+    let score = apply_risk_cap(env, provider, score);
 
     let tier = get_trust_score_tier(score);
 
@@ -146,6 +158,22 @@ pub fn calculate_trust_score(
     }
 }
 
+/// Cap the score by drawdown and Sharpe so a provider who wins often but
+/// occasionally blows up cannot reach the top tiers.
+fn apply_risk_cap(env: &Env, provider: &Address, score: u32) -> u32 {
+    let risk = crate::risk_metrics::provider_metrics(env, provider);
+    if risk.samples < crate::risk_metrics::MIN_RISK_SAMPLES {
+        return score;
+    }
+    if risk.max_drawdown_bps > EMERGING_MAX_DRAWDOWN_BPS || risk.sharpe_bps < 0 {
+        score.min(EMERGING_SCORE_CAP)
+    } else if risk.max_drawdown_bps > TRUSTED_MAX_DRAWDOWN_BPS {
+        score.min(TRUSTED_SCORE_CAP)
+    } else {
+        score
+    }
+}
+
 /// Calculate success rate component (40% weight)
 /// Returns 0-10000 basis points
 fn calculate_success_rate_component(performance: &ProviderPerformance) -> u32 {
@@ -158,15 +186,25 @@ fn calculate_success_rate_component(performance: &ProviderPerformance) -> u32 {
 }
 
 /// Calculate consistency component (20% weight)
-/// Measures inverse of ROI variance - lower variance = higher consistency
+/// Uses the information ratio against the benchmark when the provider has
+/// enough samples: an IR of -1.0 or lower scores 0, +1.0 or higher scores 10000.
 /// Returns 0-10000 basis points
-fn calculate_consistency_component(performance: &ProviderPerformance) -> u32 {
+fn calculate_consistency_component(
+    env: &Env,
+    provider: &Address,
+    performance: &ProviderPerformance,
+) -> u32 {
+    let benchmark = crate::benchmark::provider_metrics(env, provider);
+    if benchmark.samples >= crate::benchmark::MIN_BENCHMARK_SAMPLES {
+        let ir = benchmark.information_ratio_bps.clamp(-10_000, 10_000);
+        return ((ir + 10_000) / 2) as u32;
+    }
+
     if performance.total_signals < 2 {
         return 5000; // Neutral score for providers with limited history
     }
 
-    // Use success rate plus average return as a proxy for consistency.
-    // In a full implementation, this should be based on variance of individual ROIs.
+    // Without benchmark history, use success rate plus average return as a proxy.
     let success_rate = performance.success_rate.min(10000);
 
     // Normalize avg_return (-10000..10000) to 0..10000
@@ -275,6 +313,16 @@ pub(crate) fn get_trust_score_tier(score: u32) -> TrustScoreTier {
     }
 }
 
+/// Ordinal of a tier, lowest (`NewUnproven`) first, for "at least tier X" checks.
+pub(crate) fn tier_rank(tier: &TrustScoreTier) -> u32 {
+    match tier {
+        TrustScoreTier::NewUnproven => 0,
+        TrustScoreTier::Emerging => 1,
+        TrustScoreTier::Trusted => 2,
+        TrustScoreTier::HighlyTrusted => 3,
+    }
+}
+
 /// Get stored trust score for provider
 pub fn get_trust_score(env: &Env, provider: &Address) -> Option<TrustScoreDetails> {
     env.storage()
@@ -524,30 +572,46 @@ mod tests {
 
     #[test]
     fn test_consistency_component() {
-        // Insufficient history returns neutral 5000
-        let performance = ProviderPerformance {
-            total_signals: 1,
-            ..Default::default()
-        };
-        assert_eq!(calculate_consistency_component(&performance), 5000);
+        let env = Env::default();
+        #[allow(deprecated)]
+        let cid = env.register_contract(None, crate::SignalRegistry);
+        env.as_contract(&cid, || {
+            let provider = Address::generate(&env);
 
-        // Positive returns and strong success rate should increase consistency.
-        let performance = ProviderPerformance {
-            total_signals: 10,
-            success_rate: 8000,
-            avg_return: 5000,
-            ..Default::default()
-        };
-        assert_eq!(calculate_consistency_component(&performance), 7800);
+            // Insufficient history returns neutral 5000
+            let performance = ProviderPerformance {
+                total_signals: 1,
+                ..Default::default()
+            };
+            assert_eq!(
+                calculate_consistency_component(&env, &provider, &performance),
+                5000
+            );
 
-        // Negative returns reduce consistency.
-        let performance = ProviderPerformance {
-            total_signals: 10,
-            success_rate: 5000,
-            avg_return: -8000,
-            ..Default::default()
-        };
-        assert_eq!(calculate_consistency_component(&performance), 3400);
+            // Positive returns and strong success rate should increase consistency.
+            let performance = ProviderPerformance {
+                total_signals: 10,
+                success_rate: 8000,
+                avg_return: 5000,
+                ..Default::default()
+            };
+            assert_eq!(
+                calculate_consistency_component(&env, &provider, &performance),
+                7800
+            );
+
+            // Negative returns reduce consistency.
+            let performance = ProviderPerformance {
+                total_signals: 10,
+                success_rate: 5000,
+                avg_return: -8000,
+                ..Default::default()
+            };
+            assert_eq!(
+                calculate_consistency_component(&env, &provider, &performance),
+                3400
+            );
+        });
     }
 
     #[test]
@@ -578,4 +642,27 @@ mod tests {
             assert_eq!(list.get(0).unwrap().1.has_sufficient_history, true);
         });
     }
+
+    #[test]
+    fn test_risk_cap_limits_tier() {
+        let env = Env::default();
+        #[allow(deprecated)]
+        let cid = env.register_contract(None, crate::SignalRegistry);
+        env.as_contract(&cid, || {
+            let steady = Address::generate(&env);
+            let blow_up = Address::generate(&env);
+            for _ in 0..11 {
+                crate::risk_metrics::record_close(&env, &steady, 300, 3_600);
+                crate::risk_metrics::record_close(&env, &blow_up, 300, 3_600);
+            }
+            // Still profitable on average, but a 30% drawdown.
+            crate::risk_metrics::record_close(&env, &blow_up, -3_000, 3_600);
+
+            assert_eq!(apply_risk_cap(&env, &steady, 95), 95);
+            assert_eq!(apply_risk_cap(&env, &blow_up, 95), TRUSTED_SCORE_CAP);
+
+            crate::risk_metrics::record_close(&env, &blow_up, -3_000, 3_600);
+            assert_eq!(apply_risk_cap(&env, &blow_up, 95), EMERGING_SCORE_CAP);
+        });
+    }
 }
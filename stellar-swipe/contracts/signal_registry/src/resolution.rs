@@ -0,0 +1,350 @@
+//! Trustless signal outcome resolution from oracle prices.
+//!
+//! A signal created with [`SignalTargets`] declares its take-profit, stop-loss
+//! and horizon up front. Its outcome is then settled by the registry itself
+//! instead of by `record_trade_execution` / `record_signal_outcome`:
+//!
+//! - Keepers advance a per-signal cursor through the oracle's
+//!   `get_historical_price` buckets from the signal's creation, at most
+//!   [`MAX_SCAN_BUCKETS`] per call. The signal resolves at the earliest
+//!   closed bucket that crosses the take-profit (→ `Successful`) or the
+//!   stop-loss (→ `Failed`); the keeper never picks the timestamp.
+//! - A crossing only counts if its price lies within
+//!   `max_twap_deviation_bps` of the one-hour TWAP ending at that bucket, so
+//!   a single manipulated bucket is skipped rather than resolving a signal.
+//! - If neither target is crossed before the horizon, the signal resolves at
+//!   the oracle price at the horizon (the TWAP if the spot price is out of
+//!   bounds): `Successful` on a positive return, `Failed` otherwise.
+//! - The oracle keeps [`ORACLE_HISTORY_SECONDS`] (seven days) of history. If
+//!   the unscanned part of a signal's window has aged out before a keeper
+//!   reaches it, the outcome can no longer be verified: the signal is voided
+//!   as `Expired` with [`ResolutionKind::HistoryUnavailable`], zero ROI and
+//!   no effect on provider stats or reputation. Signals with horizons longer
+//!   than the history need a keeper pass at least once a week.
+//!
+//! Realized ROI is computed from the signal's entry price, which must be
+//! quoted in the same units as the oracle feed.
+
+use soroban_sdk::{contracttype, vec, Address, Env, IntoVal, Map, String, Symbol};
+use stellar_swipe_common::AssetPair;
+
+use crate::errors::{AdminError, ResolutionError};
+use crate::performance::calculate_roi;
+use crate::types::{Signal, SignalAction, SignalStatus};
+
+/// Oracle history bucket size (5 minutes).
+pub const ORACLE_BUCKET_SECONDS: u64 = 300;
+/// Window of the TWAP a submitted price is checked against.
+pub const TWAP_WINDOW_SECONDS: u64 = 3_600;
+/// How far back the oracle keeps history buckets.
+pub const ORACLE_HISTORY_SECONDS: u64 = 7 * 86_400;
+/// Buckets scanned per `resolve_signal` call.
+pub const MAX_SCAN_BUCKETS: u64 = 48;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignalTargets {
+    pub take_profit: i128,
+    pub stop_loss: i128,
+    /// Seconds after creation at which the signal resolves at market.
+    pub horizon: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolutionConfig {
+    /// Oracle contract exposing `get_historical_price(AssetPair, u64)`.
+    pub oracle: Address,
+    pub max_twap_deviation_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResolutionKind {
+    TakeProfit,
+    StopLoss,
+    Horizon,
+    /// The window aged out of oracle history before it was scanned.
+    HistoryUnavailable,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignalResolution {
+    pub kind: ResolutionKind,
+    pub keeper: Address,
+    /// Oracle price the signal resolved at.
+    pub price: i128,
+    pub twap: i128,
+    /// Timestamp of the oracle observation.
+    pub observed_at: u64,
+    pub realized_roi_bps: i128,
+    pub resolved_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ResolutionKey {
+    ResolutionConfig,
+    /// Signal asset pair string -> oracle asset pair.
+    OraclePairs,
+    Targets(u64),
+    Resolution(u64),
+    /// Next oracle bucket timestamp to scan for a signal.
+    ScanCursor(u64),
+}
+
+pub fn get_config(env: &Env) -> Option<ResolutionConfig> {
+    env.storage()
+        .instance()
+        .get(&ResolutionKey::ResolutionConfig)
+}
+
+pub fn set_config(env: &Env, config: &ResolutionConfig) -> Result<(), AdminError> {
+    if config.max_twap_deviation_bps == 0 || config.max_twap_deviation_bps > 10_000 {
+        return Err(AdminError::InvalidParameter);
+    }
+    env.storage()
+        .instance()
+        .set(&ResolutionKey::ResolutionConfig, config);
+    Ok(())
+}
+
+pub fn set_oracle_pair(env: &Env, asset_pair: String, pair: AssetPair) {
+    let mut pairs: Map<String, AssetPair> = env
+        .storage()
+        .instance()
+        .get(&ResolutionKey::OraclePairs)
+        .unwrap_or(Map::new(env));
+    pairs.set(asset_pair, pair);
+    env.storage()
+        .instance()
+        .set(&ResolutionKey::OraclePairs, &pairs);
+}
+
+pub fn get_oracle_pair(env: &Env, asset_pair: &String) -> Option<AssetPair> {
+    let pairs: Map<String, AssetPair> =
+        env.storage().instance().get(&ResolutionKey::OraclePairs)?;
+    pairs.get(asset_pair.clone())
+}
+
+/// Take-profit must be on the winning side of the entry price and
+/// stop-loss on the losing side.
+pub fn validate_targets(
+    targets: &SignalTargets,
+    action: &SignalAction,
+    price: i128,
+    max_horizon: u64,
+) -> Result<(), ResolutionError> {
+    if targets.horizon == 0 || targets.horizon > max_horizon || targets.stop_loss <= 0 {
+        return Err(ResolutionError::InvalidTargets);
+    }
+    let ordered = match action {
+        SignalAction::Buy => targets.stop_loss < price && price < targets.take_profit,
+        SignalAction::Sell => targets.take_profit < price && price < targets.stop_loss,
+    };
+    if !ordered {
+        return Err(ResolutionError::InvalidTargets);
+    }
+    Ok(())
+}
+
+pub fn set_targets(env: &Env, signal_id: u64, targets: &SignalTargets) {
+    env.storage()
+        .persistent()
+        .set(&ResolutionKey::Targets(signal_id), targets);
+}
+
+pub fn get_targets(env: &Env, signal_id: u64) -> Option<SignalTargets> {
+    env.storage()
+        .persistent()
+        .get(&ResolutionKey::Targets(signal_id))
+}
+
+/// Signals with targets are settled only through resolution.
+pub fn has_targets(env: &Env, signal_id: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&ResolutionKey::Targets(signal_id))
+}
+
+pub fn get_resolution(env: &Env, signal_id: u64) -> Option<SignalResolution> {
+    env.storage()
+        .persistent()
+        .get(&ResolutionKey::Resolution(signal_id))
+}
+
+fn historical_price(env: &Env, oracle: &Address, pair: &AssetPair, timestamp: u64) -> Option<i128> {
+    match env.try_invoke_contract::<Option<i128>, soroban_sdk::Error>(
+        oracle,
+        &Symbol::new(env, "get_historical_price"),
+        vec![env, pair.into_val(env), timestamp.into_val(env)],
+    ) {
+        Ok(Ok(price)) => price.filter(|p| *p > 0),
+        _ => None,
+    }
+}
+
+/// Average of the oracle's stored prices over the hour ending at `timestamp`.
+fn twap_at(env: &Env, oracle: &Address, pair: &AssetPair, timestamp: u64) -> Option<i128> {
+    let mut sum = 0i128;
+    let mut count = 0i128;
+    let mut t = timestamp.saturating_sub(TWAP_WINDOW_SECONDS);
+    while t <= timestamp {
+        if let Some(price) = historical_price(env, oracle, pair, t) {
+            sum = sum.saturating_add(price);
+            count += 1;
+        }
+        t += ORACLE_BUCKET_SECONDS;
+    }
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count)
+    }
+}
+
+/// Result of a keeper pass over a signal's oracle history.
+pub enum ScanOutcome {
+    /// No crossing yet; the cursor was advanced.
+    Pending,
+    Resolved(SignalStatus, SignalResolution),
+}
+
+fn get_cursor(env: &Env, signal: &Signal) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&ResolutionKey::ScanCursor(signal.id))
+        .unwrap_or(signal.timestamp - signal.timestamp % ORACLE_BUCKET_SECONDS)
+}
+
+fn resolution(
+    kind: ResolutionKind,
+    keeper: &Address,
+    price: i128,
+    twap: i128,
+    observed_at: u64,
+    realized_roi_bps: i128,
+    now: u64,
+) -> SignalResolution {
+    SignalResolution {
+        kind,
+        keeper: keeper.clone(),
+        price,
+        twap,
+        observed_at,
+        realized_roi_bps,
+        resolved_at: now,
+    }
+}
+
+/// Scan the next buckets of `signal`'s oracle history for the earliest
+/// target crossing, or settle at the horizon once it has been reached.
+/// Only the scan cursor is written; the caller stores the resolution.
+pub fn scan(env: &Env, signal: &Signal, keeper: &Address) -> Result<ScanOutcome, ResolutionError> {
+    let config = get_config(env).ok_or(ResolutionError::NotConfigured)?;
+    let pair = get_oracle_pair(env, &signal.asset_pair).ok_or(ResolutionError::NotConfigured)?;
+    let targets = get_targets(env, signal.id).ok_or(ResolutionError::NoTargets)?;
+    if get_resolution(env, signal.id).is_some()
+        || !matches!(signal.status, SignalStatus::Active | SignalStatus::Expired)
+    {
+        return Err(ResolutionError::AlreadyResolved);
+    }
+
+    let now = env.ledger().timestamp();
+    let horizon_end = signal.timestamp.saturating_add(targets.horizon);
+    let mut cursor = get_cursor(env, signal);
+    if cursor < now.saturating_sub(ORACLE_HISTORY_SECONDS) {
+        return Ok(ScanOutcome::Resolved(
+            SignalStatus::Expired,
+            resolution(
+                ResolutionKind::HistoryUnavailable,
+                keeper,
+                0,
+                0,
+                cursor,
+                0,
+                now,
+            ),
+        ));
+    }
+
+    // Closed buckets that end by the horizon are scanned for crossings; the
+    // bucket holding the horizon itself prices the horizon resolution.
+    let mut scanned = 0u64;
+    while scanned < MAX_SCAN_BUCKETS
+        && cursor.saturating_add(ORACLE_BUCKET_SECONDS) <= horizon_end
+        && cursor.saturating_add(ORACLE_BUCKET_SECONDS) <= now
+    {
+        let t = cursor;
+        cursor += ORACLE_BUCKET_SECONDS;
+        scanned += 1;
+        let Some(price) = historical_price(env, &config.oracle, &pair, t) else {
+            continue;
+        };
+        let (hit_take_profit, hit_stop_loss) = match signal.action {
+            SignalAction::Buy => (price >= targets.take_profit, price <= targets.stop_loss),
+            SignalAction::Sell => (price <= targets.take_profit, price >= targets.stop_loss),
+        };
+        if !hit_take_profit && !hit_stop_loss {
+            continue;
+        }
+        let Some(twap) = twap_at(env, &config.oracle, &pair, t) else {
+            continue;
+        };
+        if deviation_bps(price, twap) > config.max_twap_deviation_bps as i128 {
+            continue;
+        }
+        let (kind, status) = if hit_take_profit {
+            (ResolutionKind::TakeProfit, SignalStatus::Successful)
+        } else {
+            (ResolutionKind::StopLoss, SignalStatus::Failed)
+        };
+        let roi = calculate_roi(signal.price, price, &signal.action);
+        return Ok(ScanOutcome::Resolved(
+            status,
+            resolution(kind, keeper, price, twap, t, roi, now),
+        ));
+    }
+
+    if cursor.saturating_add(ORACLE_BUCKET_SECONDS) <= horizon_end || now < horizon_end {
+        env.storage()
+            .persistent()
+            .set(&ResolutionKey::ScanCursor(signal.id), &cursor);
+        return Ok(ScanOutcome::Pending);
+    }
+
+    let twap = twap_at(env, &config.oracle, &pair, horizon_end)
+        .ok_or(ResolutionError::PriceUnavailable)?;
+    let price = historical_price(env, &config.oracle, &pair, horizon_end)
+        .filter(|p| deviation_bps(*p, twap) <= config.max_twap_deviation_bps as i128)
+        .unwrap_or(twap);
+    let roi = calculate_roi(signal.price, price, &signal.action);
+    let status = if roi > 0 {
+        SignalStatus::Successful
+    } else {
+        SignalStatus::Failed
+    };
+    Ok(ScanOutcome::Resolved(
+        status,
+        resolution(
+            ResolutionKind::Horizon,
+            keeper,
+            price,
+            twap,
+            horizon_end,
+            roi,
+            now,
+        ),
+    ))
+}
+
+fn deviation_bps(price: i128, twap: i128) -> i128 {
+    (price - twap).abs().saturating_mul(10_000) / twap
+}
+
+pub fn store_resolution(env: &Env, signal_id: u64, resolution: &SignalResolution) {
+    env.storage()
+        .persistent()
+        .set(&ResolutionKey::Resolution(signal_id), resolution);
+}
@@ -0,0 +1,238 @@
+//! Risk-adjusted provider performance.
+//!
+//! Every signal that reaches `Successful` or `Failed` appends its ROI (bps)
+//! and holding time to the provider's return series, which keeps the last
+//! [`RETURN_WINDOW`] closes. Sharpe, Sortino, max drawdown, profit factor and
+//! average holding time are derived from that window on each close and kept
+//! in their own per-provider record, so they roll forward as old closes fall
+//! out and readers (leaderboard, trust score) never walk the series.
+//!
+//! Ratios are per close (no annualisation, zero risk-free rate) and scaled by
+//! 10,000 (10,000 = 1.0). Drawdown compounds the returns into an equity
+//! curve and reports the worst peak-to-trough fall in bps. All metrics are
+//! zero until [`MIN_RISK_SAMPLES`] closes are recorded.
+
+use soroban_sdk::{contracttype, Address, Env, Vec};
+use stellar_swipe_common::BASIS_POINTS_DENOMINATOR_I128;
+
+use crate::benchmark::isqrt;
+
+/// Closed signals kept per provider.
+pub const RETURN_WINDOW: u32 = 100;
+/// Closed signals needed before metrics are reported.
+pub const MIN_RISK_SAMPLES: u32 = 5;
+/// Cap for ratios that are unbounded (no volatility or no losses): 100.0.
+pub const MAX_RATIO_BPS: i128 = 1_000_000;
+
+const EQUITY_BASE: i128 = 1_000_000_000_000;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProviderReturnSeries {
+    pub returns_bps: Vec<i128>,
+    pub holding_secs: Vec<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProviderRiskMetrics {
+    pub samples: u32,
+    /// Mean return over its standard deviation.
+    pub sharpe_bps: i128,
+    /// Mean return over downside deviation.
+    pub sortino_bps: i128,
+    /// Worst peak-to-trough fall of the compounded returns, in bps.
+    pub max_drawdown_bps: i128,
+    /// Gross profit over gross loss.
+    pub profit_factor_bps: i128,
+    pub avg_holding_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum RiskKey {
+    ReturnSeries(Address),
+    /// Metrics of the current return series.
+    Metrics(Address),
+}
+
+pub fn get_series(env: &Env, provider: &Address) -> ProviderReturnSeries {
+    env.storage()
+        .persistent()
+        .get(&RiskKey::ReturnSeries(provider.clone()))
+        .unwrap_or(ProviderReturnSeries {
+            returns_bps: Vec::new(env),
+            holding_secs: Vec::new(env),
+        })
+}
+
+/// Append a closed signal to `provider`'s return series.
+pub fn record_close(env: &Env, provider: &Address, roi_bps: i128, holding_secs: u64) {
+    let mut series = get_series(env, provider);
+    push_capped(&mut series.returns_bps, roi_bps);
+    series.holding_secs.push_back(holding_secs);
+    if series.holding_secs.len() > RETURN_WINDOW {
+        series.holding_secs.pop_front();
+    }
+    env.storage()
+        .persistent()
+        .set(&RiskKey::ReturnSeries(provider.clone()), &series);
+    env.storage()
+        .persistent()
+        .set(&RiskKey::Metrics(provider.clone()), &metrics_of(&series));
+}
+
+/// Append to a return window, dropping the oldest entry when full.
+pub fn push_capped(returns: &mut Vec<i128>, roi_bps: i128) {
+    returns.push_back(roi_bps);
+    if returns.len() > RETURN_WINDOW {
+        returns.pop_front();
+    }
+}
+
+pub fn provider_metrics(env: &Env, provider: &Address) -> ProviderRiskMetrics {
+    env.storage()
+        .persistent()
+        .get(&RiskKey::Metrics(provider.clone()))
+        .unwrap_or_default()
+}
+
+fn metrics_of(series: &ProviderReturnSeries) -> ProviderRiskMetrics {
+    let mut metrics = compute(&series.returns_bps);
+    if metrics.samples >= MIN_RISK_SAMPLES {
+        let total: u64 = series.holding_secs.iter().fold(0u64, u64::saturating_add);
+        metrics.avg_holding_secs = total / series.holding_secs.len().max(1) as u64;
+    }
+    metrics
+}
+
+fn ratio(numerator: i128, denominator: i128) -> i128 {
+    if denominator == 0 {
+        return if numerator > 0 { MAX_RATIO_BPS } else { 0 };
+    }
+    numerator
+        .saturating_mul(BASIS_POINTS_DENOMINATOR_I128)
+        .checked_div(denominator)
+        .unwrap_or(0)
+        .clamp(-MAX_RATIO_BPS, MAX_RATIO_BPS)
+}
+
+/// Metrics of a return series (bps). Holding time is left at zero.
+pub fn compute(returns: &Vec<i128>) -> ProviderRiskMetrics {
+    let n = returns.len();
+    if n < MIN_RISK_SAMPLES {
+        return ProviderRiskMetrics {
+            samples: n,
+            ..Default::default()
+        };
+    }
+    let count = n as i128;
+
+    let mut sum = 0i128;
+    let mut gross_profit = 0i128;
+    let mut gross_loss = 0i128;
+    let mut downside_sq = 0i128;
+    let mut equity = EQUITY_BASE;
+    let mut peak = EQUITY_BASE;
+    let mut max_drawdown = 0i128;
+    for r in returns.iter() {
+        sum = sum.saturating_add(r);
+        if r > 0 {
+            gross_profit = gross_profit.saturating_add(r);
+        } else {
+            gross_loss = gross_loss.saturating_add(-r);
+            downside_sq = downside_sq.saturating_add(r.saturating_mul(r));
+        }
+        let growth = (BASIS_POINTS_DENOMINATOR_I128 + r).max(0);
+        equity = equity.saturating_mul(growth) / BASIS_POINTS_DENOMINATOR_I128;
+        if equity > peak {
+            peak = equity;
+        }
+        let drawdown = (peak - equity).saturating_mul(BASIS_POINTS_DENOMINATOR_I128) / peak;
+        if drawdown > max_drawdown {
+            max_drawdown = drawdown;
+        }
+        // Keep the curve in range; only ratios of equity to peak matter.
+        if peak > EQUITY_BASE.saturating_mul(1_000_000) {
+            equity /= 1_000_000;
+            peak /= 1_000_000;
+        }
+    }
+    let mean = sum / count;
+
+    let mut variance_sum = 0i128;
+    for r in returns.iter() {
+        let d = r - mean;
+        variance_sum = variance_sum.saturating_add(d.saturating_mul(d));
+    }
+    let std_dev = isqrt(variance_sum / count);
+    let downside_dev = isqrt(downside_sq / count);
+
+    ProviderRiskMetrics {
+        samples: n,
+        sharpe_bps: ratio(mean, std_dev),
+        sortino_bps: ratio(mean, downside_dev),
+        max_drawdown_bps: max_drawdown,
+        profit_factor_bps: ratio(gross_profit, gross_loss),
+        avg_holding_secs: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    #[test]
+    fn test_metrics_need_minimum_samples() {
+        let env = Env::default();
+        let m = compute(&vec![&env, 500, 500, -200, 100]);
+        assert_eq!(m.samples, 4);
+        assert_eq!(m.sharpe_bps, 0);
+        assert_eq!(m.max_drawdown_bps, 0);
+    }
+
+    #[test]
+    fn test_occasional_blow_up_is_penalised() {
+        let env = Env::default();
+        // Steady: +2% every time.
+        let steady = compute(&vec![&env, 200, 200, 150, 250, 200, 200]);
+        // Wins 5 of 6 by +3%, then loses 40%.
+        let blow_up = compute(&vec![&env, 300, 300, 300, 300, 300, -4_000]);
+
+        assert_eq!(steady.profit_factor_bps, MAX_RATIO_BPS);
+        assert_eq!(steady.max_drawdown_bps, 0);
+        assert!(steady.sharpe_bps > 10_000);
+
+        assert_eq!(blow_up.max_drawdown_bps, 4_000);
+        assert_eq!(blow_up.profit_factor_bps, 3_750);
+        assert!(blow_up.sharpe_bps < 0);
+        assert!(blow_up.sortino_bps < 0);
+    }
+
+    #[test]
+    fn test_drawdown_compounds_from_the_peak() {
+        let env = Env::default();
+        // 100 -> 110 -> 99 -> 89.1 -> 98.01 -> 107.8
+        let m = compute(&vec![&env, 1_000, -1_000, -1_000, 1_000, 1_000]);
+        assert_eq!(m.max_drawdown_bps, 1_900);
+    }
+
+    #[test]
+    fn test_window_and_holding_time() {
+        let env = Env::default();
+        let provider = Address::generate(&env);
+        let contract_id = env.register(crate::SignalRegistry, ());
+        env.as_contract(&contract_id, || {
+            for i in 0..(RETURN_WINDOW + 10) {
+                record_close(&env, &provider, 100, 3_600 * (i as u64 % 2 + 1));
+            }
+            let series = get_series(&env, &provider);
+            assert_eq!(series.returns_bps.len(), RETURN_WINDOW);
+            assert_eq!(series.holding_secs.len(), RETURN_WINDOW);
+            let m = provider_metrics(&env, &provider);
+            assert_eq!(m.samples, RETURN_WINDOW);
+            assert_eq!(m.avg_holding_secs, 5_400);
+        });
+    }
+}
@@ -0,0 +1,369 @@
+//! Composable signal search with keyset cursors.
+//!
+//! A [`SignalQuery`] combines any number of filters, and results are ordered
+//! by up to [`MAX_SORT_KEYS`] [`SearchSort`] keys with the signal id (newest
+//! first) as the final tiebreak. The cursor returned with a page encodes the
+//! sort tuple of its last signal. The next page starts strictly after
+//! that tuple, so new signals arriving between calls never shift or repeat
+//! entries on later pages.
+//!
+//! For `active_only` queries the candidate set comes from the narrowest
+//! maintained index: asset pair, then the first required tag, then the
+//! active signals of the category. The full signal map is walked when the
+//! query filters on none of these, and for every query that admits inactive
+//! signals. Indexes are appended when a signal is created or tagged.
+//! `prune_index` drops ids that are no longer active so the lists stay within
+//! read budgets; historical queries do not depend on them.
+
+use soroban_sdk::{contracttype, Bytes, Env, Map, String, Vec};
+
+use crate::categories::{RiskLevel, SignalCategory};
+use crate::errors::SearchError;
+use crate::performance;
+use crate::reputation::{self, TrustScoreTier};
+use crate::types::{Signal, SignalStatus};
+use crate::StorageKey;
+
+pub const MAX_SORT_KEYS: u32 = 3;
+const MAX_LIMIT: u32 = 50;
+const DEFAULT_LIMIT: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignalQuery {
+    pub asset_pair: Option<String>,
+    /// Any of these categories; empty matches all.
+    pub categories: Vec<SignalCategory>,
+    /// Any of these risk levels; empty matches all.
+    pub risk_levels: Vec<RiskLevel>,
+    /// Signals must carry every tag listed.
+    pub tags: Vec<String>,
+    /// `NewUnproven` admits every provider.
+    pub min_trust_tier: TrustScoreTier,
+    pub min_confidence: Option<u32>,
+    pub max_confidence: Option<u32>,
+    /// Signals without an AI score never match.
+    pub min_ai_score: Option<u32>,
+    /// Inclusive bounds on the signal timestamp.
+    pub from_time: Option<u64>,
+    pub to_time: Option<u64>,
+    /// Only active, unexpired signals.
+    pub active_only: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchSortKey {
+    Recency,
+    Confidence,
+    AiScore,
+    Volume,
+    SuccessRate,
+    Roi,
+    ProviderTrust,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchSort {
+    pub key: SearchSortKey,
+    pub descending: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchPage {
+    pub signals: Vec<Signal>,
+    /// Pass back to fetch the next page; `None` on the last page.
+    pub next_cursor: Option<Bytes>,
+}
+
+/// Sort tuple of a signal; a page's last one is handed out as the cursor.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchCursor {
+    pub keys: Vec<i128>,
+    pub signal_id: u64,
+}
+
+/// Cursor bytes: one 16-byte big-endian value per sort key, then the 8-byte
+/// signal id.
+fn encode_cursor(env: &Env, cursor: &SearchCursor) -> Bytes {
+    let mut bytes = Bytes::new(env);
+    for key in cursor.keys.iter() {
+        bytes.extend_from_array(&key.to_be_bytes());
+    }
+    bytes.extend_from_array(&cursor.signal_id.to_be_bytes());
+    bytes
+}
+
+fn decode_cursor(env: &Env, bytes: &Bytes, key_count: u32) -> Option<SearchCursor> {
+    if bytes.len() != key_count * 16 + 8 {
+        return None;
+    }
+    let mut keys = Vec::new(env);
+    for i in 0..key_count {
+        let mut buf = [0u8; 16];
+        bytes.slice(i * 16..(i + 1) * 16).copy_into_slice(&mut buf);
+        keys.push_back(i128::from_be_bytes(buf));
+    }
+    let mut id = [0u8; 8];
+    bytes.slice(key_count * 16..).copy_into_slice(&mut id);
+    Some(SearchCursor {
+        keys,
+        signal_id: u64::from_be_bytes(id),
+    })
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SearchKey {
+    /// Ids of signals on an asset pair: `Vec<u64>`.
+    PairIndex(String),
+    /// Ids of signals carrying a tag: `Vec<u64>`.
+    TagIndex(String),
+}
+
+fn append_index(env: &Env, key: SearchKey, signal_id: u64) {
+    let mut ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env));
+    if !ids.contains(signal_id) {
+        ids.push_back(signal_id);
+        env.storage().persistent().set(&key, &ids);
+    }
+}
+
+/// Add a new signal to the pair and tag indexes.
+pub fn index_signal(env: &Env, signal: &Signal) {
+    append_index(
+        env,
+        SearchKey::PairIndex(signal.asset_pair.clone()),
+        signal.id,
+    );
+    index_tags(env, signal.id, &signal.tags);
+}
+
+pub fn index_tags(env: &Env, signal_id: u64, tags: &Vec<String>) {
+    for tag in tags.iter() {
+        append_index(env, SearchKey::TagIndex(tag), signal_id);
+    }
+}
+
+/// Drop ids of signals that are no longer active from an index. Returns the
+/// number removed.
+pub fn prune_index(env: &Env, key: SearchKey, signals: &Map<u64, Signal>) -> u32 {
+    let ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env));
+    let now = env.ledger().timestamp();
+    let mut kept: Vec<u64> = Vec::new(env);
+    for id in ids.iter() {
+        if signals
+            .get(id)
+            .is_some_and(|s| s.status == SignalStatus::Active && s.expiry > now)
+        {
+            kept.push_back(id);
+        }
+    }
+    let removed = ids.len() - kept.len();
+    if removed > 0 {
+        env.storage().persistent().set(&key, &kept);
+    }
+    removed
+}
+
+fn candidate_ids(env: &Env, query: &SignalQuery, signals: &Map<u64, Signal>) -> Vec<u64> {
+    // Indexes only keep active signals once pruned.
+    if !query.active_only {
+        return signals.keys();
+    }
+    let index = if let Some(pair) = &query.asset_pair {
+        Some(SearchKey::PairIndex(pair.clone()))
+    } else {
+        query.tags.first().map(SearchKey::TagIndex)
+    };
+    if let Some(key) = index {
+        return env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+    }
+    if !query.categories.is_empty() {
+        let by_category: Map<SignalCategory, Vec<u64>> = env
+            .storage()
+            .instance()
+            .get(&StorageKey::ActiveSignalsByCategory)
+            .unwrap_or(Map::new(env));
+        let mut ids = Vec::new(env);
+        for category in query.categories.iter() {
+            ids.append(&by_category.get(category).unwrap_or(Vec::new(env)));
+        }
+        return ids;
+    }
+    signals.keys()
+}
+
+fn matches(env: &Env, query: &SignalQuery, signal: &Signal) -> bool {
+    let now = env.ledger().timestamp();
+    if query.active_only && (signal.status != SignalStatus::Active || signal.expiry <= now) {
+        return false;
+    }
+    if query
+        .asset_pair
+        .as_ref()
+        .is_some_and(|pair| *pair != signal.asset_pair)
+    {
+        return false;
+    }
+    if !query.categories.is_empty() && !query.categories.contains(&signal.category) {
+        return false;
+    }
+    if !query.risk_levels.is_empty() && !query.risk_levels.contains(&signal.risk_level) {
+        return false;
+    }
+    for tag in query.tags.iter() {
+        if !signal.tags.contains(&tag) {
+            return false;
+        }
+    }
+    if query
+        .min_confidence
+        .is_some_and(|min| signal.confidence < min)
+        || query
+            .max_confidence
+            .is_some_and(|max| signal.confidence > max)
+    {
+        return false;
+    }
+    if let Some(min) = query.min_ai_score {
+        if signal.ai_validation_score.is_none_or(|score| score < min) {
+            return false;
+        }
+    }
+    if query.from_time.is_some_and(|from| signal.timestamp < from)
+        || query.to_time.is_some_and(|to| signal.timestamp > to)
+    {
+        return false;
+    }
+    if query.min_trust_tier != TrustScoreTier::NewUnproven {
+        let tier = reputation::get_trust_score(env, &signal.provider)
+            .map(|details| details.tier)
+            .unwrap_or(TrustScoreTier::NewUnproven);
+        if reputation::tier_rank(&tier) < reputation::tier_rank(&query.min_trust_tier) {
+            return false;
+        }
+    }
+    true
+}
+
+fn sort_value(env: &Env, key: &SearchSortKey, signal: &Signal) -> i128 {
+    match key {
+        SearchSortKey::Recency => signal.timestamp as i128,
+        SearchSortKey::Confidence => signal.confidence as i128,
+        SearchSortKey::AiScore => signal.ai_validation_score.map_or(-1, |s| s as i128),
+        SearchSortKey::Volume => signal.total_volume,
+        SearchSortKey::SuccessRate => {
+            if signal.executions > 0 {
+                (signal.successful_executions as i128 * 10_000) / signal.executions as i128
+            } else {
+                0
+            }
+        }
+        SearchSortKey::Roi => performance::signal_roi(env, signal),
+        SearchSortKey::ProviderTrust => reputation::get_trust_score(env, &signal.provider)
+            .map_or(0, |details| details.score as i128),
+    }
+}
+
+/// True when `a` sorts before `b`.
+fn precedes(sort: &Vec<SearchSort>, a: &SearchCursor, b: &SearchCursor) -> bool {
+    for (i, spec) in sort.iter().enumerate() {
+        let (x, y) = (a.keys.get(i as u32).unwrap(), b.keys.get(i as u32).unwrap());
+        if x != y {
+            return if spec.descending { x > y } else { x < y };
+        }
+    }
+    a.signal_id > b.signal_id
+}
+
+pub fn search(
+    env: &Env,
+    signals: &Map<u64, Signal>,
+    query: &SignalQuery,
+    sort: &Vec<SearchSort>,
+    cursor: Option<Bytes>,
+    limit: u32,
+) -> Result<SearchPage, SearchError> {
+    if sort.len() > MAX_SORT_KEYS {
+        return Err(SearchError::TooManySortKeys);
+    }
+    let after = match cursor {
+        Some(bytes) => {
+            Some(decode_cursor(env, &bytes, sort.len()).ok_or(SearchError::InvalidCursor)?)
+        }
+        None => None,
+    };
+    let limit = match limit {
+        0 => DEFAULT_LIMIT,
+        l => l.min(MAX_LIMIT),
+    };
+
+    // Keep the first `limit + 1` matches in order; the extra one tells us
+    // whether another page exists.
+    let mut page: Vec<(SearchCursor, Signal)> = Vec::new(env);
+    for id in candidate_ids(env, query, signals).iter() {
+        let Some(signal) = signals.get(id) else {
+            continue;
+        };
+        if !matches(env, query, &signal) {
+            continue;
+        }
+        let mut keys = Vec::new(env);
+        for spec in sort.iter() {
+            keys.push_back(sort_value(env, &spec.key, &signal));
+        }
+        let position = SearchCursor {
+            keys,
+            signal_id: id,
+        };
+        if after
+            .as_ref()
+            .is_some_and(|a| !precedes(sort, a, &position))
+        {
+            continue;
+        }
+        if page.len() > limit && !precedes(sort, &position, &page.last().unwrap().0) {
+            continue;
+        }
+        let mut at = page.len();
+        while at > 0 && precedes(sort, &position, &page.get(at - 1).unwrap().0) {
+            at -= 1;
+        }
+        page.insert(at, (position, signal));
+        if page.len() > limit + 1 {
+            page.pop_back();
+        }
+    }
+
+    let has_more = page.len() > limit;
+    let mut result = Vec::new(env);
+    let mut last: Option<SearchCursor> = None;
+    for (position, signal) in page.iter().take(limit as usize) {
+        result.push_back(signal);
+        last = Some(position);
+    }
+    Ok(SearchPage {
+        signals: result,
+        next_cursor: if has_more {
+            last.map(|c| encode_cursor(env, &c))
+        } else {
+            None
+        },
+    })
+}
@@ -586,6 +586,92 @@ fn test_fee_calculation_and_collection() {
     assert_eq!(breakdown.trade_amount_after_fee, 999_000_000); // 999 XLM
 }
 
+mod schedule_fee_collector {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+    use stellar_swipe_common::{AssetPair, FeeSchedule};
+
+    #[contract]
+    pub struct MockFeeCollector;
+
+    #[contractimpl]
+    impl MockFeeCollector {
+        pub fn set_fee_schedule(env: Env, schedule: FeeSchedule) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("schedule"), &schedule);
+        }
+
+        pub fn fee_schedule(env: Env, _pair: Option<AssetPair>) -> FeeSchedule {
+            env.storage()
+                .instance()
+                .get(&symbol_short!("schedule"))
+                .unwrap()
+        }
+    }
+}
+
+#[test]
+fn test_tiered_fee_preview_uses_fee_collector_schedule() {
+    use schedule_fee_collector::{MockFeeCollector, MockFeeCollectorClient};
+    use stellar_swipe_common::{FeeTier, StakingDiscount};
+
+    let env = Env::default();
+    env.mock_all_auths();
+
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // Flat FEE_BPS until a FeeCollector is registered.
+    assert_eq!(
+        client.get_fee_schedule(),
+        FeeSchedule::flat(&env, fees::FEE_BPS)
+    );
+
+    let schedule = FeeSchedule {
+        tiers: vec![
+            &env,
+            FeeTier {
+                min_volume_usd: 0,
+                maker_rate_bps: 10,
+                taker_rate_bps: 20,
+            },
+            FeeTier {
+                min_volume_usd: 50_000,
+                maker_rate_bps: 5,
+                taker_rate_bps: 15,
+            },
+        ],
+        staking_discounts: vec![
+            &env,
+            StakingDiscount {
+                min_staked: 1_000,
+                discount_bps: 5,
+            },
+        ],
+    };
+    let fee_collector = env.register(MockFeeCollector, ());
+    MockFeeCollectorClient::new(&env, &fee_collector).set_fee_schedule(&schedule);
+    client.set_fee_collector(&admin, &fee_collector);
+    assert_eq!(client.get_fee_schedule(), schedule);
+
+    // Entry-tier taker rate (20 bps).
+    let breakdown = client.calculate_tiered_fee_preview(&1_000_000, &FeeSide::Taker, &0, &0);
+    assert_eq!(breakdown.total_fee, 2_000);
+    assert_eq!(breakdown.platform_fee, 1_400);
+
+    // High-volume maker with a stake: 5 bps tier rate - 5 bps discount, floored at 1 bp.
+    let breakdown =
+        client.calculate_tiered_fee_preview(&1_000_000, &FeeSide::Maker, &60_000, &1_000);
+    assert_eq!(breakdown.total_fee, 100);
+    assert_eq!(breakdown.trade_amount_after_fee, 999_900);
+
+    // The flat preview is unaffected by the schedule.
+    assert_eq!(client.calculate_fee_preview(&1_000_000).total_fee, 1_000);
+}
+
 #[test]
 fn test_minimum_trade_enforcement() {
     let env = Env::default();
@@ -1209,3 +1295,1439 @@ fn migration_v1_to_v2_batches() {
         assert_eq!(s.id, sid);
     }
 }
+
+#[test]
+fn test_collaboration_payout_splits_after_approval() {
+    let env = Env::default();
+    env.mock_all_auths();
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    client.initialize(&Address::generate(&env));
+
+    let primary = Address::generate(&env);
+    let co_author = Address::generate(&env);
+    let signal_id = client.create_collaborative_signal(
+        &primary,
+        &vec![&env, co_author.clone()],
+        &vec![&env, 7_000u32, 3_000u32],
+        &String::from_str(&env, "XLM/USDC"),
+        &SignalAction::Buy,
+        &1_000_000,
+        &String::from_str(&env, "Joint call"),
+        &(env.ledger().timestamp() + 86_400),
+    );
+    assert_eq!(client.get_collaboration_payout_splits(&signal_id), None);
+
+    client.approve_collaborative_signal(&signal_id, &co_author);
+    let splits = client.get_collaboration_payout_splits(&signal_id).unwrap();
+    assert_eq!(splits.len(), 2);
+    assert_eq!(splits.get(0).unwrap().recipient, primary);
+    assert_eq!(splits.get(0).unwrap().share_bps, 7_000);
+    assert_eq!(splits.get(1).unwrap().share_bps, 3_000);
+}
+
+mod benchmark_oracle {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+    use stellar_swipe_common::oracle::OraclePrice;
+
+    #[contract]
+    pub struct MockBenchmarkOracle;
+
+    #[contractimpl]
+    impl MockBenchmarkOracle {
+        pub fn set_price(env: Env, pair: u32, price: i128) {
+            env.storage().instance().set(&pair, &price);
+        }
+
+        pub fn get_price(env: Env, pair: u32) -> OraclePrice {
+            OraclePrice {
+                price: env.storage().instance().get(&pair).unwrap(),
+                decimals: 7,
+                timestamp: env.ledger().timestamp(),
+                source: symbol_short!("mock"),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_closed_signals_record_alpha_and_provider_beta() {
+    use crate::benchmark::{BenchmarkConfig, BenchmarkSource};
+    use benchmark_oracle::{MockBenchmarkOracle, MockBenchmarkOracleClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let oracle_id = env.register(MockBenchmarkOracle, ());
+    let oracle = MockBenchmarkOracleClient::new(&env, &oracle_id);
+    let pair = String::from_str(&env, "XLM/USDC");
+    client.set_benchmark_config(
+        &admin,
+        &BenchmarkConfig {
+            oracle: oracle_id.clone(),
+            source: BenchmarkSource::BaseAsset,
+        },
+    );
+    client.set_benchmark_pair_id(&admin, &pair, &1);
+
+    // Signal return = 2 x benchmark + 100 bps, so beta is 2.0.
+    let provider = Address::generate(&env);
+    let executor = Address::generate(&env);
+    let benchmark_moves: [i128; 5] = [500, 300, 400, 600, 200];
+    for (i, b) in benchmark_moves.iter().enumerate() {
+        oracle.set_price(&1, &10_000_000);
+        let signal_id = client.create_signal(
+            &provider,
+            &pair,
+            &SignalAction::Buy,
+            &100_000,
+            &String::from_str(&env, "Benchmark test"),
+            &(env.ledger().timestamp() + 86_400),
+            &SignalCategory::SWING,
+            &vec![&env, String::from_str(&env, "test")],
+            &RiskLevel::Medium,
+        );
+        // Snapshotted at creation, without opting in.
+        assert!(client.get_signal_benchmark(&signal_id).is_some());
+        assert_eq!(
+            client.try_snapshot_signal_benchmark(&signal_id),
+            Err(Ok(crate::errors::BenchmarkError::AlreadySnapshotted))
+        );
+
+        oracle.set_price(&1, &(10_000_000 + b * 1_000));
+        let exit = 100_000 + (2 * b + 100) * 10;
+        client.record_trade_execution(&executor, &signal_id, &100_000, &exit, &1_000);
+
+        let signal = client.get_signal(&signal_id).unwrap();
+        assert_eq!(signal.benchmark_return_bps, Some(*b as i64));
+        assert_eq!(signal.alpha_bps, Some((*b + 100) as i64));
+        env.ledger()
+            .set_timestamp(env.ledger().timestamp() + 3_600 * (i as u64 + 1));
+    }
+
+    // A signal whose benchmark could not be priced at creation can only be
+    // snapshotted within the window; after that it is never measured.
+    let unpriced = String::from_str(&env, "BTC/USDC");
+    client.set_benchmark_pair_id(&admin, &unpriced, &2);
+    let late = client.create_signal(
+        &provider,
+        &unpriced,
+        &SignalAction::Buy,
+        &100_000,
+        &String::from_str(&env, "Benchmark test"),
+        &(env.ledger().timestamp() + 86_400),
+        &SignalCategory::SWING,
+        &vec![&env, String::from_str(&env, "test")],
+        &RiskLevel::Medium,
+    );
+    assert!(client.get_signal_benchmark(&late).is_none());
+    oracle.set_price(&2, &10_000_000);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + crate::benchmark::SNAPSHOT_WINDOW_SECONDS + 1);
+    assert_eq!(
+        client.try_snapshot_signal_benchmark(&late),
+        Err(Ok(crate::errors::BenchmarkError::SnapshotWindowClosed))
+    );
+    client.record_trade_execution(&executor, &late, &100_000, &101_000, &1_000);
+    assert_eq!(client.get_signal(&late).unwrap().alpha_bps, None);
+
+    let metrics = client.get_provider_benchmark_metrics(&provider);
+    assert_eq!(metrics.samples, 5);
+    assert_eq!(metrics.avg_alpha_bps, 500);
+    assert_eq!(metrics.tracking_error_bps, 141);
+    assert_eq!(metrics.information_ratio_bps, 35_460);
+    assert_eq!(metrics.beta_bps, 20_000);
+}
+
+mod resolution_oracle {
+    use soroban_sdk::{contract, contractimpl, Env};
+    use stellar_swipe_common::AssetPair;
+
+    #[contract]
+    pub struct MockHistoryOracle;
+
+    #[contractimpl]
+    impl MockHistoryOracle {
+        pub fn set_price(env: Env, timestamp: u64, price: i128) {
+            env.storage().instance().set(&(timestamp / 300), &price);
+        }
+
+        pub fn get_historical_price(env: Env, _pair: AssetPair, timestamp: u64) -> Option<i128> {
+            env.storage().instance().get(&(timestamp / 300))
+        }
+    }
+}
+
+#[test]
+fn test_signals_resolve_from_oracle_crossings_and_horizon() {
+    use crate::errors::{ResolutionError, SignalOutcomeError};
+    use crate::resolution::{ResolutionConfig, ResolutionKind, SignalTargets};
+    use resolution_oracle::{MockHistoryOracle, MockHistoryOracleClient};
+    use stellar_swipe_common::{Asset as CommonAsset, AssetPair};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(100_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let oracle_id = env.register(MockHistoryOracle, ());
+    let oracle = MockHistoryOracleClient::new(&env, &oracle_id);
+    let pair = String::from_str(&env, "XLM/USDC");
+    client.set_resolution_config(
+        &admin,
+        &ResolutionConfig {
+            oracle: oracle_id,
+            max_twap_deviation_bps: 1_000,
+        },
+    );
+    client.set_resolution_pair(
+        &admin,
+        &pair,
+        &AssetPair {
+            base: CommonAsset {
+                code: String::from_str(&env, "XLM"),
+                issuer: None,
+            },
+            quote: CommonAsset {
+                code: String::from_str(&env, "USDC"),
+                issuer: None,
+            },
+        },
+    );
+
+    let provider = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let create = |targets: &SignalTargets| {
+        client.create_signal_with_targets(
+            &provider,
+            &pair,
+            &SignalAction::Buy,
+            &100_000,
+            &String::from_str(&env, "Resolution test"),
+            &SignalCategory::SWING,
+            &vec![&env, String::from_str(&env, "test")],
+            &RiskLevel::Medium,
+            targets,
+        )
+    };
+    let targets = SignalTargets {
+        take_profit: 110_000,
+        stop_loss: 95_000,
+        horizon: 86_400,
+    };
+
+    // Take-profit on the wrong side of entry is rejected.
+    let bad = client.try_create_signal_with_targets(
+        &provider,
+        &pair,
+        &SignalAction::Buy,
+        &100_000,
+        &String::from_str(&env, "Resolution test"),
+        &SignalCategory::SWING,
+        &vec![&env, String::from_str(&env, "test")],
+        &RiskLevel::Medium,
+        &SignalTargets {
+            take_profit: 90_000,
+            ..targets.clone()
+        },
+    );
+    assert_eq!(bad, Err(Ok(AdminError::InvalidParameter)));
+
+    let winner = create(&targets);
+
+    // Price drifts up over an hour without reaching either target.
+    for i in 0..12u64 {
+        oracle.set_price(&(100_000 + i * 300), &(104_000 + i as i128 * 500));
+    }
+    env.ledger().set_timestamp(103_400);
+
+    // Executions no longer move a targeted signal's status.
+    let executor = Address::generate(&env);
+    client.record_trade_execution(&executor, &winner, &100_000, &120_000, &1_000);
+    assert_eq!(
+        client.get_signal(&winner).unwrap().status,
+        SignalStatus::Active
+    );
+
+    // Scanning the history so far finds no crossing.
+    assert_eq!(
+        client.resolve_signal(&keeper, &winner),
+        SignalStatus::Active
+    );
+    assert!(client.get_signal_resolution(&winner).is_none());
+
+    // The first crossing bucket resolves the signal, whenever the keeper
+    // gets to it.
+    oracle.set_price(&103_600, &111_000);
+    oracle.set_price(&103_900, &112_000);
+    env.ledger().set_timestamp(110_000);
+    assert!(!client.is_signal_successful(&winner));
+    assert_eq!(
+        client.resolve_signal(&keeper, &winner),
+        SignalStatus::Successful
+    );
+    assert!(client.is_signal_successful(&winner));
+    let resolved = client.get_signal_resolution(&winner).unwrap();
+    assert_eq!(resolved.kind, ResolutionKind::TakeProfit);
+    // Reported at the start of the oracle bucket holding 103_600.
+    assert_eq!(resolved.observed_at, 103_500);
+    assert_eq!(resolved.price, 111_000);
+    assert_eq!(resolved.realized_roi_bps, 1_100);
+    assert_eq!(
+        client.try_resolve_signal(&keeper, &winner),
+        Err(Ok(ResolutionError::AlreadyResolved))
+    );
+    // The executor can no longer report a competing outcome.
+    let trade_executor = Address::generate(&env);
+    client.set_trade_executor(&admin, &trade_executor);
+    assert_eq!(
+        client.try_record_signal_outcome(&trade_executor, &winner, &SignalOutcome::Loss),
+        Err(Ok(SignalOutcomeError::OutcomeAlreadyRecorded))
+    );
+
+    // A single spiked bucket far from the TWAP cannot resolve a signal.
+    let loser = create(&targets);
+    for i in 0..12u64 {
+        oracle.set_price(&(110_100 + i * 300), &99_000);
+    }
+    oracle.set_price(&113_700, &80_000);
+
+    // Neither target crossed: the signal is swept to Expired and resolves
+    // at the horizon price.
+    let horizon_end = 110_000 + 86_400;
+    for i in 0..13u64 {
+        oracle.set_price(&(horizon_end - 3_600 + i * 300), &99_000);
+    }
+    env.ledger().set_timestamp(horizon_end + 60);
+    client.cleanup_expired_signals(&10);
+    assert_eq!(
+        client.get_signal(&loser).unwrap().status,
+        SignalStatus::Expired
+    );
+    // Swept but not yet resolved: the executor still cannot score it.
+    assert_eq!(
+        client.try_record_signal_outcome(&trade_executor, &loser, &SignalOutcome::Profit),
+        Err(Ok(SignalOutcomeError::OracleResolved))
+    );
+    let mut status = SignalStatus::Expired;
+    for _ in 0..8 {
+        status = client.resolve_signal(&keeper, &loser);
+        if status != SignalStatus::Expired {
+            break;
+        }
+    }
+    assert_eq!(status, SignalStatus::Failed);
+    let resolved = client.get_signal_resolution(&loser).unwrap();
+    assert_eq!(resolved.kind, ResolutionKind::Horizon);
+    assert_eq!(resolved.realized_roi_bps, -100);
+
+    let stats = client.get_provider_stats(&provider).unwrap();
+    assert_eq!(stats.total_signals, 2);
+    assert_eq!(stats.successful_signals, 1);
+    assert_eq!(stats.failed_signals, 1);
+    assert_eq!(stats.avg_return, 500);
+
+    // A window that aged out of oracle history before it was scanned is
+    // voided without touching the provider's stats.
+    let stale = create(&targets);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + crate::resolution::ORACLE_HISTORY_SECONDS + 1);
+    assert_eq!(
+        client.resolve_signal(&keeper, &stale),
+        SignalStatus::Expired
+    );
+    assert_eq!(
+        client.get_signal_resolution(&stale).unwrap().kind,
+        ResolutionKind::HistoryUnavailable
+    );
+    assert_eq!(
+        client.get_provider_stats(&provider).unwrap().total_signals,
+        2
+    );
+}
+
+#[test]
+fn test_record_combo_execution_tracks_per_leg_pnl() {
+    use crate::combos::ComponentSignal;
+    use crate::errors::ComboError;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let provider = Address::generate(&env);
+    let user = Address::generate(&env);
+    let executor = Address::generate(&env);
+    let mut signal_ids = Vec::new(&env);
+    for _ in 0..2 {
+        signal_ids.push_back(client.create_signal(
+            &provider,
+            &String::from_str(&env, "XLM/USDC"),
+            &SignalAction::Buy,
+            &100_000,
+            &String::from_str(&env, "Leg"),
+            &(env.ledger().timestamp() + 3_600),
+            &SignalCategory::SWING,
+            &Vec::new(&env),
+            &RiskLevel::Medium,
+        ));
+    }
+    let (sig1, sig2) = (signal_ids.get(0).unwrap(), signal_ids.get(1).unwrap());
+    let mut components = Vec::new(&env);
+    for signal_id in signal_ids.iter() {
+        components.push_back(ComponentSignal {
+            signal_id,
+            weight: 5_000,
+            condition: combos::ConditionGate::None,
+        });
+    }
+    let combo_id = client.create_combo_signal(
+        &provider,
+        &String::from_str(&env, "Legs"),
+        &components,
+        &ComboType::Sequential,
+    );
+
+    let leg = |signal_id: u64, amount: i128, roi: i128| ComponentExecution {
+        signal_id,
+        amount,
+        skipped: amount == 0,
+        roi,
+    };
+    // First adoption unwound before the second leg opened.
+    let partial = vec![&env, leg(sig1, 500_000, 400), leg(sig2, 0, 0)];
+    let partial_pnls = vec![&env, 20_000i128, 0];
+    assert_eq!(
+        client.try_record_combo_execution(
+            &executor,
+            &user,
+            &combo_id,
+            &1_000_000,
+            &partial,
+            &partial_pnls,
+        ),
+        Err(Ok(ComboError::Unauthorized))
+    );
+
+    client.set_trade_executor(&admin, &executor);
+    client.record_combo_execution(
+        &executor,
+        &user,
+        &combo_id,
+        &1_000_000,
+        &partial,
+        &partial_pnls,
+    );
+    let full = vec![&env, leg(sig1, 500_000, 400), leg(sig2, 500_000, -200)];
+    client.record_combo_execution(
+        &executor,
+        &user,
+        &combo_id,
+        &1_000_000,
+        &full,
+        &vec![&env, 20_000i128, -10_000],
+    );
+    assert_eq!(
+        client.try_record_combo_execution(
+            &executor,
+            &user,
+            &combo_id,
+            &1_000_000,
+            &vec![&env, leg(sig2, 500_000, 0), leg(sig1, 500_000, 0)],
+            &vec![&env, 0i128, 0],
+        ),
+        Err(Ok(ComboError::InvalidLegs))
+    );
+    assert_eq!(
+        client.try_record_combo_execution(
+            &executor,
+            &user,
+            &combo_id,
+            &1_000_000,
+            &full,
+            &vec![&env, 0i128],
+        ),
+        Err(Ok(ComboError::InvalidLegs))
+    );
+
+    let perf = client.get_combo_performance(&combo_id).unwrap();
+    assert_eq!(perf.total_executions, 2);
+    assert_eq!(perf.total_pnl, 30_000);
+    assert_eq!(perf.combined_roi, 250); // (400 + 100) / 2
+
+    let first = perf.legs.get(0).unwrap();
+    assert_eq!((first.executions, first.skipped), (2, 0));
+    assert_eq!(
+        (first.total_volume, first.total_pnl, first.average_roi),
+        (1_000_000, 40_000, 400)
+    );
+    let second = perf.legs.get(1).unwrap();
+    assert_eq!((second.executions, second.skipped), (1, 1));
+    assert_eq!(
+        (second.total_volume, second.total_pnl, second.average_roi),
+        (500_000, -10_000, -200)
+    );
+}
+
+#[test]
+fn test_bundle_copy_rebalance_and_performance() {
+    use crate::bundles::{BundleComponent, BundleStatus};
+    use crate::errors::BundleError;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let provider = Address::generate(&env);
+    let user = Address::generate(&env);
+    let fee_token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    StellarAssetClient::new(&env, &fee_token).mint(&user, &100_000);
+    let signal = |price: i128, ttl: u64| {
+        client.create_signal(
+            &provider,
+            &String::from_str(&env, "XLM/USDC"),
+            &SignalAction::Buy,
+            &price,
+            &String::from_str(&env, "Bundle leg"),
+            &(env.ledger().timestamp() + ttl),
+            &SignalCategory::SWING,
+            &Vec::new(&env),
+            &RiskLevel::Medium,
+        )
+    };
+    let (s1, s2, s3) = (
+        signal(100_000, 7_200),
+        signal(200_000, 7_200),
+        signal(300_000, 600),
+    );
+    let component = |signal_id: u64, weight: u32| BundleComponent { signal_id, weight };
+
+    assert_eq!(
+        client.try_create_bundle(
+            &provider,
+            &String::from_str(&env, "Bad"),
+            &vec![&env, component(s1, 5_000), component(s2, 4_000)],
+            &100,
+            &fee_token,
+            &1_000,
+        ),
+        Err(Ok(BundleError::InvalidWeights))
+    );
+    assert_eq!(
+        client.try_create_bundle(
+            &provider,
+            &String::from_str(&env, "Bad"),
+            &vec![&env, component(s1, 5_000), component(s2, 5_000)],
+            &2_000,
+            &fee_token,
+            &1_000,
+        ),
+        Err(Ok(BundleError::FeeTooHigh))
+    );
+
+    let bundle_id = client.create_bundle(
+        &provider,
+        &String::from_str(&env, "Majors"),
+        &vec![
+            &env,
+            component(s1, 5_000),
+            component(s2, 3_000),
+            component(s3, 2_000),
+        ],
+        &100,
+        &fee_token,
+        &1_000,
+    );
+
+    // Below the minimum fee the copy leaves nothing to allocate.
+    assert_eq!(
+        client.try_copy_bundle(&user, &bundle_id, &1_000),
+        Err(Ok(BundleError::InvalidAmount))
+    );
+    // A small copy pays the minimum fee rather than rounding to zero.
+    let copy = client.copy_bundle(&user, &bundle_id, &5_000);
+    assert_eq!(copy.fee, 1_000);
+    TokenClient::new(&env, &fee_token).transfer(&provider, &user, &1_000);
+
+    let copy = client.copy_bundle(&user, &bundle_id, &1_000_000);
+    assert_eq!(copy.fee, 10_000);
+    assert_eq!(
+        TokenClient::new(&env, &fee_token).balance(&provider),
+        10_000
+    );
+    let amounts: Vec<i128> = {
+        let mut v = Vec::new(&env);
+        for a in copy.allocations.iter() {
+            v.push_back(a.amount);
+        }
+        v
+    };
+    assert_eq!(amounts, vec![&env, 495_000, 297_000, 198_000]);
+    assert!(client.get_copy_record(&user, &s3).is_some());
+    // Adoptions are only counted once the executor reports the copy trades.
+    assert_eq!(client.get_signal(&s1).unwrap().adoption_count, 0);
+
+    // s3 expires: its weight is spread pro rata over s1 and s2.
+    env.ledger().set_timestamp(2_000);
+    assert_eq!(client.rebalance_bundle(&bundle_id), 1);
+    let bundle = client.get_bundle(&bundle_id).unwrap();
+    assert_eq!(
+        bundle.components,
+        vec![&env, component(s1, 6_250), component(s2, 3_750)]
+    );
+    assert_eq!(client.rebalance_bundle(&bundle_id), 0);
+
+    let copy = client.copy_bundle(&user, &bundle_id, &1_000_000);
+    assert_eq!(copy.allocations.len(), 2);
+    assert_eq!(TokenClient::new(&env, &fee_token).balance(&user), 80_000);
+    assert_eq!(copy.allocations.get(0).unwrap().amount, 618_750);
+    assert_eq!(copy.allocations.get(1).unwrap().amount, 371_250);
+
+    let executor = Address::generate(&env);
+    client.record_trade_execution(&executor, &s1, &100_000, &101_000, &1_000);
+    let perf = client.get_bundle_performance(&bundle_id).unwrap();
+    assert_eq!(perf.copies, 3);
+    assert_eq!(perf.total_volume, 2_005_000);
+    assert_eq!(perf.total_fees, 21_000);
+    // Only s1 has a return: 100 bps over 1_115_750 of 1_984_000 copied.
+    assert_eq!(perf.average_roi, 100 * 1_115_750 / 1_984_000);
+
+    // Once every component is gone the bundle closes.
+    env.ledger().set_timestamp(10_000);
+    assert_eq!(client.rebalance_bundle(&bundle_id), 2);
+    assert_eq!(
+        client.get_bundle(&bundle_id).unwrap().status,
+        BundleStatus::Closed
+    );
+    assert_eq!(
+        client.try_copy_bundle(&user, &bundle_id, &1_000_000),
+        Err(Ok(BundleError::BundleClosed))
+    );
+}
+
+mod subscription_portfolio {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    #[contract]
+    pub struct MockSubscriptionPortfolio;
+
+    #[contractimpl]
+    impl MockSubscriptionPortfolio {
+        pub fn set_subscribed(env: Env, user: Address, active: bool) {
+            env.storage().instance().set(&user, &active);
+        }
+
+        pub fn check_subscription(env: Env, user: Address, _provider: Address) -> bool {
+            env.storage().instance().get(&user).unwrap_or(false)
+        }
+    }
+}
+
+#[test]
+fn test_private_signal_envelopes_rotation_and_reveal() {
+    use crate::benchmark::{BenchmarkConfig, BenchmarkSource};
+    use crate::errors::PrivateSignalError;
+    use crate::private_signals::{KeyEnvelope, SealedBody};
+    use benchmark_oracle::{MockBenchmarkOracle, MockBenchmarkOracleClient};
+    use soroban_sdk::{Bytes, BytesN};
+    use stellar_swipe_common::hash_signal_commitment;
+    use subscription_portfolio::{MockSubscriptionPortfolio, MockSubscriptionPortfolioClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let portfolio_id = env.register(MockSubscriptionPortfolio, ());
+    let portfolio = MockSubscriptionPortfolioClient::new(&env, &portfolio_id);
+    client.set_user_portfolio(&admin, &portfolio_id);
+
+    let provider = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let pair = String::from_str(&env, "XLM/USDC");
+    let oracle_id = env.register(MockBenchmarkOracle, ());
+    MockBenchmarkOracleClient::new(&env, &oracle_id).set_price(&1, &10_000_000);
+    client.set_benchmark_config(
+        &admin,
+        &BenchmarkConfig {
+            oracle: oracle_id,
+            source: BenchmarkSource::BaseAsset,
+        },
+    );
+    client.set_benchmark_pair_id(&admin, &pair, &1);
+    let rationale = String::from_str(&env, "Breakout above range high");
+    let commitment = hash_signal_commitment(&env, &provider, &pair, 0, 125_000, &rationale, 42);
+    let body = |tag: u8| SealedBody {
+        ciphertext: Bytes::from_array(&env, &[tag; 48]),
+        nonce: BytesN::from_array(&env, &[tag; 12]),
+    };
+    let envelope = |subscriber: &Address, epoch: u32| KeyEnvelope {
+        subscriber: subscriber.clone(),
+        epoch,
+        ephemeral_public_key: BytesN::from_array(&env, &[epoch as u8; 32]),
+        sealed_key: Bytes::from_array(&env, &[7; 48]),
+    };
+
+    let signal_id = client.create_private_signal(
+        &provider,
+        &pair,
+        &SignalAction::Buy,
+        &(env.ledger().timestamp() + 86_400),
+        &SignalCategory::PREMIUM,
+        &Vec::new(&env),
+        &RiskLevel::Medium,
+        &body(1),
+        &commitment,
+    );
+    // Sealed signals stay out of the public map until revealed.
+    assert!(client.get_signal(&signal_id).is_none());
+    let sealed = client.get_sealed_signal(&signal_id).unwrap();
+    assert_eq!(sealed.price, 0);
+    assert_eq!(sealed.rationale, String::from_str(&env, ""));
+    // The benchmark is taken at creation, not when the price is revealed.
+    assert!(client.get_signal_benchmark(&signal_id).is_some());
+
+    client.register_encryption_key(&alice, &BytesN::from_array(&env, &[1; 32]));
+    client.register_encryption_key(&bob, &BytesN::from_array(&env, &[2; 32]));
+    portfolio.set_subscribed(&alice, &true);
+    assert_eq!(
+        client.try_add_key_envelopes(&provider, &signal_id, &vec![&env, envelope(&bob, 0)]),
+        Err(Ok(PrivateSignalError::SubscriptionRequired))
+    );
+    portfolio.set_subscribed(&bob, &true);
+    assert_eq!(
+        client.add_key_envelopes(
+            &provider,
+            &signal_id,
+            &vec![&env, envelope(&alice, 0), envelope(&bob, 0)]
+        ),
+        2
+    );
+
+    // Bob lapses: his envelope is revoked and the key must rotate.
+    portfolio.set_subscribed(&bob, &false);
+    assert_eq!(
+        client.revoke_lapsed_subscribers(&signal_id, &vec![&env, alice.clone(), bob.clone()]),
+        1
+    );
+    assert!(client.get_key_envelope(&signal_id, &bob).is_none());
+    assert!(
+        client
+            .get_private_signal(&signal_id)
+            .unwrap()
+            .rotation_required
+    );
+    assert_eq!(
+        client.try_add_key_envelopes(&provider, &signal_id, &vec![&env, envelope(&alice, 0)]),
+        Err(Ok(PrivateSignalError::RotationRequired))
+    );
+
+    assert_eq!(
+        client.try_rotate_signal_key(
+            &provider,
+            &signal_id,
+            &body(2),
+            &vec![&env, envelope(&alice, 0)]
+        ),
+        Err(Ok(PrivateSignalError::StaleEpoch))
+    );
+    assert_eq!(
+        client.rotate_signal_key(
+            &provider,
+            &signal_id,
+            &body(2),
+            &vec![&env, envelope(&alice, 1)]
+        ),
+        1
+    );
+    let private = client.get_private_signal(&signal_id).unwrap();
+    assert_eq!(private.key_epoch, 1);
+    assert!(!private.rotation_required);
+    assert_eq!(
+        client.get_key_envelope(&signal_id, &alice).unwrap().epoch,
+        1
+    );
+
+    // Revealing a better price than committed fails; the committed one lands
+    // with the original timestamp.
+    env.ledger().set_timestamp(5_000);
+    assert_eq!(
+        client.try_reveal_private_signal(&provider, &signal_id, &110_000, &rationale, &42),
+        Err(Ok(PrivateSignalError::CommitmentMismatch))
+    );
+    client.reveal_private_signal(&provider, &signal_id, &125_000, &rationale, &42);
+    let revealed = client.get_signal(&signal_id).unwrap();
+    assert_eq!(revealed.price, 125_000);
+    assert_eq!(revealed.rationale, rationale);
+    assert_eq!(revealed.timestamp, 1_000);
+    assert_eq!(
+        client.try_reveal_private_signal(&provider, &signal_id, &125_000, &rationale, &42),
+        Err(Ok(PrivateSignalError::AlreadyRevealed))
+    );
+    assert!(client.get_sealed_signal(&signal_id).is_none());
+
+    // A second private signal is never revealed: once its deadline passes it
+    // is dropped and the provider's reputation decays.
+    let unrevealed = client.create_private_signal(
+        &provider,
+        &pair,
+        &SignalAction::Sell,
+        &(env.ledger().timestamp() + 3_600),
+        &SignalCategory::PREMIUM,
+        &Vec::new(&env),
+        &RiskLevel::Medium,
+        &body(3),
+        &commitment,
+    );
+    let deadline = client
+        .get_private_signal(&unrevealed)
+        .unwrap()
+        .reveal_deadline;
+    assert_eq!(
+        deadline,
+        5_000 + 3_600 + crate::private_signals::REVEAL_GRACE_SECONDS
+    );
+    assert_eq!(
+        client.try_expire_private_signal(&unrevealed),
+        Err(Ok(PrivateSignalError::RevealWindowOpen))
+    );
+    env.ledger().set_timestamp(deadline + 1);
+    assert_eq!(
+        client.try_reveal_private_signal(&provider, &unrevealed, &125_000, &rationale, &42),
+        Err(Ok(PrivateSignalError::RevealWindowClosed))
+    );
+    client.expire_private_signal(&unrevealed);
+    assert!(client.get_private_signal(&unrevealed).is_none());
+    assert!(client.get_sealed_signal(&unrevealed).is_none());
+    assert_eq!(client.get_provider_reputation_score(&provider), 45);
+}
+
+#[test]
+fn test_signal_commitment_reveal_backdates_and_expiry_penalises() {
+    use crate::commitments::{CommitmentStatus, REVEAL_WINDOW_SECONDS};
+    use crate::errors::CommitmentError;
+    use crate::types::SignalData;
+    use stellar_swipe_common::hash_signal_commitment;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(10_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let provider = Address::generate(&env);
+    let data = SignalData {
+        asset_pair: String::from_str(&env, "XLM/USDC"),
+        action: SignalAction::Sell,
+        price: 98_000,
+        rationale: String::from_str(&env, "Rejection at resistance"),
+    };
+    let hash = hash_signal_commitment(
+        &env,
+        &provider,
+        &data.asset_pair,
+        1,
+        data.price,
+        &data.rationale,
+        7,
+    );
+    let expiry = env.ledger().timestamp() + 86_400;
+    let commit = || {
+        client.try_commit_signal(
+            &provider,
+            &data.asset_pair,
+            &SignalCategory::SWING,
+            &expiry,
+            &vec![&env, String::from_str(&env, "range")],
+            &RiskLevel::Medium,
+            &hash,
+        )
+    };
+    let commitment_id = commit().unwrap().unwrap();
+    // One pending commitment per provider and pair.
+    assert_eq!(commit(), Err(Ok(CommitmentError::AlreadyPending)));
+    let reserved = client
+        .get_signal_commitment(&commitment_id)
+        .unwrap()
+        .signal_id;
+    assert!(client.get_signal(&reserved).is_none());
+
+    env.ledger().set_timestamp(10_000 + 3_600);
+    let mut altered = data.clone();
+    altered.price = 97_000;
+    assert_eq!(
+        client.try_reveal_signal(&provider, &commitment_id, &altered, &7),
+        Err(Ok(CommitmentError::CommitmentMismatch))
+    );
+    let signal_id = client.reveal_signal(&provider, &commitment_id, &data, &7);
+    assert_eq!(signal_id, reserved);
+    let signal = client.get_signal(&signal_id).unwrap();
+    assert_eq!(signal.category, SignalCategory::SWING);
+    assert_eq!(signal.submitted_at, 10_000);
+    assert_eq!(signal.timestamp, 10_000);
+    assert_eq!(signal.price, 98_000);
+    // Expiry, tags and risk level come from the commitment.
+    assert_eq!(signal.expiry, expiry);
+    assert_eq!(signal.tags, vec![&env, String::from_str(&env, "range")]);
+    assert_eq!(signal.risk_level, RiskLevel::Medium);
+    let record = client.get_signal_commitment(&commitment_id).unwrap();
+    assert_eq!(record.status, CommitmentStatus::Revealed);
+    assert_eq!(record.signal_id, signal_id);
+
+    // Revealing frees the pair; a second commitment is never revealed.
+    let abandoned = commit().unwrap().unwrap();
+    assert_eq!(
+        client.try_expire_signal_commitment(&abandoned),
+        Err(Ok(CommitmentError::RevealWindowOpen))
+    );
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + REVEAL_WINDOW_SECONDS + 1);
+    assert_eq!(client.get_provider_reputation_score(&provider), 50);
+    client.expire_signal_commitment(&abandoned);
+    assert_eq!(client.get_provider_reputation_score(&provider), 45);
+    assert_eq!(
+        client.try_reveal_signal(&provider, &abandoned, &data, &7),
+        Err(Ok(CommitmentError::NotPending))
+    );
+}
+
+#[test]
+fn test_contest_escrow_pays_curve_and_refunds_on_cancel() {
+    use crate::contests::{
+        ContestMetric, ContestPrizeConfig, ContestStatus, MIN_SPONSORED_PRIZE_POOL,
+    };
+    use crate::errors::ContestError;
+    use crate::reputation::TrustScoreTier;
+    use crate::stake::DEFAULT_MINIMUM_STAKE;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(10_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let token = TokenClient::new(&env, &token_id);
+    let creator = Address::generate(&env);
+    let sponsor = Address::generate(&env);
+    let pool = MIN_SPONSORED_PRIZE_POOL;
+    StellarAssetClient::new(&env, &token_id).mint(&creator, &(2 * pool));
+    StellarAssetClient::new(&env, &token_id).mint(&sponsor, &pool);
+
+    let config = ContestPrizeConfig {
+        token: token_id.clone(),
+        payout_bps: vec![&env, 6_000, 4_000],
+        min_stake: DEFAULT_MINIMUM_STAKE,
+        min_trust_tier: TrustScoreTier::NewUnproven,
+    };
+    let start = env.ledger().timestamp();
+    let end = start + 86_400;
+    assert_eq!(
+        client.try_create_contest(
+            &creator,
+            &String::from_str(&env, "Bad curve"),
+            &start,
+            &end,
+            &ContestMetric::HighestROI,
+            &1,
+            &pool,
+            &ContestPrizeConfig {
+                payout_bps: vec![&env, 6_000, 3_000],
+                ..config.clone()
+            },
+        ),
+        Err(Ok(ContestError::InvalidPayoutCurve))
+    );
+    // Sponsors need a real pool; the admin does not.
+    assert_eq!(
+        client.try_create_contest(
+            &creator,
+            &String::from_str(&env, "Tiny"),
+            &start,
+            &end,
+            &ContestMetric::HighestROI,
+            &1,
+            &(pool - 1),
+            &config,
+        ),
+        Err(Ok(ContestError::PrizePoolTooSmall))
+    );
+    let contest_id = client.create_contest(
+        &creator,
+        &String::from_str(&env, "Weekly ROI"),
+        &start,
+        &end,
+        &ContestMetric::HighestROI,
+        &1,
+        &pool,
+        &config,
+    );
+    assert_eq!(
+        client.sponsor_contest(&sponsor, &contest_id, &(pool / 2)),
+        3 * pool / 2
+    );
+    assert_eq!(token.balance(&contract_id), 3 * pool / 2);
+
+    // p3 holds no stake and is kept out of the contest.
+    let executor = Address::generate(&env);
+    let (p1, p2, p3) = (
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    );
+    client.stake_tokens(&p1, &DEFAULT_MINIMUM_STAKE);
+    client.stake_tokens(&p2, &DEFAULT_MINIMUM_STAKE);
+    for (provider, exit) in [(&p1, 130_000i128), (&p2, 110_000), (&p3, 150_000)] {
+        let signal_id = client.create_signal(
+            provider,
+            &String::from_str(&env, "XLM/USDC"),
+            &SignalAction::Buy,
+            &100_000,
+            &String::from_str(&env, "Contest entry"),
+            &(env.ledger().timestamp() + 3_600),
+            &SignalCategory::SWING,
+            &Vec::new(&env),
+            &RiskLevel::Medium,
+        );
+        client.record_trade_execution(&executor, &signal_id, &100_000, &exit, &1_000);
+    }
+    assert_eq!(client.get_contest_leaderboard(&contest_id).len(), 2);
+
+    env.ledger().set_timestamp(end + 1);
+    assert_eq!(
+        client.try_sponsor_contest(&sponsor, &contest_id, &1_000),
+        Err(Ok(ContestError::ContestEnded))
+    );
+    assert_eq!(
+        client.finalize_contest(&contest_id),
+        vec![&env, p1.clone(), p2.clone()]
+    );
+    assert_eq!(token.balance(&p1), 9 * pool / 10);
+    assert_eq!(token.balance(&p2), 6 * pool / 10);
+    assert_eq!(client.get_provider_prize(&contest_id, &p2), 6 * pool / 10);
+    assert_eq!(token.balance(&contract_id), 0);
+
+    // A cancelled contest refunds its sponsors. Once it has entries only
+    // the admin can cancel it.
+    let start = env.ledger().timestamp();
+    let cancelled = client.create_contest(
+        &creator,
+        &String::from_str(&env, "Cancelled"),
+        &start,
+        &(start + 86_400),
+        &ContestMetric::HighestROI,
+        &1,
+        &pool,
+        &config,
+    );
+    client.sponsor_contest(&sponsor, &cancelled, &(pool / 2));
+    assert_eq!(
+        client.try_cancel_contest(&sponsor, &cancelled),
+        Err(Ok(ContestError::NotContestCreator))
+    );
+    client.create_signal(
+        &p1,
+        &String::from_str(&env, "XLM/USDC"),
+        &SignalAction::Buy,
+        &100_000,
+        &String::from_str(&env, "Contest entry"),
+        &(env.ledger().timestamp() + 3_600),
+        &SignalCategory::SWING,
+        &Vec::new(&env),
+        &RiskLevel::Medium,
+    );
+    assert_eq!(
+        client.try_cancel_contest(&creator, &cancelled),
+        Err(Ok(ContestError::ContestHasEntries))
+    );
+    assert_eq!(client.cancel_contest(&admin, &cancelled), 3 * pool / 2);
+    assert_eq!(token.balance(&creator), pool);
+    assert_eq!(token.balance(&sponsor), pool / 2);
+    assert_eq!(
+        client.get_contest(&cancelled).status,
+        ContestStatus::Cancelled
+    );
+    assert!(client.get_active_contests().is_empty());
+}
+
+#[test]
+fn test_legacy_contests_migrate_with_escrow() {
+    use crate::contests::{
+        ContestEntry, ContestMetric, ContestStatus, ContestStorageKey, LegacyContest,
+    };
+    use crate::errors::ContestError;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(10_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token_id = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    StellarAssetClient::new(&env, &token_id).mint(&admin, &5_000);
+
+    let provider = Address::generate(&env);
+    let legacy = |id: u64, status: ContestStatus| LegacyContest {
+        id,
+        name: String::from_str(&env, "Legacy"),
+        start_time: 9_000,
+        end_time: 100_000,
+        metric: ContestMetric::HighestROI,
+        min_signals: 1,
+        entries: Map::new(&env),
+        winners: Vec::new(&env),
+        prize_pool: 5_000,
+        status,
+    };
+    env.as_contract(&contract_id, || {
+        let mut contests: Map<u64, LegacyContest> = Map::new(&env);
+        contests.set(1, legacy(1, ContestStatus::Active));
+        contests.set(2, legacy(2, ContestStatus::Finalized));
+        env.storage()
+            .persistent()
+            .set(&ContestStorageKey::Contests, &contests);
+        env.storage()
+            .persistent()
+            .set(&ContestStorageKey::ActiveContests, &vec![&env, 1u64]);
+        env.storage()
+            .persistent()
+            .set(&ContestStorageKey::ContestCounter, &2u64);
+    });
+    assert!(matches!(
+        client.try_get_contest(&1),
+        Err(Ok(ContestError::ContestNotFound))
+    ));
+    assert_eq!(
+        client.try_migrate_legacy_contests(&provider, &token_id),
+        Err(Ok(ContestError::Unauthorized))
+    );
+    assert_eq!(client.migrate_legacy_contests(&admin, &token_id), 2);
+
+    let active = client.get_contest(&1);
+    assert_eq!(active.prize_pool, 5_000);
+    assert_eq!(active.creator, admin);
+    assert_eq!(client.get_contest_sponsor_deposit(&1, &admin), 5_000);
+    assert_eq!(
+        TokenClient::new(&env, &token_id).balance(&contract_id),
+        5_000
+    );
+    let closed = client.get_contest(&2);
+    assert_eq!(closed.status, ContestStatus::Finalized);
+    assert_eq!(client.get_contest_sponsor_deposit(&2, &admin), 0);
+
+    // The migrated contest is indexed and takes new signals.
+    client.create_signal(
+        &provider,
+        &String::from_str(&env, "XLM/USDC"),
+        &SignalAction::Buy,
+        &100_000,
+        &String::from_str(&env, "Entry"),
+        &(env.ledger().timestamp() + 3_600),
+        &SignalCategory::SWING,
+        &Vec::new(&env),
+        &RiskLevel::Medium,
+    );
+    let entry: ContestEntry = client.get_contest(&1).entries.get(provider).unwrap();
+    assert_eq!(entry.signals_submitted.len(), 1);
+}
+
+#[test]
+fn test_search_signals_filters_sorts_and_pages_with_stable_cursor() {
+    use crate::errors::SearchError;
+    use crate::search::{SearchKey, SearchSort, SearchSortKey, SignalQuery};
+    use crate::types::SignalEditInput;
+    use soroban_sdk::Bytes;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let provider = Address::generate(&env);
+    let executor = Address::generate(&env);
+    let xlm = String::from_str(&env, "XLM/USDC");
+    let breakout = String::from_str(&env, "breakout");
+    let create = |pair: &String, tags: Vec<String>, risk: RiskLevel, volume: i128| {
+        env.ledger().set_timestamp(env.ledger().timestamp() + 10);
+        let id = client.create_signal(
+            &provider,
+            pair,
+            &SignalAction::Buy,
+            &100_000,
+            &String::from_str(&env, "Search"),
+            &(env.ledger().timestamp() + 86_400),
+            &SignalCategory::SWING,
+            &tags,
+            &risk,
+        );
+        if volume > 0 {
+            client.record_trade_execution(&executor, &id, &100_000, &110_000, &volume);
+        }
+        id
+    };
+    let tagged = vec![&env, breakout.clone()];
+    let s1 = create(&xlm, tagged.clone(), RiskLevel::Low, 5_000);
+    let s2 = create(&xlm, tagged.clone(), RiskLevel::Medium, 9_000);
+    let s3 = create(&xlm, tagged.clone(), RiskLevel::Medium, 5_000);
+    let s4 = create(&xlm, tagged.clone(), RiskLevel::High, 1_000);
+    let _untagged = create(&xlm, Vec::new(&env), RiskLevel::Medium, 20_000);
+    let _other_pair = create(
+        &String::from_str(&env, "BTC/USDC"),
+        tagged.clone(),
+        RiskLevel::Medium,
+        30_000,
+    );
+    client.update_signal(
+        &provider,
+        &s4,
+        &SignalEditInput {
+            set_price: false,
+            price: 0,
+            set_rationale_hash: false,
+            rationale_hash: String::from_str(&env, ""),
+            set_confidence: true,
+            confidence: 90,
+        },
+    );
+
+    let query = SignalQuery {
+        asset_pair: Some(xlm.clone()),
+        categories: Vec::new(&env),
+        risk_levels: Vec::new(&env),
+        tags: tagged.clone(),
+        min_trust_tier: crate::reputation::TrustScoreTier::NewUnproven,
+        min_confidence: None,
+        max_confidence: None,
+        min_ai_score: None,
+        from_time: None,
+        to_time: None,
+        // Trades settle these signals, so include closed ones.
+        active_only: false,
+    };
+    let by_volume = vec![
+        &env,
+        SearchSort {
+            key: SearchSortKey::Volume,
+            descending: true,
+        },
+        SearchSort {
+            key: SearchSortKey::Recency,
+            descending: false,
+        },
+    ];
+    let ids = |signals: Vec<Signal>| {
+        let mut out = Vec::new(&env);
+        for s in signals.iter() {
+            out.push_back(s.id);
+        }
+        out
+    };
+
+    let first = client.search_signals(&query, &by_volume, &None, &2);
+    assert_eq!(ids(first.signals), vec![&env, s2, s1]);
+    let cursor = first.next_cursor.unwrap();
+
+    // A new high-volume match sorts ahead of the cursor and does not shift
+    // the next page.
+    create(&xlm, tagged.clone(), RiskLevel::Low, 50_000);
+    let second = client.search_signals(&query, &by_volume, &Some(cursor.clone()), &2);
+    assert_eq!(ids(second.signals), vec![&env, s3, s4]);
+    assert!(second.next_cursor.is_none());
+
+    let narrowed = SignalQuery {
+        risk_levels: vec![&env, RiskLevel::Medium, RiskLevel::High],
+        min_confidence: Some(80),
+        ..query.clone()
+    };
+    let page = client.search_signals(&narrowed, &by_volume, &None, &10);
+    assert_eq!(ids(page.signals), vec![&env, s4]);
+
+    assert_eq!(
+        client.try_search_signals(
+            &query,
+            &vec![&env, by_volume.get(0).unwrap()],
+            &Some(cursor),
+            &2
+        ),
+        Err(Ok(SearchError::InvalidCursor))
+    );
+    assert_eq!(
+        client.try_search_signals(
+            &query,
+            &by_volume,
+            &Some(Bytes::from_array(&env, &[1, 2])),
+            &2
+        ),
+        Err(Ok(SearchError::InvalidCursor))
+    );
+
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 86_400);
+    assert_eq!(client.prune_search_index(&SearchKey::PairIndex(xlm)), 6);
+    // Pruning only trims active-only lookups; historical queries still see
+    // every expired match.
+    let page = client.search_signals(&query, &by_volume, &None, &10);
+    assert_eq!(page.signals.len(), 5);
+    let active = SignalQuery {
+        active_only: true,
+        ..query.clone()
+    };
+    assert!(client
+        .search_signals(&active, &by_volume, &None, &10)
+        .signals
+        .is_empty());
+}
+
+#[test]
+fn test_import_creates_signals_reports_rows_and_resumes() {
+    use crate::import::ImportRowStatus;
+    use soroban_sdk::Bytes;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    #[allow(deprecated)]
+    let contract_id = env.register_contract(None, SignalRegistry);
+    let client = SignalRegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let provider = Address::generate(&env);
+    client.stake_tokens(&provider, &crate::providers::GOLD_TIER_STAKE);
+
+    // 12 rows: row 3 has a negative price; row 12 exceeds the hourly
+    // submission limit (10) and must wait for the resume.
+    let csv = Bytes::from_slice(
+        &env,
+        b"asset_pair,action,price,rationale,expiry_hours,external_id\n\
+XLM/USDC,BUY,120000,Breakout,24,tv-1\n\
+XLM/USDC,SELL,125000,Rejection,24,tv-2\n\
+XLM/USDC,BUY,-5,Bad price,24,tv-3\n\
+XLM/USDC,BUY,121000,Retest,24,tv-4\n\
+XLM/USDC,BUY,122000,Retest,24,tv-5\n\
+XLM/USDC,BUY,123000,Retest,24,tv-6\n\
+XLM/USDC,BUY,124000,Retest,24,tv-7\n\
+XLM/USDC,BUY,125000,Retest,24,tv-8\n\
+XLM/USDC,BUY,126000,Retest,24,tv-9\n\
+XLM/USDC,BUY,127000,Retest,24,tv-10\n\
+XLM/USDC,BUY,128000,Retest,24,tv-11\n\
+XLM/USDC,SELL,129000,Late row,48,tv-12",
+    );
+
+    let dry_run = client.import_signals_csv(&provider, &csv, &true);
+    assert_eq!(dry_run.success_count, 0);
+    assert_eq!(dry_run.error_count, 1);
+    assert_eq!(dry_run.rows.get(0).unwrap().status, ImportRowStatus::Valid);
+
+    let first = client.import_signals_csv(&provider, &csv, &false);
+    assert_eq!(first.success_count, 10);
+    assert_eq!(first.error_count, 2);
+    let bad = first.rows.get(2).unwrap();
+    assert_eq!(bad.status, ImportRowStatus::Failed);
+    assert_eq!(bad.error_code, errors::ImportError::InvalidPrice as u32);
+    let limited = first.rows.get(11).unwrap();
+    assert_eq!(limited.error_code, errors::ImportError::RateLimited as u32);
+
+    let first_id = first.signal_ids.get(0).unwrap();
+    let created = client.get_signal(&first_id).unwrap();
+    assert_eq!(created.price, 120_000);
+    assert_eq!(created.expiry, 1_000 + 24 * 3_600);
+    assert_eq!(
+        client.get_signal_by_external_id(&provider, &String::from_str(&env, "tv-1")),
+        Some(first_id)
+    );
+
+    // Resuming the same payload only submits the rows that did not land.
+    env.ledger().set_timestamp(1_000 + 3_601);
+    let resumed = client.import_signals_csv(&provider, &csv, &false);
+    assert_eq!(resumed.batch_id, first.batch_id);
+    assert_eq!(resumed.success_count, 1);
+    assert_eq!(resumed.skipped_count, 10);
+    assert_eq!(resumed.error_count, 1);
+    assert_eq!(
+        resumed.rows.get(0).unwrap().status,
+        ImportRowStatus::AlreadyImported
+    );
+    assert_eq!(
+        resumed.rows.get(11).unwrap().status,
+        ImportRowStatus::Created
+    );
+
+    // JSON rows dedup against external ids from any earlier import.
+    let json = Bytes::from_slice(
+        &env,
+        br#"[
+            {"asset_pair": "XLM/USDC", "action": "buy", "price": 120000,
+             "rationale": "Breakout", "expiry_hours": 24, "external_id": "tv-1"},
+            {"asset_pair": "BTC/USDC", "action": "SELL", "price": "45000000",
+             "rationale": "Telegram \"short\" call", "expiry_hours": 12, "external_id": "tg-7"}
+        ]"#,
+    );
+    let imported = client.import_signals_json(&provider, &json, &false);
+    assert_eq!(imported.skipped_count, 1);
+    assert_eq!(
+        imported.rows.get(0).unwrap().status,
+        ImportRowStatus::Duplicate
+    );
+    assert_eq!(imported.rows.get(0).unwrap().signal_id, Some(first_id));
+    assert_eq!(imported.success_count, 1);
+    let telegram = client
+        .get_signal(&imported.signal_ids.get(0).unwrap())
+        .unwrap();
+    assert_eq!(
+        telegram.rationale,
+        String::from_str(&env, "Telegram \"short\" call")
+    );
+
+    let malformed =
+        client.import_signals_json(&provider, &Bytes::from_slice(&env, b"[{\"a\":"), &false);
+    assert_eq!(malformed.error_count, 1);
+    assert_eq!(
+        malformed.rows.get(0).unwrap().error_code,
+        errors::ImportError::ParseError as u32
+    );
+}
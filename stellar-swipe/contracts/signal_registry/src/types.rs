@@ -191,6 +191,8 @@ pub enum FeeStorageKey {
     PlatformTreasury,
     ProviderTreasury,
     TreasuryBalances,
+    /// FeeCollector whose fee schedule tiered previews are quoted from.
+    FeeCollector,
 }
 
 #[contracttype]
@@ -255,9 +257,14 @@ pub struct ImportRequest {
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct ImportResultView {
+    /// SHA-256 of the payload; re-importing the same payload resumes it.
+    pub batch_id: soroban_sdk::BytesN<32>,
     pub success_count: u32,
     pub error_count: u32,
+    /// Rows already created by an earlier run or matching a known external id.
+    pub skipped_count: u32,
     pub signal_ids: soroban_sdk::Vec<u64>,
+    pub rows: soroban_sdk::Vec<crate::import::ImportRowResult>,
 }
 
 // ==========================================
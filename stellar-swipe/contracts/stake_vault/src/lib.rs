@@ -5,7 +5,7 @@ pub mod migration;
 use migration::{MigrationKey, StakeInfoV2};
 use shared::initializable;
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, token, Address, Env, Symbol,
+    contract, contracterror, contractimpl, contracttype, token, Address, Env, IntoVal, Symbol,
 };
 
 // ── Slash severity tiers ──────────────────────────────────────────────────────
@@ -119,6 +119,9 @@ pub enum StorageKey {
     LastStakeLedger(Address),
     /// Admin-configurable slashing tier percentages.
     SlashTierConfig,
+    /// FeeCollector receiving slashed stake for the copier insurance fund.
+    /// When unset, slashed stake is burned.
+    InsuranceFund,
 }
 
 #[contracterror]
@@ -585,7 +588,9 @@ impl StakeVaultContract {
     ///
     /// The slashed amount is computed from the configured tier percentages
     /// (default: minor=5%, major=30%, critical=100%).  Only the signal registry
-    /// may call this.
+    /// may call this.  Slashed tokens go to the insurance fund when one is
+    /// configured (`set_insurance_fund`) and accepts the deposit, and are
+    /// burned otherwise.
     pub fn slash_stake(
         env: Env,
         caller: Address,
@@ -656,11 +661,60 @@ impl StakeVaultContract {
             (provider.clone(), severity as u32, slash_amount, reason),
         );
 
-        token::Client::new(&env, &token).burn(&env.current_contract_address(), &slash_amount);
+        let vault = env.current_contract_address();
+        match env
+            .storage()
+            .instance()
+            .get::<_, Address>(&StorageKey::InsuranceFund)
+        {
+            Some(fund) => {
+                // Book the deposit first; the transfer below lands in the
+                // same transaction. A fund that rejects it gets nothing and
+                // the slash is burned instead, so the slash never fails.
+                let booked = env.try_invoke_contract::<(), soroban_sdk::Error>(
+                    &fund,
+                    &Symbol::new(&env, "deposit_slashed_stake"),
+                    (vault.clone(), token.clone(), slash_amount, provider.clone()).into_val(&env),
+                );
+                if matches!(booked, Ok(Ok(()))) {
+                    token::Client::new(&env, &token).transfer(&vault, &fund, &slash_amount);
+                } else {
+                    token::Client::new(&env, &token).burn(&vault, &slash_amount);
+                    #[allow(deprecated)]
+                    env.events().publish(
+                        (
+                            Symbol::new(&env, "stake_vault"),
+                            Symbol::new(&env, "slash_route_failed"),
+                        ),
+                        (provider, fund, slash_amount),
+                    );
+                }
+            }
+            None => token::Client::new(&env, &token).burn(&vault, &slash_amount),
+        }
 
         Ok(slash_amount)
     }
 
+    /// Admin: route future slashes to the FeeCollector insurance fund instead
+    /// of burning them.
+    pub fn set_insurance_fund(env: Env, fee_collector: Address) -> Result<(), StakeVaultError> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Admin)
+            .ok_or(StakeVaultError::NotInitialized)?;
+        admin.require_auth();
+        env.storage()
+            .instance()
+            .set(&StorageKey::InsuranceFund, &fee_collector);
+        Ok(())
+    }
+
+    pub fn get_insurance_fund(env: Env) -> Option<Address> {
+        env.storage().instance().get(&StorageKey::InsuranceFund)
+    }
+
     // ── Read ───────────────────────────────────────────────────────────────────
 
     pub fn get_stake(env: Env, staker: Address) -> i128 {
@@ -0,0 +1,481 @@
+//! Bracket orders: a market entry plus its exits, placed in one call.
+//!
+//! `place_bracket_order` opens the position through the same market-trade
+//! path as `execute_copy_trade`, records the oracle price as the entry, and
+//! attaches the exit legs to the new bracket:
+//!
+//! - an optional hard stop-loss price,
+//! - take-profit tiers that scale out part of the open size each,
+//! - trailing-stop tiers that arm as profit builds.
+//!
+//! Take-profit and trailing tiers are the AutoTrade exit-strategy tiers from
+//! `stellar_swipe_common::exit_tiers`, so partial scale-outs behave the same
+//! in both contracts.
+//!
+//! A registered keeper calls `check_bracket_order` with a fresh oracle price.
+//! The legs form a one-cancels-other group: once a fill closes the remaining
+//! size, the bracket closes, the UserPortfolio position is closed through
+//! `close_position_keeper` the same way the single triggers close it, and
+//! every leg that has not filled is cancelled. Partial take-profit fills only
+//! release open interest; the portfolio position closes with the last fill.
+//! The hard stop is checked first and takes priority, matching the single
+//! stop-loss / take-profit triggers.
+//!
+//! The position the exits close must be one of the user's open UserPortfolio
+//! positions, and carry no other open bracket. UserPortfolio positions do not
+//! record an asset pair, so a position is bound to the pair of the one open
+//! bracket that may close it.
+//!
+//! Brackets are long-only: the entry is a market buy, so the stop sits below
+//! the entry and take-profits above it. Legs shaped for a short are rejected
+//! with `ShortBracketUnsupported`.
+
+use soroban_sdk::{contracttype, vec, Address, Env, IntoVal, Symbol, Vec};
+use stellar_swipe_common::exit_tiers::{apply_exit_tiers, ExitKind, StopLossTier, TakeProfitTier};
+
+use crate::errors::ContractError;
+use crate::keeper::require_registered_keeper;
+use crate::triggers::{close_position_keeper, fetch_current_price, fetch_oracle_and_portfolio};
+use crate::{decrease_open_interest, execute_market_copy_trade, StorageKey};
+
+const BPS_DENOMINATOR: u32 = 10_000;
+
+// ── UserPortfolio types (wire-compatible mirrors) ────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum PositionStatus {
+    Open = 0,
+    Closed = 1,
+    Closing = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub entry_price: i128,
+    pub amount: i128,
+    pub status: PositionStatus,
+    pub realized_pnl: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PortfolioPosition {
+    pub position_id: u64,
+    pub position: Position,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Portfolio {
+    pub open_positions: Vec<PortfolioPosition>,
+    pub closed_positions: Vec<PortfolioPosition>,
+    pub closed_position_ids: Vec<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BracketStatus {
+    Open,
+    /// The hard stop-loss closed the position.
+    StoppedOut,
+    /// The take-profit tiers scaled the whole position out.
+    TakenProfit,
+    /// A trailing-stop tier closed the position.
+    TrailingStopped,
+    /// The owner detached the exits; the position is left open.
+    Cancelled,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BracketOrder {
+    pub id: u64,
+    pub user: Address,
+    pub token: Address,
+    pub asset_pair: u32,
+    /// UserPortfolio position the entry was recorded under.
+    pub trade_id: u64,
+    pub amount: i128,
+    pub entry_price: i128,
+    /// Size still open.
+    pub remaining: i128,
+    /// Highest oracle price seen, for the trailing tiers.
+    pub highest_price: i128,
+    /// Hard stop; `0` when none was set.
+    pub stop_loss_price: i128,
+    pub take_profit_tiers: Vec<TakeProfitTier>,
+    pub stop_loss_tiers: Vec<StopLossTier>,
+    pub status: BracketStatus,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BracketLeg {
+    StopLoss,
+    TakeProfit,
+    TrailingStop,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BracketLegFilled {
+    pub bracket_id: u64,
+    pub user: Address,
+    pub leg: BracketLeg,
+    pub trigger_price: i128,
+    pub current_price: i128,
+    pub amount: i128,
+    pub remaining: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BracketClosed {
+    pub bracket_id: u64,
+    pub user: Address,
+    pub status: BracketStatus,
+    /// Legs that had not filled and were cancelled with the bracket.
+    pub cancelled_legs: u32,
+}
+
+// ── Storage helpers ───────────────────────────────────────────────────────────
+
+fn next_bracket_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::NextBracketOrderId)
+        .unwrap_or(1);
+    env.storage()
+        .instance()
+        .set(&StorageKey::NextBracketOrderId, &(id + 1));
+    id
+}
+
+pub fn load_bracket(env: &Env, bracket_id: u64) -> Option<BracketOrder> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::BracketOrder(bracket_id))
+}
+
+fn save_bracket(env: &Env, bracket: &BracketOrder) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::BracketOrder(bracket.id), bracket);
+}
+
+// ── Validation ────────────────────────────────────────────────────────────────
+
+/// Fail unless `trade_id` is one of `user`'s open UserPortfolio positions
+/// without an open bracket on it.
+fn check_position(
+    env: &Env,
+    portfolio: &Address,
+    user: &Address,
+    trade_id: u64,
+) -> Result<(), ContractError> {
+    let held = env.try_invoke_contract::<Portfolio, soroban_sdk::Error>(
+        portfolio,
+        &Symbol::new(env, "get_portfolio"),
+        vec![env, user.into_val(env), false.into_val(env)],
+    );
+    let open = match held {
+        Ok(Ok(held)) => held
+            .open_positions
+            .iter()
+            .any(|p| p.position_id == trade_id && p.position.status == PositionStatus::Open),
+        _ => false,
+    };
+    if !open {
+        return Err(ContractError::TradeNotFound);
+    }
+    let bracketed = env
+        .storage()
+        .persistent()
+        .get::<_, u64>(&StorageKey::PositionBracket(user.clone(), trade_id))
+        .and_then(|id| load_bracket(env, id))
+        .is_some_and(|b| b.status == BracketStatus::Open);
+    if bracketed {
+        return Err(ContractError::PositionAlreadyBracketed);
+    }
+    Ok(())
+}
+
+fn validate_legs(
+    entry_price: i128,
+    stop_loss_price: Option<i128>,
+    take_profit_tiers: &Vec<TakeProfitTier>,
+    stop_loss_tiers: &Vec<StopLossTier>,
+) -> Result<(), ContractError> {
+    if stop_loss_price.is_none() && take_profit_tiers.is_empty() && stop_loss_tiers.is_empty() {
+        return Err(ContractError::InvalidBracketLegs);
+    }
+    let short_stop = stop_loss_price.is_some_and(|stop| stop > entry_price);
+    let short_target = take_profit_tiers
+        .first()
+        .is_some_and(|tp| tp.price < entry_price);
+    if short_stop || short_target {
+        return Err(ContractError::ShortBracketUnsupported);
+    }
+    if let Some(stop) = stop_loss_price {
+        if stop <= 0 || stop >= entry_price {
+            return Err(ContractError::InvalidBracketLegs);
+        }
+    }
+    let mut prev_price = entry_price;
+    for tp in take_profit_tiers.iter() {
+        if tp.executed
+            || tp.price <= prev_price
+            || tp.position_pct == 0
+            || tp.position_pct > BPS_DENOMINATOR
+        {
+            return Err(ContractError::InvalidBracketLegs);
+        }
+        prev_price = tp.price;
+    }
+    for tier in stop_loss_tiers.iter() {
+        if tier.active || tier.trail_pct == 0 || tier.trail_pct >= 100 {
+            return Err(ContractError::InvalidBracketLegs);
+        }
+    }
+    Ok(())
+}
+
+/// Exit legs that had not filled when the bracket closed.
+fn unfilled_legs(bracket: &BracketOrder, stop_filled: bool) -> u32 {
+    let stop = u32::from(bracket.stop_loss_price > 0 && !stop_filled);
+    let tps = bracket
+        .take_profit_tiers
+        .iter()
+        .filter(|tp| !tp.executed)
+        .count() as u32;
+    stop + tps + bracket.stop_loss_tiers.len()
+}
+
+// ── Entrypoints ───────────────────────────────────────────────────────────────
+
+/// Open `amount` of `token` for `user` and attach the exit legs to portfolio
+/// position `trade_id`. Fails, and places nothing, when `trade_id` is not an
+/// open, unbracketed position of `user`, the legs do not bracket the oracle
+/// entry price or the entry trade cannot be placed.
+#[allow(clippy::too_many_arguments)]
+pub fn place_bracket_order(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    asset_pair: u32,
+    trade_id: u64,
+    amount: i128,
+    stop_loss_price: Option<i128>,
+    take_profit_tiers: Vec<TakeProfitTier>,
+    stop_loss_tiers: Vec<StopLossTier>,
+) -> Result<u64, ContractError> {
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    let (oracle, portfolio) = fetch_oracle_and_portfolio(env)?;
+    check_position(env, &portfolio, user, trade_id)?;
+    let entry_price = fetch_current_price(env, &oracle, asset_pair)?;
+    validate_legs(
+        entry_price,
+        stop_loss_price,
+        &take_profit_tiers,
+        &stop_loss_tiers,
+    )?;
+
+    execute_market_copy_trade(env, user.clone(), token.clone(), amount, None, false, None)?;
+
+    let bracket = BracketOrder {
+        id: next_bracket_id(env),
+        user: user.clone(),
+        token: token.clone(),
+        asset_pair,
+        trade_id,
+        amount,
+        entry_price,
+        remaining: amount,
+        highest_price: entry_price,
+        stop_loss_price: stop_loss_price.unwrap_or(0),
+        take_profit_tiers,
+        stop_loss_tiers,
+        status: BracketStatus::Open,
+        created_at: env.ledger().timestamp(),
+    };
+    save_bracket(env, &bracket);
+    env.storage().persistent().set(
+        &StorageKey::PositionBracket(user.clone(), trade_id),
+        &bracket.id,
+    );
+
+    env.events().publish(
+        (
+            Symbol::new(env, "trade_executor"),
+            Symbol::new(env, "bracket_placed"),
+        ),
+        (bracket.id, user.clone(), amount, entry_price),
+    );
+    Ok(bracket.id)
+}
+
+/// Keeper step: fill whichever legs the oracle price has crossed. Returns the
+/// bracket status after the fills.
+pub fn check_bracket_order(
+    env: &Env,
+    keeper: &Address,
+    bracket_id: u64,
+) -> Result<BracketStatus, ContractError> {
+    require_registered_keeper(env, keeper)?;
+    let mut bracket = load_bracket(env, bracket_id).ok_or(ContractError::BracketNotFound)?;
+    if bracket.status != BracketStatus::Open {
+        return Err(ContractError::BracketNotOpen);
+    }
+    let (oracle, portfolio) = fetch_oracle_and_portfolio(env)?;
+    let current_price = fetch_current_price(env, &oracle, bracket.asset_pair)?;
+
+    // Hard stop first: it closes everything and cancels the other legs.
+    if bracket.stop_loss_price > 0 && current_price <= bracket.stop_loss_price {
+        let amount = bracket.remaining;
+        bracket.remaining = 0;
+        decrease_open_interest(env, &bracket.token, amount);
+        emit_leg_filled(
+            env,
+            &bracket,
+            BracketLeg::StopLoss,
+            bracket.stop_loss_price,
+            current_price,
+            amount,
+        );
+        close_bracket(
+            env,
+            &portfolio,
+            &mut bracket,
+            BracketStatus::StoppedOut,
+            true,
+        );
+        return Ok(bracket.status);
+    }
+
+    let fills = apply_exit_tiers(
+        env,
+        bracket.entry_price,
+        current_price,
+        &mut bracket.highest_price,
+        &mut bracket.remaining,
+        &mut bracket.take_profit_tiers,
+        &mut bracket.stop_loss_tiers,
+    );
+    let mut closed_by = BracketStatus::TakenProfit;
+    for fill in fills.iter() {
+        let leg = match fill.kind {
+            ExitKind::TakeProfit => BracketLeg::TakeProfit,
+            ExitKind::TrailingStop => {
+                closed_by = BracketStatus::TrailingStopped;
+                BracketLeg::TrailingStop
+            }
+        };
+        decrease_open_interest(env, &bracket.token, fill.amount);
+        emit_leg_filled(
+            env,
+            &bracket,
+            leg,
+            fill.trigger_price,
+            current_price,
+            fill.amount,
+        );
+    }
+
+    // A take-profit that scaled out the last of the size closes the bracket
+    // too, cancelling the stop and the trailing tiers.
+    if bracket.remaining == 0 {
+        close_bracket(env, &portfolio, &mut bracket, closed_by, false);
+    } else {
+        save_bracket(env, &bracket);
+    }
+    Ok(bracket.status)
+}
+
+/// Detach the exit legs of an open bracket. The position stays open.
+pub fn cancel_bracket_order(
+    env: &Env,
+    user: &Address,
+    bracket_id: u64,
+) -> Result<(), ContractError> {
+    let mut bracket = load_bracket(env, bracket_id).ok_or(ContractError::BracketNotFound)?;
+    if bracket.user != *user {
+        return Err(ContractError::Unauthorized);
+    }
+    if bracket.status != BracketStatus::Open {
+        return Err(ContractError::BracketNotOpen);
+    }
+    let cancelled_legs = unfilled_legs(&bracket, false);
+    bracket.status = BracketStatus::Cancelled;
+    save_bracket(env, &bracket);
+    emit_closed(env, &bracket, cancelled_legs);
+    Ok(())
+}
+
+/// Close the portfolio position, mark the bracket closed and cancel its
+/// unfilled legs.
+fn close_bracket(
+    env: &Env,
+    portfolio: &Address,
+    bracket: &mut BracketOrder,
+    status: BracketStatus,
+    stop_filled: bool,
+) {
+    close_position_keeper(
+        env,
+        portfolio,
+        &bracket.user,
+        bracket.trade_id,
+        bracket.asset_pair,
+    );
+    let cancelled_legs = unfilled_legs(bracket, stop_filled);
+    bracket.status = status;
+    save_bracket(env, bracket);
+    emit_closed(env, bracket, cancelled_legs);
+}
+
+fn emit_leg_filled(
+    env: &Env,
+    bracket: &BracketOrder,
+    leg: BracketLeg,
+    trigger_price: i128,
+    current_price: i128,
+    amount: i128,
+) {
+    env.events().publish(
+        (
+            Symbol::new(env, "trade_executor"),
+            Symbol::new(env, "bracket_leg_filled"),
+        ),
+        BracketLegFilled {
+            bracket_id: bracket.id,
+            user: bracket.user.clone(),
+            leg,
+            trigger_price,
+            current_price,
+            amount,
+            remaining: bracket.remaining,
+        },
+    );
+}
+
+fn emit_closed(env: &Env, bracket: &BracketOrder, cancelled_legs: u32) {
+    env.events().publish(
+        (
+            Symbol::new(env, "trade_executor"),
+            Symbol::new(env, "bracket_closed"),
+        ),
+        BracketClosed {
+            bracket_id: bracket.id,
+            user: bracket.user.clone(),
+            status: bracket.status,
+            cancelled_legs,
+        },
+    );
+}
@@ -0,0 +1,641 @@
+//! Combo signal adoption.
+//!
+//! Adopting a SignalRegistry combo fans the user's allocation out into one
+//! copy trade per component, sized by the component weight. Each leg runs
+//! through the same market-trade path as `execute_copy_trade`. A keeper then
+//! calls `advance_combo`, which settles open legs once their signal reaches
+//! an outcome and opens the legs that were waiting on it:
+//!
+//! - `Simultaneous`: every leg opens on adoption.
+//! - `Sequential`: a leg opens only after the previous one closed.
+//! - `Conditional`: ungated legs open on adoption; a gated leg opens once its
+//!   dependency closed and the condition holds, otherwise it is skipped.
+//!
+//! Each leg's tokens are pulled from the user (under an allowance granted to
+//! this contract) when the leg opens and held until it closes. Closing a leg
+//! executes the reverse trade: the held tokens are sold into the adoption's
+//! `quote_token` on the SDEX router and the proceeds are paid to the user.
+//! A leg's cost is its amount valued at the SDEX price of its token when it
+//! opened, so P&L and ROI are measured in quote-token units.
+//!
+//! A required leg failing unwinds the whole combo: pending legs are cancelled
+//! and open legs are sold at their signal's current mark. All legs of
+//! `Simultaneous` and `Sequential` combos are required and fail when their
+//! signal fails or the trade cannot be placed. In a `Conditional` combo the
+//! outcome of a leg only drives its gates; only the ungated legs are
+//! required, and they are all placed (atomically) on adoption.
+//!
+//! A leg whose signal expired stays open while the registry may still resolve
+//! it against its targets; it closes on `Expired` only when it has none.
+//!
+//! When the adoption completes or unwinds, its per-leg ROI and P&L are
+//! reported back to the registry's `record_combo_execution`.
+
+use soroban_sdk::{contracttype, token, vec, Address, Env, IntoVal, String, Symbol, Val, Vec};
+
+use crate::errors::ContractError;
+use crate::keeper::require_registered_keeper;
+use crate::sdex::{execute_sdex_swap, min_received_from_slippage};
+use crate::{
+    decrease_open_interest, execute_market_copy_trade, StorageKey, ENTRY_PRICE_DENOMINATOR,
+};
+
+const WEIGHT_TOTAL: i128 = 10_000;
+
+/// Slippage tolerated below the signal's mark when a leg is sold.
+pub const COMBO_EXIT_SLIPPAGE_BPS: u32 = 100;
+
+// ── SignalRegistry types (wire-compatible mirrors) ───────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ComboType {
+    Simultaneous,
+    Sequential,
+    Conditional,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConditionType {
+    Success,
+    Failure,
+    RoiAbove(i128),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Condition {
+    pub depends_on: u64,
+    pub condition_type: ConditionType,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConditionGate {
+    None,
+    Some(Condition),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComponentSignal {
+    pub signal_id: u64,
+    pub weight: u32,
+    pub condition: ConditionGate,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ComboStatus {
+    Active,
+    Cancelled,
+    Completed,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComboSignal {
+    pub id: u64,
+    pub name: String,
+    pub provider: Address,
+    pub component_signals: Vec<ComponentSignal>,
+    pub combo_type: ComboType,
+    pub status: ComboStatus,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SignalStatus {
+    Pending,
+    Active,
+    Executed,
+    Expired,
+    Successful,
+    Failed,
+    ProviderDeleted,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignalPerformanceView {
+    pub signal_id: u64,
+    pub executions: u32,
+    pub total_volume: i128,
+    pub average_roi: i128,
+    pub status: SignalStatus,
+}
+
+/// Leg result reported to `record_combo_execution`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComponentExecution {
+    pub signal_id: u64,
+    pub amount: i128,
+    pub skipped: bool,
+    pub roi: i128,
+}
+
+// ── Adoption state ────────────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LegStatus {
+    /// Waiting for an upstream leg.
+    Pending,
+    Open,
+    /// Sold on its signal's outcome.
+    Closed,
+    /// Gate not met, or cancelled by an unwind before opening.
+    Skipped,
+    /// The copy trade could not be placed.
+    Failed,
+    /// Sold at mark by an unwind.
+    Unwound,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComboLeg {
+    pub signal_id: u64,
+    pub token: Address,
+    pub amount: i128,
+    pub condition: ConditionGate,
+    pub status: LegStatus,
+    /// SDEX price of `token` in quote units when the leg opened, scaled by
+    /// `ENTRY_PRICE_DENOMINATOR`.
+    pub entry_price: i128,
+    /// Realized ROI in basis points once closed.
+    pub roi: i128,
+    /// Realized P&L in quote-token units once closed.
+    pub pnl: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ComboAdoptionStatus {
+    Active,
+    Completed,
+    Unwound,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComboAdoption {
+    pub id: u64,
+    pub user: Address,
+    pub combo_id: u64,
+    pub combo_type: ComboType,
+    /// Token the legs are sold into when they close.
+    pub quote_token: Address,
+    pub total_amount: i128,
+    pub legs: Vec<ComboLeg>,
+    pub status: ComboAdoptionStatus,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComboSettled {
+    pub adoption_id: u64,
+    pub combo_id: u64,
+    pub user: Address,
+    pub status: ComboAdoptionStatus,
+    pub total_pnl: i128,
+}
+
+// ── Storage helpers ───────────────────────────────────────────────────────────
+
+fn registry(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&StorageKey::SignalRegistry)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn next_adoption_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::NextComboAdoptionId)
+        .unwrap_or(1);
+    env.storage()
+        .instance()
+        .set(&StorageKey::NextComboAdoptionId, &(id + 1));
+    id
+}
+
+pub fn load_adoption(env: &Env, adoption_id: u64) -> Option<ComboAdoption> {
+    env.storage()
+        .persistent()
+        .get(&StorageKey::ComboAdoption(adoption_id))
+}
+
+fn save_adoption(env: &Env, adoption: &ComboAdoption) {
+    env.storage()
+        .persistent()
+        .set(&StorageKey::ComboAdoption(adoption.id), adoption);
+}
+
+fn signal_performance(
+    env: &Env,
+    registry: &Address,
+    signal_id: u64,
+) -> Result<SignalPerformanceView, ContractError> {
+    let view = env.try_invoke_contract::<Option<SignalPerformanceView>, soroban_sdk::Error>(
+        registry,
+        &Symbol::new(env, "get_signal_performance"),
+        vec![env, signal_id.into_val(env)],
+    );
+    match view {
+        Ok(Ok(Some(view))) => Ok(view),
+        _ => Err(ContractError::ComboSignalMissing),
+    }
+}
+
+/// Whether the registry may still resolve `signal_id` against price targets,
+/// in which case an `Expired` status is not yet its outcome.
+fn awaiting_resolution(
+    env: &Env,
+    registry: &Address,
+    signal_id: u64,
+) -> Result<bool, ContractError> {
+    let targets = env.try_invoke_contract::<Option<Val>, soroban_sdk::Error>(
+        registry,
+        &Symbol::new(env, "get_signal_targets"),
+        vec![env, signal_id.into_val(env)],
+    );
+    match targets {
+        Ok(Ok(targets)) => Ok(targets.is_some()),
+        _ => Err(ContractError::ComboSignalMissing),
+    }
+}
+
+// ── Legs ──────────────────────────────────────────────────────────────────────
+
+fn is_terminal(status: LegStatus) -> bool {
+    !matches!(status, LegStatus::Pending | LegStatus::Open)
+}
+
+/// Place the leg's copy trade and take its tokens into custody.
+fn open_leg(env: &Env, user: &Address, leg: &mut ComboLeg) -> Result<(), ContractError> {
+    let entry_price: i128 = env
+        .storage()
+        .instance()
+        .get(&StorageKey::SdexPrice(leg.token.clone()))
+        .ok_or(ContractError::OracleUnavailable)?;
+    execute_market_copy_trade(
+        env,
+        user.clone(),
+        leg.token.clone(),
+        leg.amount,
+        None,
+        false,
+        None,
+    )?;
+    let this = env.current_contract_address();
+    let pulled =
+        token::Client::new(env, &leg.token).try_transfer_from(&this, user, &this, &leg.amount);
+    if !matches!(pulled, Ok(Ok(()))) {
+        decrease_open_interest(env, &leg.token, leg.amount);
+        return Err(ContractError::InsufficientBalance);
+    }
+    leg.entry_price = entry_price;
+    leg.status = LegStatus::Open;
+    Ok(())
+}
+
+/// The leg's cost in quote-token units.
+fn leg_cost(leg: &ComboLeg) -> i128 {
+    leg.amount.saturating_mul(leg.entry_price) / ENTRY_PRICE_DENOMINATOR
+}
+
+/// Reverse trade: sell the leg's tokens into `quote_token` and pay the
+/// proceeds to the user. `mark_roi` is the signal's ROI applied to the leg's
+/// cost, which bounds the accepted slippage; the leg records the ROI actually
+/// realized against that cost.
+fn close_leg(
+    env: &Env,
+    adoption: &ComboAdoption,
+    leg: &mut ComboLeg,
+    mark_roi: i128,
+    status: LegStatus,
+) -> Result<(), ContractError> {
+    let router: Address = env
+        .storage()
+        .instance()
+        .get(&StorageKey::SdexRouter)
+        .ok_or(ContractError::NotInitialized)?;
+    let cost = leg_cost(leg);
+    let mark = cost.saturating_mul(WEIGHT_TOTAL.saturating_add(mark_roi)) / WEIGHT_TOTAL;
+    let min_out = if mark > 0 {
+        min_received_from_slippage(mark, COMBO_EXIT_SLIPPAGE_BPS)
+            .ok_or(ContractError::InvalidAmount)?
+    } else {
+        0
+    };
+    let received = execute_sdex_swap(
+        env,
+        &router,
+        &leg.token,
+        &adoption.quote_token,
+        leg.amount,
+        min_out,
+    )?;
+    if received > 0 {
+        token::Client::new(env, &adoption.quote_token).transfer(
+            &env.current_contract_address(),
+            &adoption.user,
+            &received,
+        );
+    }
+
+    leg.pnl = received.saturating_sub(cost);
+    leg.roi = if cost > 0 {
+        leg.pnl.saturating_mul(WEIGHT_TOTAL) / cost
+    } else {
+        0
+    };
+    leg.status = status;
+    decrease_open_interest(env, &leg.token, leg.amount);
+    Ok(())
+}
+
+/// Whether a gated leg may open, given its dependency. `None` while the
+/// dependency is still pending or open.
+fn gate_decision(legs: &Vec<ComboLeg>, cond: &Condition) -> Option<bool> {
+    let dep = legs.iter().find(|l| l.signal_id == cond.depends_on)?;
+    match dep.status {
+        LegStatus::Pending | LegStatus::Open => None,
+        LegStatus::Closed => Some(match cond.condition_type {
+            ConditionType::Success => dep.roi > 0,
+            ConditionType::Failure => dep.roi <= 0,
+            ConditionType::RoiAbove(threshold) => dep.roi > threshold,
+        }),
+        LegStatus::Skipped | LegStatus::Failed | LegStatus::Unwound => Some(false),
+    }
+}
+
+// ── Entrypoints ───────────────────────────────────────────────────────────────
+
+/// Adopt `combo_id` for `user`, splitting `total_amount` across the legs by
+/// weight. `tokens[i]` is the token traded for the i-th component; legs are
+/// sold into `quote_token` when they close. The user must have approved this
+/// contract for each leg's amount. Any initial leg failing to open aborts the
+/// whole adoption.
+pub fn adopt_combo(
+    env: &Env,
+    user: &Address,
+    combo_id: u64,
+    tokens: Vec<Address>,
+    quote_token: Address,
+    total_amount: i128,
+) -> Result<u64, ContractError> {
+    if total_amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    let registry = registry(env)?;
+    let combo = env.try_invoke_contract::<Option<ComboSignal>, soroban_sdk::Error>(
+        &registry,
+        &Symbol::new(env, "get_combo_signal"),
+        vec![env, combo_id.into_val(env)],
+    );
+    let Ok(Ok(Some(combo))) = combo else {
+        return Err(ContractError::ComboNotFound);
+    };
+    if combo.status != ComboStatus::Active {
+        return Err(ContractError::ComboNotActive);
+    }
+    if tokens.len() != combo.component_signals.len() {
+        return Err(ContractError::InvalidComboLegs);
+    }
+
+    let mut legs = Vec::new(env);
+    for (i, comp) in combo.component_signals.iter().enumerate() {
+        let mut leg = ComboLeg {
+            signal_id: comp.signal_id,
+            token: tokens.get(i as u32).unwrap(),
+            amount: total_amount.saturating_mul(comp.weight as i128) / WEIGHT_TOTAL,
+            condition: comp.condition,
+            status: LegStatus::Pending,
+            entry_price: 0,
+            roi: 0,
+            pnl: 0,
+        };
+        let opens_now = match combo.combo_type {
+            ComboType::Simultaneous => true,
+            ComboType::Sequential => i == 0,
+            ComboType::Conditional => leg.condition == ConditionGate::None,
+        };
+        if opens_now {
+            open_leg(env, user, &mut leg)?;
+        }
+        legs.push_back(leg);
+    }
+
+    let adoption = ComboAdoption {
+        id: next_adoption_id(env),
+        user: user.clone(),
+        combo_id,
+        combo_type: combo.combo_type,
+        quote_token,
+        total_amount,
+        legs,
+        status: ComboAdoptionStatus::Active,
+        created_at: env.ledger().timestamp(),
+    };
+    save_adoption(env, &adoption);
+    Ok(adoption.id)
+}
+
+/// Keeper step: settle legs whose signal reached an outcome, open the legs
+/// now unblocked, and unwind on a required-leg failure. Leg placement
+/// failures are recorded on the adoption rather than returned, so the
+/// unwind is persisted.
+pub fn advance_combo(
+    env: &Env,
+    keeper: &Address,
+    adoption_id: u64,
+) -> Result<ComboAdoptionStatus, ContractError> {
+    require_registered_keeper(env, keeper)?;
+    let mut adoption = load_adoption(env, adoption_id).ok_or(ContractError::ComboNotFound)?;
+    if adoption.status != ComboAdoptionStatus::Active {
+        return Err(ContractError::ComboNotActive);
+    }
+    let registry = registry(env)?;
+    let mut legs = adoption.legs.clone();
+    let mut unwind = false;
+
+    // 1. Close legs whose signal has an outcome.
+    for i in 0..legs.len() {
+        let mut leg = legs.get(i).unwrap();
+        if leg.status != LegStatus::Open {
+            continue;
+        }
+        let view = signal_performance(env, &registry, leg.signal_id)?;
+        match view.status {
+            SignalStatus::Successful | SignalStatus::ProviderDeleted => {}
+            SignalStatus::Expired => {
+                if awaiting_resolution(env, &registry, leg.signal_id)? {
+                    continue;
+                }
+            }
+            SignalStatus::Failed => {
+                // Conditional legs fail into their gates instead.
+                if adoption.combo_type != ComboType::Conditional {
+                    unwind = true;
+                }
+            }
+            _ => continue,
+        }
+        close_leg(
+            env,
+            &adoption,
+            &mut leg,
+            view.average_roi,
+            LegStatus::Closed,
+        )?;
+        legs.set(i, leg);
+    }
+
+    // 2. Open legs that were waiting on an upstream outcome.
+    if !unwind {
+        match adoption.combo_type {
+            ComboType::Simultaneous => {}
+            ComboType::Sequential => {
+                if let Some(i) = legs.iter().position(|l| l.status == LegStatus::Pending) {
+                    let i = i as u32;
+                    let upstream_closed =
+                        i == 0 || legs.get(i - 1).unwrap().status == LegStatus::Closed;
+                    if upstream_closed {
+                        let mut leg = legs.get(i).unwrap();
+                        if open_leg(env, &adoption.user, &mut leg).is_err() {
+                            leg.status = LegStatus::Failed;
+                            unwind = true;
+                        }
+                        legs.set(i, leg);
+                    }
+                }
+            }
+            ComboType::Conditional => {
+                // Repeat so chains of gates resolve within one call.
+                let mut changed = true;
+                while changed {
+                    changed = false;
+                    for i in 0..legs.len() {
+                        let mut leg = legs.get(i).unwrap();
+                        let ConditionGate::Some(cond) = leg.condition.clone() else {
+                            continue;
+                        };
+                        if leg.status != LegStatus::Pending {
+                            continue;
+                        }
+                        let Some(met) = gate_decision(&legs, &cond) else {
+                            continue;
+                        };
+                        if !met {
+                            leg.status = LegStatus::Skipped;
+                        } else if open_leg(env, &adoption.user, &mut leg).is_err() {
+                            leg.status = LegStatus::Failed;
+                        }
+                        legs.set(i, leg);
+                        changed = true;
+                    }
+                }
+                // Gates that can no longer be decided (circular dependencies).
+                if !legs.iter().any(|l| l.status == LegStatus::Open) {
+                    for i in 0..legs.len() {
+                        let mut leg = legs.get(i).unwrap();
+                        if leg.status == LegStatus::Pending {
+                            leg.status = LegStatus::Skipped;
+                            legs.set(i, leg);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // 3. Unwind: cancel pending legs and close open ones at mark.
+    if unwind {
+        for i in 0..legs.len() {
+            let mut leg = legs.get(i).unwrap();
+            match leg.status {
+                LegStatus::Pending => leg.status = LegStatus::Skipped,
+                LegStatus::Open => {
+                    let view = signal_performance(env, &registry, leg.signal_id)?;
+                    close_leg(
+                        env,
+                        &adoption,
+                        &mut leg,
+                        view.average_roi,
+                        LegStatus::Unwound,
+                    )?;
+                }
+                _ => continue,
+            }
+            legs.set(i, leg);
+        }
+        adoption.status = ComboAdoptionStatus::Unwound;
+    } else if legs.iter().all(|l| is_terminal(l.status)) {
+        adoption.status = ComboAdoptionStatus::Completed;
+    }
+
+    adoption.legs = legs;
+    if adoption.status != ComboAdoptionStatus::Active {
+        settle(env, &registry, &adoption)?;
+    }
+    save_adoption(env, &adoption);
+    Ok(adoption.status)
+}
+
+/// Report the finished adoption to the registry and emit `combo_settled`.
+fn settle(env: &Env, registry: &Address, adoption: &ComboAdoption) -> Result<(), ContractError> {
+    let mut results = Vec::new(env);
+    let mut pnls: Vec<i128> = Vec::new(env);
+    let mut total_pnl = 0i128;
+    for leg in adoption.legs.iter() {
+        let traded = matches!(leg.status, LegStatus::Closed | LegStatus::Unwound);
+        total_pnl = total_pnl.saturating_add(leg.pnl);
+        results.push_back(ComponentExecution {
+            signal_id: leg.signal_id,
+            amount: if traded { leg.amount } else { 0 },
+            skipped: !traded,
+            roi: leg.roi,
+        });
+        pnls.push_back(leg.pnl);
+    }
+
+    let recorded = env.try_invoke_contract::<(), soroban_sdk::Error>(
+        registry,
+        &Symbol::new(env, "record_combo_execution"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            adoption.user.into_val(env),
+            adoption.combo_id.into_val(env),
+            adoption.total_amount.into_val(env),
+            results.into_val(env),
+            pnls.into_val(env),
+        ],
+    );
+    if !matches!(recorded, Ok(Ok(()))) {
+        return Err(ContractError::ComboReportFailed);
+    }
+
+    env.events().publish(
+        (
+            Symbol::new(env, "trade_executor"),
+            Symbol::new(env, "combo_settled"),
+        ),
+        ComboSettled {
+            adoption_id: adoption.id,
+            combo_id: adoption.combo_id,
+            user: adoption.user.clone(),
+            status: adoption.status,
+            total_pnl,
+        },
+    );
+    Ok(())
+}
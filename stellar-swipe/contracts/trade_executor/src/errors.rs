@@ -57,6 +57,28 @@ pub enum ContractError {
     FeatureDisabled = 23,
     /// A replayed transaction was detected (nonce mismatch, duplicate hash, or expired).
     ReplayDetected = 24,
+    /// The combo or combo adoption does not exist.
+    ComboNotFound = 25,
+    /// The combo is not active, or the adoption has already settled.
+    ComboNotActive = 26,
+    /// One token must be supplied per combo component.
+    InvalidComboLegs = 27,
+    /// The compliance registry does not accept the user.
+    NotCompliant = 28,
+    /// The bracket order does not exist.
+    BracketNotFound = 29,
+    /// The bracket order has already closed or been cancelled.
+    BracketNotOpen = 30,
+    /// The exit legs do not bracket the entry price, or none were given.
+    InvalidBracketLegs = 31,
+    /// A combo component's signal could not be read from the registry.
+    ComboSignalMissing = 32,
+    /// The registry rejected the settled combo adoption.
+    ComboReportFailed = 33,
+    /// Bracket legs describe a short; brackets only support long entries.
+    ShortBracketUnsupported = 34,
+    /// The portfolio position already carries an open bracket order.
+    PositionAlreadyBracketed = 35,
 }
 
 /// Populated when [`ContractError::InsufficientLiquidity`] is returned.
@@ -7,6 +7,8 @@
 //!   whose oracle price has already crossed the trigger threshold.
 //! - `compute_keeper_reward(position_value)` — 0.1% of position value.
 //! - `KEEPER_REWARD_BPS` — the reward rate constant (10 bps = 0.1%).
+//! - A keeper registry: admin-managed addresses allowed to call keeper-only
+//!   entrypoints such as `check_bracket_order`.
 
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
 
@@ -41,6 +43,61 @@ pub struct TriggerablePosition {
 /// Stored as `Vec<(Address, u64, u32)>`.
 pub const WATCH_LIST_KEY: &str = "WatchList";
 
+/// Persistent `Vec<Address>` of registered keepers.
+pub const KEEPER_REGISTRY_KEY: &str = "Keepers";
+
+// ── Keeper registry ───────────────────────────────────────────────────────────
+
+fn load_keepers(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&Symbol::new(env, KEEPER_REGISTRY_KEY))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn save_keepers(env: &Env, keepers: &Vec<Address>) {
+    env.storage()
+        .persistent()
+        .set(&Symbol::new(env, KEEPER_REGISTRY_KEY), keepers);
+}
+
+/// Register a keeper address. The caller checks admin auth.
+pub fn add_keeper(env: &Env, keeper: Address) {
+    let mut keepers = load_keepers(env);
+    if !keepers.contains(keeper.clone()) {
+        keepers.push_back(keeper.clone());
+        save_keepers(env, &keepers);
+        env.events()
+            .publish((symbol_short!("keeper"), symbol_short!("added")), keeper);
+    }
+}
+
+/// Remove a keeper address. The caller checks admin auth.
+pub fn remove_keeper(env: &Env, keeper: &Address) {
+    let mut keepers = load_keepers(env);
+    if let Some(pos) = keepers.first_index_of(keeper.clone()) {
+        keepers.remove(pos);
+        save_keepers(env, &keepers);
+        env.events().publish(
+            (symbol_short!("keeper"), symbol_short!("removed")),
+            keeper.clone(),
+        );
+    }
+}
+
+pub fn list_keepers(env: &Env) -> Vec<Address> {
+    load_keepers(env)
+}
+
+/// Require `caller`'s auth and that it is a registered keeper.
+pub fn require_registered_keeper(env: &Env, caller: &Address) -> Result<(), ContractError> {
+    caller.require_auth();
+    if !load_keepers(env).contains(caller.clone()) {
+        return Err(ContractError::Unauthorized);
+    }
+    Ok(())
+}
+
 // ── Public helpers ────────────────────────────────────────────────────────────
 
 /// Register a `(user, trade_id, asset_pair)` tuple so keepers can discover it.
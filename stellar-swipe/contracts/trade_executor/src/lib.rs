@@ -1,5 +1,7 @@
 #![no_std]
 
+pub mod bracket;
+pub mod combo;
 pub mod dca;
 mod errors;
 pub mod feature_flags;
@@ -20,7 +22,9 @@ use soroban_sdk::{
     contract, contractimpl, contracttype, Address, Bytes, Env, IntoVal, String, Symbol, Val, Vec,
 };
 
+use stellar_swipe_common::exit_tiers::{StopLossTier, TakeProfitTier};
 use stellar_swipe_common::replay_protection::verify_and_commit;
+use stellar_swipe_common::ComplianceLevel;
 use triggers::{ORACLE_KEY, PORTFOLIO_KEY};
 use wire::TRADE_TIMEOUT_LEDGERS;
 
@@ -65,6 +69,20 @@ pub enum StorageKey {
     OpenInterestPerPair(Address),
     /// Feature flag: keyed by flag name. `true` = enabled, absent/`false` = disabled.
     FeatureFlag(String),
+    /// SignalRegistry contract that combo adoptions are read from and reported to.
+    SignalRegistry,
+    NextComboAdoptionId,
+    /// Combo adoption by id. Stores a `ComboAdoption`.
+    ComboAdoption(u64),
+    /// Compliance registry consulted before copy trades. Unset = no gate.
+    ComplianceRegistry,
+    /// Asset pair id the compliance registry knows a traded token by.
+    CompliancePair(Address),
+    NextBracketOrderId,
+    /// Bracket order by id. Stores a `BracketOrder`.
+    BracketOrder(u64),
+    /// Latest bracket placed on a user's portfolio position. Stores its id.
+    PositionBracket(Address, u64),
 }
 
 /// Temporary-storage key for the reentrancy lock on `execute_copy_trade`.
@@ -94,6 +112,17 @@ pub struct BatchTradeResult {
     pub error_code: u32,
 }
 
+/// Replay-protection fields of a call (see [`verify_and_commit`]), passed as
+/// one argument where a call would otherwise exceed the contract parameter
+/// limit.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplayProof {
+    pub nonce: u64,
+    pub tx_hash: Bytes,
+    pub expiry_ts: u64,
+}
+
 /// Instance config hoisted once per `batch_execute` call to amortize storage reads.
 #[derive(Clone)]
 struct BatchExecutionContext {
@@ -282,6 +311,24 @@ fn decrease_open_interest(env: &Env, pair: &Address, amount: i128) {
     env.storage().instance().set(&key, &next);
 }
 
+fn require_compliant(env: &Env, user: &Address, token: &Address) -> Result<(), ContractError> {
+    let registry: Option<Address> = env.storage().instance().get(&StorageKey::ComplianceRegistry);
+    match registry {
+        Some(registry) => stellar_swipe_common::require_compliant(
+            env,
+            &registry,
+            user,
+            ComplianceLevel::Basic,
+            env.storage()
+                .instance()
+                .get(&StorageKey::CompliancePair(token.clone())),
+        )
+        .map(|_| ())
+        .map_err(|_| ContractError::NotCompliant),
+        None => Ok(()),
+    }
+}
+
 fn execute_market_copy_trade(
     env: &Env,
     user: Address,
@@ -298,6 +345,7 @@ fn execute_market_copy_trade(
     if amount <= 0 {
         return Err(ContractError::InvalidAmount);
     }
+    require_compliant(env, &user, &token)?;
 
     let cb_active = batch_ctx
         .map(|c| c.circuit_breaker_active)
@@ -514,6 +562,48 @@ impl TradeExecutorContract {
             .set(&StorageKey::UserPortfolio, &portfolio);
     }
 
+    /// Admin: gate copy trades on the shared compliance registry, or pass
+    /// `None` to remove the gate.
+    pub fn set_compliance_registry(env: Env, registry: Option<Address>) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Admin)
+            .expect("not initialized");
+        admin.require_auth();
+        match registry {
+            Some(registry) => env
+                .storage()
+                .instance()
+                .set(&StorageKey::ComplianceRegistry, &registry),
+            None => env
+                .storage()
+                .instance()
+                .remove(&StorageKey::ComplianceRegistry),
+        }
+    }
+
+    pub fn get_compliance_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&StorageKey::ComplianceRegistry)
+    }
+
+    /// Admin: name the asset pair id the compliance registry uses for
+    /// `token`, so jurisdiction pair blocks apply to its trades. `None`
+    /// unmaps it.
+    pub fn set_compliance_pair(env: Env, token: Address, pair: Option<u32>) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&StorageKey::Admin)
+            .expect("not initialized");
+        admin.require_auth();
+        let key = StorageKey::CompliancePair(token);
+        match pair {
+            Some(pair) => env.storage().instance().set(&key, &pair),
+            None => env.storage().instance().remove(&key),
+        }
+    }
+
     pub fn get_user_portfolio(env: Env) -> Option<Address> {
         env.storage().instance().get(&StorageKey::UserPortfolio)
     }
@@ -759,6 +849,7 @@ impl TradeExecutorContract {
                 if price <= 0 {
                     return Err(ContractError::InvalidAmount);
                 }
+                require_compliant(&env, &user, &token)?;
 
                 let fee = effective_estimated_fee(&env);
                 let bal_key = StorageKey::LastInsufficientBalance(user.clone());
@@ -1035,8 +1126,8 @@ impl TradeExecutorContract {
     /// Realized P&L = `exit_price - (amount × entry_price / ENTRY_PRICE_DENOMINATOR)`,
     /// which expresses both terms in `to_token` units.
     ///
-    /// Replay-protection parameters (`nonce`, `tx_hash`, `expiry_ts`) are verified
-    /// via [`verify_and_commit`] before the swap executes.
+    /// The replay-protection fields in `replay` are verified via
+    /// [`verify_and_commit`] before the swap executes.
     pub fn cancel_copy_trade(
         env: Env,
         caller: Address,
@@ -1047,11 +1138,9 @@ impl TradeExecutorContract {
         amount: i128,
         min_received: i128,
         entry_price: i128,
-        nonce: u64,
-        tx_hash: Bytes,
-        expiry_ts: u64,
+        replay: ReplayProof,
     ) -> Result<(), ContractError> {
-        verify_and_commit(&env, &user, nonce, tx_hash, expiry_ts)
+        verify_and_commit(&env, &user, replay.nonce, replay.tx_hash, replay.expiry_ts)
             .map_err(|_| ContractError::ReplayDetected)?;
         caller.require_auth();
         if caller != user {
@@ -1220,6 +1309,129 @@ impl TradeExecutorContract {
         dca::cancel_dca_plan(&env, &user, signal_id)
     }
 
+    // ── Combo signals ─────────────────────────────────────────────────────────
+
+    /// Set the SignalRegistry contract used by combo adoptions (admin only).
+    pub fn set_signal_registry(env: Env, registry: Address) -> Result<(), ContractError> {
+        require_admin(&env)?;
+        env.storage()
+            .instance()
+            .set(&StorageKey::SignalRegistry, &registry);
+        Ok(())
+    }
+
+    pub fn get_signal_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&StorageKey::SignalRegistry)
+    }
+
+    /// Adopt a registry combo: `total_amount` is split across the components by
+    /// weight and each leg is placed as a copy trade of `tokens[i]`, funded
+    /// from the user's allowance to this contract. Closed legs are sold into
+    /// `quote_token`. Returns the adoption id to pass to [`Self::advance_combo`].
+    pub fn adopt_combo(
+        env: Env,
+        user: Address,
+        combo_id: u64,
+        tokens: Vec<Address>,
+        quote_token: Address,
+        total_amount: i128,
+    ) -> Result<u64, ContractError> {
+        user.require_auth();
+        feature_flags::require_feature_enabled(&env, feature_flags::FEAT_COPY_TRADE)?;
+        combo::adopt_combo(&env, &user, combo_id, tokens, quote_token, total_amount)
+    }
+
+    /// Registered keeper: settle combo legs on their signal outcomes, open
+    /// dependent legs and unwind on a required-leg failure. Returns the
+    /// adoption status.
+    pub fn advance_combo(
+        env: Env,
+        keeper: Address,
+        adoption_id: u64,
+    ) -> Result<combo::ComboAdoptionStatus, ContractError> {
+        combo::advance_combo(&env, &keeper, adoption_id)
+    }
+
+    pub fn get_combo_adoption(env: Env, adoption_id: u64) -> Option<combo::ComboAdoption> {
+        combo::load_adoption(&env, adoption_id)
+    }
+
+    // ── Bracket orders ────────────────────────────────────────────────────────
+
+    /// Open a market position and attach its exits in one call: an optional
+    /// hard `stop_loss_price`, take-profit tiers that scale out of the
+    /// position, and trailing-stop tiers. `trade_id` is the UserPortfolio
+    /// position closed when the bracket closes; it must be an open position
+    /// of `user` with no other open bracket. Returns the bracket id.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_bracket_order(
+        env: Env,
+        user: Address,
+        token: Address,
+        asset_pair: u32,
+        trade_id: u64,
+        amount: i128,
+        stop_loss_price: Option<i128>,
+        take_profit_tiers: Vec<TakeProfitTier>,
+        stop_loss_tiers: Vec<StopLossTier>,
+    ) -> Result<u64, ContractError> {
+        user.require_auth();
+        feature_flags::require_feature_enabled(&env, feature_flags::FEAT_COPY_TRADE)?;
+        bracket::place_bracket_order(
+            &env,
+            &user,
+            &token,
+            asset_pair,
+            trade_id,
+            amount,
+            stop_loss_price,
+            take_profit_tiers,
+            stop_loss_tiers,
+        )
+    }
+
+    /// Registered keeper: fill the bracket legs crossed by the oracle price.
+    /// A fill that closes the position cancels the remaining legs.
+    pub fn check_bracket_order(
+        env: Env,
+        keeper: Address,
+        bracket_id: u64,
+    ) -> Result<bracket::BracketStatus, ContractError> {
+        bracket::check_bracket_order(&env, &keeper, bracket_id)
+    }
+
+    /// Register a keeper allowed to call keeper-only entrypoints (admin only).
+    pub fn add_keeper(env: Env, keeper: Address) -> Result<(), ContractError> {
+        require_admin(&env)?;
+        keeper::add_keeper(&env, keeper);
+        Ok(())
+    }
+
+    /// Remove a registered keeper (admin only).
+    pub fn remove_keeper(env: Env, keeper: Address) -> Result<(), ContractError> {
+        require_admin(&env)?;
+        keeper::remove_keeper(&env, &keeper);
+        Ok(())
+    }
+
+    pub fn list_keepers(env: Env) -> Vec<Address> {
+        keeper::list_keepers(&env)
+    }
+
+    /// Detach the exits of an open bracket without closing the position.
+    pub fn cancel_bracket_order(
+        env: Env,
+        user: Address,
+        bracket_id: u64,
+    ) -> Result<(), ContractError> {
+        user.require_auth();
+        bracket::cancel_bracket_order(&env, &user, bracket_id)
+    }
+
+    pub fn get_bracket_order(env: Env, bracket_id: u64) -> Option<bracket::BracketOrder> {
+        bracket::load_bracket(&env, bracket_id)
+    }
+
     // ── Feature flag registry ─────────────────────────────────────────────────
 
     /// Enable or disable a named feature flag.  Admin only.
@@ -9,7 +9,7 @@ use crate::{
         MAX_POSITIONS_PER_USER, MAX_POSITION_PCT_BPS,
     },
     sdex::{self, execute_sdex_swap},
-    OrderType, TradeExecutorContract, TradeExecutorContractClient,
+    OrderType, ReplayProof, TradeExecutorContract, TradeExecutorContractClient,
 };
 use soroban_sdk::{
     contract, contractimpl, contracttype, symbol_short,
@@ -17,6 +17,7 @@ use soroban_sdk::{
     token::{self, StellarAssetClient},
     Address, Env, MuxedAddress, TryFromVal,
 };
+use stellar_swipe_common::replay_protection::current_nonce;
 
 // ── Mock UserPortfolio ────────────────────────────────────────────────────────
 //
@@ -76,7 +77,6 @@ impl MockUserPortfolio {
 
 const TRADE_AMOUNT: i128 = 1_000_000;
 
-
 fn test_tx_hash(env: &Env, seed: u8) -> soroban_sdk::Bytes {
     let mut arr = [0u8; 32];
     arr[0] = seed;
@@ -88,6 +88,23 @@ fn far_future(env: &Env) -> u64 {
     env.ledger().timestamp() + 86_400 * 365
 }
 
+/// Nonce `user`'s next replay-protected call to `exec_id` must carry.
+fn next_nonce(env: &Env, exec_id: &Address, user: &Address) -> u64 {
+    env.as_contract(exec_id, || current_nonce(env, user)) + 1
+}
+
+fn next_tx_hash(env: &Env, exec_id: &Address, user: &Address) -> soroban_sdk::Bytes {
+    test_tx_hash(env, next_nonce(env, exec_id, user) as u8)
+}
+
+fn replay(env: &Env, nonce: u64, seed: u8) -> ReplayProof {
+    ReplayProof {
+        nonce,
+        tx_hash: test_tx_hash(env, seed),
+        expiry_ts: far_future(env),
+    }
+}
+
 fn sac_token(env: &Env) -> Address {
     let issuer = Address::generate(env);
     env.register_stellar_asset_contract_v2(issuer).address()
@@ -250,7 +267,9 @@ fn execute_copy_trade_sufficient_balance_invokes_portfolio() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
     assert!(exec.get_insufficient_balance_detail(&user).is_none());
     assert_eq!(
@@ -294,7 +313,9 @@ fn twenty_first_copy_trade_fails_until_one_closed() {
             &None::<u32>,
             &OrderType::Market,
             &None,
-            &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+            &next_nonce(&env, &exec_id, &user),
+            &next_tx_hash(&env, &exec_id, &user),
+            &far_future(&env),
         );
     }
 
@@ -305,7 +326,9 @@ fn twenty_first_copy_trade_fails_until_one_closed() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
     assert_eq!(err, Err(Ok(ContractError::PositionLimitReached)));
 
@@ -317,7 +340,9 @@ fn twenty_first_copy_trade_fails_until_one_closed() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
 
     assert_eq!(
@@ -341,7 +366,9 @@ fn whitelisted_user_bypasses_position_limit() {
             &None::<u32>,
             &OrderType::Market,
             &None,
-            &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+            &next_nonce(&env, &exec_id, &user),
+            &next_tx_hash(&env, &exec_id, &user),
+            &far_future(&env),
         );
     }
 
@@ -352,7 +379,9 @@ fn whitelisted_user_bypasses_position_limit() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
     assert_eq!(err, Err(Ok(ContractError::PositionLimitReached)));
 
@@ -366,7 +395,9 @@ fn whitelisted_user_bypasses_position_limit() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
     assert_eq!(
         MockUserPortfolioClient::new(&env, &portfolio_id).get_open_position_count(&user),
@@ -383,7 +414,9 @@ fn whitelisted_user_bypasses_position_limit() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
     assert_eq!(err2, Err(Ok(ContractError::PositionLimitReached)));
 }
@@ -419,6 +452,9 @@ impl ReentrantPortfolio {
             &None::<u32>,
             &OrderType::Market,
             &None,
+            &2u64,
+            &soroban_sdk::Bytes::from_array(&env, &[9u8; 32]),
+            &(env.ledger().timestamp() + 3_600),
         );
         let blocked = matches!(result, Err(Ok(ContractError::ReentrancyDetected)));
         env.storage()
@@ -466,7 +502,9 @@ fn reentrant_call_returns_reentrancy_detected() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
     assert!(
         ReentrantPortfolioClient::new(&env, &portfolio_id).was_blocked(),
@@ -500,7 +538,9 @@ fn lock_cleared_after_successful_execution() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
     exec.execute_copy_trade(
         &user,
@@ -509,7 +549,9 @@ fn lock_cleared_after_successful_execution() {
         &None::<u32>,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
 
     assert_eq!(
@@ -778,9 +820,7 @@ impl MockPortfolioWithPositions {
         env.storage()
             .instance()
             .set(&PortfolioKey::LastClosed, &trade_id);
-        env.storage()
-            .instance()
-            .set(&PortfolioKey::LastPnl, &pnl);
+        env.storage().instance().set(&PortfolioKey::LastPnl, &pnl);
     }
     pub fn last_closed(env: Env) -> Option<u64> {
         env.storage().instance().get(&PortfolioKey::LastClosed)
@@ -826,11 +866,20 @@ fn cancel_copy_trade_success() {
     let exec = TradeExecutorContractClient::new(&env, &exec_id);
 
     MockPortfolioWithPositionsClient::new(&env, &portfolio_id).add_position_with_entry_price(
-        &user, &1u64, &10_000_000i128,
+        &user,
+        &1u64,
+        &10_000_000i128,
     );
     exec.cancel_copy_trade(
-        &user, &user, &1u64, &token_a, &token_b, &1_000_000, &900_000, &10_000_000,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &user,
+        &user,
+        &1u64,
+        &token_a,
+        &token_b,
+        &1_000_000,
+        &900_000,
+        &10_000_000,
+        &replay(&env, 1, 0),
     );
 
     assert_eq!(
@@ -846,7 +895,9 @@ fn cancel_copy_trade_unauthorized() {
     let exec = TradeExecutorContractClient::new(&env, &exec_id);
 
     MockPortfolioWithPositionsClient::new(&env, &portfolio_id).add_position_with_entry_price(
-        &user, &1u64, &10_000_000i128,
+        &user,
+        &1u64,
+        &10_000_000i128,
     );
 
     let err = env.as_contract(&exec_id, || {
@@ -860,9 +911,7 @@ fn cancel_copy_trade_unauthorized() {
             1_000_000,
             900_000,
             10_000_000,
-            1u64,
-            test_tx_hash(&env, 0),
-            far_future(&env),
+            replay(&env, 1, 0),
         )
     });
     assert_eq!(err, Err(ContractError::Unauthorized));
@@ -885,9 +934,7 @@ fn cancel_copy_trade_not_found() {
             1_000_000,
             900_000,
             10_000_000,
-            1u64,
-            test_tx_hash(&env, 0),
-            far_future(&env),
+            replay(&env, 1, 0),
         )
     });
     assert_eq!(err, Err(ContractError::TradeNotFound));
@@ -906,8 +953,15 @@ fn cancel_copy_trade_pnl_calculation() {
     let portfolio = MockPortfolioWithPositionsClient::new(&env, &portfolio_id);
     portfolio.add_position_with_entry_price(&user, &2u64, &9_500_000i128);
     exec.cancel_copy_trade(
-        &user, &user, &2u64, &token_a, &token_b, &1_000_000, &900_000, &9_500_000,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &user,
+        &user,
+        &2u64,
+        &token_a,
+        &token_b,
+        &1_000_000,
+        &900_000,
+        &9_500_000,
+        &replay(&env, 1, 0),
     );
 
     // Verify the close_position was called with the correct realized_pnl.
@@ -915,6 +969,7 @@ fn cancel_copy_trade_pnl_calculation() {
     assert_eq!(closed_id, Some(2u64));
     let pnl = portfolio.last_pnl();
     assert_eq!(pnl, Some(250_000i128), "realized PnL should be 250_000 when entry_price=0.95 and exit_price=1.2 for amount=1_000_000");
+}
 
 // ── Auth propagation: cancel_copy_trade ──────────────────────────────────────
 
@@ -925,7 +980,9 @@ fn cancel_copy_trade_third_party_rejected() {
     let third_party = Address::generate(&env);
 
     MockPortfolioWithPositionsClient::new(&env, &portfolio_id).add_position_with_entry_price(
-        &user, &5u64, &10_000_000i128,
+        &user,
+        &5u64,
+        &10_000_000i128,
     );
 
     let err = env.as_contract(&exec_id, || {
@@ -939,26 +996,32 @@ fn cancel_copy_trade_third_party_rejected() {
             1_000_000,
             900_000,
             10_000_000,
-            1u64,
-            test_tx_hash(&env, 0),
-            far_future(&env),
+            replay(&env, 1, 0),
         )
     });
     assert_eq!(err, Err(ContractError::Unauthorized));
 }
 
-
 #[test]
 fn cancel_copy_trade_replay_nonce_rejected() {
     let (env, exec_id, portfolio_id, user, token_a, token_b, _) = setup_cancel(1_000_000);
     let exec = TradeExecutorContractClient::new(&env, &exec_id);
 
     MockPortfolioWithPositionsClient::new(&env, &portfolio_id).add_position_with_entry_price(
-        &user, &1u64, &10_000_000i128,
+        &user,
+        &1u64,
+        &10_000_000i128,
     );
     exec.cancel_copy_trade(
-        &user, &user, &1u64, &token_a, &token_b, &1_000_000, &900_000, &10_000_000,
-        &1u64, &test_tx_hash(&env, 1), &far_future(&env),
+        &user,
+        &user,
+        &1u64,
+        &token_a,
+        &token_b,
+        &1_000_000,
+        &900_000,
+        &10_000_000,
+        &replay(&env, 1, 1),
     );
 
     let err = env.as_contract(&exec_id, || {
@@ -972,9 +1035,7 @@ fn cancel_copy_trade_replay_nonce_rejected() {
             1_000_000,
             900_000,
             10_000_000,
-            1u64,
-            test_tx_hash(&env, 1),
-            far_future(&env),
+            replay(&env, 1, 1),
         )
     });
     assert_eq!(err, Err(ContractError::ReplayDetected));
@@ -998,11 +1059,20 @@ fn trade_cancelled_event_has_two_topic_format() {
     let (env, exec_id, portfolio_id, user, token_a, token_b, _) = setup_cancel(1_100_000);
     let exec = TradeExecutorContractClient::new(&env, &exec_id);
     MockPortfolioWithPositionsClient::new(&env, &portfolio_id).add_position_with_entry_price(
-        &user, &1u64, &10_000_000i128,
+        &user,
+        &1u64,
+        &10_000_000i128,
     );
     exec.cancel_copy_trade(
-        &user, &user, &1u64, &token_a, &token_b, &1_000_000, &900_000, &10_000_000,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &user,
+        &user,
+        &1u64,
+        &token_a,
+        &token_b,
+        &1_000_000,
+        &900_000,
+        &10_000_000,
+        &replay(&env, 1, 0),
     );
     let (contract, event) = last_event_topics(&env);
     assert_eq!(contract, soroban_sdk::Symbol::new(&env, "trade_executor"));
@@ -1031,7 +1101,9 @@ fn volume_limit_zero_means_no_restriction() {
         &None,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
 }
 
@@ -1047,7 +1119,9 @@ fn volume_under_limit_succeeds() {
         &None,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
 }
 
@@ -1063,7 +1137,9 @@ fn volume_at_limit_succeeds() {
         &None,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
 }
 
@@ -1079,7 +1155,9 @@ fn volume_over_limit_returns_error() {
         &None,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
     assert_eq!(result, Err(Ok(ContractError::DailyVolumeLimitExceeded)));
 }
@@ -1099,7 +1177,9 @@ fn volume_resets_on_new_day() {
         &None,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
 
     // Advance to day 1.
@@ -1113,7 +1193,9 @@ fn volume_resets_on_new_day() {
         &None,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
 }
 
@@ -1141,8 +1223,17 @@ fn primary_fee_deduction_succeeds_with_sufficient_balance() {
     exec.set_user_portfolio(&portfolio_id);
 
     // Should succeed — no fallback needed.
-    let result =
-        exec.try_execute_copy_trade(&user, &token, &amount, &None, &OrderType::Market, &None, &1u64, &test_tx_hash(&env, 0), &far_future(&env));
+    let result = exec.try_execute_copy_trade(
+        &user,
+        &token,
+        &amount,
+        &None,
+        &OrderType::Market,
+        &None,
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
+    );
     assert!(result.is_ok(), "primary fee deduction should succeed");
 
     // No fee_from_received event should be emitted.
@@ -1185,8 +1276,17 @@ fn fee_fallback_activates_when_only_amount_available() {
     exec.set_copy_trade_estimated_fee(&1_000i128);
 
     // Trade should still succeed via fallback.
-    let result =
-        exec.try_execute_copy_trade(&user, &token, &amount, &None, &OrderType::Market, &None, &1u64, &test_tx_hash(&env, 0), &far_future(&env));
+    let result = exec.try_execute_copy_trade(
+        &user,
+        &token,
+        &amount,
+        &None,
+        &OrderType::Market,
+        &None,
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
+    );
     assert!(result.is_ok(), "trade should succeed via fee fallback");
 
     // fee_from_received event must be emitted.
@@ -1232,7 +1332,9 @@ fn trade_fails_when_balance_below_amount() {
         &None,
         &OrderType::Market,
         &None,
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
     assert_eq!(result, Err(Ok(ContractError::InsufficientBalance)));
 }
@@ -1255,7 +1357,9 @@ fn test_limit_order_execution() {
         &None,
         &OrderType::Limit,
         &Some(10_000i128),
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
 
     // Verify order is stored
@@ -1303,7 +1407,9 @@ fn test_limit_order_expiration() {
         &None,
         &OrderType::Limit,
         &Some(10_000i128),
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &1u64,
+        &test_tx_hash(&env, 0),
+        &far_future(&env),
     );
 
     let order_ids = exec.get_pending_limit_order_ids();
@@ -1348,7 +1454,9 @@ fn test_limit_order_persistence() {
         &None,
         &OrderType::Limit,
         &Some(10_000i128),
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
     exec.execute_copy_trade(
         &user,
@@ -1357,7 +1465,9 @@ fn test_limit_order_persistence() {
         &None,
         &OrderType::Limit,
         &Some(9_000i128),
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
     exec.execute_copy_trade(
         &user,
@@ -1366,7 +1476,9 @@ fn test_limit_order_persistence() {
         &None,
         &OrderType::Limit,
         &Some(8_000i128),
-        &1u64, &test_tx_hash(&env, 0), &far_future(&env),
+        &next_nonce(&env, &exec_id, &user),
+        &next_tx_hash(&env, &exec_id, &user),
+        &far_future(&env),
     );
 
     let initial_ids = exec.get_pending_limit_order_ids();
@@ -1,4 +1,6 @@
 pub mod test_batch_execute;
+pub mod test_bracket;
+pub mod test_combo;
 pub mod test_dca;
 pub mod test_feature_flags;
 pub mod test_market_simulation;
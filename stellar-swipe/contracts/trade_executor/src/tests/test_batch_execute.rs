@@ -360,3 +360,89 @@ fn all_trades_fail_returns_all_error_results() {
         assert!(!results.get(i).unwrap().ok);
     }
 }
+
+/// With a compliance registry configured, only attested users' trades run.
+#[test]
+fn compliance_registry_gates_each_trade() {
+    use compliance::{
+        ComplianceLevel, ComplianceRegistry, ComplianceRegistryClient, CustomerStatus,
+    };
+
+    let (env, exec_id, _) = setup();
+    let token = sac(&env);
+    let registry = ComplianceRegistryClient::new(&env, &env.register(ComplianceRegistry, ()));
+    let registry_admin = Address::generate(&env);
+    let issuer = Address::generate(&env);
+    registry.initialize(&registry_admin);
+    registry.add_issuer(&registry_admin, &issuer);
+    TradeExecutorContractClient::new(&env, &exec_id)
+        .set_compliance_registry(&Some(registry.address.clone()));
+
+    let attested = funded_user(&env, &token, 1);
+    let unattested = funded_user(&env, &token, 1);
+    registry.attest(
+        &issuer,
+        &attested,
+        &soroban_sdk::String::from_str(&env, "sep12-customer"),
+        &CustomerStatus::Accepted,
+        &ComplianceLevel::Basic,
+        &soroban_sdk::symbol_short!("DE"),
+        &0,
+    );
+
+    let mut trades: Vec<BatchTradeInput> = Vec::new(&env);
+    for user in [attested.clone(), unattested] {
+        trades.push_back(BatchTradeInput {
+            user,
+            token: token.clone(),
+            amount: AMOUNT,
+        });
+    }
+    let results = env
+        .as_contract(&exec_id, || {
+            TradeExecutorContract::batch_execute(env.clone(), trades)
+        })
+        .unwrap();
+
+    assert!(results.get(0).unwrap().ok);
+    assert_eq!(
+        results.get(1).unwrap(),
+        BatchTradeResult {
+            ok: false,
+            error_code: ContractError::NotCompliant as u32
+        }
+    );
+
+    // Once the token is mapped to a pair blocked in the user's jurisdiction,
+    // the attested user is refused too.
+    registry.set_jurisdiction_rule(
+        &registry_admin,
+        &soroban_sdk::symbol_short!("DE"),
+        &compliance::JurisdictionRule {
+            blocked: false,
+            min_level: ComplianceLevel::Basic,
+            blocked_pairs: soroban_sdk::vec![&env, 3u32],
+        },
+    );
+    TradeExecutorContractClient::new(&env, &exec_id).set_compliance_pair(&token, &Some(3));
+    let trades = soroban_sdk::vec![
+        &env,
+        BatchTradeInput {
+            user: attested,
+            token: token.clone(),
+            amount: AMOUNT,
+        },
+    ];
+    let results = env
+        .as_contract(&exec_id, || {
+            TradeExecutorContract::batch_execute(env.clone(), trades)
+        })
+        .unwrap();
+    assert_eq!(
+        results.get(0).unwrap(),
+        BatchTradeResult {
+            ok: false,
+            error_code: ContractError::NotCompliant as u32
+        }
+    );
+}
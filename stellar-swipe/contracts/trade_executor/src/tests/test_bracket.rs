@@ -0,0 +1,524 @@
+#![cfg(test)]
+//! Unit tests for bracket orders.
+//!
+//! Covers:
+//! - Take-profit tiers scaling out, with the last fill cancelling the stop
+//! - The hard stop closing the position and cancelling the tiers
+//! - A trailing tier arming on profit and closing the remaining size
+//! - Cancelling a bracket's exits
+//! - Leg validation against the oracle entry price, rejecting short legs
+//! - Closing the portfolio position on the last fill, keepers only
+//! - Requiring an open, unbracketed position of the user
+
+use crate::{
+    bracket::{BracketStatus, Portfolio, PortfolioPosition, Position, PositionStatus},
+    errors::ContractError,
+    risk_gates::DEFAULT_ESTIMATED_COPY_TRADE_FEE,
+    TradeExecutorContract, TradeExecutorContractClient,
+};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Ledger as _},
+    token::StellarAssetClient,
+    vec, Address, Env, Vec,
+};
+use stellar_swipe_common::exit_tiers::{StopLossTier, TakeProfitTier};
+
+// ── Mocks ─────────────────────────────────────────────────────────────────────
+
+#[contract]
+pub struct MockBracketPortfolio;
+
+#[contractimpl]
+impl MockBracketPortfolio {
+    pub fn validate_and_record(_env: Env, _user: Address, _max_positions: u32) -> u32 {
+        1
+    }
+
+    pub fn open_position(env: Env, user: Address, position_id: u64) {
+        let mut ids: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&user)
+            .unwrap_or(Vec::new(&env));
+        ids.push_back(position_id);
+        env.storage().instance().set(&user, &ids);
+    }
+
+    pub fn get_portfolio(env: Env, user: Address, _include_closed: bool) -> Portfolio {
+        let ids: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&user)
+            .unwrap_or(Vec::new(&env));
+        let mut open_positions = Vec::new(&env);
+        for position_id in ids.iter() {
+            open_positions.push_back(PortfolioPosition {
+                position_id,
+                position: Position {
+                    entry_price: ENTRY,
+                    amount: AMOUNT,
+                    status: PositionStatus::Open,
+                    realized_pnl: 0,
+                },
+            });
+        }
+        Portfolio {
+            open_positions,
+            closed_positions: Vec::new(&env),
+            closed_position_ids: Vec::new(&env),
+        }
+    }
+
+    pub fn close_position_keeper(
+        env: Env,
+        _caller: Address,
+        _user: Address,
+        trade_id: u64,
+        _asset_pair: u32,
+    ) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("closed"), &trade_id);
+    }
+
+    pub fn last_closed(env: Env) -> Option<u64> {
+        env.storage().instance().get(&symbol_short!("closed"))
+    }
+}
+
+#[contract]
+pub struct MockBracketOracle;
+
+#[contractimpl]
+impl MockBracketOracle {
+    pub fn set_price(env: Env, price: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &price);
+        let ts = env.ledger().timestamp().max(1);
+        env.storage().instance().set(&symbol_short!("pts"), &ts);
+    }
+
+    pub fn get_price(env: Env, _asset_pair: u32) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap()
+    }
+
+    pub fn get_price_timestamp(env: Env, _asset_pair: u32) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("pts"))
+            .unwrap_or(0u64)
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+const AMOUNT: i128 = 1_000_000;
+const ENTRY: i128 = 100;
+const TRADE_ID: u64 = 7;
+
+struct Setup {
+    env: Env,
+    exec: Address,
+    oracle: Address,
+    portfolio: Address,
+    keeper: Address,
+    user: Address,
+    token: Address,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|l| l.timestamp = 1);
+
+    let admin = Address::generate(&env);
+    let portfolio = env.register(MockBracketPortfolio, ());
+    let oracle = env.register(MockBracketOracle, ());
+    let exec = env.register(TradeExecutorContract, ());
+
+    let client = TradeExecutorContractClient::new(&env, &exec);
+    client.initialize(&admin);
+    client.set_user_portfolio(&portfolio);
+    client.set_stop_loss_portfolio(&portfolio);
+    let keeper = Address::generate(&env);
+    client.add_keeper(&keeper);
+    client.add_oracle(&oracle);
+    client.set_oracle(&oracle);
+    MockBracketOracleClient::new(&env, &oracle).set_price(&ENTRY);
+
+    let user = Address::generate(&env);
+    MockBracketPortfolioClient::new(&env, &portfolio).open_position(&user, &TRADE_ID);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &token).mint(&user, &(AMOUNT + DEFAULT_ESTIMATED_COPY_TRADE_FEE));
+    Setup {
+        env,
+        exec,
+        oracle,
+        portfolio,
+        keeper,
+        user,
+        token,
+    }
+}
+
+fn tp(price: i128, position_pct: u32) -> TakeProfitTier {
+    TakeProfitTier {
+        price,
+        position_pct,
+        executed: false,
+    }
+}
+
+fn trail(trigger_profit_pct: u32, trail_pct: u32) -> StopLossTier {
+    StopLossTier {
+        trigger_profit_pct,
+        trail_pct,
+        active: false,
+    }
+}
+
+fn set_price(s: &Setup, price: i128) {
+    MockBracketOracleClient::new(&s.env, &s.oracle).set_price(&price);
+}
+
+fn last_closed(s: &Setup) -> Option<u64> {
+    MockBracketPortfolioClient::new(&s.env, &s.portfolio).last_closed()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn take_profit_tiers_scale_out_and_last_fill_cancels_stop() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let id = client.place_bracket_order(
+        &s.user,
+        &s.token,
+        &0,
+        &TRADE_ID,
+        &AMOUNT,
+        &Some(90),
+        &vec![&s.env, tp(110, 5_000), tp(120, 10_000)],
+        &Vec::new(&s.env),
+    );
+    assert_eq!(client.get_open_interest(&s.token), AMOUNT);
+
+    set_price(&s, 112);
+    assert_eq!(
+        client.check_bracket_order(&s.keeper, &id),
+        BracketStatus::Open
+    );
+    let bracket = client.get_bracket_order(&id).unwrap();
+    assert_eq!(bracket.remaining, AMOUNT / 2);
+    assert_eq!(client.get_open_interest(&s.token), AMOUNT / 2);
+    assert_eq!(last_closed(&s), None);
+
+    set_price(&s, 125);
+    assert_eq!(
+        client.check_bracket_order(&s.keeper, &id),
+        BracketStatus::TakenProfit
+    );
+    let bracket = client.get_bracket_order(&id).unwrap();
+    assert_eq!(bracket.remaining, 0);
+    assert_eq!(client.get_open_interest(&s.token), 0);
+    assert_eq!(last_closed(&s), Some(TRADE_ID));
+
+    // The stop leg was cancelled with the bracket.
+    set_price(&s, 80);
+    assert_eq!(
+        client.try_check_bracket_order(&s.keeper, &id),
+        Err(Ok(ContractError::BracketNotOpen))
+    );
+}
+
+#[test]
+fn hard_stop_closes_position_and_cancels_tiers() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let id = client.place_bracket_order(
+        &s.user,
+        &s.token,
+        &0,
+        &TRADE_ID,
+        &AMOUNT,
+        &Some(90),
+        &vec![&s.env, tp(110, 5_000)],
+        &vec![&s.env, trail(5, 10)],
+    );
+
+    set_price(&s, 95);
+    assert_eq!(
+        client.check_bracket_order(&s.keeper, &id),
+        BracketStatus::Open
+    );
+
+    set_price(&s, 90);
+    assert_eq!(
+        client.check_bracket_order(&s.keeper, &id),
+        BracketStatus::StoppedOut
+    );
+    let bracket = client.get_bracket_order(&id).unwrap();
+    assert_eq!(bracket.remaining, 0);
+    assert!(!bracket.take_profit_tiers.get(0).unwrap().executed);
+    assert_eq!(client.get_open_interest(&s.token), 0);
+    assert_eq!(last_closed(&s), Some(TRADE_ID));
+}
+
+#[test]
+fn trailing_tier_arms_on_profit_and_closes_remaining() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let id = client.place_bracket_order(
+        &s.user,
+        &s.token,
+        &0,
+        &TRADE_ID,
+        &AMOUNT,
+        &None,
+        &vec![&s.env, tp(110, 2_500)],
+        &vec![&s.env, trail(10, 5)],
+    );
+
+    // Scales out a quarter and arms the trail at +20%.
+    set_price(&s, 120);
+    assert_eq!(
+        client.check_bracket_order(&s.keeper, &id),
+        BracketStatus::Open
+    );
+    let bracket = client.get_bracket_order(&id).unwrap();
+    assert_eq!(bracket.remaining, AMOUNT * 3 / 4);
+    assert!(bracket.stop_loss_tiers.get(0).unwrap().active);
+    assert_eq!(bracket.highest_price, 120);
+
+    // 5% below the 120 peak is 114.
+    set_price(&s, 115);
+    assert_eq!(
+        client.check_bracket_order(&s.keeper, &id),
+        BracketStatus::Open
+    );
+    set_price(&s, 113);
+    assert_eq!(
+        client.check_bracket_order(&s.keeper, &id),
+        BracketStatus::TrailingStopped
+    );
+    assert_eq!(client.get_bracket_order(&id).unwrap().remaining, 0);
+    assert_eq!(client.get_open_interest(&s.token), 0);
+}
+
+#[test]
+fn cancel_detaches_exits_and_keeps_position() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let id = client.place_bracket_order(
+        &s.user,
+        &s.token,
+        &0,
+        &TRADE_ID,
+        &AMOUNT,
+        &Some(90),
+        &Vec::new(&s.env),
+        &Vec::new(&s.env),
+    );
+
+    let other = Address::generate(&s.env);
+    assert_eq!(
+        client.try_cancel_bracket_order(&other, &id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.cancel_bracket_order(&s.user, &id);
+    let bracket = client.get_bracket_order(&id).unwrap();
+    assert_eq!(bracket.status, BracketStatus::Cancelled);
+    assert_eq!(bracket.remaining, AMOUNT);
+    assert_eq!(client.get_open_interest(&s.token), AMOUNT);
+    assert_eq!(last_closed(&s), None);
+
+    set_price(&s, 50);
+    assert_eq!(
+        client.try_check_bracket_order(&s.keeper, &id),
+        Err(Ok(ContractError::BracketNotOpen))
+    );
+}
+
+#[test]
+fn place_bracket_order_validates_legs() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let none = Vec::<TakeProfitTier>::new(&s.env);
+    let no_trail = Vec::<StopLossTier>::new(&s.env);
+
+    assert_eq!(
+        client.try_place_bracket_order(
+            &s.user, &s.token, &0, &TRADE_ID, &AMOUNT, &None, &none, &no_trail
+        ),
+        Err(Ok(ContractError::InvalidBracketLegs))
+    );
+    // Stop above entry.
+    assert_eq!(
+        client.try_place_bracket_order(
+            &s.user,
+            &s.token,
+            &0,
+            &TRADE_ID,
+            &AMOUNT,
+            &Some(ENTRY),
+            &none,
+            &no_trail
+        ),
+        Err(Ok(ContractError::InvalidBracketLegs))
+    );
+    // Short-shaped legs: stop above entry, or a target below it.
+    assert_eq!(
+        client.try_place_bracket_order(
+            &s.user,
+            &s.token,
+            &0,
+            &TRADE_ID,
+            &AMOUNT,
+            &Some(110),
+            &none,
+            &no_trail
+        ),
+        Err(Ok(ContractError::ShortBracketUnsupported))
+    );
+    assert_eq!(
+        client.try_place_bracket_order(
+            &s.user,
+            &s.token,
+            &0,
+            &TRADE_ID,
+            &AMOUNT,
+            &None,
+            &vec![&s.env, tp(90, 10_000)],
+            &no_trail
+        ),
+        Err(Ok(ContractError::ShortBracketUnsupported))
+    );
+    // Take-profit tiers must climb above entry.
+    assert_eq!(
+        client.try_place_bracket_order(
+            &s.user,
+            &s.token,
+            &0,
+            &TRADE_ID,
+            &AMOUNT,
+            &None,
+            &vec![&s.env, tp(120, 5_000), tp(110, 5_000)],
+            &no_trail,
+        ),
+        Err(Ok(ContractError::InvalidBracketLegs))
+    );
+    assert_eq!(
+        client.try_place_bracket_order(
+            &s.user,
+            &s.token,
+            &0,
+            &TRADE_ID,
+            &AMOUNT,
+            &None,
+            &none,
+            &vec![&s.env, trail(5, 100)],
+        ),
+        Err(Ok(ContractError::InvalidBracketLegs))
+    );
+    assert_eq!(
+        client.try_place_bracket_order(
+            &s.user,
+            &s.token,
+            &0,
+            &TRADE_ID,
+            &0,
+            &Some(90),
+            &none,
+            &no_trail
+        ),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(client.get_open_interest(&s.token), 0);
+    assert_eq!(
+        client.try_check_bracket_order(&s.keeper, &1),
+        Err(Ok(ContractError::BracketNotFound))
+    );
+}
+
+#[test]
+fn only_registered_keepers_check_brackets() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let id = client.place_bracket_order(
+        &s.user,
+        &s.token,
+        &0,
+        &TRADE_ID,
+        &AMOUNT,
+        &Some(90),
+        &Vec::new(&s.env),
+        &Vec::new(&s.env),
+    );
+    set_price(&s, 80);
+
+    let stranger = Address::generate(&s.env);
+    assert_eq!(
+        client.try_check_bracket_order(&stranger, &id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.remove_keeper(&s.keeper);
+    assert_eq!(
+        client.try_check_bracket_order(&s.keeper, &id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(last_closed(&s), None);
+
+    client.add_keeper(&stranger);
+    assert_eq!(
+        client.check_bracket_order(&stranger, &id),
+        BracketStatus::StoppedOut
+    );
+    assert_eq!(last_closed(&s), Some(TRADE_ID));
+}
+
+#[test]
+fn place_bracket_order_requires_an_open_unbracketed_position() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let place = |user: &Address, trade_id: u64| {
+        client.try_place_bracket_order(
+            user,
+            &s.token,
+            &0,
+            &trade_id,
+            &AMOUNT,
+            &Some(90),
+            &Vec::new(&s.env),
+            &Vec::new(&s.env),
+        )
+    };
+
+    // Not a position of the user, or not one at all.
+    assert_eq!(place(&s.user, 99), Err(Ok(ContractError::TradeNotFound)));
+    let other = Address::generate(&s.env);
+    StellarAssetClient::new(&s.env, &s.token)
+        .mint(&other, &(AMOUNT + DEFAULT_ESTIMATED_COPY_TRADE_FEE));
+    assert_eq!(
+        place(&other, TRADE_ID),
+        Err(Ok(ContractError::TradeNotFound))
+    );
+    assert_eq!(client.get_open_interest(&s.token), 0);
+
+    // One open bracket per position.
+    let id = place(&s.user, TRADE_ID).unwrap().unwrap();
+    assert_eq!(
+        place(&s.user, TRADE_ID),
+        Err(Ok(ContractError::PositionAlreadyBracketed))
+    );
+    client.cancel_bracket_order(&s.user, &id);
+    StellarAssetClient::new(&s.env, &s.token)
+        .mint(&s.user, &(AMOUNT + DEFAULT_ESTIMATED_COPY_TRADE_FEE));
+    assert!(place(&s.user, TRADE_ID).is_ok());
+}
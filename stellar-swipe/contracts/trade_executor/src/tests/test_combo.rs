@@ -0,0 +1,760 @@
+#![cfg(test)]
+//! Unit tests for combo signal adoption.
+//!
+//! Covers:
+//! - Sequential legs waiting on upstream outcomes, with per-leg P&L reported
+//! - Closed legs sold into the quote token and paid to the user
+//! - Expired legs waiting while their signal can still resolve on targets
+//! - Simultaneous combo unwinding when a leg's signal fails
+//! - Conditional gates opening or skipping legs
+//! - A sequential leg that cannot be placed unwinding the combo
+//! - Input validation on adoption
+//! - Leg P&L valued in quote units at the entry price
+//! - `advance_combo` restricted to registered keepers
+
+use crate::{
+    combo::{
+        ComboAdoptionStatus, ComboSignal, ComboStatus, ComboType, ComponentExecution,
+        ComponentSignal, Condition, ConditionGate, ConditionType, LegStatus, SignalPerformanceView,
+        SignalStatus,
+    },
+    errors::ContractError,
+    risk_gates::DEFAULT_ESTIMATED_COPY_TRADE_FEE,
+    TradeExecutorContract, TradeExecutorContractClient, ENTRY_PRICE_DENOMINATOR,
+};
+use soroban_sdk::{
+    contract, contractimpl, contracttype,
+    testutils::Address as _,
+    token::{self, StellarAssetClient},
+    vec, Address, Env, MuxedAddress, String, Vec,
+};
+
+// ── Mock UserPortfolio ────────────────────────────────────────────────────────
+
+#[contract]
+pub struct MockComboPortfolio;
+
+#[contractimpl]
+impl MockComboPortfolio {
+    pub fn validate_and_record(_env: Env, _user: Address, _max_positions: u32) -> u32 {
+        1
+    }
+}
+
+// ── Mock SDEX router ──────────────────────────────────────────────────────────
+
+#[contract]
+pub struct MockComboRouter;
+
+#[contracttype]
+#[derive(Clone)]
+enum RouterKey {
+    Price(Address),
+}
+
+#[contractimpl]
+impl MockComboRouter {
+    /// Quote-token units paid per 10_000 units of `token`.
+    pub fn set_price_bps(env: Env, token: Address, bps: i128) {
+        env.storage().instance().set(&RouterKey::Price(token), &bps);
+    }
+
+    pub fn get_best_ask(_env: Env, _from_token: Address, _to_token: Address) -> (i128, i128) {
+        (0, 10_000_000_000i128)
+    }
+
+    pub fn swap(
+        env: Env,
+        pull_from: Address,
+        from_token: Address,
+        to_token: Address,
+        amount_in: i128,
+        _min_out: i128,
+        recipient: Address,
+    ) -> i128 {
+        let router = env.current_contract_address();
+        token::Client::new(&env, &from_token)
+            .transfer_from(&router, &pull_from, &router, &amount_in);
+        let price_bps: i128 = env
+            .storage()
+            .instance()
+            .get(&RouterKey::Price(from_token))
+            .unwrap_or(10_000);
+        let amount_out = amount_in * price_bps / 10_000;
+        let to_mux: MuxedAddress = recipient.into();
+        token::Client::new(&env, &to_token).transfer(&router, &to_mux, &amount_out);
+        amount_out
+    }
+}
+
+// ── Mock SignalRegistry ───────────────────────────────────────────────────────
+
+#[contract]
+pub struct MockRegistry;
+
+#[contracttype]
+#[derive(Clone)]
+enum RegistryKey {
+    Combo(u64),
+    Signal(u64),
+    Targets(u64),
+    Recorded(u64),
+    RecordedPnls(u64),
+}
+
+#[contractimpl]
+impl MockRegistry {
+    pub fn set_combo(env: Env, combo: ComboSignal) {
+        env.storage()
+            .instance()
+            .set(&RegistryKey::Combo(combo.id), &combo);
+    }
+
+    pub fn get_combo_signal(env: Env, combo_id: u64) -> Option<ComboSignal> {
+        env.storage().instance().get(&RegistryKey::Combo(combo_id))
+    }
+
+    pub fn set_signal(env: Env, signal_id: u64, status: SignalStatus, average_roi: i128) {
+        env.storage().instance().set(
+            &RegistryKey::Signal(signal_id),
+            &SignalPerformanceView {
+                signal_id,
+                executions: 1,
+                total_volume: 0,
+                average_roi,
+                status,
+            },
+        );
+    }
+
+    pub fn get_signal_performance(env: Env, signal_id: u64) -> Option<SignalPerformanceView> {
+        env.storage()
+            .instance()
+            .get(&RegistryKey::Signal(signal_id))
+    }
+
+    /// Stands in for the registry's `SignalTargets`; only presence matters.
+    pub fn set_targets(env: Env, signal_id: u64) {
+        env.storage()
+            .instance()
+            .set(&RegistryKey::Targets(signal_id), &signal_id);
+    }
+
+    pub fn get_signal_targets(env: Env, signal_id: u64) -> Option<u64> {
+        env.storage()
+            .instance()
+            .get(&RegistryKey::Targets(signal_id))
+    }
+
+    pub fn record_combo_execution(
+        env: Env,
+        caller: Address,
+        _user: Address,
+        combo_id: u64,
+        _total_amount: i128,
+        legs: Vec<ComponentExecution>,
+        pnls: Vec<i128>,
+    ) {
+        caller.require_auth();
+        env.storage()
+            .instance()
+            .set(&RegistryKey::Recorded(combo_id), &legs);
+        env.storage()
+            .instance()
+            .set(&RegistryKey::RecordedPnls(combo_id), &pnls);
+    }
+
+    pub fn get_recorded(env: Env, combo_id: u64) -> Option<Vec<ComponentExecution>> {
+        env.storage()
+            .instance()
+            .get(&RegistryKey::Recorded(combo_id))
+    }
+
+    pub fn get_recorded_pnls(env: Env, combo_id: u64) -> Option<Vec<i128>> {
+        env.storage()
+            .instance()
+            .get(&RegistryKey::RecordedPnls(combo_id))
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+const TOTAL: i128 = 1_000_000;
+const COMBO_ID: u64 = 7;
+
+struct Setup {
+    env: Env,
+    exec: Address,
+    registry: Address,
+    router: Address,
+    quote: Address,
+    user: Address,
+    keeper: Address,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let portfolio = env.register(MockComboPortfolio, ());
+    let registry = env.register(MockRegistry, ());
+    let router = env.register(MockComboRouter, ());
+    let exec = env.register(TradeExecutorContract, ());
+
+    let client = TradeExecutorContractClient::new(&env, &exec);
+    client.initialize(&admin);
+    client.set_user_portfolio(&portfolio);
+    client.set_signal_registry(&registry);
+    client.set_sdex_router(&router);
+
+    let quote = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &quote).mint(&router, &(10 * TOTAL));
+
+    let keeper = Address::generate(&env);
+    client.add_keeper(&keeper);
+
+    let user = Address::generate(&env);
+    Setup {
+        env,
+        exec,
+        registry,
+        router,
+        quote,
+        user,
+        keeper,
+    }
+}
+
+/// A token `user` holds enough of for any leg, approved to the executor and
+/// priced 1:1 against the quote token.
+fn funded_token(s: &Setup) -> Address {
+    let token = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    StellarAssetClient::new(&s.env, &token)
+        .mint(&s.user, &(TOTAL + DEFAULT_ESTIMATED_COPY_TRADE_FEE));
+    token::Client::new(&s.env, &token).approve(&s.user, &s.exec, &TOTAL, &1_000_000);
+    TradeExecutorContractClient::new(&s.env, &s.exec)
+        .set_sdex_price(&token, &ENTRY_PRICE_DENOMINATOR);
+    token
+}
+
+/// Make the router sell `token` at `roi` basis points from entry.
+fn set_exit_roi(s: &Setup, token: &Address, roi: i128) {
+    MockComboRouterClient::new(&s.env, &s.router).set_price_bps(token, &(10_000 + roi));
+}
+
+fn component(signal_id: u64, weight: u32, condition: ConditionGate) -> ComponentSignal {
+    ComponentSignal {
+        signal_id,
+        weight,
+        condition,
+    }
+}
+
+fn gate(depends_on: u64, condition_type: ConditionType) -> ConditionGate {
+    ConditionGate::Some(Condition {
+        depends_on,
+        condition_type,
+    })
+}
+
+fn register_combo(s: &Setup, combo_type: ComboType, components: Vec<ComponentSignal>) {
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    for comp in components.iter() {
+        registry.set_signal(&comp.signal_id, &SignalStatus::Active, &0);
+    }
+    registry.set_combo(&ComboSignal {
+        id: COMBO_ID,
+        name: String::from_str(&s.env, "combo"),
+        provider: Address::generate(&s.env),
+        component_signals: components,
+        combo_type,
+        status: ComboStatus::Active,
+        created_at: 0,
+    });
+}
+
+fn leg_statuses(s: &Setup, adoption_id: u64) -> Vec<LegStatus> {
+    let adoption = TradeExecutorContractClient::new(&s.env, &s.exec)
+        .get_combo_adoption(&adoption_id)
+        .unwrap();
+    let mut statuses = Vec::new(&s.env);
+    for leg in adoption.legs.iter() {
+        statuses.push_back(leg.status);
+    }
+    statuses
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn sequential_legs_wait_for_upstream_and_report_per_leg_pnl() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    let (t1, t2) = (funded_token(&s), funded_token(&s));
+    register_combo(
+        &s,
+        ComboType::Sequential,
+        vec![
+            &s.env,
+            component(1, 6_000, ConditionGate::None),
+            component(2, 4_000, ConditionGate::None),
+        ],
+    );
+
+    let id = client.adopt_combo(
+        &s.user,
+        &COMBO_ID,
+        &vec![&s.env, t1.clone(), t2.clone()],
+        &s.quote,
+        &TOTAL,
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![&s.env, LegStatus::Open, LegStatus::Pending]
+    );
+    assert_eq!(client.get_open_interest(&t1), 600_000);
+    assert_eq!(client.get_open_interest(&t2), 0);
+
+    // Upstream still active: nothing moves.
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Active
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![&s.env, LegStatus::Open, LegStatus::Pending]
+    );
+
+    registry.set_signal(&1, &SignalStatus::Successful, &500);
+    set_exit_roi(&s, &t1, 500);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Active
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![&s.env, LegStatus::Closed, LegStatus::Open]
+    );
+    assert_eq!(client.get_open_interest(&t1), 0);
+    assert_eq!(client.get_open_interest(&t2), 400_000);
+
+    registry.set_signal(&2, &SignalStatus::Successful, &1_000);
+    // The sale realizes less than the signal's mark, within the tolerance.
+    set_exit_roi(&s, &t2, 950);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Completed
+    );
+
+    let recorded = registry.get_recorded(&COMBO_ID).unwrap();
+    let leg1 = recorded.get(0).unwrap();
+    let leg2 = recorded.get(1).unwrap();
+    assert_eq!((leg1.amount, leg1.roi), (600_000, 500));
+    assert_eq!((leg2.amount, leg2.roi), (400_000, 950));
+    assert_eq!(
+        registry.get_recorded_pnls(&COMBO_ID).unwrap(),
+        vec![&s.env, 30_000i128, 38_000]
+    );
+
+    // Both legs were sold into the quote token and the proceeds paid out.
+    let quote = token::Client::new(&s.env, &s.quote);
+    assert_eq!(quote.balance(&s.user), 630_000 + 438_000);
+    assert_eq!(token::Client::new(&s.env, &t1).balance(&s.exec), 0);
+    assert_eq!(
+        token::Client::new(&s.env, &t2).balance(&s.user),
+        TOTAL + DEFAULT_ESTIMATED_COPY_TRADE_FEE - 400_000
+    );
+
+    assert_eq!(
+        client.try_advance_combo(&s.keeper, &id),
+        Err(Ok(ContractError::ComboNotActive))
+    );
+}
+
+#[test]
+fn simultaneous_combo_unwinds_when_a_leg_fails() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    let (t1, t2) = (funded_token(&s), funded_token(&s));
+    register_combo(
+        &s,
+        ComboType::Simultaneous,
+        vec![
+            &s.env,
+            component(1, 5_000, ConditionGate::None),
+            component(2, 5_000, ConditionGate::None),
+        ],
+    );
+
+    let id = client.adopt_combo(
+        &s.user,
+        &COMBO_ID,
+        &vec![&s.env, t1.clone(), t2.clone()],
+        &s.quote,
+        &TOTAL,
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![&s.env, LegStatus::Open, LegStatus::Open]
+    );
+
+    registry.set_signal(&1, &SignalStatus::Active, &200);
+    registry.set_signal(&2, &SignalStatus::Failed, &-800);
+    set_exit_roi(&s, &t1, 200);
+    set_exit_roi(&s, &t2, -800);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Unwound
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![&s.env, LegStatus::Unwound, LegStatus::Closed]
+    );
+    assert_eq!(client.get_open_interest(&t1), 0);
+    assert_eq!(client.get_open_interest(&t2), 0);
+
+    assert_eq!(
+        registry.get_recorded_pnls(&COMBO_ID).unwrap(),
+        vec![&s.env, 10_000i128, -40_000]
+    );
+}
+
+#[test]
+fn exit_below_mark_tolerance_is_rejected() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    let t1 = funded_token(&s);
+    register_combo(
+        &s,
+        ComboType::Simultaneous,
+        vec![&s.env, component(1, 10_000, ConditionGate::None)],
+    );
+    let id = client.adopt_combo(
+        &s.user,
+        &COMBO_ID,
+        &vec![&s.env, t1.clone()],
+        &s.quote,
+        &TOTAL,
+    );
+
+    registry.set_signal(&1, &SignalStatus::Successful, &500);
+    set_exit_roi(&s, &t1, 300);
+    assert_eq!(
+        client.try_advance_combo(&s.keeper, &id),
+        Err(Ok(ContractError::SlippageExceeded))
+    );
+    assert_eq!(leg_statuses(&s, id), vec![&s.env, LegStatus::Open]);
+}
+
+#[test]
+fn expired_leg_waits_for_target_resolution() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    let (t1, t2) = (funded_token(&s), funded_token(&s));
+    register_combo(
+        &s,
+        ComboType::Simultaneous,
+        vec![
+            &s.env,
+            component(1, 5_000, ConditionGate::None),
+            component(2, 5_000, ConditionGate::None),
+        ],
+    );
+    let id = client.adopt_combo(
+        &s.user,
+        &COMBO_ID,
+        &vec![&s.env, t1.clone(), t2.clone()],
+        &s.quote,
+        &TOTAL,
+    );
+
+    // Signal 1 has targets, so expiry is not its outcome yet; signal 2 has none.
+    registry.set_targets(&1);
+    registry.set_signal(&1, &SignalStatus::Expired, &0);
+    registry.set_signal(&2, &SignalStatus::Expired, &0);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Active
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![&s.env, LegStatus::Open, LegStatus::Closed]
+    );
+
+    registry.set_signal(&1, &SignalStatus::Successful, &400);
+    set_exit_roi(&s, &t1, 400);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Completed
+    );
+    assert_eq!(
+        registry.get_recorded_pnls(&COMBO_ID).unwrap(),
+        vec![&s.env, 20_000i128, 0]
+    );
+}
+
+#[test]
+fn conditional_gates_open_or_skip_dependent_legs() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    let tokens = vec![&s.env, funded_token(&s), funded_token(&s), funded_token(&s)];
+    register_combo(
+        &s,
+        ComboType::Conditional,
+        vec![
+            &s.env,
+            component(1, 5_000, ConditionGate::None),
+            component(2, 3_000, gate(1, ConditionType::RoiAbove(250))),
+            component(3, 2_000, gate(1, ConditionType::Failure)),
+        ],
+    );
+
+    let id = client.adopt_combo(&s.user, &COMBO_ID, &tokens, &s.quote, &TOTAL);
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![
+            &s.env,
+            LegStatus::Open,
+            LegStatus::Pending,
+            LegStatus::Pending
+        ]
+    );
+
+    registry.set_signal(&1, &SignalStatus::Successful, &300);
+    set_exit_roi(&s, &tokens.get(0).unwrap(), 300);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Active
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![
+            &s.env,
+            LegStatus::Closed,
+            LegStatus::Open,
+            LegStatus::Skipped
+        ]
+    );
+
+    // A failed leg only drives gates; it does not unwind a conditional combo.
+    registry.set_signal(&2, &SignalStatus::Failed, &-100);
+    set_exit_roi(&s, &tokens.get(1).unwrap(), -100);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Completed
+    );
+
+    let recorded = registry.get_recorded(&COMBO_ID).unwrap();
+    assert!(!recorded.get(1).unwrap().skipped);
+    assert!(recorded.get(2).unwrap().skipped);
+    assert_eq!(recorded.get(2).unwrap().amount, 0);
+}
+
+#[test]
+fn sequential_leg_that_cannot_be_placed_unwinds_combo() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    let funded = funded_token(&s);
+    let unfunded = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    register_combo(
+        &s,
+        ComboType::Sequential,
+        vec![
+            &s.env,
+            component(1, 5_000, ConditionGate::None),
+            component(2, 3_000, ConditionGate::None),
+            component(3, 2_000, ConditionGate::None),
+        ],
+    );
+
+    let id = client.adopt_combo(
+        &s.user,
+        &COMBO_ID,
+        &vec![&s.env, funded.clone(), unfunded.clone(), funded.clone()],
+        &s.quote,
+        &TOTAL,
+    );
+
+    registry.set_signal(&1, &SignalStatus::Successful, &100);
+    set_exit_roi(&s, &funded, 100);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Unwound
+    );
+    assert_eq!(
+        leg_statuses(&s, id),
+        vec![
+            &s.env,
+            LegStatus::Closed,
+            LegStatus::Failed,
+            LegStatus::Skipped
+        ]
+    );
+    assert_eq!(client.get_open_interest(&unfunded), 0);
+    assert!(
+        registry
+            .get_recorded(&COMBO_ID)
+            .unwrap()
+            .get(1)
+            .unwrap()
+            .skipped
+    );
+}
+
+#[test]
+fn adopt_combo_validates_inputs() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let token = funded_token(&s);
+    register_combo(
+        &s,
+        ComboType::Simultaneous,
+        vec![
+            &s.env,
+            component(1, 5_000, ConditionGate::None),
+            component(2, 5_000, ConditionGate::None),
+        ],
+    );
+
+    assert_eq!(
+        client.try_adopt_combo(
+            &s.user,
+            &COMBO_ID,
+            &vec![&s.env, token.clone()],
+            &s.quote,
+            &TOTAL
+        ),
+        Err(Ok(ContractError::InvalidComboLegs))
+    );
+    assert_eq!(
+        client.try_adopt_combo(&s.user, &99, &vec![&s.env, token.clone()], &s.quote, &TOTAL),
+        Err(Ok(ContractError::ComboNotFound))
+    );
+    assert_eq!(
+        client.try_adopt_combo(
+            &s.user,
+            &COMBO_ID,
+            &vec![&s.env, token.clone(), token.clone()],
+            &s.quote,
+            &0
+        ),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_advance_combo(&s.keeper, &1),
+        Err(Ok(ContractError::ComboNotFound))
+    );
+
+    // A component missing from the registry is an error, not a panic.
+    let id = client.adopt_combo(
+        &s.user,
+        &COMBO_ID,
+        &vec![&s.env, token.clone(), funded_token(&s)],
+        &s.quote,
+        &TOTAL,
+    );
+    MockRegistryClient::new(&s.env, &s.registry).set_combo(&ComboSignal {
+        id: 8,
+        name: String::from_str(&s.env, "orphan"),
+        provider: Address::generate(&s.env),
+        component_signals: vec![&s.env, component(99, 10_000, ConditionGate::None)],
+        combo_type: ComboType::Simultaneous,
+        status: ComboStatus::Active,
+        created_at: 0,
+    });
+    let orphan = client.adopt_combo(
+        &s.user,
+        &8,
+        &vec![&s.env, funded_token(&s)],
+        &s.quote,
+        &TOTAL,
+    );
+    assert_eq!(
+        client.try_advance_combo(&s.keeper, &orphan),
+        Err(Ok(ContractError::ComboSignalMissing))
+    );
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Active
+    );
+}
+
+#[test]
+fn leg_pnl_is_measured_in_quote_units() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let registry = MockRegistryClient::new(&s.env, &s.registry);
+    let token = funded_token(&s);
+    // One token is worth two quote units at entry.
+    client.set_sdex_price(&token, &(2 * ENTRY_PRICE_DENOMINATOR));
+    register_combo(
+        &s,
+        ComboType::Simultaneous,
+        vec![&s.env, component(1, 10_000, ConditionGate::None)],
+    );
+    let id = client.adopt_combo(
+        &s.user,
+        &COMBO_ID,
+        &vec![&s.env, token.clone()],
+        &s.quote,
+        &TOTAL,
+    );
+
+    // Sold at 2.1 quote per token: +5% on a 2_000_000 quote cost.
+    registry.set_signal(&1, &SignalStatus::Successful, &500);
+    MockComboRouterClient::new(&s.env, &s.router).set_price_bps(&token, &21_000);
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Completed
+    );
+    assert_eq!(
+        registry
+            .get_recorded(&COMBO_ID)
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .roi,
+        500
+    );
+    assert_eq!(
+        registry.get_recorded_pnls(&COMBO_ID).unwrap(),
+        vec![&s.env, 100_000i128]
+    );
+}
+
+#[test]
+fn advance_combo_requires_a_registered_keeper() {
+    let s = setup();
+    let client = TradeExecutorContractClient::new(&s.env, &s.exec);
+    let token = funded_token(&s);
+    register_combo(
+        &s,
+        ComboType::Simultaneous,
+        vec![&s.env, component(1, 10_000, ConditionGate::None)],
+    );
+    let id = client.adopt_combo(&s.user, &COMBO_ID, &vec![&s.env, token], &s.quote, &TOTAL);
+
+    assert_eq!(
+        client.try_advance_combo(&Address::generate(&s.env), &id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.advance_combo(&s.keeper, &id),
+        ComboAdoptionStatus::Active
+    );
+}
@@ -3,10 +3,7 @@
 
 extern crate std;
 
-use soroban_sdk::{
-    testutils::Address as _,
-    Address, Env, String,
-};
+use soroban_sdk::{testutils::Address as _, Address, Env, String};
 
 use crate::{
     errors::ContractError,
@@ -14,7 +11,7 @@ use crate::{
     TradeExecutorContract, TradeExecutorContractClient,
 };
 
-fn setup(env: &Env) -> (TradeExecutorContractClient, Address) {
+fn setup(env: &Env) -> (TradeExecutorContractClient<'_>, Address) {
     let admin = Address::generate(env);
     let contract_id = env.register_contract(None, TradeExecutorContract);
     let client = TradeExecutorContractClient::new(env, &contract_id);
@@ -110,11 +107,11 @@ fn execute_copy_trade_blocked_when_flag_disabled() {
         &None,
         &crate::OrderType::Market,
         &None,
+        &1u64,
+        &soroban_sdk::Bytes::from_array(&env, &[1u8; 32]),
+        &(env.ledger().timestamp() + 3_600),
     );
-    assert_eq!(
-        result,
-        Err(Ok(ContractError::FeatureDisabled))
-    );
+    assert_eq!(result, Err(Ok(ContractError::FeatureDisabled)));
 }
 
 // ── execute_dca_interval blocked when flag disabled ───────────────────────────
@@ -130,8 +127,5 @@ fn execute_dca_interval_blocked_when_flag_disabled() {
     let user = Address::generate(&env);
 
     let result = client.try_execute_dca_interval(&user, &1u64);
-    assert_eq!(
-        result,
-        Err(Ok(ContractError::FeatureDisabled))
-    );
+    assert_eq!(result, Err(Ok(ContractError::FeatureDisabled)));
 }
@@ -312,6 +312,9 @@ fn stress_high_frequency_trades_respect_daily_volume_boundary() {
             &None::<u32>,
             &OrderType::Market,
             &None,
+            &(i as u64 + 1),
+            &soroban_sdk::Bytes::from_array(&env, &[i as u8 + 1; 32]),
+            &(env.ledger().timestamp() + 3_600),
         );
         assert!(
             result.is_ok(),
@@ -327,6 +330,9 @@ fn stress_high_frequency_trades_respect_daily_volume_boundary() {
         &None::<u32>,
         &OrderType::Market,
         &None,
+        &(trade_count as u64 + 1),
+        &soroban_sdk::Bytes::from_array(&env, &[0xff; 32]),
+        &(env.ledger().timestamp() + 3_600),
     );
     assert_eq!(result, Err(Ok(ContractError::DailyVolumeLimitExceeded)));
 }
@@ -70,7 +70,7 @@ impl BenchPortfolio {
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
-fn setup_contract(env: &Env) -> (TradeExecutorContractClient, Address, Address, Address) {
+fn setup_contract(env: &Env) -> (TradeExecutorContractClient<'_>, Address, Address, Address) {
     let admin = Address::generate(env);
     let user = Address::generate(env);
 
@@ -116,6 +116,9 @@ fn copy_trade_storage_rent_baseline_smoke() {
         &None,
         &crate::OrderType::Market,
         &None,
+        &1u64,
+        &soroban_sdk::Bytes::from_array(&env, &[1u8; 32]),
+        &(env.ledger().timestamp() + 3_600),
     );
     // The test passes if the entrypoint returns Ok (storage writes were committed).
     assert!(
@@ -139,7 +142,7 @@ fn dca_interval_storage_rent_baseline_smoke() {
     // Create a DCA plan first.
     client.execute_dca_copy_trade(
         &user,
-        &1u64,   // signal_id
+        &1u64, // signal_id
         &10_000_000i128,
         &3u32,   // num_intervals
         &10u32,  // interval_ledgers
@@ -152,7 +155,10 @@ fn dca_interval_storage_rent_baseline_smoke() {
 
     let done = client.execute_dca_interval(&user, &1u64);
     // Returns false while the plan has remaining intervals.
-    assert!(!done, "DCA plan should not be complete after the first interval");
+    assert!(
+        !done,
+        "DCA plan should not be complete after the first interval"
+    );
 }
 
 /// Delta-check: compare the current write count to the baseline and fail if the
@@ -139,7 +139,7 @@ pub fn get_take_profit(env: &Env, user: &Address, trade_id: u64) -> Option<i128>
         .get(&(Symbol::new(env, "TakeProfit"), user.clone(), trade_id))
 }
 
-fn fetch_oracle_and_portfolio(env: &Env) -> Result<(Address, Address), ContractError> {
+pub(crate) fn fetch_oracle_and_portfolio(env: &Env) -> Result<(Address, Address), ContractError> {
     let oracle: Address = env
         .storage()
         .instance()
@@ -164,7 +164,7 @@ fn fetch_oracle_and_portfolio(env: &Env) -> Result<(Address, Address), ContractE
 ///   - `Ok(price)` when the price is fresh (age ≤ `MAX_ORACLE_PRICE_AGE_SECS`)
 ///   - `Err(OracleUnavailable)` when no price has ever been set (timestamp == 0)
 ///   - `Err(OraclePriceStale)` when the price is older than `MAX_ORACLE_PRICE_AGE_SECS`
-fn fetch_current_price(
+pub(crate) fn fetch_current_price(
     env: &Env,
     oracle: &Address,
     asset_pair: u32,
@@ -205,7 +205,7 @@ fn fetch_current_price(
 /// - Callee: `UserPortfolio::close_position_keeper` — authorises via
 ///   `caller.require_auth()` where `caller == env.current_contract_address()`.
 ///   The TradeExecutor contract address is the authorising principal.
-fn close_position_keeper(
+pub(crate) fn close_position_keeper(
     env: &Env,
     portfolio: &Address,
     user: &Address,
@@ -0,0 +1,64 @@
+//! Gate for positions and subscriptions, backed by the shared compliance registry.
+//!
+//! Two flags predate the registry and are still honoured: an admin-set
+//! per-user `Restricted` flag blocks the account whatever the registry says,
+//! and `KycRequiredMode` keeps the gate closed while no registry is set, so
+//! only accounts carrying a legacy `KycVerified` flag pass until one is.
+
+use soroban_sdk::{Address, Env};
+use stellar_swipe_common::compliance::{self, ComplianceError, ComplianceLevel};
+
+use crate::storage::DataKey;
+
+pub fn registry(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::ComplianceRegistry)
+}
+
+pub fn kyc_required(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::KycRequiredMode)
+        .unwrap_or(false)
+}
+
+pub fn is_restricted(env: &Env, user: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Restricted(user.clone()))
+        .unwrap_or(false)
+}
+
+fn legacy_verified(env: &Env, user: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::KycVerified(user.clone()))
+        .unwrap_or(false)
+}
+
+/// `Ok` when `user` is not restricted and either the registry accepts them at
+/// `Basic` level on `asset_pair`, or no registry is set and KYC-required
+/// mode is off or they carry the legacy verified flag.
+pub fn check(env: &Env, user: &Address, asset_pair: Option<u32>) -> Result<(), ComplianceError> {
+    if is_restricted(env, user) {
+        return Err(ComplianceError::JurisdictionBlocked);
+    }
+    match registry(env) {
+        Some(registry) => {
+            compliance::require_compliant(env, &registry, user, ComplianceLevel::Basic, asset_pair)
+                .map(|_| ())
+        }
+        None if kyc_required(env) && !legacy_verified(env, user) => {
+            Err(ComplianceError::NoAttestation)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Whether `user` passes [`check`] on positive evidence: a registry
+/// attestation, or the legacy flag while no registry is set.
+pub fn is_verified(env: &Env, user: &Address) -> bool {
+    match registry(env) {
+        Some(_) => check(env, user, None).is_ok(),
+        None => legacy_verified(env, user) && !is_restricted(env, user),
+    }
+}
@@ -4,6 +4,7 @@
 
 mod achievements;
 mod badges;
+mod compliance;
 mod migration;
 mod onboarding;
 #[cfg(test)]
@@ -24,6 +25,7 @@ pub use preferences::{
 };
 
 use soroban_sdk::{contract, contracterror, contractimpl, contracttype, Address, Env, String, Vec};
+use stellar_swipe_common::ComplianceError;
 use storage::DataKey;
 
 pub use subscriptions::SubscriptionError;
@@ -168,34 +170,35 @@ impl UserPortfolio {
         }
     }
 
-    /// Admin: set or clear the KYC-verified flag for a user.
-    /// No PII is stored — only a boolean.
-    /// Emits `KYCStatusUpdated { user, verified }`.
-    pub fn set_kyc_status(env: Env, user: Address, verified: bool) {
+    /// Admin: point KYC checks at the shared compliance registry, or pass
+    /// `None` to stop gating positions and subscriptions.
+    pub fn set_compliance_registry(env: Env, registry: Option<Address>) {
         Self::require_admin(&env);
-        env.storage()
-            .persistent()
-            .set(&DataKey::KycVerified(user.clone()), &verified);
-        shared::events::emit_kyc_status_updated(
-            &env,
-            shared::events::EvtKycStatusUpdated {
-                schema_version: shared::events::SCHEMA_VERSION,
-                user,
-                verified,
-            },
-        );
+        match registry {
+            Some(registry) => env
+                .storage()
+                .instance()
+                .set(&DataKey::ComplianceRegistry, &registry),
+            None => env
+                .storage()
+                .instance()
+                .remove(&DataKey::ComplianceRegistry),
+        }
     }
 
-    /// Returns the KYC-verified status for a user (defaults to false if never set).
+    pub fn get_compliance_registry(env: Env) -> Option<Address> {
+        compliance::registry(&env)
+    }
+
+    /// Whether the compliance registry accepts `user` at `Basic` level. While
+    /// no registry is configured, reports the legacy admin-set flag.
     pub fn is_kyc_verified(env: Env, user: Address) -> bool {
-        env.storage()
-            .persistent()
-            .get(&DataKey::KycVerified(user))
-            .unwrap_or(false)
+        compliance::is_verified(&env, &user)
     }
 
-    /// Admin: enable or disable KYC-required mode.
-    /// When true, only KYC-verified users can open positions.
+    /// Admin: enable or disable KYC-required mode. While it is on and no
+    /// registry is configured, only users with the legacy KYC flag can open
+    /// positions or subscribe.
     pub fn set_kyc_required_mode(env: Env, required: bool) {
         Self::require_admin(&env);
         env.storage()
@@ -205,13 +208,11 @@ impl UserPortfolio {
 
     /// Returns whether KYC-required mode is active (defaults to false).
     pub fn get_kyc_required_mode(env: Env) -> bool {
-        env.storage()
-            .instance()
-            .get(&DataKey::KycRequiredMode)
-            .unwrap_or(false)
+        compliance::kyc_required(&env)
     }
 
-    /// Admin: set or clear the geographic restriction flag for a user.
+    /// Admin: set or clear the geographic restriction flag for a user. A
+    /// restricted user is blocked even when the registry accepts them.
     /// `reason_hash` is an IPFS CID of the reason document — no reason text stored on-chain.
     /// Emits `UserRestricted { user, reason_hash, restricted }`.
     pub fn set_user_restriction(
@@ -237,10 +238,7 @@ impl UserPortfolio {
 
     /// Returns whether a user is geographically restricted (defaults to false).
     pub fn is_restricted(env: Env, user: Address) -> bool {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Restricted(user))
-            .unwrap_or(false)
+        compliance::is_restricted(&env, &user)
     }
 
     pub fn set_oracle_asset_pair(env: Env, asset_pair: u32) {
@@ -299,30 +297,13 @@ impl UserPortfolio {
         if entry_price <= 0 || amount <= 0 {
             panic!("invalid entry_price or amount");
         }
-        // KYC gate: if KYC-required mode is active, only verified users may open positions.
-        let kyc_required: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::KycRequiredMode)
-            .unwrap_or(false);
-        if kyc_required {
-            let verified: bool = env
-                .storage()
-                .persistent()
-                .get(&DataKey::KycVerified(user.clone()))
-                .unwrap_or(false);
-            if !verified {
-                panic!("KYC verification required to open a position");
+        let asset_pair: Option<u32> = env.storage().instance().get(&DataKey::OracleAssetPair);
+        match compliance::check(&env, &user, asset_pair) {
+            Ok(()) => {}
+            Err(ComplianceError::JurisdictionBlocked) => {
+                panic!("user is geographically restricted")
             }
-        }
-        // Restriction gate: restricted users cannot open positions.
-        let restricted: bool = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Restricted(user.clone()))
-            .unwrap_or(false);
-        if restricted {
-            panic!("user is geographically restricted");
+            Err(_) => panic!("KYC verification required to open a position"),
         }
         let id: u64 = env
             .storage()
@@ -864,7 +845,17 @@ mod streak_tests {
 mod kyc_tests {
     use super::oracle_ok::OracleMock;
     use super::*;
+    use ::compliance::{
+        ComplianceLevel, ComplianceRegistry, ComplianceRegistryClient, CustomerStatus,
+    };
     use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{symbol_short, Symbol};
+
+    pub(crate) struct Registry<'a> {
+        pub client: ComplianceRegistryClient<'a>,
+        pub admin: Address,
+        pub issuer: Address,
+    }
 
     fn setup(env: &Env) -> (Address, Address) {
         env.mock_all_auths();
@@ -876,7 +867,34 @@ mod kyc_tests {
         (admin, contract_id)
     }
 
-    // KYC flag is readable and defaults to false.
+    /// Deploy a compliance registry with one approved issuer.
+    pub(crate) fn registry(env: &Env) -> Registry<'_> {
+        let client =
+            ComplianceRegistryClient::new(env, &env.register_contract(None, ComplianceRegistry));
+        let admin = Address::generate(env);
+        let issuer = Address::generate(env);
+        client.initialize(&admin);
+        client.add_issuer(&admin, &issuer);
+        Registry {
+            client,
+            admin,
+            issuer,
+        }
+    }
+
+    pub(crate) fn attest(env: &Env, registry: &Registry, user: &Address, jurisdiction: Symbol) {
+        registry.client.attest(
+            &registry.issuer,
+            user,
+            &String::from_str(env, "sep12-customer"),
+            &CustomerStatus::Accepted,
+            &ComplianceLevel::Basic,
+            &jurisdiction,
+            &0,
+        );
+    }
+
+    // Without a registry nobody is KYC-verified.
     #[test]
     fn kyc_flag_defaults_to_false() {
         let env = Env::default();
@@ -884,112 +902,121 @@ mod kyc_tests {
         let client = UserPortfolioClient::new(&env, &contract_id);
         let user = Address::generate(&env);
         assert!(!client.is_kyc_verified(&user));
+        assert_eq!(client.get_compliance_registry(), None);
     }
 
-    // Admin can set and clear the KYC flag.
+    // The flag follows the registry's attestation, including revocation.
     #[test]
-    fn admin_can_set_and_clear_kyc_flag() {
+    fn kyc_flag_follows_registry_attestation() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
+        let registry = registry(&env);
         let user = Address::generate(&env);
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
 
-        client.set_kyc_status(&user, &true);
+        assert!(!client.is_kyc_verified(&user));
+        attest(&env, &registry, &user, symbol_short!("DE"));
         assert!(client.is_kyc_verified(&user));
 
-        client.set_kyc_status(&user, &false);
+        registry.client.revoke_attestation(&registry.issuer, &user);
         assert!(!client.is_kyc_verified(&user));
     }
 
-    // set_kyc_status emits KYCStatusUpdated event.
+    // No registry configured: unverified user can open a position.
     #[test]
-    fn set_kyc_status_emits_event() {
-        use soroban_sdk::testutils::Events;
-        use soroban_sdk::TryFromVal;
+    fn no_registry_allows_unverified_user() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
         let user = Address::generate(&env);
 
-        client.set_kyc_status(&user, &true);
-
-        let has_kyc_event = env.events().all().iter().any(|e| {
-            let topics: soroban_sdk::Vec<soroban_sdk::Val> = e.1.clone();
-            if topics.len() < 2 {
-                return false;
-            }
-            soroban_sdk::Symbol::try_from_val(&env, &topics.get(1).unwrap())
-                .map(|s| s == soroban_sdk::Symbol::new(&env, "kyc_status_updated"))
-                .unwrap_or(false)
-        });
-        assert!(has_kyc_event, "kyc_status_updated event not emitted");
+        let id = client.open_position(&user, &100, &1_000);
+        assert_eq!(id, 1);
     }
 
-    // KYC-required mode defaults to false.
+    // Registry configured: attested user can open a position.
     #[test]
-    fn kyc_required_mode_defaults_to_false() {
+    fn registry_allows_attested_user() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
-        assert!(!client.get_kyc_required_mode());
+        let registry = registry(&env);
+        let user = Address::generate(&env);
+
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        attest(&env, &registry, &user, symbol_short!("DE"));
+
+        let id = client.open_position(&user, &100, &1_000);
+        assert_eq!(id, 1);
     }
 
-    // Non-required mode: unverified user can open a position.
+    // Registry configured: unattested user cannot open a position.
     #[test]
-    fn non_required_mode_allows_unverified_user() {
+    #[should_panic(expected = "KYC verification required to open a position")]
+    fn registry_blocks_unattested_user() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
+        let registry = registry(&env);
         let user = Address::generate(&env);
 
-        // KYC-required mode is off by default — unverified user should succeed.
-        let id = client.open_position(&user, &100, &1_000);
-        assert_eq!(id, 1);
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        client.open_position(&user, &100, &1_000);
     }
 
-    // KYC-required mode: verified user can open a position.
+    // Clearing the registry re-allows unverified users.
     #[test]
-    fn required_mode_allows_verified_user() {
+    fn clearing_registry_allows_unverified_user() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
+        let registry = registry(&env);
         let user = Address::generate(&env);
 
-        client.set_kyc_required_mode(&true);
-        client.set_kyc_status(&user, &true);
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        client.set_compliance_registry(&None);
 
         let id = client.open_position(&user, &100, &1_000);
         assert_eq!(id, 1);
     }
 
-    // KYC-required mode: unverified user cannot open a position.
+    // KYC-required mode without a registry fails closed.
     #[test]
     #[should_panic(expected = "KYC verification required to open a position")]
-    fn required_mode_blocks_unverified_user() {
+    fn required_mode_without_registry_blocks_unverified_user() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
         let user = Address::generate(&env);
 
         client.set_kyc_required_mode(&true);
-        // user is not KYC-verified — should panic
+        assert!(client.get_kyc_required_mode());
         client.open_position(&user, &100, &1_000);
     }
 
-    // Disabling KYC-required mode re-allows unverified users.
+    // Users verified before the registry keep access until one is configured.
     #[test]
-    fn disabling_required_mode_allows_unverified_user() {
+    fn legacy_kyc_flag_honoured_until_registry_set() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
         let user = Address::generate(&env);
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::KycVerified(user.clone()), &true);
+        });
 
         client.set_kyc_required_mode(&true);
-        client.set_kyc_required_mode(&false);
+        assert!(client.is_kyc_verified(&user));
+        assert_eq!(client.open_position(&user, &100, &1_000), 1);
 
-        // Mode is now off — unverified user should succeed.
-        let id = client.open_position(&user, &100, &1_000);
-        assert_eq!(id, 1);
+        let registry = registry(&env);
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        assert!(!client.is_kyc_verified(&user));
+        attest(&env, &registry, &user, symbol_short!("DE"));
+        assert!(client.is_kyc_verified(&user));
     }
 }
 
@@ -1674,8 +1701,11 @@ mod tests {
 // ── Geographic restriction unit tests ─────────────────────────────────────────
 #[cfg(test)]
 mod restriction_tests {
+    use super::kyc_tests::{attest, registry};
     use super::oracle_ok::OracleMock;
     use super::*;
+    use ::compliance::{ComplianceLevel, JurisdictionRule};
+    use soroban_sdk::symbol_short;
     use soroban_sdk::testutils::Address as _;
 
     fn setup(env: &Env) -> (Address, Address) {
@@ -1688,78 +1718,88 @@ mod restriction_tests {
         (admin, contract_id)
     }
 
-    fn reason(env: &Env) -> soroban_sdk::String {
-        soroban_sdk::String::from_str(env, "QmFakeIpfsHash")
+    fn blocked(env: &Env) -> JurisdictionRule {
+        JurisdictionRule {
+            blocked: true,
+            min_level: ComplianceLevel::None,
+            blocked_pairs: Vec::new(env),
+        }
     }
 
-    // Restriction flag defaults to false.
+    // User in a blocked jurisdiction cannot open a position.
     #[test]
-    fn restriction_defaults_to_false() {
+    #[should_panic(expected = "user is geographically restricted")]
+    fn blocked_jurisdiction_cannot_open_position() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
+        let registry = registry(&env);
         let user = Address::generate(&env);
-        assert!(!client.is_restricted(&user));
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        attest(&env, &registry, &user, symbol_short!("KP"));
+        registry.client.set_jurisdiction_rule(
+            &registry.admin,
+            &symbol_short!("KP"),
+            &blocked(&env),
+        );
+        client.open_position(&user, &100, &1_000);
     }
 
-    // Unrestricted user can open a position.
+    // Lifting the jurisdiction rule lets the user trade again.
     #[test]
-    fn unrestricted_user_can_open_position() {
+    fn lifting_jurisdiction_rule_restores_access() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
+        let registry = registry(&env);
         let user = Address::generate(&env);
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        attest(&env, &registry, &user, symbol_short!("KP"));
+        registry.client.set_jurisdiction_rule(
+            &registry.admin,
+            &symbol_short!("KP"),
+            &blocked(&env),
+        );
+        assert!(!client.is_kyc_verified(&user));
+
+        registry
+            .client
+            .remove_jurisdiction_rule(&registry.admin, &symbol_short!("KP"));
+        assert!(client.is_kyc_verified(&user));
         let id = client.open_position(&user, &100, &1_000);
         assert_eq!(id, 1);
     }
 
-    // Restricted user cannot open a position.
+    // The per-user restriction blocks even an attested user.
     #[test]
     #[should_panic(expected = "user is geographically restricted")]
-    fn restricted_user_cannot_open_position() {
+    fn restricted_user_blocked_despite_attestation() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
+        let registry = registry(&env);
         let user = Address::generate(&env);
-        client.set_user_restriction(&user, &true, &reason(&env));
-        client.open_position(&user, &100, &1_000);
-    }
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        attest(&env, &registry, &user, symbol_short!("DE"));
 
-    // Admin can remove restriction and user can trade again.
-    #[test]
-    fn admin_can_remove_restriction() {
-        let env = Env::default();
-        let (_admin, contract_id) = setup(&env);
-        let client = UserPortfolioClient::new(&env, &contract_id);
-        let user = Address::generate(&env);
-        client.set_user_restriction(&user, &true, &reason(&env));
+        client.set_user_restriction(&user, &true, &String::from_str(&env, "QmReason"));
         assert!(client.is_restricted(&user));
-        client.set_user_restriction(&user, &false, &reason(&env));
-        assert!(!client.is_restricted(&user));
-        // Should succeed now.
-        let id = client.open_position(&user, &100, &1_000);
-        assert_eq!(id, 1);
+        assert!(!client.is_kyc_verified(&user));
+        client.open_position(&user, &100, &1_000);
     }
 
-    // set_user_restriction emits user_restricted event.
+    // Clearing the restriction restores access.
     #[test]
-    fn set_user_restriction_emits_event() {
-        use soroban_sdk::testutils::Events;
-        use soroban_sdk::TryFromVal;
+    fn clearing_restriction_restores_access() {
         let env = Env::default();
         let (_admin, contract_id) = setup(&env);
         let client = UserPortfolioClient::new(&env, &contract_id);
         let user = Address::generate(&env);
-        client.set_user_restriction(&user, &true, &reason(&env));
-        let has_event = env.events().all().iter().any(|e| {
-            let topics: soroban_sdk::Vec<soroban_sdk::Val> = e.1.clone();
-            if topics.len() < 2 {
-                return false;
-            }
-            soroban_sdk::Symbol::try_from_val(&env, &topics.get(1).unwrap())
-                .map(|s| s == soroban_sdk::Symbol::new(&env, "user_restricted"))
-                .unwrap_or(false)
-        });
-        assert!(has_event, "user_restricted event not emitted");
+        let reason = String::from_str(&env, "QmReason");
+
+        client.set_user_restriction(&user, &true, &reason);
+        client.set_user_restriction(&user, &false, &reason);
+        assert!(!client.is_restricted(&user));
+        assert_eq!(client.open_position(&user, &100, &1_000), 1);
     }
 }
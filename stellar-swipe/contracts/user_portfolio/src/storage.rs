@@ -15,11 +15,14 @@ pub enum DataKey {
     UserClosedPositions(Address),
     /// Registered TradeExecutor contract allowed to call `close_position_keeper`.
     TradeExecutor,
-    /// Per-user KYC verification flag (bool). No PII stored — boolean only.
+    /// Compliance registry consulted before positions and subscriptions.
+    /// Unset = no compliance gate unless `KycRequiredMode` is on.
+    ComplianceRegistry,
+    /// Legacy admin-set KYC flag (bool). Only read while no registry is set.
     KycVerified(Address),
-    /// Global KYC-required mode (bool). When true, only KYC-verified users can trade.
+    /// When true and no registry is set, only `KycVerified` users pass.
     KycRequiredMode,
-    /// Per-user geographic restriction flag (bool). Restricted users cannot trade.
+    /// Admin-set per-user block (bool), applied on top of the registry.
     Restricted(Address),
     /// Per-user current streak (consecutive profitable closes)
     CurrentStreak(Address),
@@ -47,6 +47,8 @@ pub enum SubscriptionError {
     Overflow = 4,
     InvalidFee = 5,
     SelfSubscribe = 6,
+    /// The compliance registry does not accept the subscriber.
+    NotCompliant = 7,
 }
 
 fn require_portfolio_initialized(env: &Env) -> Result<(), SubscriptionError> {
@@ -98,6 +100,7 @@ pub fn subscribe_to_provider(
     if user == provider {
         return Err(SubscriptionError::SelfSubscribe);
     }
+    crate::compliance::check(env, user, None).map_err(|_| SubscriptionError::NotCompliant)?;
     if duration_days == 0 || duration_days > MAX_SUBSCRIPTION_DAYS {
         return Err(SubscriptionError::InvalidDuration);
     }
@@ -267,4 +270,25 @@ mod tests {
         let after = StellarAssetClient::new(&env, &token).balance(&provider);
         assert_eq!(after - before, 100_000i128);
     }
+
+    #[test]
+    fn registry_blocks_unattested_subscriber() {
+        let (env, _admin, provider, subscriber, _other, token, client) = setup();
+        let registry = crate::kyc_tests::registry(&env);
+        client.set_compliance_registry(&Some(registry.client.address.clone()));
+        client.set_provider_subscription_terms(&provider, &token, &10_000i128);
+
+        assert_eq!(
+            client.try_subscribe_to_provider(&subscriber, &provider, &7u32),
+            Err(Ok(SubscriptionError::NotCompliant))
+        );
+        crate::kyc_tests::attest(
+            &env,
+            &registry,
+            &subscriber,
+            soroban_sdk::symbol_short!("DE"),
+        );
+        client.subscribe_to_provider(&subscriber, &provider, &7u32);
+        assert!(client.check_subscription(&subscriber, &provider));
+    }
 }